@@ -0,0 +1,274 @@
+//! OpenAPI export for tool definitions.
+//!
+//! This is the reverse of [`AdapterGenerator`](crate::AdapterGenerator): it
+//! takes a set of [`ToolDefinition`]s (for example everything in a registry)
+//! and produces an OpenAPI 3.0 document where each tool is exposed as a
+//! `POST {base_path}/{tool_name}` operation. The request body is the tool's
+//! JSON Schema input and the response is a serialized `ToolResult`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use thulp_adapter::OpenApiExporter;
+//! use thulp_core::{Parameter, ToolDefinition};
+//!
+//! let tools = vec![ToolDefinition::builder("read_file")
+//!     .description("Read a file")
+//!     .parameter(Parameter::required_string("path"))
+//!     .build()];
+//!
+//! let spec = OpenApiExporter::new("File Tools", "1.0.0")
+//!     .with_server("https://tools.example.com")
+//!     .export(&tools);
+//!
+//! assert!(spec["paths"]["/tools/read_file"]["post"].is_object());
+//! ```
+
+use crate::Result;
+use serde_json::{json, Map, Value};
+use thulp_core::{ToolDefinition, UrlBuilder};
+
+/// Generates an OpenAPI 3.0 document from tool definitions.
+#[derive(Debug, Clone)]
+pub struct OpenApiExporter {
+    /// API title (`info.title`)
+    title: String,
+
+    /// API version (`info.version`)
+    version: String,
+
+    /// Optional API description (`info.description`)
+    description: Option<String>,
+
+    /// Server URLs (`servers[].url`)
+    servers: Vec<String>,
+
+    /// Path prefix for tool operations
+    base_path: String,
+}
+
+impl OpenApiExporter {
+    /// Create a new exporter with the given API title and version
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            servers: Vec::new(),
+            base_path: "/tools".to_string(),
+        }
+    }
+
+    /// Set the API description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a server URL
+    pub fn with_server(mut self, url: impl Into<String>) -> Self {
+        self.servers.push(url.into());
+        self
+    }
+
+    /// Set the path prefix for tool operations (default: `/tools`)
+    pub fn with_base_path(mut self, base_path: impl Into<String>) -> Self {
+        let base_path = base_path.into();
+        self.base_path = format!("/{}", base_path.trim_matches('/'))
+            .trim_end_matches('/')
+            .to_string();
+        self
+    }
+
+    /// Export tool definitions as an OpenAPI document
+    pub fn export(&self, tools: &[ToolDefinition]) -> Value {
+        let mut info = Map::new();
+        info.insert("title".to_string(), json!(self.title));
+        info.insert("version".to_string(), json!(self.version));
+        if let Some(description) = &self.description {
+            info.insert("description".to_string(), json!(description));
+        }
+
+        let mut paths = Map::new();
+        for tool in tools {
+            paths.insert(
+                UrlBuilder::new(&self.base_path).segment(&tool.name).build(),
+                json!({ "post": self.tool_to_operation(tool) }),
+            );
+        }
+
+        let mut spec = Map::new();
+        spec.insert("openapi".to_string(), json!("3.0.3"));
+        spec.insert("info".to_string(), Value::Object(info));
+        if !self.servers.is_empty() {
+            let servers: Vec<Value> = self
+                .servers
+                .iter()
+                .map(|url| json!({ "url": url }))
+                .collect();
+            spec.insert("servers".to_string(), Value::Array(servers));
+        }
+        spec.insert("paths".to_string(), Value::Object(paths));
+        spec.insert("components".to_string(), components());
+
+        Value::Object(spec)
+    }
+
+    /// Export tool definitions as an OpenAPI document in YAML
+    pub fn export_yaml(&self, tools: &[ToolDefinition]) -> Result<String> {
+        Ok(serde_yaml::to_string(&self.export(tools))?)
+    }
+
+    /// Convert a tool definition into an OpenAPI operation
+    fn tool_to_operation(&self, tool: &ToolDefinition) -> Value {
+        let mut schema = tool.to_mcp_input_schema();
+        // OpenAPI 3.0 requires `required` to be non-empty when present
+        if schema["required"].as_array().is_some_and(|r| r.is_empty()) {
+            if let Some(obj) = schema.as_object_mut() {
+                obj.remove("required");
+            }
+        }
+
+        let mut operation = Map::new();
+        operation.insert("operationId".to_string(), json!(tool.name));
+        if !tool.description.is_empty() {
+            operation.insert("summary".to_string(), json!(tool.description));
+        }
+        operation.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": tool.required_parameters().next().is_some(),
+                "content": {
+                    "application/json": { "schema": schema }
+                }
+            }),
+        );
+        operation.insert(
+            "responses".to_string(),
+            json!({
+                "200": {
+                    "description": "Tool executed",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/ToolResult" }
+                        }
+                    }
+                },
+                "400": { "description": "Invalid arguments" },
+                "404": { "description": "Tool not found" }
+            }),
+        );
+
+        Value::Object(operation)
+    }
+}
+
+/// Shared component schemas referenced by exported operations
+fn components() -> Value {
+    json!({
+        "schemas": {
+            "ToolResult": {
+                "type": "object",
+                "properties": {
+                    "success": { "type": "boolean" },
+                    "data": { "description": "Tool output data" },
+                    "error": { "type": "string", "nullable": true },
+                    "duration_ms": { "type": "integer", "nullable": true }
+                },
+                "required": ["success"]
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AdapterGenerator;
+    use thulp_core::{Parameter, ParameterType};
+
+    fn sample_tools() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::builder("search")
+                .description("Search the index")
+                .parameter(Parameter::required_string("query"))
+                .parameter(
+                    Parameter::builder("limit")
+                        .param_type(ParameterType::Integer)
+                        .default(json!(10))
+                        .build(),
+                )
+                .build(),
+            ToolDefinition::builder("ping").build(),
+        ]
+    }
+
+    #[test]
+    fn test_export_structure() {
+        let spec = OpenApiExporter::new("Tools", "2.0.0")
+            .with_description("Exported tools")
+            .with_server("https://api.example.com")
+            .export(&sample_tools());
+
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert_eq!(spec["info"]["title"], "Tools");
+        assert_eq!(spec["info"]["version"], "2.0.0");
+        assert_eq!(spec["servers"][0]["url"], "https://api.example.com");
+
+        let op = &spec["paths"]["/tools/search"]["post"];
+        assert_eq!(op["operationId"], "search");
+        assert_eq!(op["summary"], "Search the index");
+        assert_eq!(op["requestBody"]["required"], true);
+
+        let schema = &op["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(schema["properties"]["query"]["type"], "string");
+        assert_eq!(schema["properties"]["limit"]["default"], 10);
+        assert_eq!(schema["required"], json!(["query"]));
+        assert!(spec["components"]["schemas"]["ToolResult"].is_object());
+    }
+
+    #[test]
+    fn test_export_omits_empty_required() {
+        let spec = OpenApiExporter::new("Tools", "1.0.0").export(&sample_tools());
+        let op = &spec["paths"]["/tools/ping"]["post"];
+
+        assert_eq!(op["requestBody"]["required"], false);
+        assert!(op.get("summary").is_none());
+        assert!(op["requestBody"]["content"]["application/json"]["schema"]
+            .get("required")
+            .is_none());
+        assert!(spec.get("servers").is_none());
+    }
+
+    #[test]
+    fn test_custom_base_path() {
+        let spec = OpenApiExporter::new("Tools", "1.0.0")
+            .with_base_path("api/v1/")
+            .export(&sample_tools());
+        assert!(spec["paths"]["/api/v1/search"].is_object());
+
+        let spec = OpenApiExporter::new("Tools", "1.0.0")
+            .with_base_path("/")
+            .export(&sample_tools());
+        assert!(spec["paths"]["/search"].is_object());
+    }
+
+    #[test]
+    fn test_round_trip_through_adapter() {
+        let spec = OpenApiExporter::new("Tools", "1.0.0").export(&sample_tools());
+        let tools = AdapterGenerator::new(spec, None).generate_tools().unwrap();
+
+        let mut names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["ping", "search"]);
+    }
+
+    #[test]
+    fn test_export_yaml() {
+        let yaml = OpenApiExporter::new("Tools", "1.0.0")
+            .export_yaml(&sample_tools())
+            .unwrap();
+        assert!(yaml.contains("openapi: 3.0.3"));
+        assert!(yaml.contains("/tools/search"));
+    }
+}
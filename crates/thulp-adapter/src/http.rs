@@ -0,0 +1,663 @@
+//! Executable HTTP adapters.
+//!
+//! [`HttpAdapter`] implements [`Transport`] for tools generated from an
+//! OpenAPI specification. Each call is turned into an HTTP request: path
+//! parameters are substituted into the route, query, header and cookie
+//! parameters are placed where the spec declares them, and body properties
+//! (or the `data` argument for non-object bodies) become the JSON request
+//! body. Responses are mapped to
+//! [`ToolResult`]s, with non-2xx statuses reported as failed results.
+//!
+//! Credentials come from [`HttpAdapter::with_auth`] and
+//! [`HttpAdapter::with_auth_secret`], or for OAuth2 from an
+//! [`OAuth2Client`] given to [`HttpAdapter::with_oauth2`].
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use thulp_adapter::AdapterGenerator;
+//! use thulp_core::{ToolCall, Transport};
+//!
+//! let generator = AdapterGenerator::new(spec, Some("github".to_string()));
+//! let mut adapter = generator.http_adapter()?;
+//! for auth in generator.extract_auth_config() {
+//!     // Credentials from a secrets store, e.g. the workspace's `.thulp/secrets`
+//!     adapter = adapter.with_auth_secret(auth, &workspace.secrets(), "github_token")?;
+//! }
+//!
+//! adapter.connect().await?;
+//! let result = adapter
+//!     .call(&ToolCall::builder("getRepo").arg_str("owner", "dirmacs").arg_str("repo", "thulp").build())
+//!     .await?;
+//! ```
+
+use crate::{AuthConfig, OAuth2Client};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thulp_core::{
+    Error, Result, SecretResolver, ToolCall, ToolDefinition, ToolResult, Transport, UrlBuilder,
+};
+
+/// Where an operation parameter is sent in the HTTP request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterLocation {
+    /// Substituted into the route, e.g. `/users/{id}`
+    Path,
+    /// Appended to the query string
+    Query,
+    /// Sent as a request header
+    Header,
+    /// Sent in the `Cookie` header
+    Cookie,
+    /// Sent as the JSON request body
+    Body,
+    /// Sent as a property of the JSON request body
+    BodyField,
+}
+
+impl ParameterLocation {
+    /// Parse an OpenAPI `in` value
+    pub fn from_openapi(location: &str) -> Option<Self> {
+        match location {
+            "path" => Some(Self::Path),
+            "query" => Some(Self::Query),
+            "header" => Some(Self::Header),
+            "cookie" => Some(Self::Cookie),
+            "body" | "formData" => Some(Self::Body),
+            _ => None,
+        }
+    }
+}
+
+/// A generated tool together with the HTTP route that executes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpOperation {
+    /// The tool definition exposed to callers
+    pub tool: ToolDefinition,
+
+    /// HTTP method in lowercase (`get`, `post`, ...)
+    pub method: String,
+
+    /// Route template, e.g. `/users/{id}`
+    pub path: String,
+
+    /// Location of each tool parameter, keyed by parameter name
+    pub locations: HashMap<String, ParameterLocation>,
+}
+
+/// Transport that executes generated tools over HTTP
+pub struct HttpAdapter {
+    /// Base URL that operation paths are appended to
+    base_url: String,
+
+    /// Operations in declaration order
+    operations: Vec<HttpOperation>,
+
+    /// Authentication schemes with their credentials
+    auth: Vec<(AuthConfig, String)>,
+
+    /// OAuth2 client supplying bearer tokens
+    oauth2: Option<Arc<OAuth2Client>>,
+
+    /// HTTP client
+    client: reqwest::Client,
+
+    /// Optional per-request timeout
+    timeout: Option<Duration>,
+
+    /// Connection status
+    connected: bool,
+}
+
+impl std::fmt::Debug for HttpAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpAdapter")
+            .field("base_url", &self.base_url)
+            .field(
+                "operations",
+                &self
+                    .operations
+                    .iter()
+                    .map(|op| op.tool.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "auth",
+                &self
+                    .auth
+                    .iter()
+                    .map(|(config, _)| config.auth_type.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("oauth2", &self.oauth2.is_some())
+            .field("timeout", &self.timeout)
+            .field("connected", &self.connected)
+            .finish()
+    }
+}
+
+impl HttpAdapter {
+    /// Create an adapter for the given operations
+    pub fn new(base_url: impl Into<String>, operations: Vec<HttpOperation>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            operations,
+            auth: Vec::new(),
+            oauth2: None,
+            client: reqwest::Client::new(),
+            timeout: None,
+            connected: false,
+        }
+    }
+
+    /// Apply an authentication scheme with the given credential.
+    ///
+    /// - `apiKey`: the credential is sent in the configured header, query
+    ///   parameter or cookie
+    /// - `http` with scheme `basic`: the credential is `user:password`
+    /// - `http` (bearer), `oauth2` and `openIdConnect`: the credential is sent
+    ///   as a bearer token
+    pub fn with_auth(mut self, auth: AuthConfig, credential: impl Into<String>) -> Self {
+        self.auth.push((auth, credential.into()));
+        self
+    }
+
+    /// Apply an authentication scheme with the credential stored as secret
+    /// `name`, failing if it isn't set.
+    pub fn with_auth_secret(
+        self,
+        auth: AuthConfig,
+        secrets: &dyn SecretResolver,
+        name: &str,
+    ) -> Result<Self> {
+        let credential = secrets.resolve(name).ok_or_else(|| {
+            Error::InvalidConfig(format!(
+                "secret '{}' for {} authentication is not set",
+                name, auth.auth_type
+            ))
+        })?;
+        Ok(self.with_auth(auth, credential))
+    }
+
+    /// Send a bearer token from `oauth2` with every call. When the server
+    /// answers 401 the token is renewed and the call is made once more.
+    pub fn with_oauth2(mut self, oauth2: Arc<OAuth2Client>) -> Self {
+        self.oauth2 = Some(oauth2);
+        self
+    }
+
+    /// Use a preconfigured HTTP client
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set a per-request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Override the base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Get the base URL
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Get the operations this adapter executes
+    pub fn operations(&self) -> &[HttpOperation] {
+        &self.operations
+    }
+
+    /// Look up an operation by tool name
+    pub fn operation(&self, tool: &str) -> Option<&HttpOperation> {
+        self.operations.iter().find(|op| op.tool.name == tool)
+    }
+
+    /// Build the HTTP request for a tool call
+    fn build_request(
+        &self,
+        operation: &HttpOperation,
+        arguments: &Value,
+    ) -> Result<reqwest::RequestBuilder> {
+        let empty = serde_json::Map::new();
+        let args = arguments.as_object().unwrap_or(&empty);
+
+        let mut path_vars = HashMap::new();
+        let mut query: Vec<(String, String)> = Vec::new();
+        let mut headers: Vec<(String, String)> = Vec::new();
+        let mut cookies: Vec<String> = Vec::new();
+        let mut body = None;
+        let mut body_fields = serde_json::Map::new();
+
+        for (name, location) in &operation.locations {
+            let Some(value) = args.get(name).filter(|v| !v.is_null()) else {
+                if *location == ParameterLocation::Path {
+                    return Err(Error::MissingParameter(name.clone()));
+                }
+                continue;
+            };
+
+            match location {
+                ParameterLocation::Path => {
+                    path_vars.insert(name.clone(), value_to_string(value));
+                }
+                ParameterLocation::Query => match value {
+                    Value::Array(items) => {
+                        query.extend(items.iter().map(|v| (name.clone(), value_to_string(v))))
+                    }
+                    other => query.push((name.clone(), value_to_string(other))),
+                },
+                ParameterLocation::Header => headers.push((name.clone(), value_to_string(value))),
+                ParameterLocation::Cookie => {
+                    cookies.push(format!("{}={}", name, value_to_string(value)))
+                }
+                ParameterLocation::Body => body = Some(value.clone()),
+                ParameterLocation::BodyField => {
+                    body_fields.insert(name.clone(), value.clone());
+                }
+            }
+        }
+
+        if !body_fields.is_empty() {
+            body = match body {
+                Some(Value::Object(mut whole)) => {
+                    whole.extend(body_fields);
+                    Some(Value::Object(whole))
+                }
+                _ => Some(Value::Object(body_fields)),
+            };
+        }
+
+        let method = reqwest::Method::from_bytes(operation.method.to_uppercase().as_bytes())
+            .map_err(|e| Error::InvalidConfig(format!("Invalid HTTP method: {}", e)))?;
+        let mut basic_auth = None;
+        let mut bearer_auth = None;
+        for (auth, credential) in &self.auth {
+            match (auth.auth_type.as_str(), auth.location.as_deref()) {
+                ("apiKey", Some("query")) => {
+                    query.push((auth.name.clone().unwrap_or_default(), credential.clone()))
+                }
+                ("apiKey", Some("cookie")) => cookies.push(format!(
+                    "{}={}",
+                    auth.name.as_deref().unwrap_or_default(),
+                    credential
+                )),
+                ("apiKey", _) => headers.push((
+                    auth.name.clone().unwrap_or_else(|| "X-API-Key".to_string()),
+                    credential.clone(),
+                )),
+                ("http", _) if auth.scheme.as_deref() == Some("basic") => {
+                    let (user, password) = match credential.split_once(':') {
+                        Some((user, password)) => (user, Some(password)),
+                        None => (credential.as_str(), None),
+                    };
+                    basic_auth = Some((user, password));
+                }
+                _ => bearer_auth = Some(credential),
+            }
+        }
+
+        let url = UrlBuilder::new(&self.base_url).path_template(&operation.path, &path_vars)?;
+        let url = query
+            .into_iter()
+            .fold(url, |url, (name, value)| url.query(name, value));
+        let mut request = self.client.request(method, url.build());
+        if let Some((user, password)) = basic_auth {
+            request = request.basic_auth(user, password);
+        }
+        if let Some(credential) = bearer_auth {
+            request = request.bearer_auth(credential);
+        }
+
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if !cookies.is_empty() {
+            request = request.header(reqwest::header::COOKIE, cookies.join("; "));
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+
+        Ok(request)
+    }
+
+    /// Build and send the HTTP request for a tool call, with the OAuth2
+    /// token if there is one
+    async fn send(
+        &self,
+        operation: &HttpOperation,
+        arguments: &Value,
+    ) -> Result<reqwest::Response> {
+        let mut request = self.build_request(operation, arguments)?;
+        if let Some(oauth2) = &self.oauth2 {
+            request = request.bearer_auth(oauth2.access_token().await?);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("HTTP request failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl Transport for HttpAdapter {
+    async fn connect(&mut self) -> Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        if !self.connected {
+            return Err(Error::ExecutionFailed("not connected".to_string()));
+        }
+        Ok(self.operations.iter().map(|op| op.tool.clone()).collect())
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        if !self.connected {
+            return Err(Error::ExecutionFailed("not connected".to_string()));
+        }
+
+        let operation = self
+            .operation(&call.tool)
+            .ok_or_else(|| Error::ToolNotFound(call.tool.clone()))?;
+
+        let start = Instant::now();
+        let mut response = self.send(operation, &call.arguments).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            if let Some(oauth2) = &self.oauth2 {
+                // The token was revoked or expired early
+                oauth2.invalidate().await;
+                response = self.send(operation, &call.arguments).await?;
+            }
+        }
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to read response: {}", e)))?;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        let result = if status.is_success() {
+            let data = if text.is_empty() {
+                Value::Null
+            } else {
+                serde_json::from_str(&text).unwrap_or(Value::String(text))
+            };
+            ToolResult::success(data)
+        } else {
+            ToolResult::failure(format!("HTTP {}: {}", status, text))
+        };
+
+        Ok(result.with_duration(duration_ms))
+    }
+}
+
+/// Render an argument for a path, query, header or cookie value
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AdapterGenerator;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single HTTP response and return the raw request that was received
+    async fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        (base_url, handle)
+    }
+
+    fn spec(base_url: &str) -> Value {
+        json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Repos", "version": "1.0.0"},
+            "servers": [{"url": base_url}],
+            "paths": {
+                "/repos/{owner}/{repo}/issues": {
+                    "get": {
+                        "operationId": "listIssues",
+                        "parameters": [
+                            {"name": "state", "in": "query", "schema": {"type": "string"}},
+                            {"name": "labels", "in": "query", "schema": {"type": "array"}},
+                            {"name": "X-Trace", "in": "header", "schema": {"type": "string"}}
+                        ]
+                    },
+                    "post": {
+                        "operationId": "createIssue",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "required": ["title"],
+                                        "properties": {
+                                            "title": {"type": "string"},
+                                            "labels": {"type": "array"}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "securitySchemes": {"bearer": {"type": "http", "scheme": "bearer"}}
+            }
+        })
+    }
+
+    #[test]
+    fn test_generate_operations() {
+        let generator = AdapterGenerator::new(spec("https://api.example.com"), None);
+        let operations = generator.generate_operations().unwrap();
+        assert_eq!(operations.len(), 2);
+
+        let list = operations
+            .iter()
+            .find(|op| op.tool.name == "listIssues")
+            .unwrap();
+        assert_eq!(list.method, "get");
+        assert_eq!(list.path, "/repos/{owner}/{repo}/issues");
+        assert_eq!(list.locations["owner"], ParameterLocation::Path);
+        assert_eq!(list.locations["state"], ParameterLocation::Query);
+        assert_eq!(list.locations["X-Trace"], ParameterLocation::Header);
+
+        let create = operations
+            .iter()
+            .find(|op| op.tool.name == "createIssue")
+            .unwrap();
+        assert_eq!(create.locations["title"], ParameterLocation::BodyField);
+        assert_eq!(create.locations["labels"], ParameterLocation::BodyField);
+
+        let adapter = generator.http_adapter().unwrap();
+        assert_eq!(adapter.base_url(), "https://api.example.com");
+    }
+
+    #[test]
+    fn test_swagger2_base_url() {
+        let generator = AdapterGenerator::new(
+            json!({"swagger": "2.0", "host": "petstore.io", "basePath": "/v2", "schemes": ["http"], "paths": {}}),
+            None,
+        );
+        assert_eq!(generator.base_url().unwrap(), "http://petstore.io/v2");
+
+        let without = AdapterGenerator::new(json!({"paths": {}}), None);
+        assert!(without.base_url().is_none());
+        assert!(without.http_adapter().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_call_builds_request_and_parses_json() {
+        let (base_url, server) = serve_once("200 OK", r#"[{"number": 1}]"#).await;
+        let generator = AdapterGenerator::new(spec(&base_url), None);
+        let auth = generator.extract_auth_config().remove(0);
+
+        let mut adapter = generator.http_adapter().unwrap().with_auth(auth, "tok123");
+        adapter.connect().await.unwrap();
+
+        let call = ToolCall::builder("listIssues")
+            .arg_str("owner", "dirmacs")
+            .arg_str("repo", "my repo")
+            .arg_str("state", "open")
+            .arg("labels", json!(["bug", "p1"]))
+            .arg_str("X-Trace", "abc")
+            .build();
+        let result = adapter.call(&call).await.unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.data, Some(json!([{"number": 1}])));
+        assert!(result.duration_ms.is_some());
+
+        let request = server.await.unwrap();
+        let request_line = request.lines().next().unwrap();
+        assert!(request_line.starts_with("GET /repos/dirmacs/my%20repo/issues?"));
+        assert!(request_line.contains("state=open"));
+        assert!(request_line.contains("labels=bug&labels=p1"));
+        let lower = request.to_lowercase();
+        assert!(lower.contains("authorization: bearer tok123"));
+        assert!(lower.contains("x-trace: abc"));
+    }
+
+    #[tokio::test]
+    async fn test_call_sends_body_and_api_key() {
+        let (base_url, server) = serve_once("201 Created", r#"{"id": 7}"#).await;
+        let generator = AdapterGenerator::new(spec(&base_url), None);
+        let api_key = AuthConfig {
+            auth_type: "apiKey".to_string(),
+            scheme: None,
+            name: Some("X-API-Key".to_string()),
+            location: Some("header".to_string()),
+            flows: None,
+        };
+
+        let secrets = HashMap::from([("api_key".to_string(), "k-1".to_string())]);
+        let adapter = generator.http_adapter().unwrap();
+        assert!(matches!(
+            adapter.with_auth_secret(api_key.clone(), &secrets, "missing"),
+            Err(Error::InvalidConfig(_))
+        ));
+        let mut adapter = generator
+            .http_adapter()
+            .unwrap()
+            .with_auth_secret(api_key, &secrets, "api_key")
+            .unwrap();
+        adapter.connect().await.unwrap();
+
+        let call = ToolCall::builder("createIssue")
+            .arg_str("owner", "o")
+            .arg_str("repo", "r")
+            .arg_str("title", "Bug")
+            .build();
+        let result = adapter.call(&call).await.unwrap();
+        assert_eq!(result.data, Some(json!({"id": 7})));
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /repos/o/r/issues "));
+        assert!(request.to_lowercase().contains("x-api-key: k-1"));
+        assert!(request.ends_with(r#"{"title":"Bug"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_error_status_and_invalid_calls() {
+        let (base_url, server) = serve_once("404 Not Found", r#"{"message": "Not Found"}"#).await;
+        let mut adapter = AdapterGenerator::new(spec(&base_url), None)
+            .http_adapter()
+            .unwrap();
+
+        let call = ToolCall::builder("listIssues")
+            .arg_str("owner", "o")
+            .arg_str("repo", "r")
+            .build();
+        assert!(adapter.call(&call).await.is_err(), "requires connect");
+
+        adapter.connect().await.unwrap();
+        let result = adapter.call(&call).await.unwrap();
+        assert!(!result.is_success());
+        assert!(result.error.unwrap().contains("404"));
+        server.await.unwrap();
+
+        assert!(matches!(
+            adapter.call(&ToolCall::new("nope")).await,
+            Err(Error::ToolNotFound(_))
+        ));
+        assert!(matches!(
+            adapter.call(&ToolCall::builder("listIssues").arg_str("owner", "o").build()).await,
+            Err(Error::MissingParameter(name)) if name == "repo"
+        ));
+        assert_eq!(adapter.list_tools().await.unwrap().len(), 2);
+    }
+}
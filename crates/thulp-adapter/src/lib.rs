@@ -5,8 +5,13 @@
 //! This crate provides functionality to:
 //! - Parse OpenAPI v2/v3 specifications
 //! - Convert API endpoints into Thulp tool definitions
+//! - Resolve local `$ref` pointers (`components/schemas`, `definitions`)
 //! - Extract authentication requirements
 //! - Generate adapter configuration files
+//! - Execute generated tools over HTTP with [`HttpAdapter`]
+//! - Obtain and refresh OAuth2 tokens for them with [`OAuth2Client`]
+//! - Export tool definitions back to OpenAPI
+//! - Import OpenAI function-calling, LangChain, Anthropic and MCP tool manifests
 //!
 //! ## Example
 //!
@@ -43,7 +48,20 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use thulp_core::{Parameter, ParameterType, ToolDefinition};
+use std::collections::HashMap;
+use thulp_core::{Parameter, ParameterConstraints, ParameterType, ToolDefinition, ToolNameRules};
+
+mod export;
+mod http;
+mod manifest;
+mod oauth2;
+mod refs;
+
+pub use export::OpenApiExporter;
+pub use http::{HttpAdapter, HttpOperation, ParameterLocation};
+pub use manifest::{import_tool, import_tools, import_tools_str, ManifestFormat};
+pub use oauth2::{OAuth2Client, OAuth2Flow, OAuth2Flows, OAuth2Grant, OAuth2Token};
+pub use refs::{RefResolver, CIRCULAR_REF_KEY};
 
 /// Result type for adapter operations
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
@@ -65,6 +83,10 @@ pub struct AuthConfig {
     /// Location of the API key (query, header, cookie)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<String>,
+
+    /// Flows of an oauth2 scheme, with their URLs and scopes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flows: Option<OAuth2Flows>,
 }
 
 /// Adapter generator for converting OpenAPI specs to Thulp tools
@@ -74,6 +96,9 @@ pub struct AdapterGenerator {
 
     /// Provider name
     provider_name: String,
+
+    /// Rules renaming the generated tools
+    tool_names: ToolNameRules,
 }
 
 impl AdapterGenerator {
@@ -84,6 +109,7 @@ impl AdapterGenerator {
         Self {
             openapi_spec,
             provider_name,
+            tool_names: ToolNameRules::default(),
         }
     }
 
@@ -105,12 +131,29 @@ impl AdapterGenerator {
         Ok(Self {
             openapi_spec: spec,
             provider_name,
+            tool_names: ToolNameRules::default(),
         })
     }
 
+    /// Name the generated tools under `rules`, e.g. to turn `listUsers`
+    /// operation IDs into `list_users`
+    pub fn with_tool_names(mut self, rules: ToolNameRules) -> Self {
+        self.tool_names = rules;
+        self
+    }
+
     /// Generate Thulp tool definitions from the OpenAPI specification
     pub fn generate_tools(&self) -> Result<Vec<ToolDefinition>> {
-        let mut tools = Vec::new();
+        Ok(self
+            .generate_operations()?
+            .into_iter()
+            .map(|op| op.tool)
+            .collect())
+    }
+
+    /// Generate tool definitions together with the HTTP routes that execute them
+    pub fn generate_operations(&self) -> Result<Vec<HttpOperation>> {
+        let mut operations = Vec::new();
         let spec = &self.openapi_spec;
 
         // Get paths from the specification
@@ -131,22 +174,55 @@ impl AdapterGenerator {
                     continue;
                 }
 
-                if let Some(tool) = self.operation_to_tool(path, method, operation)? {
-                    tools.push(tool);
+                if let Some(op) = self.operation_to_http(path, method, operation)? {
+                    operations.push(op);
                 }
             }
         }
 
-        Ok(tools)
+        Ok(operations)
+    }
+
+    /// Base URL of the API, from `servers` (OpenAPI 3) or
+    /// `schemes`/`host`/`basePath` (Swagger 2)
+    pub fn base_url(&self) -> Option<String> {
+        let spec = &self.openapi_spec;
+
+        if let Some(url) = spec
+            .get("servers")
+            .and_then(|s| s.get(0))
+            .and_then(|s| s.get("url"))
+            .and_then(|u| u.as_str())
+        {
+            return Some(url.to_string());
+        }
+
+        let host = spec.get("host").and_then(|h| h.as_str())?;
+        let scheme = spec
+            .get("schemes")
+            .and_then(|s| s.get(0))
+            .and_then(|s| s.as_str())
+            .unwrap_or("https");
+        let base_path = spec.get("basePath").and_then(|b| b.as_str()).unwrap_or("");
+        Some(format!("{}://{}{}", scheme, host, base_path))
+    }
+
+    /// Create an [`HttpAdapter`] that executes the generated tools against
+    /// the specification's [`base_url`](Self::base_url)
+    pub fn http_adapter(&self) -> Result<HttpAdapter> {
+        let base_url = self.base_url().ok_or(
+            "No server URL in OpenAPI specification; use HttpAdapter::new with a base URL",
+        )?;
+        Ok(HttpAdapter::new(base_url, self.generate_operations()?))
     }
 
-    /// Convert a single OpenAPI operation to a Thulp tool definition
-    fn operation_to_tool(
+    /// Convert a single OpenAPI operation to a tool definition and its route
+    fn operation_to_http(
         &self,
         path: &str,
         method: &str,
         operation: &Value,
-    ) -> Result<Option<ToolDefinition>> {
+    ) -> Result<Option<HttpOperation>> {
         let operation = operation
             .as_object()
             .ok_or(format!("Invalid operation for {} {}", method, path))?;
@@ -170,35 +246,47 @@ impl AdapterGenerator {
 
         // Build parameters
         let mut parameters = Vec::new();
+        let mut locations = HashMap::new();
 
         // Add path parameters
         if let Some(path_params) = self.extract_path_parameters(path) {
-            parameters.extend(path_params);
+            for param in path_params {
+                locations.insert(param.name.clone(), ParameterLocation::Path);
+                parameters.push(param);
+            }
         }
 
         // Add query parameters from operation
         if let Some(query_params) = operation.get("parameters").and_then(|p| p.as_array()) {
             for param in query_params {
-                if let Some(param_def) = self.parameter_to_tool_parameter(param)? {
+                if let Some((param_def, location)) = self.parameter_to_tool_parameter(param)? {
+                    locations.insert(param_def.name.clone(), location);
                     parameters.push(param_def);
                 }
             }
         }
 
-        // Add request body as a parameter (for POST, PUT, PATCH)
+        // Add request body parameters (for POST, PUT, PATCH)
         if matches!(method, "post" | "put" | "patch") {
-            if let Some(body_param) = self.request_body_to_parameter(operation)? {
+            for (body_param, location) in self.request_body_to_parameters(operation, &locations)? {
+                locations.insert(body_param.name.clone(), location);
                 parameters.push(body_param);
             }
         }
 
         let tool = ToolDefinition {
-            name: operation_id,
+            name: self.tool_names.apply(&operation_id),
             description: description.to_string(),
             parameters,
+            destructive: method == "delete",
         };
 
-        Ok(Some(tool))
+        Ok(Some(HttpOperation {
+            tool,
+            method: method.to_string(),
+            path: path.to_string(),
+            locations,
+        }))
     }
 
     /// Extract path parameters from a path string
@@ -224,8 +312,17 @@ impl AdapterGenerator {
         }
     }
 
-    /// Convert an OpenAPI parameter to a Thulp parameter
-    fn parameter_to_tool_parameter(&self, param: &Value) -> Result<Option<Parameter>> {
+    /// Resolver for `$ref` pointers into this specification
+    pub fn resolver(&self) -> RefResolver<'_> {
+        RefResolver::new(&self.openapi_spec)
+    }
+
+    /// Convert an OpenAPI parameter to a Thulp parameter and its location
+    fn parameter_to_tool_parameter(
+        &self,
+        param: &Value,
+    ) -> Result<Option<(Parameter, ParameterLocation)>> {
+        let param = self.resolver().resolve(param)?;
         let param = param.as_object().ok_or("Invalid parameter definition")?;
 
         let name = param
@@ -233,66 +330,105 @@ impl AdapterGenerator {
             .and_then(|n| n.as_str())
             .ok_or("Parameter missing name")?;
 
-        let param_type = param
+        // OpenAPI 3 describes the value in `schema`; Swagger 2 inlines it
+        let schema = param
             .get("schema")
-            .and_then(|s| s.get("type"))
-            .and_then(|t| t.as_str())
-            .map(|t| self.parse_parameter_type(t))
-            .unwrap_or(ParameterType::String);
+            .and_then(|s| s.as_object())
+            .unwrap_or(param);
 
         let required = param
             .get("required")
             .and_then(|r| r.as_bool())
             .unwrap_or(false);
-        let description = param
-            .get("description")
-            .and_then(|d| d.as_str())
-            .unwrap_or("");
+        let description = param.get("description").and_then(|d| d.as_str());
 
-        let param_builder = Parameter::builder(name)
-            .param_type(param_type)
-            .required(required)
-            .description(description);
+        let location = param
+            .get("in")
+            .and_then(|l| l.as_str())
+            .and_then(ParameterLocation::from_openapi)
+            .unwrap_or(ParameterLocation::Query);
 
-        Ok(Some(param_builder.build()))
+        Ok(Some((
+            schema_to_parameter(name, schema, required, description),
+            location,
+        )))
     }
 
-    /// Convert request body to a parameter
-    fn request_body_to_parameter(
+    /// Convert the request body to parameters.
+    ///
+    /// A JSON object body is expanded into one [`ParameterLocation::BodyField`]
+    /// parameter per property. Other bodies, and object bodies whose property
+    /// names clash with path or query parameters, become a single `data`
+    /// parameter holding the whole body.
+    fn request_body_to_parameters(
         &self,
         operation: &serde_json::Map<String, Value>,
-    ) -> Result<Option<Parameter>> {
-        if let Some(request_body) = operation.get("requestBody") {
-            let request_body = request_body.as_object().ok_or("Invalid requestBody")?;
-
-            if let Some(content) = request_body.get("content") {
-                let content = content.as_object().ok_or("Invalid requestBody content")?;
+        existing: &HashMap<String, ParameterLocation>,
+    ) -> Result<Vec<(Parameter, ParameterLocation)>> {
+        let Some(request_body) = operation.get("requestBody") else {
+            return Ok(Vec::new());
+        };
+        let request_body = match request_body.get("$ref").and_then(|r| r.as_str()) {
+            Some(reference) => self.resolver().lookup(reference)?,
+            None => request_body,
+        };
+        let request_body = request_body.as_object().ok_or("Invalid requestBody")?;
+        let body_required = request_body
+            .get("required")
+            .and_then(|r| r.as_bool())
+            .unwrap_or(false);
 
-                // For simplicity, just use the first content type
-                if let Some((media_type, _)) = content.iter().next() {
-                    let param = Parameter::builder("data")
-                        .param_type(ParameterType::Object)
-                        .description(format!("Request body ({} media type)", media_type))
-                        .build();
+        let Some(content) = request_body.get("content") else {
+            return Ok(Vec::new());
+        };
+        let content = content.as_object().ok_or("Invalid requestBody content")?;
+
+        // Prefer JSON, then any +json type, then whatever comes first
+        let Some((media_type, media)) = content
+            .get_key_value("application/json")
+            .or_else(|| content.iter().find(|(k, _)| k.ends_with("+json")))
+            .or_else(|| content.iter().next())
+        else {
+            return Ok(Vec::new());
+        };
 
-                    return Ok(Some(param));
-                }
+        let schema = match media.get("schema") {
+            Some(schema) => self.resolver().resolve(schema)?,
+            None => Value::Null,
+        };
+        let is_json = media_type == "application/json" || media_type.ends_with("+json");
+
+        if let (true, Some(fields)) = (is_json, object_properties(&schema)) {
+            if !fields.is_empty()
+                && !fields
+                    .iter()
+                    .any(|(name, _, _)| existing.contains_key(name))
+            {
+                return Ok(fields
+                    .into_iter()
+                    .map(|(name, property, required)| {
+                        let param = match property.as_object() {
+                            Some(property) => schema_to_parameter(&name, property, required, None),
+                            None => Parameter::builder(&name).required(required).build(),
+                        };
+                        (param, ParameterLocation::BodyField)
+                    })
+                    .collect());
             }
         }
 
-        Ok(None)
-    }
+        let param_type = schema
+            .get("type")
+            .and_then(schema_type_name)
+            .map(parameter_type)
+            .unwrap_or(ParameterType::Object);
+        let param = Parameter::builder("data")
+            .param_type(param_type)
+            .required(body_required)
+            .description(format!("Request body ({} media type)", media_type))
+            .build();
 
-    /// Parse parameter type from OpenAPI type string
-    fn parse_parameter_type(&self, openapi_type: &str) -> ParameterType {
-        match openapi_type {
-            "integer" => ParameterType::Integer,
-            "number" => ParameterType::Number,
-            "boolean" => ParameterType::Boolean,
-            "array" => ParameterType::Array,
-            "object" => ParameterType::Object,
-            _ => ParameterType::String,
-        }
+        Ok(vec![(param, ParameterLocation::Body)])
     }
 
     /// Sanitize a path for use as an operation ID
@@ -356,11 +492,17 @@ impl AdapterGenerator {
             .and_then(|i| i.as_str())
             .map(|i| i.to_string());
 
+        let flows = match auth_type.as_str() {
+            "oauth2" => OAuth2Flows::from_scheme(scheme),
+            _ => None,
+        };
+
         Some(AuthConfig {
             auth_type,
             scheme: scheme_name,
             name: param_name,
             location,
+            flows,
         })
     }
 
@@ -378,6 +520,109 @@ impl AdapterGenerator {
     }
 }
 
+/// Get the type name from a schema `type`, which OpenAPI 3.1 allows to be a
+/// list such as `["string", "null"]`
+fn schema_type_name(ty: &Value) -> Option<&str> {
+    match ty {
+        Value::String(s) => Some(s),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+/// Build a parameter from a (resolved) schema
+pub(crate) fn schema_to_parameter(
+    name: &str,
+    schema: &serde_json::Map<String, Value>,
+    required: bool,
+    description: Option<&str>,
+) -> Parameter {
+    let param_type = schema
+        .get("type")
+        .and_then(schema_type_name)
+        .map(parameter_type)
+        .unwrap_or(ParameterType::String);
+
+    let description = description
+        .or_else(|| schema.get("description").and_then(|d| d.as_str()))
+        .unwrap_or("");
+
+    let mut param_builder = Parameter::builder(name)
+        .param_type(param_type)
+        .required(required)
+        .description(description);
+
+    if let Some(default) = schema.get("default") {
+        param_builder = param_builder.default(default.clone());
+    }
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        for value in values {
+            param_builder = param_builder.enum_value(value.clone());
+        }
+    }
+    // OpenAPI uses a single `example`; JSON Schema an `examples` array
+    if let Some(example) = schema.get("example") {
+        param_builder = param_builder.example(example.clone());
+    }
+    if let Some(values) = schema.get("examples").and_then(|e| e.as_array()) {
+        for value in values {
+            param_builder = param_builder.example(value.clone());
+        }
+    }
+
+    param_builder
+        .constraints(ParameterConstraints::from_schema(schema))
+        .build()
+}
+
+/// Parse parameter type from an OpenAPI / JSON Schema type string
+fn parameter_type(type_name: &str) -> ParameterType {
+    match type_name {
+        "integer" => ParameterType::Integer,
+        "number" => ParameterType::Number,
+        "boolean" => ParameterType::Boolean,
+        "array" => ParameterType::Array,
+        "object" => ParameterType::Object,
+        _ => ParameterType::String,
+    }
+}
+
+/// Collect `(name, schema, required)` for each property of an object schema,
+/// merging the members of an `allOf`
+pub(crate) fn object_properties(schema: &Value) -> Option<Vec<(String, Value, bool)>> {
+    let mut fields: Vec<(String, Value, bool)> = Vec::new();
+    let mut required: Vec<&str> = Vec::new();
+    let mut found = false;
+
+    let parts = match schema.get("allOf").and_then(|a| a.as_array()) {
+        Some(all_of) => all_of.iter().collect(),
+        None => vec![schema],
+    };
+    for part in parts {
+        if let Some(properties) = part.get("properties").and_then(|p| p.as_object()) {
+            found = true;
+            for (name, property) in properties {
+                fields.retain(|(existing, _, _)| existing != name);
+                fields.push((name.clone(), property.clone(), false));
+            }
+        }
+        if let Some(names) = part.get("required").and_then(|r| r.as_array()) {
+            required.extend(names.iter().filter_map(|n| n.as_str()));
+        }
+    }
+
+    if !found {
+        return None;
+    }
+    for (name, _, is_required) in &mut fields {
+        *is_required = required.contains(&name.as_str());
+    }
+    Some(fields)
+}
+
 /// Adapter configuration structure for serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AdapterConfig {
@@ -418,6 +663,7 @@ mod tests {
             scheme: None,
             name: Some("X-API-Key".to_string()),
             location: Some("header".to_string()),
+            flows: None,
         };
 
         let yaml = serde_yaml::to_string(&auth_config).unwrap();
@@ -522,6 +768,34 @@ mod tests {
         assert!(tool_names.contains(&"deleteUser".to_string()));
     }
 
+    #[test]
+    fn test_generate_tools_with_tool_names() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Test API", "version": "1.0.0"},
+            "paths": {
+                "/users": {
+                    "get": {"operationId": "listUsers"},
+                    "post": {"operationId": "createUser"}
+                }
+            }
+        });
+
+        let rules = ToolNameRules::new()
+            .with_snake_case()
+            .with_prefix("crm_")
+            .with_rename("createUser", "add_contact");
+        let generator = AdapterGenerator::new(spec, None).with_tool_names(rules);
+        let mut names: Vec<_> = generator
+            .generate_operations()
+            .unwrap()
+            .into_iter()
+            .map(|op| op.tool.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["add_contact", "crm_list_users"]);
+    }
+
     #[test]
     fn test_generate_tools_complex_spec() {
         let spec = serde_json::json!({
@@ -557,6 +831,221 @@ mod tests {
         assert!(param_names.contains(&"filter".to_string()));
     }
 
+    #[test]
+    fn test_generate_tools_resolves_refs() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Test API", "version": "1.0.0"},
+            "paths": {
+                "/orders": {
+                    "get": {
+                        "operationId": "listOrders",
+                        "parameters": [
+                            {"$ref": "#/components/parameters/Limit"},
+                            {
+                                "name": "status",
+                                "in": "query",
+                                "schema": {"$ref": "#/components/schemas/OrderStatus"}
+                            }
+                        ]
+                    },
+                    "post": {
+                        "operationId": "createOrder",
+                        "requestBody": {"$ref": "#/components/requestBodies/Order"}
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "Limit": {
+                        "name": "limit",
+                        "in": "query",
+                        "required": true,
+                        "schema": {"$ref": "#/components/schemas/PageSize"}
+                    }
+                },
+                "schemas": {
+                    "PageSize": {"type": "integer", "default": 20, "example": 50, "description": "Page size"},
+                    "OrderStatus": {"type": "string", "enum": ["open", "closed"]}
+                },
+                "requestBodies": {
+                    "Order": {"content": {"application/json": {"schema": {"type": "object"}}}}
+                }
+            }
+        });
+
+        let generator = AdapterGenerator::new(spec, Some("shop".to_string()));
+        let tools = generator.generate_tools().unwrap();
+        let list = tools.iter().find(|t| t.name == "listOrders").unwrap();
+
+        let limit = &list.parameters[0];
+        assert_eq!(limit.name, "limit");
+        assert_eq!(limit.param_type, ParameterType::Integer);
+        assert!(limit.required);
+        assert_eq!(limit.description, "Page size");
+        assert_eq!(limit.default, Some(serde_json::json!(20)));
+        assert_eq!(limit.examples, vec![serde_json::json!(50)]);
+
+        let status = &list.parameters[1];
+        assert_eq!(status.param_type, ParameterType::String);
+        assert_eq!(status.enum_values.len(), 2);
+
+        let create = tools.iter().find(|t| t.name == "createOrder").unwrap();
+        assert_eq!(create.parameters.len(), 1);
+        assert_eq!(create.parameters[0].name, "data");
+    }
+
+    #[test]
+    fn test_request_body_expansion() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Test API", "version": "1.0.0"},
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": {
+                                    "schema": {"$ref": "#/components/schemas/NewPet"}
+                                }
+                            }
+                        }
+                    }
+                },
+                "/pets/{id}": {
+                    "put": {
+                        "operationId": "replacePet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {"id": {"type": "integer"}}
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "patch": {
+                        "operationId": "patchPet",
+                        "requestBody": {
+                            "content": {
+                                "application/json-patch+json": {
+                                    "schema": {"type": "array"}
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Named": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {"name": {"type": "string", "description": "Pet name"}}
+                    },
+                    "NewPet": {
+                        "allOf": [
+                            {"$ref": "#/components/schemas/Named"},
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "kind": {"type": "string", "enum": ["cat", "dog"]},
+                                    "age": {"type": "integer"}
+                                }
+                            }
+                        ]
+                    }
+                }
+            }
+        });
+
+        let generator = AdapterGenerator::new(spec, None);
+        let operations = generator.generate_operations().unwrap();
+        let op = |name: &str| operations.iter().find(|o| o.tool.name == name).unwrap();
+
+        let create = &op("createPet").tool;
+        assert_eq!(create.parameters.len(), 3);
+        let name = create.get_parameter("name").unwrap();
+        assert!(name.required);
+        assert_eq!(name.description, "Pet name");
+        let kind = create.get_parameter("kind").unwrap();
+        assert!(!kind.required);
+        assert_eq!(kind.enum_values.len(), 2);
+        assert_eq!(
+            create.get_parameter("age").unwrap().param_type,
+            ParameterType::Integer
+        );
+        assert!(create.get_parameter("data").is_none());
+        assert_eq!(
+            op("createPet").locations["kind"],
+            ParameterLocation::BodyField
+        );
+
+        // Property names clashing with the path parameter keep the body whole
+        let replace = op("replacePet");
+        assert_eq!(replace.locations["data"], ParameterLocation::Body);
+        assert_eq!(replace.locations["id"], ParameterLocation::Path);
+
+        // Non-object bodies keep a typed `data` parameter
+        let patch = &op("patchPet").tool;
+        let data = patch.get_parameter("data").unwrap();
+        assert_eq!(data.param_type, ParameterType::Array);
+        assert!(data.description.contains("application/json-patch+json"));
+    }
+
+    #[test]
+    fn test_generate_tools_swagger2_definitions() {
+        let spec = serde_json::json!({
+            "swagger": "2.0",
+            "info": {"title": "Test API", "version": "1.0.0"},
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "operationId": "listPets",
+                        "parameters": [
+                            {"name": "tags", "in": "query", "type": "array"},
+                            {
+                                "name": "filter",
+                                "in": "query",
+                                "schema": {"$ref": "#/definitions/Filter"}
+                            }
+                        ]
+                    }
+                }
+            },
+            "definitions": {
+                "Filter": {
+                    "type": "object",
+                    "properties": {"next": {"$ref": "#/definitions/Filter"}}
+                }
+            }
+        });
+
+        let generator = AdapterGenerator::new(spec, None);
+        let tools = generator.generate_tools().unwrap();
+
+        assert_eq!(tools[0].parameters[0].param_type, ParameterType::Array);
+        assert_eq!(tools[0].parameters[1].param_type, ParameterType::Object);
+    }
+
+    #[test]
+    fn test_generate_tools_unresolved_ref_is_error() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/x": {"get": {"parameters": [{"$ref": "#/components/parameters/Nope"}]}}
+            }
+        });
+
+        let generator = AdapterGenerator::new(spec, None);
+        let err = generator.generate_tools().unwrap_err();
+        assert!(err.to_string().contains("#/components/parameters/Nope"));
+    }
+
     #[test]
     fn test_extract_auth_config_apikey() {
         let spec = serde_json::json!({
@@ -630,35 +1119,12 @@ mod tests {
 
     #[test]
     fn test_parse_parameter_type() {
-        let generator = AdapterGenerator::new(serde_json::json!({}), Some("test".to_string()));
-
-        assert_eq!(
-            generator.parse_parameter_type("integer"),
-            ParameterType::Integer
-        );
-        assert_eq!(
-            generator.parse_parameter_type("number"),
-            ParameterType::Number
-        );
-        assert_eq!(
-            generator.parse_parameter_type("boolean"),
-            ParameterType::Boolean
-        );
-        assert_eq!(
-            generator.parse_parameter_type("array"),
-            ParameterType::Array
-        );
-        assert_eq!(
-            generator.parse_parameter_type("object"),
-            ParameterType::Object
-        );
-        assert_eq!(
-            generator.parse_parameter_type("string"),
-            ParameterType::String
-        );
-        assert_eq!(
-            generator.parse_parameter_type("unknown"),
-            ParameterType::String
-        );
+        assert_eq!(parameter_type("integer"), ParameterType::Integer);
+        assert_eq!(parameter_type("number"), ParameterType::Number);
+        assert_eq!(parameter_type("boolean"), ParameterType::Boolean);
+        assert_eq!(parameter_type("array"), ParameterType::Array);
+        assert_eq!(parameter_type("object"), ParameterType::Object);
+        assert_eq!(parameter_type("string"), ParameterType::String);
+        assert_eq!(parameter_type("unknown"), ParameterType::String);
     }
 }
@@ -0,0 +1,392 @@
+//! Import of external tool manifests.
+//!
+//! Agent stacks built on other frameworks already describe their tools as
+//! JSON Schema wrapped in a framework-specific envelope. [`import_tools`]
+//! recognizes the common envelopes and converts each entry into a
+//! [`ToolDefinition`]:
+//!
+//! | Format | Shape |
+//! |--------|-------|
+//! | OpenAI function calling | `{"name", "description", "parameters": <schema>}` |
+//! | OpenAI tools | `{"type": "function", "function": {...}}` |
+//! | LangChain | `{"name", "description", "args_schema": <schema>}` or `{"name", "args": {<properties>}}` |
+//! | Anthropic | `{"name", "description", "input_schema": <schema>}` |
+//! | MCP | `{"name", "description", "inputSchema": <schema>}` |
+//!
+//! A manifest may be a single entry, an array of entries, or an object with a
+//! `tools` or `functions` array. Local `$ref`s inside a schema (such as the
+//! `$defs` emitted by pydantic) are resolved against that schema.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_json::json;
+//! use thulp_adapter::import_tools;
+//!
+//! let tools = import_tools(&json!({
+//!     "tools": [{
+//!         "type": "function",
+//!         "function": {
+//!             "name": "get_weather",
+//!             "description": "Get the current weather",
+//!             "parameters": {
+//!                 "type": "object",
+//!                 "properties": {"city": {"type": "string"}},
+//!                 "required": ["city"]
+//!             }
+//!         }
+//!     }]
+//! }))
+//! .unwrap();
+//!
+//! assert_eq!(tools[0].name, "get_weather");
+//! assert!(tools[0].parameters[0].required);
+//! ```
+
+use crate::{object_properties, schema_to_parameter, RefResolver, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thulp_core::{Parameter, ToolDefinition};
+
+/// Envelope format of a single manifest entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestFormat {
+    /// OpenAI function definition (`parameters`)
+    OpenAiFunction,
+    /// OpenAI tool wrapping a function (`type: function`, `function`)
+    OpenAiTool,
+    /// LangChain tool spec (`args_schema` or `args`)
+    LangChain,
+    /// Anthropic tool definition (`input_schema`)
+    Anthropic,
+    /// MCP tool definition (`inputSchema`)
+    Mcp,
+}
+
+impl ManifestFormat {
+    /// Detect the format of a single manifest entry.
+    pub fn detect(entry: &Value) -> Option<Self> {
+        let entry = entry.as_object()?;
+        if entry.get("function").is_some_and(Value::is_object) {
+            return Some(Self::OpenAiTool);
+        }
+        if !entry.get("name").is_some_and(Value::is_string) {
+            return None;
+        }
+
+        if entry.contains_key("parameters") {
+            Some(Self::OpenAiFunction)
+        } else if entry.contains_key("args_schema") || entry.contains_key("args") {
+            Some(Self::LangChain)
+        } else if entry.contains_key("input_schema") {
+            Some(Self::Anthropic)
+        } else if entry.contains_key("inputSchema") {
+            Some(Self::Mcp)
+        } else {
+            // A bare name and description is a function without parameters
+            Some(Self::OpenAiFunction)
+        }
+    }
+}
+
+/// Import every tool in a manifest.
+///
+/// Fails on the first entry whose format can't be recognized.
+pub fn import_tools(manifest: &Value) -> Result<Vec<ToolDefinition>> {
+    let entries = match manifest {
+        Value::Array(entries) => entries.as_slice(),
+        Value::Object(obj) => match obj.get("tools").or_else(|| obj.get("functions")) {
+            Some(Value::Array(entries)) => entries.as_slice(),
+            _ => std::slice::from_ref(manifest),
+        },
+        _ => return Err("Manifest must be a JSON object or array".into()),
+    };
+
+    entries.iter().map(import_tool).collect()
+}
+
+/// Parse a JSON or YAML manifest and import every tool in it.
+pub fn import_tools_str(manifest: &str) -> Result<Vec<ToolDefinition>> {
+    let value: Value = serde_json::from_str(manifest)
+        .or_else(|_| serde_yaml::from_str(manifest))
+        .map_err(|e| format!("Failed to parse manifest (tried JSON and YAML): {}", e))?;
+    import_tools(&value)
+}
+
+/// Import a single manifest entry.
+pub fn import_tool(entry: &Value) -> Result<ToolDefinition> {
+    let format = ManifestFormat::detect(entry)
+        .ok_or_else(|| format!("Unrecognized tool manifest entry: {}", entry))?;
+    let entry = match format {
+        ManifestFormat::OpenAiTool => &entry["function"],
+        _ => entry,
+    };
+
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("Tool manifest entry is missing a name")?;
+    let description = entry
+        .get("description")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+
+    let parameters = match format {
+        ManifestFormat::OpenAiFunction | ManifestFormat::OpenAiTool => {
+            schema_parameters(entry.get("parameters"))?
+        }
+        ManifestFormat::Anthropic => schema_parameters(entry.get("input_schema"))?,
+        ManifestFormat::Mcp => schema_parameters(entry.get("inputSchema"))?,
+        ManifestFormat::LangChain => match entry.get("args_schema") {
+            Some(schema) => schema_parameters(Some(schema))?,
+            None => langchain_args(entry.get("args"))?,
+        },
+    };
+
+    Ok(ToolDefinition::builder(name)
+        .description(description)
+        .parameters(parameters)
+        .build())
+}
+
+/// Convert an object schema into parameters
+fn schema_parameters(schema: Option<&Value>) -> Result<Vec<Parameter>> {
+    let Some(schema) = schema.filter(|s| !s.is_null()) else {
+        return Ok(Vec::new());
+    };
+    let schema = RefResolver::new(schema).resolve(schema)?;
+
+    Ok(object_properties(&schema)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, property, required)| property_to_parameter(&name, &property, required))
+        .collect())
+}
+
+/// Convert a LangChain `args` map (properties without a `required` list).
+///
+/// An argument is required unless it declares a default.
+fn langchain_args(args: Option<&Value>) -> Result<Vec<Parameter>> {
+    let Some(args) = args.filter(|a| !a.is_null()) else {
+        return Ok(Vec::new());
+    };
+    let args = args
+        .as_object()
+        .ok_or("LangChain `args` must be an object of argument schemas")?;
+
+    Ok(args
+        .iter()
+        .map(|(name, property)| {
+            let required = property.get("default").is_none();
+            property_to_parameter(name, property, required)
+        })
+        .collect())
+}
+
+fn property_to_parameter(name: &str, property: &Value, required: bool) -> Parameter {
+    match property.as_object() {
+        Some(schema) => {
+            let mut schema = schema.clone();
+            // `Optional[T]` is emitted as `anyOf: [T, null]`; take the first typed member
+            if !schema.contains_key("type") {
+                let member_type = ["anyOf", "oneOf"]
+                    .iter()
+                    .filter_map(|key| schema.get(*key)?.as_array())
+                    .flatten()
+                    .filter_map(|member| member.get("type"))
+                    .find(|ty| ty.as_str() != Some("null"))
+                    .cloned();
+                if let Some(ty) = member_type {
+                    schema.insert("type".to_string(), ty);
+                }
+            }
+            schema_to_parameter(name, &schema, required, None)
+        }
+        None => Parameter::builder(name).required(required).build(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use thulp_core::ParameterType;
+
+    #[test]
+    fn test_detect_formats() {
+        let cases = [
+            (
+                json!({"name": "f", "parameters": {}}),
+                ManifestFormat::OpenAiFunction,
+            ),
+            (
+                json!({"type": "function", "function": {"name": "f"}}),
+                ManifestFormat::OpenAiTool,
+            ),
+            (
+                json!({"name": "f", "args_schema": {}}),
+                ManifestFormat::LangChain,
+            ),
+            (json!({"name": "f", "args": {}}), ManifestFormat::LangChain),
+            (
+                json!({"name": "f", "input_schema": {}}),
+                ManifestFormat::Anthropic,
+            ),
+            (json!({"name": "f", "inputSchema": {}}), ManifestFormat::Mcp),
+        ];
+        for (entry, format) in cases {
+            assert_eq!(ManifestFormat::detect(&entry), Some(format));
+        }
+        assert_eq!(ManifestFormat::detect(&json!({"description": "x"})), None);
+        assert_eq!(ManifestFormat::detect(&json!("f")), None);
+    }
+
+    #[test]
+    fn test_import_openai_functions() {
+        let tools = import_tools(&json!({
+            "functions": [{
+                "name": "search",
+                "description": "Search the web",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "Search terms", "examples": ["rust"]},
+                        "limit": {"type": "integer", "default": 10},
+                        "safe": {"type": "string", "enum": ["on", "off"]}
+                    },
+                    "required": ["query"]
+                }
+            }, {
+                "name": "now",
+                "description": "Current time"
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(tools.len(), 2);
+        let search = &tools[0];
+        assert_eq!(search.description, "Search the web");
+
+        let query = search.get_parameter("query").unwrap();
+        assert!(query.required);
+        assert_eq!(query.description, "Search terms");
+        assert_eq!(query.examples, vec![json!("rust")]);
+
+        let limit = search.get_parameter("limit").unwrap();
+        assert_eq!(limit.param_type, ParameterType::Integer);
+        assert!(!limit.required);
+        assert_eq!(limit.default, Some(json!(10)));
+
+        assert_eq!(search.get_parameter("safe").unwrap().enum_values.len(), 2);
+        assert!(tools[1].parameters.is_empty());
+    }
+
+    #[test]
+    fn test_import_langchain_pydantic_schema() {
+        let tools = import_tools(&json!([{
+            "name": "create_issue",
+            "description": "Create an issue",
+            "args_schema": {
+                "title": "CreateIssueInput",
+                "type": "object",
+                "properties": {
+                    "title": {"title": "Title", "type": "string"},
+                    "labels": {
+                        "anyOf": [{"type": "array", "items": {"type": "string"}}, {"type": "null"}],
+                        "default": null
+                    },
+                    "priority": {"$ref": "#/$defs/Priority"}
+                },
+                "required": ["title", "priority"],
+                "$defs": {
+                    "Priority": {"type": "integer", "enum": [1, 2, 3]}
+                }
+            }
+        }]))
+        .unwrap();
+
+        let tool = &tools[0];
+        assert!(tool.get_parameter("title").unwrap().required);
+
+        let labels = tool.get_parameter("labels").unwrap();
+        assert_eq!(labels.param_type, ParameterType::Array);
+        assert!(!labels.required);
+
+        let priority = tool.get_parameter("priority").unwrap();
+        assert_eq!(priority.param_type, ParameterType::Integer);
+        assert!(priority.required);
+        assert_eq!(priority.enum_values, vec![json!(1), json!(2), json!(3)]);
+    }
+
+    #[test]
+    fn test_import_langchain_args() {
+        let tool = import_tool(&json!({
+            "name": "calculator",
+            "description": "Evaluate an expression",
+            "args": {
+                "expression": {"title": "Expression", "type": "string"},
+                "precision": {"title": "Precision", "type": "integer", "default": 2}
+            }
+        }))
+        .unwrap();
+
+        assert!(tool.get_parameter("expression").unwrap().required);
+        assert!(!tool.get_parameter("precision").unwrap().required);
+    }
+
+    #[test]
+    fn test_import_anthropic_and_mcp() {
+        let tools = import_tools(&json!([
+            {
+                "name": "read_file",
+                "input_schema": {
+                    "type": "object",
+                    "properties": {"path": {"type": "string"}},
+                    "required": ["path"]
+                }
+            },
+            {
+                "name": "list_dir",
+                "description": "List a directory",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"recursive": {"type": "boolean"}}
+                }
+            }
+        ]))
+        .unwrap();
+
+        assert!(tools[0].get_parameter("path").unwrap().required);
+        assert_eq!(
+            tools[1].get_parameter("recursive").unwrap().param_type,
+            ParameterType::Boolean
+        );
+    }
+
+    #[test]
+    fn test_import_str_yaml() {
+        let tools = import_tools_str(
+            r#"
+- type: function
+  function:
+    name: echo
+    parameters:
+      type: object
+      properties:
+        text: {type: string}
+"#,
+        )
+        .unwrap();
+        assert_eq!(tools[0].name, "echo");
+        assert_eq!(tools[0].parameters.len(), 1);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert!(import_tools(&json!("nope")).is_err());
+        assert!(import_tools(&json!([{"description": "no name"}])).is_err());
+        assert!(import_tool(&json!({"name": "f", "args": "x"})).is_err());
+        assert!(import_tools_str("{not valid").is_err());
+    }
+}
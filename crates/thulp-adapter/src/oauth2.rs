@@ -0,0 +1,909 @@
+//! OAuth2 for generated adapters.
+//!
+//! [`OAuth2Client`] obtains access tokens for a security scheme of type
+//! `oauth2`, with the client credentials grant or the authorization code
+//! grant. The latter opens a one-shot callback server on `127.0.0.1` and
+//! uses PKCE, so it works for command-line tools without a client secret.
+//!
+//! Tokens are refreshed with their refresh token (or fetched again, for
+//! client credentials) shortly before they expire. With
+//! [`with_store`](OAuth2Client::with_store) they are kept in a
+//! [`SecretWriter`], such as a workspace's secrets store, so an
+//! authorization survives restarts. [`HttpAdapter::with_oauth2`] sends the
+//! current token as a bearer token on every call, and gets a new one when
+//! the server answers 401.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use thulp_adapter::{AdapterGenerator, OAuth2Client};
+//!
+//! let generator = AdapterGenerator::new(spec, Some("github".to_string()));
+//! let auth = generator
+//!     .extract_auth_config()
+//!     .into_iter()
+//!     .find(|auth| auth.auth_type == "oauth2")
+//!     .unwrap();
+//!
+//! let secrets = Arc::new(workspace.secrets());
+//! let oauth2 = OAuth2Client::from_auth(&auth, "my-client-id")?
+//!     .with_store(secrets, "github_oauth");
+//! if oauth2.token().await.is_none() {
+//!     oauth2.authorize(|url| println!("Open {} to sign in", url)).await?;
+//! }
+//!
+//! let adapter = generator.http_adapter()?.with_oauth2(Arc::new(oauth2));
+//! ```
+//!
+//! [`HttpAdapter::with_oauth2`]: crate::HttpAdapter::with_oauth2
+
+use crate::AuthConfig;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thulp_core::{Error, Result, SecretWriter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+/// How long before its expiry a token is replaced
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// How long a connection to the callback server may take to send its request
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// OAuth2 flows declared by a security scheme
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2Flows {
+    /// Client credentials flow (`application` in Swagger 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_credentials: Option<OAuth2Flow>,
+
+    /// Authorization code flow (`accessCode` in Swagger 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_code: Option<OAuth2Flow>,
+}
+
+impl OAuth2Flows {
+    /// Parse the flows of an `oauth2` security scheme, OpenAPI 3 or Swagger 2
+    pub(crate) fn from_scheme(scheme: &Map<String, Value>) -> Option<Self> {
+        let mut flows = Self::default();
+        if let Some(declared) = scheme.get("flows").and_then(Value::as_object) {
+            flows.client_credentials = declared
+                .get("clientCredentials")
+                .and_then(OAuth2Flow::parse);
+            flows.authorization_code = declared
+                .get("authorizationCode")
+                .and_then(OAuth2Flow::parse);
+        } else {
+            let flow = OAuth2Flow::parse(&Value::Object(scheme.clone()));
+            match scheme.get("flow").and_then(Value::as_str) {
+                Some("application") => flows.client_credentials = flow,
+                Some("accessCode") => flows.authorization_code = flow,
+                _ => {}
+            }
+        }
+        (flows != Self::default()).then_some(flows)
+    }
+}
+
+/// URLs and scopes of one OAuth2 flow
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2Flow {
+    /// Where the user is sent to grant access (authorization code flow)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_url: Option<String>,
+
+    /// Where codes, credentials and refresh tokens are exchanged for tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+
+    /// Where tokens are refreshed, if not the token URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_url: Option<String>,
+
+    /// Scopes the flow offers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+impl OAuth2Flow {
+    fn parse(flow: &Value) -> Option<Self> {
+        let flow = flow.as_object()?;
+        let url = |key: &str| flow.get(key).and_then(Value::as_str).map(str::to_string);
+        let mut scopes: Vec<String> = flow
+            .get("scopes")
+            .and_then(Value::as_object)
+            .map(|scopes| scopes.keys().cloned().collect())
+            .unwrap_or_default();
+        scopes.sort();
+        Some(Self {
+            authorization_url: url("authorizationUrl"),
+            token_url: url("tokenUrl"),
+            refresh_url: url("refreshUrl"),
+            scopes,
+        })
+    }
+}
+
+/// Grant an [`OAuth2Client`] obtains tokens with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OAuth2Grant {
+    /// The client's own credentials, no user involved
+    ClientCredentials,
+    /// A code the user grants in the browser
+    AuthorizationCode,
+}
+
+/// An OAuth2 access token with what is needed to renew it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuth2Token {
+    /// Token sent with requests
+    pub access_token: String,
+
+    /// Token type, normally `Bearer`
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+
+    /// Token that obtains a new access token without the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+
+    /// Expiry as seconds since the Unix epoch, `None` if unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+
+    /// Scopes granted, space separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+fn default_token_type() -> String {
+    "Bearer".to_string()
+}
+
+impl OAuth2Token {
+    /// Whether the token expires within `margin`; tokens without an expiry
+    /// never do
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= unix_now().saturating_add(margin.as_secs()))
+    }
+}
+
+/// Token endpoint response (RFC 6749 section 5.1)
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: Option<String>,
+    expires_in: Option<u64>,
+    refresh_token: Option<String>,
+    scope: Option<String>,
+}
+
+/// Obtains, refreshes and stores OAuth2 tokens for one client
+pub struct OAuth2Client {
+    grant: OAuth2Grant,
+    client_id: String,
+    client_secret: Option<String>,
+    authorization_url: Option<String>,
+    token_url: String,
+    refresh_url: Option<String>,
+    scopes: Vec<String>,
+    redirect_port: u16,
+    http: reqwest::Client,
+    store: Option<(Arc<dyn SecretWriter>, String)>,
+    token: Mutex<Option<OAuth2Token>>,
+}
+
+impl std::fmt::Debug for OAuth2Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2Client")
+            .field("grant", &self.grant)
+            .field("client_id", &self.client_id)
+            .field("token_url", &self.token_url)
+            .field("scopes", &self.scopes)
+            .field("store", &self.store.as_ref().map(|(_, name)| name))
+            .finish()
+    }
+}
+
+impl OAuth2Client {
+    fn new(grant: OAuth2Grant, client_id: String, token_url: String) -> Self {
+        Self {
+            grant,
+            client_id,
+            client_secret: None,
+            authorization_url: None,
+            token_url,
+            refresh_url: None,
+            scopes: Vec::new(),
+            redirect_port: 0,
+            http: reqwest::Client::new(),
+            store: None,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Create a client for the client credentials grant
+    pub fn client_credentials(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        let mut client = Self::new(
+            OAuth2Grant::ClientCredentials,
+            client_id.into(),
+            token_url.into(),
+        );
+        client.client_secret = Some(client_secret.into());
+        client
+    }
+
+    /// Create a client for the authorization code grant
+    pub fn authorization_code(
+        authorization_url: impl Into<String>,
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+    ) -> Self {
+        let mut client = Self::new(
+            OAuth2Grant::AuthorizationCode,
+            client_id.into(),
+            token_url.into(),
+        );
+        client.authorization_url = Some(authorization_url.into());
+        client
+    }
+
+    /// Create a client for an `oauth2` scheme from
+    /// [`extract_auth_config`](crate::AdapterGenerator::extract_auth_config),
+    /// using its client credentials flow if it has one and its authorization
+    /// code flow otherwise, with every scope the flow offers. Set the client
+    /// secret with [`with_client_secret`](Self::with_client_secret).
+    pub fn from_auth(auth: &AuthConfig, client_id: impl Into<String>) -> Result<Self> {
+        let flows = auth.flows.as_ref().ok_or_else(|| {
+            Error::InvalidConfig(format!(
+                "{} authentication declares no OAuth2 flows",
+                auth.auth_type
+            ))
+        })?;
+        let (grant, flow) = match (&flows.client_credentials, &flows.authorization_code) {
+            (Some(flow), _) => (OAuth2Grant::ClientCredentials, flow),
+            (None, Some(flow)) => (OAuth2Grant::AuthorizationCode, flow),
+            (None, None) => {
+                return Err(Error::InvalidConfig(
+                    "no client credentials or authorization code flow".to_string(),
+                ))
+            }
+        };
+        let token_url = flow
+            .token_url
+            .clone()
+            .ok_or_else(|| Error::InvalidConfig("OAuth2 flow has no tokenUrl".to_string()))?;
+        if grant == OAuth2Grant::AuthorizationCode && flow.authorization_url.is_none() {
+            return Err(Error::InvalidConfig(
+                "OAuth2 flow has no authorizationUrl".to_string(),
+            ));
+        }
+
+        let mut client = Self::new(grant, client_id.into(), token_url);
+        client.authorization_url = flow.authorization_url.clone();
+        client.refresh_url = flow.refresh_url.clone();
+        client.scopes = flow.scopes.clone();
+        Ok(client)
+    }
+
+    /// Authenticate the client with a secret
+    pub fn with_client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Request these scopes instead of the flow's
+    pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Listen for the authorization callback on this port instead of a free
+    /// one, for providers that need the redirect URI registered exactly
+    pub fn with_redirect_port(mut self, port: u16) -> Self {
+        self.redirect_port = port;
+        self
+    }
+
+    /// Use a preconfigured HTTP client
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Keep the token as secret `name` in `store`, as JSON, and start from
+    /// the token stored there
+    pub fn with_store(mut self, store: Arc<dyn SecretWriter>, name: impl Into<String>) -> Self {
+        self.store = Some((store, name.into()));
+        self
+    }
+
+    /// Get the grant tokens are obtained with
+    pub fn grant(&self) -> OAuth2Grant {
+        self.grant
+    }
+
+    /// Get the current token, from the store if none was obtained yet.
+    /// `None` means the authorization code grant needs
+    /// [`authorize`](Self::authorize) first.
+    pub async fn token(&self) -> Option<OAuth2Token> {
+        let mut current = self.token.lock().await;
+        if current.is_none() {
+            *current = self.load();
+        }
+        current.clone()
+    }
+
+    /// Get an access token, refreshing or fetching a new one if the
+    /// current one is missing or about to expire
+    pub async fn access_token(&self) -> Result<String> {
+        let mut current = self.token.lock().await;
+        if current.is_none() {
+            *current = self.load();
+        }
+        if let Some(token) = current
+            .as_ref()
+            .filter(|t| !t.expires_within(EXPIRY_MARGIN))
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let refresh_token = current.as_ref().and_then(|t| t.refresh_token.clone());
+        let token = match (refresh_token, self.grant) {
+            (Some(refresh_token), OAuth2Grant::AuthorizationCode) => {
+                self.refresh(&refresh_token).await?
+            }
+            (Some(refresh_token), OAuth2Grant::ClientCredentials) => {
+                match self.refresh(&refresh_token).await {
+                    Ok(token) => token,
+                    Err(_) => self.request_client_credentials().await?,
+                }
+            }
+            (None, OAuth2Grant::ClientCredentials) => self.request_client_credentials().await?,
+            (None, OAuth2Grant::AuthorizationCode) => {
+                return Err(Error::ExecutionFailed(
+                    "OAuth2 authorization required: no valid token or refresh token".to_string(),
+                ))
+            }
+        };
+        self.save(&token)?;
+        let access_token = token.access_token.clone();
+        *current = Some(token);
+        Ok(access_token)
+    }
+
+    /// Treat the current token as expired, e.g. after the server rejected it,
+    /// so the next [`access_token`](Self::access_token) renews it
+    pub async fn invalidate(&self) {
+        if let Some(token) = self.token.lock().await.as_mut() {
+            token.expires_at = Some(0);
+        }
+    }
+
+    /// Run the authorization code grant: start a callback server on
+    /// `127.0.0.1`, pass the URL the user must open to `open` (print it or
+    /// launch a browser), and exchange the code sent to the callback for a
+    /// token, which is stored and returned.
+    ///
+    /// Waits for the callback as long as it takes; wrap the call in
+    /// [`tokio::time::timeout`] to give up.
+    pub async fn authorize(&self, open: impl FnOnce(&str)) -> Result<OAuth2Token> {
+        let authorization_url = self.authorization_url.as_deref().ok_or_else(|| {
+            Error::InvalidConfig("OAuth2 client has no authorization URL".to_string())
+        })?;
+        let listener = TcpListener::bind(("127.0.0.1", self.redirect_port))
+            .await
+            .map_err(|e| {
+                Error::ExecutionFailed(format!("Failed to start callback server: {}", e))
+            })?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| Error::ExecutionFailed(e.to_string()))?
+            .port();
+        let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+
+        let state = random_token(1);
+        let verifier = random_token(2);
+        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(Sha256::digest(verifier.as_bytes()));
+        let mut url = reqwest::Url::parse(authorization_url)
+            .map_err(|e| Error::InvalidConfig(format!("Invalid authorization URL: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+        if !self.scopes.is_empty() {
+            url.query_pairs_mut()
+                .append_pair("scope", &self.scopes.join(" "));
+        }
+        open(url.as_str());
+
+        let code = wait_for_code(&listener, &state).await?;
+        let token = self
+            .request_token(
+                &self.token_url,
+                &[
+                    ("grant_type", "authorization_code"),
+                    ("code", &code),
+                    ("redirect_uri", &redirect_uri),
+                    ("code_verifier", &verifier),
+                ],
+                None,
+            )
+            .await?;
+        self.save(&token)?;
+        *self.token.lock().await = Some(token.clone());
+        Ok(token)
+    }
+
+    async fn request_client_credentials(&self) -> Result<OAuth2Token> {
+        let scope = self.scopes.join(" ");
+        let mut form = vec![("grant_type", "client_credentials")];
+        if !scope.is_empty() {
+            form.push(("scope", &scope));
+        }
+        self.request_token(&self.token_url, &form, None).await
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<OAuth2Token> {
+        let url = self.refresh_url.as_deref().unwrap_or(&self.token_url);
+        let form = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+        self.request_token(url, &form, Some(refresh_token)).await
+    }
+
+    /// Post a token request; `refresh_token` is kept when the response
+    /// doesn't carry a new one
+    async fn request_token(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+        refresh_token: Option<&str>,
+    ) -> Result<OAuth2Token> {
+        let mut form = form.to_vec();
+        let mut request = self.http.post(url);
+        match &self.client_secret {
+            Some(secret) => request = request.basic_auth(&self.client_id, Some(secret)),
+            None => form.push(("client_id", &self.client_id)),
+        }
+        let response = request
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("OAuth2 token request failed: {}", e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to read token response: {}", e)))?;
+        if !status.is_success() {
+            return Err(Error::ExecutionFailed(format!(
+                "OAuth2 token request failed: HTTP {}: {}",
+                status, text
+            )));
+        }
+
+        let response: TokenResponse = serde_json::from_str(&text)?;
+        Ok(OAuth2Token {
+            access_token: response.access_token,
+            token_type: response.token_type.unwrap_or_else(default_token_type),
+            refresh_token: response
+                .refresh_token
+                .or_else(|| refresh_token.map(str::to_string)),
+            expires_at: response
+                .expires_in
+                .map(|secs| unix_now().saturating_add(secs)),
+            scope: response.scope,
+        })
+    }
+
+    fn load(&self) -> Option<OAuth2Token> {
+        let (store, name) = self.store.as_ref()?;
+        serde_json::from_str(&store.resolve(name)?).ok()
+    }
+
+    fn save(&self, token: &OAuth2Token) -> Result<()> {
+        match &self.store {
+            Some((store, name)) => store.write(name, &serde_json::to_string(token)?),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Accept connections until one reaches `/callback` with our `state`,
+/// answer it, and return the authorization code it carries.
+///
+/// Each connection is answered by a task of its own, so a client that
+/// connects and sends nothing can't hold up the browser's callback.
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String> {
+    let mut callbacks = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (socket, _) = accepted.map_err(|e| {
+                    Error::ExecutionFailed(format!("Callback server failed: {}", e))
+                })?;
+                callbacks.spawn(answer_callback(socket, state.to_string()));
+            }
+            Some(Ok(Some(outcome))) = callbacks.join_next() => {
+                return outcome.map_err(|e| Error::ExecutionFailed(format!("OAuth2 {}", e)));
+            }
+        }
+    }
+}
+
+/// Answer one connection to the callback server. Returns `None` unless it
+/// is the callback for `state`, since other requests, stale browser tabs
+/// included, must not end the authorization.
+async fn answer_callback(
+    mut socket: TcpStream,
+    state: String,
+) -> Option<std::result::Result<String, String>> {
+    let mut request = Vec::new();
+    let read = async {
+        let mut buf = [0u8; 4096];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 16 * 1024 {
+            match socket.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+    };
+    tokio::time::timeout(CALLBACK_READ_TIMEOUT, read)
+        .await
+        .ok()?;
+    let request = String::from_utf8_lossy(&request);
+    let target = request.split_whitespace().nth(1).unwrap_or("/");
+    let url = reqwest::Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+    if url.path() != "/callback" {
+        let _ = socket
+            .write_all(http_response("404 Not Found", "Not found").as_bytes())
+            .await;
+        return None;
+    }
+
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+    if param("state").as_deref() != Some(state.as_str()) {
+        let page = http_response(
+            "400 Bad Request",
+            "Authorization failed: state mismatch in authorization callback",
+        );
+        let _ = socket.write_all(page.as_bytes()).await;
+        return None;
+    }
+    let outcome = match (param("error"), param("code")) {
+        (Some(error), _) => Err(format!("authorization denied: {}", error)),
+        (None, Some(code)) => Ok(code),
+        (None, None) => Err("authorization callback has no code".to_string()),
+    };
+    let page = match &outcome {
+        Ok(_) => http_response(
+            "200 OK",
+            "Authorization complete. You can close this window.",
+        ),
+        Err(error) => http_response(
+            "400 Bad Request",
+            &format!("Authorization failed: {}", error),
+        ),
+    };
+    let _ = socket.write_all(page.as_bytes()).await;
+    Some(outcome)
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Random URL-safe string of 32 hex digits per part
+fn random_token(parts: usize) -> String {
+    (0..parts)
+        .map(|_| uuid::Uuid::new_v4().simple().to_string())
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AdapterGenerator;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+    use thulp_core::{ToolCall, Transport};
+
+    /// Serve one response per connection, in order, and return the raw
+    /// requests that were received
+    async fn serve(
+        responses: Vec<(&'static str, String)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let content_length = text
+                            .lines()
+                            .find_map(|l| {
+                                l.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= header_end + 4 + content_length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                requests.push(String::from_utf8_lossy(&request).to_string());
+            }
+            requests
+        });
+
+        (base_url, handle)
+    }
+
+    fn token(access_token: &str, extra: Value) -> (&'static str, String) {
+        let mut body = json!({"access_token": access_token, "token_type": "bearer"});
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        ("200 OK", body.to_string())
+    }
+
+    #[test]
+    fn test_flows_from_spec() {
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": {},
+            "components": {"securitySchemes": {"oauth": {
+                "type": "oauth2",
+                "flows": {
+                    "authorizationCode": {
+                        "authorizationUrl": "https://auth.example.com/authorize",
+                        "tokenUrl": "https://auth.example.com/token",
+                        "scopes": {"write": "Write", "read": "Read"}
+                    },
+                    "implicit": {"authorizationUrl": "https://auth.example.com/authorize", "scopes": {}}
+                }
+            }}}
+        });
+        let auth = AdapterGenerator::new(spec, None)
+            .extract_auth_config()
+            .remove(0);
+        let flow = auth
+            .flows
+            .as_ref()
+            .unwrap()
+            .authorization_code
+            .clone()
+            .unwrap();
+        assert_eq!(flow.scopes, vec!["read", "write"]);
+        assert!(auth.flows.as_ref().unwrap().client_credentials.is_none());
+
+        let client = OAuth2Client::from_auth(&auth, "cli").unwrap();
+        assert_eq!(client.grant(), OAuth2Grant::AuthorizationCode);
+        assert_eq!(client.token_url, "https://auth.example.com/token");
+
+        let swagger = json!({
+            "swagger": "2.0",
+            "paths": {},
+            "securityDefinitions": {"oauth": {
+                "type": "oauth2",
+                "flow": "application",
+                "tokenUrl": "https://auth.example.com/token",
+                "scopes": {}
+            }}
+        });
+        let auth = AdapterGenerator::new(swagger, None)
+            .extract_auth_config()
+            .remove(0);
+        let client = OAuth2Client::from_auth(&auth, "svc").unwrap();
+        assert_eq!(client.grant(), OAuth2Grant::ClientCredentials);
+
+        let api_key = AuthConfig {
+            auth_type: "apiKey".to_string(),
+            scheme: None,
+            name: None,
+            location: None,
+            flows: None,
+        };
+        assert!(matches!(
+            OAuth2Client::from_auth(&api_key, "x"),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_are_cached_and_stored() {
+        let (base_url, server) = serve(vec![token("t1", json!({"expires_in": 3600}))]).await;
+        let store = Arc::new(RwLock::new(HashMap::new()));
+        let client =
+            OAuth2Client::client_credentials(format!("{}/token", base_url), "svc", "s3cret")
+                .with_scopes(["read"])
+                .with_store(store.clone(), "api_oauth");
+
+        assert_eq!(client.access_token().await.unwrap(), "t1");
+        assert_eq!(client.access_token().await.unwrap(), "t1");
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("POST /token "));
+        assert!(requests[0].contains("grant_type=client_credentials&scope=read"));
+        // svc:s3cret
+        assert!(requests[0]
+            .to_lowercase()
+            .contains("authorization: basic c3zjonmzy3jlda=="));
+
+        let stored: OAuth2Token =
+            serde_json::from_str(&store.read().unwrap()["api_oauth"]).unwrap();
+        assert_eq!(stored.access_token, "t1");
+        assert!(!stored.expires_within(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        let (base_url, server) = serve(vec![token("t2", json!({"expires_in": 60}))]).await;
+        let expired = OAuth2Token {
+            access_token: "t1".to_string(),
+            token_type: "Bearer".to_string(),
+            refresh_token: Some("r1".to_string()),
+            expires_at: Some(unix_now() - 10),
+            scope: None,
+        };
+        let store = Arc::new(RwLock::new(HashMap::from([(
+            "oauth".to_string(),
+            serde_json::to_string(&expired).unwrap(),
+        )])));
+        let client = OAuth2Client::authorization_code(
+            "https://auth.example.com/authorize",
+            format!("{}/token", base_url),
+            "cli",
+        )
+        .with_store(store.clone(), "oauth");
+
+        assert_eq!(client.token().await.unwrap().access_token, "t1");
+        assert_eq!(client.access_token().await.unwrap(), "t2");
+        let requests = server.await.unwrap();
+        assert!(requests[0].contains("grant_type=refresh_token&refresh_token=r1&client_id=cli"));
+
+        let token = client.token().await.unwrap();
+        assert_eq!(token.refresh_token.as_deref(), Some("r1"));
+
+        let unauthorized = OAuth2Client::authorization_code("https://a", "https://t", "cli");
+        assert!(unauthorized.access_token().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_with_callback() {
+        let (base_url, server) = serve(vec![token("t1", json!({"refresh_token": "r1"}))]).await;
+        let client = OAuth2Client::authorization_code(
+            "https://auth.example.com/authorize?audience=api",
+            format!("{}/token", base_url),
+            "cli",
+        )
+        .with_scopes(["repo"]);
+
+        let (url_tx, url_rx) = tokio::sync::oneshot::channel::<String>();
+        let browser = tokio::spawn(async move {
+            let url = reqwest::Url::parse(&url_rx.await.unwrap()).unwrap();
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            assert_eq!(params["audience"], "api");
+            assert_eq!(params["code_challenge_method"], "S256");
+            assert_eq!(params["scope"], "repo");
+            let redirect = &params["redirect_uri"];
+
+            // A connection that never sends a request doesn't block the others
+            let addr = reqwest::Url::parse(redirect).unwrap();
+            let addr = format!("127.0.0.1:{}", addr.port().unwrap());
+            let _idle = tokio::net::TcpStream::connect(addr).await.unwrap();
+
+            let http = reqwest::Client::new();
+            let missing = http
+                .get(redirect.replace("/callback", "/favicon.ico"))
+                .send();
+            assert_eq!(missing.await.unwrap().status(), 404);
+            // Nor does a callback for another authorization end this one
+            let stale = format!("{}?code=old&state=other", redirect);
+            assert_eq!(http.get(stale).send().await.unwrap().status(), 400);
+            let callback = format!("{}?code=c0de&state={}", redirect, params["state"]);
+            http.get(callback).send().await.unwrap().status()
+        });
+
+        let token = client
+            .authorize(|url| url_tx.send(url.to_string()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "t1");
+        assert_eq!(browser.await.unwrap(), 200);
+        assert_eq!(client.access_token().await.unwrap(), "t1");
+
+        let request = &server.await.unwrap()[0];
+        assert!(request.contains("grant_type=authorization_code&code=c0de"));
+        assert!(request.contains("code_verifier="));
+    }
+
+    #[tokio::test]
+    async fn test_http_adapter_sends_and_renews_token() {
+        let (base_url, server) = serve(vec![
+            token("t1", json!({})),
+            ("401 Unauthorized", r#"{"message": "expired"}"#.to_string()),
+            token("t2", json!({})),
+            ("200 OK", r#"{"ok": true}"#.to_string()),
+        ])
+        .await;
+        let spec = json!({
+            "openapi": "3.0.0",
+            "servers": [{"url": base_url}],
+            "paths": {"/items": {"get": {"operationId": "listItems"}}}
+        });
+        let oauth2 = OAuth2Client::client_credentials(format!("{}/token", base_url), "svc", "s");
+        let mut adapter = AdapterGenerator::new(spec, None)
+            .http_adapter()
+            .unwrap()
+            .with_oauth2(Arc::new(oauth2));
+        adapter.connect().await.unwrap();
+
+        let result = adapter.call(&ToolCall::new("listItems")).await.unwrap();
+        assert_eq!(result.data, Some(json!({"ok": true})));
+
+        let requests = server.await.unwrap();
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("authorization: bearer t1"));
+        assert!(requests[3]
+            .to_lowercase()
+            .contains("authorization: bearer t2"));
+    }
+}
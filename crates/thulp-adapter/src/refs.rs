@@ -0,0 +1,196 @@
+//! Local `$ref` resolution for OpenAPI documents.
+//!
+//! Real-world specs define most schemas once under `components/schemas`
+//! (OpenAPI 3) or `definitions` (Swagger 2) and point at them with `$ref`.
+//! [`RefResolver`] inlines those pointers so parameter types, descriptions
+//! and enums can be read directly from the resolved schema.
+//!
+//! Only local references (`#/...`) are followed; external references are
+//! left in place. Recursive schemas are expanded until a reference repeats,
+//! at which point the repeated node is replaced by a stub carrying the
+//! target's `type` and an `x-circular-ref` marker.
+
+use crate::Result;
+use serde_json::{Map, Value};
+
+/// Key added to the stub that replaces a circular reference.
+pub const CIRCULAR_REF_KEY: &str = "x-circular-ref";
+
+/// Resolves local `$ref` pointers against an OpenAPI document.
+#[derive(Debug, Clone, Copy)]
+pub struct RefResolver<'a> {
+    root: &'a Value,
+}
+
+impl<'a> RefResolver<'a> {
+    /// Create a resolver for the given document.
+    pub fn new(root: &'a Value) -> Self {
+        Self { root }
+    }
+
+    /// Look up the target of a local reference such as
+    /// `#/components/schemas/User`, without resolving nested references.
+    pub fn lookup(&self, reference: &str) -> Result<&'a Value> {
+        let pointer = reference
+            .strip_prefix('#')
+            .ok_or_else(|| format!("Only local references are supported: {}", reference))?;
+        self.root
+            .pointer(pointer)
+            .ok_or_else(|| format!("Unresolved $ref: {}", reference).into())
+    }
+
+    /// Return a copy of `value` with every local `$ref` inlined.
+    ///
+    /// Keys next to a `$ref` (such as `description`) override the
+    /// corresponding keys of the referenced schema.
+    pub fn resolve(&self, value: &Value) -> Result<Value> {
+        self.resolve_with(value, &mut Vec::new())
+    }
+
+    fn resolve_with(&self, value: &Value, stack: &mut Vec<String>) -> Result<Value> {
+        match value {
+            Value::Object(obj) => match obj.get("$ref").and_then(Value::as_str) {
+                Some(reference) if reference.starts_with('#') => {
+                    self.resolve_ref(reference, obj, stack)
+                }
+                _ => {
+                    let mut resolved = Map::with_capacity(obj.len());
+                    for (key, child) in obj {
+                        resolved.insert(key.clone(), self.resolve_with(child, stack)?);
+                    }
+                    Ok(Value::Object(resolved))
+                }
+            },
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve_with(item, stack))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_ref(
+        &self,
+        reference: &str,
+        node: &Map<String, Value>,
+        stack: &mut Vec<String>,
+    ) -> Result<Value> {
+        let target = self.lookup(reference)?;
+
+        let mut resolved = if stack.iter().any(|r| r == reference) {
+            let mut stub = Map::new();
+            if let Some(ty) = target.get("type") {
+                stub.insert("type".to_string(), ty.clone());
+            }
+            stub.insert(
+                CIRCULAR_REF_KEY.to_string(),
+                Value::String(reference.to_string()),
+            );
+            Value::Object(stub)
+        } else {
+            stack.push(reference.to_string());
+            let resolved = self.resolve_with(target, stack);
+            stack.pop();
+            resolved?
+        };
+
+        if let Value::Object(resolved_obj) = &mut resolved {
+            for (key, sibling) in node {
+                if key != "$ref" {
+                    resolved_obj.insert(key.clone(), self.resolve_with(sibling, stack)?);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_nested_refs() {
+        let spec = json!({
+            "components": {
+                "schemas": {
+                    "User": {
+                        "type": "object",
+                        "properties": {
+                            "address": {"$ref": "#/components/schemas/Address"}
+                        }
+                    },
+                    "Address": {"type": "object", "properties": {"city": {"type": "string"}}}
+                }
+            }
+        });
+
+        let resolved = RefResolver::new(&spec)
+            .resolve(&json!({"$ref": "#/components/schemas/User"}))
+            .unwrap();
+        assert_eq!(
+            resolved["properties"]["address"]["properties"]["city"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn test_swagger_definitions_and_sibling_override() {
+        let spec = json!({
+            "definitions": {"Status": {"type": "string", "description": "Status", "enum": ["a", "b"]}}
+        });
+
+        let resolved = RefResolver::new(&spec)
+            .resolve(&json!({"$ref": "#/definitions/Status", "description": "Order status"}))
+            .unwrap();
+        assert_eq!(resolved["type"], "string");
+        assert_eq!(resolved["description"], "Order status");
+        assert_eq!(resolved["enum"], json!(["a", "b"]));
+    }
+
+    #[test]
+    fn test_recursive_refs_are_cut() {
+        let spec = json!({
+            "components": {
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "properties": {
+                            "children": {"type": "array", "items": {"$ref": "#/components/schemas/Node"}}
+                        }
+                    }
+                }
+            }
+        });
+
+        let resolved = RefResolver::new(&spec)
+            .resolve(&json!({"$ref": "#/components/schemas/Node"}))
+            .unwrap();
+        let items = &resolved["properties"]["children"]["items"];
+        assert_eq!(items["type"], "object");
+        assert_eq!(items[CIRCULAR_REF_KEY], "#/components/schemas/Node");
+    }
+
+    #[test]
+    fn test_missing_and_external_refs() {
+        let spec = json!({"components": {"schemas": {}}});
+        let resolver = RefResolver::new(&spec);
+
+        assert!(resolver
+            .resolve(&json!({"$ref": "#/components/schemas/Missing"}))
+            .is_err());
+
+        let external = json!({"$ref": "other.yaml#/Pet"});
+        assert_eq!(resolver.resolve(&external).unwrap(), external);
+
+        let escaped = json!({"paths": {"/users/{id}": {"type": "string"}}});
+        assert_eq!(
+            RefResolver::new(&escaped)
+                .lookup("#/paths/~1users~1{id}")
+                .unwrap()["type"],
+            "string"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Cookie storage for [`WebClient`](crate::WebClient).
+//!
+//! A [`CookieJar`] records the cookies servers set and sends them back on
+//! later requests, so a client that logged in once stays logged in. Jars are
+//! cheap to clone and clones share their cookies, which lets several clients
+//! use the same authenticated session.
+//!
+//! Jars can be saved to and restored from JSON, by default at
+//! `.thulp/cache/cookies.json` in a workspace. Session cookies are saved too,
+//! since login cookies often have no expiry; expired cookies are dropped.
+//!
+//! ```rust,no_run
+//! use thulp_browser::{CookieJar, WebClient, WebClientConfig};
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let jar = CookieJar::open(CookieJar::workspace_path("."))?;
+//! let client = WebClient::with_config(WebClientConfig::new().cookie_jar(jar.clone()))?;
+//!
+//! client.fetch("https://example.com/login?token=abc").await?;
+//! let account = client.fetch("https://example.com/account").await?;
+//! jar.save()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{BrowserError, Result};
+use cookie_store::CookieStore;
+use reqwest::Url;
+use reqwest_cookie_store::CookieStoreMutex;
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Cookies shared between requests and clients, optionally backed by a file.
+#[derive(Clone, Default)]
+pub struct CookieJar {
+    store: Arc<CookieStoreMutex>,
+    path: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Cookie values are credentials; only show how many there are
+        f.debug_struct("CookieJar")
+            .field("cookies", &self.len())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl CookieJar {
+    /// Create an empty in-memory jar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default cookie file of a workspace: `{root}/.thulp/cache/cookies.json`.
+    pub fn workspace_path(root: impl AsRef<Path>) -> PathBuf {
+        root.as_ref()
+            .join(".thulp")
+            .join("cache")
+            .join("cookies.json")
+    }
+
+    /// Open a jar backed by a file, loading any cookies saved there.
+    ///
+    /// A missing file gives an empty jar. [`save`](Self::save) writes back to
+    /// the same file, and clients using the jar save it whenever a response
+    /// sets cookies.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let store = match File::open(&path) {
+            Ok(file) => cookie_store::serde::json::load(BufReader::new(file))
+                .map_err(|e| cookie_error(&path, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CookieStore::default(),
+            Err(e) => return Err(cookie_error(&path, e)),
+        };
+        Ok(Self {
+            store: Arc::new(CookieStoreMutex::new(store)),
+            path: Some(path),
+        })
+    }
+
+    /// File the jar was opened from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Save the jar to the file it was opened from.
+    ///
+    /// Does nothing for an in-memory jar.
+    pub fn save(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => self.save_to(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Save unexpired cookies, session cookies included, to a file.
+    ///
+    /// Parent directories are created as needed.
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let unexpired = {
+            let store = self.lock();
+            CookieStore::from_cookies(
+                store
+                    .iter_unexpired()
+                    .map(|cookie| Ok::<_, Infallible>(cookie.clone())),
+                false,
+            )
+            .unwrap_or_default()
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| cookie_error(path, e))?;
+        }
+        let mut writer = BufWriter::new(File::create(path).map_err(|e| cookie_error(path, e))?);
+        cookie_store::serde::json::save_incl_expired_and_nonpersistent(&unexpired, &mut writer)
+            .map_err(|e| cookie_error(path, e))
+    }
+
+    /// Add a cookie as a server would with a `Set-Cookie` header for `url`.
+    ///
+    /// Useful for sessions obtained outside the client, such as a token
+    /// copied from a browser. An already expired cookie removes any cookie
+    /// it matches, like a server logging a user out.
+    pub fn insert(&self, url: &str, set_cookie: &str) -> Result<()> {
+        let url = parse_url(url)?;
+        match self.lock().parse(set_cookie, &url) {
+            Ok(_) | Err(cookie_store::CookieError::Expired) => Ok(()),
+            Err(e) => Err(BrowserError::Cookie(format!(
+                "Invalid cookie '{}': {}",
+                set_cookie, e
+            ))),
+        }
+    }
+
+    /// Value of the named cookie that would be sent to `url`.
+    pub fn get(&self, url: &str, name: &str) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        self.lock()
+            .get_request_values(&url)
+            .find(|(cookie, _)| *cookie == name)
+            .map(|(_, value)| value.to_string())
+    }
+
+    /// Number of unexpired cookies.
+    pub fn len(&self) -> usize {
+        self.lock().iter_unexpired().count()
+    }
+
+    /// Check if the jar holds no unexpired cookies.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all cookies.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// The store handed to reqwest.
+    pub(crate) fn store(&self) -> Arc<CookieStoreMutex> {
+        self.store.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CookieStore> {
+        // A poisoned store is still a valid set of cookies
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url).map_err(|e| BrowserError::InvalidUrl(format!("{}: {}", url, e)))
+}
+
+fn cookie_error(path: &Path, error: impl std::fmt::Display) -> BrowserError {
+    BrowserError::Cookie(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get() {
+        let jar = CookieJar::new();
+        jar.insert("https://example.com/", "session=abc; Path=/")
+            .unwrap();
+        jar.insert("https://other.org/", "theme=dark").unwrap();
+
+        assert_eq!(jar.len(), 2);
+        assert_eq!(
+            jar.get("https://example.com/account", "session").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(jar.get("https://other.org/", "session"), None);
+
+        // Clones share cookies
+        let shared = jar.clone();
+        shared.clear();
+        assert!(jar.is_empty());
+        assert!(jar.insert("not a url", "a=b").is_err());
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = CookieJar::workspace_path(temp.path());
+
+        let jar = CookieJar::open(&path).unwrap();
+        assert!(jar.is_empty());
+        jar.insert("https://example.com/", "session=abc").unwrap();
+        jar.insert("https://example.com/", "old=value").unwrap();
+        jar.insert(
+            "https://example.com/",
+            "old=gone; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+        )
+        .unwrap();
+        jar.save().unwrap();
+        assert!(path.exists());
+
+        let restored = CookieJar::open(&path).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            restored.get("https://example.com/", "session").as_deref(),
+            Some("abc")
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            CookieJar::open(&path),
+            Err(BrowserError::Cookie(_))
+        ));
+    }
+}
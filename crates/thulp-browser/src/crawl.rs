@@ -0,0 +1,395 @@
+//! Crawling a site from a seed URL.
+//!
+//! A [`Crawler`] fetches the seed page, follows its links to pages on the
+//! same origin, and continues breadth-first until it reaches the maximum
+//! depth or page count. Each URL is fetched at most once, ignoring
+//! `#fragments`, and up to [`concurrency`](Crawler::concurrency) pages are
+//! fetched at a time. Rate limits configured on the [`WebClient`] apply to
+//! every request.
+//!
+//! Pages outside a set of [`languages`](Crawler::languages), or with less
+//! text than [`min_text_length`](Crawler::min_text_length), can be left out
+//! of the results so that navigation stubs, error pages and translations
+//! don't reach downstream skills. Their links are still followed.
+//!
+//! ```rust,no_run
+//! use futures::StreamExt;
+//! use thulp_browser::{Crawler, WebClient};
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let crawler = Crawler::new(WebClient::builder().build()?)
+//!     .max_depth(2)
+//!     .max_pages(50)
+//!     .languages(["en"])
+//!     .min_text_length(200);
+//!
+//! // Collect everything at once...
+//! let pages = crawler.crawl("https://example.com/docs/").await?;
+//!
+//! // ...or handle pages as they arrive
+//! let mut stream = crawler.crawl_stream("https://example.com/docs/")?;
+//! while let Some(result) = stream.next().await {
+//!     let crawled = result?;
+//!     println!("{} {:?}", crawled.depth, crawled.page.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::language::normalize_code;
+use crate::{BrowserError, Page, Result, WebClient};
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::Url;
+use std::collections::HashSet;
+use std::pin::Pin;
+
+/// Pages yielded by [`Crawler::crawl_stream`]
+pub type CrawlStream = Pin<Box<dyn Stream<Item = Result<CrawledPage>> + Send>>;
+
+/// A page fetched during a crawl
+#[derive(Debug, Clone)]
+pub struct CrawledPage {
+    /// The fetched page
+    pub page: Page,
+    /// Number of links followed from the seed; the seed itself is 0
+    pub depth: usize,
+}
+
+/// Breadth-first crawler over a single origin
+///
+/// Every page of a crawl, and of any clone of the crawler, is fetched with
+/// the one [`WebClient`], so connections are pooled across the whole run.
+#[derive(Debug, Clone)]
+pub struct Crawler {
+    client: WebClient,
+    max_depth: usize,
+    max_pages: usize,
+    concurrency: usize,
+    languages: Vec<String>,
+    min_text_length: usize,
+}
+
+impl Crawler {
+    /// Create a crawler with a depth of 2, at most 100 pages and 4 fetches
+    /// at a time
+    pub fn new(client: WebClient) -> Self {
+        Self {
+            client,
+            max_depth: 2,
+            max_pages: 100,
+            concurrency: 4,
+            languages: Vec::new(),
+            min_text_length: 0,
+        }
+    }
+
+    /// Get the client pages are fetched with
+    pub fn client(&self) -> &WebClient {
+        &self.client
+    }
+
+    /// Set how many links deep to follow from the seed
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the maximum number of pages to fetch, failed fetches included
+    pub fn max_pages(mut self, pages: usize) -> Self {
+        self.max_pages = pages;
+        self
+    }
+
+    /// Set how many pages are fetched at once
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Only yield pages detected to be in one of these languages
+    ///
+    /// Takes ISO 639-3 codes such as `eng`, ISO 639-1 codes such as `en`,
+    /// or language tags such as `en-US`. Pages whose language can't be
+    /// detected are left out too.
+    pub fn languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.languages = languages
+            .into_iter()
+            .map(|code| normalize_code(code.as_ref()))
+            .collect();
+        self
+    }
+
+    /// Only yield pages with at least this many characters of text,
+    /// counting runs of whitespace as one
+    pub fn min_text_length(mut self, chars: usize) -> Self {
+        self.min_text_length = chars;
+        self
+    }
+
+    /// Check whether a fetched page passes the language and length filters
+    fn accepts(&self, page: &Page) -> bool {
+        if self.languages.is_empty() && self.min_text_length == 0 {
+            return true;
+        }
+        let text = page.text();
+        if self.min_text_length > 0 {
+            let length = text
+                .split_whitespace()
+                .map(|word| word.chars().count() + 1)
+                .sum::<usize>()
+                .saturating_sub(1);
+            if length < self.min_text_length {
+                tracing::debug!(url = %page.url, length, "Skipping page with too little text");
+                return false;
+            }
+        }
+        if !self.languages.is_empty() {
+            let language = crate::detect_language(&text).map(|detected| detected.code);
+            if !language
+                .as_ref()
+                .is_some_and(|code| self.languages.contains(code))
+            {
+                tracing::debug!(url = %page.url, ?language, "Skipping page in another language");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Crawl from a seed URL and collect the pages fetched
+    ///
+    /// Pages that fail to load are logged and left out. Fails only if the
+    /// seed isn't a valid URL.
+    pub async fn crawl(&self, seed: &str) -> Result<Vec<CrawledPage>> {
+        let results: Vec<_> = self.crawl_stream(seed)?.collect().await;
+        Ok(results
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(crawled) => Some(crawled),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Skipping page that failed to load");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Crawl from a seed URL, yielding each page as soon as it is fetched
+    ///
+    /// Pages are yielded depth by depth, except those the language and text
+    /// length filters leave out. A page that fails to load is
+    /// yielded as an error and the crawl continues. Dropping the stream
+    /// stops the crawl.
+    pub fn crawl_stream(&self, seed: &str) -> Result<CrawlStream> {
+        let seed = parse_http_url(seed)?;
+        let (tx, rx) = tokio::sync::mpsc::channel(self.concurrency);
+        let crawler = self.clone();
+
+        tokio::spawn(async move {
+            let origin = seed.origin().ascii_serialization();
+            let mut seen = HashSet::from([seed.to_string()]);
+            let mut frontier = vec![seed];
+            let mut fetched = 0;
+
+            for depth in 0..=crawler.max_depth {
+                frontier.truncate(crawler.max_pages.saturating_sub(fetched));
+                if frontier.is_empty() {
+                    break;
+                }
+                fetched += frontier.len();
+
+                let client = &crawler.client;
+                let mut pages = stream::iter(std::mem::take(&mut frontier))
+                    .map(|url| async move { client.fetch(url.as_str()).await })
+                    .buffered(crawler.concurrency);
+
+                while let Some(result) = pages.next().await {
+                    if let Ok(page) = &result {
+                        if depth < crawler.max_depth {
+                            for link in same_origin_links(page, &origin) {
+                                if seen.insert(link.to_string()) {
+                                    frontier.push(link);
+                                }
+                            }
+                        }
+                        if !crawler.accepts(page) {
+                            continue;
+                        }
+                    }
+                    let result = result.map(|page| CrawledPage { page, depth });
+                    if tx.send(result).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        })))
+    }
+}
+
+/// Parse a URL, accepting only http and https, without its fragment
+fn parse_http_url(url: &str) -> Result<Url> {
+    let mut parsed =
+        Url::parse(url).map_err(|e| BrowserError::InvalidUrl(format!("{}: {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(BrowserError::InvalidUrl(format!(
+            "{}: only http and https can be crawled",
+            url
+        )));
+    }
+    parsed.set_fragment(None);
+    Ok(parsed)
+}
+
+/// Links on a page that lead to the same origin
+fn same_origin_links(page: &Page, origin: &str) -> Vec<Url> {
+    page.links()
+        .iter()
+        .filter_map(|link| parse_http_url(link).ok())
+        .filter(|url| url.origin().ascii_serialization() == origin)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, Route};
+
+    async fn site() -> String {
+        serve(vec![
+            (
+                "/",
+                Route::html(
+                    r##"<title>Home</title>
+                    <a href="/a">A</a> <a href="b">B</a> <a href="/a#top">A again</a>
+                    <a href="https://elsewhere.example/">Away</a> <a href="mailto:x@y.z">Mail</a>"##,
+                ),
+            ),
+            ("/a", Route::html(r#"<title>A</title><a href="/a/deep">Deep</a>"#)),
+            ("/b", Route::html(r#"<title>B</title><a href="/">Home</a><a href="/missing">Gone</a>"#)),
+            ("/a/deep", Route::html("<title>Deep</title>")),
+        ])
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_crawl_depth_and_dedup() {
+        let base = site().await;
+
+        let pages = Crawler::new(WebClient::new())
+            .max_depth(1)
+            .crawl(&format!("{}/", base))
+            .await
+            .unwrap();
+        let titles: Vec<_> = pages
+            .iter()
+            .map(|c| (c.depth, c.page.title.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (0, "Home".to_string()),
+                (1, "A".to_string()),
+                (1, "B".to_string())
+            ]
+        );
+
+        // Depth 2 reaches the deep page and the broken link, which loads
+        // as a 404 page
+        let results: Vec<_> = Crawler::new(WebClient::new())
+            .crawl_stream(&format!("{}/", base))
+            .unwrap()
+            .collect()
+            .await;
+        let mut statuses: Vec<_> = results
+            .into_iter()
+            .map(|r| {
+                let crawled = r.unwrap();
+                (crawled.depth, crawled.page.status)
+            })
+            .collect();
+        statuses.sort();
+        assert_eq!(
+            statuses,
+            vec![(0, 200), (1, 200), (1, 200), (2, 200), (2, 404)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crawl_page_limit() {
+        let base = site().await;
+        let pages = Crawler::new(WebClient::new())
+            .max_pages(2)
+            .concurrency(1)
+            .crawl(&format!("{}/", base))
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_language_and_length_filters() {
+        let base = serve(vec![
+            (
+                "/",
+                Route::html(
+                    r#"<title>Home</title><a href="/en">EN</a> <a href="/de">DE</a>
+                    <a href="/stub">Stub</a>"#,
+                ),
+            ),
+            (
+                "/en",
+                Route::html(
+                    "<title>EN</title><p>The quick brown fox jumps over the lazy dog \
+                     while the cat sleeps on the warm sofa by the window.</p>",
+                ),
+            ),
+            (
+                "/de",
+                Route::html(
+                    "<title>DE</title><p>Der schnelle braune Fuchs springt über den \
+                     faulen Hund, während die Katze auf dem warmen Sofa schläft.</p>",
+                ),
+            ),
+            ("/stub", Route::html("<title>Stub</title><p>See also.</p>")),
+        ])
+        .await;
+
+        let titles = |pages: Vec<CrawledPage>| -> Vec<String> {
+            let mut titles: Vec<_> = pages.into_iter().filter_map(|c| c.page.title).collect();
+            titles.sort();
+            titles
+        };
+        let crawler = Crawler::new(WebClient::new()).max_depth(1);
+
+        // The seed is too short to keep, but its links are still followed
+        let pages = crawler
+            .clone()
+            .min_text_length(40)
+            .crawl(&format!("{}/", base))
+            .await
+            .unwrap();
+        assert_eq!(titles(pages), ["DE", "EN"]);
+
+        let pages = crawler
+            .languages(["en-GB"])
+            .crawl(&format!("{}/", base))
+            .await
+            .unwrap();
+        assert_eq!(titles(pages), ["EN"]);
+    }
+
+    #[test]
+    fn test_rejects_non_http_seed() {
+        let crawler = Crawler::new(WebClient::new());
+        assert!(crawler.crawl_stream("ftp://example.com").is_err());
+        assert!(crawler.crawl_stream("not a url").is_err());
+    }
+}
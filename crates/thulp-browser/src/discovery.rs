@@ -0,0 +1,538 @@
+//! Parsing of sitemaps and RSS/Atom feeds.
+//!
+//! Sites list their pages in `sitemap.xml` and their latest posts in feeds.
+//! [`Sitemap`] and [`Feed`] read both into [`FeedEntry`] lists that can seed a
+//! [`Crawler`](crate::Crawler) or be polled for new entries.
+//!
+//! Elements are matched by local name, so namespace prefixes don't matter.
+//! RSS 0.9x/2.0, RSS 1.0 (RDF) and Atom 1.0 feeds are recognised.
+//!
+//! ```rust,no_run
+//! use thulp_browser::WebClient;
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let client = WebClient::new();
+//!
+//! let sitemap = client.fetch_sitemap("https://example.com/sitemap.xml").await?;
+//! let seeds: Vec<_> = sitemap.urls().collect();
+//!
+//! let feed = client.fetch_feed("https://example.com/feed.xml").await?;
+//! for entry in &feed.entries {
+//!     println!("{} {:?} {:?}", entry.url, entry.title, entry.published);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{BrowserError, Result};
+use roxmltree::{Document, Node, ParsingOptions};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A page listed in a sitemap or feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    /// URL of the page, as written in the document unless it was fetched
+    /// with [`WebClient`](crate::WebClient), which makes it absolute
+    pub url: String,
+
+    /// Entry title; sitemaps have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Publication date as written in the document: a feed's publication
+    /// (or last update) date, or a sitemap's `lastmod`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+impl FeedEntry {
+    /// Parse [`published`](Self::published) into a point in time
+    ///
+    /// Understands RFC 3339 and the shorter W3C forms used by sitemaps
+    /// (`2024-05-01`), and RFC 822 dates used by RSS. Returns `None` for
+    /// anything else.
+    pub fn published_at(&self) -> Option<SystemTime> {
+        let text = self.published.as_deref()?.trim();
+        let secs = parse_w3c_date(text).or_else(|| parse_rfc822_date(text))?;
+        Some(match u64::try_from(secs) {
+            Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()),
+        })
+    }
+}
+
+/// Contents of a `sitemap.xml` file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sitemap {
+    /// Pages listed by a `<urlset>`
+    pub entries: Vec<FeedEntry>,
+
+    /// Further sitemaps listed by a `<sitemapindex>`
+    pub sitemaps: Vec<FeedEntry>,
+}
+
+impl Sitemap {
+    /// Parse a sitemap or sitemap index
+    ///
+    /// Returns [`BrowserError::Parse`] if the text isn't XML or the root
+    /// element is neither `urlset` nor `sitemapindex`.
+    pub fn parse(xml: &str) -> Result<Self> {
+        let document = parse_xml(xml)?;
+        let root = document.root_element();
+        let (item, is_index) = match root.tag_name().name() {
+            "urlset" => ("url", false),
+            "sitemapindex" => ("sitemap", true),
+            other => {
+                return Err(BrowserError::Parse(format!(
+                    "expected a urlset or sitemapindex, found <{}>",
+                    other
+                )))
+            }
+        };
+
+        let entries = children(root, item)
+            .filter_map(|node| {
+                Some(FeedEntry {
+                    url: child_text(node, "loc")?,
+                    title: None,
+                    published: child_text(node, "lastmod"),
+                })
+            })
+            .collect();
+
+        Ok(if is_index {
+            Self {
+                entries: Vec::new(),
+                sitemaps: entries,
+            }
+        } else {
+            Self {
+                entries,
+                sitemaps: Vec::new(),
+            }
+        })
+    }
+
+    /// URLs of the listed pages
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.url.as_str())
+    }
+
+    /// Check if the sitemap is an index of other sitemaps
+    pub fn is_index(&self) -> bool {
+        !self.sitemaps.is_empty()
+    }
+}
+
+/// Syndication format of a [`Feed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedKind {
+    /// RSS 0.9x, 1.0 or 2.0
+    Rss,
+    /// Atom 1.0
+    Atom,
+}
+
+/// Contents of an RSS or Atom feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Feed {
+    /// Feed format
+    pub kind: FeedKind,
+
+    /// Feed title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Items in document order, usually newest first
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// Parse an RSS or Atom feed
+    ///
+    /// Items without a link are left out: an RSS item falls back to its
+    /// `guid` unless `isPermaLink="false"`, and an Atom entry uses its
+    /// `alternate` link, or the first link without a `rel`. Returns
+    /// [`BrowserError::Parse`] if the text isn't XML or not a feed.
+    pub fn parse(xml: &str) -> Result<Self> {
+        let document = parse_xml(xml)?;
+        let root = document.root_element();
+        match root.tag_name().name() {
+            "rss" | "RDF" => Ok(Self::parse_rss(root)),
+            "feed" => Ok(Self::parse_atom(root)),
+            other => Err(BrowserError::Parse(format!(
+                "expected an RSS or Atom feed, found <{}>",
+                other
+            ))),
+        }
+    }
+
+    fn parse_rss(root: Node) -> Self {
+        let channel = children(root, "channel").next();
+        // RSS 2.0 nests items in the channel; RSS 1.0 puts them beside it
+        let items = channel
+            .into_iter()
+            .flat_map(|channel| children(channel, "item"))
+            .chain(children(root, "item"));
+
+        let entries = items
+            .filter_map(|item| {
+                let url = child_text(item, "link").or_else(|| {
+                    let guid = children(item, "guid").next()?;
+                    if guid.attribute("isPermaLink") == Some("false") {
+                        return None;
+                    }
+                    node_text(guid)
+                })?;
+                Some(FeedEntry {
+                    url,
+                    title: child_text(item, "title"),
+                    // dc:date in RSS 1.0
+                    published: child_text(item, "pubDate").or_else(|| child_text(item, "date")),
+                })
+            })
+            .collect();
+
+        Self {
+            kind: FeedKind::Rss,
+            title: channel.and_then(|channel| child_text(channel, "title")),
+            entries,
+        }
+    }
+
+    fn parse_atom(root: Node) -> Self {
+        let entries = children(root, "entry")
+            .filter_map(|entry| {
+                let links: Vec<_> = children(entry, "link").collect();
+                let url = links
+                    .iter()
+                    .find(|link| link.attribute("rel") == Some("alternate"))
+                    .or_else(|| links.iter().find(|link| link.attribute("rel").is_none()))
+                    .and_then(|link| link.attribute("href"))?
+                    .trim()
+                    .to_string();
+                Some(FeedEntry {
+                    url,
+                    title: child_text(entry, "title"),
+                    published: child_text(entry, "published")
+                        .or_else(|| child_text(entry, "updated")),
+                })
+            })
+            .collect();
+
+        Self {
+            kind: FeedKind::Atom,
+            title: child_text(root, "title"),
+            entries,
+        }
+    }
+}
+
+fn parse_xml(xml: &str) -> Result<Document<'_>> {
+    // Old RSS files often declare a DOCTYPE
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(xml, options).map_err(|e| BrowserError::Parse(e.to_string()))
+}
+
+/// Child elements with a local name
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Trimmed text of the first child element with a local name, if not empty
+fn child_text(node: Node, name: &str) -> Option<String> {
+    children(node, name).next().and_then(node_text)
+}
+
+/// Trimmed text content of an element, CDATA included, if not empty
+fn node_text(node: Node) -> Option<String> {
+    let text: String = node
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Days from 1970-01-01 to a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    const DAYS_IN_MONTH: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1..=12).contains(&month) || day == 0 || day > DAYS_IN_MONTH[month as usize - 1] {
+        return None;
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    if month == 2 && day == 29 && !leap {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Seconds since the epoch for `hh:mm[:ss[.fff]]`, ignoring fractions
+fn parse_clock(text: &str) -> Option<i64> {
+    let mut parts = text.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds: i64 = match parts.next() {
+        Some(seconds) => seconds.split('.').next()?.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// `YYYY[-MM[-DD[Thh:mm[:ss[.fff]](Z|±hh:mm)]]]`, as seconds since the epoch
+fn parse_w3c_date(text: &str) -> Option<i64> {
+    let (date, time) = match text.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut parts = date.split('-');
+    let year_text = parts.next()?;
+    if year_text.len() != 4 {
+        return None;
+    }
+    let year: i64 = year_text.parse().ok()?;
+    let month: u32 = parts.next().map_or(Some(1), |m| m.parse().ok())?;
+    let day: u32 = parts.next().map_or(Some(1), |d| d.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let days = days_from_civil(year, month, day)?;
+
+    let Some(time) = time else {
+        return Some(days * 86_400);
+    };
+    let (clock, offset) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(split) = time.rfind(['+', '-']) {
+        let (clock, zone) = time.split_at(split);
+        let sign = if zone.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = zone[1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, sign * offset)
+    } else {
+        // No zone designator: read as UTC
+        (time, 0)
+    };
+    Some(days * 86_400 + parse_clock(clock)? - offset)
+}
+
+/// `[Day, ]DD Mon YYYY hh:mm[:ss] zone`, as seconds since the epoch
+fn parse_rfc822_date(text: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let mut parts = text.split_whitespace().peekable();
+    if parts.peek()?.ends_with(',') {
+        parts.next();
+    }
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month_text = parts.next()?.to_ascii_lowercase();
+    let month = MONTHS.iter().position(|m| month_text.starts_with(m))? as u32 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    // Two-digit years, as RFC 2822 reads them
+    let year = match year {
+        0..=49 => year + 2000,
+        50..=999 => year + 1900,
+        _ => year,
+    };
+    let clock = parse_clock(parts.next()?)?;
+    let offset = match parts.next().unwrap_or("GMT") {
+        "GMT" | "UT" | "UTC" | "Z" => 0,
+        "EST" => -5 * 3600,
+        "EDT" => -4 * 3600,
+        "CST" => -6 * 3600,
+        "CDT" => -5 * 3600,
+        "MST" => -7 * 3600,
+        "MDT" => -6 * 3600,
+        "PST" => -8 * 3600,
+        "PDT" => -7 * 3600,
+        zone => {
+            let sign = match zone.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let digits = &zone[1..];
+            if digits.len() != 4 {
+                return None;
+            }
+            let hours: i64 = digits[..2].parse().ok()?;
+            let minutes: i64 = digits[2..].parse().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+    Some(days_from_civil(year, month, day)? * 86_400 + clock - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    fn entry(published: &str) -> FeedEntry {
+        FeedEntry {
+            url: "https://example.com".to_string(),
+            title: None,
+            published: Some(published.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_sitemap_and_index() {
+        let sitemap = Sitemap::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc> https://example.com/ </loc><lastmod>2024-05-01</lastmod></url>
+              <url><loc>https://example.com/docs</loc><changefreq>daily</changefreq></url>
+              <url><lastmod>2024-05-01</lastmod></url>
+            </urlset>"#,
+        )
+        .unwrap();
+        assert!(!sitemap.is_index());
+        assert_eq!(
+            sitemap.urls().collect::<Vec<_>>(),
+            ["https://example.com/", "https://example.com/docs"]
+        );
+        assert_eq!(sitemap.entries[0].published.as_deref(), Some("2024-05-01"));
+        assert!(sitemap.entries[1].published.is_none());
+
+        let index = Sitemap::parse(
+            r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <sitemap><loc>https://example.com/sitemap-posts.xml</loc></sitemap>
+            </sitemapindex>"#,
+        )
+        .unwrap();
+        assert!(index.is_index());
+        assert_eq!(
+            index.sitemaps[0].url,
+            "https://example.com/sitemap-posts.xml"
+        );
+
+        assert!(Sitemap::parse("<rss/>").is_err());
+        assert!(Sitemap::parse("not xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = Feed::parse(
+            r#"<?xml version="1.0"?>
+            <!DOCTYPE rss PUBLIC "-//Netscape Communications//DTD RSS 0.91//EN" "">
+            <rss version="2.0">
+              <channel>
+                <title>Example Blog</title>
+                <item>
+                  <title><![CDATA[Hello & welcome]]></title>
+                  <link>https://example.com/hello</link>
+                  <pubDate>Wed, 01 May 2024 10:00:00 +0200</pubDate>
+                </item>
+                <item>
+                  <guid>https://example.com/permalink</guid>
+                </item>
+                <item>
+                  <title>No link</title>
+                  <guid isPermaLink="false">tag:example.com,2024:3</guid>
+                </item>
+              </channel>
+            </rss>"#,
+        )
+        .unwrap();
+        assert_eq!(feed.kind, FeedKind::Rss);
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(feed.entries[0].title.as_deref(), Some("Hello & welcome"));
+        assert_eq!(feed.entries[0].url, "https://example.com/hello");
+        assert_eq!(feed.entries[0].published_at(), at(1_714_550_400));
+        assert_eq!(feed.entries[1].url, "https://example.com/permalink");
+
+        let rdf = Feed::parse(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                       xmlns="http://purl.org/rss/1.0/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <channel><title>RDF</title></channel>
+              <item><title>One</title><link>https://example.com/1</link><dc:date>2024-05-01T08:00:00Z</dc:date></item>
+            </rdf:RDF>"#,
+        )
+        .unwrap();
+        assert_eq!(rdf.title.as_deref(), Some("RDF"));
+        assert_eq!(rdf.entries[0].published_at(), at(1_714_550_400));
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let feed = Feed::parse(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Atom Feed</title>
+              <entry>
+                <title>First</title>
+                <link rel="self" href="https://example.com/api/1"/>
+                <link rel="alternate" href="https://example.com/1"/>
+                <updated>2024-05-02T00:00:00Z</updated>
+                <published>2024-05-01T08:00:00Z</published>
+              </entry>
+              <entry>
+                <title>Second</title>
+                <link href="https://example.com/2"/>
+                <updated>2024-05-01T10:00:00.123+02:00</updated>
+              </entry>
+              <entry><title>Unlinked</title></entry>
+            </feed>"#,
+        )
+        .unwrap();
+        assert_eq!(feed.kind, FeedKind::Atom);
+        assert_eq!(feed.title.as_deref(), Some("Atom Feed"));
+        let urls: Vec<_> = feed.entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, ["https://example.com/1", "https://example.com/2"]);
+        assert_eq!(feed.entries[0].published_at(), at(1_714_550_400));
+        assert_eq!(feed.entries[1].published_at(), at(1_714_550_400));
+
+        assert!(Feed::parse("<html/>").is_err());
+    }
+
+    #[test]
+    fn test_published_at_formats() {
+        assert_eq!(entry("2024-05-01").published_at(), at(1_714_521_600));
+        assert_eq!(entry("2024-05").published_at(), at(1_714_521_600));
+        assert_eq!(entry("1970-01-01T00:00Z").published_at(), at(0));
+        assert_eq!(
+            entry("Wed, 01 May 2024 04:00:00 EDT").published_at(),
+            at(1_714_550_400)
+        );
+        assert_eq!(
+            entry("1 May 24 08:00 GMT").published_at(),
+            at(1_714_550_400)
+        );
+        assert_eq!(
+            entry("1969-12-31T23:59:59Z").published_at(),
+            Some(UNIX_EPOCH - Duration::from_secs(1))
+        );
+        assert!(entry("2024-02-30").published_at().is_none());
+        assert!(entry("2023-02-29").published_at().is_none());
+        assert!(entry("2024-02-29").published_at().is_some());
+        assert!(entry("yesterday").published_at().is_none());
+        assert!(entry("2024-05-01T25:00:00Z").published_at().is_none());
+    }
+}
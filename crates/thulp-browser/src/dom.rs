@@ -0,0 +1,160 @@
+//! Structured extraction from HTML using CSS selectors.
+//!
+//! This module parses HTML with [scraper](https://docs.rs/scraper) (html5ever
+//! under the hood) and returns matched nodes as owned [`Element`] values, so
+//! results can be serialized or passed between skill steps.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use thulp_browser::Page;
+//!
+//! let page = Page::new(
+//!     "https://example.com".to_string(),
+//!     r#"<div class="article"><p>First</p><p>Second</p></div>"#.to_string(),
+//!     200,
+//! );
+//!
+//! let paragraphs = page.select("div.article > p").unwrap();
+//! assert_eq!(paragraphs.len(), 2);
+//! assert_eq!(paragraphs[0].text, "First");
+//! ```
+
+use crate::{BrowserError, Result};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An HTML element matched by a CSS selector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Element {
+    /// Tag name (lowercase)
+    pub tag: String,
+
+    /// Concatenated text content of the element and its descendants
+    pub text: String,
+
+    /// HTML of the element's children
+    pub inner_html: String,
+
+    /// HTML of the element including its own tag
+    pub outer_html: String,
+
+    /// Element attributes
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+impl Element {
+    /// Get an attribute value
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Check if the element has the given class
+    pub fn has_class(&self, class: &str) -> bool {
+        self.attr("class")
+            .map(|classes| classes.split_whitespace().any(|c| c == class))
+            .unwrap_or(false)
+    }
+
+    /// Run a selector against this element's children
+    pub fn select(&self, selector: &str) -> Result<Vec<Element>> {
+        select_fragment(&self.inner_html, selector)
+    }
+
+    fn from_ref(element: ElementRef<'_>) -> Self {
+        let value = element.value();
+        Self {
+            tag: value.name().to_string(),
+            text: element.text().collect::<String>().trim().to_string(),
+            inner_html: element.inner_html(),
+            outer_html: element.html(),
+            attributes: value
+                .attrs()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Parse a CSS selector, mapping errors to [`BrowserError::Parse`].
+fn parse_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector)
+        .map_err(|e| BrowserError::Parse(format!("invalid selector '{}': {}", selector, e)))
+}
+
+/// Select elements from a full HTML document.
+pub fn select_document(html: &str, selector: &str) -> Result<Vec<Element>> {
+    let selector = parse_selector(selector)?;
+    let document = Html::parse_document(html);
+    Ok(document.select(&selector).map(Element::from_ref).collect())
+}
+
+/// Select elements from an HTML fragment.
+pub fn select_fragment(html: &str, selector: &str) -> Result<Vec<Element>> {
+    let selector = parse_selector(selector)?;
+    let fragment = Html::parse_fragment(html);
+    Ok(fragment.select(&selector).map(Element::from_ref).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = r#"
+        <html>
+          <body>
+            <div class="article main" id="post">
+              <h1>Title</h1>
+              <p>First <b>bold</b></p>
+              <p class="note">Second</p>
+              <a href="/next" rel="next">Next</a>
+            </div>
+            <p>Outside</p>
+          </body>
+        </html>
+    "#;
+
+    #[test]
+    fn test_select_child_combinator() {
+        let elements = select_document(HTML, "div.article > p").unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].tag, "p");
+        assert_eq!(elements[0].text, "First bold");
+        assert_eq!(elements[0].inner_html, "First <b>bold</b>");
+        assert_eq!(elements[1].text, "Second");
+    }
+
+    #[test]
+    fn test_select_attributes() {
+        let links = select_document(HTML, "a[rel=next]").unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].attr("href"), Some("/next"));
+        assert!(links[0].outer_html.starts_with("<a "));
+
+        let div = &select_document(HTML, "#post").unwrap()[0];
+        assert!(div.has_class("article"));
+        assert!(div.has_class("main"));
+        assert!(!div.has_class("art"));
+    }
+
+    #[test]
+    fn test_nested_select() {
+        let div = &select_document(HTML, "div.article").unwrap()[0];
+        let notes = div.select("p.note").unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].text, "Second");
+    }
+
+    #[test]
+    fn test_no_matches() {
+        assert!(select_document(HTML, "table td").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_selector() {
+        let err = select_document(HTML, "div[").unwrap_err();
+        assert!(matches!(err, BrowserError::Parse(_)));
+    }
+}
@@ -0,0 +1,442 @@
+//! Per-domain politeness settings.
+//!
+//! Some sites need gentler or different treatment than the client's global
+//! [rate limit](crate::rate_limit): a partner API that allows one request
+//! every few seconds, a docs host that should never see more than two
+//! requests at once, or an endpoint that needs its own credentials. A
+//! [`DomainPolicies`] map gives each of them a [`DomainPolicy`], enforced by
+//! the [`WebClient`](crate::WebClient) on every request, crawls included.
+//!
+//! Patterns are host names, optionally starting with `*.` to also cover every
+//! subdomain. The most specific pattern wins: an exact host before wildcards,
+//! and longer wildcards before shorter ones. Delays and concurrency caps are
+//! shared by all hosts a pattern covers, so `*.partner.com` bounds the load
+//! on the partner as a whole.
+//!
+//! The map is usually read from the `domains` section of the workspace's
+//! `.thulp/config.yaml`, where tokens can come from `${secret:name}`
+//! references:
+//!
+//! ```yaml
+//! domains:
+//!   api.partner.com:
+//!     crawl_delay: 2.5
+//!     max_concurrent: 1
+//!     headers:
+//!       X-Client: thulp
+//!     auth:
+//!       type: bearer
+//!       token: ${secret:partner_token}
+//!   "*.docs.example.com":
+//!     crawl_delay: 1
+//! ```
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use thulp_browser::{DomainAuth, DomainPolicies, DomainPolicy, WebClient};
+//!
+//! # fn example(workspace_config: serde_json::Value) -> Result<(), thulp_browser::BrowserError> {
+//! let mut domains = DomainPolicies::from_config(&workspace_config)?;
+//! domains.insert(
+//!     "intranet.example.com",
+//!     DomainPolicy::new()
+//!         .crawl_delay(Duration::from_millis(200))
+//!         .auth(DomainAuth::basic("scraper", "hunter2")),
+//! );
+//!
+//! let client = WebClient::builder().domains(domains).build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::request::Request;
+use crate::{BrowserError, Result};
+use reqwest::Url;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Credentials sent to a domain
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainAuth {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// HTTP basic authentication
+    Basic {
+        username: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+impl DomainAuth {
+    /// Authenticate with a bearer token.
+    pub fn bearer(token: impl Into<String>) -> Self {
+        DomainAuth::Bearer {
+            token: token.into(),
+        }
+    }
+
+    /// Authenticate with a user name and password.
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        DomainAuth::Basic {
+            username: username.into(),
+            password: Some(password.into()),
+        }
+    }
+}
+
+impl std::fmt::Debug for DomainAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomainAuth::Bearer { .. } => f.debug_struct("Bearer").finish_non_exhaustive(),
+            DomainAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// How requests to one domain are paced and decorated
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DomainPolicy {
+    /// Minimum time between the starts of two requests, in seconds in
+    /// configuration files
+    #[serde(
+        serialize_with = "serialize_secs",
+        deserialize_with = "deserialize_secs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub crawl_delay: Option<Duration>,
+    /// Most requests in flight at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    /// Headers added to requests that don't set them already
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Credentials, unless the request has its own `Authorization` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<DomainAuth>,
+}
+
+impl DomainPolicy {
+    /// Create a policy that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minimum delay between requests.
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.crawl_delay = Some(delay);
+        self
+    }
+
+    /// Cap the requests in flight, at least 1.
+    pub fn max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = Some(max.max(1));
+        self
+    }
+
+    /// Add a header.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the credentials.
+    pub fn auth(mut self, auth: DomainAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+}
+
+fn serialize_secs<S: Serializer>(
+    delay: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match delay {
+        Some(delay) => serializer.serialize_f64(delay.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_secs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error> {
+    let Some(secs) = Option::<f64>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    Duration::try_from_secs_f64(secs)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Domain patterns and their policies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DomainPolicies {
+    policies: BTreeMap<String, DomainPolicy>,
+}
+
+impl DomainPolicies {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the `domains` section of a workspace configuration, as returned
+    /// by `Workspace::load_config`. A missing section gives an empty map.
+    pub fn from_config(config: &serde_json::Value) -> Result<Self> {
+        match config.get("domains") {
+            None | Some(serde_json::Value::Null) => Ok(Self::new()),
+            Some(domains) => serde_json::from_value(domains.clone())
+                .map(Self::normalized)
+                .map_err(|e| BrowserError::Parse(format!("domains: {}", e))),
+        }
+    }
+
+    /// Parse a YAML map of patterns to policies.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml)
+            .map(Self::normalized)
+            .map_err(|e| BrowserError::Parse(e.to_string()))
+    }
+
+    /// Set the policy of a host, or of a `*.` pattern covering its
+    /// subdomains.
+    pub fn insert(&mut self, pattern: impl Into<String>, policy: DomainPolicy) {
+        self.policies
+            .insert(pattern.into().to_ascii_lowercase(), policy);
+    }
+
+    /// Find the pattern and policy that apply to a host.
+    pub fn find(&self, host: &str) -> Option<(&str, &DomainPolicy)> {
+        let host = host.to_ascii_lowercase();
+        if let Some((pattern, policy)) = self.policies.get_key_value(&host) {
+            return Some((pattern, policy));
+        }
+        self.policies
+            .iter()
+            .filter_map(|(pattern, policy)| {
+                let domain = pattern.strip_prefix("*.")?;
+                let covered = host == domain
+                    || host
+                        .strip_suffix(domain)
+                        .is_some_and(|sub| sub.ends_with('.'));
+                covered.then_some((pattern.as_str(), policy))
+            })
+            .max_by_key(|(pattern, _)| pattern.len())
+    }
+
+    /// Get the number of patterns.
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    /// Check if there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    fn normalized(self) -> Self {
+        let mut normalized = Self::new();
+        for (pattern, mut policy) in self.policies {
+            policy.max_concurrent = policy.max_concurrent.map(|max| max.max(1));
+            normalized.insert(pattern, policy);
+        }
+        normalized
+    }
+}
+
+/// Shared state enforcing [`DomainPolicies`]
+#[derive(Debug)]
+pub(crate) struct DomainLimiter {
+    policies: DomainPolicies,
+    /// Concurrency caps by pattern
+    permits: Mutex<HashMap<String, Arc<Semaphore>>>,
+    /// Earliest time the next request under each pattern may start
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl DomainLimiter {
+    pub(crate) fn new(policies: DomainPolicies) -> Self {
+        Self {
+            policies,
+            permits: Mutex::new(HashMap::new()),
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait until the policy of the URL's host lets a request start.
+    ///
+    /// The returned permit, if the policy caps concurrency, holds the
+    /// request's slot until it is dropped.
+    pub(crate) async fn acquire(&self, url: &Url) -> Option<OwnedSemaphorePermit> {
+        let (pattern, policy) = self.policies.find(url.host_str()?)?;
+
+        let permit = match policy.max_concurrent {
+            Some(max) => {
+                let semaphore = self
+                    .permits
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .entry(pattern.to_string())
+                    .or_insert_with(|| Arc::new(Semaphore::new(max)))
+                    .clone();
+                Some(
+                    semaphore
+                        .acquire_owned()
+                        .await
+                        .expect("domain semaphore is never closed"),
+                )
+            }
+            None => None,
+        };
+
+        if let Some(delay) = policy.crawl_delay {
+            let now = Instant::now();
+            let start = {
+                let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+                let start = next_slot
+                    .get(pattern)
+                    .copied()
+                    .map_or(now, |slot| slot.max(now));
+                next_slot.insert(pattern.to_string(), start + delay);
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+        permit
+    }
+
+    /// Add the headers and credentials of the URL's policy to a request
+    pub(crate) fn decorate(
+        &self,
+        url: &Url,
+        request: &Request,
+        mut builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::RequestBuilder> {
+        let Some((_, policy)) = url.host_str().and_then(|host| self.policies.find(host)) else {
+            return Ok(builder);
+        };
+        let has_header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .any(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        };
+
+        for (name, value) in &policy.headers {
+            if has_header(name) {
+                continue;
+            }
+            let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            let header_value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            builder = builder.header(header_name, header_value);
+        }
+        if !has_header("authorization") {
+            builder = match &policy.auth {
+                Some(DomainAuth::Bearer { token }) => builder.bearer_auth(token),
+                Some(DomainAuth::Basic { username, password }) => {
+                    builder.basic_auth(username, password.as_ref())
+                }
+                None => builder,
+            };
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_pattern_wins() {
+        let policies = DomainPolicies::from_yaml(
+            r#"
+"*.example.com":
+  crawl_delay: 1
+"*.api.example.com":
+  crawl_delay: 0.5
+  max_concurrent: 0
+Api.Example.com:
+  crawl_delay: 2
+"#,
+        )
+        .unwrap();
+
+        let pattern = |host| policies.find(host).map(|(pattern, _)| pattern);
+        assert_eq!(pattern("api.example.com"), Some("api.example.com"));
+        assert_eq!(pattern("v2.api.example.com"), Some("*.api.example.com"));
+        assert_eq!(pattern("docs.example.com"), Some("*.example.com"));
+        assert_eq!(pattern("example.com"), Some("*.example.com"));
+        assert_eq!(pattern("badexample.com"), None);
+
+        let (_, policy) = policies.find("v2.api.example.com").unwrap();
+        assert_eq!(policy.crawl_delay, Some(Duration::from_millis(500)));
+        assert_eq!(policy.max_concurrent, Some(1));
+    }
+
+    #[test]
+    fn test_from_workspace_config() {
+        let config = serde_json::json!({
+            "servers": {},
+            "domains": {
+                "api.partner.com": {
+                    "headers": {"X-Client": "thulp"},
+                    "auth": {"type": "bearer", "token": "s3cret"}
+                }
+            }
+        });
+        let policies = DomainPolicies::from_config(&config).unwrap();
+        let (_, policy) = policies.find("api.partner.com").unwrap();
+        assert_eq!(policy.auth, Some(DomainAuth::bearer("s3cret")));
+        assert!(!format!("{:?}", policy).contains("s3cret"));
+
+        assert!(DomainPolicies::from_config(&serde_json::json!({}))
+            .unwrap()
+            .is_empty());
+        let invalid = serde_json::json!({"domains": {"a.com": {"crawl_delay": -1}}});
+        assert!(matches!(
+            DomainPolicies::from_config(&invalid),
+            Err(BrowserError::Parse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_limiter_paces_and_caps_pattern() {
+        let mut policies = DomainPolicies::new();
+        policies.insert(
+            "*.partner.com",
+            DomainPolicy::new()
+                .crawl_delay(Duration::from_millis(40))
+                .max_concurrent(1),
+        );
+        let limiter = DomainLimiter::new(policies);
+        let a = Url::parse("https://a.partner.com/").unwrap();
+        let b = Url::parse("https://b.partner.com/").unwrap();
+
+        let start = Instant::now();
+        let permit = limiter.acquire(&a).await;
+        assert!(permit.is_some());
+        // Subdomains share the pattern's slot
+        let blocked = tokio::time::timeout(Duration::from_millis(20), limiter.acquire(&b));
+        assert!(blocked.await.is_err());
+        drop(permit);
+        let _permit = limiter.acquire(&b).await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        // Unlisted hosts are not held back
+        let other = Url::parse("https://other.com/").unwrap();
+        assert!(limiter.acquire(&other).await.is_none());
+    }
+}
@@ -0,0 +1,180 @@
+//! Detecting the language of page text.
+//!
+//! [`Page::detect_language`](crate::Page::detect_language) guesses the
+//! language of a page's visible text from its letter trigrams, without
+//! trusting the `lang` attribute that templates often leave at a default.
+//! Languages are identified by ISO 639-3 codes such as `eng` and `deu`;
+//! [`Crawler::languages`](crate::Crawler::languages) also accepts ISO 639-1
+//! codes and language tags such as `en` or `pt-BR`.
+//!
+//! ```rust
+//! use thulp_browser::Page;
+//!
+//! let page = Page::new(
+//!     "https://example.com".to_string(),
+//!     "<p>Der schnelle braune Fuchs springt über den faulen Hund, \
+//!      während die Katze auf dem Sofa schläft.</p>"
+//!         .to_string(),
+//!     200,
+//! );
+//! let language = page.detect_language().unwrap();
+//! assert_eq!(language.code, "deu");
+//! assert_eq!(language.name, "German");
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Text sampled for detection; the start of a page is enough and keeps
+/// detection fast on very long pages
+const SAMPLE_CHARS: usize = 10_000;
+
+/// Language detected in a text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    /// ISO 639-3 code, e.g. `eng`
+    pub code: String,
+    /// English name, e.g. `English`
+    pub name: String,
+    /// How sure the detector is, from 0.0 to 1.0
+    pub confidence: f64,
+    /// Whether the text was long and distinct enough to trust the result
+    pub reliable: bool,
+}
+
+/// Detect the language of a text.
+///
+/// Returns `None` for text without letters of any known script.
+pub fn detect_language(text: &str) -> Option<DetectedLanguage> {
+    let sample = match text.char_indices().nth(SAMPLE_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    };
+    let info = whatlang::detect(sample)?;
+    Some(DetectedLanguage {
+        code: info.lang().code().to_string(),
+        name: info.lang().eng_name().to_string(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+    })
+}
+
+/// Turn an ISO 639-1 code or language tag into the ISO 639-3 code used by
+/// [`DetectedLanguage::code`]; other codes are lowercased and kept
+pub(crate) fn normalize_code(code: &str) -> String {
+    let primary = code
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let three_letter = match primary.as_str() {
+        "af" => "afr",
+        "ak" => "aka",
+        "am" => "amh",
+        "ar" => "ara",
+        "az" => "aze",
+        "be" => "bel",
+        "bg" => "bul",
+        "bn" => "ben",
+        "ca" => "cat",
+        "cs" => "ces",
+        "da" => "dan",
+        "de" => "deu",
+        "el" => "ell",
+        "en" => "eng",
+        "eo" => "epo",
+        "es" => "spa",
+        "et" => "est",
+        "fa" => "pes",
+        "fi" => "fin",
+        "fr" => "fra",
+        "gu" => "guj",
+        "he" => "heb",
+        "hi" => "hin",
+        "hr" => "hrv",
+        "hu" => "hun",
+        "hy" => "hye",
+        "id" => "ind",
+        "it" => "ita",
+        "ja" => "jpn",
+        "jv" => "jav",
+        "ka" => "kat",
+        "km" => "khm",
+        "kn" => "kan",
+        "ko" => "kor",
+        "la" => "lat",
+        "lt" => "lit",
+        "lv" => "lav",
+        "mk" => "mkd",
+        "ml" => "mal",
+        "mr" => "mar",
+        "my" => "mya",
+        "nb" | "no" => "nob",
+        "ne" => "nep",
+        "nl" => "nld",
+        "or" => "ori",
+        "pa" => "pan",
+        "pl" => "pol",
+        "pt" => "por",
+        "ro" => "ron",
+        "ru" => "rus",
+        "si" => "sin",
+        "sk" => "slk",
+        "sl" => "slv",
+        "sn" => "sna",
+        "sr" => "srp",
+        "sv" => "swe",
+        "ta" => "tam",
+        "te" => "tel",
+        "th" => "tha",
+        "tk" => "tuk",
+        "tl" => "tgl",
+        "tr" => "tur",
+        "uk" => "ukr",
+        "ur" => "urd",
+        "uz" => "uzb",
+        "vi" => "vie",
+        "yi" => "yid",
+        "zh" => "cmn",
+        "zu" => "zul",
+        _ => return primary,
+    };
+    three_letter.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_common_languages() {
+        let samples = [
+            (
+                "eng",
+                "The quick brown fox jumps over the lazy dog while the cat sleeps on the sofa.",
+            ),
+            (
+                "fra",
+                "Le renard brun rapide saute par-dessus le chien paresseux pendant que le chat dort.",
+            ),
+            (
+                "spa",
+                "El rápido zorro marrón salta sobre el perro perezoso mientras el gato duerme.",
+            ),
+        ];
+        for (code, text) in samples {
+            let detected = detect_language(text).unwrap();
+            assert_eq!(detected.code, code, "{}", text);
+            assert!(detected.confidence > 0.0);
+        }
+        assert!(detect_language("1234 !!! 5678").is_none());
+    }
+
+    #[test]
+    fn test_normalize_code() {
+        assert_eq!(normalize_code("en"), "eng");
+        assert_eq!(normalize_code("pt-BR"), "por");
+        assert_eq!(normalize_code("zh_Hant"), "cmn");
+        assert_eq!(normalize_code("DEU"), "deu");
+    }
+}
@@ -5,8 +5,22 @@
 //! This crate provides tools for:
 //! - Web page fetching and parsing
 //! - HTML content extraction
+//! - HTML to Markdown conversion, see [`markdown`]
+//! - CSS selector queries over parsed HTML
+//! - Declarative field extraction from YAML recipes, see [`recipe`]
 //! - Basic web scraping operations
+//! - POST, PUT and DELETE requests with JSON, form and multipart bodies, see [`request`]
+//! - Cookie jars and custom headers for logged-in sessions
+//! - Per-host rate limiting and `robots.txt` support, see [`rate_limit`]
+//! - Per-domain crawl delays, concurrency caps, headers and credentials, see [`domains`]
+//! - Connection pool tuning for high-volume scraping, see [`pool`]
+//! - Response size limits that abort oversized downloads early
+//! - Change monitoring of selected page elements, see [`monitor`]
+//! - Same-origin crawling up to a depth or page limit, see [`crawl`]
+//! - Language detection of page text, see [`language`]
+//! - Sitemap and RSS/Atom feed parsing, see [`discovery`]
 //! - CDP (Chrome DevTools Protocol) browser automation (feature-gated)
+//! - Headless rendering of JavaScript-heavy pages (feature-gated)
 //!
 //! ## Basic Web Fetching
 //!
@@ -36,8 +50,59 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Headless Rendering (requires `headless` feature)
+//!
+//! ```rust,ignore
+//! use thulp_browser::render::{HeadlessRenderer, RenderConfig};
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let config = RenderConfig::new().wait_for_selector("#app");
+//! let renderer = HeadlessRenderer::launch(config).await?;
+//! let rendered = renderer.render("https://example.com").await?;
+//! println!("Title: {:?}", rendered.title);
+//! # Ok(())
+//! # }
+//! ```
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod cookies;
+pub mod crawl;
+pub mod discovery;
+pub mod dom;
+pub mod domains;
+pub mod language;
+pub mod markdown;
+pub mod monitor;
+pub mod pool;
+pub mod rate_limit;
+pub mod recipe;
+pub mod request;
+pub mod robots;
+
+pub use cookies::CookieJar;
+pub use crawl::{CrawlStream, CrawledPage, Crawler};
+pub use discovery::{Feed, FeedEntry, FeedKind, Sitemap};
+pub use dom::Element;
+pub use domains::{DomainAuth, DomainPolicies, DomainPolicy};
+pub use language::{detect_language, DetectedLanguage};
+pub use markdown::MarkdownOptions;
+pub use monitor::{Change, ChangeKind, PageDiff, PageMonitor, PageSnapshot};
+pub use pool::{HttpVersion, PoolConfig};
+pub use rate_limit::RateLimitConfig;
+pub use recipe::{Extractor, FieldRule, ScrapeRecipe};
+pub use request::{Body, Method, MultipartForm, Request};
+pub use robots::RobotsTxt;
+
+use domains::DomainLimiter;
+use pool::HostLimiter;
+use rate_limit::RateLimiter;
+
+#[cfg(test)]
+mod test_server;
 
 /// Result type for browser operations
 pub type Result<T> = std::result::Result<T, BrowserError>;
@@ -77,6 +142,21 @@ pub enum BrowserError {
 
     #[error("Timeout: {0}")]
     Timeout(String),
+
+    #[error("Cookie error: {0}")]
+    Cookie(String),
+
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+
+    #[error("Disallowed by robots.txt: {0}")]
+    RobotsDisallowed(String),
+
+    #[error("Extraction failed: {0}")]
+    Extraction(String),
+
+    #[error("Response from {url} is larger than {limit} bytes")]
+    BodyTooLarge { url: String, limit: u64 },
 }
 
 /// Web page content
@@ -85,7 +165,7 @@ pub struct Page {
     /// The URL of the page
     pub url: String,
 
-    /// The HTML content
+    /// The HTML content, or the raw body of a non-HTML response
     pub html: String,
 
     /// The page title (if found)
@@ -93,26 +173,141 @@ pub struct Page {
 
     /// HTTP status code
     pub status: u16,
+
+    /// MIME type from the `Content-Type` header, lowercase and without
+    /// parameters such as `charset`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
 impl Page {
     /// Create a new page
     pub fn new(url: String, html: String, status: u16) -> Self {
-        let title = extract_title(&html);
-        Self {
+        let mut page = Self {
             url,
             html,
-            title,
+            title: None,
             status,
+            content_type: None,
+        };
+        if !page.is_json() {
+            page.title = extract_title(&page.html);
+        }
+        page
+    }
+
+    /// Set the `Content-Type` the page was served with
+    ///
+    /// JSON pages have no title.
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        self.content_type = (!mime.is_empty()).then_some(mime);
+        if self.is_json() {
+            self.title = None;
+        }
+        self
+    }
+
+    /// Check if the page holds JSON rather than HTML
+    ///
+    /// True for `application/json` and `+json` types such as
+    /// `application/ld+json`. Pages without a content type, or served as
+    /// `text/plain`, are sniffed: a body that parses as a JSON object or
+    /// array counts as JSON.
+    pub fn is_json(&self) -> bool {
+        match self.content_type.as_deref() {
+            Some(mime) if mime == "application/json" || mime.ends_with("+json") => true,
+            None | Some("text/plain") => {
+                let body = self.html.trim_start();
+                (body.starts_with('{') || body.starts_with('['))
+                    && serde_json::from_str::<serde_json::Value>(body).is_ok()
+            }
+            Some(_) => false,
         }
     }
 
+    /// Parse the body as JSON
+    ///
+    /// Returns [`BrowserError::Parse`] if the body isn't valid JSON.
+    pub fn json(&self) -> Result<serde_json::Value> {
+        serde_json::from_str(&self.html)
+            .map_err(|e| BrowserError::Parse(format!("{} is not valid JSON: {}", self.url, e)))
+    }
+
     /// Extract text content from the HTML
+    ///
+    /// JSON pages are returned as is.
     pub fn text(&self) -> String {
+        if self.is_json() {
+            return self.html.clone();
+        }
         // Simple text extraction - in a real implementation would use html5ever or similar
         strip_html_tags(&self.html)
     }
 
+    /// Convert the page to Markdown, keeping links and images
+    ///
+    /// See [`markdown`] for what is converted. JSON pages become a
+    /// pretty-printed `json` code block.
+    pub fn to_markdown(&self) -> String {
+        self.to_markdown_with(&MarkdownOptions::default())
+    }
+
+    /// Convert the page to Markdown with the given options
+    pub fn to_markdown_with(&self, options: &MarkdownOptions) -> String {
+        if self.is_json() {
+            let pretty = self
+                .json()
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                .unwrap_or_else(|| self.html.trim().to_string());
+            return format!("```json\n{}\n```", pretty);
+        }
+        markdown::html_to_markdown(&self.html, &self.url, options)
+    }
+
+    /// Select elements matching a CSS selector
+    ///
+    /// Returns [`BrowserError::Parse`] if the selector is invalid. JSON
+    /// pages have no elements.
+    pub fn select(&self, selector: &str) -> Result<Vec<Element>> {
+        if self.is_json() {
+            return Ok(Vec::new());
+        }
+        dom::select_document(&self.html, selector)
+    }
+
+    /// Select the first element matching a CSS selector
+    pub fn select_first(&self, selector: &str) -> Result<Option<Element>> {
+        Ok(self.select(selector)?.into_iter().next())
+    }
+
+    /// Absolute URLs of the page's `<a href>` links, in document order
+    ///
+    /// Relative links are resolved against the page URL, and links that
+    /// can't be resolved are skipped.
+    pub fn links(&self) -> Vec<String> {
+        let Ok(base) = reqwest::Url::parse(&self.url) else {
+            return Vec::new();
+        };
+        self.select("a[href]")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|a| base.join(a.attr("href")?).ok())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Extract fields with a compiled [`ScrapeRecipe`]
+    pub fn extract(&self, extractor: &Extractor) -> Result<serde_json::Value> {
+        extractor.extract(self)
+    }
+
     /// Get the content length
     pub fn len(&self) -> usize {
         self.html.len()
@@ -122,47 +317,530 @@ impl Page {
     pub fn is_empty(&self) -> bool {
         self.html.is_empty()
     }
+
+    /// Detect the language of the page's text
+    ///
+    /// See [`language`] for how it's detected.
+    pub fn detect_language(&self) -> Option<DetectedLanguage> {
+        language::detect_language(&self.text())
+    }
+}
+
+/// Configuration for a [`WebClient`]
+#[derive(Debug, Clone, Default)]
+pub struct WebClientConfig {
+    /// User-Agent header sent with every request
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: Vec<(String, String)>,
+    /// Jar that stores cookies between requests
+    pub cookie_jar: Option<CookieJar>,
+    /// Timeout for each request
+    pub timeout: Option<Duration>,
+    /// Rate limit applied to requests; `None` sends them as fast as asked
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Connection pool settings
+    pub pool: PoolConfig,
+    /// Politeness settings for particular domains
+    pub domains: DomainPolicies,
+    /// Largest response body read, in bytes; `None` reads any size
+    pub max_body_size: Option<u64>,
+}
+
+impl WebClientConfig {
+    /// Create a configuration with reqwest's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the User-Agent header.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Add a header sent with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Store cookies in a jar, which may be shared with other clients.
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Set the timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Rate limit requests.
+    pub fn rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the connection pool settings.
+    pub fn pool(mut self, pool: PoolConfig) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Set the policy of a host, or of a `*.` pattern covering its
+    /// subdomains.
+    pub fn domain(mut self, pattern: impl Into<String>, policy: DomainPolicy) -> Self {
+        self.domains.insert(pattern, policy);
+        self
+    }
+
+    /// Replace the per-domain policies.
+    pub fn domains(mut self, domains: DomainPolicies) -> Self {
+        self.domains = domains;
+        self
+    }
+
+    /// Refuse response bodies larger than `bytes`.
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+}
+
+/// Builder for a [`WebClient`], rate limited with
+/// [`RateLimitConfig::default`] unless told otherwise
+#[derive(Debug, Clone)]
+pub struct WebClientBuilder {
+    config: WebClientConfig,
+}
+
+impl Default for WebClientBuilder {
+    fn default() -> Self {
+        Self {
+            config: WebClientConfig::new().rate_limit(RateLimitConfig::default()),
+        }
+    }
+}
+
+impl WebClientBuilder {
+    /// Create a builder with the default rate limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the User-Agent header, also used to match `robots.txt` rules.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config = self.config.user_agent(user_agent);
+        self
+    }
+
+    /// Add a header sent with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config = self.config.header(name, value);
+        self
+    }
+
+    /// Store cookies in a jar, which may be shared with other clients.
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.config = self.config.cookie_jar(jar);
+        self
+    }
+
+    /// Set the timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config = self.config.timeout(timeout);
+        self
+    }
+
+    /// Replace the rate limit settings.
+    pub fn rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.config.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Turn rate limiting off.
+    pub fn no_rate_limit(mut self) -> Self {
+        self.config.rate_limit = None;
+        self
+    }
+
+    /// Set the minimum delay between requests to the same host.
+    pub fn per_host_delay(mut self, delay: Duration) -> Self {
+        self.rate_limit_mut().per_host_delay = delay;
+        self
+    }
+
+    /// Set the maximum number of requests in flight at once.
+    pub fn max_concurrent(mut self, max: usize) -> Self {
+        self.rate_limit_mut().max_concurrent = max;
+        self
+    }
+
+    /// Set the random variation of the per-host delay, as a fraction of it.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.rate_limit_mut().jitter = jitter;
+        self
+    }
+
+    /// Set whether URLs disallowed by `robots.txt` are refused.
+    pub fn respect_robots_txt(mut self, respect: bool) -> Self {
+        self.rate_limit_mut().respect_robots_txt = respect;
+        self
+    }
+
+    /// Replace the connection pool settings.
+    pub fn pool(mut self, pool: PoolConfig) -> Self {
+        self.config.pool = pool;
+        self
+    }
+
+    /// Set how many idle connections are kept per host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool = self.config.pool.with_max_idle_per_host(max);
+        self
+    }
+
+    /// Set how long idle connections stay open.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool = self.config.pool.with_idle_timeout(timeout);
+        self
+    }
+
+    /// Send TCP keep-alive probes at an interval.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.config.pool = self.config.pool.with_tcp_keepalive(interval);
+        self
+    }
+
+    /// Set the timeout for establishing connections.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool = self.config.pool.with_connect_timeout(timeout);
+        self
+    }
+
+    /// Set the HTTP versions to use.
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.config.pool = self.config.pool.with_http_version(version);
+        self
+    }
+
+    /// Cap the requests in flight to each host.
+    pub fn max_connections_per_host(mut self, max: usize) -> Self {
+        self.config.pool = self.config.pool.with_max_connections_per_host(max);
+        self
+    }
+
+    /// Connect to `addr` for `host` instead of looking it up in DNS.
+    pub fn resolve(mut self, host: impl Into<String>, addr: std::net::SocketAddr) -> Self {
+        self.config.pool = self.config.pool.with_resolve(host, addr);
+        self
+    }
+
+    /// Set the policy of a host, or of a `*.` pattern covering its
+    /// subdomains.
+    pub fn domain(mut self, pattern: impl Into<String>, policy: DomainPolicy) -> Self {
+        self.config = self.config.domain(pattern, policy);
+        self
+    }
+
+    /// Replace the per-domain policies.
+    pub fn domains(mut self, domains: DomainPolicies) -> Self {
+        self.config.domains = domains;
+        self
+    }
+
+    /// Refuse response bodies larger than `bytes`, see
+    /// [`WebClient::send`].
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.config = self.config.max_body_size(bytes);
+        self
+    }
+
+    /// Build the client.
+    pub fn build(self) -> Result<WebClient> {
+        WebClient::with_config(self.config)
+    }
+
+    fn rate_limit_mut(&mut self) -> &mut RateLimitConfig {
+        self.config.rate_limit.get_or_insert_with(Default::default)
+    }
 }
 
 /// Simple web client for fetching pages
+///
+/// Cloning a client is cheap, and clones share connections and cookies.
+#[derive(Debug, Clone)]
 pub struct WebClient {
     /// HTTP client
     client: reqwest::Client,
+    /// Cookies sent and received by the client
+    cookie_jar: Option<CookieJar>,
+    /// Configured User-Agent, matched against robots.txt
+    user_agent: Option<String>,
+    /// Rate limiter shared by clones of the client
+    limiter: Option<Arc<RateLimiter>>,
+    /// Per-host cap on requests in flight, shared by clones of the client
+    host_limiter: Option<Arc<HostLimiter>>,
+    /// Per-domain policies, shared by clones of the client
+    domain_limiter: Option<Arc<DomainLimiter>>,
+    /// Largest response body read, in bytes
+    max_body_size: Option<u64>,
 }
 
 impl WebClient {
     /// Create a new web client
+    ///
+    /// The client is not rate limited; use [`WebClient::builder`] for one
+    /// that is.
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            cookie_jar: None,
+            user_agent: None,
+            limiter: None,
+            host_limiter: None,
+            domain_limiter: None,
+            max_body_size: None,
         }
     }
 
+    /// Start building a rate-limited web client
+    pub fn builder() -> WebClientBuilder {
+        WebClientBuilder::new()
+    }
+
+    /// Create a web client with custom headers, cookies, timeout or pool
+    /// settings
+    ///
+    /// Returns [`BrowserError::InvalidHeader`] for header names or values
+    /// that aren't valid HTTP.
+    pub fn with_config(config: WebClientConfig) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &config.headers {
+            let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            let header_value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            headers.append(header_name, header_value);
+        }
+
+        let mut builder = reqwest::Client::builder().default_headers(headers);
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(jar) = &config.cookie_jar {
+            builder = builder.cookie_provider(jar.store());
+        }
+        builder = config.pool.apply(builder);
+        let client = builder
+            .build()
+            .map_err(|e| BrowserError::Http(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            cookie_jar: config.cookie_jar,
+            user_agent: config.user_agent,
+            limiter: config
+                .rate_limit
+                .map(|rate_limit| Arc::new(RateLimiter::new(rate_limit))),
+            host_limiter: config
+                .pool
+                .max_connections_per_host
+                .map(|max| Arc::new(HostLimiter::new(max))),
+            domain_limiter: (!config.domains.is_empty())
+                .then(|| Arc::new(DomainLimiter::new(config.domains))),
+            max_body_size: config.max_body_size,
+        })
+    }
+
+    /// Get the client's cookie jar, if it has one
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_ref()
+    }
+
     /// Fetch a web page
+    ///
+    /// If the response sets cookies and the client's jar was opened from a
+    /// file, the jar is saved.
     pub async fn fetch(&self, url: &str) -> Result<Page> {
-        let response = self
-            .client
-            .get(url)
+        self.send(Request::get(url)).await
+    }
+
+    /// Send a request with any method, body and extra headers
+    ///
+    /// The response is returned as a [`Page`] whatever its status; cookies
+    /// are handled as in [`fetch`](Self::fetch). With a
+    /// [`max_body_size`](WebClientBuilder::max_body_size), a response whose
+    /// `Content-Length` is over the limit fails with
+    /// [`BrowserError::BodyTooLarge`] before its body is read, and one
+    /// without a length fails as soon as the bytes read pass the limit.
+    pub async fn send(&self, request: Request) -> Result<Page> {
+        let _permits = self.acquire(&request.url).await?;
+
+        let mut builder = request.build(&self.client)?;
+        if let Some(limiter) = &self.domain_limiter {
+            if let Ok(url) = reqwest::Url::parse(&request.url) {
+                builder = limiter.decorate(&url, &request, builder)?;
+            }
+        }
+        let response = builder
             .send()
             .await
             .map_err(|e| BrowserError::Http(e.to_string()))?;
 
+        if let Some(jar) = &self.cookie_jar {
+            if response.headers().contains_key(reqwest::header::SET_COOKIE) {
+                jar.save()?;
+            }
+        }
+
         let status = response.status().as_u16();
-        let html = response
-            .text()
-            .await
-            .map_err(|e| BrowserError::Http(e.to_string()))?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let html = match self.max_body_size {
+            Some(limit) => read_limited(response, limit, &request.url).await?,
+            None => response
+                .text()
+                .await
+                .map_err(|e| BrowserError::Http(e.to_string()))?,
+        };
+
+        let page = Page::new(request.url, html, status);
+        Ok(match content_type {
+            Some(content_type) => page.with_content_type(&content_type),
+            None => page,
+        })
+    }
+
+    /// Wait until the domain policy, rate limit and per-host cap let a
+    /// request to `url` start, returning the permits that hold its slots
+    async fn acquire(&self, url: &str) -> Result<Vec<tokio::sync::OwnedSemaphorePermit>> {
+        let mut permits = Vec::new();
+        if self.limiter.is_none() && self.host_limiter.is_none() && self.domain_limiter.is_none() {
+            return Ok(permits);
+        }
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| BrowserError::InvalidUrl(format!("{}: {}", url, e)))?;
+        if let Some(limiter) = &self.domain_limiter {
+            permits.extend(limiter.acquire(&parsed).await);
+        }
+        if let Some(limiter) = &self.limiter {
+            permits.push(
+                limiter
+                    .acquire(&self.client, &parsed, self.user_agent.as_deref())
+                    .await?,
+            );
+        }
+        if let Some(limiter) = &self.host_limiter {
+            permits.push(limiter.acquire(&parsed).await);
+        }
+        Ok(permits)
+    }
+
+    /// Fetch and parse a sitemap
+    ///
+    /// If the sitemap is an index, every sitemap it lists is fetched too
+    /// and their pages are collected in
+    /// [`entries`](Sitemap::entries); ones that fail to load are logged and
+    /// skipped. Relative URLs are resolved against the sitemap's URL.
+    pub async fn fetch_sitemap(&self, url: &str) -> Result<Sitemap> {
+        let mut sitemap = Sitemap::parse(&self.fetch_document(url).await?)?;
+        resolve_entries(url, &mut sitemap.sitemaps);
+        for child in &sitemap.sitemaps {
+            let listed = match self.fetch_document(&child.url).await {
+                Ok(xml) => Sitemap::parse(&xml),
+                Err(e) => Err(e),
+            };
+            match listed {
+                Ok(mut listed) => {
+                    resolve_entries(&child.url, &mut listed.entries);
+                    sitemap.entries.append(&mut listed.entries);
+                }
+                Err(e) => {
+                    tracing::warn!(url = %child.url, error = %e, "Skipping sitemap that failed to load")
+                }
+            }
+        }
+        resolve_entries(url, &mut sitemap.entries);
+        Ok(sitemap)
+    }
+
+    /// Fetch and parse an RSS or Atom feed
+    ///
+    /// Relative entry URLs are resolved against the feed's URL.
+    pub async fn fetch_feed(&self, url: &str) -> Result<Feed> {
+        let mut feed = Feed::parse(&self.fetch_document(url).await?)?;
+        resolve_entries(url, &mut feed.entries);
+        Ok(feed)
+    }
 
-        Ok(Page::new(url.to_string(), html, status))
+    /// Fetch a document, failing on non-success statuses
+    async fn fetch_document(&self, url: &str) -> Result<String> {
+        let page = self.fetch(url).await?;
+        if !(200..300).contains(&page.status) {
+            return Err(BrowserError::Http(format!(
+                "{} returned status {}",
+                url, page.status
+            )));
+        }
+        Ok(page.html)
     }
 }
 
+/// Read a response body chunk by chunk, giving up once it passes `limit`
+/// bytes so an oversized response is never held in memory
+async fn read_limited(mut response: reqwest::Response, limit: u64, url: &str) -> Result<String> {
+    let too_large = || BrowserError::BodyTooLarge {
+        url: url.to_string(),
+        limit,
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| BrowserError::Http(e.to_string()))?
+    {
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
 impl Default for WebClient {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Make entry URLs absolute, leaving ones that don't parse as they are
+fn resolve_entries(base: &str, entries: &mut [FeedEntry]) {
+    let Ok(base) = reqwest::Url::parse(base) else {
+        return;
+    };
+    for entry in entries {
+        if let Ok(url) = base.join(&entry.url) {
+            entry.url = url.to_string();
+        }
+    }
+}
+
 /// Extract title from HTML content
 fn extract_title(html: &str) -> Option<String> {
     // Simple regex-based title extraction
@@ -194,6 +872,10 @@ fn strip_html_tags(html: &str) -> String {
 #[cfg(feature = "cdp")]
 pub mod cdp;
 
+/// Headless browser rendering module.
+#[cfg(feature = "headless")]
+pub mod render;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,10 +908,72 @@ mod tests {
         assert!(!text.contains("<"));
     }
 
+    #[test]
+    fn test_page_links() {
+        let page = Page::new(
+            "https://example.com/docs/intro".to_string(),
+            r#"<a href="setup">Setup</a><a href="/">Home</a><a>No href</a>
+               <a href="https://other.org/x#y">Other</a>"#
+                .to_string(),
+            200,
+        );
+        assert_eq!(
+            page.links(),
+            vec![
+                "https://example.com/docs/setup",
+                "https://example.com/",
+                "https://other.org/x#y",
+            ]
+        );
+    }
+
     #[test]
     fn test_web_client_creation() {
+        // Just verify it can be created
         let _client = WebClient::new();
-        assert!(true); // Just verify it can be created
+    }
+
+    #[tokio::test]
+    async fn test_web_client_config() {
+        let jar = CookieJar::new();
+        jar.insert("https://example.com/", "session=abc").unwrap();
+
+        let config = WebClientConfig::new()
+            .user_agent("thulp-test")
+            .header("X-Api-Key", "secret")
+            .timeout(Duration::from_secs(5))
+            .cookie_jar(jar.clone());
+        let client = WebClient::with_config(config).unwrap();
+
+        // The client sees cookies added to the shared jar
+        let shared = client.clone();
+        jar.insert("https://example.com/", "theme=dark").unwrap();
+        assert_eq!(shared.cookie_jar().unwrap().len(), 2);
+        assert!(WebClient::new().cookie_jar().is_none());
+
+        let bad = WebClientConfig::new().header("Bad Header", "x");
+        assert!(matches!(
+            WebClient::with_config(bad),
+            Err(BrowserError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_web_client_builder() {
+        let client = WebClient::builder().build().unwrap();
+        assert!(client.limiter.is_some());
+        assert!(WebClient::new().limiter.is_none());
+
+        let builder = WebClient::builder()
+            .per_host_delay(Duration::from_millis(500))
+            .respect_robots_txt(false);
+        let rate_limit = builder.config.rate_limit.clone().unwrap();
+        assert_eq!(rate_limit.per_host_delay, Duration::from_millis(500));
+        assert!(!rate_limit.respect_robots_txt);
+        assert_eq!(rate_limit.max_concurrent, 4);
+
+        let unlimited = WebClient::builder().no_rate_limit().build().unwrap();
+        assert!(unlimited.limiter.is_none());
     }
 
     #[test]
@@ -260,4 +1004,291 @@ mod tests {
         assert_eq!(page.len(), 11);
         assert!(!page.is_empty());
     }
+
+    #[test]
+    fn test_page_select() {
+        let page = Page::new(
+            "https://example.com".to_string(),
+            r#"<ul><li><a href="/a">A</a></li><li><a href="/b">B</a></li></ul>"#.to_string(),
+            200,
+        );
+        let links = page.select("li > a").unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[1].text, "B");
+        assert_eq!(links[1].attr("href"), Some("/b"));
+
+        let first = page.select_first("a").unwrap().unwrap();
+        assert_eq!(first.text, "A");
+        assert!(page.select_first("table").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_json_page_detection() {
+        let page = Page::new(
+            "https://api.example.com/items".to_string(),
+            r#"{"items": [{"id": 1}], "title": "<title>Not a title</title>"}"#.to_string(),
+            200,
+        );
+        // No content type, so the body is sniffed
+        assert!(page.is_json());
+        assert!(page.title.is_none());
+        assert_eq!(page.json().unwrap()["items"][0]["id"], 1);
+        assert!(page.links().is_empty());
+        assert_eq!(page.text(), page.html);
+        assert!(page.to_markdown().starts_with("```json\n{\n"));
+
+        let page = Page::new("https://example.com".to_string(), "[1, 2]".to_string(), 200)
+            .with_content_type("application/problem+json; charset=utf-8");
+        assert_eq!(
+            page.content_type.as_deref(),
+            Some("application/problem+json")
+        );
+        assert!(page.is_json());
+
+        // An explicit HTML type wins over sniffing
+        let page = Page::new("https://example.com".to_string(), "[1, 2]".to_string(), 200)
+            .with_content_type("text/html");
+        assert!(!page.is_json());
+
+        let page = Page::new(
+            "https://example.com".to_string(),
+            "<html><title>Test</title></html>".to_string(),
+            200,
+        );
+        assert!(!page.is_json());
+        assert!(matches!(page.json(), Err(BrowserError::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_json_content_type() {
+        use test_server::{serve, Route};
+
+        let base = serve(vec![
+            (
+                "/api",
+                Route {
+                    content_type: "application/json; charset=utf-8",
+                    body: r#"{"ok": true}"#.to_string(),
+                },
+            ),
+            ("/page", Route::html("<title>Page</title>")),
+        ])
+        .await;
+
+        let client = WebClient::new();
+        let api = client.fetch(&format!("{}/api", base)).await.unwrap();
+        assert_eq!(api.content_type.as_deref(), Some("application/json"));
+        assert!(api.is_json());
+        assert_eq!(api.json().unwrap()["ok"], true);
+
+        let page = client.fetch(&format!("{}/page", base)).await.unwrap();
+        assert_eq!(page.content_type.as_deref(), Some("text/html"));
+        assert!(!page.is_json());
+        assert_eq!(page.title.as_deref(), Some("Page"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sitemap_index_and_feed() {
+        use test_server::{serve, Route};
+
+        let xml = |body: &str| Route {
+            content_type: "application/xml",
+            body: body.to_string(),
+        };
+        let base = serve(vec![
+            (
+                "/sitemap.xml",
+                xml(r#"<sitemapindex><sitemap><loc>/pages.xml</loc></sitemap>
+                    <sitemap><loc>/missing.xml</loc></sitemap></sitemapindex>"#),
+            ),
+            (
+                "/pages.xml",
+                xml("<urlset><url><loc>/a</loc></url><url><loc>https://example.com/b</loc></url></urlset>"),
+            ),
+            (
+                "/blog/feed.xml",
+                xml(r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry><link href="post-1"/></entry></feed>"#),
+            ),
+        ])
+        .await;
+
+        let client = WebClient::new();
+        let sitemap = client
+            .fetch_sitemap(&format!("{}/sitemap.xml", base))
+            .await
+            .unwrap();
+        assert_eq!(sitemap.sitemaps.len(), 2);
+        assert_eq!(
+            sitemap.urls().collect::<Vec<_>>(),
+            [format!("{}/a", base).as_str(), "https://example.com/b"]
+        );
+
+        let feed = client
+            .fetch_feed(&format!("{}/blog/feed.xml", base))
+            .await
+            .unwrap();
+        assert_eq!(feed.entries[0].url, format!("{}/blog/post-1", base));
+
+        let err = client
+            .fetch_feed(&format!("{}/missing.xml", base))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[tokio::test]
+    async fn test_pool_settings_and_resolve_override() {
+        use test_server::{serve, Route};
+
+        let base = serve(vec![("/", Route::html("<title>Pinned</title>"))]).await;
+        let addr: std::net::SocketAddr = base.trim_start_matches("http://").parse().unwrap();
+
+        let client = WebClient::builder()
+            .no_rate_limit()
+            .pool_max_idle_per_host(4)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .tcp_keepalive(Duration::from_secs(15))
+            .connect_timeout(Duration::from_secs(2))
+            .http_version(HttpVersion::Http1Only)
+            .max_connections_per_host(2)
+            .resolve("scrape.test", addr)
+            .build()
+            .unwrap();
+
+        // The host only exists through the override
+        let url = format!("http://scrape.test:{}/", addr.port());
+        let pages = futures::future::join_all((0..4).map(|_| client.fetch(&url))).await;
+        for page in pages {
+            assert_eq!(page.unwrap().title.as_deref(), Some("Pinned"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_methods_and_bodies() {
+        use test_server::serve_echo;
+
+        let base = serve_echo().await;
+        let client = WebClient::new();
+
+        let page = client
+            .send(
+                Request::post(format!("{}/items", base))
+                    .header("X-Token", "abc")
+                    .json(serde_json::json!({"name": "widget"})),
+            )
+            .await
+            .unwrap();
+        let echoed = page.text();
+        assert!(echoed.starts_with("POST /items HTTP/1.1"));
+        assert!(echoed.to_lowercase().contains("x-token: abc"));
+        assert!(echoed.contains("application/json"));
+        assert!(echoed.ends_with(r#"{"name":"widget"}"#));
+
+        let page = client
+            .send(Request::put(format!("{}/form", base)).form([("a", "1"), ("b", "x y")]))
+            .await
+            .unwrap();
+        assert!(page.text().starts_with("PUT /form"));
+        assert!(page.text().ends_with("a=1&b=x+y"));
+
+        let form = MultipartForm::new().text("title", "Report").file(
+            "attachment",
+            "report.csv",
+            b"a,b".to_vec(),
+        );
+        let page = client
+            .send(Request::post(format!("{}/upload", base)).multipart(form))
+            .await
+            .unwrap();
+        let echoed = page.text();
+        assert!(echoed.contains("multipart/form-data; boundary="));
+        assert!(echoed.contains(r#"name="attachment"; filename="report.csv""#));
+        assert!(echoed.to_lowercase().contains("content-type: text/csv"));
+
+        let page = client
+            .send(Request::delete(format!("{}/items/1", base)))
+            .await
+            .unwrap();
+        assert!(page.text().starts_with("DELETE /items/1"));
+    }
+
+    #[tokio::test]
+    async fn test_max_body_size() {
+        use test_server::{serve, Route};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let base = serve(vec![
+            ("/small", Route::html("tiny")),
+            ("/large", Route::html("x".repeat(1000))),
+        ])
+        .await;
+        let client = WebClient::builder()
+            .no_rate_limit()
+            .max_body_size(100)
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.fetch(&format!("{}/small", base)).await.unwrap().html,
+            "tiny"
+        );
+        let err = client.fetch(&format!("{}/large", base)).await.unwrap_err();
+        assert!(matches!(err, BrowserError::BodyTooLarge { limit: 100, .. }));
+
+        // Without a Content-Length the body is counted as it streams in
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n";
+            let _ = socket.write_all(head.as_bytes()).await;
+            for _ in 0..10 {
+                if socket.write_all(&[b'y'; 64]).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let err = client
+            .fetch(&format!("http://{}/stream", addr))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BrowserError::BodyTooLarge { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_domain_policy_headers_and_auth() {
+        use test_server::serve_echo;
+
+        let base = serve_echo().await;
+        let client = WebClient::with_config(
+            WebClientConfig::new().domain(
+                "127.0.0.1",
+                DomainPolicy::new()
+                    .header("X-Client", "thulp")
+                    .auth(DomainAuth::bearer("partner-token")),
+            ),
+        )
+        .unwrap();
+
+        let echoed = client.fetch(&format!("{}/a", base)).await.unwrap().text();
+        let echoed = echoed.to_lowercase();
+        assert!(echoed.contains("x-client: thulp"));
+        assert!(echoed.contains("authorization: bearer partner-token"));
+
+        // The request's own headers take precedence
+        let echoed = client
+            .send(
+                Request::get(format!("{}/b", base))
+                    .header("Authorization", "Bearer mine")
+                    .header("x-client", "other"),
+            )
+            .await
+            .unwrap()
+            .text()
+            .to_lowercase();
+        assert!(echoed.contains("authorization: bearer mine"));
+        assert!(!echoed.contains("partner-token"));
+        assert_eq!(echoed.matches("x-client").count(), 1);
+    }
 }
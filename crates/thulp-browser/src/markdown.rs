@@ -0,0 +1,504 @@
+//! Conversion of HTML pages to Markdown.
+//!
+//! Markdown keeps a page's structure (headings, lists, tables, code blocks
+//! and emphasis) while dropping the markup noise, which makes it a better
+//! input for language models than either raw HTML or plain text. Scripts,
+//! styles and other non-content elements are left out, and relative link
+//! and image URLs are resolved against the page URL.
+//!
+//! ```rust
+//! use thulp_browser::{MarkdownOptions, Page};
+//!
+//! let page = Page::new(
+//!     "https://example.com/docs/".to_string(),
+//!     r#"<h1>Guide</h1><p>Read the <a href="intro">intro</a> first.</p>"#.to_string(),
+//!     200,
+//! );
+//!
+//! assert_eq!(
+//!     page.to_markdown(),
+//!     "# Guide\n\nRead the [intro](https://example.com/docs/intro) first."
+//! );
+//! assert_eq!(
+//!     page.to_markdown_with(&MarkdownOptions::new().links(false)),
+//!     "# Guide\n\nRead the intro first."
+//! );
+//! ```
+
+use reqwest::Url;
+use scraper::node::Element as ElementData;
+use scraper::{ElementRef, Html, Node};
+
+/// Elements whose content never appears in the output
+const SKIPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object",
+];
+
+/// Elements rendered as separate blocks rather than inline text
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "center",
+    "details",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+/// Options for [`Page::to_markdown_with`](crate::Page::to_markdown_with)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Keep links as `[text](url)`; when off only their text is kept
+    pub links: bool,
+    /// Keep images as `![alt](src)`; when off they are dropped
+    pub images: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            links: true,
+            images: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Create options that keep links and images
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether links are kept
+    pub fn links(mut self, links: bool) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Set whether images are kept
+    pub fn images(mut self, images: bool) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+/// Convert an HTML document to Markdown, resolving relative URLs against
+/// `base_url` when it is a valid URL.
+pub(crate) fn html_to_markdown(html: &str, base_url: &str, options: &MarkdownOptions) -> String {
+    let document = Html::parse_document(html);
+    let converter = Converter {
+        options,
+        base: Url::parse(base_url).ok(),
+    };
+    converter.blocks(document.root_element()).join("\n\n")
+}
+
+struct Converter<'a> {
+    options: &'a MarkdownOptions,
+    base: Option<Url>,
+}
+
+impl Converter<'_> {
+    /// Render an element's children as Markdown blocks.
+    ///
+    /// Runs of inline content between block elements become paragraphs.
+    fn blocks(&self, parent: ElementRef<'_>) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut paragraph = String::new();
+
+        for child in parent.children() {
+            match child.value() {
+                Node::Text(text) => paragraph.push_str(&collapse_whitespace(text)),
+                Node::Element(data) if SKIPPED.contains(&data.name()) => {}
+                Node::Element(data) => {
+                    let Some(element) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if BLOCKS.contains(&data.name()) {
+                        push_paragraph(&mut paragraph, &mut blocks);
+                        blocks.extend(self.block(element, data));
+                    } else {
+                        paragraph.push_str(&self.inline_element(element, data));
+                    }
+                }
+                _ => {}
+            }
+        }
+        push_paragraph(&mut paragraph, &mut blocks);
+        blocks
+    }
+
+    /// Render a block element, which may produce no blocks at all.
+    fn block(&self, element: ElementRef<'_>, data: &ElementData) -> Vec<String> {
+        let name = data.name();
+        let rendered = match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let text = single_line(&self.inline(element));
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                format!("{} {}", "#".repeat(level), text)
+            }
+            "hr" => "---".to_string(),
+            "ul" => self.list(element, None),
+            "ol" => {
+                let start = data
+                    .attr("start")
+                    .and_then(|start| start.trim().parse().ok())
+                    .unwrap_or(1);
+                self.list(element, Some(start))
+            }
+            "pre" => code_block(element),
+            "blockquote" => self
+                .blocks(element)
+                .join("\n\n")
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "table" => self.table(element),
+            _ => return self.blocks(element),
+        };
+        if rendered.is_empty() {
+            Vec::new()
+        } else {
+            vec![rendered]
+        }
+    }
+
+    /// Render a list, numbered from `start` if it is ordered.
+    ///
+    /// Nested blocks, such as sublists, are indented under their item.
+    fn list(&self, element: ElementRef<'_>, start: Option<usize>) -> String {
+        let mut lines = Vec::new();
+        let items = element
+            .child_elements()
+            .filter(|child| child.value().name() == "li");
+
+        for (i, item) in items.enumerate() {
+            let marker = match start {
+                Some(start) => format!("{}.", start + i),
+                None => "-".to_string(),
+            };
+            let indent = " ".repeat(marker.len() + 1);
+            let body = self.blocks(item).join("\n");
+            let mut body_lines = body.lines();
+
+            lines.push(format!(
+                "{} {}",
+                marker,
+                body_lines.next().unwrap_or_default()
+            ));
+            for line in body_lines {
+                if line.is_empty() {
+                    lines.push(String::new());
+                } else {
+                    lines.push(format!("{}{}", indent, line));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Render a table as a pipe table, using the first row as the header.
+    fn table(&self, element: ElementRef<'_>) -> String {
+        let rows: Vec<Vec<String>> = element
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|row| row.value().name() == "tr")
+            .map(|row| {
+                row.child_elements()
+                    .filter(|cell| matches!(cell.value().name(), "th" | "td"))
+                    .map(|cell| single_line(&self.inline(cell)).replace('|', "\\|"))
+                    .collect()
+            })
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let format_row = |cells: &[String]| {
+            let mut line = String::from("|");
+            for i in 0..columns {
+                line.push(' ');
+                line.push_str(cells.get(i).map(String::as_str).unwrap_or_default());
+                line.push_str(" |");
+            }
+            line
+        };
+
+        let mut lines = vec![format_row(&rows[0])];
+        lines.push(format!("|{}", " --- |".repeat(columns)));
+        lines.extend(rows[1..].iter().map(|row| format_row(row)));
+        lines.join("\n")
+    }
+
+    /// Render an element's children as inline Markdown.
+    fn inline(&self, element: ElementRef<'_>) -> String {
+        let mut out = String::new();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(&collapse_whitespace(text)),
+                Node::Element(data) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        out.push_str(&self.inline_element(child, data));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn inline_element(&self, element: ElementRef<'_>, data: &ElementData) -> String {
+        match data.name() {
+            name if SKIPPED.contains(&name) => String::new(),
+            "br" => "\n".to_string(),
+            "a" => {
+                let text = self.inline(element);
+                match data.attr("href") {
+                    Some(href) if self.options.links && !text.trim().is_empty() => {
+                        let url = self.resolve(href);
+                        wrap(&text, "[", &format!("]({})", url))
+                    }
+                    _ => text,
+                }
+            }
+            "img" => match data.attr("src") {
+                Some(src) if self.options.images => format!(
+                    "![{}]({})",
+                    single_line(data.attr("alt").unwrap_or_default()),
+                    self.resolve(src)
+                ),
+                _ => String::new(),
+            },
+            "strong" | "b" => wrap(&self.inline(element), "**", "**"),
+            "em" | "i" => wrap(&self.inline(element), "*", "*"),
+            "del" | "s" | "strike" => wrap(&self.inline(element), "~~", "~~"),
+            "code" | "kbd" | "samp" => {
+                let text = collapse_whitespace(&element.text().collect::<String>());
+                let fence = "`".repeat(longest_run(&text, '`') + 1);
+                wrap(&text, &fence, &fence)
+            }
+            _ => self.inline(element),
+        }
+    }
+
+    /// Resolve a URL against the page URL, keeping it as written if that
+    /// fails.
+    fn resolve(&self, url: &str) -> String {
+        self.base
+            .as_ref()
+            .and_then(|base| base.join(url).ok())
+            .map(String::from)
+            .unwrap_or_else(|| url.to_string())
+    }
+}
+
+/// Render a `<pre>` element as a fenced code block, taking the language from
+/// a `language-*` or `lang-*` class on it or its `<code>` child.
+fn code_block(element: ElementRef<'_>) -> String {
+    let language = std::iter::once(element)
+        .chain(
+            element
+                .child_elements()
+                .filter(|c| c.value().name() == "code"),
+        )
+        .flat_map(|e| e.value().classes())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+
+    let text = element.text().collect::<String>();
+    let text = text.strip_prefix('\n').unwrap_or(&text).trim_end();
+    let fence = "`".repeat(longest_run(text, '`').max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, language, text, fence)
+}
+
+/// Add a paragraph of inline content to the blocks, if it has any text.
+fn push_paragraph(paragraph: &mut String, blocks: &mut Vec<String>) {
+    let text = paragraph
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !text.is_empty() {
+        blocks.push(text);
+    }
+    paragraph.clear();
+}
+
+/// Surround text with markers, keeping its outer whitespace outside them
+fn wrap(text: &str, open: &str, close: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{}{}{}{}{}", leading, open, trimmed, close, trailing)
+}
+
+/// Replace each run of whitespace with a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Length of the longest run of `c` in `text`
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        html_to_markdown(html, "https://example.com/docs/", &MarkdownOptions::new())
+    }
+
+    #[test]
+    fn test_headings_paragraphs_and_emphasis() {
+        let markdown = convert(
+            r#"<html><head><title>T</title><style>p { color: red }</style></head>
+            <body>
+              <h1>  Getting   started </h1>
+              <p>Install <strong>thulp</strong>, then run <code>thulp init</code>.<br>Done!</p>
+              <script>alert("hi")</script>
+              <div>Loose <em>text</em> <del>old</del></div>
+              <hr>
+              <h3></h3>
+            </body></html>"#,
+        );
+        assert_eq!(
+            markdown,
+            "# Getting started\n\n\
+             Install **thulp**, then run `thulp init`.\nDone!\n\n\
+             Loose *text* ~~old~~\n\n\
+             ---"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let markdown = convert(
+            r#"<ul>
+                 <li>One</li>
+                 <li>Two
+                   <ol start="3"><li>Three</li><li><p>Four</p></li></ol>
+                 </li>
+               </ul>"#,
+        );
+        assert_eq!(markdown, "- One\n- Two\n  3. Three\n  4. Four");
+    }
+
+    #[test]
+    fn test_links_and_images() {
+        let html = r#"<p>See <a href="api"> the API </a> and <a href="https://rust-lang.org">Rust</a>.
+            <img src="/logo.png" alt="Logo"> <a href="x"><img src="x.png"></a></p>"#;
+
+        assert_eq!(
+            convert(html),
+            "See [the API](https://example.com/docs/api) and [Rust](https://rust-lang.org/). \
+             ![Logo](https://example.com/logo.png) [![](https://example.com/docs/x.png)](https://example.com/docs/x)"
+        );
+
+        let options = MarkdownOptions::new().links(false).images(false);
+        assert_eq!(
+            html_to_markdown(html, "https://example.com/docs/", &options),
+            "See the API and Rust."
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let markdown = convert(
+            r#"<table>
+                 <thead><tr><th>Name</th><th>Value</th></tr></thead>
+                 <tbody>
+                   <tr><td>a|b</td><td><b>1</b></td></tr>
+                   <tr><td>only</td></tr>
+                 </tbody>
+               </table>"#,
+        );
+        assert_eq!(
+            markdown,
+            "| Name | Value |\n| --- | --- |\n| a\\|b | **1** |\n| only |  |"
+        );
+        assert_eq!(convert("<table></table>"), "");
+    }
+
+    #[test]
+    fn test_code_blocks_and_quotes() {
+        let markdown = convert(
+            "<pre><code class=\"language-rust\">fn main() {\n    println!(\"```\");\n}\n</code></pre>\
+             <blockquote><p>Quoted</p><p>Twice</p></blockquote>",
+        );
+        assert_eq!(
+            markdown,
+            "````rust\nfn main() {\n    println!(\"```\");\n}\n````\n\n> Quoted\n>\n> Twice"
+        );
+    }
+}
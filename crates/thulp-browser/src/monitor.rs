@@ -0,0 +1,470 @@
+//! Watching pages for changes.
+//!
+//! A [`PageMonitor`] fetches pages, keeps a normalized [`PageSnapshot`] of
+//! the elements matched by a set of CSS selectors, and on the next fetch
+//! reports what changed as a [`PageDiff`]: elements that appeared or
+//! disappeared, text that changed, and elements whose markup structure
+//! changed. Text is compared with whitespace collapsed, so reflowed markup
+//! doesn't count as a change.
+//!
+//! A [`threshold`](PageMonitor::threshold) filters out small edits, such as
+//! a timestamp in a docs footer, by the share of words that changed.
+//!
+//! ```rust,no_run
+//! use thulp_browser::{PageMonitor, WebClient};
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let mut monitor = PageMonitor::new(WebClient::new())
+//!     .selector(".price")
+//!     .selector("main h2")
+//!     .threshold(0.01);
+//!
+//! // The first check records a baseline
+//! monitor.check("https://shop.example.com/item/42").await?;
+//!
+//! // Later checks report what changed since the previous one
+//! if let Some(diff) = monitor.check("https://shop.example.com/item/42").await? {
+//!     for change in &diff.changes {
+//!         println!("{:?} {}: {:?} -> {:?}", change.kind, change.selector, change.before, change.after);
+//!     }
+//! }
+//! monitor.save("monitor.json")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::dom::select_fragment;
+use crate::{BrowserError, Page, Result, WebClient};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Normalized content of one matched element
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementSnapshot {
+    /// Text with whitespace collapsed
+    pub text: String,
+    /// Tag names of the element and its descendants, in document order
+    pub structure: String,
+}
+
+/// The watched elements of a page at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    /// URL of the page
+    pub url: String,
+    /// When the page was fetched, in seconds since the Unix epoch
+    pub taken_at: u64,
+    /// Matched elements by selector, in document order
+    pub sections: BTreeMap<String, Vec<ElementSnapshot>>,
+}
+
+impl PageSnapshot {
+    /// Take a snapshot of the elements matching `selectors`.
+    ///
+    /// With no selectors the whole `<body>` is watched.
+    pub fn capture(page: &Page, selectors: &[String]) -> Result<Self> {
+        let body = ["body".to_string()];
+        let selectors = if selectors.is_empty() {
+            &body[..]
+        } else {
+            selectors
+        };
+        let mut sections = BTreeMap::new();
+        for selector in selectors {
+            let elements = page
+                .select(selector)?
+                .into_iter()
+                .map(|element| {
+                    let mut structure = vec![element.tag.clone()];
+                    if let Ok(descendants) = select_fragment(&element.inner_html, "*") {
+                        structure.extend(
+                            descendants
+                                .into_iter()
+                                .map(|e| e.tag)
+                                .filter(|tag| tag != "html"),
+                        );
+                    }
+                    ElementSnapshot {
+                        text: normalize_text(&element.text),
+                        structure: structure.join(" "),
+                    }
+                })
+                .collect();
+            sections.insert(selector.clone(), elements);
+        }
+        Ok(Self {
+            url: page.url.clone(),
+            taken_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            sections,
+        })
+    }
+
+    /// Compare with a later snapshot of the same page.
+    ///
+    /// Elements are matched by position under each selector.
+    pub fn diff(&self, later: &PageSnapshot) -> PageDiff {
+        let empty = Vec::new();
+        let mut changes = Vec::new();
+        let selectors = self.sections.keys().chain(
+            later
+                .sections
+                .keys()
+                .filter(|s| !self.sections.contains_key(*s)),
+        );
+        for selector in selectors {
+            let before = self.sections.get(selector).unwrap_or(&empty);
+            let after = later.sections.get(selector).unwrap_or(&empty);
+            for index in 0..before.len().max(after.len()) {
+                let change =
+                    |kind, before: Option<&ElementSnapshot>, after: Option<&ElementSnapshot>| {
+                        Change {
+                            selector: selector.clone(),
+                            index,
+                            kind,
+                            before: before.map(|e| e.text.clone()),
+                            after: after.map(|e| e.text.clone()),
+                        }
+                    };
+                match (before.get(index), after.get(index)) {
+                    (Some(old), Some(new)) => {
+                        if old.text != new.text {
+                            changes.push(change(ChangeKind::TextChanged, Some(old), Some(new)));
+                        }
+                        if old.structure != new.structure {
+                            changes.push(change(
+                                ChangeKind::StructureChanged,
+                                Some(old),
+                                Some(new),
+                            ));
+                        }
+                    }
+                    (Some(old), None) => changes.push(change(ChangeKind::Removed, Some(old), None)),
+                    (None, Some(new)) => changes.push(change(ChangeKind::Added, None, Some(new))),
+                    (None, None) => {}
+                }
+            }
+        }
+
+        PageDiff {
+            url: later.url.clone(),
+            change_ratio: word_change_ratio(&self.text(), &later.text()),
+            changes,
+        }
+    }
+
+    /// All watched text, for measuring how much changed
+    fn text(&self) -> String {
+        self.sections
+            .values()
+            .flatten()
+            .map(|e| e.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// What happened to a watched element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A new element matches the selector
+    Added,
+    /// An element no longer matches the selector
+    Removed,
+    /// The element's text changed
+    TextChanged,
+    /// Elements were added, removed or retagged inside the element
+    StructureChanged,
+}
+
+/// One change to a watched element
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    /// Selector the element matched
+    pub selector: String,
+    /// Position among the selector's matches
+    pub index: usize,
+    /// What changed
+    pub kind: ChangeKind,
+    /// Text before, unless the element was added
+    pub before: Option<String>,
+    /// Text after, unless the element was removed
+    pub after: Option<String>,
+}
+
+/// Changes between two snapshots of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageDiff {
+    /// URL of the page
+    pub url: String,
+    /// Share of watched words that changed, from 0.0 to 1.0
+    pub change_ratio: f64,
+    /// Individual changes, grouped by selector
+    pub changes: Vec<Change>,
+}
+
+impl PageDiff {
+    /// Check if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Check if any element changed structure, appeared or disappeared.
+    pub fn is_structural(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| c.kind != ChangeKind::TextChanged)
+    }
+}
+
+/// Fetches pages and reports changes to chosen elements between fetches
+#[derive(Debug, Clone)]
+pub struct PageMonitor {
+    client: WebClient,
+    selectors: Vec<String>,
+    threshold: f64,
+    snapshots: HashMap<String, PageSnapshot>,
+}
+
+impl PageMonitor {
+    /// Create a monitor watching each page's whole body.
+    pub fn new(client: WebClient) -> Self {
+        Self {
+            client,
+            selectors: Vec::new(),
+            threshold: 0.0,
+            snapshots: HashMap::new(),
+        }
+    }
+
+    /// Watch elements matching a CSS selector instead of the whole body.
+    pub fn selector(mut self, selector: impl Into<String>) -> Self {
+        self.selectors.push(selector.into());
+        self
+    }
+
+    /// Only report text changes affecting at least this share of the
+    /// watched words, between 0.0 and 1.0.
+    ///
+    /// Structural changes are always reported.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fetch a page and compare it with the previous snapshot.
+    ///
+    /// Returns `None` the first time a page is checked, and when the changes
+    /// fall below the threshold. The new snapshot replaces the old one only
+    /// when a diff is reported, so small edits add up until they cross the
+    /// threshold.
+    pub async fn check(&mut self, url: &str) -> Result<Option<PageDiff>> {
+        let page = self.client.fetch(url).await?;
+        if !(200..300).contains(&page.status) {
+            return Err(BrowserError::Http(format!(
+                "{} returned status {}",
+                url, page.status
+            )));
+        }
+        self.check_page(&page)
+    }
+
+    /// Compare an already fetched page with its previous snapshot.
+    pub fn check_page(&mut self, page: &Page) -> Result<Option<PageDiff>> {
+        let snapshot = PageSnapshot::capture(page, &self.selectors)?;
+        let Some(previous) = self.snapshots.get(&page.url) else {
+            self.snapshots.insert(page.url.clone(), snapshot);
+            return Ok(None);
+        };
+
+        let diff = previous.diff(&snapshot);
+        let significant =
+            !diff.is_empty() && (diff.is_structural() || diff.change_ratio >= self.threshold);
+        if !significant {
+            return Ok(None);
+        }
+        self.snapshots.insert(page.url.clone(), snapshot);
+        Ok(Some(diff))
+    }
+
+    /// Get the last reported snapshot of a page.
+    pub fn snapshot(&self, url: &str) -> Option<&PageSnapshot> {
+        self.snapshots.get(url)
+    }
+
+    /// Forget a page's snapshot, so the next check records a new baseline.
+    pub fn reset(&mut self, url: &str) -> Option<PageSnapshot> {
+        self.snapshots.remove(url)
+    }
+
+    /// Save the snapshots to a JSON file, creating parent directories.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut snapshots: Vec<_> = self.snapshots.values().collect();
+        snapshots.sort_by(|a, b| a.url.cmp(&b.url));
+        let json = serde_json::to_string_pretty(&snapshots)
+            .map_err(|e| BrowserError::Parse(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BrowserError::Parse(format!("{}: {}", parent.display(), e)))?;
+        }
+        std::fs::write(path, json)
+            .map_err(|e| BrowserError::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load snapshots saved with [`save`](Self::save), replacing those of
+    /// the same pages. A missing file loads nothing.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(BrowserError::Parse(format!("{}: {}", path.display(), e))),
+        };
+        let snapshots: Vec<PageSnapshot> = serde_json::from_str(&json)
+            .map_err(|e| BrowserError::Parse(format!("{}: {}", path.display(), e)))?;
+        for snapshot in snapshots {
+            self.snapshots.insert(snapshot.url.clone(), snapshot);
+        }
+        Ok(())
+    }
+}
+
+/// Collapse runs of whitespace into single spaces
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn word_counts(text: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_default() += 1;
+    }
+    counts
+}
+
+/// Share of words not common to both texts (Dice distance over word counts)
+fn word_change_ratio(before: &str, after: &str) -> f64 {
+    let (before, after) = (word_counts(before), word_counts(after));
+    let total: usize = before.values().sum::<usize>() + after.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+    let common: usize = before
+        .iter()
+        .map(|(word, n)| (*n).min(after.get(word).copied().unwrap_or(0)))
+        .sum();
+    1.0 - (2 * common) as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(html: &str) -> Page {
+        Page::new(
+            "https://shop.example.com/item".to_string(),
+            html.to_string(),
+            200,
+        )
+    }
+
+    #[test]
+    fn test_snapshot_diff_kinds() {
+        let selectors = vec![".price".to_string(), "li".to_string()];
+        let before = PageSnapshot::capture(
+            &page(
+                r#"<span class="price">$10</span>
+                   <ul><li>Red</li><li>Blue  <b>new</b></li></ul>"#,
+            ),
+            &selectors,
+        )
+        .unwrap();
+        let after = PageSnapshot::capture(
+            &page(
+                r#"<span class="price">
+                     $12
+                   </span>
+                   <ul><li>Red</li><li>Blue <i>new</i></li><li>Green</li></ul>"#,
+            ),
+            &selectors,
+        )
+        .unwrap();
+
+        assert_eq!(before.sections[".price"][0].text, "$10");
+        let diff = before.diff(&after);
+        let kinds: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|c| (c.selector.as_str(), c.index, c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (".price", 0, ChangeKind::TextChanged),
+                ("li", 1, ChangeKind::StructureChanged),
+                ("li", 2, ChangeKind::Added),
+            ]
+        );
+        assert_eq!(diff.changes[0].before.as_deref(), Some("$10"));
+        assert_eq!(diff.changes[0].after.as_deref(), Some("$12"));
+        assert!(diff.is_structural());
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_monitor_threshold_accumulates_small_edits() {
+        let mut monitor = PageMonitor::new(WebClient::new())
+            .selector("p")
+            .threshold(0.3);
+        let words = "one two three four five six seven eight nine ten";
+
+        assert!(monitor
+            .check_page(&page(&format!("<p>{}</p>", words)))
+            .unwrap()
+            .is_none());
+        // One word in ten changed: below the threshold
+        let small = words.replace("ten", "TEN");
+        assert!(monitor
+            .check_page(&page(&format!("<p>{}</p>", small)))
+            .unwrap()
+            .is_none());
+        // Measured against the baseline, four words have now changed
+        let large = small
+            .replace("one", "ONE")
+            .replace("two", "TWO")
+            .replace("three", "THREE");
+        let diff = monitor
+            .check_page(&page(&format!("<p>{}</p>", large)))
+            .unwrap()
+            .unwrap();
+        assert!((diff.change_ratio - 0.4).abs() < 1e-9);
+        assert_eq!(
+            monitor.snapshot(&diff.url).unwrap().sections["p"][0].text,
+            large
+        );
+    }
+
+    #[test]
+    fn test_monitor_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/monitor.json");
+
+        let mut monitor = PageMonitor::new(WebClient::new()).selector("h1");
+        monitor.check_page(&page("<h1>Docs v1</h1>")).unwrap();
+        monitor.save(&path).unwrap();
+
+        let mut restored = PageMonitor::new(WebClient::new()).selector("h1");
+        restored.load(&path).unwrap();
+        let diff = restored
+            .check_page(&page("<h1>Docs v2</h1>"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(diff.changes[0].before.as_deref(), Some("Docs v1"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Connection pool tuning for [`WebClient`](crate::WebClient).
+//!
+//! A client keeps idle connections open and reuses them for later requests
+//! to the same host; clones of a client, and a [`Crawler`](crate::Crawler)
+//! built from it, share one pool. For high-volume scraping the pool can be
+//! sized, kept alive longer, pinned to HTTP/1.1 or HTTP/2, and capped per
+//! host:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use thulp_browser::{HttpVersion, WebClient};
+//!
+//! # fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let client = WebClient::builder()
+//!     .pool_max_idle_per_host(32)
+//!     .pool_idle_timeout(Duration::from_secs(120))
+//!     .tcp_keepalive(Duration::from_secs(60))
+//!     .connect_timeout(Duration::from_secs(5))
+//!     .http_version(HttpVersion::Http1Only)
+//!     .max_connections_per_host(8)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::Url;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// HTTP versions a client may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpVersion {
+    /// HTTP/2 where the server offers it over TLS, HTTP/1.1 otherwise
+    #[default]
+    Negotiate,
+    /// Always HTTP/1.1
+    Http1Only,
+    /// Always HTTP/2, without negotiation; only for servers known to speak it
+    Http2PriorKnowledge,
+}
+
+/// Connection pool settings
+///
+/// Unset values keep reqwest's defaults: up to `usize::MAX` idle connections
+/// per host, closed after 90 seconds idle, no TCP keep-alive and no connect
+/// timeout.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolConfig {
+    /// Idle connections kept open per host
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection stays open
+    pub idle_timeout: Option<Duration>,
+    /// Interval of TCP keep-alive probes on open connections
+    pub tcp_keepalive: Option<Duration>,
+    /// Timeout for establishing a connection, DNS lookup included
+    pub connect_timeout: Option<Duration>,
+    /// HTTP versions to use
+    pub http_version: HttpVersion,
+    /// Requests in flight to one host at once; over HTTP/1.1 this bounds
+    /// the connections opened to it
+    pub max_connections_per_host: Option<usize>,
+    /// Addresses used for hosts instead of resolving them
+    pub resolve: Vec<(String, SocketAddr)>,
+}
+
+impl PoolConfig {
+    /// Create a configuration with reqwest's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many idle connections are kept per host.
+    pub fn with_max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set how long idle connections stay open.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Send TCP keep-alive probes at an interval.
+    pub fn with_tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Set the timeout for establishing connections.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the HTTP versions to use.
+    pub fn with_http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Cap the requests in flight to each host, at least 1.
+    pub fn with_max_connections_per_host(mut self, max: usize) -> Self {
+        self.max_connections_per_host = Some(max.max(1));
+        self
+    }
+
+    /// Connect to `addr` for `host` instead of looking it up in DNS.
+    ///
+    /// The port in the URL is used in place of the address's port.
+    pub fn with_resolve(mut self, host: impl Into<String>, addr: SocketAddr) -> Self {
+        self.resolve.push((host.into(), addr));
+        self
+    }
+
+    /// Apply the settings to a reqwest client builder
+    pub(crate) fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        builder = match self.http_version {
+            HttpVersion::Negotiate => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+        for (host, addr) in &self.resolve {
+            builder = builder.resolve(host, *addr);
+        }
+        builder
+    }
+}
+
+/// Caps the requests in flight to each host
+#[derive(Debug)]
+pub(crate) struct HostLimiter {
+    max: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            max: max.max(1),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot on the URL's host, held until the permit drops
+    pub(crate) async fn acquire(&self, url: &Url) -> OwnedSemaphorePermit {
+        let key = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let semaphore = self
+            .hosts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max)))
+            .clone();
+        semaphore
+            .acquire_owned()
+            .await
+            .expect("host semaphore is never closed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_host_limiter_caps_each_host() {
+        let limiter = HostLimiter::new(2);
+        let a = Url::parse("https://a.example/one").unwrap();
+        let b = Url::parse("https://b.example/").unwrap();
+
+        let first = limiter.acquire(&a).await;
+        let _second = limiter
+            .acquire(&Url::parse("https://a.example/two").unwrap())
+            .await;
+        let wait = Duration::from_millis(20);
+        assert!(tokio::time::timeout(wait, limiter.acquire(&a))
+            .await
+            .is_err());
+        // Other hosts, and other ports, have their own slots
+        let _other = tokio::time::timeout(wait, limiter.acquire(&b))
+            .await
+            .unwrap();
+        let _port = tokio::time::timeout(
+            wait,
+            limiter.acquire(&Url::parse("https://a.example:8443/").unwrap()),
+        )
+        .await
+        .unwrap();
+
+        drop(first);
+        let _third = tokio::time::timeout(wait, limiter.acquire(&a))
+            .await
+            .unwrap();
+    }
+}
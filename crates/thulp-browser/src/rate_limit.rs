@@ -0,0 +1,291 @@
+//! Rate limiting for [`WebClient`](crate::WebClient).
+//!
+//! A rate limit spaces out requests to the same host, caps how many requests
+//! are in flight at once and can refuse URLs that the site's `robots.txt`
+//! disallows. Clients built with [`WebClient::builder`](crate::WebClient::builder)
+//! are rate limited by default; [`WebClient::new`](crate::WebClient::new) is
+//! not.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use thulp_browser::WebClient;
+//!
+//! # fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let client = WebClient::builder()
+//!     .per_host_delay(Duration::from_secs(2))
+//!     .max_concurrent(2)
+//!     .respect_robots_txt(true)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::robots::RobotsTxt;
+use crate::{BrowserError, Result};
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thulp_core::UrlBuilder;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// User agent matched against `robots.txt` when none is configured.
+const DEFAULT_ROBOTS_AGENT: &str = "thulp";
+
+/// Rate limit settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Minimum time between the starts of two requests to the same host
+    pub per_host_delay: Duration,
+    /// Maximum number of requests in flight at once, across all hosts
+    pub max_concurrent: usize,
+    /// Random variation of the per-host delay, as a fraction of it
+    pub jitter: f64,
+    /// Fetch `robots.txt` for each host and refuse disallowed URLs
+    pub respect_robots_txt: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_host_delay: Duration::from_secs(1),
+            max_concurrent: 4,
+            jitter: 0.25,
+            respect_robots_txt: true,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Create a configuration with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minimum delay between requests to the same host.
+    pub fn with_per_host_delay(mut self, delay: Duration) -> Self {
+        self.per_host_delay = delay;
+        self
+    }
+
+    /// Set the maximum number of concurrent requests.
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = max;
+        self
+    }
+
+    /// Set the jitter, clamped to `0.0..=1.0`.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set whether `robots.txt` is respected.
+    pub fn with_respect_robots_txt(mut self, respect: bool) -> Self {
+        self.respect_robots_txt = respect;
+        self
+    }
+
+    /// Per-host delay with jitter applied.
+    fn jittered_delay(&self) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return self.per_host_delay;
+        }
+        let factor = 1.0 + jitter * (fastrand::f64() * 2.0 - 1.0);
+        self.per_host_delay.mul_f64(factor)
+    }
+}
+
+/// Shared state enforcing a [`RateLimitConfig`]
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    permits: Arc<Semaphore>,
+    /// Earliest time the next request to each host may start
+    next_slot: Mutex<HashMap<String, Instant>>,
+    robots: tokio::sync::Mutex<HashMap<String, Arc<RobotsTxt>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        let permits = Arc::new(Semaphore::new(config.max_concurrent.max(1)));
+        Self {
+            config,
+            permits,
+            next_slot: Mutex::new(HashMap::new()),
+            robots: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait until a request to `url` may start.
+    ///
+    /// The returned permit counts towards the concurrency limit until it is
+    /// dropped. Fails with [`BrowserError::RobotsDisallowed`] if robots.txt
+    /// forbids the URL.
+    pub(crate) async fn acquire(
+        &self,
+        client: &reqwest::Client,
+        url: &Url,
+        user_agent: Option<&str>,
+    ) -> Result<OwnedSemaphorePermit> {
+        let agent = user_agent.unwrap_or(DEFAULT_ROBOTS_AGENT);
+        let robots = if self.config.respect_robots_txt {
+            let robots = self.robots_for(client, url).await;
+            let mut path = url.path().to_string();
+            if let Some(query) = url.query() {
+                path.push('?');
+                path.push_str(query);
+            }
+            if !robots.is_allowed(agent, &path) {
+                return Err(BrowserError::RobotsDisallowed(url.to_string()));
+            }
+            Some(robots)
+        } else {
+            None
+        };
+
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| BrowserError::Http(e.to_string()))?;
+
+        let mut delay = self.config.jittered_delay();
+        if let Some(crawl_delay) = robots.and_then(|robots| robots.crawl_delay(agent)) {
+            delay = delay.max(crawl_delay);
+        }
+        let start = self.reserve_slot(url.host_str().unwrap_or_default(), delay);
+        tokio::time::sleep_until(start).await;
+
+        Ok(permit)
+    }
+
+    /// Reserve the next start time for a host, `delay` after the previous one.
+    fn reserve_slot(&self, host: &str, delay: Duration) -> Instant {
+        let now = Instant::now();
+        let mut next_slot = self
+            .next_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let start = next_slot
+            .get(host)
+            .copied()
+            .map_or(now, |slot| slot.max(now));
+        next_slot.insert(host.to_string(), start + delay);
+        start
+    }
+
+    /// Fetch and cache the robots.txt of a URL's origin.
+    ///
+    /// Missing or unreachable files allow everything.
+    async fn robots_for(&self, client: &reqwest::Client, url: &Url) -> Arc<RobotsTxt> {
+        let origin = url.origin().ascii_serialization();
+        let mut cache = self.robots.lock().await;
+        if let Some(robots) = cache.get(&origin) {
+            return robots.clone();
+        }
+
+        let robots_url = UrlBuilder::new(&origin).path("robots.txt").build();
+        let text = match client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                response.text().await.unwrap_or_default()
+            }
+            Ok(_) => String::new(),
+            Err(e) => {
+                tracing::debug!(url = %robots_url, error = %e, "Could not fetch robots.txt");
+                String::new()
+            }
+        };
+
+        let robots = Arc::new(RobotsTxt::parse(&text));
+        cache.insert(origin, robots.clone());
+        robots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(delay_ms: u64, max_concurrent: usize) -> RateLimiter {
+        RateLimiter::new(
+            RateLimitConfig::new()
+                .with_per_host_delay(Duration::from_millis(delay_ms))
+                .with_max_concurrent(max_concurrent)
+                .with_jitter(0.0)
+                .with_respect_robots_txt(false),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_spaces_requests_per_host() {
+        let limiter = limiter(40, 8);
+        let client = reqwest::Client::new();
+        let a = Url::parse("https://a.example/").unwrap();
+        let b = Url::parse("https://b.example/").unwrap();
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let _permit = limiter.acquire(&client, &a, None).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(80));
+
+        // Another host has its own schedule
+        let other = Instant::now();
+        let _permit = limiter.acquire(&client, &b, None).await.unwrap();
+        assert!(other.elapsed() < Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_limits_concurrency() {
+        let limiter = limiter(0, 1);
+        let client = reqwest::Client::new();
+        let url = Url::parse("https://a.example/").unwrap();
+
+        let permit = limiter.acquire(&client, &url, None).await.unwrap();
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(20),
+            limiter.acquire(&client, &url, None),
+        );
+        assert!(blocked.await.is_err());
+
+        drop(permit);
+        assert!(limiter.acquire(&client, &url, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refuses_urls_disallowed_by_robots() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::new()
+                .with_per_host_delay(Duration::ZERO)
+                .with_respect_robots_txt(true),
+        );
+        limiter.robots.lock().await.insert(
+            "https://a.example".to_string(),
+            Arc::new(RobotsTxt::parse("User-agent: *\nDisallow: /admin")),
+        );
+        let client = reqwest::Client::new();
+
+        let admin = Url::parse("https://a.example/admin/users").unwrap();
+        assert!(matches!(
+            limiter.acquire(&client, &admin, None).await,
+            Err(BrowserError::RobotsDisallowed(_))
+        ));
+        let home = Url::parse("https://a.example/").unwrap();
+        assert!(limiter.acquire(&client, &home, None).await.is_ok());
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let config = RateLimitConfig::new()
+            .with_per_host_delay(Duration::from_millis(100))
+            .with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = config.jittered_delay();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+}
@@ -0,0 +1,654 @@
+//! Declarative extraction rules.
+//!
+//! A [`ScrapeRecipe`] maps field names to CSS selectors, so a scraper for a
+//! site is a YAML file instead of code. Recipes are compiled into an
+//! [`Extractor`], which turns a [`Page`] into a JSON object.
+//!
+//! ```yaml
+//! name: blog-post
+//! url_pattern: "^https://blog\\.example\\.com/posts/"
+//! fields:
+//!   # A bare selector takes the element's text
+//!   title: h1
+//!   published:
+//!     selector: time
+//!     attribute: datetime
+//!   reading_time:
+//!     selector: .meta
+//!     regex: "(\\d+) min read"
+//!     transform: [integer]
+//!   tags:
+//!     selector: .tags a
+//!     multiple: true
+//!     transform: [lowercase]
+//!   links:
+//!     selector: article a
+//!     attribute: href
+//!     multiple: true
+//!     transform: [absolute_url]
+//!   comments:
+//!     selector: .comment
+//!     multiple: true
+//!     fields:
+//!       author: .author
+//!       body: { selector: .body, extract: html }
+//! ```
+//!
+//! For each field, the first element matching `selector` (or every element,
+//! with `multiple: true`) gives a value: its whitespace-collapsed text, an
+//! `attribute`, its inner or outer `html`, or an object built from nested
+//! `fields` matched inside it. A `regex` keeps the first capture group (or
+//! the whole match), then `transform` steps run in order. A field with no
+//! value is `null` (or `[]` for `multiple`) unless it has a `default`;
+//! `required: true` makes it an error instead.
+//!
+//! Recipes shared in a workspace live in `.thulp/recipes/*.yaml`; see
+//! [`load_dir`] and [`workspace_dir`].
+//!
+//! ```rust
+//! use thulp_browser::{Page, ScrapeRecipe};
+//!
+//! let recipe = ScrapeRecipe::from_yaml("name: titles\nfields:\n  title: h1\n").unwrap();
+//! let extractor = recipe.compile().unwrap();
+//!
+//! let page = Page::new(
+//!     "https://example.com".to_string(),
+//!     "<h1> Hello </h1>".to_string(),
+//!     200,
+//! );
+//! assert_eq!(page.extract(&extractor).unwrap()["title"], "Hello");
+//! ```
+
+use crate::{BrowserError, Page, Result};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A named set of extraction rules, usually read from YAML
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrapeRecipe {
+    /// Recipe name
+    pub name: String,
+
+    /// What the recipe extracts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Regex matched against page URLs the recipe applies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_pattern: Option<String>,
+
+    /// Fields to extract, by name
+    pub fields: BTreeMap<String, FieldRule>,
+}
+
+/// What to read from a matched element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractKind {
+    /// Text content with whitespace collapsed
+    #[default]
+    Text,
+    /// HTML of the element's children
+    Html,
+    /// HTML of the element including its own tag
+    OuterHtml,
+}
+
+/// Post-processing step applied to an extracted value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// Strip leading and trailing whitespace
+    Trim,
+    /// Convert to lowercase
+    Lowercase,
+    /// Convert to uppercase
+    Uppercase,
+    /// Parse a number, ignoring anything but digits, `.` and `-`
+    Number,
+    /// Parse an integer, ignoring anything but digits and `-`
+    Integer,
+    /// Resolve a relative URL against the page URL
+    AbsoluteUrl,
+}
+
+/// How one field is extracted
+///
+/// In YAML a field can also be given as just a selector string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "FieldSpec")]
+pub struct FieldRule {
+    /// CSS selector, matched within the page or the parent field's element
+    pub selector: String,
+
+    /// Attribute to read instead of the element's content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+
+    /// Content to read when no attribute is given
+    #[serde(default)]
+    pub extract: ExtractKind,
+
+    /// Regex applied to the value, keeping the first capture group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+
+    /// Steps applied to the value, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transform: Vec<Transform>,
+
+    /// Collect every match into a list
+    #[serde(default)]
+    pub multiple: bool,
+
+    /// Fail extraction if the field has no value
+    #[serde(default)]
+    pub required: bool,
+
+    /// Value used when nothing matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+
+    /// Nested fields, extracted from within each matched element
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, FieldRule>,
+}
+
+impl FieldRule {
+    /// Create a rule reading the text of the first match of a selector
+    pub fn new(selector: impl Into<String>) -> Self {
+        Self {
+            selector: selector.into(),
+            attribute: None,
+            extract: ExtractKind::Text,
+            regex: None,
+            transform: Vec::new(),
+            multiple: false,
+            required: false,
+            default: None,
+            fields: BTreeMap::new(),
+        }
+    }
+}
+
+/// The forms a field can be written in
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FieldSpec {
+    Selector(String),
+    Rule {
+        selector: String,
+        #[serde(default)]
+        attribute: Option<String>,
+        #[serde(default)]
+        extract: ExtractKind,
+        #[serde(default)]
+        regex: Option<String>,
+        #[serde(default)]
+        transform: Vec<Transform>,
+        #[serde(default)]
+        multiple: bool,
+        #[serde(default)]
+        required: bool,
+        #[serde(default)]
+        default: Option<Value>,
+        #[serde(default)]
+        fields: BTreeMap<String, FieldRule>,
+    },
+}
+
+impl From<FieldSpec> for FieldRule {
+    fn from(spec: FieldSpec) -> Self {
+        match spec {
+            FieldSpec::Selector(selector) => Self::new(selector),
+            FieldSpec::Rule {
+                selector,
+                attribute,
+                extract,
+                regex,
+                transform,
+                multiple,
+                required,
+                default,
+                fields,
+            } => Self {
+                selector,
+                attribute,
+                extract,
+                regex,
+                transform,
+                multiple,
+                required,
+                default,
+                fields,
+            },
+        }
+    }
+}
+
+impl ScrapeRecipe {
+    /// Parse a recipe from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| BrowserError::Parse(format!("invalid scrape recipe: {}", e)))
+    }
+
+    /// Read a recipe from a YAML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| BrowserError::Parse(format!("{}: {}", path.display(), e)))?;
+        Self::from_yaml(&yaml)
+            .map_err(|e| BrowserError::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write the recipe to a YAML file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let yaml = serde_yaml::to_string(self).map_err(|e| BrowserError::Parse(e.to_string()))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BrowserError::Parse(format!("{}: {}", parent.display(), e)))?;
+        }
+        std::fs::write(path, yaml)
+            .map_err(|e| BrowserError::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Compile the recipe, checking every selector and regex
+    ///
+    /// Returns [`BrowserError::Parse`] naming the field with an invalid
+    /// selector or regex.
+    pub fn compile(&self) -> Result<Extractor> {
+        let url_pattern = self
+            .url_pattern
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    BrowserError::Parse(format!("recipe '{}' url_pattern: {}", self.name, e))
+                })
+            })
+            .transpose()?;
+        Ok(Extractor {
+            name: self.name.clone(),
+            url_pattern,
+            fields: compile_fields(&self.fields, "")?,
+        })
+    }
+}
+
+/// A compiled [`ScrapeRecipe`], ready to apply to pages
+#[derive(Debug, Clone)]
+pub struct Extractor {
+    name: String,
+    url_pattern: Option<Regex>,
+    fields: Vec<CompiledField>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledField {
+    /// Dotted path for error messages
+    path: String,
+    name: String,
+    selector: Selector,
+    attribute: Option<String>,
+    extract: ExtractKind,
+    regex: Option<Regex>,
+    transform: Vec<Transform>,
+    multiple: bool,
+    required: bool,
+    default: Option<Value>,
+    fields: Vec<CompiledField>,
+}
+
+fn compile_fields(
+    fields: &BTreeMap<String, FieldRule>,
+    parent: &str,
+) -> Result<Vec<CompiledField>> {
+    fields
+        .iter()
+        .map(|(name, rule)| {
+            let path = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", parent, name)
+            };
+            let selector = Selector::parse(&rule.selector).map_err(|e| {
+                BrowserError::Parse(format!(
+                    "field '{}': invalid selector '{}': {}",
+                    path, rule.selector, e
+                ))
+            })?;
+            let regex = rule
+                .regex
+                .as_deref()
+                .map(|pattern| {
+                    Regex::new(pattern)
+                        .map_err(|e| BrowserError::Parse(format!("field '{}': {}", path, e)))
+                })
+                .transpose()?;
+            Ok(CompiledField {
+                fields: compile_fields(&rule.fields, &path)?,
+                path,
+                name: name.clone(),
+                selector,
+                attribute: rule.attribute.clone(),
+                extract: rule.extract,
+                regex,
+                transform: rule.transform.clone(),
+                multiple: rule.multiple,
+                required: rule.required,
+                default: rule.default.clone(),
+            })
+        })
+        .collect()
+}
+
+impl Extractor {
+    /// Name of the recipe
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if the recipe applies to a URL
+    ///
+    /// Recipes without a `url_pattern` apply everywhere.
+    pub fn matches(&self, url: &str) -> bool {
+        self.url_pattern
+            .as_ref()
+            .map_or(true, |pattern| pattern.is_match(url))
+    }
+
+    /// Extract the recipe's fields from a page
+    ///
+    /// Returns [`BrowserError::Extraction`] if a required field has no
+    /// value.
+    pub fn extract(&self, page: &Page) -> Result<Value> {
+        self.extract_html(&page.html, &page.url)
+    }
+
+    /// Extract the recipe's fields from HTML, resolving URLs against
+    /// `base_url`
+    pub fn extract_html(&self, html: &str, base_url: &str) -> Result<Value> {
+        let document = Html::parse_document(html);
+        let base = reqwest::Url::parse(base_url).ok();
+        extract_fields(document.root_element(), &self.fields, base.as_ref()).map(Value::Object)
+    }
+}
+
+fn extract_fields(
+    scope: ElementRef<'_>,
+    fields: &[CompiledField],
+    base: Option<&reqwest::Url>,
+) -> Result<Map<String, Value>> {
+    let mut object = Map::new();
+    for field in fields {
+        let mut values = Vec::new();
+        for element in scope.select(&field.selector) {
+            if let Some(value) = field_value(element, field, base)? {
+                values.push(value);
+                if !field.multiple {
+                    break;
+                }
+            }
+        }
+
+        let value = if field.multiple {
+            if values.is_empty() {
+                field.default.clone()
+            } else {
+                Some(Value::Array(values))
+            }
+        } else {
+            values.pop().or_else(|| field.default.clone())
+        };
+        let value = match value {
+            Some(value) => value,
+            None if field.required => {
+                return Err(BrowserError::Extraction(format!(
+                    "required field '{}' has no value",
+                    field.path
+                )))
+            }
+            None if field.multiple => Value::Array(Vec::new()),
+            None => Value::Null,
+        };
+        object.insert(field.name.clone(), value);
+    }
+    Ok(object)
+}
+
+/// Value of a field for one matched element, if it has one
+fn field_value(
+    element: ElementRef<'_>,
+    field: &CompiledField,
+    base: Option<&reqwest::Url>,
+) -> Result<Option<Value>> {
+    if !field.fields.is_empty() {
+        return extract_fields(element, &field.fields, base).map(|o| Some(Value::Object(o)));
+    }
+
+    let raw = match &field.attribute {
+        Some(attribute) => match element.value().attr(attribute) {
+            Some(value) => value.to_string(),
+            None => return Ok(None),
+        },
+        None => match field.extract {
+            ExtractKind::Text => element
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            ExtractKind::Html => element.inner_html(),
+            ExtractKind::OuterHtml => element.html(),
+        },
+    };
+
+    let text = match &field.regex {
+        Some(regex) => match regex.captures(&raw) {
+            Some(captures) => captures
+                .get(1)
+                .or_else(|| captures.get(0))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default(),
+            None => return Ok(None),
+        },
+        None => raw,
+    };
+
+    let mut value = Value::String(text);
+    for step in &field.transform {
+        match apply_transform(*step, value, base) {
+            Some(next) => value = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Apply a step, or `None` if the value can't be converted
+fn apply_transform(step: Transform, value: Value, base: Option<&reqwest::Url>) -> Option<Value> {
+    let Value::String(text) = value else {
+        // Numbers only come out of the last conversion
+        return Some(value);
+    };
+    Some(match step {
+        Transform::Trim => Value::String(text.trim().to_string()),
+        Transform::Lowercase => Value::String(text.to_lowercase()),
+        Transform::Uppercase => Value::String(text.to_uppercase()),
+        Transform::Number => {
+            let digits: String = text
+                .chars()
+                .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+                .collect();
+            serde_json::Number::from_f64(digits.parse().ok()?).map(Value::Number)?
+        }
+        Transform::Integer => {
+            let digits: String = text
+                .chars()
+                .filter(|c| c.is_ascii_digit() || *c == '-')
+                .collect();
+            Value::from(digits.parse::<i64>().ok()?)
+        }
+        Transform::AbsoluteUrl => match base.and_then(|base| base.join(text.trim()).ok()) {
+            Some(url) => Value::String(url.to_string()),
+            None => Value::String(text),
+        },
+    })
+}
+
+/// Directory of a workspace's shared recipes: `{root}/.thulp/recipes`
+pub fn workspace_dir(root: impl AsRef<Path>) -> PathBuf {
+    root.as_ref().join(".thulp").join("recipes")
+}
+
+/// Load every `.yaml` and `.yml` recipe in a directory, sorted by file name
+///
+/// A missing directory has no recipes.
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<ScrapeRecipe>> {
+    let dir = dir.as_ref();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(BrowserError::Parse(format!("{}: {}", dir.display(), e))),
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("yaml" | "yml")
+            )
+        })
+        .collect();
+    paths.sort();
+    paths.iter().map(ScrapeRecipe::load).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPE: &str = r#"
+name: blog-post
+url_pattern: "^https://blog\\.example\\.com/posts/"
+fields:
+  title: h1
+  published:
+    selector: time
+    attribute: datetime
+  reading_time:
+    selector: .meta
+    regex: "(\\d+) min read"
+    transform: [integer]
+  price:
+    selector: .price
+    transform: [number]
+  tags:
+    selector: .tags a
+    multiple: true
+    transform: [lowercase]
+  links:
+    selector: article a
+    attribute: href
+    multiple: true
+    transform: [absolute_url]
+  summary:
+    selector: .summary
+    default: "none"
+  comments:
+    selector: .comment
+    multiple: true
+    fields:
+      author: .author
+      body: { selector: .body, extract: html }
+"#;
+
+    const HTML: &str = r#"
+        <article>
+          <h1>  Hello
+              world </h1>
+          <time datetime="2024-05-01">May 1</time>
+          <p class="meta">Posted by Ann · 7 min read</p>
+          <p class="price">$1,299.50</p>
+          <div class="tags"><a href="/t/rust">Rust</a><a href="/t/web">WEB</a></div>
+        </article>
+        <div class="comment"><span class="author">Bo</span><p class="body">Nice <b>post</b></p></div>
+        <div class="comment"><span class="author">Cy</span></div>
+    "#;
+
+    #[test]
+    fn test_extract_fields() {
+        let extractor = ScrapeRecipe::from_yaml(RECIPE).unwrap().compile().unwrap();
+        let page = Page::new(
+            "https://blog.example.com/posts/1".to_string(),
+            HTML.to_string(),
+            200,
+        );
+        assert!(extractor.matches(&page.url));
+        assert!(!extractor.matches("https://example.com/"));
+
+        let data = page.extract(&extractor).unwrap();
+        assert_eq!(data["title"], "Hello world");
+        assert_eq!(data["published"], "2024-05-01");
+        assert_eq!(data["reading_time"], 7);
+        assert_eq!(data["price"], 1299.5);
+        assert_eq!(data["tags"], serde_json::json!(["rust", "web"]));
+        assert_eq!(
+            data["links"],
+            serde_json::json!([
+                "https://blog.example.com/t/rust",
+                "https://blog.example.com/t/web"
+            ])
+        );
+        assert_eq!(data["summary"], "none");
+        assert_eq!(
+            data["comments"],
+            serde_json::json!([
+                {"author": "Bo", "body": "Nice <b>post</b>"},
+                {"author": "Cy", "body": null}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_required_and_invalid_rules() {
+        let mut recipe = ScrapeRecipe::from_yaml("name: r\nfields:\n  title: h1\n").unwrap();
+        recipe.fields.get_mut("title").unwrap().required = true;
+        let err = recipe
+            .compile()
+            .unwrap()
+            .extract_html("<p>no heading</p>", "https://example.com")
+            .unwrap_err();
+        assert!(matches!(err, BrowserError::Extraction(_)));
+        assert!(err.to_string().contains("'title'"));
+
+        let mut nested = FieldRule::new(".item");
+        nested
+            .fields
+            .insert("bad".to_string(), FieldRule::new("div["));
+        recipe.fields.insert("items".to_string(), nested);
+        let err = recipe.compile().unwrap_err();
+        assert!(err.to_string().contains("field 'items.bad'"));
+
+        assert!(ScrapeRecipe::from_yaml("fields: {}").is_err());
+    }
+
+    #[test]
+    fn test_workspace_recipes_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = workspace_dir(temp.path());
+        assert!(load_dir(&dir).unwrap().is_empty());
+
+        let recipe = ScrapeRecipe::from_yaml(RECIPE).unwrap();
+        recipe.save(dir.join("blog.yaml")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a recipe").unwrap();
+
+        let loaded = load_dir(&dir).unwrap();
+        assert_eq!(loaded, vec![recipe]);
+    }
+}
@@ -0,0 +1,363 @@
+//! Headless browser rendering backend.
+//!
+//! [`WebClient`](crate::WebClient) only sees the HTML returned by the server,
+//! which is often an empty shell on JavaScript-heavy sites. This module drives
+//! a headless Chromium instance via [chromiumoxide](https://docs.rs/chromiumoxide)
+//! to load a page, run its scripts, and capture the resulting DOM as a
+//! [`RenderedPage`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use thulp_browser::render::{HeadlessRenderer, RenderConfig};
+//!
+//! let config = RenderConfig::new()
+//!     .navigation_timeout(Duration::from_secs(20))
+//!     .wait_for_selector("div.article");
+//!
+//! let renderer = HeadlessRenderer::launch(config).await?;
+//! let rendered = renderer.render("https://example.com").await?;
+//! println!("{}", rendered.text());
+//!
+//! renderer.close().await?;
+//! ```
+
+use crate::{extract_title, strip_html_tags, BrowserError, Page, Result};
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// Configuration for the headless rendering backend.
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+    /// Path to Chrome/Chromium executable (auto-detected if None)
+    pub executable_path: Option<PathBuf>,
+    /// Run without a visible window
+    pub headless: bool,
+    /// Maximum time to wait for navigation to finish
+    pub navigation_timeout: Duration,
+    /// Selector that must be present before the DOM is captured
+    pub wait_for_selector: Option<String>,
+    /// Maximum time to wait for `wait_for_selector` to match
+    pub selector_timeout: Duration,
+    /// Interval between selector checks
+    pub poll_interval: Duration,
+    /// Window width
+    pub width: u32,
+    /// Window height
+    pub height: u32,
+    /// Additional browser arguments
+    pub args: Vec<String>,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderConfig {
+    /// Create a new render configuration with defaults.
+    pub fn new() -> Self {
+        Self {
+            executable_path: None,
+            headless: true,
+            navigation_timeout: Duration::from_secs(30),
+            wait_for_selector: None,
+            selector_timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_millis(100),
+            width: 1280,
+            height: 720,
+            args: Vec::new(),
+        }
+    }
+
+    /// Set the executable path.
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable_path = Some(path.into());
+        self
+    }
+
+    /// Enable or disable headless mode.
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Set the navigation timeout.
+    pub fn navigation_timeout(mut self, timeout: Duration) -> Self {
+        self.navigation_timeout = timeout;
+        self
+    }
+
+    /// Wait for a CSS selector to match before capturing the DOM.
+    pub fn wait_for_selector(mut self, selector: impl Into<String>) -> Self {
+        self.wait_for_selector = Some(selector.into());
+        self
+    }
+
+    /// Set the selector wait timeout.
+    pub fn selector_timeout(mut self, timeout: Duration) -> Self {
+        self.selector_timeout = timeout;
+        self
+    }
+
+    /// Set the window size.
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Add a browser argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    fn to_browser_config(&self) -> Result<BrowserConfig> {
+        let mut builder = BrowserConfig::builder()
+            .window_size(self.width, self.height)
+            .request_timeout(self.navigation_timeout)
+            .args(self.args.clone());
+        if !self.headless {
+            builder = builder.with_head();
+        }
+        if let Some(path) = &self.executable_path {
+            builder = builder.chrome_executable(path);
+        }
+        builder.build().map_err(BrowserError::BrowserLaunch)
+    }
+}
+
+/// A page captured after JavaScript execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedPage {
+    /// The URL that was requested
+    pub url: String,
+
+    /// The URL after redirects and client-side navigation
+    pub final_url: String,
+
+    /// The serialized post-JavaScript DOM
+    pub html: String,
+
+    /// The document title (if any)
+    pub title: Option<String>,
+
+    /// Time spent loading and rendering, in milliseconds
+    pub render_ms: u64,
+}
+
+impl RenderedPage {
+    /// Extract text content from the rendered DOM
+    pub fn text(&self) -> String {
+        strip_html_tags(&self.html)
+    }
+
+    /// Convert into a [`Page`] so the rendered DOM can be used with the
+    /// same APIs as fetched HTML.
+    ///
+    /// The browser does not expose a single HTTP status for a rendered page,
+    /// so the resulting page reports `200`.
+    pub fn into_page(self) -> Page {
+        let title = self.title.or_else(|| extract_title(&self.html));
+        Page {
+            url: self.final_url,
+            html: self.html,
+            title,
+            status: 200,
+            content_type: Some("text/html".to_string()),
+        }
+    }
+}
+
+impl From<RenderedPage> for Page {
+    fn from(rendered: RenderedPage) -> Self {
+        rendered.into_page()
+    }
+}
+
+/// Renders pages in a headless Chromium instance.
+///
+/// One browser process is shared by all renders; each call to
+/// [`render`](Self::render) opens and closes its own tab.
+pub struct HeadlessRenderer {
+    browser: Mutex<Browser>,
+    handler: JoinHandle<()>,
+    config: RenderConfig,
+}
+
+impl HeadlessRenderer {
+    /// Launch a browser for rendering.
+    pub async fn launch(config: RenderConfig) -> Result<Self> {
+        let (browser, mut handler) = Browser::launch(config.to_browser_config()?)
+            .await
+            .map_err(|e| BrowserError::BrowserLaunch(e.to_string()))?;
+
+        // The handler drives the CDP websocket and must be polled continuously
+        let handler = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            browser: Mutex::new(browser),
+            handler,
+            config,
+        })
+    }
+
+    /// Get the renderer configuration.
+    pub fn config(&self) -> &RenderConfig {
+        &self.config
+    }
+
+    /// Render a page using the configured wait-for-selector.
+    pub async fn render(&self, url: &str) -> Result<RenderedPage> {
+        self.render_with_selector(url, self.config.wait_for_selector.as_deref())
+            .await
+    }
+
+    /// Render a page, waiting for the given selector instead of the
+    /// configured one.
+    pub async fn render_with_selector(
+        &self,
+        url: &str,
+        wait_for_selector: Option<&str>,
+    ) -> Result<RenderedPage> {
+        let started = std::time::Instant::now();
+
+        let page = {
+            let browser = self.browser.lock().await;
+            browser
+                .new_page("about:blank")
+                .await
+                .map_err(|e| BrowserError::CdpProtocol(e.to_string()))?
+        };
+
+        let result = self.capture(&page, url, wait_for_selector, started).await;
+        let _ = page.close().await;
+        result
+    }
+
+    async fn capture(
+        &self,
+        page: &chromiumoxide::Page,
+        url: &str,
+        wait_for_selector: Option<&str>,
+        started: std::time::Instant,
+    ) -> Result<RenderedPage> {
+        let timeout = self.config.navigation_timeout;
+        tokio::time::timeout(timeout, async {
+            page.goto(url).await?;
+            page.wait_for_navigation().await?;
+            Ok::<_, chromiumoxide::error::CdpError>(())
+        })
+        .await
+        .map_err(|_| {
+            BrowserError::Timeout(format!("navigation to {} exceeded {:?}", url, timeout))
+        })?
+        .map_err(|e| BrowserError::Navigation(e.to_string()))?;
+
+        if let Some(selector) = wait_for_selector {
+            self.wait_for(page, selector).await?;
+        }
+
+        let html = page
+            .content()
+            .await
+            .map_err(|e| BrowserError::CdpProtocol(e.to_string()))?;
+        let title = page
+            .get_title()
+            .await
+            .map_err(|e| BrowserError::CdpProtocol(e.to_string()))?;
+        let final_url = page
+            .url()
+            .await
+            .map_err(|e| BrowserError::CdpProtocol(e.to_string()))?
+            .unwrap_or_else(|| url.to_string());
+
+        Ok(RenderedPage {
+            url: url.to_string(),
+            final_url,
+            html,
+            title,
+            render_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    async fn wait_for(&self, page: &chromiumoxide::Page, selector: &str) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.config.selector_timeout;
+        loop {
+            if page.find_element(selector).await.is_ok() {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(BrowserError::Timeout(format!(
+                    "selector '{}' did not appear within {:?}",
+                    selector, self.config.selector_timeout
+                )));
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Close the browser.
+    pub async fn close(self) -> Result<()> {
+        let mut browser = self.browser.into_inner();
+        browser
+            .close()
+            .await
+            .map_err(|e| BrowserError::CdpConnection(e.to_string()))?;
+        let _ = browser.wait().await;
+        self.handler.abort();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_config() {
+        let config = RenderConfig::new()
+            .navigation_timeout(Duration::from_secs(5))
+            .wait_for_selector("#app")
+            .selector_timeout(Duration::from_secs(2))
+            .window_size(800, 600);
+
+        assert!(config.headless);
+        assert_eq!(config.navigation_timeout, Duration::from_secs(5));
+        assert_eq!(config.wait_for_selector.as_deref(), Some("#app"));
+        assert_eq!(config.selector_timeout, Duration::from_secs(2));
+        assert_eq!(config.width, 800);
+    }
+
+    #[test]
+    fn test_rendered_page_into_page() {
+        let rendered = RenderedPage {
+            url: "https://example.com".to_string(),
+            final_url: "https://example.com/home".to_string(),
+            html: "<html><head><title>Home</title></head><body><p>Hi</p></body></html>".to_string(),
+            title: None,
+            render_ms: 12,
+        };
+
+        assert!(rendered.text().contains("Hi"));
+
+        let page: Page = rendered.into();
+        assert_eq!(page.url, "https://example.com/home");
+        assert_eq!(page.title, Some("Home".to_string()));
+        assert_eq!(page.status, 200);
+    }
+}
@@ -0,0 +1,453 @@
+//! Requests beyond plain page fetches.
+//!
+//! [`WebClient::fetch`](crate::WebClient::fetch) covers `GET`; a [`Request`]
+//! describes any other call — submitting a form, posting JSON to an API,
+//! uploading a file or deleting a resource — with headers of its own on top
+//! of the client's defaults. Sending it goes through the same cookies, rate
+//! limits and connection pool as a fetch and returns the response as a
+//! [`Page`](crate::Page).
+//!
+//! ```rust,no_run
+//! use serde_json::json;
+//! use thulp_browser::{MultipartForm, Request, WebClient};
+//!
+//! # async fn example() -> Result<(), thulp_browser::BrowserError> {
+//! let client = WebClient::new();
+//!
+//! let created = client
+//!     .send(
+//!         Request::post("https://api.example.com/items")
+//!             .header("Authorization", "Bearer token")
+//!             .json(json!({"name": "widget"})),
+//!     )
+//!     .await?;
+//! println!("{}", created.json()?["id"]);
+//!
+//! let form = MultipartForm::new()
+//!     .text("title", "Report")
+//!     .file("attachment", "report.csv", b"a,b\n1,2\n".to_vec());
+//! client
+//!     .send(Request::post("https://example.com/upload").multipart(form))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{BrowserError, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thulp_core::UrlBuilder;
+
+/// HTTP request methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+}
+
+impl Method {
+    /// Get the method name as sent on the wire.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+        }
+    }
+
+    fn to_reqwest(self) -> reqwest::Method {
+        match self {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+            Method::Head => reqwest::Method::HEAD,
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Method {
+    type Err = BrowserError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "GET" => Ok(Method::Get),
+            "POST" => Ok(Method::Post),
+            "PUT" => Ok(Method::Put),
+            "PATCH" => Ok(Method::Patch),
+            "DELETE" => Ok(Method::Delete),
+            "HEAD" => Ok(Method::Head),
+            _ => Err(BrowserError::Http(format!("unsupported method: {}", s))),
+        }
+    }
+}
+
+/// Body of a request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Body {
+    /// No body
+    #[default]
+    Empty,
+    /// Text sent with the given content type
+    Text {
+        content: String,
+        content_type: String,
+    },
+    /// Raw bytes sent as `application/octet-stream`
+    Bytes(Vec<u8>),
+    /// JSON sent as `application/json`
+    Json(serde_json::Value),
+    /// Fields sent as `application/x-www-form-urlencoded`
+    Form(Vec<(String, String)>),
+    /// Fields and files sent as `multipart/form-data`
+    Multipart(MultipartForm),
+}
+
+/// A `multipart/form-data` body
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultipartForm {
+    parts: Vec<Part>,
+}
+
+/// One field of a multipart form
+#[derive(Debug, Clone, PartialEq)]
+struct Part {
+    name: String,
+    content: Vec<u8>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl MultipartForm {
+    /// Create an empty form.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a text field.
+    pub fn text(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            content: value.into().into_bytes(),
+            file_name: None,
+            content_type: None,
+        });
+        self
+    }
+
+    /// Add a file, with its content type guessed from the file name.
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content: Vec<u8>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            content,
+            file_name: Some(file_name.into()),
+            content_type: None,
+        });
+        self
+    }
+
+    /// Add a file with an explicit content type.
+    pub fn file_with_type(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content: Vec<u8>,
+        content_type: impl Into<String>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            content,
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type.into()),
+        });
+        self
+    }
+
+    /// Read a file from disk and add it under its own file name.
+    pub async fn file_path(
+        self,
+        name: impl Into<String>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read(path)
+            .await
+            .map_err(|e| BrowserError::Http(format!("failed to read {}: {}", path.display(), e)))?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(self.file(name, file_name, content))
+    }
+
+    /// Get the number of fields and files.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Check if the form has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    fn to_reqwest(&self) -> Result<reqwest::multipart::Form> {
+        let mut form = reqwest::multipart::Form::new();
+        for part in &self.parts {
+            let mut body = reqwest::multipart::Part::bytes(part.content.clone());
+            if let Some(file_name) = &part.file_name {
+                body = body.file_name(file_name.clone());
+                let content_type = part
+                    .content_type
+                    .as_deref()
+                    .unwrap_or_else(|| guess_content_type(file_name));
+                body = body
+                    .mime_str(content_type)
+                    .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", content_type, e)))?;
+            }
+            form = form.part(part.name.clone(), body);
+        }
+        Ok(form)
+    }
+}
+
+/// Guess a file's content type from its extension
+fn guess_content_type(file_name: &str) -> &'static str {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// An HTTP request to send with [`WebClient::send`](crate::WebClient::send)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// Request method
+    pub method: Method,
+    /// Target URL
+    pub url: String,
+    /// Headers added to the client's default headers
+    pub headers: Vec<(String, String)>,
+    /// Request body
+    pub body: Body,
+    /// Timeout overriding the client's for this request
+    pub timeout: Option<Duration>,
+}
+
+impl Request {
+    /// Create a request with no body.
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: Body::Empty,
+            timeout: None,
+        }
+    }
+
+    /// Create a `GET` request.
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(Method::Get, url)
+    }
+
+    /// Create a `POST` request.
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(Method::Post, url)
+    }
+
+    /// Create a `PUT` request.
+    pub fn put(url: impl Into<String>) -> Self {
+        Self::new(Method::Put, url)
+    }
+
+    /// Create a `PATCH` request.
+    pub fn patch(url: impl Into<String>) -> Self {
+        Self::new(Method::Patch, url)
+    }
+
+    /// Create a `DELETE` request.
+    pub fn delete(url: impl Into<String>) -> Self {
+        Self::new(Method::Delete, url)
+    }
+
+    /// Create a `HEAD` request.
+    pub fn head(url: impl Into<String>) -> Self {
+        Self::new(Method::Head, url)
+    }
+
+    /// Append an encoded query parameter to the URL.
+    pub fn query(mut self, name: impl AsRef<str>, value: impl ToString) -> Self {
+        self.url = UrlBuilder::new(&self.url).query(name, value).build();
+        self
+    }
+
+    /// Add a header for this request only.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send a JSON body.
+    pub fn json(mut self, value: serde_json::Value) -> Self {
+        self.body = Body::Json(value);
+        self
+    }
+
+    /// Send URL-encoded form fields.
+    pub fn form<K, V>(mut self, fields: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.body = Body::Form(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Send a multipart form.
+    pub fn multipart(mut self, form: MultipartForm) -> Self {
+        self.body = Body::Multipart(form);
+        self
+    }
+
+    /// Send text with a content type.
+    pub fn text(mut self, content: impl Into<String>, content_type: impl Into<String>) -> Self {
+        self.body = Body::Text {
+            content: content.into(),
+            content_type: content_type.into(),
+        };
+        self
+    }
+
+    /// Send raw bytes.
+    pub fn bytes(mut self, content: Vec<u8>) -> Self {
+        self.body = Body::Bytes(content);
+        self
+    }
+
+    /// Set a timeout for this request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Build the reqwest request
+    ///
+    /// Returns [`BrowserError::InvalidHeader`] for header names or values
+    /// that aren't valid HTTP.
+    pub(crate) fn build(&self, client: &reqwest::Client) -> Result<reqwest::RequestBuilder> {
+        let mut builder = client.request(self.method.to_reqwest(), &self.url);
+        for (name, value) in &self.headers {
+            let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            let header_value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|e| BrowserError::InvalidHeader(format!("{}: {}", name, e)))?;
+            builder = builder.header(header_name, header_value);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder = match &self.body {
+            Body::Empty => builder,
+            Body::Text {
+                content,
+                content_type,
+            } => builder
+                .header(reqwest::header::CONTENT_TYPE, content_type.as_str())
+                .body(content.clone()),
+            Body::Bytes(content) => builder
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                .body(content.clone()),
+            Body::Json(value) => builder.json(value),
+            Body::Form(fields) => builder.form(fields),
+            Body::Multipart(form) => builder.multipart(form.to_reqwest()?),
+        };
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_round_trip() {
+        for method in [Method::Get, Method::Post, Method::Delete] {
+            assert_eq!(method.as_str().parse::<Method>().unwrap(), method);
+        }
+        assert_eq!("patch".parse::<Method>().unwrap(), Method::Patch);
+        assert!("TRACE".parse::<Method>().is_err());
+        assert_eq!(
+            serde_json::to_string(&Method::Put).unwrap(),
+            "\"PUT\"".to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_request_header() {
+        let client = reqwest::Client::new();
+        let request = Request::get("https://example.com").header("Bad Name", "x");
+        assert!(matches!(
+            request.build(&client),
+            Err(BrowserError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_parameters_are_encoded() {
+        let request = Request::get("https://example.com/search?lang=en")
+            .query("q", "a&b c")
+            .query("page", 2);
+        assert_eq!(
+            request.url,
+            "https://example.com/search?lang=en&q=a%26b%20c&page=2"
+        );
+    }
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type("report.CSV"), "text/csv");
+        assert_eq!(guess_content_type("photo.jpeg"), "image/jpeg");
+        assert_eq!(guess_content_type("blob"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,197 @@
+//! Parsing and matching of `robots.txt` files.
+//!
+//! Supports `User-agent`, `Allow`, `Disallow` and `Crawl-delay` lines, the
+//! `*` and `$` wildcards in paths, and the usual precedence rules: the group
+//! naming the most specific user agent applies, the longest matching rule
+//! wins, and `Allow` wins ties.
+
+use std::time::Duration;
+
+/// Rules from a `robots.txt` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsTxt {
+    groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl RobotsTxt {
+    /// Parse the contents of a `robots.txt` file.
+    ///
+    /// Unknown lines are ignored, so any text parses.
+    pub fn parse(text: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        // Consecutive User-agent lines share the rules that follow them
+        let mut collecting_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !collecting_agents {
+                        groups.push(Group::default());
+                        collecting_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    collecting_agents = false;
+                    // An empty Disallow allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    collecting_agents = false;
+                    if let (Some(group), Ok(secs)) = (groups.last_mut(), value.parse::<f64>()) {
+                        if secs.is_finite() && secs >= 0.0 {
+                            group.crawl_delay = Some(Duration::from_secs_f64(secs));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { groups }
+    }
+
+    /// Check if `user_agent` may fetch `path`.
+    ///
+    /// `path` should include the query string, if any.
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        let Some(group) = self.group_for(user_agent) else {
+            return true;
+        };
+
+        group
+            .rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .map_or(true, |rule| rule.allow)
+    }
+
+    /// The `Crawl-delay` requested for `user_agent`, if any.
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.group_for(user_agent)
+            .and_then(|group| group.crawl_delay)
+    }
+
+    /// The group naming the longest part of `user_agent`, or the `*` group.
+    fn group_for(&self, user_agent: &str) -> Option<&Group> {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let named = self
+            .groups
+            .iter()
+            .flat_map(|group| group.agents.iter().map(move |agent| (agent, group)))
+            .filter(|(agent, _)| agent.as_str() != "*" && user_agent.contains(agent.as_str()))
+            .max_by_key(|(agent, _)| agent.len())
+            .map(|(_, group)| group);
+
+        named.or_else(|| {
+            self.groups
+                .iter()
+                .find(|group| group.agents.iter().any(|agent| agent == "*"))
+        })
+    }
+}
+
+/// Match a rule pattern against a path, where `*` matches any run of
+/// characters and a trailing `$` anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let is_last = i == parts.len() - 1;
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+# Example rules
+User-agent: *
+Disallow: /private/
+Allow: /private/public-page
+Disallow: /*.pdf$
+Crawl-delay: 2
+
+User-agent: thulp
+User-agent: other-bot
+Disallow: /
+Allow: /docs
+";
+
+    #[test]
+    fn test_rules_and_precedence() {
+        let robots = RobotsTxt::parse(ROBOTS);
+
+        assert!(robots.is_allowed("mybot/1.0", "/"));
+        assert!(!robots.is_allowed("mybot/1.0", "/private/keys"));
+        assert!(robots.is_allowed("mybot/1.0", "/private/public-page"));
+        assert!(!robots.is_allowed("mybot/1.0", "/papers/report.pdf"));
+        assert!(robots.is_allowed("mybot/1.0", "/papers/report.pdf?page=2"));
+        assert_eq!(
+            robots.crawl_delay("mybot/1.0"),
+            Some(Duration::from_secs(2))
+        );
+
+        // The named group replaces the * group
+        assert!(!robots.is_allowed("Thulp/0.3", "/about"));
+        assert!(robots.is_allowed("Thulp/0.3", "/docs/intro"));
+        assert_eq!(robots.crawl_delay("thulp"), None);
+    }
+
+    #[test]
+    fn test_empty_and_missing_rules() {
+        assert!(RobotsTxt::parse("").is_allowed("any", "/anything"));
+        assert!(RobotsTxt::parse("User-agent: *\nDisallow:\n").is_allowed("any", "/x"));
+        assert!(RobotsTxt::parse("not a robots file").is_allowed("any", "/x"));
+    }
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use indicatif::ProgressBar;
+use std::io::{BufRead, IsTerminal, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use thulp_skills::{ApprovalDecision, ApprovalHandler, ApprovalRequest, AutoApprove, AutoDeny};
+
+/// Who approves skill steps marked `requires_approval`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalMode {
+    /// Ask on the terminal
+    Prompt,
+    /// Approve every step
+    Approve,
+    /// Deny every step
+    Deny,
+    /// POST each request to a URL and use its answer
+    #[cfg(feature = "remote")]
+    Webhook(String),
+}
+
+impl FromStr for ApprovalMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prompt" => Ok(Self::Prompt),
+            "approve" => Ok(Self::Approve),
+            "deny" => Ok(Self::Deny),
+            #[cfg(feature = "remote")]
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Self::Webhook(url.to_string()))
+            }
+            #[cfg(not(feature = "remote"))]
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Err("webhook approvals need the 'remote' feature".to_string())
+            }
+            other => Err(format!(
+                "unknown approval mode '{}': use prompt, approve, deny or a webhook URL",
+                other
+            )),
+        }
+    }
+}
+
+impl ApprovalMode {
+    /// Prompt when stdin is a terminal, deny otherwise
+    pub fn interactive_default() -> Self {
+        if std::io::stdin().is_terminal() {
+            Self::Prompt
+        } else {
+            Self::Deny
+        }
+    }
+
+    /// The handler for this mode; prompts hide `bar` while they wait
+    pub fn handler(&self, bar: Option<ProgressBar>) -> Arc<dyn ApprovalHandler> {
+        match self {
+            Self::Prompt => Arc::new(PromptApprovals {
+                bar,
+                lock: tokio::sync::Mutex::new(()),
+            }),
+            Self::Approve => Arc::new(AutoApprove),
+            Self::Deny => Arc::new(AutoDeny::with_reason(
+                "approvals are denied (pass --approval to change)",
+            )),
+            #[cfg(feature = "remote")]
+            Self::Webhook(url) => Arc::new(WebhookApprovals {
+                url: url.clone(),
+                client: reqwest::Client::new(),
+            }),
+        }
+    }
+}
+
+/// Asks on stderr and reads the answer from stdin, one step at a time
+struct PromptApprovals {
+    bar: Option<ProgressBar>,
+    lock: tokio::sync::Mutex<()>,
+}
+
+#[async_trait]
+impl ApprovalHandler for PromptApprovals {
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision {
+        // Steps of one wave may ask at once
+        let _turn = self.lock.lock().await;
+        let question = describe(request);
+        let bar = self.bar.clone();
+        let answer = tokio::task::spawn_blocking(move || {
+            let ask = || {
+                eprintln!("{}", question);
+                eprint!("Approve? [y/N] ");
+                let _ = std::io::stderr().flush();
+                let mut line = String::new();
+                std::io::stdin().lock().read_line(&mut line).map(|_| line)
+            };
+            match bar {
+                Some(bar) => bar.suspend(ask),
+                None => ask(),
+            }
+        })
+        .await;
+        match answer {
+            Ok(Ok(line)) if matches!(line.trim().to_lowercase().as_str(), "y" | "yes") => {
+                ApprovalDecision::Approved
+            }
+            Ok(Ok(_)) => ApprovalDecision::denied("denied at the prompt"),
+            Ok(Err(e)) => ApprovalDecision::denied(format!("couldn't read an answer: {}", e)),
+            Err(e) => ApprovalDecision::denied(format!("prompt failed: {}", e)),
+        }
+    }
+}
+
+/// The question asked for a request
+fn describe(request: &ApprovalRequest) -> String {
+    let target = match (&request.skill, request.tool.as_str()) {
+        (Some(skill), _) => format!("run skill {}", skill),
+        (None, "") => "continue".to_string(),
+        (None, tool) => format!("call {}", tool),
+    };
+    let arguments =
+        serde_json::to_string_pretty(&request.arguments).unwrap_or_else(|_| "null".to_string());
+    format!(
+        "🔐 Step '{}' needs approval to {} with:\n{}",
+        request.step, target, arguments
+    )
+}
+
+/// POSTs each request as JSON and expects `{"approved": bool, "reason": ...}`
+#[cfg(feature = "remote")]
+struct WebhookApprovals {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "remote")]
+#[async_trait]
+impl ApprovalHandler for WebhookApprovals {
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision {
+        #[derive(serde::Deserialize)]
+        struct Answer {
+            approved: bool,
+            #[serde(default)]
+            reason: Option<String>,
+        }
+
+        let answer = async {
+            self.client
+                .post(&self.url)
+                .json(request)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Answer>()
+                .await
+        }
+        .await;
+        match answer {
+            Ok(Answer { approved: true, .. }) => ApprovalDecision::Approved,
+            Ok(Answer { reason, .. }) => {
+                ApprovalDecision::denied(reason.unwrap_or_else(|| "denied by webhook".to_string()))
+            }
+            Err(e) => ApprovalDecision::denied(format!("approval webhook failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_approval_mode() {
+        assert_eq!("prompt".parse(), Ok(ApprovalMode::Prompt));
+        assert_eq!("deny".parse(), Ok(ApprovalMode::Deny));
+        assert!("sometimes".parse::<ApprovalMode>().is_err());
+        #[cfg(feature = "remote")]
+        assert_eq!(
+            "https://example.com/approve".parse(),
+            Ok(ApprovalMode::Webhook("https://example.com/approve".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let request = ApprovalRequest {
+            step: "drop".to_string(),
+            tool: "db.drop_table".to_string(),
+            skill: None,
+            arguments: json!({"table": "logs"}),
+            depth: 0,
+        };
+        assert_eq!(
+            describe(&request),
+            "🔐 Step 'drop' needs approval to call db.drop_table with:\n{\n  \"table\": \"logs\"\n}"
+        );
+    }
+}
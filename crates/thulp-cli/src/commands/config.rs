@@ -1,7 +1,12 @@
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use crate::output::Output;
+use thulp_core::ExecPolicy;
+use thulp_workspace::{ConfigExpander, Workspace, WorkspaceConfig};
+
+pub use thulp_workspace::ServerConfig;
 
 #[derive(Subcommand, Debug)]
 pub enum ConfigCommands {
@@ -57,11 +62,146 @@ pub enum ServerType {
     Http,
 }
 
+/// Path of the workspace config
+fn config_file(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".thulp").join("config.yaml")
+}
+
+/// Load the workspace config with its `${ENV}` and `${secret:name}`
+/// references expanded; `None` when the workspace has no config
+pub fn load_config(
+    workspace_dir: &Path,
+) -> Result<Option<WorkspaceConfig>, Box<dyn std::error::Error>> {
+    let workspace = Workspace::new("cli", "cli", workspace_dir.to_path_buf());
+    let config_path = workspace.config_path();
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let expander = ConfigExpander::new().with_secrets(workspace.secrets());
+    Ok(Some(WorkspaceConfig::load(&config_path, &expander)?))
+}
+
+/// Read the MCP servers configured in the workspace, by name
+pub fn load_servers(
+    workspace_dir: &Path,
+) -> Result<BTreeMap<String, ServerConfig>, Box<dyn std::error::Error>> {
+    let config = load_config(workspace_dir)?
+        .ok_or("No workspace found. Run 'thulp init' first.")?;
+    Ok(config.servers)
+}
+
+/// Read the `exec` section of the workspace config: the local commands
+/// the built-in `local.exec` tool may run, in the workspace root unless it
+/// sets a `working_dir`. `None` when the section is missing.
+pub fn load_exec_policy(
+    workspace_dir: &Path,
+) -> Result<Option<ExecPolicy>, Box<dyn std::error::Error>> {
+    let Some(mut policy) = load_config(workspace_dir)?.and_then(|config| config.exec) else {
+        return Ok(None);
+    };
+    policy.working_dir = Some(match policy.working_dir {
+        Some(dir) => workspace_dir.join(dir),
+        None => workspace_dir.to_path_buf(),
+    });
+    Ok(Some(policy))
+}
+
+/// The workspace in `workspace_dir`, with the name, `metadata` and
+/// `context` of its config, which skills read as
+/// `{{workspace.context.project_id}}`
+pub fn load_workspace(workspace_dir: &Path) -> Result<Workspace, Box<dyn std::error::Error>> {
+    let mut workspace = Workspace::new("cli", "cli", workspace_dir.to_path_buf());
+    let Some(config) = load_config(workspace_dir)? else {
+        return Ok(workspace);
+    };
+    workspace
+        .apply_config(&serde_json::to_value(&config)?)
+        .map_err(|e| format!("Invalid workspace in {}: {}", workspace.config_path().display(), e))?;
+    Ok(workspace)
+}
+
+/// Extra skill directories from `skill_paths` in the workspace config,
+/// relative to the workspace root
+pub fn configured_skill_paths(workspace_dir: &Path) -> Vec<PathBuf> {
+    WorkspaceConfig::load_raw(config_file(workspace_dir))
+        .map(|config| {
+            config
+                .skill_paths
+                .iter()
+                .map(|path| workspace_dir.join(path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Pick the server to run a tool on: the named one, or the only one
+/// configured when no name is given
+pub fn resolve_server<'a>(
+    servers: &'a BTreeMap<String, ServerConfig>,
+    name: Option<&str>,
+) -> Result<(&'a str, &'a ServerConfig), Box<dyn std::error::Error>> {
+    if let Some(name) = name {
+        return servers
+            .get_key_value(name)
+            .map(|(name, config)| (name.as_str(), config))
+            .ok_or_else(|| {
+                format!(
+                    "Server '{}' is not configured. Add it with 'thulp config add-server'.",
+                    name
+                )
+                .into()
+            });
+    }
+    let mut iter = servers.iter();
+    match (iter.next(), iter.next()) {
+        (Some((name, config)), None) => Ok((name.as_str(), config)),
+        (None, _) => {
+            Err("No MCP servers configured. Use 'thulp config add-server' first.".into())
+        }
+        _ => Err(format!(
+            "Several servers are configured ({}); name one as <server>.<tool>",
+            servers.keys().cloned().collect::<Vec<_>>().join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Whether the workspace config sets `read_only: true`
+pub fn config_read_only(workspace_dir: &Path) -> bool {
+    WorkspaceConfig::load_raw(config_file(workspace_dir))
+        .map(|config| config.read_only)
+        .unwrap_or(false)
+}
+
+/// Print unknown keys in the workspace config, which are often typos
+fn warn_unknown_keys(config: &WorkspaceConfig) {
+    for warning in config.warnings() {
+        eprintln!("⚠️  config.yaml: {}", warning);
+    }
+}
+
+/// Report a write skipped in read-only mode; returns true if it was skipped
+pub(crate) fn skip_write(read_only: bool, path: &Path, output: &Output) -> bool {
+    if read_only {
+        if output.is_json() {
+            output.print_json(&json!({
+                "status": "skipped",
+                "read_only": true,
+                "path": path.display().to_string()
+            }));
+        } else {
+            output.print_text(&format!("⚠️  Read-only mode: not writing {}", path.display()));
+        }
+    }
+    read_only
+}
+
 /// Handle `thulp init` command
 pub fn handle_init(
     dir: PathBuf,
     name: Option<String>,
     force: bool,
+    read_only: bool,
     output: &Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let thulp_dir = dir.join(".thulp");
@@ -83,6 +223,10 @@ pub fn handle_init(
         return Ok(());
     }
 
+    if skip_write(read_only, &thulp_dir, output) {
+        return Ok(());
+    }
+
     // Create directory structure
     std::fs::create_dir_all(&thulp_dir)?;
     std::fs::create_dir_all(thulp_dir.join("skills"))?;
@@ -98,19 +242,16 @@ pub fn handle_init(
     });
 
     // Create config.yaml
-    let config = json!({
-        "name": workspace_name,
-        "version": "1.0",
-        "servers": {},
-        "settings": {
-            "default_timeout": 30,
-            "max_retries": 3
-        }
-    });
-
-    let config_path = thulp_dir.join("config.yaml");
-    let config_yaml = serde_yaml::to_string(&config)?;
-    std::fs::write(&config_path, config_yaml)?;
+    let config = WorkspaceConfig {
+        name: Some(workspace_name.clone()),
+        version: Some("1.0".to_string()),
+        settings: BTreeMap::from([
+            ("default_timeout".to_string(), json!(30)),
+            ("max_retries".to_string(), json!(3)),
+        ]),
+        ..Default::default()
+    };
+    config.save(thulp_dir.join("config.yaml"))?;
 
     // Create .gitignore
     let gitignore = "sessions/\ncache/\n*.log\n";
@@ -158,69 +299,55 @@ pub fn handle_init(
 pub fn handle_config_commands(
     command: ConfigCommands,
     workspace_dir: &Path,
+    read_only: bool,
     output: &Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let config_path = workspace_dir.join(".thulp/config.yaml");
+    let config_path = config_file(workspace_dir);
+    if !config_path.exists() {
+        if matches!(command, ConfigCommands::Show | ConfigCommands::Servers) {
+            if output.is_json() {
+                output.print_json(&json!({"error": "not_initialized"}));
+            } else {
+                output.print_text("❌ No workspace found. Run 'thulp init' first.");
+            }
+            return Ok(());
+        }
+        return Err("No workspace found. Run 'thulp init' first.".into());
+    }
+
+    // Edits keep `${...}` references as written rather than their values
+    let mut config = WorkspaceConfig::load_raw(&config_path)?;
 
     match command {
         ConfigCommands::Show => {
-            if config_path.exists() {
-                let content = std::fs::read_to_string(&config_path)?;
-                if output.is_json() {
-                    let parsed: serde_json::Value = serde_yaml::from_str(&content)?;
-                    output.print_json(&parsed);
-                } else {
-                    output.print_text(&content);
-                }
+            warn_unknown_keys(&config);
+            if output.is_json() {
+                output.print_json(&serde_json::to_value(&config)?);
             } else {
-                if output.is_json() {
-                    output.print_json(&json!({"error": "not_initialized"}));
-                } else {
-                    output.print_text("❌ No workspace found. Run 'thulp init' first.");
-                }
+                output.print_text(&std::fs::read_to_string(&config_path)?);
             }
         }
         ConfigCommands::Get { key } => {
-            if config_path.exists() {
-                let content = std::fs::read_to_string(&config_path)?;
-                let config: serde_json::Value = serde_yaml::from_str(&content)?;
-
-                let parts: Vec<&str> = key.split('.').collect();
-                let mut current = &config;
-                for part in &parts {
-                    if let Some(next) = current.get(part) {
-                        current = next;
-                    } else {
-                        return Err(format!("Key not found: {}", key).into());
-                    }
-                }
-
-                if output.is_json() {
-                    output.print_json(current);
-                } else {
-                    output.print_text(&serde_json::to_string_pretty(current)?);
-                }
+            warn_unknown_keys(&config);
+            let value = config.get(&key).ok_or_else(|| format!("Key not found: {}", key))?;
+            if output.is_json() {
+                output.print_json(&value);
             } else {
-                return Err("No workspace found. Run 'thulp init' first.".into());
+                output.print_text(&serde_json::to_string_pretty(&value)?);
             }
         }
         ConfigCommands::Set { key, value } => {
-            if !config_path.exists() {
-                return Err("No workspace found. Run 'thulp init' first.".into());
-            }
-
-            let content = std::fs::read_to_string(&config_path)?;
-            let mut config: serde_json::Value = serde_yaml::from_str(&content)?;
-
             let parsed_value: serde_json::Value = serde_json::from_str(&value)
                 .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
 
-            if let Some(obj) = config.as_object_mut() {
-                obj.insert(key.clone(), parsed_value);
+            // Turning read-only mode off has to stay possible from the CLI
+            if key != "read_only" && skip_write(read_only, &config_path, output) {
+                return Ok(());
             }
 
-            let updated = serde_yaml::to_string(&config)?;
-            std::fs::write(&config_path, updated)?;
+            config.set(&key, parsed_value)?;
+            warn_unknown_keys(&config);
+            config.save(&config_path)?;
 
             if output.is_json() {
                 output.print_json(&json!({"status": "updated", "key": key}));
@@ -234,34 +361,17 @@ pub fn handle_config_commands(
             target,
             args,
         } => {
-            if !config_path.exists() {
-                return Err("No workspace found. Run 'thulp init' first.".into());
+            if skip_write(read_only, &config_path, output) {
+                return Ok(());
             }
 
-            let content = std::fs::read_to_string(&config_path)?;
-            let mut config: serde_json::Value = serde_yaml::from_str(&content)?;
-
-            let server_config = match r#type {
-                ServerType::Stdio => json!({
-                    "type": "stdio",
-                    "command": target,
-                    "args": args
-                }),
-                ServerType::Http => json!({
-                    "type": "http",
-                    "url": target
-                }),
+            let server = match r#type {
+                ServerType::Stdio => ServerConfig::stdio(target, args),
+                ServerType::Http => ServerConfig::http(target),
             };
-
-            if let Some(obj) = config.as_object_mut() {
-                let servers = obj.entry("servers").or_insert_with(|| json!({}));
-                if let Some(servers_obj) = servers.as_object_mut() {
-                    servers_obj.insert(name.clone(), server_config);
-                }
-            }
-
-            let updated = serde_yaml::to_string(&config)?;
-            std::fs::write(&config_path, updated)?;
+            config.add_server(name.clone(), server)?;
+            warn_unknown_keys(&config);
+            config.save(&config_path)?;
 
             if output.is_json() {
                 output.print_json(&json!({"status": "added", "server": name}));
@@ -270,35 +380,14 @@ pub fn handle_config_commands(
             }
         }
         ConfigCommands::Servers => {
-            if config_path.exists() {
-                let content = std::fs::read_to_string(&config_path)?;
-                let config: serde_json::Value = serde_yaml::from_str(&content)?;
-
-                let servers = config.get("servers").cloned().unwrap_or(json!({}));
-
-                if output.is_json() {
-                    output.print_json(&servers);
-                } else {
-                    if let Some(obj) = servers.as_object() {
-                        if obj.is_empty() {
-                            output.print_text("No servers configured.");
-                        } else {
-                            output.print_text("Configured servers:");
-                            for (name, config) in obj {
-                                let server_type = config
-                                    .get("type")
-                                    .and_then(|t| t.as_str())
-                                    .unwrap_or("unknown");
-                                output.print_text(&format!("  🔌 {} ({})", name, server_type));
-                            }
-                        }
-                    }
-                }
+            if output.is_json() {
+                output.print_json(&serde_json::to_value(&config.servers)?);
+            } else if config.servers.is_empty() {
+                output.print_text("No servers configured.");
             } else {
-                if output.is_json() {
-                    output.print_json(&json!({"error": "not_initialized"}));
-                } else {
-                    output.print_text("❌ No workspace found. Run 'thulp init' first.");
+                output.print_text("Configured servers:");
+                for (name, server) in &config.servers {
+                    output.print_text(&format!("  🔌 {} ({})", name, server.kind()));
                 }
             }
         }
@@ -306,3 +395,50 @@ pub fn handle_config_commands(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn test_config_commands_edit_typed_config() {
+        let temp = std::env::temp_dir().join(format!("thulp-config-test-{}", std::process::id()));
+        let output = Output::new(OutputFormat::Json);
+        handle_init(temp.clone(), Some("demo".to_string()), true, false, &output).unwrap();
+        let config_path = config_file(&temp);
+        let mut raw = std::fs::read_to_string(&config_path).unwrap();
+        raw.push_str("env:\n  MCP_HOST: localhost:9000\n");
+        std::fs::write(&config_path, raw).unwrap();
+
+        let set = |key: &str, value: &str| {
+            let command = ConfigCommands::Set {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            handle_config_commands(command, &temp, false, &output)
+        };
+        set("settings.max_retries", "5").unwrap();
+        let add = ConfigCommands::AddServer {
+            name: "web".to_string(),
+            r#type: ServerType::Http,
+            target: "http://${MCP_HOST}/mcp".to_string(),
+            args: vec![],
+        };
+        handle_config_commands(add, &temp, false, &output).unwrap();
+
+        // Invalid values are rejected without touching the file
+        assert!(set("version_policy", "sometimes").is_err());
+        assert!(set("servers.web.type", "ftp").is_err());
+
+        let raw = WorkspaceConfig::load_raw(&config_path).unwrap();
+        assert_eq!(raw.settings["max_retries"], json!(5));
+        assert_eq!(raw.get("servers.web.url"), Some(json!("http://${MCP_HOST}/mcp")));
+        assert_eq!(raw.version_policy, None);
+
+        let servers = load_servers(&temp).unwrap();
+        assert_eq!(servers["web"], ServerConfig::http("http://localhost:9000/mcp"));
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+}
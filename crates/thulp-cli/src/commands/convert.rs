@@ -1,7 +1,8 @@
 use clap::Subcommand;
 use serde_json::json;
 use std::path::PathBuf;
-use thulp_adapter::AdapterGenerator;
+use thulp_adapter::{import_tools_str, AdapterGenerator};
+use thulp_core::ToolNameRules;
 use crate::output::Output;
 
 #[derive(Subcommand, Debug)]
@@ -13,6 +14,20 @@ pub enum ConvertCommands {
         /// Output file for generated config
         #[arg(long, value_name = "FILE")]
         out: Option<PathBuf>,
+        /// Convert camelCase operation IDs to snake_case tool names
+        #[arg(long)]
+        snake_case: bool,
+        /// Prefix for the tool names
+        #[arg(long, value_name = "PREFIX")]
+        prefix: Option<String>,
+    },
+    /// Import an OpenAI, LangChain, Anthropic or MCP tool manifest
+    Manifest {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        /// Write the imported tool definitions as JSON
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
     },
     /// Show conversion examples
     Examples,
@@ -27,6 +42,8 @@ pub fn handle_convert_commands(
         ConvertCommands::OpenApi {
             file,
             out: output_file,
+            snake_case,
+            prefix,
         } => {
             let spec_content = std::fs::read_to_string(&file)?;
 
@@ -42,7 +59,13 @@ pub fn handle_convert_commands(
                     .map_err(|e| format!("Failed to parse spec (tried JSON and YAML): {}", e))?
             };
 
-            let generator = AdapterGenerator::new(spec, Some("api-adapter".to_string()));
+            let tool_names = ToolNameRules {
+                snake_case,
+                prefix,
+                ..Default::default()
+            };
+            let generator = AdapterGenerator::new(spec, Some("api-adapter".to_string()))
+                .with_tool_names(tool_names);
             let tools = generator
                 .generate_tools()
                 .map_err(|e| format!("Failed to generate tools: {}", e))?;
@@ -90,6 +113,45 @@ pub fn handle_convert_commands(
                 output.print_text("✅ Conversion complete");
             }
         }
+        ConvertCommands::Manifest {
+            file,
+            out: output_file,
+        } => {
+            let content = std::fs::read_to_string(&file)?;
+            let tools = import_tools_str(&content)
+                .map_err(|e| format!("Failed to import manifest: {}", e))?;
+
+            if output.is_json() {
+                output.print_json(&json!({
+                    "source": file.display().to_string(),
+                    "tools_imported": tools.len(),
+                    "tools": tools.iter().map(|t| json!({
+                        "name": t.name,
+                        "description": t.description,
+                        "parameter_count": t.parameters.len()
+                    })).collect::<Vec<_>>()
+                }));
+            } else {
+                output.print_text(&format!("Imported {} tool definitions", tools.len()));
+                for tool in &tools {
+                    output.print_text(&format!(
+                        "  - {}: {} parameters",
+                        tool.name,
+                        tool.parameters.len()
+                    ));
+                }
+            }
+
+            if let Some(output_path) = output_file {
+                std::fs::write(&output_path, serde_json::to_string_pretty(&tools)?)?;
+                if !output.is_json() {
+                    output.print_text(&format!(
+                        "✅ Tool definitions written to: {}",
+                        output_path.display()
+                    ));
+                }
+            }
+        }
         ConvertCommands::Examples => {
             if output.is_json() {
                 let example_spec = json!({
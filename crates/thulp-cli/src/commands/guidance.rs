@@ -0,0 +1,170 @@
+use clap::Subcommand;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use crate::output::Output;
+use thulp_guidance::snapshot::{self, SnapshotTester};
+use thulp_guidance::{GuidanceDefaults, TemplateRegistry, UsageStats};
+
+#[derive(Subcommand, Debug)]
+pub enum GuidanceCommands {
+    /// Render templates with fixture variables and compare against snapshots
+    Test {
+        /// Template directory (default: .thulp/prompts in the workspace)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Fixture directory (default: tests/ in the template directory)
+        #[arg(long, value_name = "DIR")]
+        fixtures: Option<PathBuf>,
+
+        /// Write missing and changed snapshots instead of failing
+        #[arg(short, long)]
+        update: bool,
+    },
+
+    /// Report how often each template was rendered and how often it failed
+    Usage {
+        /// Template directory, so unused templates are listed too
+        /// (default: .thulp/prompts in the workspace)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+}
+
+pub fn handle_guidance_commands(
+    action: GuidanceCommands,
+    workspace_dir: &Path,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        GuidanceCommands::Test { dir, fixtures, update } => {
+            let dir = dir.unwrap_or_else(|| workspace_dir.join(".thulp").join("prompts"));
+            let fixtures = fixtures.unwrap_or_else(|| dir.join("tests"));
+            run_snapshot_tests(workspace_dir, &dir, &fixtures, update, output)
+        }
+        GuidanceCommands::Usage { dir } => {
+            let dir = dir.unwrap_or_else(|| workspace_dir.join(".thulp").join("prompts"));
+            print_usage_report(workspace_dir, &dir, output)
+        }
+    }
+}
+
+/// Print the usage recorded in the workspace's usage file, including
+/// templates in `dir` that were never rendered
+fn print_usage_report(
+    workspace_dir: &Path,
+    dir: &Path,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = UsageStats::workspace_path(workspace_dir);
+    let stats = UsageStats::load(&path)
+        .map_err(|e| format!("Failed to load template usage: {}", e))?;
+    let templates = if dir.exists() {
+        TemplateRegistry::load_dir(dir)
+            .map_err(|e| format!("Failed to load templates from {}: {}", dir.display(), e))?
+            .list()
+    } else {
+        Vec::new()
+    };
+    let report = stats.report(templates);
+
+    if output.is_json() {
+        output.print_json(&report);
+    } else if report.templates.is_empty() {
+        output.print_text(&format!("No template usage recorded in {}", path.display()));
+    } else {
+        output.print_text(&report.to_string());
+    }
+    Ok(())
+}
+
+/// Check templates in `dir` against the snapshots of the fixtures in
+/// `fixtures`, failing if any case doesn't match. Templates render with the
+/// workspace's guidance defaults.
+fn run_snapshot_tests(
+    workspace_dir: &Path,
+    dir: &Path,
+    fixtures: &Path,
+    update: bool,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let defaults = GuidanceDefaults::load_workspace(workspace_dir)
+        .map_err(|e| format!("Failed to load guidance defaults: {}", e))?;
+    let registry = TemplateRegistry::load_dir(dir)
+        .map_err(|e| format!("Failed to load templates from {}: {}", dir.display(), e))?
+        .with_defaults(defaults);
+    let cases = snapshot::load_cases(fixtures)
+        .map_err(|e| format!("Failed to load fixtures from {}: {}", fixtures.display(), e))?;
+    let report = SnapshotTester::new(&registry, fixtures.join("snapshots"))
+        .update(update)
+        .run(&cases)?;
+
+    if output.is_json() {
+        output.print_json(&json!({
+            "success": report.is_success(),
+            "results": report.results,
+        }));
+    } else {
+        output.print_text(&report.to_string());
+    }
+
+    let failed = report.failures().count();
+    if failed > 0 {
+        return Err(format!(
+            "{} snapshot case(s) failed; rerun with --update to accept the new output",
+            failed
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn test_run_snapshot_tests() {
+        let temp = std::env::temp_dir().join(format!("thulp-guidance-test-{}", std::process::id()));
+        let fixtures = temp.join("tests");
+        std::fs::create_dir_all(&fixtures).unwrap();
+        std::fs::write(temp.join("greet.prompt"), "Hello {{name}}{{mark}}\n").unwrap();
+        let defaults = GuidanceDefaults::workspace_path(&temp);
+        std::fs::create_dir_all(defaults.parent().unwrap()).unwrap();
+        std::fs::write(&defaults, "variables:\n  mark: \"!\"\n").unwrap();
+        std::fs::write(
+            fixtures.join("greet.yaml"),
+            "template: greet\ncases:\n  world:\n    name: World\n",
+        )
+        .unwrap();
+        let output = Output::new(OutputFormat::Json);
+
+        assert!(run_snapshot_tests(&temp, &temp, &fixtures, false, &output).is_err());
+        run_snapshot_tests(&temp, &temp, &fixtures, true, &output).unwrap();
+        run_snapshot_tests(&temp, &temp, &fixtures, false, &output).unwrap();
+        let snapshot = fixtures.join("snapshots").join("greet").join("world.snap");
+        assert_eq!(std::fs::read_to_string(snapshot).unwrap(), "Hello World!\n");
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[test]
+    fn test_print_usage_report() {
+        let temp = std::env::temp_dir().join(format!("thulp-guidance-usage-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+        let output = Output::new(OutputFormat::Json);
+        print_usage_report(&temp, &temp.join("missing"), &output).unwrap();
+
+        std::fs::write(temp.join("greet.prompt"), "Hello {{name}}!\n").unwrap();
+        let registry = TemplateRegistry::load_dir(&temp).unwrap();
+        registry.render("greet", &Default::default()).unwrap_err();
+        registry.usage().save(UsageStats::workspace_path(&temp)).unwrap();
+        print_usage_report(&temp, &temp, &output).unwrap();
+
+        std::fs::write(UsageStats::workspace_path(&temp), "[]").unwrap();
+        assert!(print_usage_report(&temp, &temp, &output).is_err());
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+}
@@ -34,8 +34,6 @@ pub enum McpCommands {
     Status,
 }
 
-#[derive(Subcommand, Debug)]
-
 pub async fn handle_mcp_commands(
     command: McpCommands,
     output: &Output,
@@ -103,16 +101,23 @@ pub async fn handle_mcp_commands(
                 None => json!({}),
             };
 
+            let result = output.truncate_result(&serde_json::Value::Null);
+
             if output.is_json() {
                 output.print_json(&json!({
                     "tool": name,
                     "status": "completed",
-                    "result": null
+                    "result": result.value,
+                    "truncated": result.truncated
                 }));
             } else {
                 output.print_text(&format!("Calling tool '{}' on MCP server...", name));
                 output.print_text("✅ Tool call completed");
+                if !result.value.is_null() {
+                    output.print_text(&serde_json::to_string_pretty(&result.value)?);
+                }
             }
+            output.print_truncation_hint(&result);
         }
         McpCommands::Status => {
             if output.is_json() {
@@ -1,7 +1,15 @@
 pub mod config;
+#[cfg(feature = "adapter")]
 pub mod convert;
+pub mod guidance;
+#[cfg(feature = "repl")]
+pub mod repl;
+pub mod secrets;
 pub mod skill;
 pub mod tools;
+pub mod update;
 
 #[cfg(feature = "mcp")]
 pub mod mcp;
+#[cfg(feature = "mcp")]
+pub mod serve;
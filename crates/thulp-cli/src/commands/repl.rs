@@ -0,0 +1,574 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::approval::ApprovalMode;
+use crate::commands::{config, skill, tools};
+use crate::commands::tools::BoxedTransport;
+use crate::output::Output;
+use crate::progress::ProgressMode;
+use thulp_core::Transport;
+use thulp_skill_files::paths;
+
+/// Meta-commands, completed along with tool and skill names
+const META_COMMANDS: &[&str] = &["/help", "/tools", "/skills", "/results", "/quit"];
+
+const HELP: &str = "\
+Commands:
+  <server.tool> key=value ...   Call a tool (or pass a JSON object)
+  <skill> key=value ...         Run a skill workflow
+  /tools                        List the tools of the connected servers
+  /skills                       List skills with a skill.yaml workflow
+  /results                      List the results so far
+  /help                         Show this help
+  /quit                         Leave (or press Ctrl-D)
+
+Arguments can refer to earlier results: $1 is the first result, $last the
+latest, and $2.items.0 a path into one.";
+
+/// A line typed at the prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplCommand {
+    /// Nothing to do
+    Empty,
+    Help,
+    Tools,
+    Skills,
+    Results,
+    Quit,
+    /// Call a tool or run a skill
+    Invoke {
+        name: String,
+        arguments: serde_json::Value,
+    },
+}
+
+impl ReplCommand {
+    /// Parse a line: a meta-command, or a tool or skill name followed by
+    /// key=value pairs or a JSON object
+    pub fn parse(line: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let line = line.trim();
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim()));
+        let command = match name {
+            "" => Self::Empty,
+            "/help" | "/?" => Self::Help,
+            "/tools" => Self::Tools,
+            "/skills" => Self::Skills,
+            "/results" => Self::Results,
+            "/quit" | "/exit" => Self::Quit,
+            meta if meta.starts_with('/') => {
+                return Err(format!("Unknown command '{}'; try /help", meta).into())
+            }
+            name => {
+                let arguments = if rest.starts_with('{') {
+                    tools::parse_arguments(Vec::new(), Some(rest.to_string()))?
+                } else {
+                    tools::parse_arguments(split_words(rest)?, None)?
+                };
+                Self::Invoke {
+                    name: name.to_string(),
+                    arguments,
+                }
+            }
+        };
+        Ok(command)
+    }
+}
+
+/// Split on whitespace, keeping quoted words together
+fn split_words(line: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unclosed quote".into());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Results of earlier commands, referenced as `$1`, `$2`, ... and `$last`
+#[derive(Debug, Default)]
+pub struct ReplContext {
+    results: Vec<serde_json::Value>,
+}
+
+impl ReplContext {
+    /// Store a result, returning its number
+    pub fn push(&mut self, result: serde_json::Value) -> usize {
+        self.results.push(result);
+        self.results.len()
+    }
+
+    /// Replace strings that are exactly a reference, like `$1` or
+    /// `$last.items.0`, with the value they point to. Other strings,
+    /// such as `$5 each`, are left alone.
+    pub fn resolve(
+        &self,
+        value: serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        Ok(match value {
+            serde_json::Value::String(text) => match parse_reference(&text) {
+                Some((index, path)) => self.lookup(&text, index, path)?,
+                None => serde_json::Value::String(text),
+            },
+            serde_json::Value::Array(items) => serde_json::Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.resolve(item))
+                    .collect::<Result<_, _>>()?,
+            ),
+            serde_json::Value::Object(fields) => serde_json::Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, self.resolve(value)?)))
+                    .collect::<Result<_, Box<dyn std::error::Error>>>()?,
+            ),
+            other => other,
+        })
+    }
+
+    fn lookup(
+        &self,
+        reference: &str,
+        number: Option<usize>,
+        path: Option<&str>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let index = match number {
+            Some(number) => number.checked_sub(1),
+            None => self.results.len().checked_sub(1),
+        };
+        let mut value = index
+            .and_then(|index| self.results.get(index))
+            .ok_or_else(|| format!("No result {}", reference))?;
+        for segment in path.into_iter().flat_map(|path| path.split('.')) {
+            value = match value {
+                serde_json::Value::Array(items) => {
+                    segment.parse::<usize>().ok().and_then(|i| items.get(i))
+                }
+                serde_json::Value::Object(fields) => fields.get(segment),
+                _ => None,
+            }
+            .ok_or_else(|| format!("'{}' not found in {}", segment, reference))?;
+        }
+        Ok(value.clone())
+    }
+}
+
+/// Split `$N.path` or `$last.path` into the result number (`None` for
+/// `$last`) and path
+fn parse_reference(text: &str) -> Option<(Option<usize>, Option<&str>)> {
+    let rest = text.strip_prefix('$')?;
+    let (head, path) = rest
+        .split_once('.')
+        .map_or((rest, None), |(head, path)| (head, Some(path)));
+    if path.is_some_and(|path| path.is_empty() || path.contains(char::is_whitespace)) {
+        return None;
+    }
+    match head {
+        "last" => Some((None, path)),
+        number if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+            Some((Some(number.parse().ok()?), path))
+        }
+        _ => None,
+    }
+}
+
+/// Completes tool and skill names and meta-commands at the start of a line
+#[derive(Helper, Highlighter, Hinter, Validator)]
+pub struct ReplHelper {
+    names: Vec<String>,
+}
+
+impl ReplHelper {
+    pub fn new(tools: &[String], skills: &[String]) -> Self {
+        let mut names: Vec<String> = META_COMMANDS.iter().map(|c| c.to_string()).collect();
+        names.extend(tools.iter().cloned());
+        names.extend(skills.iter().cloned());
+        Self { names }
+    }
+
+    /// Names starting with `prefix`
+    pub fn candidates(&self, prefix: &str) -> Vec<&str> {
+        self.names
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let prefix = &line[..pos];
+        if prefix.contains(char::is_whitespace) {
+            return Ok((pos, Vec::new()));
+        }
+        let pairs = self
+            .candidates(prefix)
+            .into_iter()
+            .map(|name| Pair {
+                display: name.to_string(),
+                replacement: name.to_string(),
+            })
+            .collect();
+        Ok((0, pairs))
+    }
+}
+
+/// Handle `thulp repl`: read commands until `/quit`, keeping the
+/// workspace's MCP servers connected in between
+pub async fn handle_repl(
+    workspace_dir: &Path,
+    timeout: Option<u64>,
+    read_only: bool,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let servers = config::load_servers(workspace_dir)?;
+    let exec = config::load_exec_policy(workspace_dir)?;
+    if servers.is_empty() && exec.is_none() {
+        output.print_text("⚠️  No MCP servers configured; add one with 'thulp config add-server'");
+    }
+    let sessions = skill::RunSessions::open(workspace_dir, read_only).await?;
+    let notifications = sessions.as_ref().map(skill::RunSessions::notification_sink);
+    let transport = match tools::workspace_transport(&servers, exec, notifications) {
+        Ok(mut transport) => {
+            transport
+                .connect()
+                .await
+                .map_err(|e| format!("Failed to connect to MCP servers: {}", e))?;
+            Some(Arc::new(BoxedTransport(transport)))
+        }
+        Err(e) => {
+            output.print_text(&format!("⚠️  {}", e));
+            None
+        }
+    };
+    let tool_names: Vec<String> = match &transport {
+        Some(transport) => transport.list_tools().await?.into_iter().map(|t| t.name).collect(),
+        None => Vec::new(),
+    };
+    let skill_names = skill::workflow_names(workspace_dir);
+
+    let mut editor: Editor<ReplHelper, _> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper::new(&tool_names, &skill_names)));
+    let history = (!read_only)
+        .then(|| history_path(workspace_dir))
+        .flatten();
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    output.print_text(&format!(
+        "thulp {} - {} tools, {} skills. Type /help for commands.",
+        env!("CARGO_PKG_VERSION"),
+        tool_names.len(),
+        skill_names.len()
+    ));
+
+    let mut repl = Repl {
+        workspace_dir,
+        transport: transport.clone(),
+        sessions,
+        tool_names,
+        skill_names,
+        context: ReplContext::default(),
+        tool_timeout: tools::tool_timeout(workspace_dir, timeout)?,
+        timeout: timeout.map(Duration::from_secs),
+        read_only,
+        output,
+    };
+    loop {
+        let line = match tokio::task::block_in_place(|| editor.readline("thulp> ")) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+        match ReplCommand::parse(&line) {
+            Ok(ReplCommand::Quit) => break,
+            Ok(command) => {
+                if let Err(e) = repl.execute(command).await {
+                    eprintln!("❌ {}", e);
+                }
+            }
+            Err(e) => eprintln!("❌ {}", e),
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    drop(repl);
+    if let Some(mut transport) = transport.and_then(|t| Arc::try_unwrap(t).ok()) {
+        if let Err(e) = transport.disconnect().await {
+            eprintln!("⚠️  Failed to disconnect from MCP servers: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// History file in the workspace's `.thulp` directory, if there is one
+fn history_path(workspace_dir: &Path) -> Option<PathBuf> {
+    let dir = paths::thulp_dir(workspace_dir);
+    dir.is_dir().then(|| dir.join("repl_history"))
+}
+
+struct Repl<'a> {
+    workspace_dir: &'a Path,
+    transport: Option<Arc<BoxedTransport>>,
+    /// Where skill runs are recorded; `None` in read-only mode
+    sessions: Option<skill::RunSessions>,
+    tool_names: Vec<String>,
+    skill_names: Vec<String>,
+    context: ReplContext,
+    /// Timeout of tool calls
+    tool_timeout: Duration,
+    /// Timeout given on the command line, over the workspace settings
+    timeout: Option<Duration>,
+    read_only: bool,
+    output: &'a Output,
+}
+
+impl Repl<'_> {
+    async fn execute(&mut self, command: ReplCommand) -> Result<(), Box<dyn std::error::Error>> {
+        let output = self.output;
+        match command {
+            ReplCommand::Empty | ReplCommand::Quit => {}
+            ReplCommand::Help => output.print_text(HELP),
+            ReplCommand::Tools => print_names("tools", &self.tool_names, output),
+            ReplCommand::Skills => print_names("skills", &self.skill_names, output),
+            ReplCommand::Results => {
+                for (i, result) in self.context.results.iter().enumerate() {
+                    self.print_result(i + 1, result);
+                }
+            }
+            ReplCommand::Invoke { name, arguments } => {
+                let arguments = self.context.resolve(arguments)?;
+                let result = if self.tool_names.contains(&name) {
+                    self.call_tool(&name, arguments).await?
+                } else if self.skill_names.contains(&name) {
+                    self.run_skill(&name, arguments).await?
+                } else {
+                    return Err(format!("Unknown tool or skill '{}'; see /tools and /skills", name).into());
+                };
+                let number = self.context.push(result);
+                self.print_result(number, &self.context.results[number - 1]);
+            }
+        }
+        Ok(())
+    }
+
+    fn transport(&self) -> Result<&Arc<BoxedTransport>, Box<dyn std::error::Error>> {
+        self.transport
+            .as_ref()
+            .ok_or_else(|| "No MCP servers connected".into())
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let transport = self.transport()?;
+        let server = name.split_once('.').map_or(name, |(server, _)| server);
+        let result = tools::call_tool(
+            transport.as_ref(),
+            server,
+            name,
+            arguments,
+            tools::CallOptions {
+                timeout: self.tool_timeout,
+                stream: false,
+                read_only: self.read_only,
+            },
+            self.output,
+        )
+        .await?;
+        if !result.is_success() {
+            return Err(format!(
+                "Tool '{}' failed: {}",
+                name,
+                result.error.as_deref().unwrap_or("unknown error")
+            )
+            .into());
+        }
+        Ok(result.data.unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn run_skill(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let transport = self.transport()?.clone();
+        let workflow = skill::load_skill_workflow(self.workspace_dir, name)?;
+        let inputs: HashMap<String, serde_json::Value> = match arguments {
+            serde_json::Value::Object(map) => map.into_iter().collect(),
+            _ => return Err("Skill inputs must be a JSON object".into()),
+        };
+        let config = skill::run_config(
+            self.workspace_dir,
+            workflow.steps.len(),
+            self.timeout,
+            self.read_only,
+        )?;
+        let workspace = config::load_workspace(self.workspace_dir)?;
+        let session = match &self.sessions {
+            Some(sessions) => Some((sessions, sessions.begin(&workflow).await?)),
+            None => None,
+        };
+        let (outcome, approvals) = skill::execute_skill(
+            transport,
+            &workflow,
+            inputs.clone(),
+            config,
+            &workspace,
+            &ApprovalMode::Prompt,
+            ProgressMode::for_output(self.output),
+        )
+        .await;
+        if let Some((sessions, id)) = session {
+            sessions.record(&id, &workflow, &inputs, &outcome, &approvals).await?;
+        }
+        let result = outcome?;
+        if !result.success {
+            return Err(format!(
+                "Skill '{}' failed: {}",
+                name,
+                result.error.as_deref().unwrap_or("unknown error")
+            )
+            .into());
+        }
+        Ok(result.output.unwrap_or(serde_json::Value::Null))
+    }
+
+    fn print_result(&self, number: usize, result: &serde_json::Value) {
+        let data = self.output.truncate_result(result);
+        if self.output.is_json() {
+            self.output.print_json(&json!({
+                "result": number,
+                "value": data.value,
+                "truncated": data.truncated
+            }));
+        } else {
+            let text = match &data.value {
+                serde_json::Value::String(text) => text.clone(),
+                value => serde_json::to_string_pretty(value).unwrap_or_default(),
+            };
+            self.output.print_text(&format!("${} = {}", number, text));
+        }
+        self.output.print_truncation_hint(&data);
+    }
+}
+
+fn print_names(kind: &str, names: &[String], output: &Output) {
+    if output.is_json() {
+        output.print_json(&json!({ kind: names }));
+    } else if names.is_empty() {
+        output.print_text(&format!("No {} available.", kind));
+    } else {
+        for name in names {
+            output.print_text(&format!("  {}", name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(ReplCommand::parse("  ").unwrap(), ReplCommand::Empty);
+        assert_eq!(ReplCommand::parse("/tools").unwrap(), ReplCommand::Tools);
+        assert_eq!(ReplCommand::parse("/exit").unwrap(), ReplCommand::Quit);
+        assert!(ReplCommand::parse("/nope").is_err());
+
+        assert_eq!(
+            ReplCommand::parse(r#"github.search query="rust async" limit=5 from=$last"#).unwrap(),
+            ReplCommand::Invoke {
+                name: "github.search".to_string(),
+                arguments: json!({"query": "rust async", "limit": 5, "from": "$last"}),
+            }
+        );
+        assert_eq!(
+            ReplCommand::parse(r#"summarize {"text": "$1.body"}"#).unwrap(),
+            ReplCommand::Invoke {
+                name: "summarize".to_string(),
+                arguments: json!({"text": "$1.body"}),
+            }
+        );
+        assert!(ReplCommand::parse("echo text=\"open").is_err());
+        assert!(ReplCommand::parse("echo text").is_err());
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let mut context = ReplContext::default();
+        assert!(context.resolve(json!("$last")).is_err());
+        context.push(json!({"items": [{"id": 7}]}));
+        context.push(json!("second"));
+
+        let resolved = context
+            .resolve(json!({"id": "$1.items.0.id", "all": ["$last", "$2"], "cost": "$5 each"}))
+            .unwrap();
+        assert_eq!(
+            resolved,
+            json!({"id": 7, "all": ["second", "second"], "cost": "$5 each"})
+        );
+        assert!(context.resolve(json!("$3")).is_err());
+        assert!(context.resolve(json!("$1.missing")).is_err());
+    }
+
+    #[test]
+    fn test_completion_candidates() {
+        let helper = ReplHelper::new(
+            &["github.search".to_string(), "github.issues".to_string()],
+            &["summarize".to_string()],
+        );
+        assert_eq!(helper.candidates("github.s"), ["github.search"]);
+        assert_eq!(helper.candidates("/sk"), ["/skills"]);
+        assert_eq!(helper.candidates("su"), ["summarize"]);
+        assert!(helper.candidates("x").is_empty());
+    }
+}
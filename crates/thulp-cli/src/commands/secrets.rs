@@ -0,0 +1,103 @@
+use clap::Subcommand;
+use serde_json::json;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use crate::output::Output;
+use thulp_workspace::{FileSecretsStore, SecretsStore, Workspace};
+
+#[derive(Subcommand, Debug)]
+pub enum SecretCommands {
+    /// Store a secret, read from stdin unless given
+    Set {
+        /// Secret name, referenced as {{secret:NAME}} in skill arguments
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Secret value (visible in shell history; prefer stdin)
+        #[arg(value_name = "VALUE")]
+        value: Option<String>,
+    },
+
+    /// List the names of stored secrets
+    List,
+
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+/// The file secrets store of the workspace in `workspace_dir`
+pub fn workspace_secrets(workspace_dir: &Path) -> FileSecretsStore {
+    Workspace::new("cli", "cli", workspace_dir.to_path_buf()).secrets()
+}
+
+pub fn handle_secret_commands(
+    action: SecretCommands,
+    workspace_dir: &Path,
+    read_only: bool,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let store = workspace_secrets(workspace_dir);
+    match action {
+        SecretCommands::Set { name, value } => {
+            if super::config::skip_write(read_only, &store.dir().join(&name), output) {
+                return Ok(());
+            }
+            let value = match value {
+                Some(value) => value,
+                None => read_value(&name)?,
+            };
+            store.set(&name, &value)?;
+            if output.is_json() {
+                output.print_json(&json!({"status": "set", "name": name}));
+            } else {
+                output.print_text(&format!("🔑 Secret '{}' set", name));
+            }
+        }
+        SecretCommands::List => {
+            let names = store.names()?;
+            if output.is_json() {
+                output.print_json(&json!({"secrets": names}));
+            } else if names.is_empty() {
+                output.print_text("No secrets stored.");
+            } else {
+                output.print_text("Stored secrets:");
+                for name in names {
+                    output.print_text(&format!("  🔑 {}", name));
+                }
+            }
+        }
+        SecretCommands::Remove { name } => {
+            if super::config::skip_write(read_only, &store.dir().join(&name), output) {
+                return Ok(());
+            }
+            let removed = store.remove(&name)?;
+            if output.is_json() {
+                output.print_json(&json!({"name": name, "removed": removed}));
+            } else if removed {
+                output.print_text(&format!("✅ Secret '{}' removed", name));
+            } else {
+                output.print_text(&format!("⚠️  No secret named '{}'", name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a secret value from stdin, dropping the trailing newline
+fn read_value(name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Value for '{}': ", name);
+    }
+    let mut value = String::new();
+    stdin.read_to_string(&mut value)?;
+    let value = value.trim_end_matches(['\r', '\n']);
+    if value.is_empty() {
+        return Err(format!("No value given for secret '{}'", name).into());
+    }
+    Ok(value.to_string())
+}
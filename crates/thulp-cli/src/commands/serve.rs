@@ -0,0 +1,408 @@
+//! `thulp serve`: publish the workspace's tools and skills as an MCP server
+
+use crate::approval::ApprovalMode;
+use crate::commands::config;
+use crate::commands::skill;
+use crate::commands::tools;
+use crate::output::Output;
+use crate::progress::ProgressMode;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use thulp_core::{Parameter, ParameterType, ToolCall, ToolDefinition, ToolResult, Transport};
+use thulp_mcp::{McpConnectionManager, McpServer, Readiness, ReadinessCheck};
+use thulp_skills::{ApprovalDecision, ApprovalRequest, Skill};
+use tokio::sync::RwLock;
+
+/// Handle `thulp serve`: connect the workspace's MCP servers and serve their
+/// tools, plus every skill with a workflow, over stdio or HTTP
+///
+/// Status goes to stderr, since on stdio stdout carries the protocol. Tools
+/// marked destructive are only called when `approval` was given, and then
+/// only once it approves the call.
+pub async fn handle_serve(
+    workspace_dir: &Path,
+    http: Option<String>,
+    token: Option<String>,
+    timeout: Option<u64>,
+    approval: Option<ApprovalMode>,
+    read_only: bool,
+    _output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if approval == Some(ApprovalMode::Prompt) {
+        return Err("thulp serve can't prompt for approvals; use approve, deny or a URL".into());
+    }
+    let servers = config::load_servers(workspace_dir)?;
+    let exec = config::load_exec_policy(workspace_dir)?;
+    let serves_tools = !servers.is_empty() || exec.is_some();
+    let sessions = skill::RunSessions::open(workspace_dir, read_only).await?;
+    let notifications = sessions.as_ref().map(skill::RunSessions::notification_sink);
+    let mut transport = tools::workspace_manager(&servers, exec, notifications)?;
+    if !serves_tools {
+        eprintln!("⚠️  No MCP servers configured; serving skills only");
+    } else {
+        transport
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to connect to MCP servers: {}", e))?;
+    }
+    let transport = Arc::new(transport);
+
+    let mut skills = Vec::new();
+    let mut skipped = Vec::new();
+    for name in skill::workflow_names(workspace_dir) {
+        match skill::load_skill_workflow(workspace_dir, &name) {
+            Ok(workflow) => skills.push((name, workflow)),
+            Err(e) => {
+                eprintln!("⚠️  Skipping skill '{}': {}", name, e);
+                skipped.push(name);
+            }
+        }
+    }
+    let workspace_tools = Arc::new(WorkspaceTools {
+        transport: transport.clone(),
+        skills,
+        skipped,
+        workspace_dir: workspace_dir.to_path_buf(),
+        sessions,
+        timeout: timeout.map(Duration::from_secs),
+        approval,
+        read_only,
+        listed: RwLock::default(),
+    });
+    let tool_count = workspace_tools.list_tools().await?.len();
+    let mut server = McpServer::new(workspace_tools.clone())
+        .with_readiness(workspace_tools)
+        .with_name("thulp", env!("CARGO_PKG_VERSION"))
+        .with_instructions(
+            "Tools of the thulp workspace's MCP servers, named server_tool, \
+             and its skills, which run several tool calls as one.",
+        );
+
+    let served = match &http {
+        Some(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+            let local = listener.local_addr()?;
+            if !local.ip().is_loopback() {
+                if token.is_none() {
+                    return Err(format!(
+                        "Refusing to serve on {}, which isn't a loopback address, without \
+                         --token or THULP_SERVE_TOKEN",
+                        local
+                    )
+                    .into());
+                }
+                eprintln!(
+                    "⚠️  {} is reachable from other hosts; clients need the token",
+                    local
+                );
+            }
+            if let Some(token) = token {
+                server = server.with_bearer_token(token);
+            }
+            eprintln!(
+                "Serving {} tools on http://{} (POST /mcp, GET /sse, GET /readyz)",
+                tool_count, local
+            );
+            server.serve_http(listener).await
+        }
+        None => {
+            eprintln!("Serving {} tools on stdio", tool_count);
+            server.serve_stdio().await
+        }
+    };
+
+    drop(server);
+    if let Ok(mut transport) = Arc::try_unwrap(transport) {
+        if let Err(e) = transport.disconnect().await {
+            eprintln!("⚠️  Failed to disconnect from MCP servers: {}", e);
+        }
+    }
+    Ok(served?)
+}
+
+/// The workspace's MCP tools, plus its skills as tools that run the skill
+/// over them
+struct WorkspaceTools {
+    transport: Arc<McpConnectionManager>,
+    skills: Vec<(String, Skill)>,
+    /// Skills whose workflow failed to load
+    skipped: Vec<String>,
+    workspace_dir: PathBuf,
+    /// Where skill runs are recorded; `None` in read-only mode
+    sessions: Option<skill::RunSessions>,
+    /// Timeout given on the command line, over the workspace settings
+    timeout: Option<Duration>,
+    /// Who approves gated skill steps and destructive tool calls; without
+    /// one, steps are denied and destructive tools refused
+    approval: Option<ApprovalMode>,
+    read_only: bool,
+    /// The servers' tools as last listed, so calls don't list them again
+    listed: RwLock<Vec<ToolDefinition>>,
+}
+
+impl WorkspaceTools {
+    async fn run_skill(&self, skill: &Skill, arguments: &serde_json::Value) -> ToolResult {
+        let inputs: HashMap<String, serde_json::Value> = match arguments {
+            serde_json::Value::Object(map) => map.clone().into_iter().collect(),
+            _ => HashMap::new(),
+        };
+        let config = match skill::run_config(
+            &self.workspace_dir,
+            skill.steps.len(),
+            self.timeout,
+            self.read_only,
+        ) {
+            Ok(config) => config,
+            Err(e) => return ToolResult::failure(e.to_string()),
+        };
+        let workspace = match config::load_workspace(&self.workspace_dir) {
+            Ok(workspace) => workspace,
+            Err(e) => return ToolResult::failure(e.to_string()),
+        };
+        let session = match &self.sessions {
+            Some(sessions) => match sessions.begin(skill).await {
+                Ok(id) => Some((sessions, id)),
+                Err(e) => {
+                    eprintln!("⚠️  Failed to start session for '{}': {}", skill.name, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let (outcome, approvals) = skill::execute_skill(
+            self.transport.clone(),
+            skill,
+            inputs.clone(),
+            config,
+            &workspace,
+            self.approval.as_ref().unwrap_or(&ApprovalMode::Deny),
+            ProgressMode::Off,
+        )
+        .await;
+        if let Some((sessions, id)) = session {
+            let recorded = sessions
+                .record(&id, skill, &inputs, &outcome, &approvals)
+                .await
+                .map_err(|e| e.to_string());
+            if let Err(e) = recorded {
+                eprintln!("⚠️  Failed to record session for '{}': {}", skill.name, e);
+            }
+        }
+        match outcome {
+            Ok(result) if result.success => {
+                ToolResult::success(result.output.unwrap_or(serde_json::Value::Null))
+            }
+            Ok(result) => ToolResult::failure(
+                result
+                    .error
+                    .unwrap_or_else(|| format!("Skill '{}' failed", skill.name)),
+            ),
+            Err(e) => ToolResult::failure(e.to_string()),
+        }
+    }
+
+    /// List the servers' tools and remember them for later calls
+    async fn list_server_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        let tools = self.transport.list_tools().await?;
+        *self.listed.write().await = tools.clone();
+        Ok(tools)
+    }
+
+    /// Definition of a server tool, listing the tools again only when it
+    /// wasn't among those last listed
+    async fn server_tool(&self, name: &str) -> thulp_core::Result<Option<ToolDefinition>> {
+        let find = |tools: &[ToolDefinition]| tools.iter().find(|tool| tool.name == name).cloned();
+        if let Some(definition) = find(&self.listed.read().await) {
+            return Ok(Some(definition));
+        }
+        Ok(find(&self.list_server_tools().await?))
+    }
+
+    /// Call a workspace tool, refusing destructive ones in read-only mode or
+    /// when the call isn't approved
+    async fn call_tool(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        let Some(definition) = self.server_tool(&call.tool).await? else {
+            return Err(thulp_core::Error::ToolNotFound(call.tool.clone()));
+        };
+        if definition.destructive {
+            if self.read_only {
+                return Ok(ToolResult::failure(format!(
+                    "Tool '{}' is destructive and cannot run in read-only mode",
+                    call.tool
+                )));
+            }
+            let Some(approval) = &self.approval else {
+                return Ok(ToolResult::failure(format!(
+                    "Tool '{}' is destructive; start thulp serve with --approval to allow it",
+                    call.tool
+                )));
+            };
+            let request = ApprovalRequest {
+                step: call.tool.clone(),
+                tool: call.tool.clone(),
+                skill: None,
+                arguments: call.arguments.clone(),
+                depth: 0,
+            };
+            if let ApprovalDecision::Denied { reason } =
+                approval.handler(None).approve(&request).await
+            {
+                return Ok(ToolResult::failure(format!(
+                    "Call to tool '{}' was denied: {}",
+                    call.tool, reason
+                )));
+            }
+        }
+        self.transport.call(call).await
+    }
+}
+
+/// Ready when every server is healthy; skills that failed to load are
+/// listed but don't make it unready
+#[async_trait]
+impl ReadinessCheck for WorkspaceTools {
+    async fn readiness(&self) -> Readiness {
+        let mut readiness = self.transport.readiness().await;
+        readiness.details.insert(
+            "skills".to_string(),
+            serde_json::json!({ "loaded": self.skills.len(), "skipped": self.skipped }),
+        );
+        readiness
+    }
+}
+
+/// Tool definition of a skill: one parameter per input, required unless it
+/// has a default
+fn skill_tool(name: &str, skill: &Skill) -> ToolDefinition {
+    let mut tool = ToolDefinition::builder(name).description(&skill.description);
+    for input in &skill.inputs {
+        let mut parameter = Parameter::builder(input);
+        match skill.defaults.get(input) {
+            Some(default) => {
+                parameter = parameter
+                    .param_type(value_type(default))
+                    .default(default.clone());
+            }
+            None => parameter = parameter.required(true),
+        }
+        tool = tool.parameter(parameter.build());
+    }
+    tool.build()
+}
+
+fn value_type(value: &serde_json::Value) -> ParameterType {
+    match value {
+        serde_json::Value::Bool(_) => ParameterType::Boolean,
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => ParameterType::Integer,
+        serde_json::Value::Number(_) => ParameterType::Number,
+        serde_json::Value::Array(_) => ParameterType::Array,
+        serde_json::Value::Object(_) => ParameterType::Object,
+        _ => ParameterType::String,
+    }
+}
+
+#[async_trait]
+impl Transport for WorkspaceTools {
+    // The servers are connected for as long as `thulp serve` runs
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        let mut tools = self.list_server_tools().await?;
+        tools.extend(self.skills.iter().map(|(name, skill)| skill_tool(name, skill)));
+        Ok(tools)
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        match self.skills.iter().find(|(name, _)| name == &call.tool) {
+            Some((_, skill)) => Ok(self.run_skill(skill, &call.arguments).await),
+            None => self.call_tool(call).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_skill_tool() {
+        let mut skill = Skill::new("summarize", "Summarize a file");
+        skill.inputs = vec!["path".to_string(), "length".to_string()];
+        skill.defaults.insert("length".to_string(), json!(100));
+
+        let tool = skill_tool("summarize", &skill);
+        assert_eq!(tool.description, "Summarize a file");
+        let schema = tool.to_mcp_input_schema();
+        assert_eq!(schema["required"], json!(["path"]));
+        assert_eq!(schema["properties"]["length"]["type"], "integer");
+        assert_eq!(schema["properties"]["length"]["default"], 100);
+    }
+
+    /// Serves `local.exec`, allowed to run echo
+    async fn exec_tools(approval: Option<ApprovalMode>, read_only: bool) -> WorkspaceTools {
+        let policy = thulp_core::ExecPolicy::new().allow_command("echo");
+        let transport = tools::workspace_manager(&Default::default(), Some(policy), None);
+        let mut transport = transport.unwrap();
+        transport.connect().await.unwrap();
+        WorkspaceTools {
+            transport: Arc::new(transport),
+            skills: Vec::new(),
+            skipped: vec!["broken".to_string()],
+            workspace_dir: std::env::temp_dir(),
+            sessions: None,
+            timeout: None,
+            approval,
+            read_only,
+            listed: RwLock::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_destructive_tools_need_approval() {
+        let call = ToolCall::with_args("local.exec", json!({"command": "echo", "args": ["hi"]}));
+
+        let result = exec_tools(None, false).await.call(&call).await.unwrap();
+        assert!(result.error.unwrap().contains("--approval"));
+        let tools = exec_tools(Some(ApprovalMode::Deny), false).await;
+        assert!(tools.call(&call).await.unwrap().error.unwrap().contains("was denied"));
+        let tools = exec_tools(Some(ApprovalMode::Approve), true).await;
+        assert!(tools.call(&call).await.unwrap().error.unwrap().contains("read-only mode"));
+
+        let tools = exec_tools(Some(ApprovalMode::Approve), false).await;
+        let result = tools.call(&call).await.unwrap();
+        assert_eq!(result.data.unwrap()["stdout"], json!("hi\n"));
+
+        // Later calls check the tool against the listing the first one made
+        assert!(tools.listed.read().await.iter().any(|tool| tool.name == "local.exec"));
+        let missing = ToolCall::new("local.missing");
+        assert!(matches!(
+            tools.call(&missing).await,
+            Err(thulp_core::Error::ToolNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_readiness() {
+        let readiness = exec_tools(None, false).await.readiness().await;
+        assert!(readiness.ready);
+        assert_eq!(readiness.details["servers"][0]["server"], "local");
+        assert_eq!(readiness.details["servers"][0]["status"], "healthy");
+        assert_eq!(readiness.details["skills"], json!({ "loaded": 0, "skipped": ["broken"] }));
+    }
+}
@@ -1,7 +1,25 @@
 use clap::{Subcommand, ValueEnum};
 use serde_json::json;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use crate::approval::ApprovalMode;
+use crate::commands::config;
+use crate::commands::tools::{self, BoxedTransport};
 use crate::output::Output;
+use crate::progress::ProgressMode;
+use thulp_core::{NotificationSink, Transport};
+#[cfg(feature = "remote")]
+use thulp_skill_files::package::{self, PackageClient, PackageIndex};
+use thulp_skill_files::paths;
+use thulp_skills::{
+    ApprovalRecord, DefaultSkillExecutor, ExecutionConfig, ExecutionContext, ExecutionTrace,
+    ProgressHooks, Skill, SkillError, SkillExecutor, SkillReport, SkillResult, SnapshotLog,
+};
+use thulp_workspace::{
+    EntryType, SessionId, SessionManager, SessionNotificationSink, SessionType, Workspace,
+};
 
 #[derive(Subcommand, Debug)]
 pub enum SkillCommands {
@@ -37,9 +55,10 @@ pub enum SkillCommands {
         #[arg(short, long)]
         json: Option<String>,
 
-        /// Timeout in seconds (per step)
-        #[arg(short, long, default_value = "60")]
-        timeout: u64,
+        /// Timeout in seconds per step and tool call (default: the
+        /// workspace's `settings`)
+        #[arg(short, long)]
+        timeout: Option<u64>,
 
         /// Dry run (show execution plan without running)
         #[arg(long)]
@@ -48,6 +67,41 @@ pub enum SkillCommands {
         /// Continue on step failure
         #[arg(long)]
         continue_on_error: bool,
+
+        /// Don't show progress (the bar, or NDJSON events with --output json)
+        #[arg(long)]
+        no_progress: bool,
+
+        /// Who approves steps marked requires_approval: prompt, approve,
+        /// deny, or a webhook URL (default: prompt on a terminal, else deny)
+        #[arg(long, value_name = "MODE")]
+        approval: Option<ApprovalMode>,
+    },
+
+    /// Inspect the context snapshots recorded during a skill run
+    Inspect {
+        /// Run ID (the snapshot log name in .thulp/runs)
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Only show the snapshot taken before this step
+        #[arg(long)]
+        step: Option<String>,
+
+        /// Resolve this template (e.g. "{{search.results}}") at each step
+        #[arg(long)]
+        template: Option<String>,
+    },
+
+    /// Compare a skill's plan with the tool calls of a recorded trace
+    Diff {
+        /// Skill name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Trace file saved from a run of the skill
+        #[arg(value_name = "TRACE")]
+        trace: PathBuf,
     },
 
     /// Validate a skill definition
@@ -71,6 +125,50 @@ pub enum SkillCommands {
         #[arg(short, long, value_enum, default_value = "shell")]
         format: ExportFormat,
     },
+
+    /// Install a skill package from a package index
+    #[cfg(feature = "remote")]
+    Install {
+        /// Package name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Version or version range to install (default: newest stable)
+        #[arg(long, value_name = "VERSION")]
+        version: Option<String>,
+
+        /// Package index URL (default: skill_index in .thulp/config.yaml)
+        #[arg(long, value_name = "URL", env = "THULP_SKILL_INDEX")]
+        index: Option<String>,
+
+        /// Where to install the skill
+        #[arg(short, long, value_enum, default_value = "workspace")]
+        scope: SkillScope,
+
+        /// Replace an existing skill that wasn't installed from a package
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Check installed skill packages for updates and install them
+    #[cfg(feature = "remote")]
+    Update {
+        /// Only update this package
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+
+        /// Package index URL (default: the index each package came from)
+        #[arg(long, value_name = "URL", env = "THULP_SKILL_INDEX")]
+        index: Option<String>,
+
+        /// Scope the packages are installed in
+        #[arg(short, long, value_enum, default_value = "workspace")]
+        scope: SkillScope,
+
+        /// Only report available updates
+        #[arg(long)]
+        check: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -96,6 +194,7 @@ pub enum ExportFormat {
 pub async fn handle_skill_commands(
     command: SkillCommands,
     workspace_dir: &Path,
+    read_only: bool,
     output: &Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match command {
@@ -112,6 +211,8 @@ pub async fn handle_skill_commands(
             timeout,
             dry_run,
             continue_on_error,
+            no_progress,
+            approval,
         } => {
             handle_skill_run(SkillRunOpts {
                 workspace_dir,
@@ -121,10 +222,23 @@ pub async fn handle_skill_commands(
                 timeout,
                 dry_run,
                 continue_on_error,
+                no_progress,
+                approval: approval.unwrap_or_else(ApprovalMode::interactive_default),
+                read_only,
                 output,
             })
             .await?;
         }
+        SkillCommands::Inspect {
+            run_id,
+            step,
+            template,
+        } => {
+            handle_skill_inspect(workspace_dir, &run_id, step, template, output)?;
+        }
+        SkillCommands::Diff { name, trace } => {
+            handle_skill_diff(workspace_dir, &name, &trace, output)?;
+        }
         SkillCommands::Validate { file } => {
             handle_skill_validate(&file, output)?;
         }
@@ -135,6 +249,25 @@ pub async fn handle_skill_commands(
         } => {
             handle_skill_export(workspace_dir, &name, output_file, format, output)?;
         }
+        #[cfg(feature = "remote")]
+        SkillCommands::Install {
+            name,
+            version,
+            index,
+            scope,
+            force,
+        } => {
+            handle_skill_install(workspace_dir, &name, version, index, scope, force, output).await?;
+        }
+        #[cfg(feature = "remote")]
+        SkillCommands::Update {
+            name,
+            index,
+            scope,
+            check,
+        } => {
+            handle_skill_update(workspace_dir, name, index, scope, check, output).await?;
+        }
     }
     Ok(())
 }
@@ -148,16 +281,18 @@ pub fn handle_skill_list(
     let mut skills = Vec::new();
 
     // Collect skills from different scopes
-    let scopes_to_check: Vec<(SkillScope, PathBuf)> = match scope {
-        Some(s) => vec![(s, get_scope_path(workspace_dir, s))],
-        None => vec![
-            (SkillScope::Project, workspace_dir.join("skills")),
-            (SkillScope::Workspace, workspace_dir.join(".thulp/skills")),
-            (
-                SkillScope::Global,
-                dirs::home_dir().unwrap_or_default().join(".thulp/skills"),
-            ),
-        ],
+    let scope_name = |s: SkillScope| format!("{:?}", s).to_lowercase();
+    let scopes_to_check: Vec<(String, PathBuf)> = match scope {
+        Some(s) => vec![(scope_name(s), get_scope_path(workspace_dir, s))],
+        None => [SkillScope::Project, SkillScope::Workspace, SkillScope::Global]
+            .into_iter()
+            .map(|s| (scope_name(s), get_scope_path(workspace_dir, s)))
+            .chain(
+                config::configured_skill_paths(workspace_dir)
+                    .into_iter()
+                    .map(|path| ("path".to_string(), path)),
+            )
+            .collect(),
     };
 
     for (scope, path) in scopes_to_check {
@@ -179,7 +314,7 @@ pub fn handle_skill_list(
                             if tag.is_none() {
                                 skills.push(json!({
                                     "name": name,
-                                    "scope": format!("{:?}", scope).to_lowercase(),
+                                    "scope": scope,
                                     "path": entry_path.display().to_string()
                                 }));
                             }
@@ -217,27 +352,30 @@ pub fn handle_skill_list(
 pub fn get_scope_path(workspace_dir: &Path, scope: SkillScope) -> PathBuf {
     match scope {
         SkillScope::Project => workspace_dir.join("skills"),
-        SkillScope::Workspace => workspace_dir.join(".thulp/skills"),
-        SkillScope::Global => dirs::home_dir().unwrap_or_default().join(".thulp/skills"),
+        SkillScope::Workspace => paths::thulp_dir(workspace_dir).join("skills"),
+        SkillScope::Global => paths::global_thulp_dir()
+            .unwrap_or_else(|| PathBuf::from(".thulp"))
+            .join("skills"),
     }
 }
 
+/// Directories searched for skills by name: the project, workspace and
+/// global scopes, then the `skill_paths` of the workspace config
+fn skill_search_dirs(workspace_dir: &Path) -> Vec<PathBuf> {
+    [SkillScope::Project, SkillScope::Workspace, SkillScope::Global]
+        .into_iter()
+        .map(|scope| get_scope_path(workspace_dir, scope))
+        .chain(config::configured_skill_paths(workspace_dir))
+        .collect()
+}
+
 pub fn handle_skill_show(
     workspace_dir: &Path,
     name: &str,
     output: &Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Search for skill in all scopes
-    let scopes = [
-        workspace_dir.join("skills").join(name),
-        workspace_dir.join(".thulp/skills").join(name),
-        dirs::home_dir()
-            .unwrap_or_default()
-            .join(".thulp/skills")
-            .join(name),
-    ];
-
-    for skill_dir in scopes {
+    for skill_dir in skill_search_dirs(workspace_dir).into_iter().map(|dir| dir.join(name)) {
         let skill_md = skill_dir.join("SKILL.md");
         if skill_md.exists() {
             let content = std::fs::read_to_string(&skill_md)?;
@@ -284,9 +422,12 @@ pub struct SkillRunOpts<'a> {
     name: &'a str,
     params: Vec<String>,
     json_params: Option<String>,
-    timeout: u64,
+    timeout: Option<u64>,
     dry_run: bool,
     continue_on_error: bool,
+    no_progress: bool,
+    approval: ApprovalMode,
+    read_only: bool,
     output: &'a Output,
 }
 
@@ -294,13 +435,16 @@ pub async fn handle_skill_run(
     opts: SkillRunOpts<'_>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let SkillRunOpts {
-        workspace_dir: _workspace_dir,
+        workspace_dir,
         name,
         params,
         json_params,
         timeout,
         dry_run,
         continue_on_error,
+        no_progress,
+        approval,
+        read_only,
         output,
     } = opts;
     // Parse parameters
@@ -318,44 +462,447 @@ pub async fn handle_skill_run(
         serde_json::Value::Object(map)
     };
 
+    let timeout = timeout.map(Duration::from_secs);
     if dry_run {
+        let skill = load_skill_workflow(workspace_dir, name)?;
+        let inputs: HashMap<String, serde_json::Value> = match parameters {
+            serde_json::Value::Object(map) => map.into_iter().collect(),
+            _ => return Err("Parameters must be a JSON object".into()),
+        };
+        let plan = skill.plan(&inputs, None)?;
+        let timeout = run_config(workspace_dir, skill.steps.len(), timeout, read_only)?
+            .timeout
+            .step_timeout
+            .as_secs();
         if output.is_json() {
             output.print_json(&json!({
                 "dry_run": true,
                 "skill": name,
-                "parameters": parameters,
                 "timeout": timeout,
                 "continue_on_error": continue_on_error,
-                "status": "would_execute"
+                "valid": plan.is_valid(),
+                "plan": plan,
             }));
         } else {
-            output.print_text("🔍 Dry run - would execute skill:");
-            output.print_text(&format!("   Skill: {}", name));
+            output.print_text("🔍 Dry run - nothing will be executed");
             output.print_text(&format!("   Timeout: {}s per step", timeout));
             output.print_text(&format!("   Continue on error: {}", continue_on_error));
-            output.print_text(&format!(
-                "   Parameters: {}",
-                serde_json::to_string_pretty(&parameters)?
-            ));
+            output.print_text("");
+            output.print_text(plan.to_string().trim_end());
         }
         return Ok(());
     }
 
-    // Placeholder for actual skill execution
-    // In full implementation: load skill, create executor, run steps
+    let mut skill = load_skill_workflow(workspace_dir, name)?;
+    if continue_on_error {
+        for step in &mut skill.steps {
+            step.continue_on_error = true;
+        }
+    }
+    let inputs: HashMap<String, serde_json::Value> = match parameters {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        _ => return Err("Parameters must be a JSON object".into()),
+    };
+
+    let servers = config::load_servers(workspace_dir)?;
+    let exec = config::load_exec_policy(workspace_dir)?;
+    let sessions = RunSessions::open(workspace_dir, read_only).await?;
+    let notifications = sessions.as_ref().map(RunSessions::notification_sink);
+    let mut transport = tools::workspace_transport(&servers, exec, notifications)?;
+    transport
+        .connect()
+        .await
+        .map_err(|e| format!("Failed to connect to MCP servers: {}", e))?;
+    let transport = Arc::new(BoxedTransport(transport));
+    let session = match &sessions {
+        Some(sessions) => Some(sessions.begin(&skill).await?),
+        None => None,
+    };
+
+    output.print_text(&format!("🚀 Executing skill: {}", name));
+    let config = run_config(workspace_dir, skill.steps.len(), timeout, read_only)?;
+    let started_at = SystemTime::now();
+    let (outcome, approvals) = execute_skill(
+        transport.clone(),
+        &skill,
+        inputs.clone(),
+        config,
+        &config::load_workspace(workspace_dir)?,
+        &approval,
+        if no_progress {
+            ProgressMode::Off
+        } else {
+            ProgressMode::for_output(output)
+        },
+    )
+    .await;
+    let finished_at = SystemTime::now();
+    if let Ok(mut transport) = Arc::try_unwrap(transport) {
+        if let Err(e) = transport.disconnect().await {
+            eprintln!("⚠️  Failed to disconnect from MCP servers: {}", e);
+        }
+    }
+
+    let session_id = match (&sessions, &session) {
+        (Some(sessions), Some(id)) => {
+            Some(sessions.record(id, &skill, &inputs, &outcome, &approvals).await?)
+        }
+        _ => None,
+    };
+
+    if output.is_json() {
+        let mut report = SkillReport::new(name, &outcome, started_at, finished_at);
+        // Only the final output is printed, within the byte budget
+        for step in &mut report.steps {
+            step.output = None;
+        }
+        let data = report.output.take().map(|value| output.truncate_result(&value));
+        report.output = data.as_ref().map(|data| data.value.clone());
+        let mut line = serde_json::to_value(&report)?;
+        line["session_id"] = json!(session_id);
+        line["truncated"] = json!(data.as_ref().is_some_and(|data| data.truncated));
+        // Follows the progress events, so stdout stays one object per line
+        output.print_json_line(&line);
+        if let Some(data) = &data {
+            output.print_truncation_hint(data);
+        }
+        if !outcome?.success {
+            return Err(format!("Skill '{}' failed", name).into());
+        }
+        return Ok(());
+    }
+
+    let result = outcome?;
+    let data = output.truncate_result(result.output.as_ref().unwrap_or(&serde_json::Value::Null));
+    output.print_text("");
+    if result.success {
+        output.print_text(&format!("✅ Skill '{}' completed", name));
+    } else {
+        output.print_text(&format!(
+            "❌ Skill '{}' failed: {}",
+            name,
+            result.error.as_deref().unwrap_or("unknown error")
+        ));
+    }
+    // Failures the run carried on from, with what they cost
+    for degradation in &result.degradations {
+        let text = degradation.to_string().replace('\n', "\n   ");
+        output.print_text(&format!("⚠️  {}", text));
+    }
+    match &data.value {
+        serde_json::Value::Null => {}
+        serde_json::Value::String(text) => output.print_text(text),
+        value => output.print_text(&serde_json::to_string_pretty(value)?),
+    }
+    if let Some(session_id) = &session_id {
+        output.print_text(&format!("   Session: {}", session_id));
+    }
+    output.print_truncation_hint(&data);
+
+    if !result.success {
+        return Err(format!("Skill '{}' failed", name).into());
+    }
+    Ok(())
+}
+
+/// Execution config for a CLI run: the workspace's settings, with
+/// `timeout` overriding them per step and tool call, and for the whole
+/// skill that much per step. In `read_only` mode destructive tools are
+/// refused.
+pub fn run_config(
+    workspace_dir: &Path,
+    steps: usize,
+    timeout: Option<Duration>,
+    read_only: bool,
+) -> Result<ExecutionConfig, Box<dyn std::error::Error>> {
+    let config = ExecutionConfig::from_workspace(workspace_dir)
+        .map_err(|e| format!("Failed to load workspace settings: {}", e))?
+        .with_read_only(read_only);
+    let Some(timeout) = timeout else {
+        return Ok(config);
+    };
+    let steps = u32::try_from(steps.max(1)).unwrap_or(u32::MAX);
+    let timeouts = config
+        .timeout
+        .clone()
+        .with_step_timeout(timeout)
+        .with_tool_timeout(timeout)
+        .with_skill_timeout(timeout.saturating_mul(steps));
+    Ok(config.with_timeout(timeouts))
+}
+
+/// Run a skill over `transport` with `workspace`'s secrets and
+/// `{{workspace.*}}` variables, gated steps approved as `approval` says and
+/// progress shown as `progress` says. Returns the outcome and the approval
+/// decisions taken.
+pub async fn execute_skill<T: Transport + 'static>(
+    transport: Arc<T>,
+    skill: &Skill,
+    inputs: HashMap<String, serde_json::Value>,
+    config: ExecutionConfig,
+    workspace: &Workspace,
+    approval: &ApprovalMode,
+    progress: ProgressMode,
+) -> (Result<SkillResult, SkillError>, Vec<ApprovalRecord>) {
+    let (hooks, events) = ProgressHooks::channel();
+    let (renderer, bar) = progress.spawn(events);
+    let (outcome, approvals) = {
+        let executor = DefaultSkillExecutor::from_arcs(transport, Arc::new(hooks));
+        let mut context = ExecutionContext::from_inputs(inputs)
+            .with_config(config)
+            .with_secrets(Arc::new(workspace.secrets()))
+            .with_workspace(workspace.template_variables())
+            .with_approval_handler(approval.handler(bar));
+        let outcome = executor.execute(skill, &mut context).await;
+        (outcome, context.approvals())
+    };
+    // The executor is gone, so the channel is closed and rendering finishes
+    let _ = renderer.await;
+    (outcome, approvals)
+}
+
+/// The workspace's sessions, recording each skill run with an entry per
+/// step, one per approval decision and one for the run, and masking the
+/// workspace's secrets. Notifications MCP servers send during a run are
+/// recorded in its session through [`RunSessions::notification_sink`].
+pub struct RunSessions {
+    sessions: Arc<SessionManager>,
+    notifications: Arc<SessionNotificationSink>,
+}
+
+impl RunSessions {
+    /// Open the workspace's sessions; `None` in read-only mode, where runs
+    /// aren't recorded
+    pub async fn open(
+        workspace_dir: &Path,
+        read_only: bool,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if read_only {
+            return Ok(None);
+        }
+        let workspace = Workspace::new("cli", "cli", workspace_dir.to_path_buf());
+        let redactor = workspace.secrets().redactor()?;
+        let sessions = SessionManager::new(&workspace).await?.with_redactor(Arc::new(redactor));
+        let sessions = Arc::new(sessions);
+        Ok(Some(Self {
+            notifications: Arc::new(SessionNotificationSink::new(sessions.clone())),
+            sessions,
+        }))
+    }
+
+    /// Sink for the workspace's MCP servers, recording what they send into
+    /// the session of the latest run still going
+    pub fn notification_sink(&self) -> Arc<dyn NotificationSink> {
+        self.notifications.clone()
+    }
+
+    /// Start the session of a run of `skill`
+    pub async fn begin(&self, skill: &Skill) -> Result<SessionId, Box<dyn std::error::Error>> {
+        let session = self
+            .sessions
+            .create_session(
+                format!("skill run: {}", skill.name),
+                SessionType::Agent {
+                    agent_name: "thulp".to_string(),
+                },
+            )
+            .await?;
+        let id = session.id().clone();
+        self.notifications.set_session(Some(id.clone())).await;
+        Ok(id)
+    }
+
+    /// Record the run in the session started for it and close the session.
+    /// Returns the session's ID.
+    pub async fn record(
+        &self,
+        id: &SessionId,
+        skill: &Skill,
+        inputs: &HashMap<String, serde_json::Value>,
+        outcome: &Result<SkillResult, SkillError>,
+        approvals: &[ApprovalRecord],
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.notifications.end_session(id).await;
+        let sessions = &self.sessions;
+        let (success, content) = match outcome {
+            Ok(result) => {
+                for (step_name, step_result) in &result.step_results {
+                    let tool = skill
+                        .steps
+                        .iter()
+                        .find(|step| &step.name == step_name)
+                        .map_or(step_name.as_str(), |step| step.tool.as_str());
+                    let entry = EntryType::ToolCall {
+                        tool_name: tool.to_string(),
+                        success: step_result.success,
+                    };
+                    let content = json!({ "step": step_name, "result": step_result });
+                    sessions.add_entry(id, entry, content).await?;
+                }
+                let content = json!({
+                    "inputs": inputs,
+                    "output": result.output,
+                    "error": result.error,
+                    "usage": result.usage,
+                    "degradations": result.degradations,
+                });
+                (result.success, content)
+            }
+            Err(e) => (false, json!({ "inputs": inputs, "error": e.to_string() })),
+        };
+        for approval in approvals {
+            let entry = EntryType::SystemEvent {
+                event: "approval".to_string(),
+            };
+            sessions.add_entry(id, entry, json!(approval)).await?;
+        }
+        let entry = EntryType::SkillExecution {
+            skill_name: skill.name.clone(),
+            success,
+        };
+        sessions.add_entry(id, entry, content).await?;
+        if success {
+            sessions.complete_session(id).await?;
+        } else {
+            sessions.fail_session(id).await?;
+        }
+        Ok(id.to_string())
+    }
+}
+
+/// Names of the skills with a `skill.yaml` workflow in any scope or
+/// configured `skill_paths`, sorted
+#[cfg(any(feature = "mcp", feature = "repl"))]
+pub fn workflow_names(workspace_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = skill_search_dirs(workspace_dir)
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().join("skill.yaml").exists())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Find a skill's `skill.yaml` workflow in the project, workspace or global
+/// scope, or the configured `skill_paths`, and parse it
+pub fn load_skill_workflow(
+    workspace_dir: &Path,
+    name: &str,
+) -> Result<Skill, Box<dyn std::error::Error>> {
+    for dir in skill_search_dirs(workspace_dir) {
+        let skill_dir = dir.join(name);
+        let skill_yaml = skill_dir.join("skill.yaml");
+        if skill_yaml.exists() {
+            let content = std::fs::read_to_string(&skill_yaml)?;
+            return serde_yaml::from_str(&content)
+                .map_err(|e| format!("{}: {}", skill_yaml.display(), e).into());
+        }
+        if skill_dir.join("SKILL.md").exists() {
+            return Err(format!("Skill '{}' has no skill.yaml workflow to plan", name).into());
+        }
+    }
+    Err(format!("Skill '{}' not found", name).into())
+}
+
+/// Plan a skill with the inputs of a recorded trace and show where the
+/// recorded calls diverge from the plan
+pub fn handle_skill_diff(
+    workspace_dir: &Path,
+    name: &str,
+    trace: &Path,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let skill = load_skill_workflow(workspace_dir, name)?;
+    let trace = ExecutionTrace::load(trace)?;
+    let diff = skill.plan(&trace.inputs, None)?.diff(&trace);
     if output.is_json() {
         output.print_json(&json!({
-            "status": "not_implemented",
             "skill": name,
-            "message": "Skill execution requires loaded workspace and MCP connections"
+            "matches_plan": diff.matches_plan(),
+            "diff": diff,
         }));
     } else {
-        output.print_text(&format!("🚀 Executing skill: {}", name));
-        output.print_text("");
-        output.print_text("⚠️  Full skill execution requires:");
-        output.print_text("   1. Initialized workspace (thulp init)");
-        output.print_text("   2. Configured MCP servers (thulp config add-server)");
-        output.print_text("   3. Valid skill definition");
+        output.print_text(diff.to_string().trim_end());
+    }
+    Ok(())
+}
+
+pub fn handle_skill_inspect(
+    workspace_dir: &Path,
+    run_id: &str,
+    step: Option<String>,
+    template: Option<String>,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let runs_dir = paths::thulp_dir(workspace_dir).join("runs");
+    let log = SnapshotLog::load(&runs_dir, run_id)?;
+
+    let snapshots: Vec<_> = match &step {
+        Some(name) => vec![log
+            .step(name)
+            .ok_or_else(|| format!("No snapshot for step '{}' in run '{}'", name, run_id))?],
+        None => log.snapshots.iter().collect(),
+    };
+
+    let mut entries = Vec::with_capacity(snapshots.len());
+    for snapshot in &snapshots {
+        let resolved = match &template {
+            Some(template) => Some(snapshot.resolve(template)?),
+            None => None,
+        };
+        entries.push((snapshot, resolved));
+    }
+
+    if output.is_json() {
+        output.print_json(&json!({
+            "run_id": log.run_id,
+            "skill": log.skill,
+            "template": template,
+            "snapshots": entries.iter().map(|(snapshot, resolved)| {
+                let mut entry = serde_json::to_value(snapshot).unwrap_or_default();
+                if let Some(resolved) = resolved {
+                    entry["resolved"] = resolved.clone();
+                }
+                entry
+            }).collect::<Vec<_>>()
+        }));
+    } else {
+        output.print_text(&format!("Run {} of skill '{}'", log.run_id, log.skill));
+        if entries.is_empty() {
+            output.print_text("  No snapshots recorded");
+        }
+        for (snapshot, resolved) in &entries {
+            output.print_text("");
+            output.print_text(&format!(
+                "[{}] {} ({})",
+                snapshot.step_index, snapshot.step_name, snapshot.tool
+            ));
+            output.print_text(&format!(
+                "   Arguments: {}",
+                serde_json::to_string(&snapshot.arguments)?
+            ));
+            match (&template, resolved) {
+                (Some(template), Some(resolved)) => output.print_text(&format!(
+                    "   {} => {}",
+                    template,
+                    serde_json::to_string(resolved)?
+                )),
+                _ => {
+                    let mut names: Vec<_> = snapshot.variables.keys().collect();
+                    names.sort();
+                    for name in names {
+                        output.print_text(&format!(
+                            "   {} = {}",
+                            name,
+                            serde_json::to_string(&snapshot.variables[name])?
+                        ));
+                    }
+                }
+            }
+        }
     }
 
     Ok(())
@@ -468,3 +1015,408 @@ echo "Skill execution complete"
 
     Ok(())
 }
+
+/// Package index from `skill_index` in the workspace config
+#[cfg(feature = "remote")]
+fn configured_skill_index(workspace_dir: &Path) -> Option<String> {
+    let config_path = paths::thulp_dir(workspace_dir).join("config.yaml");
+    thulp_workspace::WorkspaceConfig::load_raw(config_path).ok()?.skill_index
+}
+
+#[cfg(feature = "remote")]
+pub async fn handle_skill_install(
+    workspace_dir: &Path,
+    name: &str,
+    version: Option<String>,
+    index: Option<String>,
+    scope: SkillScope,
+    force: bool,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index_url = index
+        .or_else(|| configured_skill_index(workspace_dir))
+        .ok_or("No package index configured. Pass --index, set THULP_SKILL_INDEX or add skill_index to .thulp/config.yaml")?;
+
+    let client = PackageClient::new(&index_url)?;
+    let index = client.fetch_index().await?;
+    let entry = index.find(name, version.as_deref())?.ok_or_else(|| match &version {
+        Some(version) => format!("No version of '{}' matching '{}' in {}", name, version, index_url),
+        None => format!("Package '{}' not found in {}", name, index_url),
+    })?;
+
+    let skills_dir = get_scope_path(workspace_dir, scope);
+    let installed = client.install(entry, &skills_dir, force).await?;
+    let path = skills_dir.join(&installed.name);
+
+    if output.is_json() {
+        output.print_json(&json!({
+            "status": "installed",
+            "name": installed.name,
+            "version": installed.version,
+            "path": path.display().to_string(),
+            "files": installed.files.len()
+        }));
+    } else {
+        output.print_text(&format!(
+            "✅ Installed {} {} to {}",
+            installed.name,
+            installed.version,
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "remote")]
+pub async fn handle_skill_update(
+    workspace_dir: &Path,
+    name: Option<String>,
+    index: Option<String>,
+    scope: SkillScope,
+    check: bool,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let skills_dir = get_scope_path(workspace_dir, scope);
+    let mut installed = package::installed_packages(&skills_dir)?;
+    if let Some(name) = &name {
+        installed.retain(|package| &package.name == name);
+        if installed.is_empty() {
+            return Err(format!(
+                "Package '{}' is not installed in {}",
+                name,
+                skills_dir.display()
+            )
+            .into());
+        }
+    }
+
+    // Fetch each index once, even when several packages come from it
+    let mut indexes: HashMap<String, (PackageClient, PackageIndex)> = HashMap::new();
+    let mut updates = Vec::new();
+    for package in &installed {
+        let url = index.clone().unwrap_or_else(|| package.index.clone());
+        if !indexes.contains_key(&url) {
+            let client = PackageClient::new(&url)?;
+            let fetched = client.fetch_index().await?;
+            indexes.insert(url.clone(), (client, fetched));
+        }
+        let (_, fetched) = &indexes[&url];
+        for update in package::check_updates(fetched, std::slice::from_ref(package)) {
+            updates.push((update, url.clone()));
+        }
+    }
+
+    if !check {
+        for (update, url) in &updates {
+            let (client, fetched) = &indexes[url];
+            let version = update.available.to_string();
+            if let Some(entry) = fetched.find(&update.name, Some(&version))? {
+                client.install(entry, &skills_dir, false).await?;
+            }
+        }
+    }
+
+    if output.is_json() {
+        output.print_json(&json!({
+            "checked": installed.len(),
+            "updated": !check,
+            "updates": updates.iter().map(|(update, url)| json!({
+                "name": update.name,
+                "installed": update.installed.to_string(),
+                "available": update.available.to_string(),
+                "index": url
+            })).collect::<Vec<_>>()
+        }));
+    } else if installed.is_empty() {
+        output.print_text(&format!("No skill packages installed in {}", skills_dir.display()));
+    } else if updates.is_empty() {
+        output.print_text(&format!("All {} skill package(s) are up to date.", installed.len()));
+    } else {
+        for (update, _) in &updates {
+            let status = if check { "⬆️  Update available:" } else { "✅ Updated" };
+            output.print_text(&format!(
+                "{} {} {} -> {}",
+                status, update.name, update.installed, update.available
+            ));
+        }
+        if check {
+            output.print_text("");
+            output.print_text("Run 'thulp skill update' to install them.");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::secrets::workspace_secrets;
+    use async_trait::async_trait;
+    use thulp_core::{ToolCall, ToolDefinition, ToolResult};
+    use thulp_skills::SkillStep;
+    use thulp_workspace::{SecretsStore, SessionId, SessionStatus};
+
+    /// Serves an `upper` tool that uppercases its `text` argument
+    struct UpperTransport;
+
+    #[async_trait]
+    impl Transport for UpperTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![ToolDefinition::builder("upper").build()])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            match call.arguments["text"].as_str() {
+                Some(text) => Ok(ToolResult::success(json!(text.to_uppercase()))),
+                None => Err(thulp_core::Error::MissingParameter("text".to_string())),
+            }
+        }
+    }
+
+    fn skill(arguments: serde_json::Value) -> Skill {
+        Skill::new("shout", "Uppercase a greeting").with_step(SkillStep {
+            name: "shout".to_string(),
+            tool: "upper".to_string(),
+            arguments,
+            ..Default::default()
+        })
+    }
+
+    async fn run(
+        workspace_dir: &Path,
+        skill: &Skill,
+    ) -> (Result<SkillResult, SkillError>, String) {
+        run_with(workspace_dir, skill, &ApprovalMode::Deny).await
+    }
+
+    async fn run_with(
+        workspace_dir: &Path,
+        skill: &Skill,
+        approval: &ApprovalMode,
+    ) -> (Result<SkillResult, SkillError>, String) {
+        let inputs = HashMap::from([("name".to_string(), json!("ann"))]);
+        let timeout = Some(Duration::from_secs(5));
+        let config = run_config(workspace_dir, skill.steps.len(), timeout, false).unwrap();
+        let sessions = RunSessions::open(workspace_dir, false).await.unwrap().unwrap();
+        let id = sessions.begin(skill).await.unwrap();
+        let (outcome, approvals) = execute_skill(
+            Arc::new(UpperTransport),
+            skill,
+            inputs.clone(),
+            config,
+            &config::load_workspace(workspace_dir).unwrap(),
+            approval,
+            ProgressMode::Off,
+        )
+        .await;
+        let session_id = sessions.record(&id, skill, &inputs, &outcome, &approvals).await;
+        (outcome, session_id.unwrap())
+    }
+
+    async fn load_session(workspace_dir: &Path, id: &str) -> thulp_workspace::Session {
+        let workspace = Workspace::new("cli", "cli", workspace_dir.to_path_buf());
+        let sessions = SessionManager::new(&workspace).await.unwrap();
+        sessions
+            .load_session(&SessionId::from_string(id).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_execute_skill_records_session() {
+        let temp = std::env::temp_dir().join(format!("thulp-skill-run-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+
+        let skill = skill(json!({"text": "hello {{name}}"}));
+        let (outcome, session_id) = run(&temp, &skill).await;
+        let result = outcome.unwrap();
+        assert!(result.success);
+        assert_eq!(result.step_results[0].1.data, Some(json!("HELLO ANN")));
+
+        let session = load_session(&temp, &session_id).await;
+        assert_eq!(session.status(), SessionStatus::Completed);
+        assert_eq!(session.entries.len(), 2);
+        assert!(matches!(
+            &session.entries[0].entry_type,
+            EntryType::ToolCall { tool_name, success: true } if tool_name == "upper"
+        ));
+
+        let failing = super::tests::skill(json!({}));
+        let (_, session_id) = run(&temp, &failing).await;
+        let session = load_session(&temp, &session_id).await;
+        assert_eq!(session.status(), SessionStatus::Failed);
+
+        // Read-only runs aren't recorded
+        assert!(RunSessions::open(&temp, true).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[cfg(all(unix, feature = "mcp"))]
+    #[tokio::test]
+    async fn test_run_session_records_server_notifications() {
+        let temp =
+            std::env::temp_dir().join(format!("thulp-skill-notify-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+        let script = temp.join("server.sh");
+        std::fs::write(
+            &script,
+            r#"while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"capabilities":{}}}' ;;
+    *tools/list*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[{"name":"upper"}]}}' ;;
+    *tools/call*)
+      echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info"}}'
+      echo '{"jsonrpc":"2.0","id":'$id',"result":{"text":"HI"}}' ;;
+  esac
+done
+"#,
+        )
+        .unwrap();
+        let servers = std::collections::BTreeMap::from([(
+            "srv".to_string(),
+            config::ServerConfig::stdio("sh", vec![script.to_string_lossy().into_owned()]),
+        )]);
+
+        let sessions = RunSessions::open(&temp, false).await.unwrap().unwrap();
+        let notifications = Some(sessions.notification_sink());
+        let mut transport = tools::workspace_transport(&servers, None, notifications).unwrap();
+        transport.connect().await.unwrap();
+        let mut skill = skill(json!({"text": "hi"}));
+        skill.steps[0].tool = "srv.upper".to_string();
+
+        let id = sessions.begin(&skill).await.unwrap();
+        let config = run_config(&temp, 1, None, false).unwrap();
+        let (outcome, approvals) = execute_skill(
+            Arc::new(BoxedTransport(transport)),
+            &skill,
+            HashMap::new(),
+            config,
+            &config::load_workspace(&temp).unwrap(),
+            &ApprovalMode::Deny,
+            ProgressMode::Off,
+        )
+        .await;
+        assert!(outcome.as_ref().unwrap().success);
+        let session_id = sessions
+            .record(&id, &skill, &HashMap::new(), &outcome, &approvals)
+            .await
+            .unwrap();
+
+        let session = load_session(&temp, &session_id).await;
+        assert!(matches!(
+            &session.entries[0].entry_type,
+            EntryType::ServerLog { server, level } if server == "srv" && level == "info"
+        ));
+        assert_eq!(session.entries.len(), 3);
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_skill_records_degradations() {
+        let temp =
+            std::env::temp_dir().join(format!("thulp-skill-degraded-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+
+        let mut skill = skill(json!({}));
+        skill.steps[0].continue_on_error = true;
+        let skill = skill.with_step(SkillStep {
+            name: "echo".to_string(),
+            tool: "upper".to_string(),
+            arguments: json!({"text": "{{shout | default('quiet')}}"}),
+            ..Default::default()
+        });
+        let (outcome, session_id) = run(&temp, &skill).await;
+        let result = outcome.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, Some(json!("QUIET")));
+        assert_eq!(result.degradations[0].fallbacks[0].step, "echo");
+
+        let session = load_session(&temp, &session_id).await;
+        assert_eq!(session.status(), SessionStatus::Completed);
+        let run = session.entries.last().unwrap();
+        assert_eq!(run.content["degradations"][0]["step"], "shout");
+        assert_eq!(run.content["degradations"][0]["action"], "continue_on_error");
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_skill_masks_secrets() {
+        let temp = std::env::temp_dir().join(format!("thulp-skill-secret-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+        workspace_secrets(&temp).set("word", "OPEN SESAME").unwrap();
+
+        let skill = skill(json!({"text": "{{secret:word}}"}));
+        let (outcome, session_id) = run(&temp, &skill).await;
+        assert_eq!(outcome.unwrap().step_results[0].1.data, Some(json!("OPEN SESAME")));
+
+        let session = load_session(&temp, &session_id).await;
+        let recorded = serde_json::to_string(&session.entries).unwrap();
+        assert!(recorded.contains("[REDACTED]"));
+        assert!(!recorded.contains("OPEN SESAME"));
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_skill_reads_workspace_context() {
+        let temp =
+            std::env::temp_dir().join(format!("thulp-skill-workspace-{}", std::process::id()));
+        std::fs::create_dir_all(temp.join(".thulp")).unwrap();
+        std::fs::write(
+            temp.join(".thulp").join("config.yaml"),
+            "name: demo\ncontext:\n  project_id: p-42\n",
+        )
+        .unwrap();
+
+        let skill = skill(json!({"text": "{{workspace.name}}/{{workspace.context.project_id}}"}));
+        let (outcome, _) = run(&temp, &skill).await;
+        assert_eq!(outcome.unwrap().step_results[0].1.data, Some(json!("DEMO/P-42")));
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_skill_records_approvals() {
+        let temp =
+            std::env::temp_dir().join(format!("thulp-skill-approval-{}", std::process::id()));
+        std::fs::create_dir_all(&temp).unwrap();
+        let mut skill = skill(json!({"text": "{{name}}"}));
+        skill.steps[0].requires_approval = true;
+
+        let (outcome, session_id) = run_with(&temp, &skill, &ApprovalMode::Approve).await;
+        assert_eq!(outcome.unwrap().step_results[0].1.data, Some(json!("ANN")));
+        let session = load_session(&temp, &session_id).await;
+        let approval = session
+            .entries
+            .iter()
+            .find(|entry| {
+                matches!(&entry.entry_type, EntryType::SystemEvent { event } if event == "approval")
+            })
+            .unwrap();
+        assert_eq!(approval.content["decision"]["decision"], "approved");
+        assert_eq!(approval.content["request"]["arguments"], json!({"text": "ann"}));
+
+        let (outcome, _) = run(&temp, &skill).await;
+        assert_eq!(outcome.unwrap_err().code(), "approval_denied");
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+}
@@ -1,7 +1,21 @@
+use async_trait::async_trait;
 use clap::Subcommand;
 use serde_json::json;
+use futures::StreamExt;
+use std::collections::BTreeMap;
 use std::path::Path;
-use thulp_core::{Parameter, ParameterType, ToolCall, ToolDefinition};
+use std::sync::Arc;
+use std::time::Duration;
+use thulp_skills::ExecutionSettings;
+use thulp_core::{
+    collect_stream, ExecPolicy, NotificationSink, Parameter, ParameterType, ToolCall,
+    ToolDefinition, ToolResult, ToolResultStream, Transport, UsageRenderer,
+};
+#[cfg(feature = "mcp")]
+use thulp_core::RenamedTransport;
+#[cfg(feature = "mcp")]
+use thulp_mcp::{McpClient, McpConnectionManager, McpTransport};
+use crate::commands::config::{self, ServerConfig};
 use crate::output::Output;
 
 #[derive(Subcommand, Debug)]
@@ -22,31 +36,81 @@ pub enum ToolCommands {
     },
 }
 
+/// How `thulp run` executes a tool
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    /// Timeout in seconds, instead of the workspace's `default_timeout`
+    pub timeout: Option<u64>,
+    /// Validate and print the call without executing it
+    pub dry_run: bool,
+    /// Print partial output as the tool streams it
+    pub stream: bool,
+    /// Refuse tools marked destructive
+    pub read_only: bool,
+}
+
+/// How a single tool call is made
+#[derive(Debug, Clone, Copy)]
+pub struct CallOptions {
+    /// Give up after this long
+    pub timeout: Duration,
+    /// Print partial output as the tool streams it
+    pub stream: bool,
+    /// Refuse tools marked destructive
+    pub read_only: bool,
+}
+
+/// `timeout` seconds, or the workspace's tool timeout when not given
+pub fn tool_timeout(
+    workspace_dir: &Path,
+    timeout: Option<u64>,
+) -> Result<Duration, Box<dyn std::error::Error>> {
+    if let Some(timeout) = timeout {
+        return Ok(Duration::from_secs(timeout));
+    }
+    let settings = ExecutionSettings::load_workspace(workspace_dir)
+        .map_err(|e| format!("Failed to load workspace settings: {}", e))?;
+    Ok(settings.to_config().timeout.tool_timeout)
+}
+
+/// Tool arguments from a JSON string, or else from key=value pairs
+pub fn parse_arguments(
+    args: Vec<String>,
+    json_args: Option<String>,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    if let Some(json_str) = json_args {
+        return Ok(serde_json::from_str(&json_str)?);
+    }
+    let mut map = serde_json::Map::new();
+    for arg in args {
+        if let Some((key, value)) = arg.split_once('=') {
+            // Try to parse as JSON value, fallback to string
+            let parsed_value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+            map.insert(key.to_string(), parsed_value);
+        } else {
+            return Err(format!("Invalid argument format: '{}'. Use key=value", arg).into());
+        }
+    }
+    Ok(serde_json::Value::Object(map))
+}
+
 pub async fn handle_run(
+    workspace_dir: &Path,
     tool: &str,
     args: Vec<String>,
     json_args: Option<String>,
-    timeout: u64,
-    dry_run: bool,
+    options: RunOptions,
     output: &Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Parse arguments
-    let arguments: serde_json::Value = if let Some(json_str) = json_args {
-        serde_json::from_str(&json_str)?
-    } else {
-        let mut map = serde_json::Map::new();
-        for arg in args {
-            if let Some((key, value)) = arg.split_once('=') {
-                // Try to parse as JSON value, fallback to string
-                let parsed_value = serde_json::from_str(value)
-                    .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
-                map.insert(key.to_string(), parsed_value);
-            } else {
-                return Err(format!("Invalid argument format: '{}'. Use key=value", arg).into());
-            }
-        }
-        serde_json::Value::Object(map)
-    };
+    let RunOptions {
+        timeout,
+        dry_run,
+        stream,
+        read_only,
+    } = options;
+    let arguments = parse_arguments(args, json_args)?;
+    let timeout = tool_timeout(workspace_dir, timeout)?;
 
     // Parse tool name (format: server.tool or just tool)
     let (server_name, tool_name) = if let Some((server, tool)) = tool.split_once('.') {
@@ -62,7 +126,8 @@ pub async fn handle_run(
                 "tool": tool_name,
                 "server": server_name,
                 "arguments": arguments,
-                "timeout": timeout
+                "timeout": timeout.as_secs(),
+                "stream": stream
             }));
         } else {
             output.print_text("🔍 Dry run - would execute:");
@@ -70,7 +135,10 @@ pub async fn handle_run(
             if let Some(ref server) = server_name {
                 output.print_text(&format!("   Server: {}", server));
             }
-            output.print_text(&format!("   Timeout: {}s", timeout));
+            output.print_text(&format!("   Timeout: {}s", timeout.as_secs()));
+            if stream {
+                output.print_text("   Streaming: on");
+            }
             output.print_text(&format!(
                 "   Arguments: {}",
                 serde_json::to_string_pretty(&arguments)?
@@ -79,39 +147,324 @@ pub async fn handle_run(
         return Ok(());
     }
 
-    // For now, we'll show a placeholder since MCP execution requires server config
-    // In a full implementation, this would:
-    // 1. Load workspace config
-    // 2. Find the appropriate server
-    // 3. Connect via MCP
-    // 4. Execute the tool
-    // 5. Return the result
+    let servers = config::load_servers(workspace_dir)?;
+    let exec = config::load_exec_policy(workspace_dir)?;
+    let (server, mut transport): (&str, Box<dyn Transport>) = match (server_name.as_deref(), exec)
+    {
+        // `local.exec`, unless a server is configured under that name
+        (Some(EXEC_SERVER), Some(policy)) if !servers.contains_key(EXEC_SERVER) => {
+            (EXEC_SERVER, Box::new(thulp_core::ExecTransport::new(policy)))
+        }
+        (server_name, _) => {
+            let (server, server_config) = config::resolve_server(&servers, server_name)?;
+            (server, server_transport(server, server_config, None)?)
+        }
+    };
+
+    output.print_text(&format!("🔧 Executing tool: {} on {}", tool_name, server));
+    let options = CallOptions {
+        timeout,
+        stream,
+        read_only,
+    };
+    let result = execute_tool(
+        transport.as_mut(),
+        server,
+        &tool_name,
+        arguments.clone(),
+        options,
+        output,
+    )
+    .await?;
+    let data = output.truncate_result(result.data.as_ref().unwrap_or(&serde_json::Value::Null));
 
     if output.is_json() {
         output.print_json(&json!({
-            "status": "not_implemented",
+            "status": if result.success { "success" } else { "failed" },
             "tool": tool_name,
-            "server": server_name,
+            "server": server,
             "arguments": arguments,
-            "message": "Tool execution requires configured MCP servers. Use 'thulp config add-server' first."
+            "result": data.value,
+            "error": result.error,
+            "duration_ms": result.duration_ms,
+            "truncated": data.truncated
         }));
-    } else {
-        output.print_text(&format!("🔧 Executing tool: {}", tool_name));
-        if let Some(ref server) = server_name {
-            output.print_text(&format!("   Server: {}", server));
+    } else if result.success {
+        // Streamed output has already been printed chunk by chunk
+        if !stream {
+            match &data.value {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(text) => output.print_text(text),
+                value => output.print_text(&serde_json::to_string_pretty(value)?),
+            }
+        }
+        match result.duration_ms {
+            Some(ms) => output.print_text(&format!("✅ Completed in {}ms", ms)),
+            None => output.print_text("✅ Completed"),
         }
-        output.print_text(&format!(
-            "   Arguments: {}",
-            serde_json::to_string(&arguments)?
-        ));
-        output.print_text("");
-        output.print_text("⚠️  Tool execution requires configured MCP servers.");
-        output.print_text("   Use 'thulp config add-server' to add a server first.");
     }
+    output.print_truncation_hint(&data);
 
+    if !result.success {
+        return Err(format!(
+            "Tool '{}' failed: {}",
+            tool_name,
+            result.error.as_deref().unwrap_or("unknown error")
+        )
+        .into());
+    }
     Ok(())
 }
 
+/// Transport for a configured MCP server, with its tools renamed under
+/// its `tool_names` rules and the notifications it sends delivered to
+/// `notifications`
+#[cfg(feature = "mcp")]
+fn server_transport(
+    name: &str,
+    config: &ServerConfig,
+    notifications: Option<Arc<dyn NotificationSink>>,
+) -> Result<Box<dyn Transport>, Box<dyn std::error::Error>> {
+    let mut transport = mcp_transport(name, config);
+    if let Some(sink) = notifications {
+        transport = transport.with_notification_sink(sink);
+    }
+    let rules = config.tool_names();
+    if rules.is_empty() {
+        return Ok(Box::new(transport));
+    }
+    Ok(Box::new(RenamedTransport::new(transport, rules.clone())))
+}
+
+/// Transport for a configured MCP server
+#[cfg(not(feature = "mcp"))]
+fn server_transport(
+    name: &str,
+    _config: &ServerConfig,
+    _notifications: Option<Arc<dyn NotificationSink>>,
+) -> Result<Box<dyn Transport>, Box<dyn std::error::Error>> {
+    Err(no_mcp_support(&format!("connect to server '{}'", name)))
+}
+
+/// Server name the `exec` tool is served under, as `local.exec`
+pub const EXEC_SERVER: &str = "local";
+
+/// One transport over every configured MCP server, with tools named
+/// `server.tool`, plus `local.exec` when there is an `exec` policy; no
+/// tools when there is neither. Notifications the servers send go to
+/// `notifications`.
+#[cfg(feature = "mcp")]
+pub fn workspace_transport(
+    servers: &BTreeMap<String, ServerConfig>,
+    exec: Option<ExecPolicy>,
+    notifications: Option<Arc<dyn NotificationSink>>,
+) -> Result<Box<dyn Transport>, Box<dyn std::error::Error>> {
+    if servers.is_empty() && exec.is_none() {
+        return Ok(Box::new(NoTools::default()));
+    }
+    Ok(Box::new(workspace_manager(servers, exec, notifications)?))
+}
+
+/// The connection manager behind [`workspace_transport`], for callers that
+/// check each server's health
+#[cfg(feature = "mcp")]
+pub fn workspace_manager(
+    servers: &BTreeMap<String, ServerConfig>,
+    exec: Option<ExecPolicy>,
+    notifications: Option<Arc<dyn NotificationSink>>,
+) -> Result<McpConnectionManager, Box<dyn std::error::Error>> {
+    let mut manager = McpConnectionManager::new();
+    for (name, config) in servers {
+        let transport = server_transport(name, config, notifications.clone())?;
+        manager.add_server(name.clone(), BoxedTransport(transport))?;
+    }
+    if let Some(policy) = exec {
+        manager.add_server(EXEC_SERVER, thulp_core::ExecTransport::new(policy))?;
+    }
+    Ok(manager)
+}
+
+/// `local.exec` when there is an `exec` policy, and no tools otherwise:
+/// builds without MCP support skip the configured servers
+#[cfg(not(feature = "mcp"))]
+pub fn workspace_transport(
+    servers: &BTreeMap<String, ServerConfig>,
+    exec: Option<ExecPolicy>,
+    _notifications: Option<Arc<dyn NotificationSink>>,
+) -> Result<Box<dyn Transport>, Box<dyn std::error::Error>> {
+    if !servers.is_empty() {
+        let names = servers.keys().cloned().collect::<Vec<_>>().join(", ");
+        eprintln!("⚠️  {}", no_mcp_support(&format!("connect to {}", names)));
+    }
+    match exec {
+        Some(policy) => Ok(Box::new(thulp_core::RenamedTransport::new(
+            thulp_core::ExecTransport::new(policy),
+            thulp_core::ToolNameRules::new().with_prefix(format!("{}.", EXEC_SERVER)),
+        ))),
+        None => Ok(Box::new(NoTools::default())),
+    }
+}
+
+/// A transport without tools, for workspaces with nothing to connect to
+#[derive(Default)]
+struct NoTools {
+    connected: bool,
+}
+
+#[async_trait]
+impl Transport for NoTools {
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        self.connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        Ok(Vec::new())
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        Err(thulp_core::Error::ToolNotFound(call.tool.clone()))
+    }
+}
+
+#[cfg(feature = "mcp")]
+fn mcp_transport(name: &str, config: &ServerConfig) -> McpTransport {
+    match config {
+        ServerConfig::Stdio { command, args, .. } => {
+            McpTransport::new_stdio(name.to_string(), command.clone(), Some(args.clone()))
+        }
+        ServerConfig::Http { url, .. } => McpTransport::new_http(name.to_string(), url.clone()),
+    }
+}
+
+#[cfg(not(feature = "mcp"))]
+fn no_mcp_support(action: &str) -> Box<dyn std::error::Error> {
+    format!(
+        "Can't {}: this build of thulp has no MCP support. \
+         Reinstall with `cargo install thulp --features mcp`.",
+        action
+    )
+    .into()
+}
+
+/// A boxed [`Transport`] usable where a sized one is expected, such as
+/// a skill executor
+pub struct BoxedTransport(pub Box<dyn Transport>);
+
+#[async_trait]
+impl Transport for BoxedTransport {
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        self.0.connect().await
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        self.0.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.0.is_connected()
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        self.0.list_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        self.0.call(call).await
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> thulp_core::Result<ToolResultStream> {
+        self.0.call_streaming(call).await
+    }
+}
+
+/// Connect, check the arguments against the server's definition of the tool
+/// and call it, giving up after `timeout`
+async fn execute_tool(
+    transport: &mut dyn Transport,
+    server: &str,
+    tool: &str,
+    arguments: serde_json::Value,
+    options: CallOptions,
+    output: &Output,
+) -> Result<ToolResult, Box<dyn std::error::Error>> {
+    transport
+        .connect()
+        .await
+        .map_err(|e| format!("Failed to connect to server '{}': {}", server, e))?;
+    let result = call_tool(transport, server, tool, arguments, options, output).await;
+    if let Err(e) = transport.disconnect().await {
+        eprintln!("⚠️  Failed to disconnect from server '{}': {}", server, e);
+    }
+    result
+}
+
+/// Check the arguments against the server's definition of the tool and
+/// call it on a connected transport, giving up after the timeout.
+/// Destructive tools are refused in read-only mode.
+pub async fn call_tool(
+    transport: &dyn Transport,
+    server: &str,
+    tool: &str,
+    arguments: serde_json::Value,
+    options: CallOptions,
+    output: &Output,
+) -> Result<ToolResult, Box<dyn std::error::Error>> {
+    let CallOptions {
+        timeout,
+        stream,
+        read_only,
+    } = options;
+    let definition = transport
+        .list_tools()
+        .await?
+        .into_iter()
+        .find(|definition| definition.name == tool)
+        .ok_or_else(|| format!("Tool '{}' not found on server '{}'", tool, server))?;
+    definition
+        .validate_args(&arguments)
+        .map_err(|e| format!("Invalid arguments for '{}': {}", tool, e))?;
+    if read_only && definition.destructive {
+        return Err(format!(
+            "Tool '{}' is destructive and cannot run in read-only mode",
+            tool
+        )
+        .into());
+    }
+
+    let call = ToolCall::with_args(tool, arguments);
+    let execution = async {
+        if !stream {
+            return transport.call(&call).await;
+        }
+        let chunks = transport.call_streaming(&call).await?.inspect(|chunk| {
+            if let Ok(ToolResult {
+                data: Some(data), ..
+            }) = chunk
+            {
+                match data {
+                    serde_json::Value::String(text) => output.print_text(text),
+                    value => output.print_text(&value.to_string()),
+                }
+            }
+        });
+        collect_stream(Box::pin(chunks)).await
+    };
+    match tokio::time::timeout(timeout, execution).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(format!("Tool '{}' timed out after {}s", tool, timeout.as_secs()).into()),
+    }
+}
+
 pub async fn handle_tool_commands(
     command: ToolCommands,
     output: &Output,
@@ -152,6 +505,8 @@ pub async fn handle_tool_commands(
                 }
             };
 
+            let usage = UsageRenderer::new().render(&tool);
+
             if output.is_json() {
                 output.print_json(&json!({
                     "name": tool.name,
@@ -162,7 +517,9 @@ pub async fn handle_tool_commands(
                         "required": p.required,
                         "description": p.description,
                         "default": p.default,
-                    })).collect::<Vec<_>>()
+                        "examples": p.examples,
+                    })).collect::<Vec<_>>(),
+                    "usage": usage,
                 }));
             } else {
                 output.print_text(&format!("Tool: {}", tool.name));
@@ -185,6 +542,19 @@ pub async fn handle_tool_commands(
                     if let Some(ref default) = param.default {
                         output.print_text(&format!("    Default: {}", default));
                     }
+                    if !param.examples.is_empty() {
+                        let examples: Vec<String> =
+                            param.examples.iter().map(|e| e.to_string()).collect();
+                        output.print_text(&format!("    Examples: {}", examples.join(", ")));
+                    }
+                }
+                output.print_text("");
+                output.print_text("Usage:");
+                output.print_text(&format!("  {}", usage.cli));
+                output.print_text("");
+                output.print_text("JSON:");
+                for line in serde_json::to_string_pretty(&usage.json)?.lines() {
+                    output.print_text(&format!("  {}", line));
                 }
             }
         }
@@ -324,6 +694,7 @@ pub fn create_read_file_tool() -> ToolDefinition {
                 .param_type(ParameterType::String)
                 .required(true)
                 .description("Path to the file to read")
+                .example(json!("./README.md"))
                 .build(),
         )
         .parameter(
@@ -345,6 +716,7 @@ pub fn create_api_call_tool() -> ToolDefinition {
                 .param_type(ParameterType::String)
                 .required(true)
                 .description("URL to make the API request to")
+                .example(json!("https://api.github.com/repos/dirmacs/thulp"))
                 .build(),
         )
         .parameter(
@@ -358,7 +730,211 @@ pub fn create_api_call_tool() -> ToolDefinition {
             Parameter::builder("headers")
                 .param_type(ParameterType::Object)
                 .description("HTTP headers as key-value pairs")
+                .example(json!({"Accept": "application/json"}))
                 .build(),
         )
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    /// Serves an `echo` tool that returns its `text` argument in two chunks
+    struct EchoTransport {
+        connected: bool,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl Transport for EchoTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            self.connected = true;
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            self.connected = false;
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![ToolDefinition::builder("echo")
+                .parameter(
+                    Parameter::builder("text")
+                        .param_type(ParameterType::String)
+                        .required(true)
+                        .build(),
+                )
+                .build()])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ToolResult::success(call.arguments["text"].clone()))
+        }
+
+        async fn call_streaming(&self, call: &ToolCall) -> thulp_core::Result<ToolResultStream> {
+            let text = call.arguments["text"].as_str().unwrap_or_default();
+            let (head, tail) = text.split_at(text.len() / 2);
+            let chunks = vec![
+                Ok(ToolResult::success(json!(head))),
+                Ok(ToolResult::success(json!(tail))),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    fn echo(delay: Duration) -> EchoTransport {
+        EchoTransport {
+            connected: false,
+            delay,
+        }
+    }
+
+    async fn run(
+        transport: &mut EchoTransport,
+        tool: &str,
+        arguments: serde_json::Value,
+        stream: bool,
+    ) -> Result<ToolResult, Box<dyn std::error::Error>> {
+        let output = Output::new(OutputFormat::Json);
+        let options = CallOptions {
+            timeout: Duration::from_millis(100),
+            stream,
+            read_only: false,
+        };
+        execute_tool(transport, "local", tool, arguments, options, &output).await
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool() {
+        let mut transport = echo(Duration::ZERO);
+
+        let result = run(&mut transport, "echo", json!({"text": "hi"}), false).await.unwrap();
+        assert_eq!(result.data, Some(json!("hi")));
+        assert!(!transport.is_connected());
+
+        let result = run(&mut transport, "echo", json!({"text": "hello"}), true).await.unwrap();
+        assert_eq!(result.data, Some(json!("hello")));
+
+        let err = run(&mut transport, "echo", json!({}), false).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid arguments"), "{}", err);
+
+        let err = run(&mut transport, "shout", json!({}), false).await.unwrap_err();
+        assert_eq!(err.to_string(), "Tool 'shout' not found on server 'local'");
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_timeout() {
+        let mut transport = echo(Duration::from_secs(60));
+
+        let err = run(&mut transport, "echo", json!({"text": "hi"}), false).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(!transport.is_connected());
+    }
+
+    #[test]
+    fn test_resolve_server() {
+        let temp = std::env::temp_dir().join(format!("thulp-run-test-{}", std::process::id()));
+        std::fs::create_dir_all(temp.join(".thulp")).unwrap();
+        let config_path = temp.join(".thulp").join("config.yaml");
+
+        std::fs::write(&config_path, "servers: {}\n").unwrap();
+        let servers = config::load_servers(&temp).unwrap();
+        assert!(config::resolve_server(&servers, None).is_err());
+
+        std::fs::write(
+            &config_path,
+            "servers:\n  fs:\n    type: stdio\n    command: mcp-fs\n    args: [.]\n",
+        )
+        .unwrap();
+        let servers = config::load_servers(&temp).unwrap();
+        let (name, server) = config::resolve_server(&servers, None).unwrap();
+        assert_eq!(name, "fs");
+        assert_eq!(
+            server,
+            &ServerConfig::stdio("mcp-fs", vec![".".to_string()])
+        );
+
+        std::fs::write(
+            &config_path,
+            "servers:\n  fs:\n    type: stdio\n    command: mcp-fs\n  web:\n    type: http\n    url: http://localhost:8080\n",
+        )
+        .unwrap();
+        let servers = config::load_servers(&temp).unwrap();
+        let err = config::resolve_server(&servers, None).unwrap_err();
+        assert!(err.to_string().contains("fs, web"), "{}", err);
+        let (name, _) = config::resolve_server(&servers, Some("web")).unwrap();
+        assert_eq!(name, "web");
+        assert!(config::resolve_server(&servers, Some("db")).is_err());
+
+        std::fs::write(
+            &config_path,
+            "servers:\n  gh:\n    type: http\n    url: http://localhost:8080\n    tool_names:\n      snake_case: true\n      prefix: gh_\n",
+        )
+        .unwrap();
+        let servers = config::load_servers(&temp).unwrap();
+        assert_eq!(servers["gh"].tool_names().apply("createIssue"), "gh_create_issue");
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exec_policy_from_workspace() {
+        let temp = std::env::temp_dir().join(format!("thulp-exec-policy-{}", std::process::id()));
+        std::fs::create_dir_all(temp.join(".thulp")).unwrap();
+        let config_path = temp.join(".thulp").join("config.yaml");
+
+        std::fs::write(&config_path, "servers: {}\n").unwrap();
+        assert!(config::load_exec_policy(&temp).unwrap().is_none());
+
+        std::fs::write(&config_path, "exec:\n  commands: [echo]\n  timeout: 5\n").unwrap();
+        let policy = config::load_exec_policy(&temp).unwrap().unwrap();
+        assert!(policy.allows("echo"));
+        assert_eq!(policy.timeout, Duration::from_secs(5));
+        assert_eq!(policy.working_dir.as_deref(), Some(temp.as_path()));
+
+        let mut transport = workspace_transport(&BTreeMap::new(), Some(policy), None).unwrap();
+        transport.connect().await.unwrap();
+        let tools = transport.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "local.exec");
+        let result = transport
+            .call(&ToolCall::with_args("local.exec", json!({"command": "echo", "args": ["hi"]})))
+            .await
+            .unwrap();
+        assert_eq!(result.data.unwrap()["stdout"], json!("hi\n"));
+
+        let mut transport = workspace_transport(&BTreeMap::new(), None, None).unwrap();
+        transport.connect().await.unwrap();
+        assert!(transport.list_tools().await.unwrap().is_empty());
+
+        std::fs::write(&config_path, "exec:\n  commands: echo\n").unwrap();
+        assert!(config::load_exec_policy(&temp).is_err());
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+
+    #[test]
+    fn test_tool_timeout() {
+        let temp = std::env::temp_dir().join(format!("thulp-tool-timeout-{}", std::process::id()));
+        std::fs::create_dir_all(temp.join(".thulp")).unwrap();
+        assert_eq!(tool_timeout(&temp, None).unwrap(), Duration::from_secs(30));
+
+        std::fs::write(
+            temp.join(".thulp").join("config.yaml"),
+            "settings:\n  default_timeout: 12\n",
+        )
+        .unwrap();
+        assert_eq!(tool_timeout(&temp, None).unwrap(), Duration::from_secs(12));
+        assert_eq!(tool_timeout(&temp, Some(3)).unwrap(), Duration::from_secs(3));
+
+        std::fs::remove_dir_all(&temp).unwrap();
+    }
+}
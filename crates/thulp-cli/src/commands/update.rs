@@ -0,0 +1,312 @@
+#[cfg(feature = "remote")]
+use clap::Subcommand;
+use semver::{Version, VersionReq};
+#[cfg(feature = "remote")]
+use serde_json::json;
+use std::path::Path;
+#[cfg(feature = "remote")]
+use std::process::Command;
+#[cfg(feature = "remote")]
+use crate::output::Output;
+use thulp_workspace::WorkspaceConfig;
+
+/// crates.io API endpoint listing published thulp versions
+#[cfg(feature = "remote")]
+const CRATES_IO_URL: &str = "https://crates.io/api/v1/crates/thulp";
+
+/// Version of the running binary
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg(feature = "remote")]
+#[derive(Subcommand, Debug)]
+pub enum SelfCommands {
+    /// Update thulp to the latest release (or the workspace's pinned range)
+    Update {
+        /// Version or version range to install (e.g. 0.4.1, ^0.4)
+        #[arg(long, value_name = "VERSION")]
+        version: Option<String>,
+
+        /// Only check whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Reinstall even if the version is already installed
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// What to do when the binary does not satisfy `required_version`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Print a warning and continue
+    Warn,
+    /// Refuse to run
+    Block,
+}
+
+/// Version pin read from a workspace's config.yaml
+#[derive(Debug, Clone)]
+pub struct VersionPin {
+    pub required: VersionReq,
+    pub policy: VersionPolicy,
+}
+
+/// Read `required_version` and `version_policy` from a workspace config
+pub fn read_version_pin(workspace_dir: &Path) -> Result<Option<VersionPin>, Box<dyn std::error::Error>> {
+    let config_path = workspace_dir.join(".thulp").join("config.yaml");
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let config = WorkspaceConfig::load_raw(&config_path)?;
+
+    let Some(required) = config.required_version.as_deref() else {
+        return Ok(None);
+    };
+    let required = VersionReq::parse(required)
+        .map_err(|e| format!("Invalid required_version '{}' in {}: {}", required, config_path.display(), e))?;
+    let policy = match config.version_policy.as_deref() {
+        None | Some("block") => VersionPolicy::Block,
+        Some("warn") => VersionPolicy::Warn,
+        Some(other) => {
+            return Err(format!("Invalid version_policy '{}' (expected warn or block)", other).into())
+        }
+    };
+    Ok(Some(VersionPin { required, policy }))
+}
+
+/// Check the running binary against the workspace's pinned version range.
+///
+/// Warns on stderr or fails, depending on `version_policy`.
+pub fn check_required_version(workspace_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(pin) = read_version_pin(workspace_dir)? else {
+        return Ok(());
+    };
+    let current = Version::parse(CURRENT_VERSION)?;
+    if pin.required.matches(&current) {
+        return Ok(());
+    }
+
+    let message = format!(
+        "thulp {} does not satisfy required_version '{}' of this workspace",
+        current, pin.required
+    );
+    match pin.policy {
+        VersionPolicy::Warn => {
+            eprintln!("⚠️  {}", message);
+            Ok(())
+        }
+        VersionPolicy::Block => Err(format!(
+            "{}. Run 'thulp self update' to install a matching version, or set version_policy: warn",
+            message
+        )
+        .into()),
+    }
+}
+
+/// Pick the newest version matching `req`; without a requirement, the newest stable release
+#[cfg(feature = "remote")]
+pub fn select_version<'a>(available: &'a [Version], req: Option<&VersionReq>) -> Option<&'a Version> {
+    available
+        .iter()
+        .filter(|v| match req {
+            Some(req) => req.matches(v),
+            None => v.pre.is_empty(),
+        })
+        .max()
+}
+
+/// Parse a `--version` argument: a bare version means exactly that version
+#[cfg(feature = "remote")]
+fn parse_requested(version: &str) -> Result<VersionReq, Box<dyn std::error::Error>> {
+    match Version::parse(version) {
+        Ok(exact) => Ok(VersionReq::parse(&format!("={}", exact))?),
+        Err(_) => Ok(VersionReq::parse(version)
+            .map_err(|e| format!("Invalid version '{}': {}", version, e))?),
+    }
+}
+
+/// Cargo features this binary was built with
+#[cfg(feature = "remote")]
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("adapter", cfg!(feature = "adapter")),
+        ("mcp", cfg!(feature = "mcp")),
+        ("remote", cfg!(feature = "remote")),
+        ("repl", cfg!(feature = "repl")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// Fetch the non-yanked versions of thulp published on crates.io
+#[cfg(feature = "remote")]
+async fn fetch_versions() -> Result<Vec<Version>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::builder()
+        .user_agent(format!("thulp/{} (self update)", CURRENT_VERSION))
+        .build()?;
+    let body: serde_json::Value = client
+        .get(CRATES_IO_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let versions = body
+        .get("versions")
+        .and_then(|v| v.as_array())
+        .ok_or("Unexpected response from crates.io")?;
+    Ok(versions
+        .iter()
+        .filter(|v| !v.get("yanked").and_then(|y| y.as_bool()).unwrap_or(false))
+        .filter_map(|v| v.get("num").and_then(|n| n.as_str()))
+        .filter_map(|n| Version::parse(n).ok())
+        .collect())
+}
+
+/// Handle `thulp self` subcommands
+#[cfg(feature = "remote")]
+pub async fn handle_self_commands(
+    command: SelfCommands,
+    workspace_dir: &Path,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        SelfCommands::Update { version, check, force } => {
+            handle_update(version, check, force, workspace_dir, output).await
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+async fn handle_update(
+    version: Option<String>,
+    check: bool,
+    force: bool,
+    workspace_dir: &Path,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let current = Version::parse(CURRENT_VERSION)?;
+    // An explicit version wins over the workspace pin
+    let req = match &version {
+        Some(version) => Some(parse_requested(version)?),
+        None => read_version_pin(workspace_dir)?.map(|pin| pin.required),
+    };
+
+    let available = fetch_versions().await?;
+    let target = select_version(&available, req.as_ref())
+        .cloned()
+        .ok_or_else(|| match &req {
+            Some(req) => format!("No published thulp version matches '{}'", req),
+            None => "No published thulp versions found".to_string(),
+        })?;
+
+    let up_to_date = target == current && !force;
+    if check || up_to_date {
+        let status = if target == current {
+            "up_to_date"
+        } else {
+            "update_available"
+        };
+        if output.is_json() {
+            output.print_json(&json!({
+                "status": status,
+                "current": current.to_string(),
+                "target": target.to_string(),
+                "requirement": req.as_ref().map(|r| r.to_string()),
+            }));
+        } else if target == current {
+            output.print_text(&format!("✅ thulp {} is up to date", current));
+        } else {
+            output.print_text(&format!("⬆️  thulp {} is available (installed: {})", target, current));
+            output.print_text("   Run 'thulp self update' to install it");
+        }
+        return Ok(());
+    }
+
+    let mut install = Command::new("cargo");
+    install.args(["install", "thulp", "--locked", "--version"]);
+    install.arg(format!("={}", target));
+    // Reinstall with the features this binary was built with
+    install.args(["--no-default-features", "--features", &enabled_features().join(",")]);
+    if target == current {
+        install.arg("--force");
+    }
+    if !output.is_json() {
+        output.print_text(&format!("📦 Installing thulp {} (installed: {})...", target, current));
+    }
+    let status = install.status().map_err(|e| format!("Failed to run cargo install: {}", e))?;
+    if !status.success() {
+        return Err(format!("cargo install failed with {}", status).into());
+    }
+
+    if output.is_json() {
+        output.print_json(&json!({
+            "status": "updated",
+            "previous": current.to_string(),
+            "installed": target.to_string(),
+        }));
+    } else {
+        output.print_text(&format!("✅ Updated thulp {} → {}", current, target));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "remote")]
+    fn versions(list: &[&str]) -> Vec<Version> {
+        list.iter().map(|v| Version::parse(v).unwrap()).collect()
+    }
+
+    fn workspace(name: &str, config: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "thulp-version-pin-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(dir.join(".thulp")).unwrap();
+        std::fs::write(dir.join(".thulp").join("config.yaml"), config).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_select_version() {
+        let available = versions(&["0.3.1", "0.3.2", "0.4.0", "0.5.0-beta.1"]);
+
+        assert_eq!(select_version(&available, None).unwrap().to_string(), "0.4.0");
+        let req = VersionReq::parse("~0.3").unwrap();
+        assert_eq!(select_version(&available, Some(&req)).unwrap().to_string(), "0.3.2");
+        let req = parse_requested("0.3.1").unwrap();
+        assert_eq!(select_version(&available, Some(&req)).unwrap().to_string(), "0.3.1");
+        let req = VersionReq::parse(">=1").unwrap();
+        assert!(select_version(&available, Some(&req)).is_none());
+    }
+
+    #[test]
+    fn test_version_pin() {
+        let dir = workspace("block", "name: demo\nrequired_version: \">=99.0\"\n");
+        let pin = read_version_pin(&dir).unwrap().unwrap();
+        assert_eq!(pin.policy, VersionPolicy::Block);
+        assert!(check_required_version(&dir)
+            .unwrap_err()
+            .to_string()
+            .contains("does not satisfy required_version"));
+
+        let dir = workspace("warn", "required_version: \">=99.0\"\nversion_policy: warn\n");
+        assert!(check_required_version(&dir).is_ok());
+
+        let dir = workspace("match", &format!("required_version: \"={}\"\n", CURRENT_VERSION));
+        assert!(check_required_version(&dir).is_ok());
+
+        let dir = workspace("invalid", "required_version: \"not a range\"\n");
+        assert!(read_version_pin(&dir).is_err());
+
+        assert!(read_version_pin(Path::new("/nonexistent")).unwrap().is_none());
+    }
+}
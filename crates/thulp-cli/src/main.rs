@@ -1,21 +1,21 @@
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell};
-use serde::Serialize;
-use serde_json::json;
-use std::path::{Path, PathBuf};
-use thulp_adapter::AdapterGenerator;
-use thulp_core::{Parameter, ParameterType, ToolCall, ToolDefinition};
-
-#[cfg(feature = "mcp")]
-use thulp_mcp::{McpClient, McpTransport};
+use std::path::PathBuf;
 
+mod approval;
 mod output;
+mod progress;
 mod commands;
 use output::{Output, OutputFormat};
-use commands::config::{ConfigCommands, ServerType};
-use commands::skill::{SkillCommands, SkillScope, ExportFormat};
+use commands::config::ConfigCommands;
+use commands::secrets::SecretCommands;
+use commands::skill::SkillCommands;
 use commands::tools::ToolCommands;
+#[cfg(feature = "adapter")]
 use commands::convert::ConvertCommands;
+use commands::guidance::GuidanceCommands;
+#[cfg(feature = "remote")]
+use commands::update::SelfCommands;
 
 #[cfg(feature = "mcp")]
 use commands::mcp::McpCommands;
@@ -33,6 +33,14 @@ struct Cli {
     #[arg(short = 'w', long, global = true)]
     workspace: Option<PathBuf>,
 
+    /// Truncate printed results to this many bytes (0 = no limit)
+    #[arg(long, value_name = "BYTES", default_value_t = output::DEFAULT_MAX_OUTPUT_BYTES, global = true)]
+    max_output_bytes: usize,
+
+    /// Don't write to workspace storage (also enabled by `read_only: true` in config)
+    #[arg(long, global = true)]
+    read_only: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -68,13 +76,17 @@ enum Commands {
         #[arg(short, long)]
         json: Option<String>,
 
-        /// Timeout in seconds
-        #[arg(short, long, default_value = "30")]
-        timeout: u64,
+        /// Timeout in seconds (default: the workspace's `default_timeout`)
+        #[arg(short, long)]
+        timeout: Option<u64>,
 
         /// Dry run (validate without executing)
         #[arg(long)]
         dry_run: bool,
+
+        /// Print partial output as the tool streams it
+        #[arg(long)]
+        stream: bool,
     },
 
     /// Skill workflow commands
@@ -96,21 +108,75 @@ enum Commands {
         action: McpCommands,
     },
 
-    /// Convert OpenAPI specifications to tool definitions
+    #[cfg(feature = "adapter")]
+    /// Convert OpenAPI specifications and tool manifests to tool definitions
     Convert {
         #[command(subcommand)]
         action: ConvertCommands,
     },
 
+    /// Prompt template commands
+    Guidance {
+        #[command(subcommand)]
+        action: GuidanceCommands,
+    },
+
+    #[cfg(feature = "repl")]
+    /// Start an interactive session that keeps MCP servers connected
+    Repl {
+        /// Timeout in seconds for each tool call and skill step (default:
+        /// the workspace's `settings`)
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+
+    #[cfg(feature = "mcp")]
+    /// Serve the workspace's tools and skills as an MCP server (stdio by default)
+    Serve {
+        /// Serve over HTTP on this address instead of stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+
+        /// Timeout in seconds for each skill step (default: the workspace's
+        /// `settings`)
+        #[arg(short, long)]
+        timeout: Option<u64>,
+
+        /// Who approves skill steps marked requires_approval and calls to
+        /// destructive tools: approve, deny, or a webhook URL (default: deny
+        /// steps, refuse destructive tools)
+        #[arg(long, value_name = "MODE")]
+        approval: Option<approval::ApprovalMode>,
+
+        /// Token HTTP clients must send as `Authorization: Bearer`; required
+        /// to serve on an address other than loopback
+        #[arg(long, value_name = "TOKEN", env = "THULP_SERVE_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
+
     /// Workspace configuration commands
     Config {
         #[command(subcommand)]
         action: ConfigCommands,
     },
 
+    /// Manage the workspace's secrets
+    Secrets {
+        #[command(subcommand)]
+        action: SecretCommands,
+    },
+
     /// Demonstrate core functionality
     Demo,
 
+    #[cfg(feature = "remote")]
+    /// Manage the thulp installation
+    #[command(name = "self")]
+    SelfManage {
+        #[command(subcommand)]
+        action: SelfCommands,
+    },
+
     /// Validate configuration files
     Validate {
         #[arg(value_name = "FILE")]
@@ -134,12 +200,24 @@ enum Commands {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
-    let output = Output::new(cli.output);
+    let output = Output::new(cli.output).with_max_output_bytes(cli.max_output_bytes);
     let workspace_dir = cli.workspace.unwrap_or_else(|| PathBuf::from("."));
+    let read_only = cli.read_only || commands::config::config_read_only(&workspace_dir);
+
+    // Commands that set up or repair the workspace run whatever the pin says
+    let exempt = match cli.command {
+        Commands::Init { .. } | Commands::Config { .. } | Commands::Completions { .. } => true,
+        #[cfg(feature = "remote")]
+        Commands::SelfManage { .. } => true,
+        _ => false,
+    };
+    if !exempt {
+        commands::update::check_required_version(&workspace_dir)?;
+    }
 
     match cli.command {
         Commands::Init { dir, name, force } => {
-            commands::config::handle_init(dir.unwrap_or(workspace_dir), name, force, &output)?
+            commands::config::handle_init(dir.unwrap_or(workspace_dir), name, force, read_only, &output)?
         }
         Commands::Run {
             tool,
@@ -147,15 +225,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             json,
             timeout,
             dry_run,
-        } => commands::tools::handle_run(&tool, args, json, timeout, dry_run, &output).await?,
+            stream,
+        } => {
+            let options = commands::tools::RunOptions { timeout, dry_run, stream, read_only };
+            commands::tools::handle_run(&workspace_dir, &tool, args, json, options, &output).await?
+        }
         Commands::Skill { action } => {
-            commands::skill::handle_skill_commands(action, &workspace_dir, &output).await?
+            commands::skill::handle_skill_commands(action, &workspace_dir, read_only, &output).await?
         }
         Commands::Tools { action } => commands::tools::handle_tool_commands(action, &output).await?,
         #[cfg(feature = "mcp")]
         Commands::Mcp { action } => commands::mcp::handle_mcp_commands(action, &output).await?,
+        #[cfg(feature = "adapter")]
         Commands::Convert { action } => commands::convert::handle_convert_commands(action, &output)?,
-        Commands::Config { action } => commands::config::handle_config_commands(action, &workspace_dir, &output)?,
+        Commands::Guidance { action } => {
+            commands::guidance::handle_guidance_commands(action, &workspace_dir, &output)?
+        }
+        #[cfg(feature = "repl")]
+        Commands::Repl { timeout } => {
+            commands::repl::handle_repl(&workspace_dir, timeout, read_only, &output).await?
+        }
+        #[cfg(feature = "mcp")]
+        Commands::Serve {
+            http,
+            timeout,
+            approval,
+            token,
+        } => {
+            commands::serve::handle_serve(
+                &workspace_dir,
+                http,
+                token,
+                timeout,
+                approval,
+                read_only,
+                &output,
+            )
+            .await?
+        }
+        Commands::Config { action } => commands::config::handle_config_commands(action, &workspace_dir, read_only, &output)?,
+        Commands::Secrets { action } => {
+            commands::secrets::handle_secret_commands(action, &workspace_dir, read_only, &output)?
+        }
+        #[cfg(feature = "remote")]
+        Commands::SelfManage { action } => {
+            commands::update::handle_self_commands(action, &workspace_dir, &output).await?
+        }
         Commands::Demo => commands::tools::run_demo(&output).await?,
         Commands::Validate { file } => commands::tools::validate_file(&file, &output)?,
         Commands::Completions { shell, dir } => generate_completions(shell, dir)?,
@@ -219,6 +334,24 @@ mod tests {
         assert!(matches!(cli.output, OutputFormat::Json));
     }
 
+    #[test]
+    fn test_max_output_bytes() {
+        let cli = Cli::try_parse_from(["thulp", "demo"]).unwrap();
+        assert_eq!(cli.max_output_bytes, output::DEFAULT_MAX_OUTPUT_BYTES);
+
+        let cli = Cli::try_parse_from(["thulp", "demo", "--max-output-bytes", "0"]).unwrap();
+        assert_eq!(cli.max_output_bytes, 0);
+
+        assert!(Cli::try_parse_from(["thulp", "--max-output-bytes", "lots", "demo"]).is_err());
+    }
+
+    #[test]
+    fn test_read_only_flag() {
+        let cli = Cli::try_parse_from(["thulp", "config", "set", "a", "1", "--read-only"]).unwrap();
+        assert!(cli.read_only);
+        assert!(!Cli::try_parse_from(["thulp", "demo"]).unwrap().read_only);
+    }
+
     #[test]
     fn test_completions_command() {
         let cli = Cli::try_parse_from(["thulp", "completions", "bash"]);
@@ -269,6 +402,74 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_run_command_stream() {
+        let cli = Cli::try_parse_from(["thulp", "run", "tail_logs", "--stream"]).unwrap();
+        assert!(matches!(cli.command, Commands::Run { stream: true, .. }));
+    }
+
+    #[test]
+    fn test_guidance_test_command() {
+        let cli = Cli::try_parse_from(["thulp", "guidance", "test", "--dir", "prompts", "--update"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Guidance {
+                action: GuidanceCommands::Test { update: true, .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_guidance_usage_command() {
+        let cli = Cli::try_parse_from(["thulp", "guidance", "usage", "--dir", "prompts"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Guidance {
+                action: GuidanceCommands::Usage { dir: Some(_) }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_secrets_command() {
+        let cli = Cli::try_parse_from(["thulp", "secrets", "set", "github_token"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Secrets {
+                action: SecretCommands::Set { value: None, .. }
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["thulp", "secrets", "remove", "github_token"]).unwrap();
+        assert!(matches!(cli.command, Commands::Secrets { action: SecretCommands::Remove { .. } }));
+    }
+
+    #[cfg(feature = "repl")]
+    #[test]
+    fn test_repl_command() {
+        let cli = Cli::try_parse_from(["thulp", "repl", "--timeout", "5"]).unwrap();
+        assert!(matches!(cli.command, Commands::Repl { timeout: Some(5) }));
+    }
+
+    #[cfg(feature = "mcp")]
+    #[test]
+    fn test_serve_command() {
+        let cli = Cli::try_parse_from(["thulp", "serve"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Serve { http: None, timeout: None, approval: None, token: None }
+        ));
+
+        let cli = Cli::try_parse_from(["thulp", "serve", "--http", "127.0.0.1:3000"]).unwrap();
+        assert!(matches!(cli.command, Commands::Serve { http: Some(_), .. }));
+
+        let cli = Cli::try_parse_from(["thulp", "serve", "--approval", "approve"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Serve { approval: Some(approval::ApprovalMode::Approve), .. }
+        ));
+    }
+
     #[test]
     fn test_skill_list_command() {
         let cli = Cli::try_parse_from(["thulp", "skill", "list"]);
@@ -300,17 +501,47 @@ mod tests {
             "--timeout", "120",
             "--dry-run",
             "--continue-on-error",
+            "--no-progress",
             "input=value"
         ]);
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_skill_run_approval() {
+        let cli = Cli::try_parse_from(["thulp", "skill", "run", "cleanup", "--approval", "deny"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Skill {
+                action: SkillCommands::Run { approval: Some(approval::ApprovalMode::Deny), .. }
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["thulp", "skill", "run", "cleanup", "--approval", "maybe"]);
+        assert!(cli.is_err());
+    }
+
     #[test]
     fn test_skill_validate_command() {
         let cli = Cli::try_parse_from(["thulp", "skill", "validate", "skill.yaml"]);
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_skill_inspect_command() {
+        let cli = Cli::try_parse_from([
+            "thulp", "skill", "inspect", "run-1", "--step", "search", "--template", "{{query}}",
+        ]);
+        assert!(cli.is_ok());
+    }
+
+    #[test]
+    fn test_skill_diff_command() {
+        let cli = Cli::try_parse_from(["thulp", "skill", "diff", "research", "traces/run.json"]);
+        assert!(cli.is_ok());
+    }
+
     #[test]
     fn test_skill_export_command() {
         let cli = Cli::try_parse_from(["thulp", "skill", "export", "my-skill", "--format", "shell"]);
@@ -375,6 +606,55 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[cfg(feature = "adapter")]
+    #[test]
+    fn test_convert_manifest_command() {
+        let cli = Cli::try_parse_from([
+            "thulp", "convert", "manifest", "tools.json", "--out", "thulp-tools.json",
+        ]);
+        assert!(cli.is_ok());
+    }
+
+    #[cfg(feature = "adapter")]
+    #[test]
+    fn test_convert_openapi_tool_names() {
+        let cli = Cli::try_parse_from([
+            "thulp", "convert", "open-api", "api.yaml", "--snake-case", "--prefix", "crm_",
+        ]);
+        assert!(cli.is_ok());
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_self_update_command() {
+        let cli = Cli::try_parse_from(["thulp", "self", "update", "--check", "--version", "^0.3"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::SelfManage {
+                action: SelfCommands::Update { check: true, .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_subcommands_follow_features() {
+        let cli = Cli::command();
+        let has = |name: &str| cli.find_subcommand(name).is_some();
+        assert_eq!(has("convert"), cfg!(feature = "adapter"));
+        assert_eq!(has("mcp"), cfg!(feature = "mcp"));
+        assert_eq!(has("serve"), cfg!(feature = "mcp"));
+        assert_eq!(has("self"), cfg!(feature = "remote"));
+        assert_eq!(has("repl"), cfg!(feature = "repl"));
+
+        let skill = cli.find_subcommand("skill").unwrap();
+        assert_eq!(skill.find_subcommand("install").is_some(), cfg!(feature = "remote"));
+
+        // The minimal build (`--no-default-features`) keeps the core commands
+        for name in ["init", "run", "skill", "tools", "guidance", "config", "validate"] {
+            assert!(has(name), "missing subcommand '{}'", name);
+        }
+    }
+
     #[test]
     fn test_workspace_flag() {
         let cli = Cli::try_parse_from(["thulp", "-w", "/custom/path", "config", "show"]);
@@ -1,5 +1,6 @@
 use clap::ValueEnum;
 use serde::Serialize;
+use serde_json::Value;
 
 /// Output format for CLI commands
 #[derive(Debug, Clone, Copy, Default, ValueEnum)]
@@ -16,11 +17,21 @@ pub enum OutputFormat {
 /// Output helper for formatted output
 pub struct Output {
     pub format: OutputFormat,
+    /// Byte budget for printed results (0 = unlimited)
+    pub max_output_bytes: usize,
 }
 
 impl Output {
     pub fn new(format: OutputFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+        }
+    }
+
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
     }
 
     pub fn print_text(&self, text: &str) {
@@ -41,7 +52,229 @@ impl Output {
         }
     }
 
+    /// Print `data` as one line of JSON whatever the JSON format, for
+    /// output that is a stream of lines
+    pub fn print_json_line<T: Serialize>(&self, data: &T) {
+        if self.is_json() {
+            println!("{}", serde_json::to_string(data).unwrap());
+        }
+    }
+
     pub fn is_json(&self) -> bool {
         matches!(self.format, OutputFormat::Json | OutputFormat::JsonCompact)
     }
+
+    /// Truncate a tool or skill result to the configured byte budget.
+    pub fn truncate_result(&self, result: &Value) -> TruncatedJson {
+        truncate_json(result, self.max_output_bytes)
+    }
+
+    /// Tell the user a result was truncated and how to see all of it.
+    ///
+    /// Written to stderr so JSON on stdout stays parseable.
+    pub fn print_truncation_hint(&self, result: &TruncatedJson) {
+        if !result.truncated {
+            return;
+        }
+        eprintln!(
+            "⚠️  Result truncated: showing {} of {} bytes.",
+            result.shown_bytes, result.original_bytes
+        );
+        eprintln!("   Re-run with --max-output-bytes 0 to print everything.");
+    }
+}
+
+/// Default limit for printed results, in bytes of compact JSON
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Shortest a string is cut down to before other limits are tightened
+const MIN_STRING_BYTES: usize = 16;
+
+/// A result value after truncation to an output budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruncatedJson {
+    /// The (possibly truncated) value
+    pub value: Value,
+    /// Size of the original value as compact JSON
+    pub original_bytes: usize,
+    /// Size of `value` as compact JSON
+    pub shown_bytes: usize,
+    /// Whether anything was cut
+    pub truncated: bool,
+}
+
+/// Truncate a JSON value so its compact encoding fits in `max_bytes`.
+///
+/// The shape of the value is kept: objects keep all of their keys, long
+/// strings are cut with a `… (+N bytes)` marker, arrays keep their leading
+/// items followed by a `… N more items` marker, and containers nested deeper
+/// than the current depth limit are summarised as `{… N keys}` or
+/// `[… N items]`. Limits are tightened until the value fits; top-level keys
+/// are always preserved, so a very wide object may still exceed the budget.
+///
+/// A `max_bytes` of 0 disables truncation.
+pub fn truncate_json(value: &Value, max_bytes: usize) -> TruncatedJson {
+    let original_bytes = compact_len(value);
+    if max_bytes == 0 || original_bytes <= max_bytes {
+        return TruncatedJson {
+            value: value.clone(),
+            original_bytes,
+            shown_bytes: original_bytes,
+            truncated: false,
+        };
+    }
+
+    let mut limits = Limits {
+        depth: 8,
+        items: 64,
+        string: max_bytes.max(MIN_STRING_BYTES),
+    };
+    loop {
+        let candidate = limits.apply(value, 0);
+        let shown_bytes = compact_len(&candidate);
+        if shown_bytes <= max_bytes || !limits.tighten() {
+            return TruncatedJson {
+                value: candidate,
+                original_bytes,
+                shown_bytes,
+                truncated: true,
+            };
+        }
+    }
+}
+
+fn compact_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
+}
+
+/// Truncation limits, tightened step by step until a value fits
+struct Limits {
+    depth: usize,
+    items: usize,
+    string: usize,
+}
+
+impl Limits {
+    /// Tighten the limits; returns false once they can't get any tighter.
+    ///
+    /// Strings and arrays shrink first, then nesting depth.
+    fn tighten(&mut self) -> bool {
+        if self.string > MIN_STRING_BYTES || self.items > 1 {
+            self.string = (self.string / 2).max(MIN_STRING_BYTES);
+            self.items = (self.items / 2).max(1);
+            true
+        } else if self.depth > 1 {
+            self.depth -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn apply(&self, value: &Value, depth: usize) -> Value {
+        match value {
+            Value::String(s) if s.len() > self.string => {
+                let mut end = self.string;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                Value::String(format!("{}… (+{} bytes)", &s[..end], s.len() - end))
+            }
+            Value::Array(items) if depth >= self.depth => {
+                Value::String(format!("[… {} items]", items.len()))
+            }
+            Value::Array(items) => {
+                let mut kept: Vec<Value> = items
+                    .iter()
+                    .take(self.items)
+                    .map(|v| self.apply(v, depth + 1))
+                    .collect();
+                if items.len() > self.items {
+                    kept.push(Value::String(format!(
+                        "… {} more items",
+                        items.len() - self.items
+                    )));
+                }
+                Value::Array(kept)
+            }
+            Value::Object(obj) if depth >= self.depth => {
+                Value::String(format!("{{… {} keys}}", obj.len()))
+            }
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), self.apply(v, depth + 1)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_small_values_untouched() {
+        let value = json!({"ok": true, "items": [1, 2, 3]});
+        let result = truncate_json(&value, 1024);
+        assert!(!result.truncated);
+        assert_eq!(result.value, value);
+
+        let unlimited = truncate_json(&json!({"text": "x".repeat(10_000)}), 0);
+        assert!(!unlimited.truncated);
+    }
+
+    #[test]
+    fn test_long_strings_are_cut() {
+        let value = json!({"content": "a".repeat(5_000), "status": "ok"});
+        let result = truncate_json(&value, 512);
+
+        assert!(result.truncated);
+        assert!(result.shown_bytes <= 512);
+        assert_eq!(result.original_bytes, compact_len(&value));
+        assert_eq!(result.value["status"], "ok");
+        assert!(result.value["content"].as_str().unwrap().contains("bytes)"));
+    }
+
+    #[test]
+    fn test_arrays_keep_leading_items() {
+        let rows: Vec<Value> = (0..500).map(|i| json!({"id": i, "name": "row"})).collect();
+        let result = truncate_json(&json!({"rows": rows}), 1024);
+
+        assert!(result.truncated);
+        assert!(result.shown_bytes <= 1024);
+        let kept = result.value["rows"].as_array().unwrap();
+        assert_eq!(kept[0]["id"], 0);
+        assert!(kept
+            .last()
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .ends_with("more items"));
+    }
+
+    #[test]
+    fn test_deep_nesting_is_summarised_and_keys_kept() {
+        let mut value = json!({"leaf": "x".repeat(200)});
+        for i in 0..20 {
+            value = json!({format!("level{}", i): value, "pad": "y".repeat(100)});
+        }
+        let result = truncate_json(&value, 200);
+
+        assert!(result.truncated);
+        assert!(result.shown_bytes <= 200);
+        let obj = result.value.as_object().unwrap();
+        assert!(obj.contains_key("level19"));
+        assert!(obj.contains_key("pad"));
+    }
+
+    #[test]
+    fn test_multibyte_strings() {
+        let value = json!("é".repeat(1_000));
+        let result = truncate_json(&value, 64);
+        assert!(result.truncated);
+        assert!(result.value.as_str().unwrap().starts_with('é'));
+    }
 }
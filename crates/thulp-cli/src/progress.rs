@@ -0,0 +1,186 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use thulp_skills::ProgressEvent;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+
+use crate::output::Output;
+
+/// How skill progress is shown while a skill runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Nothing
+    Off,
+    /// A live progress bar on a terminal, one line per step otherwise
+    Bar,
+    /// One JSON object per event on stdout
+    Ndjson,
+}
+
+impl ProgressMode {
+    /// The mode matching the output format
+    pub fn for_output(output: &Output) -> Self {
+        if output.is_json() {
+            Self::Ndjson
+        } else {
+            Self::Bar
+        }
+    }
+
+    /// Render the events from `events` until the channel closes, returning
+    /// the bar being drawn, if any, so prompts can hide it
+    pub fn spawn(
+        self,
+        events: UnboundedReceiver<ProgressEvent>,
+    ) -> (JoinHandle<()>, Option<ProgressBar>) {
+        match self {
+            Self::Off => (tokio::spawn(drain(events)), None),
+            Self::Bar => {
+                let bar = ProgressBar::new(0);
+                (tokio::spawn(render_bar(events, bar.clone())), Some(bar))
+            }
+            Self::Ndjson => (tokio::spawn(render_ndjson(events)), None),
+        }
+    }
+}
+
+async fn drain(mut events: UnboundedReceiver<ProgressEvent>) {
+    while events.recv().await.is_some() {}
+}
+
+async fn render_ndjson(mut events: UnboundedReceiver<ProgressEvent>) {
+    while let Some(event) = events.recv().await {
+        if let Ok(line) = serde_json::to_string(&event) {
+            println!("{}", line);
+        }
+    }
+}
+
+async fn render_bar(mut events: UnboundedReceiver<ProgressEvent>, bar: ProgressBar) {
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} [{bar:30}] {pos}/{len} {msg} ({elapsed})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    bar.enable_steady_tick(std::time::Duration::from_millis(120));
+
+    while let Some(event) = events.recv().await {
+        if let Some(line) = step_line(&event) {
+            // Without a terminal the bar is hidden, so steps are logged instead
+            if bar.is_hidden() {
+                println!("{}", line);
+            } else {
+                bar.println(line);
+            }
+        }
+        match event {
+            ProgressEvent::SkillStarted {
+                depth: 0, steps, ..
+            } => bar.set_length(steps as u64),
+            ProgressEvent::StepStarted {
+                depth: 0,
+                index,
+                total,
+                step,
+                tool,
+            } => bar.set_message(format!("[{}/{}] {} ({})", index + 1, total, step, tool)),
+            ProgressEvent::StepFinished { depth: 0, .. }
+            | ProgressEvent::StepSkipped { depth: 0, .. } => bar.inc(1),
+            _ => {}
+        }
+    }
+    bar.finish_and_clear();
+}
+
+/// The line logged for an event, nested skills indented
+fn step_line(event: &ProgressEvent) -> Option<String> {
+    let (depth, line) = match event {
+        ProgressEvent::StepStarted {
+            depth,
+            index,
+            step,
+            tool,
+            ..
+        } => (*depth, format!("▶️  [{}] {} ({})", index + 1, step, tool)),
+        ProgressEvent::StepSkipped {
+            depth, index, step, ..
+        } => (*depth, format!("⏭️  [{}] {} skipped", index + 1, step)),
+        ProgressEvent::StepRetrying {
+            depth,
+            step,
+            attempt,
+            error,
+        } => (*depth, format!("   ↻ {} attempt {} after: {}", step, attempt, error)),
+        ProgressEvent::StepTimedOut {
+            depth,
+            step,
+            duration_ms,
+        } => (*depth, format!("   ⏱️  {} timed out after {}ms", step, duration_ms)),
+        ProgressEvent::StepFinished {
+            depth,
+            duration_ms,
+            error,
+            ..
+        } => match error {
+            None => (*depth, format!("   ✅ {}ms", duration_ms)),
+            Some(error) => (*depth, format!("   ❌ {}ms: {}", duration_ms, error)),
+        },
+        ProgressEvent::ApprovalDecided {
+            depth,
+            step,
+            reason,
+            ..
+        } => match reason {
+            None => (*depth, format!("   🔓 {} approved", step)),
+            Some(reason) => (*depth, format!("   🔒 {} denied: {}", step, reason)),
+        },
+        ProgressEvent::Cancelled { depth, step } => match step {
+            Some(step) => (*depth, format!("⛔ Cancelled during {}", step)),
+            None => (*depth, "⛔ Cancelled".to_string()),
+        },
+        ProgressEvent::SkillStarted { .. } | ProgressEvent::SkillFinished { .. } => return None,
+    };
+    Some(format!("{}{}", "  ".repeat(depth), line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_line() {
+        let started = ProgressEvent::StepStarted {
+            depth: 0,
+            index: 0,
+            total: 2,
+            step: "fetch".to_string(),
+            tool: "http.get".to_string(),
+        };
+        assert_eq!(step_line(&started).unwrap(), "▶️  [1] fetch (http.get)");
+
+        let finished = ProgressEvent::StepFinished {
+            depth: 1,
+            index: 0,
+            total: 1,
+            step: "inner".to_string(),
+            success: false,
+            duration_ms: 5,
+            error: Some("boom".to_string()),
+        };
+        assert_eq!(step_line(&finished).unwrap(), "     ❌ 5ms: boom");
+
+        let denied = ProgressEvent::ApprovalDecided {
+            depth: 0,
+            step: "drop".to_string(),
+            approved: false,
+            reason: Some("not today".to_string()),
+        };
+        assert_eq!(step_line(&denied).unwrap(), "   🔒 drop denied: not today");
+
+        let skill = ProgressEvent::SkillStarted {
+            depth: 0,
+            skill: "report".to_string(),
+            steps: 2,
+        };
+        assert!(step_line(&skill).is_none());
+    }
+}
@@ -46,6 +46,8 @@ fn test_cli_tools_show() {
     assert!(stdout.contains("Tool: read_file"));
     assert!(stdout.contains("path"));
     assert!(stdout.contains("encoding"));
+    assert!(stdout.contains("Usage:"));
+    assert!(stdout.contains("thulp run read_file path=./README.md"));
 }
 
 #[test]
@@ -116,3 +118,160 @@ fn test_cli_convert_examples() {
     assert!(stdout.contains("OpenAPI Conversion Examples"));
     assert!(stdout.contains("GitHub API"));
 }
+
+#[test]
+fn test_cli_init_read_only() {
+    let dir = std::env::temp_dir().join(format!("thulp-read-only-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--package", "thulp", "--", "--read-only", "init"])
+        .arg(&dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Read-only mode"));
+    assert!(!dir.join(".thulp").exists());
+}
+
+#[test]
+fn test_cli_skill_install_and_update() {
+    let dir = std::env::temp_dir().join(format!("thulp-skill-install-{}", std::process::id()));
+    let registry = dir.join("registry");
+    std::fs::create_dir_all(&registry).unwrap();
+    std::fs::write(
+        registry.join("greet.md"),
+        "---\nname: greet\ndescription: Say hello\n---\nSay hello.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        registry.join("index.json"),
+        r#"{"packages": [{"name": "greet", "version": "1.0.0", "files": [{
+            "path": "SKILL.md",
+            "url": "greet.md",
+            "sha256": "7ce425572a5b0e43cf27c5b90ebcc33a12c6022776393d7bd42e4422d5098783"
+        }]}]}"#,
+    )
+    .unwrap();
+    let index = format!("file://{}", registry.join("index.json").display());
+
+    let output = Command::new("cargo")
+        .args(["run", "--package", "thulp", "--", "-w"])
+        .arg(&dir)
+        .args(["skill", "install", "greet", "--index", &index])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Installed greet 1.0.0"));
+    assert!(dir.join(".thulp/skills/greet/SKILL.md").exists());
+
+    let output = Command::new("cargo")
+        .args(["run", "--package", "thulp", "--", "-w"])
+        .arg(&dir)
+        .args(["skill", "update", "--check"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("up to date"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_skill_run_dry_run_plans_steps() {
+    let dir = std::env::temp_dir().join(format!("thulp-skill-plan-{}", std::process::id()));
+    let skill_dir = dir.join("skills/lookup");
+    std::fs::create_dir_all(&skill_dir).unwrap();
+    std::fs::write(
+        skill_dir.join("skill.yaml"),
+        r#"name: lookup
+description: Search and fetch the top hit
+inputs: [query]
+steps:
+  - name: search
+    tool: web_search
+    arguments: {q: "{{query}}"}
+  - name: fetch
+    tool: http_get
+    arguments: {url: "{{search.results[0].url}}"}
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--package", "thulp", "--", "-o", "json", "-w"])
+        .arg(&dir)
+        .args(["skill", "run", "lookup", "--dry-run", "query=rust"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["valid"], true);
+    assert_eq!(json["plan"]["steps"][0]["arguments"]["q"], "rust");
+    assert_eq!(
+        json["plan"]["steps"][1]["deferred"][0],
+        "search.results[0].url"
+    );
+
+    let output = Command::new("cargo")
+        .args(["run", "--package", "thulp", "--", "-w"])
+        .arg(&dir)
+        .args(["skill", "run", "lookup", "--dry-run"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1. search -> web_search"));
+    assert!(stdout.contains("missing input 'query'"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_read_only_refuses_destructive_tools() {
+    let dir = std::env::temp_dir().join(format!("thulp-read-only-exec-{}", std::process::id()));
+    let skill_dir = dir.join("skills/mark");
+    std::fs::create_dir_all(&skill_dir).unwrap();
+    std::fs::create_dir_all(dir.join(".thulp")).unwrap();
+    std::fs::write(dir.join(".thulp/config.yaml"), "exec:\n  commands: [touch]\n").unwrap();
+    std::fs::write(
+        skill_dir.join("skill.yaml"),
+        r#"name: mark
+description: Leave a marker file
+steps:
+  - name: touch
+    tool: local.exec
+    arguments: {command: touch, args: [marker]}
+"#,
+    )
+    .unwrap();
+    let touch = r#"{"command": "touch", "args": ["marker"]}"#;
+    let thulp = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--package", "thulp", "--", "-w"])
+            .arg(&dir)
+            .args(args)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = thulp(&["--read-only", "skill", "run", "mark"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ReadOnly(\"local.exec\")"), "{}", stderr);
+    assert!(!dir.join("marker").exists());
+
+    let output = thulp(&["--read-only", "run", "local.exec", "--json", touch]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("read-only mode"), "{}", stderr);
+    assert!(!dir.join("marker").exists());
+
+    let output = thulp(&["run", "local.exec", "--json", touch]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.join("marker").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
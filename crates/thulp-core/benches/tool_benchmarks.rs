@@ -61,7 +61,7 @@ fn benchmark_parameter_matching(c: &mut Criterion) {
     let param_types = vec![
         ("string", ParameterType::String, json!("test")),
         ("integer", ParameterType::Integer, json!(42)),
-        ("number", ParameterType::Number, json!(3.14)),
+        ("number", ParameterType::Number, json!(2.5)),
         ("boolean", ParameterType::Boolean, json!(true)),
         ("array", ParameterType::Array, json!([1, 2, 3])),
         ("object", ParameterType::Object, json!({"key": "value"})),
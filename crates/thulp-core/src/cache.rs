@@ -0,0 +1,465 @@
+//! Memoizing tool results.
+//!
+//! [`CachedTransport`] wraps another [`Transport`] and remembers successful
+//! results by call fingerprint: the tool name plus its arguments, with object
+//! keys sorted so `{"a": 1, "b": 2}` and `{"b": 2, "a": 1}` hit the same
+//! entry. Repeated searches and lookups within a skill are then answered
+//! without a round trip to the server.
+//!
+//! Only wrap tools whose calls are idempotent; a cached write would silently
+//! be skipped. Restrict caching to such tools with [`CacheConfig::with_tool`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use thulp_core::{CacheConfig, CachedTransport};
+//! # use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+//! # struct Echo;
+//! # #[async_trait::async_trait]
+//! # impl Transport for Echo {
+//! #     async fn connect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     async fn disconnect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     fn is_connected(&self) -> bool { true }
+//! #     async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> { Ok(vec![]) }
+//! #     async fn call(&self, _: &ToolCall) -> thulp_core::Result<ToolResult> {
+//! #         Ok(ToolResult::success(serde_json::json!("ok")))
+//! #     }
+//! # }
+//!
+//! let transport = CachedTransport::new(
+//!     Echo,
+//!     CacheConfig::new()
+//!         .with_ttl(Duration::from_secs(60))
+//!         .with_max_entries(500)
+//!         .with_tool("search")
+//!         .with_tool("lookup"),
+//! );
+//! ```
+
+use crate::{Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long results are kept and how many of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// How long a result stays valid after it was stored
+    pub ttl: Duration,
+
+    /// Most results kept; the least recently used is evicted beyond this
+    pub max_entries: usize,
+
+    /// Only cache these tools (empty means every tool)
+    pub tools: Vec<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CacheConfig {
+    /// Cache every tool's results for 5 minutes, keeping at most 1000.
+    pub fn new() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            max_entries: 1000,
+            tools: Vec::new(),
+        }
+    }
+
+    /// Set how long results stay valid.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the most results kept, at least 1.
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max.max(1);
+        self
+    }
+
+    /// Only cache calls to the given tool.
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tools.push(tool.into());
+        self
+    }
+
+    fn applies_to(&self, tool: &str) -> bool {
+        self.tools.is_empty() || self.tools.iter().any(|t| t == tool)
+    }
+}
+
+/// Counts of cache lookups and evictions by a [`CachedTransport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Calls answered from the cache
+    pub hits: u64,
+    /// Cacheable calls forwarded to the wrapped transport
+    pub misses: u64,
+    /// Results dropped to stay under the entry limit
+    pub evictions: u64,
+    /// Results currently stored
+    pub entries: usize,
+}
+
+/// A stored result
+struct Entry {
+    tool: String,
+    result: ToolResult,
+    stored: Instant,
+    last_used: u64,
+}
+
+/// Entries by fingerprint, with a counter ordering their use
+#[derive(Default)]
+struct Store {
+    entries: HashMap<String, Entry>,
+    clock: u64,
+}
+
+impl Store {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+/// A [`Transport`] wrapper that memoizes successful tool results.
+///
+/// Failed results and errors are never cached, and streaming calls always go
+/// to the wrapped transport. Disconnecting clears the cache.
+pub struct CachedTransport<T> {
+    inner: T,
+    config: CacheConfig,
+    store: Mutex<Store>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<T> std::fmt::Debug for CachedTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedTransport")
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl<T> CachedTransport<T> {
+    /// Wrap a transport.
+    pub fn new(inner: T, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            store: Mutex::new(Store::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the transport.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Get the cache configuration.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Get counts of hits, misses and evictions so far.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.lock().entries.len(),
+        }
+    }
+
+    /// Drop every cached result of a tool.
+    pub fn invalidate(&self, tool: &str) {
+        self.lock().entries.retain(|_, entry| entry.tool != tool);
+    }
+
+    /// Drop every cached result.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Store> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Look up a fresh result, dropping it if it has expired
+    fn get(&self, key: &str) -> Option<ToolResult> {
+        let mut store = self.lock();
+        let now = store.tick();
+        let ttl = self.config.ttl;
+        match store.entries.get_mut(key) {
+            Some(entry) if entry.stored.elapsed() < ttl => {
+                entry.last_used = now;
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                store.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a result, making room by dropping expired entries and then the
+    /// least recently used
+    fn put(&self, key: String, tool: &str, result: ToolResult) {
+        let mut store = self.lock();
+        let now = store.tick();
+        if !store.entries.contains_key(&key) && store.entries.len() >= self.config.max_entries {
+            let ttl = self.config.ttl;
+            store
+                .entries
+                .retain(|_, entry| entry.stored.elapsed() < ttl);
+            while store.entries.len() >= self.config.max_entries {
+                let oldest = store
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                let Some(oldest) = oldest else { break };
+                store.entries.remove(&oldest);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        store.entries.insert(
+            key,
+            Entry {
+                tool: tool.to_string(),
+                result,
+                stored: Instant::now(),
+                last_used: now,
+            },
+        );
+    }
+}
+
+/// Identify a call by its tool and arguments, independent of key order
+pub(crate) fn fingerprint(call: &ToolCall) -> String {
+    let mut key = call.tool.clone();
+    key.push('\0');
+    write_canonical(&call.arguments, &mut key);
+    key
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for CachedTransport<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.clear();
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.inner.list_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        if !self.config.applies_to(&call.tool) {
+            return self.inner.call(call).await;
+        }
+
+        let key = call.fingerprint();
+        if let Some(result) = self.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(result);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = self.inner.call(call).await?;
+        if result.is_success() {
+            self.put(key, &call.tool, result.clone());
+        }
+        Ok(result)
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        self.inner.call_streaming(call).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Echoes the arguments back and counts calls; `fail` returns a failure
+    #[derive(Default)]
+    struct Counting {
+        calls: AtomicU64,
+    }
+
+    #[async_trait]
+    impl Transport for Counting {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if call.tool == "fail" {
+                return Ok(ToolResult::failure("nope"));
+            }
+            Ok(ToolResult::success(call.arguments.clone()))
+        }
+    }
+
+    fn calls(transport: &CachedTransport<Counting>) -> u64 {
+        transport.inner().calls.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_calls_hit_cache() {
+        let transport = CachedTransport::new(Counting::default(), CacheConfig::new());
+        let first = ToolCall::builder("search")
+            .arg("q", json!("rust"))
+            .arg("limit", json!(5))
+            .build();
+        let reordered = ToolCall::builder("search")
+            .arg("limit", json!(5))
+            .arg("q", json!("rust"))
+            .build();
+
+        let a = transport.call(&first).await.unwrap();
+        let b = transport.call(&reordered).await.unwrap();
+        assert_eq!(a.data, b.data);
+        assert_eq!(calls(&transport), 1);
+
+        let other = ToolCall::builder("search").arg("q", json!("go")).build();
+        transport.call(&other).await.unwrap();
+        assert_eq!(calls(&transport), 2);
+
+        let stats = transport.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_failures_and_unlisted_tools_are_not_cached() {
+        let transport =
+            CachedTransport::new(Counting::default(), CacheConfig::new().with_tool("fail"));
+
+        transport.call(&ToolCall::new("fail")).await.unwrap();
+        transport.call(&ToolCall::new("fail")).await.unwrap();
+        transport.call(&ToolCall::new("write")).await.unwrap();
+        transport.call(&ToolCall::new("write")).await.unwrap();
+
+        assert_eq!(calls(&transport), 4);
+        assert_eq!(transport.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expiry_and_invalidate() {
+        let transport = CachedTransport::new(
+            Counting::default(),
+            CacheConfig::new().with_ttl(Duration::from_millis(30)),
+        );
+        let call = ToolCall::new("lookup");
+
+        transport.call(&call).await.unwrap();
+        transport.call(&call).await.unwrap();
+        assert_eq!(calls(&transport), 1);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        transport.call(&call).await.unwrap();
+        assert_eq!(calls(&transport), 2);
+
+        transport.invalidate("lookup");
+        transport.call(&call).await.unwrap();
+        assert_eq!(calls(&transport), 3);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let transport =
+            CachedTransport::new(Counting::default(), CacheConfig::new().with_max_entries(2));
+        let a = ToolCall::new("a");
+        let b = ToolCall::new("b");
+        let c = ToolCall::new("c");
+
+        transport.call(&a).await.unwrap();
+        transport.call(&b).await.unwrap();
+        // Touch `a` so `b` is the least recently used
+        transport.call(&a).await.unwrap();
+        transport.call(&c).await.unwrap();
+
+        let stats = transport.stats();
+        assert_eq!((stats.entries, stats.evictions), (2, 1));
+
+        transport.call(&a).await.unwrap();
+        assert_eq!(calls(&transport), 3);
+        transport.call(&b).await.unwrap();
+        assert_eq!(calls(&transport), 4);
+    }
+}
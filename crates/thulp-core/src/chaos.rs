@@ -0,0 +1,419 @@
+//! Failure injection for resilience testing.
+//!
+//! [`ChaosTransport`] wraps another [`Transport`] and disrupts a configurable
+//! fraction of tool calls with extra latency, errors, or hangs. Put it between
+//! an executor and a real or mock transport to check that retry, timeout and
+//! `continue_on_error` settings behave the way you expect when tools misbehave.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use thulp_core::{ChaosConfig, ChaosTransport};
+//! # use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+//! # struct Echo;
+//! # #[async_trait::async_trait]
+//! # impl Transport for Echo {
+//! #     async fn connect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     async fn disconnect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     fn is_connected(&self) -> bool { true }
+//! #     async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> { Ok(vec![]) }
+//! #     async fn call(&self, _: &ToolCall) -> thulp_core::Result<ToolResult> {
+//! #         Ok(ToolResult::success(serde_json::json!("ok")))
+//! #     }
+//! # }
+//!
+//! let transport = ChaosTransport::new(
+//!     Echo,
+//!     ChaosConfig::new()
+//!         .with_error_rate(0.2)
+//!         .with_timeout_rate(0.05)
+//!         .with_latency(0.5, Duration::from_millis(10), Duration::from_millis(200))
+//!         .with_seed(42),
+//! );
+//! ```
+
+use crate::{Error, Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default error message for injected failures.
+///
+/// Mentions a 503 so retry policies that retry server errors treat it as
+/// transient.
+pub const DEFAULT_CHAOS_ERROR: &str = "Chaos: injected failure (503 Service Unavailable)";
+
+/// Which faults to inject and how often.
+///
+/// Rates are probabilities between `0.0` and `1.0`; out-of-range values are
+/// clamped. Error and timeout rates are exclusive (a call gets at most one of
+/// them), latency is rolled independently and applies before either.
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// Fraction of calls that fail with [`error_message`](Self::error_message)
+    pub error_rate: f64,
+
+    /// Fraction of calls that hang for [`hang`](Self::hang) and then fail
+    pub timeout_rate: f64,
+
+    /// Fraction of calls delayed by a random duration in `latency`
+    pub latency_rate: f64,
+
+    /// Range of injected latency (inclusive bounds)
+    pub latency: (Duration, Duration),
+
+    /// How long an injected timeout hangs before failing
+    pub hang: Duration,
+
+    /// Message of injected errors
+    pub error_message: String,
+
+    /// Seed for reproducible fault sequences
+    pub seed: Option<u64>,
+
+    /// Only disrupt these tools (empty means every tool)
+    pub tools: Vec<String>,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChaosConfig {
+    /// Create a configuration that injects nothing.
+    pub fn new() -> Self {
+        Self {
+            error_rate: 0.0,
+            timeout_rate: 0.0,
+            latency_rate: 0.0,
+            latency: (Duration::ZERO, Duration::ZERO),
+            hang: Duration::from_secs(300),
+            error_message: DEFAULT_CHAOS_ERROR.to_string(),
+            seed: None,
+            tools: Vec::new(),
+        }
+    }
+
+    /// Fail a fraction of calls.
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Make a fraction of calls hang, simulating a tool that never answers.
+    pub fn with_timeout_rate(mut self, rate: f64) -> Self {
+        self.timeout_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay a fraction of calls by a random duration between `min` and `max`.
+    pub fn with_latency(mut self, rate: f64, min: Duration, max: Duration) -> Self {
+        self.latency_rate = rate.clamp(0.0, 1.0);
+        self.latency = (min.min(max), max.max(min));
+        self
+    }
+
+    /// Set how long an injected timeout hangs (default: 5 minutes).
+    pub fn with_hang(mut self, hang: Duration) -> Self {
+        self.hang = hang;
+        self
+    }
+
+    /// Set the message of injected errors.
+    pub fn with_error_message(mut self, message: impl Into<String>) -> Self {
+        self.error_message = message.into();
+        self
+    }
+
+    /// Seed the random generator so runs inject the same faults.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Only disrupt calls to the given tool.
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tools.push(tool.into());
+        self
+    }
+
+    fn applies_to(&self, tool: &str) -> bool {
+        self.tools.is_empty() || self.tools.iter().any(|t| t == tool)
+    }
+}
+
+/// Counts of calls seen and faults injected by a [`ChaosTransport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// Calls that went through the wrapper
+    pub calls: u64,
+    /// Calls failed with an injected error
+    pub errors: u64,
+    /// Calls made to hang
+    pub timeouts: u64,
+    /// Calls delayed by injected latency
+    pub delayed: u64,
+}
+
+/// Fault decided for a single call
+enum Fault {
+    None,
+    Error,
+    Timeout,
+}
+
+/// A [`Transport`] wrapper that injects latency, errors and timeouts.
+///
+/// Only tool calls are disrupted; connecting and listing tools pass through.
+pub struct ChaosTransport<T> {
+    inner: T,
+    config: ChaosConfig,
+    rng: Mutex<fastrand::Rng>,
+    calls: AtomicU64,
+    errors: AtomicU64,
+    timeouts: AtomicU64,
+    delayed: AtomicU64,
+}
+
+impl<T> std::fmt::Debug for ChaosTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosTransport")
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl<T> ChaosTransport<T> {
+    /// Wrap a transport.
+    pub fn new(inner: T, config: ChaosConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        Self {
+            inner,
+            config,
+            rng: Mutex::new(rng),
+            calls: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            delayed: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the transport.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Get the chaos configuration.
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Get counts of calls and injected faults so far.
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            delayed: self.delayed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Roll the dice for a call and apply any latency or fault.
+    async fn disrupt(&self, call: &ToolCall) -> Result<()> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        if !self.config.applies_to(&call.tool) {
+            return Ok(());
+        }
+
+        let (delay, fault) = {
+            let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+            let delay = (rng.f64() < self.config.latency_rate).then(|| {
+                let (min, max) = self.config.latency;
+                let span = (max - min).as_millis() as u64;
+                min + Duration::from_millis(rng.u64(0..=span))
+            });
+            let roll = rng.f64();
+            let fault = if roll < self.config.error_rate {
+                Fault::Error
+            } else if roll < self.config.error_rate + self.config.timeout_rate {
+                Fault::Timeout
+            } else {
+                Fault::None
+            };
+            (delay, fault)
+        };
+
+        if let Some(delay) = delay {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+        }
+
+        match fault {
+            Fault::None => Ok(()),
+            Fault::Error => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                Err(Error::ExecutionFailed(self.config.error_message.clone()))
+            }
+            Fault::Timeout => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(self.config.hang).await;
+                Err(Error::ExecutionFailed(format!(
+                    "Chaos: injected timeout, call to '{}' timed out after {:?}",
+                    call.tool, self.config.hang
+                )))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for ChaosTransport<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.inner.list_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        self.disrupt(call).await?;
+        self.inner.call(call).await
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        self.disrupt(call).await?;
+        self.inner.call_streaming(call).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Echo;
+
+    #[async_trait]
+    impl Transport for Echo {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+            Ok(ToolResult::success(json!(call.tool)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_faults_passes_through() {
+        let transport = ChaosTransport::new(Echo, ChaosConfig::new());
+        let result = transport.call(&ToolCall::new("echo")).await.unwrap();
+
+        assert_eq!(result.data, Some(json!("echo")));
+        assert_eq!(
+            transport.stats(),
+            ChaosStats {
+                calls: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_rate_is_seeded_and_proportional() {
+        let config = ChaosConfig::new().with_error_rate(0.3).with_seed(7);
+        let run = |config: ChaosConfig| async move {
+            let transport = ChaosTransport::new(Echo, config);
+            let mut outcomes = Vec::new();
+            for _ in 0..200 {
+                outcomes.push(transport.call(&ToolCall::new("echo")).await.is_ok());
+            }
+            (outcomes, transport.stats())
+        };
+
+        let (first, stats) = run(config.clone()).await;
+        let (second, _) = run(config).await;
+        assert_eq!(first, second);
+
+        assert_eq!(stats.calls, 200);
+        assert!(
+            (30..=90).contains(&stats.errors),
+            "errors: {}",
+            stats.errors
+        );
+        assert_eq!(first.iter().filter(|ok| !**ok).count() as u64, stats.errors);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_hangs_then_fails() {
+        let transport = ChaosTransport::new(
+            Echo,
+            ChaosConfig::new()
+                .with_timeout_rate(1.0)
+                .with_hang(Duration::from_millis(200)),
+        );
+
+        let call = ToolCall::new("slow");
+        let outcome = tokio::time::timeout(Duration::from_millis(20), transport.call(&call)).await;
+        assert!(outcome.is_err(), "call should still be hanging");
+
+        let err = transport.call(&call).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert_eq!(transport.stats().timeouts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_latency_and_tool_filter() {
+        let transport = ChaosTransport::new(
+            Echo,
+            ChaosConfig::new()
+                .with_error_rate(1.0)
+                .with_latency(1.0, Duration::from_millis(5), Duration::from_millis(10))
+                .with_tool("flaky"),
+        );
+
+        assert!(transport.call(&ToolCall::new("stable")).await.is_ok());
+
+        let start = std::time::Instant::now();
+        let err = transport.call(&ToolCall::new("flaky")).await.unwrap_err();
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        assert!(err.to_string().contains(DEFAULT_CHAOS_ERROR));
+
+        let stats = transport.stats();
+        assert_eq!((stats.calls, stats.errors, stats.delayed), (2, 1, 1));
+    }
+}
@@ -38,6 +38,15 @@ pub enum Error {
     /// Invalid configuration.
     #[error("invalid configuration: {0}")]
     InvalidConfig(String),
+
+    /// A parameter value breaks one of its constraints.
+    #[error("parameter '{name}' {reason}")]
+    ConstraintViolation {
+        /// Parameter name.
+        name: String,
+        /// Which limit was broken, e.g. `must be at most 10, got 12`.
+        reason: String,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,665 @@
+//! Local commands as a tool.
+//!
+//! [`ExecTransport`] serves one tool, [`EXEC_TOOL`], that runs a local
+//! program and returns its exit code, stdout and stderr, so skills can use
+//! command-line tools without an MCP server wrapping them. What may run is
+//! decided by an [`ExecPolicy`]:
+//!
+//! - only the programs in [`commands`](ExecPolicy::commands) run, started
+//!   directly rather than through a shell, so arguments are never
+//!   interpreted
+//! - the environment is cleared except for `PATH`, the policy's
+//!   [`env`](ExecPolicy::env) and the variables a call may set
+//!   ([`allow_env`](ExecPolicy::allow_env))
+//! - commands are killed after [`timeout`](ExecPolicy::timeout), which a
+//!   call can shorten but not extend
+//! - [`call`](Transport::call) keeps at most
+//!   [`max_output_bytes`](ExecPolicy::max_output_bytes) of each stream
+//!
+//! The tool is marked destructive, so read-only runs refuse it.
+//! [`call_streaming`](Transport::call_streaming) yields
+//! `{"stdout": "..."}` and `{"stderr": "..."}` chunks line by line as the
+//! command writes them, then `{"exit_code": 0}`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use thulp_core::{ExecPolicy, ExecTransport, ToolCall, Transport};
+//! # async fn example() -> thulp_core::Result<()> {
+//! let transport = ExecTransport::new(ExecPolicy::new().allow_command("git"));
+//! let result = transport
+//!     .call(&ToolCall::with_args(
+//!         "exec",
+//!         serde_json::json!({"command": "git", "args": ["status", "--short"]}),
+//!     ))
+//!     .await?;
+//! println!("{}", result.data.unwrap()["stdout"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A policy deserializes from configuration, with the timeout in seconds:
+//!
+//! ```yaml
+//! commands: [git, jq, /usr/local/bin/lint]
+//! allow_env: [GIT_DIR]
+//! env:
+//!   LANG: C.UTF-8
+//! timeout: 30
+//! max_output_bytes: 65536
+//! ```
+
+use crate::{
+    Error, Parameter, ParameterType, Result, ToolCall, ToolDefinition, ToolResult,
+    ToolResultStream, Transport,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Name of the tool [`ExecTransport`] serves.
+pub const EXEC_TOOL: &str = "exec";
+
+/// How long output is still read after the command exits
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What [`ExecTransport`] may run, and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecPolicy {
+    /// Programs that may run, matched exactly against the `command`
+    /// argument: names looked up in `PATH`, or paths
+    pub commands: Vec<String>,
+
+    /// Environment variables a call may set
+    pub allow_env: Vec<String>,
+
+    /// Environment variables set for every command
+    pub env: BTreeMap<String, String>,
+
+    /// Pass this process's whole environment on, instead of only `PATH`
+    pub inherit_env: bool,
+
+    /// Directory commands run in (default: the current directory)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+
+    /// Longest a command may run before it is killed
+    #[serde(with = "seconds")]
+    pub timeout: Duration,
+
+    /// Bytes of stdout and of stderr kept in a result
+    pub max_output_bytes: usize,
+}
+
+impl Default for ExecPolicy {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            allow_env: Vec::new(),
+            env: BTreeMap::new(),
+            inherit_env: false,
+            working_dir: None,
+            timeout: Duration::from_secs(60),
+            max_output_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl ExecPolicy {
+    /// Create a policy that allows no commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow a program to run.
+    pub fn allow_command(mut self, command: impl Into<String>) -> Self {
+        self.commands.push(command.into());
+        self
+    }
+
+    /// Let calls set an environment variable.
+    pub fn allow_env(mut self, name: impl Into<String>) -> Self {
+        self.allow_env.push(name.into());
+        self
+    }
+
+    /// Set an environment variable for every command.
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Pass this process's whole environment on to commands.
+    pub fn with_inherit_env(mut self, inherit_env: bool) -> Self {
+        self.inherit_env = inherit_env;
+        self
+    }
+
+    /// Run commands in `dir`.
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the longest a command may run.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the bytes of stdout and of stderr kept in a result.
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Whether `command` may run.
+    pub fn allows(&self, command: &str) -> bool {
+        self.commands.iter().any(|allowed| allowed == command)
+    }
+}
+
+/// Transport serving the [`EXEC_TOOL`] tool under an [`ExecPolicy`].
+#[derive(Debug, Clone)]
+pub struct ExecTransport {
+    policy: ExecPolicy,
+    connected: bool,
+}
+
+impl ExecTransport {
+    /// Create a transport enforcing `policy`.
+    pub fn new(policy: ExecPolicy) -> Self {
+        Self {
+            policy,
+            connected: false,
+        }
+    }
+
+    /// Get the policy.
+    pub fn policy(&self) -> &ExecPolicy {
+        &self.policy
+    }
+
+    /// Definition of the [`EXEC_TOOL`] tool.
+    pub fn definition(&self) -> ToolDefinition {
+        ToolDefinition::builder(EXEC_TOOL)
+            .description(format!(
+                "Run a local command without a shell. Allowed commands: {}",
+                if self.policy.commands.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.policy.commands.join(", ")
+                }
+            ))
+            .parameter(
+                Parameter::builder("command")
+                    .param_type(ParameterType::String)
+                    .required(true)
+                    .description("Program to run")
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("args")
+                    .param_type(ParameterType::Array)
+                    .description("Arguments, passed as is")
+                    .items(Parameter::new("arg"))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("stdin")
+                    .param_type(ParameterType::String)
+                    .description("Text written to the command's standard input")
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("env")
+                    .param_type(ParameterType::Object)
+                    .description("Environment variables to set, among those the policy allows")
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("timeout")
+                    .param_type(ParameterType::Number)
+                    .description("Seconds before the command is killed, at most the policy's")
+                    .build(),
+            )
+            .destructive(true)
+            .build()
+    }
+
+    /// Check a call against the policy and start its command, returning
+    /// the chunks it produces
+    fn start(&self, call: &ToolCall) -> Result<mpsc::Receiver<Result<ToolResult>>> {
+        if call.tool != EXEC_TOOL {
+            return Err(Error::ToolNotFound(call.tool.clone()));
+        }
+        let empty = Map::new();
+        let args = call.arguments.as_object().unwrap_or(&empty);
+        let program = args
+            .get("command")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::MissingParameter("command".to_string()))?;
+        if !self.policy.allows(program) {
+            return Err(Error::ExecutionFailed(format!(
+                "command '{}' is not allowed by the exec policy",
+                program
+            )));
+        }
+
+        let mut command = Command::new(program);
+        match args.get("args") {
+            None | Some(Value::Null) => {}
+            Some(Value::Array(items)) => {
+                for item in items {
+                    command.arg(arg_string(item)?);
+                }
+            }
+            Some(other) => return Err(invalid_type("args", "array", other)),
+        }
+
+        if !self.policy.inherit_env {
+            command.env_clear();
+            if let Some(path) = std::env::var_os("PATH") {
+                command.env("PATH", path);
+            }
+        }
+        command.envs(&self.policy.env);
+        match args.get("env") {
+            None | Some(Value::Null) => {}
+            Some(Value::Object(env)) => {
+                for (name, value) in env {
+                    if !self.policy.allow_env.iter().any(|allowed| allowed == name) {
+                        return Err(Error::ExecutionFailed(format!(
+                            "environment variable '{}' is not allowed by the exec policy",
+                            name
+                        )));
+                    }
+                    command.env(name, arg_string(value)?);
+                }
+            }
+            Some(other) => return Err(invalid_type("env", "object", other)),
+        }
+        if let Some(dir) = &self.policy.working_dir {
+            command.current_dir(dir);
+        }
+
+        let timeout = match args.get("timeout").and_then(Value::as_f64) {
+            Some(secs) if secs > 0.0 => self
+                .policy
+                .timeout
+                .min(Duration::try_from_secs_f64(secs).unwrap_or(self.policy.timeout)),
+            _ => self.policy.timeout,
+        };
+        let stdin = match args.get("stdin") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(other) => return Err(invalid_type("stdin", "string", other)),
+        };
+
+        let mut child = command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::ExecutionFailed(format!("failed to run '{}': {}", program, e)))?;
+
+        let (tx, rx) = mpsc::channel(64);
+        let start = Instant::now();
+        if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            tokio::spawn(async move {
+                // A command that exits without reading its input closes the pipe
+                let _ = pipe.write_all(text.as_bytes()).await;
+            });
+        }
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|pipe| forward_lines(pipe, "stdout", tx.clone())),
+            child
+                .stderr
+                .take()
+                .map(|pipe| forward_lines(pipe, "stderr", tx.clone())),
+        ];
+        let program = program.to_string();
+        tokio::spawn(async move {
+            let status = match tokio::time::timeout(timeout, child.wait()).await {
+                Ok(status) => Some(status),
+                Err(_) => {
+                    let _ = child.kill().await;
+                    None
+                }
+            };
+            // Let the readers drain what the command wrote before it exited,
+            // but not wait on processes it left behind holding the pipes
+            for mut reader in readers.into_iter().flatten() {
+                if tokio::time::timeout(DRAIN_TIMEOUT, &mut reader)
+                    .await
+                    .is_err()
+                {
+                    reader.abort();
+                }
+            }
+            let duration_ms = start.elapsed().as_millis() as u64;
+            let result = match status {
+                Some(Ok(status)) if status.success() => {
+                    ToolResult::success(json!({"exit_code": 0}))
+                }
+                Some(Ok(status)) => ToolResult {
+                    success: false,
+                    data: Some(json!({"exit_code": status.code()})),
+                    error: Some(format!("'{}' exited with {}", program, status)),
+                    duration_ms: None,
+                },
+                Some(Err(e)) => {
+                    ToolResult::failure(format!("failed to wait for '{}': {}", program, e))
+                }
+                None => ToolResult::failure(format!(
+                    "'{}' timed out after {:.1}s and was killed",
+                    program,
+                    timeout.as_secs_f64()
+                )),
+            };
+            let _ = tx.send(Ok(result.with_duration(duration_ms))).await;
+        });
+        Ok(rx)
+    }
+}
+
+#[async_trait]
+impl Transport for ExecTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        Ok(vec![self.definition()])
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        let mut chunks = self.start(call)?;
+        let max = self.policy.max_output_bytes;
+        let mut output = [String::new(), String::new()];
+        let mut truncated = false;
+        while let Some(chunk) = chunks.recv().await {
+            let mut chunk = chunk?;
+            let data = chunk.data.take().unwrap_or(Value::Null);
+            for (index, key) in ["stdout", "stderr"].into_iter().enumerate() {
+                if let Some(text) = data.get(key).and_then(Value::as_str) {
+                    truncated |= push_capped(&mut output[index], text, max);
+                }
+            }
+            if let Some(exit_code) = data.get("exit_code") {
+                let [stdout, stderr] = output;
+                chunk.data = Some(json!({
+                    "exit_code": exit_code,
+                    "stdout": stdout,
+                    "stderr": stderr,
+                    "truncated": truncated,
+                }));
+                return Ok(chunk);
+            }
+            if !chunk.success {
+                return Ok(chunk);
+            }
+        }
+        Err(Error::ExecutionFailed(
+            "command ended without a status".to_string(),
+        ))
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        let chunks = self.start(call)?;
+        Ok(futures::stream::unfold(chunks, |mut chunks| async move {
+            chunks.recv().await.map(|chunk| (chunk, chunks))
+        })
+        .boxed())
+    }
+}
+
+/// Send each line read from `pipe` as a `{stream: line}` chunk
+fn forward_lines(
+    pipe: impl AsyncRead + Unpin + Send + 'static,
+    stream: &'static str,
+    tx: mpsc::Sender<Result<ToolResult>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&line).into_owned();
+                    // Keep reading after the receiver is gone, so the command
+                    // never blocks on a full pipe
+                    let _ = tx
+                        .send(Ok(ToolResult::success(json!({ stream: text }))))
+                        .await;
+                }
+            }
+        }
+    })
+}
+
+/// Append `text` up to `max` bytes in total, returning whether any was cut
+fn push_capped(buffer: &mut String, text: &str, max: usize) -> bool {
+    let room = max.saturating_sub(buffer.len());
+    if text.len() <= room {
+        buffer.push_str(text);
+        return false;
+    }
+    let mut end = room;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    buffer.push_str(&text[..end]);
+    true
+}
+
+/// Render a scalar argument for the command line or environment
+fn arg_string(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        other => Err(invalid_type("args", "string", other)),
+    }
+}
+
+fn invalid_type(name: &str, expected: &str, value: &Value) -> Error {
+    let actual = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    Error::InvalidParameterType {
+        name: name.to_string(),
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+/// Durations as (fractional) seconds
+mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::collect_stream;
+
+    fn exec(arguments: Value) -> ToolCall {
+        ToolCall::with_args(EXEC_TOOL, arguments)
+    }
+
+    #[tokio::test]
+    async fn test_exec_captures_output() {
+        let transport = ExecTransport::new(ExecPolicy::new().allow_command("sh").allow_env("NAME"));
+        let result = transport
+            .call(&exec(json!({
+                "command": "sh",
+                "args": ["-c", "read line; echo \"$line $NAME\"; echo oops >&2"],
+                "stdin": "hello\n",
+                "env": {"NAME": "world"}
+            })))
+            .await
+            .unwrap();
+
+        assert!(result.is_success());
+        let data = result.data.unwrap();
+        assert_eq!(data["exit_code"], 0);
+        assert_eq!(data["stdout"], "hello world\n");
+        assert_eq!(data["stderr"], "oops\n");
+        assert_eq!(data["truncated"], false);
+        assert!(result.duration_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_exec_policy_is_enforced() {
+        let transport = ExecTransport::new(ExecPolicy::new().allow_command("sh"));
+        assert!(transport.definition().destructive);
+
+        let denied = exec(json!({"command": "rm", "args": ["-rf", "x"]}));
+        assert!(matches!(
+            transport.call(&denied).await,
+            Err(Error::ExecutionFailed(_))
+        ));
+        let env = exec(json!({"command": "sh", "env": {"LD_PRELOAD": "x"}}));
+        assert!(matches!(
+            transport.call(&env).await,
+            Err(Error::ExecutionFailed(_))
+        ));
+        assert!(matches!(
+            transport.call(&exec(json!({}))).await,
+            Err(Error::MissingParameter(_))
+        ));
+
+        // Only PATH is passed on
+        std::env::set_var("THULP_EXEC_TEST", "leaked");
+        let result = transport
+            .call(&exec(
+                json!({"command": "sh", "args": ["-c", "echo \"[$THULP_EXEC_TEST]\""]}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(result.data.unwrap()["stdout"], "[]\n");
+    }
+
+    #[tokio::test]
+    async fn test_exec_failures_and_limits() {
+        let policy = ExecPolicy::new()
+            .allow_command("sh")
+            .with_timeout(Duration::from_secs(5))
+            .with_max_output_bytes(4);
+        let transport = ExecTransport::new(policy);
+
+        let result = transport
+            .call(&exec(
+                json!({"command": "sh", "args": ["-c", "echo partial; exit 3"]}),
+            ))
+            .await
+            .unwrap();
+        assert!(!result.is_success());
+        let data = result.data.unwrap();
+        assert_eq!(data["exit_code"], 3);
+        assert_eq!(data["stdout"], "part");
+        assert_eq!(data["truncated"], true);
+
+        let started = Instant::now();
+        let result = transport
+            .call(&exec(
+                json!({"command": "sh", "args": ["-c", "sleep 10"], "timeout": 0.2}),
+            ))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_exec_streams_lines() {
+        let transport = ExecTransport::new(ExecPolicy::new().allow_command("sh"));
+        let stream = transport
+            .call_streaming(&exec(
+                json!({"command": "sh", "args": ["-c", "echo a; echo b"]}),
+            ))
+            .await
+            .unwrap();
+        let chunks: Vec<_> = stream
+            .map(|chunk| chunk.unwrap().data.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            vec![
+                json!({"stdout": "a\n"}),
+                json!({"stdout": "b\n"}),
+                json!({"exit_code": 0})
+            ]
+        );
+
+        let stream = transport
+            .call_streaming(&exec(json!({"command": "sh", "args": ["-c", "exit 1"]})))
+            .await
+            .unwrap();
+        assert!(!collect_stream(stream).await.unwrap().is_success());
+    }
+
+    #[test]
+    fn test_exec_policy_from_config() {
+        let policy: ExecPolicy = serde_json::from_value(json!({
+            "commands": ["git", "jq"],
+            "env": {"LANG": "C.UTF-8"},
+            "timeout": 2.5
+        }))
+        .unwrap();
+        assert!(policy.allows("jq"));
+        assert!(!policy.allows("jq2"));
+        assert_eq!(policy.timeout, Duration::from_millis(2500));
+        assert_eq!(policy.max_output_bytes, 1024 * 1024);
+    }
+}
@@ -11,8 +11,13 @@
 //! - [`ToolDefinition`]: Describes an available tool with its parameters and metadata
 //! - [`ToolCall`]: Represents a request to execute a specific tool with arguments
 //! - [`ToolResult`]: The result of a tool execution (success or failure)
+//! - [`ToolResultStream`]: Partial results streamed by [`Transport::call_streaming`]
 //! - [`Parameter`]: Defines a tool parameter with type information and validation rules
 //! - [`ParameterType`]: Strongly-typed parameter types (String, Integer, Number, Boolean, Array, Object)
+//! - [`ParameterConstraints`]: Ranges, lengths, patterns and item counts checked by [`ToolDefinition::validate_args`]
+//! - [`NormalizedArgs`]: Arguments coerced to their parameter types by [`ToolDefinition::normalize_args`]
+//! - [`UsageRenderer`]: Renders CLI and JSON usage snippets from parameter examples
+//! - [`UrlBuilder`]: Joins paths, expands route templates and encodes query parameters
 //!
 //! ## MCP Types
 //!
@@ -20,11 +25,32 @@
 //! - [`ResourceContents`]: Content of a read resource (text or blob)
 //! - [`Prompt`]: MCP prompt definition with arguments
 //! - [`PromptMessage`]: Message in a rendered prompt
+//! - [`McpNotification`]: Server notification (log, progress, resource updated)
 //!
 //! ## Traits
 //!
 //! - [`Tool`]: Trait for implementing executable tools
 //! - [`Transport`]: Trait for implementing tool transport layers (e.g., MCP, HTTP, gRPC)
+//! - [`NotificationSink`]: Trait for receiving server notifications
+//! - [`Redactor`]: Trait for masking sensitive values before logging or persistence
+//! - [`SecretResolver`]: Trait for looking up secrets by name
+//! - [`SecretWriter`]: Trait for secret stores that can also be written to
+//! - [`TransportMiddleware`]: Trait for intercepting tool calls, stacked with [`LayeredTransport`]
+//!
+//! ## Routing
+//!
+//! - [`MultiplexTransport`]: Routes calls across providers of the same tool with failover
+//! - [`CachedTransport`]: Memoizes results of idempotent tool calls with a TTL and size limit
+//! - [`ExecTransport`]: Runs allow-listed local commands as the `exec` tool under an [`ExecPolicy`]
+//! - [`RenamedTransport`]: Lists a transport's tools under names given by [`ToolNameRules`]
+//!
+//! ## Runtime
+//!
+//! - [`ThulpRuntime`]: Owns background tasks and shuts them down gracefully with a deadline
+//!
+//! ## Testing
+//!
+//! - [`ChaosTransport`]: Wraps a transport and injects latency, errors and timeouts
 //!
 //! ## Features
 //!
@@ -196,18 +222,44 @@
 //! }
 //! ```
 
+mod cache;
+mod chaos;
 mod error;
+mod exec;
 mod mcp;
+mod middleware;
+mod multiplex;
+mod naming;
 mod parameter;
+mod redact;
+mod runtime;
+mod stream;
 mod tool;
 mod traits;
+mod url;
+mod usage;
 
+pub use cache::{CacheConfig, CacheStats, CachedTransport};
+pub use chaos::{ChaosConfig, ChaosStats, ChaosTransport, DEFAULT_CHAOS_ERROR};
 pub use error::{Error, Result};
+pub use exec::{ExecPolicy, ExecTransport, EXEC_TOOL};
 pub use mcp::{
-    EmbeddedResource, GetPromptResult, Prompt, PromptArgument, PromptBuilder, PromptContent,
-    PromptListResult, PromptMessage, Resource, ResourceAnnotations, ResourceBuilder,
-    ResourceContents, ResourceListResult, ResourceTemplate, ResourceTemplateListResult,
+    EmbeddedResource, GetPromptResult, LoggingLevel, McpNotification, Prompt, PromptArgument,
+    PromptBuilder, PromptContent, PromptListResult, PromptMessage, Resource, ResourceAnnotations,
+    ResourceBuilder, ResourceContents, ResourceListResult, ResourceTemplate,
+    ResourceTemplateListResult,
+};
+pub use middleware::{InjectArguments, LayeredTransport, Next, RedactResults, TransportMiddleware};
+pub use multiplex::{HealthConfig, MultiplexTransport, ProviderStats, RoutingPolicy};
+pub use naming::{to_snake_case, RenamedTransport, ToolNameRules};
+pub use parameter::{Parameter, ParameterBuilder, ParameterConstraints, ParameterType};
+pub use redact::{PathRedactor, SecretRedactor, REDACTED};
+pub use runtime::{ShutdownReport, ShutdownSignal, ThulpRuntime};
+pub use stream::{collect_stream, single_chunk, ToolResultStream};
+pub use tool::{
+    Coercion, NormalizedArgs, ToolCall, ToolCallBuilder, ToolDefinition, ToolDefinitionBuilder,
+    ToolResult,
 };
-pub use parameter::{Parameter, ParameterBuilder, ParameterType};
-pub use tool::{ToolCall, ToolCallBuilder, ToolDefinition, ToolDefinitionBuilder, ToolResult};
-pub use traits::{Tool, Transport};
+pub use traits::{NotificationSink, Redactor, SecretResolver, SecretWriter, Tool, Transport};
+pub use url::{percent_encode, UrlBuilder};
+pub use usage::{ToolUsage, UsageRenderer};
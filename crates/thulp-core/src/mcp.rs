@@ -429,6 +429,133 @@ pub struct ResourceTemplateListResult {
     pub next_cursor: Option<String>,
 }
 
+/// Severity of an MCP log message (RFC 5424 levels).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    #[default]
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LoggingLevel {
+    /// Get the level as its protocol string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoggingLevel::Debug => "debug",
+            LoggingLevel::Info => "info",
+            LoggingLevel::Notice => "notice",
+            LoggingLevel::Warning => "warning",
+            LoggingLevel::Error => "error",
+            LoggingLevel::Critical => "critical",
+            LoggingLevel::Alert => "alert",
+            LoggingLevel::Emergency => "emergency",
+        }
+    }
+}
+
+/// Notification sent by an MCP server outside of a request/response pair.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum McpNotification {
+    /// `notifications/message`: a log message from the server
+    Log {
+        level: LoggingLevel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        logger: Option<String>,
+        data: serde_json::Value,
+    },
+    /// `notifications/progress`: progress on a long-running request
+    Progress {
+        progress_token: serde_json::Value,
+        progress: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// `notifications/resources/updated`: a subscribed resource changed
+    ResourceUpdated { uri: String },
+    /// `notifications/resources/list_changed`
+    ResourceListChanged,
+    /// `notifications/tools/list_changed`
+    ToolListChanged,
+    /// `notifications/prompts/list_changed`
+    PromptListChanged,
+    /// Any notification without a dedicated variant
+    Other {
+        method: String,
+        params: serde_json::Value,
+    },
+}
+
+impl McpNotification {
+    /// Parse a JSON-RPC notification by method name and params.
+    ///
+    /// Known methods with malformed params fall back to [`McpNotification::Other`]
+    /// so nothing is dropped.
+    pub fn from_jsonrpc(method: &str, params: serde_json::Value) -> Self {
+        let str_field = |key: &str| params.get(key).and_then(|v| v.as_str()).map(String::from);
+        let parsed = match method {
+            "notifications/message" => params.get("level").and_then(|level| {
+                Some(McpNotification::Log {
+                    level: serde_json::from_value(level.clone()).ok()?,
+                    logger: str_field("logger"),
+                    data: params
+                        .get("data")
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null),
+                })
+            }),
+            "notifications/progress" => {
+                params
+                    .get("progress")
+                    .and_then(|p| p.as_f64())
+                    .map(|progress| McpNotification::Progress {
+                        progress_token: params
+                            .get("progressToken")
+                            .cloned()
+                            .unwrap_or(serde_json::Value::Null),
+                        progress,
+                        total: params.get("total").and_then(|t| t.as_f64()),
+                        message: str_field("message"),
+                    })
+            }
+            "notifications/resources/updated" => {
+                str_field("uri").map(|uri| McpNotification::ResourceUpdated { uri })
+            }
+            "notifications/resources/list_changed" => Some(McpNotification::ResourceListChanged),
+            "notifications/tools/list_changed" => Some(McpNotification::ToolListChanged),
+            "notifications/prompts/list_changed" => Some(McpNotification::PromptListChanged),
+            _ => None,
+        };
+
+        parsed.unwrap_or_else(|| McpNotification::Other {
+            method: method.to_string(),
+            params,
+        })
+    }
+
+    /// Get the JSON-RPC method name of this notification.
+    pub fn method(&self) -> &str {
+        match self {
+            McpNotification::Log { .. } => "notifications/message",
+            McpNotification::Progress { .. } => "notifications/progress",
+            McpNotification::ResourceUpdated { .. } => "notifications/resources/updated",
+            McpNotification::ResourceListChanged => "notifications/resources/list_changed",
+            McpNotification::ToolListChanged => "notifications/tools/list_changed",
+            McpNotification::PromptListChanged => "notifications/prompts/list_changed",
+            McpNotification::Other { method, .. } => method,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,4 +654,52 @@ mod tests {
         let parsed: Prompt = serde_json::from_str(&json).unwrap();
         assert_eq!(prompt, parsed);
     }
+
+    #[test]
+    fn test_notification_from_jsonrpc_log() {
+        let n = McpNotification::from_jsonrpc(
+            "notifications/message",
+            serde_json::json!({"level": "warning", "logger": "db", "data": {"msg": "slow"}}),
+        );
+        assert_eq!(
+            n,
+            McpNotification::Log {
+                level: LoggingLevel::Warning,
+                logger: Some("db".to_string()),
+                data: serde_json::json!({"msg": "slow"}),
+            }
+        );
+        assert_eq!(n.method(), "notifications/message");
+    }
+
+    #[test]
+    fn test_notification_from_jsonrpc_progress() {
+        let n = McpNotification::from_jsonrpc(
+            "notifications/progress",
+            serde_json::json!({"progressToken": "t1", "progress": 5, "total": 10}),
+        );
+        match n {
+            McpNotification::Progress {
+                progress, total, ..
+            } => {
+                assert_eq!(progress, 5.0);
+                assert_eq!(total, Some(10.0));
+            }
+            other => panic!("unexpected notification: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_notification_from_jsonrpc_fallback() {
+        let n =
+            McpNotification::from_jsonrpc("notifications/resources/updated", serde_json::json!({}));
+        assert!(matches!(n, McpNotification::Other { .. }));
+        assert_eq!(n.method(), "notifications/resources/updated");
+
+        let n = McpNotification::from_jsonrpc(
+            "notifications/tools/list_changed",
+            serde_json::Value::Null,
+        );
+        assert_eq!(n, McpNotification::ToolListChanged);
+    }
 }
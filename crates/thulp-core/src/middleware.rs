@@ -0,0 +1,442 @@
+//! Composable interceptors around tool calls.
+//!
+//! A [`TransportMiddleware`] sees every call on its way to a transport and
+//! every result on its way back, and decides whether, and with what
+//! arguments, to pass the call on. [`LayeredTransport`] stacks middleware
+//! around a transport the way tower layers stack around a service: the first
+//! layer added is the outermost, so it sees the call first and the result
+//! last.
+//!
+//! Two middleware are provided: [`InjectArguments`] adds fixed arguments,
+//! such as credentials, to calls, and [`RedactResults`] masks sensitive
+//! values in result data with a [`Redactor`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use std::sync::Arc;
+//! use async_trait::async_trait;
+//! use thulp_core::{
+//!     InjectArguments, LayeredTransport, Next, PathRedactor, RedactResults, Result, ToolCall,
+//!     ToolResult, TransportMiddleware,
+//! };
+//! # use thulp_core::{ToolDefinition, Transport};
+//! # struct Echo;
+//! # #[async_trait::async_trait]
+//! # impl Transport for Echo {
+//! #     async fn connect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     async fn disconnect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+//! #     fn is_connected(&self) -> bool { true }
+//! #     async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> { Ok(vec![]) }
+//! #     async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+//! #         Ok(ToolResult::success(call.arguments.clone()))
+//! #     }
+//! # }
+//!
+//! /// Counts calls per transport
+//! #[derive(Default)]
+//! struct CountCalls(AtomicU64);
+//!
+//! #[async_trait]
+//! impl TransportMiddleware for CountCalls {
+//!     async fn handle(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResult> {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!         next.run(call).await
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> thulp_core::Result<()> {
+//! let transport = LayeredTransport::new(Echo)
+//!     .layer(CountCalls::default())
+//!     .layer(RedactResults::new(Arc::new(PathRedactor::new().with_path("api_key"))))
+//!     .layer(InjectArguments::new().with_argument("api_key", "secret"));
+//!
+//! let result = transport.call(&ToolCall::new("search")).await?;
+//! assert_eq!(result.data.unwrap()["api_key"], "[REDACTED]");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Redactor, Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Intercepts tool calls made through a [`LayeredTransport`].
+///
+/// Both methods default to passing the call on untouched, so a middleware
+/// only overrides what it cares about. To change a call, pass a modified copy
+/// to [`Next::run`]; to short-circuit it, return without calling `next`.
+#[async_trait]
+pub trait TransportMiddleware: Send + Sync {
+    /// Handle a call, usually by passing it on to `next`.
+    async fn handle(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResult> {
+        next.run(call).await
+    }
+
+    /// Handle a streaming call, usually by passing it on to `next`.
+    async fn handle_streaming(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResultStream> {
+        next.run_streaming(call).await
+    }
+}
+
+/// The rest of the middleware chain, ending at the wrapped transport.
+pub struct Next<'a> {
+    middleware: &'a [Arc<dyn TransportMiddleware>],
+    transport: &'a dyn Transport,
+}
+
+impl<'a> Next<'a> {
+    /// Pass a call to the next middleware, or to the transport.
+    pub async fn run(self, call: &ToolCall) -> Result<ToolResult> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                first
+                    .handle(
+                        call,
+                        Next {
+                            middleware: rest,
+                            transport: self.transport,
+                        },
+                    )
+                    .await
+            }
+            None => self.transport.call(call).await,
+        }
+    }
+
+    /// Pass a streaming call to the next middleware, or to the transport.
+    pub async fn run_streaming(self, call: &ToolCall) -> Result<ToolResultStream> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                first
+                    .handle_streaming(
+                        call,
+                        Next {
+                            middleware: rest,
+                            transport: self.transport,
+                        },
+                    )
+                    .await
+            }
+            None => self.transport.call_streaming(call).await,
+        }
+    }
+}
+
+/// A [`Transport`] wrapped in a stack of [`TransportMiddleware`].
+///
+/// Connecting and listing tools go straight to the wrapped transport.
+pub struct LayeredTransport<T> {
+    inner: T,
+    middleware: Vec<Arc<dyn TransportMiddleware>>,
+}
+
+impl<T> std::fmt::Debug for LayeredTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayeredTransport")
+            .field("layers", &self.middleware.len())
+            .finish()
+    }
+}
+
+impl<T> LayeredTransport<T> {
+    /// Wrap a transport with no middleware.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Add a middleware inside the ones already added.
+    pub fn layer(self, middleware: impl TransportMiddleware + 'static) -> Self {
+        self.layer_arc(Arc::new(middleware))
+    }
+
+    /// Add a shared middleware inside the ones already added.
+    pub fn layer_arc(mut self, middleware: Arc<dyn TransportMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Get the number of middleware layers.
+    pub fn len(&self) -> usize {
+        self.middleware.len()
+    }
+
+    /// Check if no middleware has been added.
+    pub fn is_empty(&self) -> bool {
+        self.middleware.is_empty()
+    }
+
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the transport, dropping the middleware.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Transport> LayeredTransport<T> {
+    fn next(&self) -> Next<'_> {
+        Next {
+            middleware: &self.middleware,
+            transport: &self.inner,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for LayeredTransport<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.inner.list_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        self.next().run(call).await
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        self.next().run_streaming(call).await
+    }
+}
+
+/// Adds fixed arguments, such as API keys, to every call.
+///
+/// Arguments the call already sets are kept unless
+/// [`overwrite`](Self::overwrite) is enabled. Calls whose arguments aren't
+/// an object are passed on untouched.
+#[derive(Debug, Clone, Default)]
+pub struct InjectArguments {
+    arguments: Map<String, Value>,
+    tools: Vec<String>,
+    overwrite: bool,
+}
+
+impl InjectArguments {
+    /// Create a middleware that injects nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject an argument.
+    pub fn with_argument(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.arguments.insert(name.into(), value.into());
+        self
+    }
+
+    /// Only inject into calls to the given tool.
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tools.push(tool.into());
+        self
+    }
+
+    /// Replace arguments the call already sets.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Copy of the call with the arguments injected, if any apply
+    fn inject(&self, call: &ToolCall) -> Option<ToolCall> {
+        if !self.tools.is_empty() && !self.tools.contains(&call.tool) {
+            return None;
+        }
+        let mut call = call.clone();
+        if call.arguments.is_null() {
+            call.arguments = Value::Object(Map::new());
+        }
+        let arguments = call.arguments.as_object_mut()?;
+        for (name, value) in &self.arguments {
+            if self.overwrite || !arguments.contains_key(name) {
+                arguments.insert(name.clone(), value.clone());
+            }
+        }
+        Some(call)
+    }
+}
+
+#[async_trait]
+impl TransportMiddleware for InjectArguments {
+    async fn handle(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResult> {
+        match self.inject(call) {
+            Some(call) => next.run(&call).await,
+            None => next.run(call).await,
+        }
+    }
+
+    async fn handle_streaming(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResultStream> {
+        match self.inject(call) {
+            Some(call) => next.run_streaming(&call).await,
+            None => next.run_streaming(call).await,
+        }
+    }
+}
+
+/// Masks sensitive values in result data before it reaches the caller.
+///
+/// Streamed chunks are passed through as they are.
+pub struct RedactResults {
+    redactor: Arc<dyn Redactor>,
+}
+
+impl std::fmt::Debug for RedactResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedactResults").finish_non_exhaustive()
+    }
+}
+
+impl RedactResults {
+    /// Redact results with a redactor.
+    pub fn new(redactor: Arc<dyn Redactor>) -> Self {
+        Self { redactor }
+    }
+}
+
+#[async_trait]
+impl TransportMiddleware for RedactResults {
+    async fn handle(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResult> {
+        let mut result = next.run(call).await?;
+        if let Some(data) = &result.data {
+            result.data = Some(self.redactor.redact(data));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, PathRedactor};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    struct Echo;
+
+    #[async_trait]
+    impl Transport for Echo {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+            Ok(ToolResult::success(call.arguments.clone()))
+        }
+    }
+
+    /// Records the order it sees calls and results in
+    struct Trace {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl TransportMiddleware for Trace {
+        async fn handle(&self, call: &ToolCall, next: Next<'_>) -> Result<ToolResult> {
+            self.log.lock().unwrap().push(format!("{} in", self.name));
+            let result = next.run(call).await;
+            self.log.lock().unwrap().push(format!("{} out", self.name));
+            result
+        }
+    }
+
+    struct Deny;
+
+    #[async_trait]
+    impl TransportMiddleware for Deny {
+        async fn handle(&self, call: &ToolCall, _next: Next<'_>) -> Result<ToolResult> {
+            Err(Error::ExecutionFailed(format!("'{}' is denied", call.tool)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_layers_run_outermost_first() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let transport = LayeredTransport::new(Echo)
+            .layer(Trace {
+                name: "outer",
+                log: log.clone(),
+            })
+            .layer(Trace {
+                name: "inner",
+                log: log.clone(),
+            });
+
+        transport.call(&ToolCall::new("echo")).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["outer in", "inner in", "inner out", "outer out"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_circuit_skips_transport() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let transport = LayeredTransport::new(Echo).layer(Deny).layer(Trace {
+            name: "never",
+            log: log.clone(),
+        });
+
+        let err = transport.call(&ToolCall::new("rm")).await.unwrap_err();
+        assert!(err.to_string().contains("'rm' is denied"));
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_inject_arguments_and_redact() {
+        let transport = LayeredTransport::new(Echo)
+            .layer(RedactResults::new(Arc::new(
+                PathRedactor::new().with_path("token"),
+            )))
+            .layer(
+                InjectArguments::new()
+                    .with_argument("token", "secret")
+                    .with_argument("region", "eu")
+                    .with_tool("search"),
+            );
+
+        let call = ToolCall::builder("search")
+            .arg("region", json!("us"))
+            .build();
+        let data = transport.call(&call).await.unwrap().data.unwrap();
+        assert_eq!(data, json!({"token": "[REDACTED]", "region": "us"}));
+
+        let data = transport
+            .call(&ToolCall::new("other"))
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert_eq!(data, json!({}));
+    }
+}
@@ -0,0 +1,673 @@
+//! Routing tool calls across several providers.
+
+use crate::{Error, Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Weight given to the newest sample in the moving latency average.
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// How a [`MultiplexTransport`] chooses between providers of the same tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPolicy {
+    /// Rotate through providers, each getting calls in proportion to its weight
+    #[default]
+    RoundRobin,
+    /// Pick providers at random in proportion to weight divided by average latency
+    LatencyWeighted,
+    /// Stay on the provider that last served a tool; the first listed wins initially
+    Sticky,
+}
+
+/// When providers are considered unhealthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthConfig {
+    /// Consecutive transport errors after which a provider is taken out of rotation
+    pub failure_threshold: u32,
+
+    /// How long an unhealthy provider stays out of rotation
+    pub cooldown: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthConfig {
+    /// Three strikes, thirty seconds out.
+    pub fn new() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the number of consecutive failures that mark a provider unhealthy.
+    pub fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Set how long unhealthy providers are skipped.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+/// Health and traffic counters for one provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderStats {
+    /// Provider name
+    pub name: String,
+
+    /// Routing weight
+    pub weight: u32,
+
+    /// Whether the provider is currently in rotation
+    pub healthy: bool,
+
+    /// Calls routed to the provider
+    pub calls: u64,
+
+    /// Calls that failed with a transport error
+    pub failures: u64,
+
+    /// Moving average of call latency, once a call has succeeded
+    pub avg_latency: Option<Duration>,
+}
+
+struct Provider {
+    name: String,
+    transport: Box<dyn Transport>,
+    weight: u32,
+}
+
+#[derive(Default)]
+struct Health {
+    calls: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+    latency_secs: Option<f64>,
+}
+
+impl Health {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.map_or(true, |until| now >= until)
+    }
+}
+
+struct RouterState {
+    /// Provider indices exposing each tool, once tools have been listed
+    tools: Option<HashMap<String, Vec<usize>>>,
+    health: Vec<Health>,
+    /// Round-robin position per tool
+    cursors: HashMap<String, u64>,
+    /// Provider that last served each tool, for sticky routing
+    sticky: HashMap<String, usize>,
+    rng: fastrand::Rng,
+}
+
+/// A [`Transport`] that routes each call to one of several providers.
+///
+/// Each tool is routed to the providers that list it; when more than one
+/// server exposes the same tool, a [`RoutingPolicy`] picks which one serves a
+/// call:
+///
+/// | Policy | Behaviour |
+/// |--------|-----------|
+/// | [`RoundRobin`](RoutingPolicy::RoundRobin) | Rotate through providers, in proportion to their weights |
+/// | [`LatencyWeighted`](RoutingPolicy::LatencyWeighted) | Prefer providers that answer fastest, scaled by weight |
+/// | [`Sticky`](RoutingPolicy::Sticky) | Keep using the provider that last served the tool until it fails |
+///
+/// If a provider returns a transport error the call fails over to the next
+/// candidate. Providers that fail [`failure_threshold`](HealthConfig::failure_threshold)
+/// times in a row are taken out of rotation for a
+/// [`cooldown`](HealthConfig::cooldown), and only tried again once every
+/// healthy provider has failed. A [`ToolResult`] reporting failure is an
+/// answer from the tool, not a provider fault, and is returned as is.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use thulp_core::{HealthConfig, MultiplexTransport, RoutingPolicy};
+/// # use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+/// # struct Server;
+/// # #[async_trait::async_trait]
+/// # impl Transport for Server {
+/// #     async fn connect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+/// #     async fn disconnect(&mut self) -> thulp_core::Result<()> { Ok(()) }
+/// #     fn is_connected(&self) -> bool { true }
+/// #     async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+/// #         Ok(vec![ToolDefinition::new("search")])
+/// #     }
+/// #     async fn call(&self, _: &ToolCall) -> thulp_core::Result<ToolResult> {
+/// #         Ok(ToolResult::success(serde_json::json!("ok")))
+/// #     }
+/// # }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> thulp_core::Result<()> {
+/// let mut transport = MultiplexTransport::new(RoutingPolicy::LatencyWeighted)
+///     .with_provider("primary", Server)
+///     .with_weighted_provider("backup", Server, 2)
+///     .with_health(HealthConfig::new().with_cooldown(Duration::from_secs(10)));
+/// transport.connect().await?;
+///
+/// assert_eq!(transport.providers_for("search"), ["primary", "backup"]);
+/// let result = transport.call(&ToolCall::new("search")).await?;
+/// assert!(result.is_success());
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiplexTransport {
+    providers: Vec<Provider>,
+    policy: RoutingPolicy,
+    health: HealthConfig,
+    state: Mutex<RouterState>,
+}
+
+impl std::fmt::Debug for MultiplexTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiplexTransport")
+            .field("policy", &self.policy)
+            .field("health", &self.health)
+            .field("providers", &self.stats())
+            .finish()
+    }
+}
+
+impl MultiplexTransport {
+    /// Create a transport without providers.
+    pub fn new(policy: RoutingPolicy) -> Self {
+        Self {
+            providers: Vec::new(),
+            policy,
+            health: HealthConfig::new(),
+            state: Mutex::new(RouterState {
+                tools: None,
+                health: Vec::new(),
+                cursors: HashMap::new(),
+                sticky: HashMap::new(),
+                rng: fastrand::Rng::new(),
+            }),
+        }
+    }
+
+    /// Add a provider with weight 1.
+    pub fn with_provider(
+        self,
+        name: impl Into<String>,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        self.with_weighted_provider(name, transport, 1)
+    }
+
+    /// Add a provider with a routing weight (at least 1).
+    ///
+    /// Providers listed first are preferred by sticky routing and when
+    /// tool definitions differ between providers.
+    pub fn with_weighted_provider(
+        mut self,
+        name: impl Into<String>,
+        transport: impl Transport + 'static,
+        weight: u32,
+    ) -> Self {
+        self.providers.push(Provider {
+            name: name.into(),
+            transport: Box::new(transport),
+            weight: weight.max(1),
+        });
+        self.lock().health.push(Health::default());
+        self
+    }
+
+    /// Set when providers are taken out of rotation.
+    pub fn with_health(mut self, health: HealthConfig) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Seed the random choices of latency-weighted routing.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.lock().rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+
+    /// Get the routing policy.
+    pub fn policy(&self) -> RoutingPolicy {
+        self.policy
+    }
+
+    /// Names of the providers exposing a tool, in the order they were added.
+    ///
+    /// Empty until tools have been listed (which [`connect`](Transport::connect)
+    /// does).
+    pub fn providers_for(&self, tool: &str) -> Vec<String> {
+        let state = self.lock();
+        state
+            .tools
+            .as_ref()
+            .and_then(|tools| tools.get(tool))
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|&i| self.providers[i].name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get health and traffic counters for every provider.
+    pub fn stats(&self) -> Vec<ProviderStats> {
+        let state = self.lock();
+        let now = Instant::now();
+        self.providers
+            .iter()
+            .zip(&state.health)
+            .map(|(provider, health)| ProviderStats {
+                name: provider.name.clone(),
+                weight: provider.weight,
+                healthy: health.is_healthy(now),
+                calls: health.calls,
+                failures: health.failures,
+                avg_latency: health.latency_secs.map(Duration::from_secs_f64),
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RouterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Providers to try for a tool, best first, unhealthy ones last
+    fn candidates(&self, tool: &str) -> Result<Vec<usize>> {
+        let mut state = self.lock();
+        let all: Vec<usize> = match &state.tools {
+            Some(tools) => tools
+                .get(tool)
+                .cloned()
+                .ok_or_else(|| Error::ToolNotFound(tool.to_string()))?,
+            // Tools not listed yet: let every provider have a go
+            None => (0..self.providers.len()).collect(),
+        };
+
+        let now = Instant::now();
+        let (mut healthy, mut unhealthy): (Vec<usize>, Vec<usize>) = all
+            .into_iter()
+            .partition(|&i| state.health[i].is_healthy(now));
+        unhealthy.sort_by_key(|&i| state.health[i].unhealthy_until);
+
+        if !healthy.is_empty() {
+            match self.policy {
+                RoutingPolicy::RoundRobin => {
+                    let total: u64 = healthy
+                        .iter()
+                        .map(|&i| self.providers[i].weight as u64)
+                        .sum();
+                    let cursor = state.cursors.entry(tool.to_string()).or_insert(0);
+                    let mut slot = *cursor % total;
+                    *cursor += 1;
+                    let first = healthy
+                        .iter()
+                        .position(|&i| {
+                            let weight = self.providers[i].weight as u64;
+                            let hit = slot < weight;
+                            slot = slot.saturating_sub(weight);
+                            hit
+                        })
+                        .unwrap_or(0);
+                    healthy.rotate_left(first);
+                }
+                RoutingPolicy::LatencyWeighted => {
+                    let scores: Vec<f64> = healthy
+                        .iter()
+                        .map(|&i| match state.health[i].latency_secs {
+                            Some(secs) => self.providers[i].weight as f64 / secs.max(1e-3),
+                            // Unmeasured providers go first so they get measured
+                            None => f64::INFINITY,
+                        })
+                        .collect();
+                    let mut order: Vec<usize> = (0..healthy.len()).collect();
+                    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+                    if scores[order[0]].is_finite() {
+                        let total: f64 = scores.iter().sum();
+                        let mut roll = state.rng.f64() * total;
+                        let pick = (0..scores.len())
+                            .find(|&k| {
+                                roll -= scores[k];
+                                roll < 0.0
+                            })
+                            .unwrap_or(order[0]);
+                        order.retain(|&k| k != pick);
+                        order.insert(0, pick);
+                    }
+                    healthy = order.into_iter().map(|k| healthy[k]).collect();
+                }
+                RoutingPolicy::Sticky => {
+                    if let Some(&current) = state.sticky.get(tool) {
+                        if let Some(pos) = healthy.iter().position(|&i| i == current) {
+                            let current = healthy.remove(pos);
+                            healthy.insert(0, current);
+                        }
+                    }
+                }
+            }
+        }
+
+        healthy.extend(unhealthy);
+        Ok(healthy)
+    }
+
+    /// Update a provider's health after a call
+    fn record(&self, index: usize, tool: &str, outcome: std::result::Result<Duration, ()>) {
+        let mut state = self.lock();
+        let health = &mut state.health[index];
+        health.calls += 1;
+        match outcome {
+            Ok(elapsed) => {
+                let secs = elapsed.as_secs_f64();
+                health.latency_secs = Some(match health.latency_secs {
+                    Some(avg) => avg + LATENCY_SMOOTHING * (secs - avg),
+                    None => secs,
+                });
+                health.consecutive_failures = 0;
+                health.unhealthy_until = None;
+                state.sticky.insert(tool.to_string(), index);
+            }
+            Err(()) => {
+                health.failures += 1;
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= self.health.failure_threshold {
+                    health.unhealthy_until = Some(Instant::now() + self.health.cooldown);
+                }
+                if state.sticky.get(tool) == Some(&index) {
+                    state.sticky.remove(tool);
+                }
+            }
+        }
+    }
+
+    /// Rebuild the tool index from every connected provider.
+    ///
+    /// Returns the tool definitions, taking each tool's definition from the
+    /// first provider that lists it.
+    pub async fn refresh_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut definitions = Vec::new();
+        for (i, provider) in self.providers.iter().enumerate() {
+            if !provider.transport.is_connected() {
+                continue;
+            }
+            for tool in provider.transport.list_tools().await? {
+                let providers = index.entry(tool.name.clone()).or_default();
+                if providers.is_empty() {
+                    definitions.push(tool);
+                }
+                providers.push(i);
+            }
+        }
+        self.lock().tools = Some(index);
+        Ok(definitions)
+    }
+}
+
+#[async_trait]
+impl Transport for MultiplexTransport {
+    /// Connect every provider and index their tools.
+    ///
+    /// Providers that fail to connect are skipped; this only fails if none
+    /// connect.
+    async fn connect(&mut self) -> Result<()> {
+        let mut last_error = None;
+        for provider in &mut self.providers {
+            if let Err(e) = provider.transport.connect().await {
+                last_error = Some(Error::ExecutionFailed(format!(
+                    "provider '{}' failed to connect: {}",
+                    provider.name, e
+                )));
+            }
+        }
+        if !self.is_connected() {
+            return Err(last_error
+                .unwrap_or_else(|| Error::InvalidConfig("No providers configured".to_string())));
+        }
+        self.refresh_tools().await?;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for provider in &mut self.providers {
+            if let Err(e) = provider.transport.disconnect().await {
+                result = Err(e);
+            }
+        }
+        self.lock().tools = None;
+        result
+    }
+
+    fn is_connected(&self) -> bool {
+        self.providers.iter().any(|p| p.transport.is_connected())
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.refresh_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        let mut last_error = None;
+        for index in self.candidates(&call.tool)? {
+            let start = Instant::now();
+            match self.providers[index].transport.call(call).await {
+                Ok(result) => {
+                    self.record(index, &call.tool, Ok(start.elapsed()));
+                    return Ok(result);
+                }
+                Err(e) => {
+                    self.record(index, &call.tool, Err(()));
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::ToolNotFound(call.tool.clone())))
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        let mut last_error = None;
+        for index in self.candidates(&call.tool)? {
+            let start = Instant::now();
+            match self.providers[index].transport.call_streaming(call).await {
+                Ok(stream) => {
+                    self.record(index, &call.tool, Ok(start.elapsed()));
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    self.record(index, &call.tool, Err(()));
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::ToolNotFound(call.tool.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Provider answering with its own name after a delay
+    struct Server {
+        name: &'static str,
+        tools: Vec<&'static str>,
+        delay: Duration,
+        down: Arc<AtomicBool>,
+    }
+
+    impl Server {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                tools: vec!["search"],
+                delay: Duration::ZERO,
+                down: Arc::new(AtomicBool::new(false)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Transport for Server {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(self.tools.iter().map(|t| ToolDefinition::new(*t)).collect())
+        }
+
+        async fn call(&self, _call: &ToolCall) -> Result<ToolResult> {
+            tokio::time::sleep(self.delay).await;
+            if self.down.load(Ordering::SeqCst) {
+                return Err(Error::ExecutionFailed(format!("{} is down", self.name)));
+            }
+            Ok(ToolResult::success(json!(self.name)))
+        }
+    }
+
+    async fn served_by(transport: &MultiplexTransport, calls: usize) -> Vec<String> {
+        let mut names = Vec::new();
+        for _ in 0..calls {
+            let result = transport.call(&ToolCall::new("search")).await.unwrap();
+            names.push(result.data.unwrap().as_str().unwrap().to_string());
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin() {
+        let mut transport = MultiplexTransport::new(RoutingPolicy::RoundRobin)
+            .with_provider("a", Server::new("a"))
+            .with_weighted_provider("b", Server::new("b"), 2);
+        transport.connect().await.unwrap();
+
+        assert_eq!(
+            served_by(&transport, 6).await,
+            ["a", "b", "b", "a", "b", "b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_index() {
+        let mut only_a = Server::new("a");
+        only_a.tools = vec!["search", "fetch"];
+        let mut transport = MultiplexTransport::new(RoutingPolicy::RoundRobin)
+            .with_provider("a", only_a)
+            .with_provider("b", Server::new("b"));
+        let tools = transport.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 2);
+        transport.connect().await.unwrap();
+
+        assert_eq!(transport.providers_for("search"), ["a", "b"]);
+        assert_eq!(transport.providers_for("fetch"), ["a"]);
+        for _ in 0..3 {
+            let result = transport.call(&ToolCall::new("fetch")).await.unwrap();
+            assert_eq!(result.data, Some(json!("a")));
+        }
+        assert!(matches!(
+            transport.call(&ToolCall::new("missing")).await,
+            Err(Error::ToolNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failover_and_health() {
+        let primary = Server::new("primary");
+        let down = primary.down.clone();
+        let mut transport = MultiplexTransport::new(RoutingPolicy::Sticky)
+            .with_provider("primary", primary)
+            .with_provider("backup", Server::new("backup"))
+            .with_health(
+                HealthConfig::new()
+                    .with_failure_threshold(2)
+                    .with_cooldown(Duration::from_millis(50)),
+            );
+        transport.connect().await.unwrap();
+        assert_eq!(served_by(&transport, 2).await, ["primary", "primary"]);
+
+        // Calls fail over to the backup and stick to it
+        down.store(true, Ordering::SeqCst);
+        assert_eq!(served_by(&transport, 3).await, ["backup"; 3]);
+        let stats = transport.stats();
+        assert_eq!((stats[0].calls, stats[0].failures), (3, 1));
+        assert!(stats[0].healthy);
+
+        // A second failure in a row marks the primary unhealthy
+        assert_eq!(served_by_fresh_tool(&transport).await, "backup");
+        assert!(!transport.stats()[0].healthy);
+
+        // After the cooldown it is tried again and recovers
+        down.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(transport.stats()[0].healthy);
+        assert_eq!(served_by_fresh_tool(&transport).await, "primary");
+        assert_eq!(transport.stats()[0].failures, 2);
+    }
+
+    /// Call a tool nothing is stuck to, so the primary is tried first
+    async fn served_by_fresh_tool(transport: &MultiplexTransport) -> String {
+        transport.lock().sticky.clear();
+        served_by(transport, 1).await.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_failing() {
+        let a = Server::new("a");
+        let b = Server::new("b");
+        a.down.store(true, Ordering::SeqCst);
+        b.down.store(true, Ordering::SeqCst);
+        let transport = MultiplexTransport::new(RoutingPolicy::RoundRobin)
+            .with_provider("a", a)
+            .with_provider("b", b);
+
+        let err = transport.call(&ToolCall::new("search")).await.unwrap_err();
+        assert!(err.to_string().contains("is down"));
+        assert!(transport.stats().iter().all(|s| s.failures == 1));
+    }
+
+    #[tokio::test]
+    async fn test_latency_weighted_prefers_fast_provider() {
+        let mut slow = Server::new("slow");
+        slow.delay = Duration::from_millis(20);
+        let mut transport = MultiplexTransport::new(RoutingPolicy::LatencyWeighted)
+            .with_provider("slow", slow)
+            .with_provider("fast", Server::new("fast"))
+            .with_seed(3);
+        transport.connect().await.unwrap();
+
+        // The first two calls measure each provider
+        let names = served_by(&transport, 22).await;
+        assert_eq!(names[..2], ["slow", "fast"]);
+        let fast = names[2..].iter().filter(|n| *n == "fast").count();
+        assert!(fast >= 17, "fast served {} of 20", fast);
+        assert!(transport.stats()[0].avg_latency.unwrap() >= Duration::from_millis(20));
+    }
+}
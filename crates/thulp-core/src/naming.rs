@@ -0,0 +1,353 @@
+//! Renaming the tools of a server or adapter.
+//!
+//! Servers name their tools however they like: `createIssue` next to
+//! `list_repos` next to `search-code`. [`ToolNameRules`] describe how to
+//! bring one source's names in line with the rest of a catalog, and
+//! [`RenamedTransport`] applies them to a transport: its tools are listed
+//! under the new names, and calls to a new name are sent to the server under
+//! the original one.
+//!
+//! Each name goes through the rules in order:
+//!
+//! 1. an explicit [`rename`](ToolNameRules::with_rename) of the original name
+//!    is used as it is, and the other rules are skipped
+//! 2. with [`snake_case`](ToolNameRules::with_snake_case), `camelCase`,
+//!    `PascalCase` and `kebab-case` become `snake_case`
+//! 3. the [`prefix`](ToolNameRules::with_prefix) is prepended
+//!
+//! The rules deserialize from configuration such as:
+//!
+//! ```yaml
+//! snake_case: true
+//! prefix: gh_
+//! rename:
+//!   searchCode: code_search
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use thulp_core::ToolNameRules;
+//!
+//! let rules = ToolNameRules::new()
+//!     .with_snake_case()
+//!     .with_prefix("gh_")
+//!     .with_rename("searchCode", "code_search");
+//!
+//! assert_eq!(rules.apply("createIssue"), "gh_create_issue");
+//! assert_eq!(rules.apply("HTTPRequest"), "gh_http_request");
+//! assert_eq!(rules.apply("searchCode"), "code_search");
+//! ```
+
+use crate::{Error, Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+/// How to rename the tools of one source
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolNameRules {
+    /// New names for particular tools, by original name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rename: BTreeMap<String, String>,
+
+    /// Convert camelCase, PascalCase and kebab-case names to snake_case
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub snake_case: bool,
+
+    /// Prepended to every name that isn't renamed explicitly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+impl ToolNameRules {
+    /// Create rules that keep every name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename the tool called `from` to `to`.
+    pub fn with_rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.rename.insert(from.into(), to.into());
+        self
+    }
+
+    /// Convert names to snake_case.
+    pub fn with_snake_case(mut self) -> Self {
+        self.snake_case = true;
+        self
+    }
+
+    /// Prepend `prefix` to names.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Check whether the rules keep every name.
+    pub fn is_empty(&self) -> bool {
+        self.rename.is_empty() && !self.snake_case && self.prefix.is_none()
+    }
+
+    /// The new name of the tool called `name`.
+    pub fn apply(&self, name: &str) -> String {
+        if let Some(renamed) = self.rename.get(name) {
+            return renamed.clone();
+        }
+        let name = if self.snake_case {
+            to_snake_case(name)
+        } else {
+            name.to_string()
+        };
+        match &self.prefix {
+            Some(prefix) => format!("{}{}", prefix, name),
+            None => name,
+        }
+    }
+
+    /// Rename tool definitions, failing if two would get the same name.
+    pub fn apply_all(&self, tools: Vec<ToolDefinition>) -> Result<Vec<ToolDefinition>> {
+        let mut originals: HashMap<String, String> = HashMap::new();
+        tools
+            .into_iter()
+            .map(|mut tool| {
+                let name = self.apply(&tool.name);
+                if let Some(other) = originals.insert(name.clone(), tool.name.clone()) {
+                    return Err(Error::InvalidConfig(format!(
+                        "tools '{}' and '{}' would both be named '{}'",
+                        other, tool.name, name
+                    )));
+                }
+                tool.name = name;
+                Ok(tool)
+            })
+            .collect()
+    }
+}
+
+/// Convert `camelCase`, `PascalCase` and `kebab-case` to `snake_case`,
+/// keeping acronyms together: `getHTTPResponse` becomes
+/// `get_http_response`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '-' || c == ' ' {
+            out.push('_');
+        } else if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(p) if p.is_lowercase() || p.is_ascii_digit() => true,
+                // The last capital of an acronym starts the next word
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+            if boundary && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// A [`Transport`] listing another's tools under new names.
+///
+/// Calls are mapped back to the original names, learnt from the last
+/// [`list_tools`](Transport::list_tools); a call before the first listing
+/// lists the tools itself.
+pub struct RenamedTransport<T> {
+    inner: T,
+    rules: ToolNameRules,
+    /// Original name of each new name
+    originals: RwLock<HashMap<String, String>>,
+}
+
+impl<T> std::fmt::Debug for RenamedTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenamedTransport")
+            .field("rules", &self.rules)
+            .finish()
+    }
+}
+
+impl<T: Transport> RenamedTransport<T> {
+    /// Rename the tools of `inner` under `rules`.
+    pub fn new(inner: T, rules: ToolNameRules) -> Self {
+        Self {
+            inner,
+            rules,
+            originals: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// The rules applied.
+    pub fn rules(&self) -> &ToolNameRules {
+        &self.rules
+    }
+
+    /// The call with the tool's original name
+    async fn original(&self, call: &ToolCall) -> Result<ToolCall> {
+        let known = |name: &str| self.originals.read().unwrap().get(name).cloned();
+        let original = match known(&call.tool) {
+            Some(original) => original,
+            None => {
+                self.list_tools().await?;
+                known(&call.tool).ok_or_else(|| Error::ToolNotFound(call.tool.clone()))?
+            }
+        };
+        Ok(ToolCall {
+            tool: original,
+            arguments: call.arguments.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RenamedTransport<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let tools = self.inner.list_tools().await?;
+        let names: Vec<String> = tools.iter().map(|t| t.name.clone()).collect();
+        let renamed = self.rules.apply_all(tools)?;
+        *self.originals.write().unwrap() =
+            renamed.iter().map(|t| t.name.clone()).zip(names).collect();
+        Ok(renamed)
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        self.inner.call(&self.original(call).await?).await
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        self.inner.call_streaming(&self.original(call).await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Echoes the tool name it was called with
+    struct Named(Vec<&'static str>);
+
+    #[async_trait]
+    impl Transport for Named {
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            Ok(self
+                .0
+                .iter()
+                .map(|name| ToolDefinition::new(*name))
+                .collect())
+        }
+
+        async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+            Ok(ToolResult::success(json!(call.tool)))
+        }
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        for (name, expected) in [
+            ("createIssue", "create_issue"),
+            ("CreateIssue", "create_issue"),
+            ("getHTTPResponse", "get_http_response"),
+            ("list-repos", "list_repos"),
+            ("already_snake", "already_snake"),
+            ("v2Search", "v2_search"),
+            ("oauth2Token", "oauth2_token"),
+        ] {
+            assert_eq!(to_snake_case(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_rules_from_config() {
+        let rules: ToolNameRules = serde_json::from_value(json!({
+            "snake_case": true,
+            "prefix": "gh_",
+            "rename": {"searchCode": "code_search"}
+        }))
+        .unwrap();
+        assert_eq!(rules.apply("createIssue"), "gh_create_issue");
+        assert_eq!(rules.apply("searchCode"), "code_search");
+        assert!(ToolNameRules::new().is_empty());
+        assert_eq!(ToolNameRules::new().apply("createIssue"), "createIssue");
+
+        let clash = ToolNameRules::new().with_snake_case();
+        let err = clash
+            .apply_all(vec![
+                ToolDefinition::new("listRepos"),
+                ToolDefinition::new("list_repos"),
+            ])
+            .unwrap_err();
+        assert!(err.to_string().contains("would both be named 'list_repos'"));
+    }
+
+    #[tokio::test]
+    async fn test_renamed_transport() {
+        let transport = RenamedTransport::new(
+            Named(vec!["createIssue", "searchCode"]),
+            ToolNameRules::new()
+                .with_snake_case()
+                .with_rename("searchCode", "find"),
+        );
+
+        // Calls before any listing learn the names themselves
+        let result = transport
+            .call(&ToolCall::new("create_issue"))
+            .await
+            .unwrap();
+        assert_eq!(result.data, Some(json!("createIssue")));
+
+        let names: Vec<_> = transport
+            .list_tools()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["create_issue", "find"]);
+        let result = transport.call(&ToolCall::new("find")).await.unwrap();
+        assert_eq!(result.data, Some(json!("searchCode")));
+
+        let err = transport
+            .call(&ToolCall::new("searchCode"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ToolNotFound(name) if name == "searchCode"));
+    }
+}
@@ -1,6 +1,8 @@
 //! Parameter types for tool definitions.
 
+use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// The type of a parameter.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -73,6 +75,22 @@ pub struct Parameter {
     /// Enum of allowed values.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub enum_values: Vec<serde_json::Value>,
+
+    /// Example values, used when rendering usage documentation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<serde_json::Value>,
+
+    /// Limits on the value beyond its type.
+    #[serde(flatten)]
+    pub constraints: ParameterConstraints,
+
+    /// Members of an object parameter; other members are allowed unchecked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<Parameter>,
+
+    /// Schema every item of an array parameter must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Parameter>>,
 }
 
 impl Parameter {
@@ -85,9 +103,18 @@ impl Parameter {
             description: String::new(),
             default: None,
             enum_values: Vec::new(),
+            examples: Vec::new(),
+            constraints: ParameterConstraints::default(),
+            properties: Vec::new(),
+            items: None,
         }
     }
 
+    /// Get a member of an object parameter by name.
+    pub fn get_property(&self, name: &str) -> Option<&Parameter> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
     /// Create a builder for a parameter.
     pub fn builder(name: impl Into<String>) -> ParameterBuilder {
         ParameterBuilder::new(name)
@@ -102,6 +129,10 @@ impl Parameter {
             description: String::new(),
             default: None,
             enum_values: Vec::new(),
+            examples: Vec::new(),
+            constraints: ParameterConstraints::default(),
+            properties: Vec::new(),
+            items: None,
         }
     }
 
@@ -114,7 +145,166 @@ impl Parameter {
             description: String::new(),
             default: None,
             enum_values: Vec::new(),
+            examples: Vec::new(),
+            constraints: ParameterConstraints::default(),
+            properties: Vec::new(),
+            items: None,
+        }
+    }
+}
+
+/// Limits on a parameter value beyond its type, mirroring the JSON Schema
+/// keywords of the same names.
+///
+/// Each limit applies only to values of the matching JSON type, so a
+/// `max_length` on a parameter that also accepts numbers leaves numbers
+/// alone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterConstraints {
+    /// Smallest allowed number, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+
+    /// Largest allowed number, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+
+    /// Fewest characters allowed in a string.
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "minLength")]
+    pub min_length: Option<usize>,
+
+    /// Most characters allowed in a string.
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "maxLength")]
+    pub max_length: Option<usize>,
+
+    /// Regular expression a string must match somewhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// Fewest items allowed in an array.
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "minItems")]
+    pub min_items: Option<usize>,
+
+    /// Most items allowed in an array.
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "maxItems")]
+    pub max_items: Option<usize>,
+}
+
+impl ParameterConstraints {
+    /// Check if no constraint is set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Read the constraint keywords of a JSON Schema property.
+    pub fn from_schema(schema: &serde_json::Map<String, Value>) -> Self {
+        let number = |key: &str| schema.get(key).and_then(Value::as_f64);
+        let count = |key: &str| schema.get(key).and_then(Value::as_u64).map(|n| n as usize);
+        Self {
+            minimum: number("minimum"),
+            maximum: number("maximum"),
+            min_length: count("minLength"),
+            max_length: count("maxLength"),
+            pattern: schema
+                .get("pattern")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            min_items: count("minItems"),
+            max_items: count("maxItems"),
+        }
+    }
+
+    /// Write the constraints as JSON Schema keywords into a property.
+    pub fn write_schema(&self, schema: &mut serde_json::Map<String, Value>) {
+        let numbers = [("minimum", self.minimum), ("maximum", self.maximum)];
+        for (key, value) in numbers {
+            if let Some(value) = value {
+                schema.insert(key.to_string(), Value::from(value));
+            }
+        }
+        let counts = [
+            ("minLength", self.min_length),
+            ("maxLength", self.max_length),
+            ("minItems", self.min_items),
+            ("maxItems", self.max_items),
+        ];
+        for (key, value) in counts {
+            if let Some(value) = value {
+                schema.insert(key.to_string(), Value::from(value));
+            }
+        }
+        if let Some(pattern) = &self.pattern {
+            schema.insert("pattern".to_string(), Value::String(pattern.clone()));
+        }
+    }
+
+    /// Check a value of parameter `name` against the constraints.
+    ///
+    /// Returns [`Error::ConstraintViolation`] naming the first limit the
+    /// value breaks, or [`Error::InvalidConfig`] if the pattern isn't a
+    /// valid regular expression.
+    pub fn check(&self, name: &str, value: &Value) -> Result<()> {
+        let violation = |reason: String| {
+            Err(Error::ConstraintViolation {
+                name: name.to_string(),
+                reason,
+            })
+        };
+        match value {
+            Value::Number(number) => {
+                let n = number.as_f64().unwrap_or_default();
+                if let Some(minimum) = self.minimum.filter(|&minimum| n < minimum) {
+                    return violation(format!("must be at least {}, got {}", minimum, number));
+                }
+                if let Some(maximum) = self.maximum.filter(|&maximum| n > maximum) {
+                    return violation(format!("must be at most {}, got {}", maximum, number));
+                }
+            }
+            Value::String(s) => {
+                let length = s.chars().count();
+                if let Some(min) = self.min_length.filter(|&min| length < min) {
+                    return violation(format!(
+                        "must be at least {} characters long, got {}",
+                        min, length
+                    ));
+                }
+                if let Some(max) = self.max_length.filter(|&max| length > max) {
+                    return violation(format!(
+                        "must be at most {} characters long, got {}",
+                        max, length
+                    ));
+                }
+                if let Some(pattern) = &self.pattern {
+                    let regex = regex::Regex::new(pattern).map_err(|e| {
+                        Error::InvalidConfig(format!(
+                            "parameter '{}' has an invalid pattern: {}",
+                            name, e
+                        ))
+                    })?;
+                    if !regex.is_match(s) {
+                        return violation(format!("must match pattern '{}'", pattern));
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min) = self.min_items.filter(|&min| items.len() < min) {
+                    return violation(format!(
+                        "must have at least {} items, got {}",
+                        min,
+                        items.len()
+                    ));
+                }
+                if let Some(max) = self.max_items.filter(|&max| items.len() > max) {
+                    return violation(format!(
+                        "must have at most {} items, got {}",
+                        max,
+                        items.len()
+                    ));
+                }
+            }
+            _ => {}
         }
+        Ok(())
     }
 }
 
@@ -127,6 +317,10 @@ pub struct ParameterBuilder {
     description: String,
     default: Option<serde_json::Value>,
     enum_values: Vec<serde_json::Value>,
+    examples: Vec<serde_json::Value>,
+    constraints: ParameterConstraints,
+    properties: Vec<Parameter>,
+    items: Option<Box<Parameter>>,
 }
 
 impl ParameterBuilder {
@@ -168,6 +362,78 @@ impl ParameterBuilder {
         self
     }
 
+    /// Add an example value.
+    pub fn example(mut self, value: serde_json::Value) -> Self {
+        self.examples.push(value);
+        self
+    }
+
+    /// Set the smallest allowed number.
+    pub fn minimum(mut self, minimum: f64) -> Self {
+        self.constraints.minimum = Some(minimum);
+        self
+    }
+
+    /// Set the largest allowed number.
+    pub fn maximum(mut self, maximum: f64) -> Self {
+        self.constraints.maximum = Some(maximum);
+        self
+    }
+
+    /// Set the fewest characters allowed in a string.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.constraints.min_length = Some(min_length);
+        self
+    }
+
+    /// Set the most characters allowed in a string.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.constraints.max_length = Some(max_length);
+        self
+    }
+
+    /// Set a regular expression strings must match.
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.constraints.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Set the fewest items allowed in an array.
+    pub fn min_items(mut self, min_items: usize) -> Self {
+        self.constraints.min_items = Some(min_items);
+        self
+    }
+
+    /// Set the most items allowed in an array.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.constraints.max_items = Some(max_items);
+        self
+    }
+
+    /// Replace all constraints.
+    pub fn constraints(mut self, constraints: ParameterConstraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Add a member to an object parameter.
+    pub fn property(mut self, property: Parameter) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    /// Add members to an object parameter.
+    pub fn properties(mut self, properties: impl IntoIterator<Item = Parameter>) -> Self {
+        self.properties.extend(properties);
+        self
+    }
+
+    /// Set the schema of an array parameter's items.
+    pub fn items(mut self, items: Parameter) -> Self {
+        self.items = Some(Box::new(items));
+        self
+    }
+
     /// Build the parameter.
     pub fn build(self) -> Parameter {
         Parameter {
@@ -177,6 +443,10 @@ impl ParameterBuilder {
             description: self.description,
             default: self.default,
             enum_values: self.enum_values,
+            examples: self.examples,
+            constraints: self.constraints,
+            properties: self.properties,
+            items: self.items,
         }
     }
 }
@@ -215,7 +485,7 @@ mod tests {
     fn parameter_type_matches_number() {
         assert!(ParameterType::Number.matches(&json!(123)));
         assert!(ParameterType::Number.matches(&json!(1.5)));
-        assert!(ParameterType::Number.matches(&json!(-3.14)));
+        assert!(ParameterType::Number.matches(&json!(-2.5)));
         assert!(!ParameterType::Number.matches(&json!("1.5")));
     }
 
@@ -327,13 +597,13 @@ mod tests {
         assert!(param_type.matches(&json!(42)));
         assert!(param_type.matches(&json!(-42)));
         assert!(param_type.matches(&json!(9223372036854775807i64)));
-        assert!(!param_type.matches(&json!(3.14)));
+        assert!(!param_type.matches(&json!(2.5)));
         assert!(!param_type.matches(&json!("42")));
 
         // Number should match any numeric value
         let param_type = ParameterType::Number;
         assert!(param_type.matches(&json!(42)));
-        assert!(param_type.matches(&json!(3.14)));
+        assert!(param_type.matches(&json!(2.5)));
         assert!(param_type.matches(&json!(-42)));
         assert!(!param_type.matches(&json!("42")));
 
@@ -359,6 +629,23 @@ mod tests {
         assert!(!param_type.matches(&json!("{}")));
     }
 
+    #[test]
+    fn parameter_builder_with_examples() {
+        let param = Parameter::builder("city")
+            .example(json!("Berlin"))
+            .example(json!("Tokyo"))
+            .build();
+
+        assert_eq!(param.examples, vec![json!("Berlin"), json!("Tokyo")]);
+
+        let value = serde_json::to_value(&param).unwrap();
+        assert_eq!(value["examples"], json!(["Berlin", "Tokyo"]));
+        assert!(serde_json::to_value(Parameter::new("x"))
+            .unwrap()
+            .get("examples")
+            .is_none());
+    }
+
     #[test]
     fn parameter_enum_validation() {
         let param = Parameter::builder("status")
@@ -411,4 +698,65 @@ mod tests {
         assert_eq!(original.description, deserialized.description);
         assert_eq!(original.default, deserialized.default);
     }
+
+    #[test]
+    fn parameter_constraints_check() {
+        let param = Parameter::builder("age")
+            .param_type(ParameterType::Integer)
+            .minimum(0.0)
+            .maximum(120.0)
+            .build();
+        assert!(param.constraints.check("age", &json!(30)).is_ok());
+        let err = param.constraints.check("age", &json!(150)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parameter 'age' must be at most 120, got 150"
+        );
+
+        let code = Parameter::builder("code")
+            .min_length(2)
+            .max_length(4)
+            .pattern("^[A-Z]+$")
+            .build()
+            .constraints;
+        assert!(code.check("code", &json!("ÄBC")).is_err());
+        assert!(code.check("code", &json!("ABC")).is_ok());
+        assert_eq!(
+            code.check("code", &json!("A")).unwrap_err().to_string(),
+            "parameter 'code' must be at least 2 characters long, got 1"
+        );
+        assert!(matches!(
+            code.check("code", &json!("abc")),
+            Err(Error::ConstraintViolation { .. })
+        ));
+
+        let tags = Parameter::builder("tags").max_items(2).build().constraints;
+        assert!(tags.check("tags", &json!(["a", "b", "c"])).is_err());
+        // Limits only apply to values of their type
+        assert!(tags.check("tags", &json!("abc")).is_ok());
+
+        let invalid = Parameter::builder("x").pattern("(").build().constraints;
+        assert!(matches!(
+            invalid.check("x", &json!("y")),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn parameter_constraints_serialization() {
+        let param = Parameter::builder("limit")
+            .param_type(ParameterType::Integer)
+            .minimum(1.0)
+            .build();
+        let value = serde_json::to_value(&param).unwrap();
+        assert_eq!(value["minimum"], json!(1.0));
+        assert!(value.get("max_length").is_none());
+        assert_eq!(serde_json::from_value::<Parameter>(value).unwrap(), param);
+
+        let parsed: Parameter =
+            serde_json::from_value(json!({"name": "q", "minLength": 3, "max_length": 5})).unwrap();
+        assert_eq!(parsed.constraints.min_length, Some(3));
+        assert_eq!(parsed.constraints.max_length, Some(5));
+        assert!(Parameter::new("q").constraints.is_empty());
+    }
 }
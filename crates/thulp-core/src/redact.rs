@@ -0,0 +1,370 @@
+//! Path- and value-based redaction of sensitive values.
+
+use crate::Redactor;
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+
+/// Default replacement for redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Masks values whose path matches one of a set of glob patterns.
+///
+/// Patterns are dot-separated paths matched case-insensitively against object
+/// keys and array indices:
+///
+/// - `headers.Authorization` matches exactly that path
+/// - `*` matches any single segment (`*.password` matches `db.password`)
+/// - `**` matches any number of segments (`**.token` matches `token`,
+///   `auth.token` and `items.0.token`)
+///
+/// A matched value is replaced as a whole, even if it is an object or array.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use thulp_core::{PathRedactor, Redactor};
+///
+/// let redactor = PathRedactor::new()
+///     .with_path("*.password")
+///     .with_path("headers.Authorization");
+///
+/// let redacted = redactor.redact(&json!({
+///     "db": {"user": "admin", "password": "hunter2"},
+///     "headers": {"Authorization": "Bearer abc"}
+/// }));
+///
+/// assert_eq!(redacted["db"]["user"], "admin");
+/// assert_eq!(redacted["db"]["password"], "[REDACTED]");
+/// assert_eq!(redacted["headers"]["Authorization"], "[REDACTED]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathRedactor {
+    patterns: Vec<Vec<String>>,
+    mask: String,
+}
+
+impl Default for PathRedactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathRedactor {
+    /// Create a redactor with no patterns.
+    pub fn new() -> Self {
+        Self {
+            patterns: Vec::new(),
+            mask: REDACTED.to_string(),
+        }
+    }
+
+    /// Create a redactor for commonly sensitive field names at any depth
+    /// (`password`, `secret`, `token`, `api_key`, `authorization`, ...).
+    pub fn sensitive_defaults() -> Self {
+        [
+            "password",
+            "passwd",
+            "secret",
+            "token",
+            "access_token",
+            "refresh_token",
+            "api_key",
+            "apikey",
+            "authorization",
+            "cookie",
+        ]
+        .into_iter()
+        .fold(Self::new(), |r, key| r.with_path(format!("**.{}", key)))
+    }
+
+    /// Add a path pattern to mask.
+    pub fn with_path(mut self, pattern: impl AsRef<str>) -> Self {
+        self.patterns.push(
+            pattern
+                .as_ref()
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+                .collect(),
+        );
+        self
+    }
+
+    /// Add several path patterns to mask.
+    pub fn with_paths<I, S>(self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        patterns.into_iter().fold(self, |r, p| r.with_path(p))
+    }
+
+    /// Set the replacement string (default: `[REDACTED]`).
+    pub fn with_mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Check whether no patterns are configured.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check whether a path matches any configured pattern.
+    pub fn matches(&self, path: &[&str]) -> bool {
+        let path: Vec<String> = path.iter().map(|s| s.to_lowercase()).collect();
+        self.matches_lower(&path)
+    }
+
+    fn matches_lower(&self, path: &[String]) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, path))
+    }
+
+    fn redact_at(&self, value: &Value, path: &mut Vec<String>) -> Value {
+        match value {
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(key, child)| {
+                        path.push(key.to_lowercase());
+                        let redacted = self.redact_child(child, path);
+                        path.pop();
+                        (key.clone(), redacted)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, child)| {
+                        path.push(index.to_string());
+                        let redacted = self.redact_child(child, path);
+                        path.pop();
+                        redacted
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn redact_child(&self, child: &Value, path: &mut Vec<String>) -> Value {
+        if self.matches_lower(path) {
+            Value::String(self.mask.clone())
+        } else {
+            self.redact_at(child, path)
+        }
+    }
+}
+
+impl Redactor for PathRedactor {
+    fn redact(&self, value: &Value) -> Value {
+        if self.patterns.is_empty() {
+            return value.clone();
+        }
+        self.redact_at(value, &mut Vec::new())
+    }
+}
+
+/// Masks known secret values wherever they appear in strings.
+///
+/// Where [`PathRedactor`] masks fields by name, this masks the values
+/// themselves, so a secret is hidden even when it ends up in an unexpected
+/// field or inside a longer string such as `"Bearer <token>"`. Clones share
+/// their secrets, so values added after a clone was handed out are masked
+/// by it too.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use thulp_core::{Redactor, SecretRedactor};
+///
+/// let redactor = SecretRedactor::new().with_secret("ghp_123");
+/// let redacted = redactor.redact(&json!({"headers": ["Authorization: Bearer ghp_123"]}));
+/// assert_eq!(redacted, json!({"headers": ["Authorization: Bearer [REDACTED]"]}));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SecretRedactor {
+    secrets: Arc<RwLock<Vec<String>>>,
+}
+
+impl SecretRedactor {
+    /// Create a redactor with no secrets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a secret value to mask.
+    pub fn with_secret(self, secret: impl Into<String>) -> Self {
+        self.add(secret);
+        self
+    }
+
+    /// Add a secret value to mask, for this redactor and its clones.
+    ///
+    /// Empty values are ignored.
+    pub fn add(&self, secret: impl Into<String>) {
+        let secret = secret.into();
+        if secret.is_empty() {
+            return;
+        }
+        let mut secrets = self.secrets.write().unwrap();
+        if !secrets.contains(&secret) {
+            secrets.push(secret);
+            // Longest first, so a secret containing another is masked whole
+            secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        }
+    }
+
+    /// Number of secret values masked.
+    pub fn len(&self) -> usize {
+        self.secrets.read().unwrap().len()
+    }
+
+    /// Check whether no secrets are known.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Mask every known secret in a string.
+    pub fn redact_str(&self, text: &str) -> String {
+        let secrets = self.secrets.read().unwrap();
+        secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret.as_str(), REDACTED)
+        })
+    }
+
+    fn redact_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact_str(s)),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(key, child)| (key.clone(), self.redact_value(child)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+impl Redactor for SecretRedactor {
+    fn redact(&self, value: &Value) -> Value {
+        if self.is_empty() {
+            return value.clone();
+        }
+        self.redact_value(value)
+    }
+}
+
+/// Match a lowercase path against a lowercase glob pattern.
+fn glob_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((head, rest)) if head == "**" => {
+            (0..=path.len()).any(|skip| glob_match(rest, &path[skip..]))
+        }
+        Some((head, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                (head == "*" || head == segment) && glob_match(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_exact_and_single_wildcard() {
+        let redactor = PathRedactor::new()
+            .with_path("*.password")
+            .with_path("headers.authorization");
+
+        let input = json!({
+            "password": "top-level",
+            "db": {"password": "hunter2", "host": "localhost"},
+            "nested": {"db": {"password": "deep"}},
+            "headers": {"Authorization": "Bearer abc", "Accept": "*/*"}
+        });
+        let out = redactor.redact(&input);
+
+        assert_eq!(out["password"], "top-level");
+        assert_eq!(out["db"]["password"], REDACTED);
+        assert_eq!(out["db"]["host"], "localhost");
+        assert_eq!(out["nested"]["db"]["password"], "deep");
+        assert_eq!(out["headers"]["Authorization"], REDACTED);
+        assert_eq!(out["headers"]["Accept"], "*/*");
+    }
+
+    #[test]
+    fn test_double_wildcard_and_arrays() {
+        let redactor = PathRedactor::new().with_path("**.token");
+
+        let out = redactor.redact(&json!({
+            "token": "a",
+            "auth": {"token": "b"},
+            "items": [{"token": "c", "id": 1}],
+            "credentials": {"token": {"value": "d"}}
+        }));
+
+        assert_eq!(out["token"], REDACTED);
+        assert_eq!(out["auth"]["token"], REDACTED);
+        assert_eq!(out["items"][0]["token"], REDACTED);
+        assert_eq!(out["items"][0]["id"], 1);
+        assert_eq!(out["credentials"]["token"], REDACTED);
+    }
+
+    #[test]
+    fn test_sensitive_defaults_and_mask() {
+        let redactor = PathRedactor::sensitive_defaults().with_mask("***");
+        let out = redactor.redact(&json!({
+            "query": "rust",
+            "api_key": "k",
+            "request": {"headers": {"Cookie": "session=1"}}
+        }));
+
+        assert_eq!(out["query"], "rust");
+        assert_eq!(out["api_key"], "***");
+        assert_eq!(out["request"]["headers"]["Cookie"], "***");
+    }
+
+    #[test]
+    fn test_no_patterns_is_identity() {
+        let input = json!({"password": "x", "list": [1, 2]});
+        assert!(PathRedactor::new().is_empty());
+        assert_eq!(PathRedactor::new().redact(&input), input);
+        assert_eq!(PathRedactor::new().redact(&json!("plain")), json!("plain"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let redactor = PathRedactor::new().with_path("servers.*.headers.**");
+        assert!(redactor.matches(&["servers", "github", "headers", "X-Key"]));
+        assert!(!redactor.matches(&["servers", "github", "url"]));
+    }
+
+    #[test]
+    fn test_secret_redactor() {
+        let redactor = SecretRedactor::new().with_secret("abc");
+        let shared = redactor.clone();
+        redactor.add("abcdef");
+        redactor.add("");
+
+        assert_eq!(shared.len(), 2);
+        assert_eq!(
+            shared.redact(&json!({"url": "https://x?k=abcdef", "tokens": ["abc", 1]})),
+            json!({"url": "https://x?k=[REDACTED]", "tokens": ["[REDACTED]", 1]})
+        );
+        assert_eq!(SecretRedactor::new().redact(&json!("abc")), json!("abc"));
+    }
+}
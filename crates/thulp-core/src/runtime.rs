@@ -0,0 +1,300 @@
+//! Ownership and graceful shutdown of background tasks.
+//!
+//! Long-lived components (file watchers, schedulers, MCP keep-alives) run as
+//! background tasks. Spawning them through a [`ThulpRuntime`] ties their
+//! lifetime to the runtime handle: every task receives a [`ShutdownSignal`],
+//! [`ThulpRuntime::shutdown`] asks all tasks to stop and waits for them up to a
+//! deadline, and tasks still running after the deadline are aborted. Dropping
+//! the last handle aborts anything left, so no task outlives its owner.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use thulp_core::ThulpRuntime;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> thulp_core::Result<()> {
+//! let runtime = ThulpRuntime::new();
+//!
+//! runtime.spawn("keep-alive", |mut shutdown| async move {
+//!     loop {
+//!         tokio::select! {
+//!             _ = shutdown.wait() => break,
+//!             _ = tokio::time::sleep(Duration::from_secs(30)) => { /* ping */ }
+//!         }
+//!     }
+//! })?;
+//!
+//! let report = runtime.shutdown(Duration::from_secs(5)).await;
+//! assert_eq!(report.completed, vec!["keep-alive".to_string()]);
+//! assert!(report.is_clean());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, Result};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Handle that owns background tasks and shuts them down together.
+///
+/// Cloning the handle is cheap; all clones share the same set of tasks.
+#[derive(Clone)]
+pub struct ThulpRuntime {
+    inner: Arc<RuntimeInner>,
+}
+
+struct RuntimeInner {
+    /// Running tasks with their names
+    tasks: Mutex<Vec<(String, JoinHandle<()>)>>,
+    /// Set to `true` once shutdown starts
+    shutdown_tx: watch::Sender<bool>,
+}
+
+impl Drop for RuntimeInner {
+    fn drop(&mut self) {
+        if let Ok(tasks) = self.tasks.get_mut() {
+            for (_, handle) in tasks.drain(..) {
+                handle.abort();
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ThulpRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThulpRuntime")
+            .field("tasks", &self.task_names())
+            .field("shutting_down", &self.is_shutting_down())
+            .finish()
+    }
+}
+
+impl Default for ThulpRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThulpRuntime {
+    /// Create a runtime with no tasks.
+    pub fn new() -> Self {
+        let (shutdown_tx, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(RuntimeInner {
+                tasks: Mutex::new(Vec::new()),
+                shutdown_tx,
+            }),
+        }
+    }
+
+    /// Spawn a named background task.
+    ///
+    /// The closure receives a [`ShutdownSignal`] that completes when
+    /// [`shutdown`](Self::shutdown) is called; tasks should stop promptly
+    /// once it fires. Must be called from within a tokio runtime.
+    ///
+    /// Returns an error if the runtime is already shutting down.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, task: F) -> Result<()>
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let mut tasks = self.inner.tasks.lock().unwrap();
+        if self.is_shutting_down() {
+            return Err(Error::ExecutionFailed(format!(
+                "cannot spawn '{}': runtime is shutting down",
+                name
+            )));
+        }
+
+        tasks.retain(|(_, handle)| !handle.is_finished());
+        let handle = tokio::spawn(task(self.signal()));
+        tasks.push((name, handle));
+        Ok(())
+    }
+
+    /// Get a signal that completes when shutdown starts.
+    ///
+    /// Useful for components that manage their own tasks but should stop
+    /// together with the runtime.
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            rx: self.inner.shutdown_tx.subscribe(),
+        }
+    }
+
+    /// Check whether shutdown has started.
+    pub fn is_shutting_down(&self) -> bool {
+        *self.inner.shutdown_tx.borrow()
+    }
+
+    /// Number of tasks still running.
+    pub fn task_count(&self) -> usize {
+        let mut tasks = self.inner.tasks.lock().unwrap();
+        tasks.retain(|(_, handle)| !handle.is_finished());
+        tasks.len()
+    }
+
+    /// Names of tasks still running.
+    pub fn task_names(&self) -> Vec<String> {
+        let mut tasks = self.inner.tasks.lock().unwrap();
+        tasks.retain(|(_, handle)| !handle.is_finished());
+        tasks.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Signal all tasks to stop and wait for them, up to `deadline` in total.
+    ///
+    /// Tasks that haven't finished when the deadline passes are aborted.
+    /// Further calls to [`spawn`](Self::spawn) fail once shutdown has started.
+    pub async fn shutdown(&self, deadline: Duration) -> ShutdownReport {
+        let tasks = {
+            let mut tasks = self.inner.tasks.lock().unwrap();
+            self.inner.shutdown_tx.send_replace(true);
+            std::mem::take(&mut *tasks)
+        };
+
+        let deadline = tokio::time::Instant::now() + deadline;
+        let mut report = ShutdownReport::default();
+
+        for (name, mut handle) in tasks {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(())) => report.completed.push(name),
+                Ok(Err(e)) if e.is_panic() => report.panicked.push(name),
+                Ok(Err(_)) => report.aborted.push(name),
+                Err(_) => {
+                    handle.abort();
+                    let _ = handle.await;
+                    report.aborted.push(name);
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Completes when the owning [`ThulpRuntime`] starts shutting down.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    rx: watch::Receiver<bool>,
+}
+
+impl ShutdownSignal {
+    /// Check whether shutdown has started.
+    pub fn is_shutdown(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Wait until shutdown starts (or the runtime is dropped).
+    pub async fn wait(&mut self) {
+        let _ = self.rx.wait_for(|shutdown| *shutdown).await;
+    }
+}
+
+/// Outcome of [`ThulpRuntime::shutdown`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Tasks that finished before the deadline
+    pub completed: Vec<String>,
+    /// Tasks aborted because they outlived the deadline
+    pub aborted: Vec<String>,
+    /// Tasks that panicked
+    pub panicked: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Whether every task finished on its own.
+    pub fn is_clean(&self) -> bool {
+        self.aborted.is_empty() && self.panicked.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_graceful_shutdown() {
+        let runtime = ThulpRuntime::new();
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+
+        let flag = cleaned_up.clone();
+        runtime
+            .spawn("watcher", |mut shutdown| async move {
+                shutdown.wait().await;
+                flag.store(true, Ordering::SeqCst);
+            })
+            .unwrap();
+        runtime.spawn("oneshot", |_| async {}).unwrap();
+
+        tokio::task::yield_now().await;
+        assert_eq!(runtime.task_names(), vec!["watcher".to_string()]);
+
+        let report = runtime.shutdown(Duration::from_secs(1)).await;
+        assert!(report.is_clean());
+        assert_eq!(report.completed, vec!["watcher".to_string()]);
+        assert!(cleaned_up.load(Ordering::SeqCst));
+        assert_eq!(runtime.task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_aborts_stuck_tasks() {
+        let runtime = ThulpRuntime::new();
+        runtime
+            .spawn("stuck", |_| async {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            })
+            .unwrap();
+        runtime
+            .spawn("panics", |mut shutdown| async move {
+                shutdown.wait().await;
+                panic!("boom");
+            })
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let report = runtime.shutdown(Duration::from_millis(50)).await;
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(report.aborted, vec!["stuck".to_string()]);
+        assert_eq!(report.panicked, vec!["panics".to_string()]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_after_shutdown_fails() {
+        let runtime = ThulpRuntime::new();
+        let signal = runtime.signal();
+        assert!(!signal.is_shutdown());
+
+        runtime.shutdown(Duration::from_millis(10)).await;
+        assert!(signal.is_shutdown());
+        assert!(runtime.is_shutting_down());
+        assert!(runtime.spawn("late", |_| async {}).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_aborts_tasks() {
+        let runtime = ThulpRuntime::new();
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        runtime
+            .spawn("holder", |_| async move {
+                let _tx = tx;
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+            })
+            .unwrap();
+
+        drop(runtime);
+        // The sender is dropped when the aborted task is torn down
+        assert!(tokio::time::timeout(Duration::from_secs(1), rx)
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,160 @@
+//! Streaming tool results.
+//!
+//! Long-running tools (log tails, LLM generations) can return their output
+//! incrementally through [`Transport::call_streaming`](crate::Transport::call_streaming)
+//! as a [`ToolResultStream`] of partial [`ToolResult`]s. Consumers that only
+//! need the final value can fold the stream back into a single result with
+//! [`collect_stream`].
+
+use crate::{Result, ToolResult};
+use futures::stream::{Stream, StreamExt};
+use serde_json::Value;
+use std::pin::Pin;
+
+/// Stream of partial tool results.
+///
+/// Each item is one chunk of output. A failed chunk ends the call: consumers
+/// should stop reading after it.
+pub type ToolResultStream = Pin<Box<dyn Stream<Item = Result<ToolResult>> + Send>>;
+
+/// Wrap a complete result as a single-chunk stream.
+pub fn single_chunk(result: ToolResult) -> ToolResultStream {
+    Box::pin(futures::stream::once(async move { Ok(result) }))
+}
+
+/// Drain a stream, merging its chunks into one result.
+///
+/// Chunk data is merged as follows:
+/// - a single chunk is returned as is
+/// - string chunks are concatenated
+/// - array chunks are flattened into one array
+/// - any other mix becomes an array of the chunk values
+///
+/// The first failed chunk is returned as the result. The reported duration
+/// is the largest duration of any chunk.
+pub async fn collect_stream<S>(mut stream: S) -> Result<ToolResult>
+where
+    S: Stream<Item = Result<ToolResult>> + Unpin,
+{
+    let mut chunks: Vec<Value> = Vec::new();
+    let mut duration_ms: Option<u64> = None;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        duration_ms = duration_ms.max(chunk.duration_ms);
+        if !chunk.is_success() {
+            return Ok(ToolResult {
+                duration_ms,
+                ..chunk
+            });
+        }
+        if let Some(data) = chunk.data {
+            chunks.push(data);
+        }
+    }
+
+    let mut result = ToolResult::success(merge_chunks(chunks));
+    result.duration_ms = duration_ms;
+    Ok(result)
+}
+
+/// Merge chunk values into one value
+fn merge_chunks(mut chunks: Vec<Value>) -> Value {
+    if chunks.len() == 1 {
+        return chunks.remove(0);
+    }
+    if chunks.is_empty() {
+        return Value::Null;
+    }
+
+    if chunks.iter().all(Value::is_string) {
+        let text: String = chunks
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .concat();
+        return Value::String(text);
+    }
+    if chunks.iter().all(Value::is_array) {
+        return Value::Array(
+            chunks
+                .into_iter()
+                .flat_map(|chunk| match chunk {
+                    Value::Array(items) => items,
+                    _ => Vec::new(),
+                })
+                .collect(),
+        );
+    }
+    Value::Array(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use serde_json::json;
+
+    fn stream_of(items: Vec<Result<ToolResult>>) -> ToolResultStream {
+        Box::pin(futures::stream::iter(items))
+    }
+
+    #[tokio::test]
+    async fn test_collect_strings_and_arrays() {
+        let text = collect_stream(stream_of(vec![
+            Ok(ToolResult::success(json!("hello "))),
+            Ok(ToolResult::success(json!("world")).with_duration(12)),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(text.data, Some(json!("hello world")));
+        assert_eq!(text.duration_ms, Some(12));
+
+        let lines = collect_stream(stream_of(vec![
+            Ok(ToolResult::success(json!(["a", "b"]))),
+            Ok(ToolResult::success(json!(["c"]))),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(lines.data, Some(json!(["a", "b", "c"])));
+
+        let mixed = collect_stream(stream_of(vec![
+            Ok(ToolResult::success(json!({"progress": 50}))),
+            Ok(ToolResult::success(json!("done"))),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(mixed.data, Some(json!([{"progress": 50}, "done"])));
+    }
+
+    #[tokio::test]
+    async fn test_collect_single_and_empty() {
+        let single = collect_stream(single_chunk(ToolResult::success(json!({"ok": true}))))
+            .await
+            .unwrap();
+        assert_eq!(single.data, Some(json!({"ok": true})));
+
+        let empty = collect_stream(stream_of(Vec::new())).await.unwrap();
+        assert!(empty.is_success());
+        assert_eq!(empty.data, Some(Value::Null));
+    }
+
+    #[tokio::test]
+    async fn test_collect_stops_at_failure() {
+        let result = collect_stream(stream_of(vec![
+            Ok(ToolResult::success(json!("partial"))),
+            Ok(ToolResult::failure("tail lost")),
+            Ok(ToolResult::success(json!("ignored"))),
+        ]))
+        .await
+        .unwrap();
+        assert!(!result.is_success());
+        assert_eq!(result.error.as_deref(), Some("tail lost"));
+
+        let err = collect_stream(stream_of(vec![Err(Error::ExecutionFailed(
+            "connection reset".to_string(),
+        ))]))
+        .await;
+        assert!(err.is_err());
+    }
+}
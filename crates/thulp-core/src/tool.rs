@@ -1,6 +1,6 @@
 //! Tool types for thulp.
 
-use crate::{Error, Parameter, ParameterType, Result};
+use crate::{Error, Parameter, ParameterConstraints, ParameterType, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -17,6 +17,12 @@ pub struct ToolDefinition {
     /// Parameters accepted by the tool.
     #[serde(default)]
     pub parameters: Vec<Parameter>,
+
+    /// Whether the tool may modify or delete data.
+    ///
+    /// Destructive tools are refused when executing in read-only mode.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub destructive: bool,
 }
 
 impl ToolDefinition {
@@ -26,6 +32,7 @@ impl ToolDefinition {
             name: name.into(),
             description: String::new(),
             parameters: Vec::new(),
+            destructive: false,
         }
     }
 
@@ -62,124 +69,362 @@ impl ToolDefinition {
         // Check parameter types
         for (key, value) in args_obj {
             if let Some(param) = self.get_parameter(key) {
-                if !param.param_type.matches(value) {
-                    return Err(Error::InvalidParameterType {
-                        name: key.clone(),
-                        expected: param.param_type.as_str().to_string(),
-                        actual: json_type_name(value).to_string(),
-                    });
-                }
-
-                // Check enum values if defined
-                if !param.enum_values.is_empty() && !param.enum_values.contains(value) {
-                    return Err(Error::InvalidConfig(format!(
-                        "parameter '{}' must be one of: {:?}",
-                        key, param.enum_values
-                    )));
-                }
+                check_value(param, key, value)?;
             }
         }
 
         Ok(())
     }
 
+    /// Coerce and complete arguments, then validate them.
+    ///
+    /// An opt-in, more lenient alternative to
+    /// [`validate_args`](Self::validate_args) for arguments written by a
+    /// model. Values whose JSON type doesn't match their parameter are
+    /// converted where there is only one sensible reading: `"5"` to `5` for
+    /// numbers, `"true"`/`"false"` to booleans, a whole number such as
+    /// `5.0` to an integer, and JSON text to an array or object. Missing
+    /// parameters with a default get it filled in. Nested object members
+    /// and array items are handled the same way.
+    pub fn normalize_args(&self, args: &Value) -> Result<NormalizedArgs> {
+        let mut arguments = args.as_object().cloned().unwrap_or_default();
+        let mut coercions = Vec::new();
+        let mut defaults = Vec::new();
+        normalize_members(
+            &self.parameters,
+            "",
+            &mut arguments,
+            &mut coercions,
+            &mut defaults,
+        );
+
+        let arguments = Value::Object(arguments);
+        self.validate_args(&arguments)?;
+        Ok(NormalizedArgs {
+            arguments,
+            coercions,
+            defaults,
+        })
+    }
+
     /// Convert this tool definition into an MCP-compatible JSON Schema `Value`
     /// suitable for the `tools[].function.parameters` field that
     /// OpenAI-compatible LLM APIs expect.
     ///
     /// Inverse of `parse_mcp_input_schema`. Round-trip is structurally stable
-    /// for `name`, `param_type`, `required`, `description`, `default`, and
-    /// `enum_values`. Round-trip is exact when no extra schema fields are
-    /// present.
+    /// for `name`, `param_type`, `required`, `description`, `default`,
+    /// `enum_values`, `examples`, `constraints`, and nested `properties` and
+    /// `items`. Round-trip is exact when no extra schema fields are present.
     pub fn to_mcp_input_schema(&self) -> serde_json::Value {
-        let mut properties = serde_json::Map::new();
-        let mut required: Vec<serde_json::Value> = Vec::new();
-
-        for param in &self.parameters {
-            let mut prop = serde_json::Map::new();
-            prop.insert(
-                "type".to_string(),
-                serde_json::Value::String(param.param_type.as_str().to_string()),
-            );
-            if !param.description.is_empty() {
-                prop.insert(
-                    "description".to_string(),
-                    serde_json::Value::String(param.description.clone()),
-                );
-            }
-            if !param.enum_values.is_empty() {
-                prop.insert(
-                    "enum".to_string(),
-                    serde_json::Value::Array(param.enum_values.clone()),
-                );
+        let mut schema = serde_json::Map::new();
+        schema.insert(
+            "type".to_string(),
+            serde_json::Value::String("object".to_string()),
+        );
+        write_properties(&self.parameters, &mut schema);
+        serde_json::Value::Object(schema)
+    }
+
+    /// Parse MCP inputSchema into Parameters
+    pub fn parse_mcp_input_schema(schema: &serde_json::Value) -> Result<Vec<Parameter>> {
+        Ok(parse_properties(schema))
+    }
+}
+
+/// Check one argument against its parameter, descending into object
+/// members and array items; `path` names the value in errors, e.g.
+/// `config.hosts[2]`.
+fn check_value(param: &Parameter, path: &str, value: &Value) -> Result<()> {
+    if !param.param_type.matches(value) {
+        return Err(Error::InvalidParameterType {
+            name: path.to_string(),
+            expected: param.param_type.as_str().to_string(),
+            actual: json_type_name(value).to_string(),
+        });
+    }
+
+    // Check enum values if defined
+    if !param.enum_values.is_empty() && !param.enum_values.contains(value) {
+        return Err(Error::InvalidConfig(format!(
+            "parameter '{}' must be one of: {:?}",
+            path, param.enum_values
+        )));
+    }
+
+    param.constraints.check(path, value)?;
+
+    match value {
+        Value::Object(members) => {
+            for property in &param.properties {
+                let member_path = format!("{}.{}", path, property.name);
+                match members.get(&property.name) {
+                    Some(member) => check_value(property, &member_path, member)?,
+                    None if property.required && property.default.is_none() => {
+                        return Err(Error::MissingParameter(member_path));
+                    }
+                    None => {}
+                }
             }
-            if let Some(default) = &param.default {
-                prop.insert("default".to_string(), default.clone());
+        }
+        Value::Array(values) => {
+            if let Some(items) = &param.items {
+                for (index, item) in values.iter().enumerate() {
+                    check_value(items, &format!("{}[{}]", path, index), item)?;
+                }
             }
-            properties.insert(param.name.clone(), serde_json::Value::Object(prop));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Arguments returned by [`ToolDefinition::normalize_args`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedArgs {
+    /// The arguments with values coerced and defaults filled in.
+    pub arguments: Value,
+
+    /// Values converted to their parameter's type.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coercions: Vec<Coercion>,
 
-            if param.required {
-                required.push(serde_json::Value::String(param.name.clone()));
+    /// Paths of parameters that were filled from their defaults.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defaults: Vec<String>,
+}
+
+impl NormalizedArgs {
+    /// Whether the arguments were returned unchanged.
+    pub fn is_unchanged(&self) -> bool {
+        self.coercions.is_empty() && self.defaults.is_empty()
+    }
+}
+
+/// A value converted to its parameter's type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coercion {
+    /// Path of the value, e.g. `limit` or `filter.tags[0]`.
+    pub path: String,
+
+    /// The value as given.
+    pub from: Value,
+
+    /// The value it was converted to.
+    pub to: Value,
+}
+
+/// Coerce the members of an object and fill in their defaults.
+fn normalize_members(
+    params: &[Parameter],
+    prefix: &str,
+    members: &mut serde_json::Map<String, Value>,
+    coercions: &mut Vec<Coercion>,
+    defaults: &mut Vec<String>,
+) {
+    for param in params {
+        let path = if prefix.is_empty() {
+            param.name.clone()
+        } else {
+            format!("{}.{}", prefix, param.name)
+        };
+        match members.get_mut(&param.name) {
+            Some(value) => normalize_value(param, &path, value, coercions, defaults),
+            None => {
+                if let Some(default) = &param.default {
+                    members.insert(param.name.clone(), default.clone());
+                    defaults.push(path);
+                }
             }
         }
+    }
+}
 
-        serde_json::json!({
-            "type": "object",
-            "properties": properties,
-            "required": required,
-        })
+/// Coerce a value to its parameter's type, then descend into it.
+fn normalize_value(
+    param: &Parameter,
+    path: &str,
+    value: &mut Value,
+    coercions: &mut Vec<Coercion>,
+    defaults: &mut Vec<String>,
+) {
+    if let Some(coerced) = coerce(param.param_type, value) {
+        let from = std::mem::replace(value, coerced.clone());
+        coercions.push(Coercion {
+            path: path.to_string(),
+            from,
+            to: coerced,
+        });
     }
 
-    /// Parse MCP inputSchema into Parameters
-    pub fn parse_mcp_input_schema(schema: &serde_json::Value) -> Result<Vec<Parameter>> {
-        let mut params = Vec::new();
-
-        if let Some(properties) = schema.get("properties") {
-            if let Some(props_obj) = properties.as_object() {
-                for (name, prop) in props_obj {
-                    let param_type = if let Some(type_val) = prop.get("type") {
-                        match type_val.as_str() {
-                            Some("string") => ParameterType::String,
-                            Some("integer") => ParameterType::Integer,
-                            Some("number") => ParameterType::Number,
-                            Some("boolean") => ParameterType::Boolean,
-                            Some("array") => ParameterType::Array,
-                            Some("object") => ParameterType::Object,
-                            _ => ParameterType::String,
-                        }
-                    } else {
-                        ParameterType::String
-                    };
-
-                    let description = prop
-                        .get("description")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    let required = if let Some(req_array) = schema.get("required") {
-                        if let Some(arr) = req_array.as_array() {
-                            arr.iter().any(|v| v.as_str() == Some(name))
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
-                    };
-
-                    params.push(Parameter {
-                        name: name.to_string(),
-                        param_type,
-                        description,
-                        required,
-                        default: None,
-                        enum_values: vec![],
-                    });
+    match value {
+        Value::Object(members) => {
+            normalize_members(&param.properties, path, members, coercions, defaults);
+        }
+        Value::Array(values) => {
+            if let Some(items) = &param.items {
+                for (index, item) in values.iter_mut().enumerate() {
+                    let item_path = format!("{}[{}]", path, index);
+                    normalize_value(items, &item_path, item, coercions, defaults);
                 }
             }
         }
+        _ => {}
+    }
+}
+
+/// Convert a value to a parameter type if it has exactly one reading as it.
+fn coerce(param_type: ParameterType, value: &Value) -> Option<Value> {
+    match (param_type, value) {
+        (ParameterType::Integer, Value::String(s)) => {
+            let s = s.trim();
+            s.parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| s.parse::<u64>().map(Value::from))
+                .ok()
+        }
+        (ParameterType::Integer, Value::Number(n)) if !n.is_i64() && !n.is_u64() => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+            .map(|f| Value::from(f as i64)),
+        (ParameterType::Number, Value::String(s)) => {
+            let s = s.trim();
+            s.parse::<i64>().map(Value::from).ok().or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+            })
+        }
+        (ParameterType::Boolean, Value::String(s)) => {
+            match s.trim().to_ascii_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            }
+        }
+        (ParameterType::Array, Value::String(s)) => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(Value::is_array),
+        (ParameterType::Object, Value::String(s)) => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(Value::is_object),
+        _ => None,
+    }
+}
+
+/// Write `properties` and `required` for a list of parameters into a schema.
+fn write_properties(params: &[Parameter], schema: &mut serde_json::Map<String, Value>) {
+    let mut properties = serde_json::Map::new();
+    let mut required: Vec<serde_json::Value> = Vec::new();
+
+    for param in params {
+        properties.insert(param.name.clone(), parameter_schema(param));
+        if param.required {
+            required.push(serde_json::Value::String(param.name.clone()));
+        }
+    }
+
+    schema.insert(
+        "properties".to_string(),
+        serde_json::Value::Object(properties),
+    );
+    schema.insert("required".to_string(), serde_json::Value::Array(required));
+}
+
+/// JSON Schema for a single parameter.
+fn parameter_schema(param: &Parameter) -> serde_json::Value {
+    let mut prop = serde_json::Map::new();
+    prop.insert(
+        "type".to_string(),
+        serde_json::Value::String(param.param_type.as_str().to_string()),
+    );
+    if !param.description.is_empty() {
+        prop.insert(
+            "description".to_string(),
+            serde_json::Value::String(param.description.clone()),
+        );
+    }
+    if !param.enum_values.is_empty() {
+        prop.insert(
+            "enum".to_string(),
+            serde_json::Value::Array(param.enum_values.clone()),
+        );
+    }
+    if let Some(default) = &param.default {
+        prop.insert("default".to_string(), default.clone());
+    }
+    if !param.examples.is_empty() {
+        prop.insert(
+            "examples".to_string(),
+            serde_json::Value::Array(param.examples.clone()),
+        );
+    }
+    param.constraints.write_schema(&mut prop);
+    if !param.properties.is_empty() {
+        write_properties(&param.properties, &mut prop);
+    }
+    if let Some(items) = &param.items {
+        prop.insert("items".to_string(), parameter_schema(items));
+    }
+    serde_json::Value::Object(prop)
+}
+
+/// Parameters for the `properties` of an object schema.
+fn parse_properties(schema: &serde_json::Value) -> Vec<Parameter> {
+    let Some(props_obj) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    let required_names = schema.get("required").and_then(|r| r.as_array());
+
+    props_obj
+        .iter()
+        .map(|(name, prop)| {
+            let required =
+                required_names.is_some_and(|arr| arr.iter().any(|v| v.as_str() == Some(name)));
+            parse_parameter(name, prop, required)
+        })
+        .collect()
+}
 
-        Ok(params)
+/// Parameter for one property schema.
+fn parse_parameter(name: &str, prop: &serde_json::Value, required: bool) -> Parameter {
+    let param_type = match prop.get("type").and_then(|t| t.as_str()) {
+        Some("integer") => ParameterType::Integer,
+        Some("number") => ParameterType::Number,
+        Some("boolean") => ParameterType::Boolean,
+        Some("array") => ParameterType::Array,
+        Some("object") => ParameterType::Object,
+        _ => ParameterType::String,
+    };
+
+    let description = prop
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    Parameter {
+        name: name.to_string(),
+        param_type,
+        description,
+        required,
+        default: None,
+        enum_values: vec![],
+        examples: prop
+            .get("examples")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default(),
+        constraints: prop
+            .as_object()
+            .map(ParameterConstraints::from_schema)
+            .unwrap_or_default(),
+        properties: parse_properties(prop),
+        items: prop
+            .get("items")
+            .filter(|items| items.is_object())
+            .map(|items| Box::new(parse_parameter("items", items, false))),
     }
 }
 
@@ -201,6 +446,7 @@ pub struct ToolDefinitionBuilder {
     name: String,
     description: String,
     parameters: Vec<Parameter>,
+    destructive: bool,
 }
 
 impl ToolDefinitionBuilder {
@@ -230,12 +476,19 @@ impl ToolDefinitionBuilder {
         self
     }
 
+    /// Mark the tool as destructive.
+    pub fn destructive(mut self, destructive: bool) -> Self {
+        self.destructive = destructive;
+        self
+    }
+
     /// Build the tool definition.
     pub fn build(self) -> ToolDefinition {
         ToolDefinition {
             name: self.name,
             description: self.description,
             parameters: self.parameters,
+            destructive: self.destructive,
         }
     }
 }
@@ -272,6 +525,12 @@ impl ToolCall {
     pub fn builder(tool: impl Into<String>) -> ToolCallBuilder {
         ToolCallBuilder::new(tool)
     }
+
+    /// Key identifying the call: the tool name plus the arguments, with
+    /// object keys sorted so their order doesn't matter.
+    pub fn fingerprint(&self) -> String {
+        crate::cache::fingerprint(self)
+    }
 }
 
 /// Builder for [`ToolCall`].
@@ -467,6 +726,139 @@ mod tests {
         assert!(matches!(result, Err(Error::MissingParameter(_))));
     }
 
+    #[test]
+    fn tool_definition_validate_args_constraints() {
+        let tool = ToolDefinition::builder("search")
+            .parameter(
+                Parameter::builder("limit")
+                    .param_type(ParameterType::Integer)
+                    .minimum(1.0)
+                    .maximum(50.0)
+                    .build(),
+            )
+            .build();
+
+        assert!(tool.validate_args(&json!({"limit": 10})).is_ok());
+        let err = tool.validate_args(&json!({"limit": 0})).unwrap_err();
+        assert_eq!(err.to_string(), "parameter 'limit' must be at least 1, got 0");
+
+        // Constraints survive a round trip through an MCP input schema
+        let schema = tool.to_mcp_input_schema();
+        assert_eq!(schema["properties"]["limit"]["maximum"], json!(50.0));
+        let params = ToolDefinition::parse_mcp_input_schema(&schema).unwrap();
+        assert_eq!(params[0].constraints, tool.parameters[0].constraints);
+    }
+
+    #[test]
+    fn tool_definition_validate_args_nested() {
+        let tool = ToolDefinition::builder("deploy")
+            .parameter(
+                Parameter::builder("target")
+                    .param_type(ParameterType::Object)
+                    .required(true)
+                    .property(Parameter::required_string("host"))
+                    .property(
+                        Parameter::builder("ports")
+                            .param_type(ParameterType::Array)
+                            .items(
+                                Parameter::builder("port")
+                                    .param_type(ParameterType::Integer)
+                                    .maximum(65535.0)
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        assert!(tool
+            .validate_args(&json!({"target": {"host": "a", "ports": [80, 443], "extra": 1}}))
+            .is_ok());
+        assert!(matches!(
+            tool.validate_args(&json!({"target": {"ports": []}})),
+            Err(Error::MissingParameter(name)) if name == "target.host"
+        ));
+        assert!(matches!(
+            tool.validate_args(&json!({"target": {"host": "a", "ports": [80, "x"]}})),
+            Err(Error::InvalidParameterType { name, .. }) if name == "target.ports[1]"
+        ));
+        let err = tool
+            .validate_args(&json!({"target": {"host": "a", "ports": [70000]}}))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parameter 'target.ports[0]' must be at most 65535, got 70000"
+        );
+
+        // Nested members survive a round trip through an MCP input schema
+        let schema = tool.to_mcp_input_schema();
+        let target = &schema["properties"]["target"];
+        assert_eq!(target["required"], json!(["host"]));
+        assert_eq!(target["properties"]["ports"]["items"]["type"], "integer");
+        let params = ToolDefinition::parse_mcp_input_schema(&schema).unwrap();
+        let ports = params[0].get_property("ports").unwrap();
+        assert!(params[0].get_property("host").unwrap().required);
+        assert_eq!(
+            ports.items.as_ref().unwrap().constraints,
+            tool.parameters[0].properties[1].items.as_ref().unwrap().constraints
+        );
+    }
+
+    #[test]
+    fn tool_definition_normalize_args() {
+        let tool = ToolDefinition::builder("search")
+            .parameter(
+                Parameter::builder("limit")
+                    .param_type(ParameterType::Integer)
+                    .maximum(50.0)
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("exact")
+                    .param_type(ParameterType::Boolean)
+                    .default(json!(false))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("filter")
+                    .param_type(ParameterType::Object)
+                    .property(
+                        Parameter::builder("min_score")
+                            .param_type(ParameterType::Number)
+                            .build(),
+                    )
+                    .property(
+                        Parameter::builder("tags")
+                            .param_type(ParameterType::Array)
+                            .default(json!([]))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        let normalized = tool
+            .normalize_args(&json!({"limit": "5", "filter": "{\"min_score\": \"0.5\"}"}))
+            .unwrap();
+        assert_eq!(
+            normalized.arguments,
+            json!({"limit": 5, "exact": false, "filter": {"min_score": 0.5, "tags": []}})
+        );
+        let paths: Vec<_> = normalized.coercions.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["limit", "filter", "filter.min_score"]);
+        assert_eq!(normalized.coercions[0].from, json!("5"));
+        assert_eq!(normalized.defaults, ["exact", "filter.tags"]);
+
+        // Ambiguous values are left alone and still fail validation
+        assert!(tool.normalize_args(&json!({"exact": "yes"})).is_err());
+        assert!(tool.normalize_args(&json!({"limit": "5.5"})).is_err());
+        assert!(tool.normalize_args(&json!({"limit": 7.0})).unwrap().arguments["limit"].is_i64());
+        // Coerced values are still checked against constraints
+        assert!(tool.normalize_args(&json!({"limit": "80"})).is_err());
+        assert!(tool.normalize_args(&json!({"limit": 3})).unwrap().coercions.is_empty());
+    }
+
     #[test]
     fn tool_definition_validate_args_with_default() {
         let tool = ToolDefinition::builder("test")
@@ -591,6 +983,17 @@ mod tests {
         assert_eq!(tool, parsed);
     }
 
+    #[test]
+    fn tool_definition_destructive_flag() {
+        let tool = ToolDefinition::new("read");
+        assert!(!serde_json::to_string(&tool).unwrap().contains("destructive"));
+
+        let tool = ToolDefinition::builder("drop_table").destructive(true).build();
+        let json = serde_json::to_value(&tool).unwrap();
+        assert_eq!(json["destructive"], json!(true));
+        assert!(serde_json::from_value::<ToolDefinition>(json).unwrap().destructive);
+    }
+
     #[test]
     fn tool_definition_validate_args_edge_cases() {
         let tool = ToolDefinition::builder("test")
@@ -665,7 +1068,7 @@ mod tests {
         let args = json!({
             "str_param": "test",
             "int_param": 42,
-            "num_param": 3.14,
+            "num_param": 2.5,
             "bool_param": true,
             "arr_param": [1, 2, 3],
             "obj_param": {"key": "value"}
@@ -674,8 +1077,8 @@ mod tests {
         assert!(tool.validate_args(&args).is_ok());
 
         // Test each wrong type individually
-        assert!(tool.validate_args(&json!({"str_param": 42, "int_param": 42, "num_param": 3.14, "bool_param": true, "arr_param": [], "obj_param": {}})).is_err());
-        assert!(tool.validate_args(&json!({"str_param": "test", "int_param": "not int", "num_param": 3.14, "bool_param": true, "arr_param": [], "obj_param": {}})).is_err());
+        assert!(tool.validate_args(&json!({"str_param": 42, "int_param": 42, "num_param": 2.5, "bool_param": true, "arr_param": [], "obj_param": {}})).is_err());
+        assert!(tool.validate_args(&json!({"str_param": "test", "int_param": "not int", "num_param": 2.5, "bool_param": true, "arr_param": [], "obj_param": {}})).is_err());
     }
 
     #[test]
@@ -934,6 +1337,24 @@ mod tests {
         assert_eq!(level["default"], "med");
     }
 
+    #[test]
+    fn to_mcp_input_schema_round_trips_examples() {
+        let def = ToolDefinition::builder("with_examples")
+            .parameter(
+                Parameter::builder("city")
+                    .example(json!("Berlin"))
+                    .example(json!("Tokyo"))
+                    .build(),
+            )
+            .build();
+
+        let schema = def.to_mcp_input_schema();
+        assert_eq!(schema["properties"]["city"]["examples"], json!(["Berlin", "Tokyo"]));
+
+        let parsed = ToolDefinition::parse_mcp_input_schema(&schema).unwrap();
+        assert_eq!(parsed[0].examples, def.parameters[0].examples);
+    }
+
     #[test]
     fn to_mcp_input_schema_empty_definition_yields_empty_properties() {
         let def = ToolDefinition::new("noargs");
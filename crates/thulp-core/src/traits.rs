@@ -1,6 +1,9 @@
 //! Core traits for thulp.
 
-use crate::{Result, ToolCall, ToolDefinition, ToolResult};
+use crate::{
+    stream::single_chunk, McpNotification, Result, ToolCall, ToolDefinition, ToolResult,
+    ToolResultStream,
+};
 use async_trait::async_trait;
 use serde_json::Value;
 
@@ -41,6 +44,74 @@ pub trait Transport: Send + Sync {
 
     /// Execute a tool call.
     async fn call(&self, call: &ToolCall) -> Result<ToolResult>;
+
+    /// Execute a tool call, streaming partial results as they arrive.
+    ///
+    /// The default implementation awaits [`call`](Self::call) and yields its
+    /// result as a single chunk. Transports that can deliver output
+    /// incrementally should override this.
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        Ok(single_chunk(self.call(call).await?))
+    }
+}
+
+/// Trait for receiving server notifications (logs, progress, resource updates).
+///
+/// Implement this to capture notifications from MCP servers, e.g. to record
+/// them in a session or forward them to a UI.
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    /// Handle a notification from the named server.
+    async fn notify(&self, server: &str, notification: &McpNotification) -> Result<()>;
+}
+
+/// Trait for masking sensitive values before they are logged or persisted.
+///
+/// Hooks, session recording and transport logging pass tool arguments and
+/// results through a redactor so secrets are masked consistently wherever
+/// they might be printed or stored. See [`PathRedactor`](crate::PathRedactor)
+/// for a glob-based implementation.
+pub trait Redactor: Send + Sync {
+    /// Return a copy of `value` with sensitive fields masked.
+    fn redact(&self, value: &Value) -> Value;
+}
+
+/// Trait for looking up secrets by name.
+///
+/// Configuration references like `${secret:name}` and skill arguments like
+/// `{{secret:name}}` are resolved through this trait, so credentials can
+/// live in a secrets store rather than in files that get committed.
+pub trait SecretResolver: Send + Sync {
+    /// Look up a secret by name.
+    fn resolve(&self, name: &str) -> Option<String>;
+}
+
+impl SecretResolver for std::collections::HashMap<String, String> {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+impl SecretResolver for std::sync::RwLock<std::collections::HashMap<String, String>> {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.read().ok()?.get(name).cloned()
+    }
+}
+
+/// A [`SecretResolver`] that can also store secrets, for credentials
+/// obtained at run time such as OAuth2 tokens.
+pub trait SecretWriter: SecretResolver {
+    /// Store a secret, replacing any previous value.
+    fn write(&self, name: &str, value: &str) -> Result<()>;
+}
+
+impl SecretWriter for std::sync::RwLock<std::collections::HashMap<String, String>> {
+    fn write(&self, name: &str, value: &str) -> Result<()> {
+        self.write()
+            .map_err(|e| crate::Error::ExecutionFailed(e.to_string()))?
+            .insert(name.to_string(), value.to_string());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -183,4 +254,16 @@ mod tests {
         assert!(result.is_success());
         assert_eq!(result.data.unwrap()["tool"], "test_tool");
     }
+
+    #[tokio::test]
+    async fn transport_trait_call_streaming_default() {
+        let transport = MockTransport::new(vec![]);
+
+        let call = ToolCall::new("test_tool");
+        let stream = transport.call_streaming(&call).await.unwrap();
+        let result = crate::collect_stream(stream).await.unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.data.unwrap()["tool"], "test_tool");
+    }
 }
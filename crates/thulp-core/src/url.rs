@@ -0,0 +1,300 @@
+//! URL construction with proper encoding.
+//!
+//! [`UrlBuilder`] assembles a URL from a base, path pieces, a route template
+//! and query parameters, percent-encoding each part for where it goes. It
+//! replaces gluing URLs together with `format!`, which breaks as soon as a
+//! value holds a space, `&`, `/` or `#`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use thulp_core::UrlBuilder;
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert("owner".to_string(), "dirmacs".to_string());
+//! vars.insert("repo".to_string(), "thulp core".to_string());
+//!
+//! let url = UrlBuilder::new("https://api.example.com/v1/")
+//!     .path_template("/repos/{owner}/{repo}", &vars)
+//!     .unwrap()
+//!     .segment("issues")
+//!     .query("q", "is:open label:bug")
+//!     .query("page", 2)
+//!     .build();
+//! assert_eq!(
+//!     url,
+//!     "https://api.example.com/v1/repos/dirmacs/thulp%20core/issues?q=is%3Aopen%20label%3Abug&page=2"
+//! );
+//! ```
+
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Builds URLs from a base, path pieces and query parameters.
+///
+/// Anything already in the base, including its query string, is kept as
+/// written; only what is added through the builder is encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlBuilder {
+    /// Scheme, authority and path of the URL
+    base: String,
+    /// Encoded `name=value` pairs
+    query: Vec<String>,
+    /// Encoded fragment
+    fragment: Option<String>,
+}
+
+impl UrlBuilder {
+    /// Start from a base URL such as `https://api.example.com/v1`.
+    pub fn new(base: impl AsRef<str>) -> Self {
+        let base = base.as_ref();
+        let (base, fragment) = match base.split_once('#') {
+            Some((base, fragment)) => (base, Some(fragment.to_string())),
+            None => (base, None),
+        };
+        let (base, query) = match base.split_once('?') {
+            Some((base, query)) => (
+                base,
+                query
+                    .split('&')
+                    .filter(|pair| !pair.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+            None => (base, Vec::new()),
+        };
+        Self {
+            base: base.to_string(),
+            query,
+            fragment,
+        }
+    }
+
+    /// Append a path, joining it with a single `/`.
+    ///
+    /// Slashes separate segments; other characters that can't appear in a
+    /// path are encoded, while existing `%XX` escapes are kept.
+    pub fn path(mut self, path: impl AsRef<str>) -> Self {
+        let path = path.as_ref().trim_start_matches('/');
+        if path.is_empty() {
+            return self;
+        }
+        self.push_separator();
+        encode_into(&mut self.base, path, is_path_char);
+        self
+    }
+
+    /// Append one path segment, encoding `/` and every other reserved
+    /// character in it.
+    pub fn segment(mut self, segment: impl AsRef<str>) -> Self {
+        self.push_separator();
+        encode_into(&mut self.base, segment.as_ref(), is_unreserved);
+        self
+    }
+
+    /// Append a route template such as `/users/{id}/posts`, substituting
+    /// each `{name}` with the encoded value of `name`.
+    ///
+    /// `{+name}` substitutes the value keeping its slashes, for parameters
+    /// that hold a sub-path. Returns [`Error::MissingParameter`] for a name
+    /// missing from `vars`, and [`Error::InvalidConfig`] for an unclosed
+    /// brace.
+    pub fn path_template(self, template: &str, vars: &HashMap<String, String>) -> Result<Self> {
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..].find('}').ok_or_else(|| {
+                Error::InvalidConfig(format!("unclosed '{{' in URL template '{}'", template))
+            })? + open;
+            expanded.push_str(&rest[..open]);
+            let name = &rest[open + 1..close];
+            let (name, keep_slashes) = match name.strip_prefix('+') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            let value = vars
+                .get(name)
+                .ok_or_else(|| Error::MissingParameter(name.to_string()))?;
+            if keep_slashes {
+                encode_into(&mut expanded, value, |c| c == b'/' || is_unreserved(c));
+            } else {
+                encode_into(&mut expanded, value, is_unreserved);
+            }
+            rest = &rest[close + 1..];
+        }
+        expanded.push_str(rest);
+        Ok(self.path(expanded))
+    }
+
+    /// Add a query parameter. Repeated names are kept, in order.
+    pub fn query(mut self, name: impl AsRef<str>, value: impl ToString) -> Self {
+        let mut pair = String::new();
+        encode_into(&mut pair, name.as_ref(), is_unreserved);
+        pair.push('=');
+        encode_into(&mut pair, &value.to_string(), is_unreserved);
+        self.query.push(pair);
+        self
+    }
+
+    /// Add a query parameter if there is a value.
+    pub fn query_opt(self, name: impl AsRef<str>, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(value) => self.query(name, value),
+            None => self,
+        }
+    }
+
+    /// Add a query parameter for each value, e.g. `tag=a&tag=b`.
+    pub fn query_all<V: ToString>(
+        mut self,
+        name: impl AsRef<str>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        for value in values {
+            self = self.query(name.as_ref(), value);
+        }
+        self
+    }
+
+    /// Set the fragment after `#`.
+    pub fn fragment(mut self, fragment: impl AsRef<str>) -> Self {
+        let mut encoded = String::new();
+        encode_into(&mut encoded, fragment.as_ref(), is_path_char);
+        self.fragment = Some(encoded);
+        self
+    }
+
+    /// Assemble the URL.
+    pub fn build(&self) -> String {
+        let mut url = self.base.clone();
+        if !self.query.is_empty() {
+            url.push('?');
+            url.push_str(&self.query.join("&"));
+        }
+        if let Some(fragment) = &self.fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        url
+    }
+
+    /// Make sure the base ends with exactly one `/`
+    fn push_separator(&mut self) {
+        if !self.base.ends_with('/') {
+            self.base.push('/');
+        }
+    }
+}
+
+impl std::fmt::Display for UrlBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.build())
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters, so the
+/// result is safe as a path segment, query name or query value.
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    encode_into(&mut encoded, value, is_unreserved);
+    encoded
+}
+
+fn encode_into(out: &mut String, value: &str, keep: impl Fn(u8) -> bool) {
+    for byte in value.bytes() {
+        if keep(byte) {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{:02X}", byte);
+        }
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// Characters allowed unencoded in a path, plus `%` for existing escapes
+fn is_path_char(byte: u8) -> bool {
+    is_unreserved(byte)
+        || matches!(
+            byte,
+            b'/' | b'%'
+                | b'!'
+                | b'$'
+                | b'&'
+                | b'\''
+                | b'('
+                | b')'
+                | b'*'
+                | b'+'
+                | b','
+                | b';'
+                | b'='
+                | b':'
+                | b'@'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_joins_paths_with_single_slash() {
+        for base in ["https://h.example/v1", "https://h.example/v1/"] {
+            for path in ["users", "/users"] {
+                assert_eq!(
+                    UrlBuilder::new(base).path(path).build(),
+                    "https://h.example/v1/users"
+                );
+            }
+        }
+        assert_eq!(
+            UrlBuilder::new("https://h.example")
+                .path("docs/a b%2F.md")
+                .segment("x/y?")
+                .build(),
+            "https://h.example/docs/a%20b%2F.md/x%2Fy%3F"
+        );
+    }
+
+    #[test]
+    fn test_query_and_fragment() {
+        let url = UrlBuilder::new("https://h.example/search?lang=en#old")
+            .query("q", "rust & go")
+            .query_opt("page", None::<u32>)
+            .query_opt("limit", Some(10))
+            .query_all("tag", ["a", "b"])
+            .fragment("results")
+            .build();
+        assert_eq!(
+            url,
+            "https://h.example/search?lang=en&q=rust%20%26%20go&limit=10&tag=a&tag=b#results"
+        );
+    }
+
+    #[test]
+    fn test_path_template() {
+        let vars: HashMap<String, String> = [("id", "a/b"), ("file", "dir/f.txt")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let url = UrlBuilder::new("https://h.example")
+            .path_template("/items/{id}/files/{+file}", &vars)
+            .unwrap()
+            .build();
+        assert_eq!(url, "https://h.example/items/a%2Fb/files/dir/f.txt");
+
+        assert!(matches!(
+            UrlBuilder::new("https://h.example").path_template("/{missing}", &vars),
+            Err(Error::MissingParameter(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            UrlBuilder::new("https://h.example").path_template("/{id", &vars),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,268 @@
+//! Usage snippets for tool definitions.
+//!
+//! [`UsageRenderer`] turns a [`ToolDefinition`] into ready-to-run examples: a
+//! `thulp run` command line and the JSON [`ToolCall`] payload. Argument values
+//! come from each parameter's [`examples`](Parameter::examples), falling back
+//! to its default, its first allowed enum value, and finally a placeholder for
+//! its type.
+
+use crate::{Parameter, ParameterType, ToolCall, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Rendered usage snippets for a tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    /// Shell command line invoking the tool
+    pub cli: String,
+
+    /// JSON tool call payload
+    pub json: Value,
+}
+
+/// Renders usage snippets for tool definitions.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use thulp_core::{Parameter, ToolDefinition, UsageRenderer};
+///
+/// let tool = ToolDefinition::builder("search")
+///     .parameter(
+///         Parameter::builder("query")
+///             .required(true)
+///             .example(json!("rust async"))
+///             .build(),
+///     )
+///     .build();
+///
+/// let usage = UsageRenderer::new().render(&tool);
+/// assert_eq!(usage.cli, "thulp run search 'query=rust async'");
+/// assert_eq!(usage.json["arguments"]["query"], "rust async");
+/// ```
+#[derive(Debug, Clone)]
+pub struct UsageRenderer {
+    /// Command prefix for the CLI snippet
+    command: String,
+
+    /// Whether to include optional parameters without examples
+    include_optional: bool,
+}
+
+impl Default for UsageRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageRenderer {
+    /// Create a renderer for `thulp run` that shows required parameters and
+    /// any parameter with an example.
+    pub fn new() -> Self {
+        Self {
+            command: "thulp run".to_string(),
+            include_optional: false,
+        }
+    }
+
+    /// Set the command prefix (default: `thulp run`).
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = command.into();
+        self
+    }
+
+    /// Include every optional parameter, not just those with examples.
+    pub fn with_optional(mut self, include: bool) -> Self {
+        self.include_optional = include;
+        self
+    }
+
+    /// Build example arguments for a tool.
+    pub fn arguments(&self, tool: &ToolDefinition) -> Value {
+        let arguments: Map<String, Value> = self
+            .shown(tool)
+            .map(|p| (p.name.clone(), p.example_value()))
+            .collect();
+        Value::Object(arguments)
+    }
+
+    /// Render the CLI command line for a tool.
+    pub fn cli(&self, tool: &ToolDefinition) -> String {
+        let mut line = format!("{} {}", self.command, shell_quote(&tool.name));
+        // Keep declaration order rather than the map's key order
+        for param in self.shown(tool) {
+            let value = cli_value(&param.example_value());
+            line.push(' ');
+            line.push_str(&shell_quote(&format!("{}={}", param.name, value)));
+        }
+        line
+    }
+
+    /// Render the JSON tool call payload for a tool.
+    pub fn json(&self, tool: &ToolDefinition) -> Value {
+        let call = ToolCall::with_args(&tool.name, self.arguments(tool));
+        serde_json::to_value(call).unwrap_or_default()
+    }
+
+    /// Render both snippets.
+    pub fn render(&self, tool: &ToolDefinition) -> ToolUsage {
+        ToolUsage {
+            cli: self.cli(tool),
+            json: self.json(tool),
+        }
+    }
+
+    /// Parameters that appear in the snippets
+    fn shown<'a>(&'a self, tool: &'a ToolDefinition) -> impl Iterator<Item = &'a Parameter> {
+        tool.parameters
+            .iter()
+            .filter(|p| p.required || self.include_optional || !p.examples.is_empty())
+    }
+}
+
+impl Parameter {
+    /// A representative value for documentation: the first example, the
+    /// default, the first enum value, or a placeholder for the type.
+    pub fn example_value(&self) -> Value {
+        if let Some(example) = self
+            .examples
+            .first()
+            .or(self.default.as_ref())
+            .or(self.enum_values.first())
+        {
+            return example.clone();
+        }
+        match self.param_type {
+            ParameterType::String => Value::String(format!("<{}>", self.name)),
+            ParameterType::Integer => Value::from(0),
+            ParameterType::Number => Value::from(0.0),
+            ParameterType::Boolean => Value::Bool(false),
+            ParameterType::Array => Value::Array(Vec::new()),
+            ParameterType::Object => Value::Object(Map::new()),
+        }
+    }
+}
+
+/// Format a value the way `thulp run key=value` parses it back: values are
+/// read as JSON first, so strings that look like JSON are JSON-encoded
+fn cli_value(value: &Value) -> String {
+    match value {
+        Value::String(s) if serde_json::from_str::<Value>(s).is_err() => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Quote a word for POSIX shells when it contains special characters
+fn shell_quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool() -> ToolDefinition {
+        ToolDefinition::builder("api_call")
+            .parameter(
+                Parameter::builder("url")
+                    .required(true)
+                    .example(json!("https://example.com/users"))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("method")
+                    .default(json!("GET"))
+                    .enum_value(json!("GET"))
+                    .enum_value(json!("POST"))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("headers")
+                    .param_type(ParameterType::Object)
+                    .example(json!({"Accept": "application/json"}))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("retries")
+                    .param_type(ParameterType::Integer)
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_example_value_fallbacks() {
+        let tool = tool();
+        let value = |name: &str| tool.get_parameter(name).unwrap().example_value();
+
+        assert_eq!(value("url"), json!("https://example.com/users"));
+        assert_eq!(value("method"), json!("GET"));
+        assert_eq!(value("retries"), json!(0));
+        assert_eq!(Parameter::new("id").example_value(), json!("<id>"));
+    }
+
+    #[test]
+    fn test_render_required_and_examples() {
+        let usage = UsageRenderer::new().render(&tool());
+
+        assert_eq!(
+            usage.cli,
+            r#"thulp run api_call url=https://example.com/users 'headers={"Accept":"application/json"}'"#
+        );
+        assert_eq!(
+            usage.json,
+            json!({
+                "tool": "api_call",
+                "arguments": {
+                    "url": "https://example.com/users",
+                    "headers": {"Accept": "application/json"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_with_optional_and_command() {
+        let renderer = UsageRenderer::new()
+            .with_command("thulp mcp call")
+            .with_optional(true);
+        let cli = renderer.cli(&tool());
+
+        assert!(cli.starts_with("thulp mcp call api_call "));
+        assert!(cli.contains("method=GET"));
+        assert!(cli.contains("retries=0"));
+    }
+
+    #[test]
+    fn test_cli_values_round_trip() {
+        let tool = ToolDefinition::builder("t")
+            .parameter(
+                Parameter::builder("n")
+                    .required(true)
+                    .example(json!("42"))
+                    .build(),
+            )
+            .parameter(
+                Parameter::builder("q")
+                    .required(true)
+                    .example(json!("it's"))
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(
+            UsageRenderer::new().cli(&tool),
+            r#"thulp run t 'n="42"' 'q=it'\''s'"#
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Workspace-wide template variables.
+//!
+//! A workspace can define variables every template sees, such as the
+//! company name or a house style, in `.thulp/guidance/defaults.yaml`:
+//!
+//! ```yaml
+//! variables:
+//!   company: Acme
+//!   tone: formal
+//! templates:
+//!   code-review:
+//!     tone: direct
+//! ```
+//!
+//! `variables` apply to every template and `templates` override them for a
+//! single template. Both are merged when rendering through a
+//! [`TemplateRegistry`](crate::TemplateRegistry), below the variables passed
+//! in and above the template's own frontmatter defaults, so precedence from
+//! lowest to highest is:
+//!
+//! 1. the template's `defaults`
+//! 2. workspace `variables`
+//! 3. workspace `templates.<name>`
+//! 4. variables passed to `render`
+
+use crate::{GuidanceError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Defaults file relative to a workspace directory
+pub const WORKSPACE_DEFAULTS_PATH: &str = ".thulp/guidance/defaults.yaml";
+
+/// Template variables shared across a workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuidanceDefaults {
+    /// Variables for every template
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, Value>,
+
+    /// Variables for single templates, by template name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, HashMap<String, Value>>,
+}
+
+impl GuidanceDefaults {
+    /// Create empty defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a variable for every template
+    pub fn with_variable(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.variables.insert(key.into(), value);
+        self
+    }
+
+    /// Set a variable for one template
+    pub fn with_template_variable(
+        mut self,
+        template: impl Into<String>,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Self {
+        self.templates
+            .entry(template.into())
+            .or_default()
+            .insert(key.into(), value);
+        self
+    }
+
+    /// Check whether no variables are set
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.templates.values().all(HashMap::is_empty)
+    }
+
+    /// Parse defaults from YAML or JSON.
+    pub fn parse(source: &str) -> Result<Self> {
+        if source.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(source)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("defaults: {}", e)))
+    }
+
+    /// Read a defaults file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| match e {
+            GuidanceError::InvalidFormat(message) => {
+                GuidanceError::InvalidFormat(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Read a workspace's `.thulp/guidance/defaults.yaml`, or return empty
+    /// defaults if it doesn't have one.
+    pub fn load_workspace(workspace_dir: impl AsRef<Path>) -> Result<Self> {
+        let path = Self::workspace_path(workspace_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::from_file(path)
+    }
+
+    /// Path of a workspace's defaults file
+    pub fn workspace_path(workspace_dir: impl AsRef<Path>) -> PathBuf {
+        workspace_dir.as_ref().join(WORKSPACE_DEFAULTS_PATH)
+    }
+
+    /// Variables for a template: the shared ones with its overrides applied.
+    pub fn variables_for(&self, template: &str) -> HashMap<String, Value> {
+        let mut variables = self.variables.clone();
+        if let Some(overrides) = self.templates.get(template) {
+            variables.extend(overrides.clone());
+        }
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptTemplate, TemplateRegistry};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_precedence() {
+        let defaults = GuidanceDefaults::parse(
+            "variables:\n  company: Acme\n  tone: formal\ntemplates:\n  review:\n    tone: direct\n",
+        )
+        .unwrap();
+        let mut registry = TemplateRegistry::new().with_defaults(defaults);
+        registry.register(
+            PromptTemplate::new("review", "{{company}}: be {{tone}}, {{length}}")
+                .with_default("tone", "kind")
+                .with_default("length", "short"),
+        );
+        registry.register(PromptTemplate::new("intro", "{{company}} is {{tone}}"));
+
+        assert_eq!(
+            registry.render("review", &HashMap::new()).unwrap(),
+            "Acme: be direct, short"
+        );
+        assert_eq!(
+            registry.render("intro", &HashMap::new()).unwrap(),
+            "Acme is formal"
+        );
+
+        let mut vars = HashMap::new();
+        vars.insert("tone".to_string(), json!("casual"));
+        assert_eq!(
+            registry.render_values("review", &vars).unwrap(),
+            "Acme: be casual, short"
+        );
+    }
+
+    #[test]
+    fn test_load_workspace() {
+        let temp = TempDir::new().unwrap();
+        assert!(GuidanceDefaults::load_workspace(temp.path())
+            .unwrap()
+            .is_empty());
+
+        let path = GuidanceDefaults::workspace_path(temp.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "variables:\n  policy: [no secrets, cite sources]\n").unwrap();
+        let defaults = GuidanceDefaults::load_workspace(temp.path()).unwrap();
+        assert_eq!(
+            defaults.variables_for("any")["policy"],
+            json!(["no secrets", "cite sources"])
+        );
+
+        std::fs::write(&path, "variables: [oops]\n").unwrap();
+        let err = GuidanceDefaults::load_workspace(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("defaults.yaml"), "{}", err);
+    }
+}
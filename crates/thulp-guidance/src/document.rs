@@ -0,0 +1,459 @@
+//! Assembling prompts from named sections.
+//!
+//! A [`PromptDocument`] collects sections such as the system prompt,
+//! instructions, context and examples, puts them in a fixed order, keeps each
+//! within its token budget, and renders them either as one Markdown prompt
+//! or as chat messages.
+//!
+//! ```rust
+//! use thulp_guidance::document::{PromptDocument, Role, Section};
+//!
+//! let document = PromptDocument::new()
+//!     .examples("Input: 2 + 2\nOutput: 4")
+//!     .system("You are a careful calculator.")
+//!     .instructions("Answer with the result only.")
+//!     .section(Section::new("context", "# Notes\nIntegers only.").with_max_tokens(50));
+//!
+//! assert_eq!(
+//!     document.render(),
+//!     "You are a careful calculator.\n\n## Instructions\n\n\
+//!      Answer with the result only.\n\n## Context\n\n### Notes\n\
+//!      Integers only.\n\n## Examples\n\n\
+//!      Input: 2 + 2\nOutput: 4\n"
+//! );
+//!
+//! let messages = document.to_messages();
+//! assert_eq!(messages[0].role, Role::System);
+//! assert_eq!(messages[1].role, Role::User);
+//! ```
+//!
+//! Token counts are estimated at four characters per token, which is close
+//! enough for budgeting English text without tying the crate to a tokenizer.
+
+use serde::{Deserialize, Serialize};
+
+/// Characters per token assumed by [`estimate_tokens`]
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Level of the headings sections are rendered under
+const SECTION_HEADING_LEVEL: usize = 2;
+
+/// Sections trimmed below this many tokens to fit the document budget are
+/// dropped instead
+const MIN_SECTION_TOKENS: usize = 16;
+
+/// Appended where a section was cut short
+pub const TRUNCATION_MARKER: &str = "[truncated]";
+
+/// Estimate how many tokens a text takes.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Who a chat message is from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// System prompt
+    System,
+    /// User turn
+    User,
+    /// Assistant turn
+    Assistant,
+}
+
+/// A message of a chat prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Who the message is from
+    pub role: Role,
+    /// Message text
+    pub content: String,
+}
+
+/// A named part of a [`PromptDocument`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Section name, unique within a document
+    pub name: String,
+    /// Markdown content
+    pub content: String,
+    /// Heading rendered above the content, if any
+    pub title: Option<String>,
+    /// Role of the chat message the section goes into
+    pub role: Role,
+    /// Position in the document; lower comes first
+    pub order: i32,
+    /// Most tokens the content may take
+    pub max_tokens: Option<usize>,
+}
+
+impl Section {
+    /// Create a section.
+    ///
+    /// `system`, `instructions`, `context` and `examples` are placed in that
+    /// order, before any other section. The `system` section is untitled and
+    /// goes into a system message; others are titled after their name and
+    /// go into user messages.
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        let name = name.into();
+        let (order, role) = match name.as_str() {
+            "system" => (0, Role::System),
+            "instructions" => (10, Role::User),
+            "context" => (20, Role::User),
+            "examples" => (30, Role::User),
+            _ => (40, Role::User),
+        };
+        let title = (role != Role::System).then(|| default_title(&name));
+        Self {
+            name,
+            content: content.into(),
+            title,
+            role,
+            order,
+            max_tokens: None,
+        }
+    }
+
+    /// Set the heading
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Render the content without a heading
+    pub fn untitled(mut self) -> Self {
+        self.title = None;
+        self
+    }
+
+    /// Set the chat role
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Set the position in the document
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Limit the content to a number of tokens
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Markdown for the section with the given content
+    fn to_markdown(&self, content: &str) -> String {
+        match &self.title {
+            Some(title) => format!(
+                "{} {}\n\n{}",
+                "#".repeat(SECTION_HEADING_LEVEL),
+                title,
+                shift_headings(content, SECTION_HEADING_LEVEL)
+            ),
+            None => content.to_string(),
+        }
+    }
+}
+
+/// A prompt built from ordered, budgeted sections
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptDocument {
+    sections: Vec<Section>,
+    max_tokens: Option<usize>,
+}
+
+impl PromptDocument {
+    /// Create an empty document
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a section, replacing any section with the same name
+    pub fn section(mut self, section: Section) -> Self {
+        match self.sections.iter_mut().find(|s| s.name == section.name) {
+            Some(existing) => *existing = section,
+            None => self.sections.push(section),
+        }
+        self
+    }
+
+    /// Set the system prompt
+    pub fn system(self, content: impl Into<String>) -> Self {
+        self.section(Section::new("system", content))
+    }
+
+    /// Set the instructions
+    pub fn instructions(self, content: impl Into<String>) -> Self {
+        self.section(Section::new("instructions", content))
+    }
+
+    /// Set the context
+    pub fn context(self, content: impl Into<String>) -> Self {
+        self.section(Section::new("context", content))
+    }
+
+    /// Set the examples
+    pub fn examples(self, content: impl Into<String>) -> Self {
+        self.section(Section::new("examples", content))
+    }
+
+    /// Limit the contents of all sections together to a number of tokens.
+    ///
+    /// When over budget, sections are trimmed starting from the last one;
+    /// a section that would be left with only a few tokens is dropped.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Get a section by name
+    pub fn get(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Sections in the order they are rendered
+    pub fn sections(&self) -> Vec<&Section> {
+        let mut sections: Vec<&Section> = self.sections.iter().collect();
+        sections.sort_by_key(|s| s.order);
+        sections
+    }
+
+    /// Estimated tokens of the rendered prompt
+    pub fn estimated_tokens(&self) -> usize {
+        estimate_tokens(&self.render())
+    }
+
+    /// Render as a single Markdown prompt.
+    pub fn render(&self) -> String {
+        let parts: Vec<String> = self
+            .fitted()
+            .into_iter()
+            .map(|(section, content)| section.to_markdown(&content))
+            .collect();
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!("{}\n", parts.join("\n\n"))
+    }
+
+    /// Render as chat messages, one per run of sections with the same role.
+    pub fn to_messages(&self) -> Vec<ChatMessage> {
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        for (section, content) in self.fitted() {
+            let markdown = section.to_markdown(&content);
+            match messages.last_mut() {
+                Some(last) if last.role == section.role => {
+                    last.content.push_str("\n\n");
+                    last.content.push_str(&markdown);
+                }
+                _ => messages.push(ChatMessage {
+                    role: section.role,
+                    content: markdown,
+                }),
+            }
+        }
+        messages
+    }
+
+    /// Non-empty sections in order, with their contents cut to fit the
+    /// section and document budgets
+    fn fitted(&self) -> Vec<(&Section, String)> {
+        let mut fitted: Vec<(&Section, String)> = self
+            .sections()
+            .into_iter()
+            .map(|section| {
+                let content = section.content.trim();
+                let content = match section.max_tokens {
+                    Some(max_tokens) => truncate_markdown(content, max_tokens),
+                    None => content.to_string(),
+                };
+                (section, content)
+            })
+            .filter(|(_, content)| !content.is_empty())
+            .collect();
+
+        if let Some(max_tokens) = self.max_tokens {
+            for index in (0..fitted.len()).rev() {
+                let total: usize = fitted.iter().map(|(_, c)| estimate_tokens(c)).sum();
+                if total <= max_tokens {
+                    break;
+                }
+                let content = &mut fitted[index].1;
+                let budget = estimate_tokens(content).saturating_sub(total - max_tokens);
+                *content = if budget < MIN_SECTION_TOKENS {
+                    String::new()
+                } else {
+                    truncate_markdown(content, budget)
+                };
+            }
+            fitted.retain(|(_, content)| !content.is_empty());
+        }
+        fitted
+    }
+}
+
+/// Title a section after its name, e.g. `style_guide` becomes `Style guide`
+fn default_title(name: &str) -> String {
+    let words = name.replace(['_', '-'], " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Whether a line opens or closes a fenced code block
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Push Markdown headings outside code blocks down by `levels`, so they
+/// nest under the section heading
+fn shift_headings(content: &str, levels: usize) -> String {
+    let mut shifted = String::with_capacity(content.len() + levels * 4);
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            let hashes = line.chars().take_while(|c| *c == '#').count();
+            let rest = &line[hashes..];
+            if (1..=6).contains(&hashes) && (rest.starts_with(' ') || rest.trim().is_empty()) {
+                shifted.push_str(&"#".repeat((hashes + levels).min(6) - hashes));
+            }
+        }
+        shifted.push_str(line);
+    }
+    shifted
+}
+
+/// Cut content to about `max_tokens`, preferring to end at a paragraph or
+/// line break and closing a code block left open by the cut
+fn truncate_markdown(content: &str, max_tokens: usize) -> String {
+    if estimate_tokens(content) <= max_tokens {
+        return content.to_string();
+    }
+    let marker_tokens = estimate_tokens(TRUNCATION_MARKER) + 1;
+    let max_chars = max_tokens.saturating_sub(marker_tokens) * CHARS_PER_TOKEN;
+    let limit = content
+        .char_indices()
+        .nth(max_chars)
+        .map_or(content.len(), |(index, _)| index);
+    let head = &content[..limit];
+    let cut = head
+        .rfind("\n\n")
+        .filter(|&index| index >= limit / 2)
+        .or_else(|| head.rfind('\n').filter(|&index| index >= limit / 2))
+        .unwrap_or(limit);
+
+    let mut truncated = content[..cut].trim_end().to_string();
+    if truncated.lines().filter(|line| is_fence(line)).count() % 2 == 1 {
+        truncated.push_str("\n```");
+    }
+    if !truncated.is_empty() {
+        truncated.push('\n');
+    }
+    truncated.push_str(TRUNCATION_MARKER);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_are_ordered_and_merged_into_messages() {
+        let document = PromptDocument::new()
+            .section(Section::new("style_guide", "Be brief.").with_order(5))
+            .context("Some context")
+            .system("System prompt")
+            .section(
+                Section::new("persona", "A pirate")
+                    .with_role(Role::System)
+                    .with_order(1),
+            )
+            .instructions("   ")
+            .context("Replaced context");
+
+        let names: Vec<_> = document
+            .sections()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "system",
+                "persona",
+                "style_guide",
+                "instructions",
+                "context"
+            ]
+        );
+
+        let messages = document.to_messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[0].content,
+            "System prompt\n\n## Persona\n\nA pirate"
+        );
+        assert_eq!(
+            messages[1].content,
+            "## Style guide\n\nBe brief.\n\n## Context\n\nReplaced context"
+        );
+    }
+
+    #[test]
+    fn test_section_budget_truncates_at_boundaries() {
+        let paragraph = "word ".repeat(20);
+        let content = format!("{}\n\n```\ncode\n{}", paragraph, paragraph);
+        let truncated = truncate_markdown(&content, 40);
+        assert_eq!(
+            truncated,
+            format!("{}\n{}", paragraph.trim_end(), TRUNCATION_MARKER)
+        );
+
+        // A cut inside a code block closes it
+        let code = format!("```\n{}```\n", "line\n".repeat(40));
+        let truncated = truncate_markdown(&code, 20);
+        assert!(truncated.starts_with("```\nline\n"), "{}", truncated);
+        assert!(truncated.ends_with(&format!("line\n```\n{}", TRUNCATION_MARKER)));
+        assert_eq!(truncate_markdown("short", 5), "short");
+    }
+
+    #[test]
+    fn test_document_budget_trims_last_sections_first() {
+        let long = "x".repeat(400);
+        let document = PromptDocument::new()
+            .system("Keep me")
+            .context(long.clone())
+            .examples(long)
+            .with_max_tokens(120);
+
+        let fitted = document.fitted();
+        let names: Vec<_> = fitted.iter().map(|(s, _)| s.name.as_str()).collect();
+        assert_eq!(names, ["system", "context", "examples"]);
+        assert_eq!(fitted[1].1.len(), 400);
+        let total: usize = fitted.iter().map(|(_, c)| estimate_tokens(c)).sum();
+        assert!(total <= 120, "{}", total);
+
+        let document = document.with_max_tokens(105);
+        let fitted = document.fitted();
+        let names: Vec<_> = fitted.iter().map(|(s, _)| s.name.as_str()).collect();
+        assert_eq!(names, ["system", "context"]);
+    }
+
+    #[test]
+    fn test_shift_headings_skips_code() {
+        let content = "# Title\ntext #tag\n```\n# comment\n```\n###### Deep\n#nospace";
+        assert_eq!(
+            shift_headings(content, 2),
+            "### Title\ntext #tag\n```\n# comment\n```\n###### Deep\n#nospace"
+        );
+    }
+}
@@ -3,10 +3,74 @@
 //! Prompt guidance and template system for thulp.
 //!
 //! This crate provides utilities for creating, managing, and rendering
-//! prompt templates for AI agent interactions.
+//! prompt templates for AI agent interactions. Templates can be built in
+//! code or loaded from `.prompt` and `.md` files with YAML frontmatter, see
+//! [`loader`], with workspace-wide variables from [`defaults`]. Templates
+//! can be chained into [`pipeline`]s, where each
+//! render feeds the next, and rendered parts assembled into a budgeted
+//! [`PromptDocument`]. Rendered output can be checked against golden files with
+//! [`snapshot`], and the registry keeps [`usage`] counts for every template.
+//!
+//! ## Template Syntax
+//!
+//! | Syntax | Meaning |
+//! |--------|---------|
+//! | `{{name}}`, `{{user.name}}` | Insert a variable, following dotted paths into JSON values |
+//! | `{{#if var}}...{{else}}...{{/if}}` | Render when `var` is set and not empty, `false`, `0` or null |
+//! | `{{#unless var}}...{{/unless}}` | Render when `var` is missing or empty |
+//! | `{{#each items}}...{{else}}...{{/each}}` | Render once per item; `{{else}}` when there are none |
+//! | `{{this}}`, `{{@index}}`, `{{@first}}`, `{{@last}}`, `{{@key}}` | The current item and its position inside `{{#each}}` |
+//! | `{{> name}}` | Include another template registered in the [`TemplateRegistry`] |
+//! | `{{! note}}`, `{{!-- note --}}` | A comment, left out of the output; the long form may contain `}}` |
+//! | `\{{` | A literal `{{` |
+//!
+//! Fields of the current `{{#each}}` item can also be used directly, so
+//! `{{#each files}}{{path}}{{/each}}` works for a list of objects. Block tags
+//! on a line of their own don't leave blank lines behind.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use thulp_guidance::PromptTemplate;
+//!
+//! let template = PromptTemplate::new(
+//!     "review",
+//!     "Review these files:\n\
+//!      {{#each files}}\n\
+//!      - {{path}}{{#if focus}} (focus: {{focus}}){{/if}}\n\
+//!      {{/each}}\n",
+//! );
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert(
+//!     "files".to_string(),
+//!     json!([{"path": "src/lib.rs", "focus": "errors"}, {"path": "README.md"}]),
+//! );
+//! assert_eq!(
+//!     template.render_values(&vars).unwrap(),
+//!     "Review these files:\n- src/lib.rs (focus: errors)\n- README.md\n"
+//! );
+//! ```
+
+pub mod defaults;
+pub mod document;
+pub mod loader;
+pub mod pipeline;
+mod render;
+pub mod snapshot;
+pub mod usage;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+pub use defaults::GuidanceDefaults;
+pub use document::{ChatMessage, PromptDocument, Role, Section};
+#[cfg(feature = "watch")]
+pub use loader::SharedRegistry;
+pub use pipeline::{Pipeline, PipelineStep, PipelineTransport, Transform};
+pub use usage::{TemplateUsage, UsageReport, UsageStats};
 
 /// Result type for guidance operations
 pub type Result<T> = std::result::Result<T, GuidanceError>;
@@ -22,6 +86,12 @@ pub enum GuidanceError {
 
     #[error("Invalid format: {0}")]
     InvalidFormat(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Watch error: {0}")]
+    Watch(String),
 }
 
 /// A prompt template with variable substitution support
@@ -30,12 +100,62 @@ pub struct PromptTemplate {
     /// Template name
     pub name: String,
 
-    /// Template content with {{variable}} placeholders
+    /// Template content with `{{variable}}` placeholders and blocks
     pub content: String,
 
     /// Default values for variables
     #[serde(default)]
     pub defaults: HashMap<String, String>,
+
+    /// What the template is for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Variables that must be provided or defaulted for rendering
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+
+    /// Notes for template authors, never rendered
+    #[serde(default, skip_serializing_if = "TemplateMetadata::is_empty")]
+    pub metadata: TemplateMetadata,
+}
+
+/// Who owns a template, what it's for and which models it targets.
+///
+/// Set under `metadata` in a template file's frontmatter. Fields beyond the
+/// known ones are kept in [`extra`](Self::extra).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateMetadata {
+    /// Person or team maintaining the template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// Why the template exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+
+    /// Models the template is written for; empty means any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+
+    /// Any other fields
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl TemplateMetadata {
+    /// Check whether no metadata is set
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none()
+            && self.purpose.is_none()
+            && self.models.is_empty()
+            && self.extra.is_empty()
+    }
+
+    /// Check whether the template is meant for `model`
+    pub fn targets(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|m| m == model)
+    }
 }
 
 impl PromptTemplate {
@@ -45,9 +165,30 @@ impl PromptTemplate {
             name: name.into(),
             content: content.into(),
             defaults: HashMap::new(),
+            description: None,
+            required: Vec::new(),
+            metadata: TemplateMetadata::default(),
         }
     }
 
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Require a variable, even where the content only uses it in a block
+    pub fn with_required(mut self, variable: impl Into<String>) -> Self {
+        self.required.push(variable.into());
+        self
+    }
+
+    /// Set the author metadata
+    pub fn with_metadata(mut self, metadata: TemplateMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Set a default value for a variable
     pub fn with_default(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.defaults.insert(key.into(), value.into());
@@ -55,34 +196,50 @@ impl PromptTemplate {
     }
 
     /// Render the template with the given variables
+    ///
+    /// `{{#each}}` iterates a string variable as a JSON array if it holds
+    /// one, otherwise line by line. Including partials requires rendering
+    /// through a [`TemplateRegistry`].
     pub fn render(&self, variables: &HashMap<String, String>) -> Result<String> {
-        let mut result = self.content.clone();
+        self.render_values(&string_values(variables))
+    }
 
+    /// Render the template with JSON variables, for lists and nested data
+    pub fn render_values(&self, variables: &HashMap<String, Value>) -> Result<String> {
+        self.render_with(variables, &|_| None)
+    }
+
+    fn render_with(
+        &self,
+        variables: &HashMap<String, Value>,
+        partials: &render::Partials<'_>,
+    ) -> Result<String> {
         // Merge defaults with provided variables
-        let mut all_vars = self.defaults.clone();
+        let mut all_vars: HashMap<String, Value> = string_values(&self.defaults);
         all_vars.extend(variables.clone());
 
-        // Replace all {{variable}} placeholders
-        for (key, value) in all_vars {
-            let placeholder = format!("{{{{{}}}}}", key);
-            result = result.replace(&placeholder, &value);
-        }
-
-        // Check for unresolved placeholders
-        if result.contains("{{") && result.contains("}}") {
-            return Err(GuidanceError::Template(
-                "Template contains unresolved placeholders".to_string(),
-            ));
+        if let Some(missing) = self.required.iter().find(|v| !all_vars.contains_key(*v)) {
+            return Err(GuidanceError::VariableNotFound(missing.clone()));
         }
 
-        Ok(result)
+        render::render(&self.content, all_vars, partials)
     }
 }
 
-/// A collection of prompt templates
+fn string_values(variables: &HashMap<String, String>) -> HashMap<String, Value> {
+    variables
+        .iter()
+        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+        .collect()
+}
+
+/// A collection of prompt templates and the pipelines that chain them
 #[derive(Debug, Default)]
 pub struct TemplateRegistry {
     templates: HashMap<String, PromptTemplate>,
+    pipelines: HashMap<String, Pipeline>,
+    defaults: GuidanceDefaults,
+    usage: Mutex<UsageStats>,
 }
 
 impl TemplateRegistry {
@@ -91,6 +248,22 @@ impl TemplateRegistry {
         Self::default()
     }
 
+    /// Merge workspace defaults into every render
+    pub fn with_defaults(mut self, defaults: GuidanceDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Replace the workspace defaults
+    pub fn set_defaults(&mut self, defaults: GuidanceDefaults) {
+        self.defaults = defaults;
+    }
+
+    /// Workspace defaults merged into every render
+    pub fn defaults(&self) -> &GuidanceDefaults {
+        &self.defaults
+    }
+
     /// Register a template
     pub fn register(&mut self, template: PromptTemplate) {
         self.templates.insert(template.name.clone(), template);
@@ -102,22 +275,81 @@ impl TemplateRegistry {
     }
 
     /// Render a template by name with variables
+    ///
+    /// `{{> name}}` partials are resolved from the templates in this registry.
     pub fn render(&self, name: &str, variables: &HashMap<String, String>) -> Result<String> {
+        self.render_values(name, &string_values(variables))
+    }
+
+    /// Render a template by name with JSON variables
+    ///
+    /// Workspace [`defaults`](Self::with_defaults) fill in variables that
+    /// aren't given, taking precedence over the template's own defaults.
+    /// The render is counted in the template's [`usage`](Self::usage).
+    pub fn render_values(&self, name: &str, variables: &HashMap<String, Value>) -> Result<String> {
         let template = self
             .get(name)
             .ok_or_else(|| GuidanceError::VariableNotFound(name.to_string()))?;
-        template.render(variables)
+        let mut all_vars = self.defaults.variables_for(name);
+        all_vars.extend(variables.clone());
+        let result = template.render_with(&all_vars, &|partial| self.get(partial));
+        self.usage_stats().record(name, &result);
+        result
     }
 
     /// List all template names
     pub fn list(&self) -> Vec<String> {
         self.templates.keys().cloned().collect()
     }
+
+    /// Usage counts of the templates rendered so far
+    pub fn usage(&self) -> UsageStats {
+        self.usage_stats().clone()
+    }
+
+    /// Take the usage counts so far, starting again from zero
+    pub fn take_usage(&self) -> UsageStats {
+        std::mem::take(&mut *self.usage_stats())
+    }
+
+    /// Report on the usage of every registered template
+    pub fn usage_report(&self) -> UsageReport {
+        self.usage_stats().report(self.templates.keys().cloned())
+    }
+
+    fn usage_stats(&self) -> std::sync::MutexGuard<'_, UsageStats> {
+        // Counts stay usable even if a panic poisoned the lock mid-update
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register a pipeline
+    pub fn register_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipelines.insert(pipeline.name.clone(), pipeline);
+    }
+
+    /// Get a pipeline by name
+    pub fn get_pipeline(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines.get(name)
+    }
+
+    /// Run a pipeline by name, returning its last step's output
+    pub fn run_pipeline(&self, name: &str, inputs: &HashMap<String, Value>) -> Result<Value> {
+        let pipeline = self
+            .get_pipeline(name)
+            .ok_or_else(|| GuidanceError::VariableNotFound(name.to_string()))?;
+        pipeline.run(self, inputs)
+    }
+
+    /// List all pipeline names
+    pub fn list_pipelines(&self) -> Vec<String> {
+        self.pipelines.keys().cloned().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_template_creation() {
@@ -181,4 +413,147 @@ mod tests {
         let result = registry.render("greeting", &vars).unwrap();
         assert_eq!(result, "Hello World!");
     }
+
+    #[test]
+    fn test_comments() {
+        let template = PromptTemplate::new(
+            "test",
+            "{{!-- Keep this short; {{name}} is the user --}}\nHi {{name}}{{! trailing note }}!\n  {{! whole line }}\nBye",
+        );
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "Ann".to_string());
+        assert_eq!(template.render(&vars).unwrap(), "Hi Ann!\nBye");
+
+        let unclosed = PromptTemplate::new("test", "{{!-- never ends }}");
+        assert!(unclosed.render(&HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let template = PromptTemplate::new(
+            "test",
+            "Task: {{task}}\n{{#if context}}\nContext: {{context}}\n{{else}}\nNo context.\n{{/if}}\n{{#unless terse}}Explain your steps.{{/unless}}",
+        );
+
+        let mut vars = HashMap::new();
+        vars.insert("task".to_string(), "fix the build".to_string());
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "Task: fix the build\nNo context.\nExplain your steps."
+        );
+
+        vars.insert("context".to_string(), "CI logs".to_string());
+        vars.insert("terse".to_string(), "yes".to_string());
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "Task: fix the build\nContext: CI logs\n"
+        );
+    }
+
+    #[test]
+    fn test_each() {
+        let template = PromptTemplate::new(
+            "test",
+            "{{#each items}}{{@index}}:{{this}}{{#unless @last}}, {{/unless}}{{else}}none{{/each}}",
+        );
+
+        let mut vars = HashMap::new();
+        vars.insert("items".to_string(), json!(["a", "b", "c"]));
+        assert_eq!(template.render_values(&vars).unwrap(), "0:a, 1:b, 2:c");
+
+        vars.insert("items".to_string(), json!([]));
+        assert_eq!(template.render_values(&vars).unwrap(), "none");
+
+        // String variables iterate as JSON arrays or line by line
+        let mut vars = HashMap::new();
+        vars.insert("items".to_string(), "[1, 2]".to_string());
+        assert_eq!(template.render(&vars).unwrap(), "0:1, 1:2");
+        vars.insert("items".to_string(), "x\n\ny\n".to_string());
+        assert_eq!(template.render(&vars).unwrap(), "0:x, 1:y");
+
+        // Item fields shadow outer variables; others stay visible
+        let template = PromptTemplate::new(
+            "test",
+            "{{#each users}}{{name}}@{{team}}{{#each roles}} {{this}}/{{name}}{{/each}};{{/each}}",
+        );
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), json!("outer"));
+        vars.insert("team".to_string(), json!("core"));
+        vars.insert(
+            "users".to_string(),
+            json!([{"name": "ana", "roles": ["admin"]}, {"name": "bo", "team": "web", "roles": []}]),
+        );
+        assert_eq!(
+            template.render_values(&vars).unwrap(),
+            "ana@core admin/ana;bo@web;"
+        );
+    }
+
+    #[test]
+    fn test_partials() {
+        let mut registry = TemplateRegistry::new();
+        registry.register(
+            PromptTemplate::new("signature", "-- {{author}}").with_default("author", "thulp"),
+        );
+        registry.register(PromptTemplate::new(
+            "note",
+            "{{#each notes}}* {{this}}\n{{/each}}{{> signature}}",
+        ));
+
+        let mut vars = HashMap::new();
+        vars.insert("notes".to_string(), json!(["one", "two"]));
+        assert_eq!(
+            registry.render_values("note", &vars).unwrap(),
+            "* one\n* two\n-- thulp"
+        );
+
+        vars.insert("author".to_string(), json!("ana"));
+        assert!(registry
+            .render_values("note", &vars)
+            .unwrap()
+            .ends_with("-- ana"));
+
+        // Partials need a registry, and must exist
+        let note = registry.get("note").unwrap();
+        assert!(matches!(
+            note.render_values(&vars),
+            Err(GuidanceError::Template(_))
+        ));
+
+        registry.register(PromptTemplate::new("loop", "again {{> loop}}"));
+        let err = registry.render("loop", &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("nested"));
+    }
+
+    #[test]
+    fn test_template_errors() {
+        for content in [
+            "{{#if a}}open",
+            "{{#if a}}x{{/each}}",
+            "{{/if}}",
+            "{{else}}",
+            "{{#with a}}x{{/with}}",
+            "{{#if}}x{{/if}}",
+            "{{#if a}}x{{else}}y{{else}}z{{/if}}",
+            "unclosed {{name",
+        ] {
+            let err = PromptTemplate::new("test", content)
+                .render(&HashMap::new())
+                .unwrap_err();
+            assert!(
+                matches!(err, GuidanceError::Template(_)),
+                "{content}: {err}"
+            );
+        }
+
+        // Missing variables name the variable; escaped braces are literal
+        let err = PromptTemplate::new("test", "Hi {{user.name}}")
+            .render(&HashMap::new())
+            .unwrap_err();
+        assert!(matches!(err, GuidanceError::VariableNotFound(ref name) if name == "user.name"));
+        let literal = PromptTemplate::new("test", "Use \\{{name}} syntax")
+            .render(&HashMap::new())
+            .unwrap();
+        assert_eq!(literal, "Use {{name}} syntax");
+    }
 }
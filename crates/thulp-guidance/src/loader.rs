@@ -0,0 +1,364 @@
+//! Loading templates from files.
+//!
+//! [`TemplateRegistry::load_dir`] reads every `.prompt` and `.md` file below
+//! a directory. A file may start with YAML frontmatter:
+//!
+//! ```text
+//! ---
+//! name: code-review
+//! description: Review a change before merging
+//! required: [language, diff]
+//! defaults:
+//!   tone: friendly
+//! metadata:
+//!   owner: platform-team
+//!   models: [gpt-4o]
+//! ---
+//! Review this {{language}} change in a {{tone}} tone:
+//! {{diff}}
+//! ```
+//!
+//! Without a `name`, a template is named after its path relative to the
+//! directory minus the extension, so `reviews/security.md` becomes
+//! `reviews/security`.
+//!
+//! With the `watch` feature, `TemplateRegistry::watch_dir` also keeps a
+//! shared registry in sync with the directory, so edits are picked up
+//! without a restart.
+
+use crate::{GuidanceError, PromptTemplate, Result, TemplateMetadata, TemplateRegistry};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File extensions loaded as templates.
+pub const TEMPLATE_EXTENSIONS: &[&str] = &["prompt", "md"];
+
+const FRONTMATTER_DELIMITER: &str = "---";
+
+/// Metadata at the top of a template file.
+#[derive(Debug, Default, Deserialize)]
+struct Frontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(default)]
+    defaults: HashMap<String, String>,
+    #[serde(default)]
+    metadata: TemplateMetadata,
+}
+
+impl PromptTemplate {
+    /// Parse template source with optional YAML frontmatter.
+    ///
+    /// `default_name` is used unless the frontmatter sets a `name`.
+    pub fn parse(default_name: impl Into<String>, source: &str) -> Result<Self> {
+        let (frontmatter, body) = split_frontmatter(source)?;
+        Ok(Self {
+            name: frontmatter.name.unwrap_or_else(|| default_name.into()),
+            content: body.to_string(),
+            defaults: frontmatter.defaults,
+            description: frontmatter.description,
+            required: frontmatter.required,
+            metadata: frontmatter.metadata,
+        })
+    }
+
+    /// Read a template file, named after its file stem unless the
+    /// frontmatter sets a `name`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(stem, &source).map_err(|e| in_file(path, e))
+    }
+}
+
+impl TemplateRegistry {
+    /// Load all templates below a directory.
+    ///
+    /// Fails if a file can't be read or parsed, or if two files define a
+    /// template with the same name.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut registry = Self::new();
+        let mut sources: HashMap<String, PathBuf> = HashMap::new();
+
+        for path in template_files(dir)? {
+            let name = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let source = std::fs::read_to_string(&path)?;
+            let template = PromptTemplate::parse(name, &source).map_err(|e| in_file(&path, e))?;
+
+            if let Some(previous) = sources.insert(template.name.clone(), path.clone()) {
+                return Err(GuidanceError::InvalidFormat(format!(
+                    "Template '{}' is defined in both {} and {}",
+                    template.name,
+                    previous.display(),
+                    path.display()
+                )));
+            }
+            registry.register(template);
+        }
+
+        Ok(registry)
+    }
+}
+
+/// Prefix an error with the file it came from.
+fn in_file(path: &Path, error: GuidanceError) -> GuidanceError {
+    match error {
+        GuidanceError::InvalidFormat(message) => {
+            GuidanceError::InvalidFormat(format!("{}: {}", path.display(), message))
+        }
+        other => other,
+    }
+}
+
+/// Split source into frontmatter and body.
+fn split_frontmatter(source: &str) -> Result<(Frontmatter, &str)> {
+    let Some(rest) = source.strip_prefix(FRONTMATTER_DELIMITER).and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) else {
+        return Ok((Frontmatter::default(), source));
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONTMATTER_DELIMITER {
+            let yaml = &rest[..offset];
+            let frontmatter = if yaml.trim().is_empty() {
+                Frontmatter::default()
+            } else {
+                serde_yaml::from_str(yaml)
+                    .map_err(|e| GuidanceError::InvalidFormat(format!("frontmatter: {}", e)))?
+            };
+            return Ok((frontmatter, &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    Err(GuidanceError::InvalidFormat(
+        "Missing closing frontmatter delimiter".to_string(),
+    ))
+}
+
+/// Template files below a directory, sorted by path.
+fn template_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| TEMPLATE_EXTENSIONS.contains(&ext))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(feature = "watch")]
+mod watch {
+    use super::template_files;
+    use crate::{GuidanceError, Result, TemplateRegistry};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, RwLock};
+    use std::time::{Duration, SystemTime};
+    use thulp_core::ThulpRuntime;
+
+    /// A registry kept up to date by [`TemplateRegistry::watch_dir`].
+    pub type SharedRegistry = Arc<RwLock<TemplateRegistry>>;
+
+    impl TemplateRegistry {
+        /// Load a directory and keep the returned registry in sync with it.
+        ///
+        /// A task spawned on `runtime` checks the directory every `interval`
+        /// and reloads all templates when a file is added, removed or
+        /// modified. If a reload fails, for example on a half-written file,
+        /// the previous templates stay in place and a warning is logged. The
+        /// task stops when the runtime shuts down.
+        pub fn watch_dir(
+            dir: impl Into<PathBuf>,
+            runtime: &ThulpRuntime,
+            interval: Duration,
+        ) -> Result<SharedRegistry> {
+            let dir = dir.into();
+            let registry = Arc::new(RwLock::new(Self::load_dir(&dir)?));
+            let mut seen = fingerprint(&dir);
+
+            let shared = registry.clone();
+            runtime
+                .spawn("template-watcher", move |mut shutdown| async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    ticker.tick().await;
+                    loop {
+                        tokio::select! {
+                            _ = shutdown.wait() => break,
+                            _ = ticker.tick() => {}
+                        }
+                        let current = fingerprint(&dir);
+                        if current == seen {
+                            continue;
+                        }
+                        seen = current;
+                        match Self::load_dir(&dir) {
+                            Ok(reloaded) => {
+                                tracing::info!(dir = ?dir, templates = reloaded.list().len(), "Reloaded templates");
+                                *shared.write().unwrap() = reloaded;
+                            }
+                            Err(e) => {
+                                tracing::warn!(dir = ?dir, error = %e, "Failed to reload templates; keeping previous ones");
+                            }
+                        }
+                    }
+                })
+                .map_err(|e| GuidanceError::Watch(e.to_string()))?;
+
+            Ok(registry)
+        }
+    }
+
+    /// Paths, sizes and modification times of the template files.
+    fn fingerprint(dir: &Path) -> Vec<(PathBuf, u64, Option<SystemTime>)> {
+        template_files(dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(&path).ok();
+                let len = metadata.as_ref().map_or(0, |m| m.len());
+                let modified = metadata.and_then(|m| m.modified().ok());
+                (path, len, modified)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "watch")]
+pub use watch::SharedRegistry;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_parse_frontmatter() {
+        let template = PromptTemplate::parse(
+            "fallback",
+            "---\nname: review\ndescription: Review code\nrequired: [diff]\ndefaults:\n  tone: kind\n---\nBe {{tone}}: {{diff}}",
+        )
+        .unwrap();
+        assert_eq!(template.name, "review");
+        assert_eq!(template.description.as_deref(), Some("Review code"));
+        assert_eq!(template.required, vec!["diff".to_string()]);
+        assert_eq!(template.content, "Be {{tone}}: {{diff}}");
+
+        let err = template.render(&HashMap::new()).unwrap_err();
+        assert!(matches!(err, GuidanceError::VariableNotFound(ref name) if name == "diff"));
+
+        let plain = PromptTemplate::parse("fallback", "Just {{text}}\n---\n").unwrap();
+        assert_eq!(plain.name, "fallback");
+        assert_eq!(plain.content, "Just {{text}}\n---\n");
+
+        assert!(template.metadata.is_empty());
+
+        let template = PromptTemplate::parse(
+            "review",
+            "---\nmetadata:\n  owner: platform\n  purpose: PR review\n  models: [gpt-4o]\n  ticket: 42\n---\nReview it",
+        )
+        .unwrap();
+        assert_eq!(template.metadata.owner.as_deref(), Some("platform"));
+        assert_eq!(template.metadata.purpose.as_deref(), Some("PR review"));
+        assert!(template.metadata.targets("gpt-4o"));
+        assert!(!template.metadata.targets("llama-3"));
+        assert_eq!(template.metadata.extra["ticket"], 42);
+        assert_eq!(template.render(&HashMap::new()).unwrap(), "Review it");
+
+        assert!(PromptTemplate::parse("x", "---\nname: x\nno end").is_err());
+        assert!(PromptTemplate::parse("x", "---\nrequired: {a\n---\n").is_err());
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "greeting.prompt", "Hello {{name}}!");
+        write(
+            temp.path(),
+            "reviews/security.md",
+            "---\ndescription: Security review\n---\nCheck {{> greeting}}",
+        );
+        write(temp.path(), "notes.txt", "not a template");
+
+        let registry = TemplateRegistry::load_dir(temp.path()).unwrap();
+        let mut names = registry.list();
+        names.sort();
+        assert_eq!(names, vec!["greeting", "reviews/security"]);
+
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "auth.rs".to_string());
+        assert_eq!(
+            registry.render("reviews/security", &vars).unwrap(),
+            "Check Hello auth.rs!"
+        );
+
+        write(temp.path(), "other.md", "---\nname: greeting\n---\nHi");
+        let err = TemplateRegistry::load_dir(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("defined in both"));
+    }
+
+    #[cfg(feature = "watch")]
+    #[tokio::test]
+    async fn test_watch_dir_reloads() {
+        use std::time::Duration;
+        use thulp_core::ThulpRuntime;
+
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "greeting.prompt", "Hello");
+
+        let runtime = ThulpRuntime::new();
+        let registry =
+            TemplateRegistry::watch_dir(temp.path(), &runtime, Duration::from_millis(10)).unwrap();
+        let render = || registry.read().unwrap().render("greeting", &HashMap::new());
+        assert_eq!(render().unwrap(), "Hello");
+
+        write(temp.path(), "greeting.prompt", "Hello again");
+        for _ in 0..100 {
+            if render().unwrap() != "Hello" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(render().unwrap(), "Hello again");
+
+        // A broken edit keeps the last good version
+        write(temp.path(), "greeting.prompt", "---\nname: [broken\n---\n");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(render().unwrap(), "Hello again");
+
+        assert!(runtime.shutdown(Duration::from_secs(1)).await.is_clean());
+    }
+}
@@ -0,0 +1,477 @@
+//! Chaining template renders into pipelines.
+//!
+//! A [`Pipeline`] renders templates one after another. Each step's output,
+//! after its [`Transform`]s, is stored as a variable that later steps can
+//! use, and the last step's output is the pipeline's result:
+//!
+//! ```yaml
+//! name: review-summary
+//! description: Review a diff, then summarize the findings
+//! inputs:
+//!   - name: diff
+//!     type: string
+//!     required: true
+//! steps:
+//!   - template: code-review
+//!     output: review
+//!     transforms: [trim]
+//!   - template: summarize
+//!     variables:
+//!       max_points: 3
+//!     transforms: [trim, lines]
+//! ```
+//!
+//! Pipelines are registered in a [`TemplateRegistry`] next to the
+//! templates they render. [`PipelineTransport`] exposes each registered
+//! pipeline as a tool, so a skill step can run a whole pipeline by name.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use thulp_guidance::pipeline::{Pipeline, PipelineStep, Transform};
+//! use thulp_guidance::{PromptTemplate, TemplateRegistry};
+//!
+//! let mut registry = TemplateRegistry::new();
+//! registry.register(PromptTemplate::new("topic", "  {{subject}} in depth  "));
+//! registry.register(PromptTemplate::new("outline", "Outline: {{topic}}"));
+//! registry.register_pipeline(
+//!     Pipeline::new("plan")
+//!         .with_step(PipelineStep::new("topic").with_transform(Transform::Trim))
+//!         .with_step(PipelineStep::new("outline")),
+//! );
+//!
+//! let mut inputs = HashMap::new();
+//! inputs.insert("subject".to_string(), json!("Rust"));
+//! let output = registry.run_pipeline("plan", &inputs).unwrap();
+//! assert_eq!(output, json!("Outline: Rust in depth"));
+//! ```
+
+use crate::{GuidanceError, Result, TemplateRegistry};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use thulp_core::{Parameter, ToolCall, ToolDefinition, ToolResult, Transport};
+
+/// Templates rendered in sequence, each step feeding the next
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    /// Pipeline name, also the tool name in [`PipelineTransport`]
+    pub name: String,
+
+    /// What the pipeline produces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Variables callers pass in, checked before the first step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<Parameter>,
+
+    /// Steps in the order they run
+    #[serde(default)]
+    pub steps: Vec<PipelineStep>,
+}
+
+/// One template render in a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStep {
+    /// Template to render
+    pub template: String,
+
+    /// Variable the output is stored as (default: the template name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+
+    /// Fixed variables for this step, overriding inputs and earlier outputs
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, Value>,
+
+    /// Transformations applied to the rendered text, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<Transform>,
+}
+
+/// A transformation of a step's output
+///
+/// In YAML, transforms without arguments are written as plain names
+/// (`trim`), the others as single-key maps (`truncate: 200`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// Strip leading and trailing whitespace
+    Trim,
+    /// Convert to lowercase
+    Lowercase,
+    /// Convert to uppercase
+    Uppercase,
+    /// Split into a list of non-blank, trimmed lines
+    Lines,
+    /// Parse the text as JSON
+    Json,
+    /// Keep at most this many characters
+    Truncate(usize),
+    /// Replace every occurrence of a string
+    Replace {
+        /// Text to find
+        from: String,
+        /// Replacement
+        to: String,
+    },
+}
+
+impl Transform {
+    /// Apply the transformation to a value.
+    ///
+    /// Every transform works on text; applying one after `lines` or `json`
+    /// fails unless that produced a string.
+    pub fn apply(&self, value: Value) -> Result<Value> {
+        let Value::String(text) = value else {
+            return Err(GuidanceError::InvalidFormat(format!(
+                "transform '{}' expects text, got {}",
+                self.name(),
+                value
+            )));
+        };
+        Ok(match self {
+            Self::Trim => Value::String(text.trim().to_string()),
+            Self::Lowercase => Value::String(text.to_lowercase()),
+            Self::Uppercase => Value::String(text.to_uppercase()),
+            Self::Lines => Value::Array(
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| Value::String(line.to_string()))
+                    .collect(),
+            ),
+            Self::Json => serde_json::from_str(text.trim()).map_err(|e| {
+                GuidanceError::InvalidFormat(format!("output is not valid JSON: {}", e))
+            })?,
+            Self::Truncate(max_chars) => Value::String(text.chars().take(*max_chars).collect()),
+            Self::Replace { from, to } => Value::String(text.replace(from.as_str(), to)),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Trim => "trim",
+            Self::Lowercase => "lowercase",
+            Self::Uppercase => "uppercase",
+            Self::Lines => "lines",
+            Self::Json => "json",
+            Self::Truncate(_) => "truncate",
+            Self::Replace { .. } => "replace",
+        }
+    }
+}
+
+impl PipelineStep {
+    /// Create a step rendering a template
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            output: None,
+            variables: HashMap::new(),
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Store the output under a different variable name
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    /// Set a fixed variable for this step
+    pub fn with_variable(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.variables.insert(key.into(), value);
+        self
+    }
+
+    /// Add a transformation of the output
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Variable the output is stored as
+    pub fn output_name(&self) -> &str {
+        self.output.as_deref().unwrap_or(&self.template)
+    }
+}
+
+impl Pipeline {
+    /// Create an empty pipeline
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            inputs: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Declare an input variable
+    pub fn with_input(mut self, input: Parameter) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Append a step
+    pub fn with_step(mut self, step: PipelineStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Parse a pipeline definition from YAML or JSON.
+    pub fn parse(source: &str) -> Result<Self> {
+        // Going through a JSON value lets transforms with arguments be
+        // written as plain maps rather than YAML tags
+        let value: Value = serde_yaml::from_str(source)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("pipeline: {}", e)))?;
+        serde_json::from_value(value)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("pipeline: {}", e)))
+    }
+
+    /// Read a pipeline definition file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| match e {
+            GuidanceError::InvalidFormat(message) => {
+                GuidanceError::InvalidFormat(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Tool definition describing how to call the pipeline.
+    pub fn definition(&self) -> ToolDefinition {
+        ToolDefinition::builder(&self.name)
+            .description(self.description.clone().unwrap_or_default())
+            .parameters(self.inputs.iter().cloned())
+            .build()
+    }
+
+    /// Run the pipeline against the templates in `registry`.
+    ///
+    /// Inputs are checked against [`inputs`](Self::inputs) and missing ones
+    /// filled from their defaults. Returns the last step's output.
+    pub fn run(
+        &self,
+        registry: &TemplateRegistry,
+        inputs: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        if self.steps.is_empty() {
+            return Err(GuidanceError::InvalidFormat(format!(
+                "Pipeline '{}' has no steps",
+                self.name
+            )));
+        }
+        let args = Value::Object(inputs.clone().into_iter().collect());
+        self.definition()
+            .validate_args(&args)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("{}: {}", self.name, e)))?;
+
+        let mut variables = inputs.clone();
+        for input in &self.inputs {
+            if let Some(default) = &input.default {
+                variables
+                    .entry(input.name.clone())
+                    .or_insert_with(|| default.clone());
+            }
+        }
+
+        let mut output = Value::Null;
+        for step in &self.steps {
+            let mut step_variables = variables.clone();
+            step_variables.extend(step.variables.clone());
+            let rendered = registry
+                .render_values(&step.template, &step_variables)
+                .map_err(|e| in_step(&self.name, step, e))?;
+            output = step
+                .transforms
+                .iter()
+                .try_fold(Value::String(rendered), |value, transform| {
+                    transform.apply(value)
+                })
+                .map_err(|e| in_step(&self.name, step, e))?;
+            variables.insert(step.output_name().to_string(), output.clone());
+        }
+        Ok(output)
+    }
+}
+
+/// Prefix an error with the step it came from.
+fn in_step(pipeline: &str, step: &PipelineStep, error: GuidanceError) -> GuidanceError {
+    let context = format!("pipeline '{}', step '{}'", pipeline, step.template);
+    match error {
+        GuidanceError::Template(message) => {
+            GuidanceError::Template(format!("{}: {}", context, message))
+        }
+        GuidanceError::InvalidFormat(message) => {
+            GuidanceError::InvalidFormat(format!("{}: {}", context, message))
+        }
+        other => other,
+    }
+}
+
+/// Exposes the pipelines of a registry as tools
+///
+/// Each pipeline is listed as a tool named after it, with its inputs as
+/// parameters. A call runs the pipeline with the call's arguments and
+/// returns its output; a failing pipeline is reported as a failed
+/// [`ToolResult`]. Combine it with other transports through a
+/// [`MultiplexTransport`](thulp_core::MultiplexTransport) to let skills mix
+/// pipelines and tools.
+#[derive(Debug, Clone)]
+pub struct PipelineTransport {
+    registry: Arc<TemplateRegistry>,
+}
+
+impl PipelineTransport {
+    /// Serve the pipelines of a registry
+    pub fn new(registry: impl Into<Arc<TemplateRegistry>>) -> Self {
+        Self {
+            registry: registry.into(),
+        }
+    }
+
+    /// The registry pipelines are run against
+    pub fn registry(&self) -> &TemplateRegistry {
+        &self.registry
+    }
+}
+
+#[async_trait]
+impl Transport for PipelineTransport {
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        let mut names = self.registry.list_pipelines();
+        names.sort();
+        Ok(names
+            .iter()
+            .filter_map(|name| self.registry.get_pipeline(name))
+            .map(Pipeline::definition)
+            .collect())
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        if self.registry.get_pipeline(&call.tool).is_none() {
+            return Err(thulp_core::Error::ToolNotFound(call.tool.clone()));
+        }
+        let inputs = match &call.arguments {
+            Value::Object(args) => args.clone().into_iter().collect(),
+            Value::Null => HashMap::new(),
+            other => {
+                return Ok(ToolResult::failure(format!(
+                    "pipeline arguments must be an object, got {}",
+                    other
+                )))
+            }
+        };
+        Ok(match self.registry.run_pipeline(&call.tool, &inputs) {
+            Ok(output) => ToolResult::success(output),
+            Err(e) => ToolResult::failure(e.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PromptTemplate;
+    use serde_json::json;
+
+    fn registry() -> TemplateRegistry {
+        let mut registry = TemplateRegistry::new();
+        registry.register(PromptTemplate::new(
+            "findings",
+            "{{#each issues}}\n- {{this}}\n{{/each}}\n",
+        ));
+        registry.register(PromptTemplate::new(
+            "summary",
+            "{{count}} of:{{#each findings}} [{{this}}]{{/each}}",
+        ));
+        registry.register_pipeline(
+            Pipeline::parse(
+                "name: review\n\
+                 inputs:\n  - {name: issues, type: array, required: true}\n\
+                 steps:\n\
+                 \x20 - template: findings\n\
+                 \x20   transforms: [uppercase, lines]\n\
+                 \x20 - template: summary\n\
+                 \x20   variables: {count: 2}\n\
+                 \x20   transforms: [{replace: {from: '[', to: '('}}, {truncate: 12}]\n",
+            )
+            .unwrap(),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_pipeline_chains_steps() {
+        let registry = registry();
+        let mut inputs = HashMap::new();
+        inputs.insert("issues".to_string(), json!(["unwrap", "todo"]));
+        assert_eq!(
+            registry.run_pipeline("review", &inputs).unwrap(),
+            json!("2 of: (- UNW")
+        );
+
+        // Inputs are checked before anything renders
+        let err = registry
+            .run_pipeline("review", &HashMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("issues"), "{}", err);
+
+        let failing = Pipeline::new("bad")
+            .with_step(PipelineStep::new("findings").with_transform(Transform::Json));
+        let err = failing.run(&registry, &inputs).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("pipeline 'bad', step 'findings': output is not valid JSON"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_transport() {
+        let transport = PipelineTransport::new(registry());
+        let tools = transport.list_tools().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert!(tools[0].get_parameter("issues").unwrap().required);
+
+        let result = transport
+            .call(&ToolCall::with_args("review", json!({"issues": ["a"]})))
+            .await
+            .unwrap();
+        assert_eq!(result.data, Some(json!("2 of: (- A]")));
+
+        let result = transport
+            .call(&ToolCall::with_args("review", json!({"issues": "a"})))
+            .await
+            .unwrap();
+        assert!(!result.is_success());
+        assert!(transport.call(&ToolCall::new("missing")).await.is_err());
+    }
+}
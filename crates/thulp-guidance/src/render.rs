@@ -0,0 +1,456 @@
+//! Parsing and rendering of template content.
+//!
+//! Templates are tokenized into alternating text and `{{ ... }}` tags, parsed
+//! into a tree of blocks, then rendered against a set of JSON variables. A
+//! block tag alone on its line takes the line with it, so blocks can be laid
+//! out on their own lines without leaving blank lines in the output.
+//! Comments are dropped, and one alone on its line takes the line too.
+
+use crate::{GuidanceError, PromptTemplate, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// How deeply partials may include each other before rendering gives up.
+const MAX_PARTIAL_DEPTH: usize = 16;
+
+/// Looks up partials by name.
+pub(crate) type Partials<'a> = dyn Fn(&str) -> Option<&'a PromptTemplate> + 'a;
+
+/// Render template content.
+pub(crate) fn render(
+    content: &str,
+    variables: HashMap<String, Value>,
+    partials: &Partials<'_>,
+) -> Result<String> {
+    let nodes = parse(content)?;
+    let mut renderer = Renderer {
+        root: variables,
+        frames: Vec::new(),
+        partials,
+        depth: 0,
+    };
+    let mut output = String::with_capacity(content.len());
+    renderer.render_nodes(&nodes, &mut output)?;
+    Ok(output)
+}
+
+fn template_error(message: impl Into<String>) -> GuidanceError {
+    GuidanceError::Template(message.into())
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+#[derive(Debug)]
+enum Token<'a> {
+    Text(String),
+    Tag(&'a str),
+}
+
+#[derive(Debug)]
+enum Node<'a> {
+    Text(String),
+    Var(&'a str),
+    If {
+        path: &'a str,
+        negate: bool,
+        then: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+    },
+    Each {
+        path: &'a str,
+        body: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+    },
+    Partial(&'a str),
+}
+
+/// What ended a run of nodes.
+enum End<'a> {
+    Eof,
+    Else,
+    Close(&'a str),
+}
+
+fn parse(content: &str) -> Result<Vec<Node<'_>>> {
+    let mut tokens = tokenize(content)?.into_iter();
+    match parse_nodes(&mut tokens)? {
+        (nodes, End::Eof) => Ok(nodes),
+        (_, End::Else) => Err(template_error("'{{else}}' outside of a block")),
+        (_, End::Close(name)) => Err(template_error(format!(
+            "'{{{{/{}}}}}' without a matching block",
+            name
+        ))),
+    }
+}
+
+/// Split content into text and tags. The result always alternates text and
+/// tags, starting and ending with (possibly empty) text. `\{{` is a literal
+/// `{{`, and a `{{!-- --}}` comment ends at the first `--}}`.
+fn tokenize(content: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = content;
+    while let Some(open) = rest.find("{{") {
+        if rest[..open].ends_with('\\') {
+            text.push_str(&rest[..open - 1]);
+            text.push_str("{{");
+            rest = &rest[open + 2..];
+            continue;
+        }
+        text.push_str(&rest[..open]);
+        let inner = &rest[open + 2..];
+        // A `{{!-- --}}` comment may itself contain `}}`
+        let close = if inner.starts_with("!--") {
+            inner.find("--}}").map(|close| close + 2)
+        } else {
+            inner.find("}}")
+        };
+        let close = close.ok_or_else(|| {
+            template_error(format!(
+                "Unclosed '{{{{' at byte {}",
+                content.len() - rest.len() + open
+            ))
+        })?;
+        tokens.push(Token::Text(std::mem::take(&mut text)));
+        tokens.push(Token::Tag(inner[..close].trim()));
+        rest = &inner[close + 2..];
+    }
+    text.push_str(rest);
+    tokens.push(Token::Text(text));
+
+    strip_standalone_lines(&mut tokens);
+    Ok(tokens)
+}
+
+/// Remove the lines of block tags and comments that stand alone on their
+/// line.
+fn strip_standalone_lines(tokens: &mut [Token<'_>]) {
+    let last = tokens.len() - 1;
+    let text = |token: &Token<'_>| match token {
+        Token::Text(text) => text.clone(),
+        Token::Tag(_) => String::new(),
+    };
+    let standalone: Vec<bool> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| match token {
+            Token::Tag(tag) if tag.starts_with(['#', '/', '!']) || *tag == "else" => {
+                let before = text(&tokens[i - 1]);
+                let after = text(&tokens[i + 1]);
+                let line_start = before.rsplit('\n').next().unwrap_or_default();
+                let line_end = after.split('\n').next().unwrap_or_default();
+                line_start.trim().is_empty()
+                    && (before.contains('\n') || i == 1)
+                    && line_end.trim().is_empty()
+                    && (after.contains('\n') || i + 1 == last)
+            }
+            _ => false,
+        })
+        .collect();
+
+    for i in (0..tokens.len()).step_by(2) {
+        let Token::Text(text) = &mut tokens[i] else {
+            continue;
+        };
+        let start = if i > 0 && standalone[i - 1] {
+            text.find('\n').map_or(text.len(), |n| n + 1)
+        } else {
+            0
+        };
+        let end = if i < last && standalone[i + 1] {
+            text.rfind('\n').map_or(0, |n| n + 1)
+        } else {
+            text.len()
+        };
+        *text = if start < end {
+            text[start..end].to_string()
+        } else {
+            String::new()
+        };
+    }
+}
+
+fn parse_nodes<'a>(tokens: &mut std::vec::IntoIter<Token<'a>>) -> Result<(Vec<Node<'a>>, End<'a>)> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text));
+                }
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        if tag.starts_with('!') {
+            // Comments are for template authors only
+            continue;
+        }
+
+        if let Some(block) = tag.strip_prefix('#') {
+            let (helper, path) = block
+                .split_once(char::is_whitespace)
+                .map_or((block, ""), |(helper, path)| (helper, path.trim()));
+            if !matches!(helper, "if" | "unless" | "each") {
+                return Err(template_error(format!(
+                    "Unknown block '{{{{#{}}}}}'",
+                    helper
+                )));
+            }
+            if path.is_empty() {
+                return Err(template_error(format!(
+                    "'{{{{#{}}}}}' needs a variable",
+                    helper
+                )));
+            }
+
+            let (body, end) = parse_nodes(tokens)?;
+            let (otherwise, end) = match end {
+                End::Else => parse_nodes(tokens)?,
+                end => (Vec::new(), end),
+            };
+            match end {
+                End::Close(name) if name == helper => {}
+                End::Close(name) => {
+                    return Err(template_error(format!(
+                        "'{{{{#{} {}}}}}' closed by '{{{{/{}}}}}'",
+                        helper, path, name
+                    )))
+                }
+                End::Else => {
+                    return Err(template_error(format!(
+                        "'{{{{#{} {}}}}}' has more than one '{{{{else}}}}'",
+                        helper, path
+                    )))
+                }
+                End::Eof => {
+                    return Err(template_error(format!(
+                        "'{{{{#{} {}}}}}' is never closed",
+                        helper, path
+                    )))
+                }
+            }
+
+            nodes.push(match helper {
+                "each" => Node::Each {
+                    path,
+                    body,
+                    otherwise,
+                },
+                _ => Node::If {
+                    path,
+                    negate: helper == "unless",
+                    then: body,
+                    otherwise,
+                },
+            });
+        } else if let Some(name) = tag.strip_prefix('/') {
+            return Ok((nodes, End::Close(name.trim())));
+        } else if tag == "else" {
+            return Ok((nodes, End::Else));
+        } else if let Some(name) = tag.strip_prefix('>') {
+            nodes.push(Node::Partial(name.trim()));
+        } else {
+            nodes.push(Node::Var(tag));
+        }
+    }
+    Ok((nodes, End::Eof))
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// The item of an enclosing `{{#each}}` block.
+#[derive(Clone)]
+struct Frame {
+    item: Value,
+    key: Option<String>,
+    index: usize,
+    len: usize,
+}
+
+struct Renderer<'r, 'p> {
+    root: HashMap<String, Value>,
+    frames: Vec<Frame>,
+    partials: &'r Partials<'p>,
+    depth: usize,
+}
+
+impl Renderer<'_, '_> {
+    fn render_nodes(&mut self, nodes: &[Node<'_>], output: &mut String) -> Result<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Var(path) => {
+                    let value = self
+                        .lookup(path)
+                        .ok_or_else(|| GuidanceError::VariableNotFound(path.to_string()))?;
+                    output.push_str(&to_text(&value));
+                }
+                Node::If {
+                    path,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if is_truthy(self.lookup(path).as_ref()) != *negate {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.render_nodes(branch, output)?;
+                }
+                Node::Each {
+                    path,
+                    body,
+                    otherwise,
+                } => {
+                    let items = self.lookup(path).map(into_items).unwrap_or_default();
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, output)?;
+                        continue;
+                    }
+                    let len = items.len();
+                    for (index, (key, item)) in items.into_iter().enumerate() {
+                        self.frames.push(Frame {
+                            item,
+                            key,
+                            index,
+                            len,
+                        });
+                        let rendered = self.render_nodes(body, output);
+                        self.frames.pop();
+                        rendered?;
+                    }
+                }
+                Node::Partial(name) => self.render_partial(name, output)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn render_partial(&mut self, name: &str, output: &mut String) -> Result<()> {
+        if self.depth >= MAX_PARTIAL_DEPTH {
+            return Err(template_error(format!(
+                "Partial '{}' nested more than {} levels deep; do partials include each other?",
+                name, MAX_PARTIAL_DEPTH
+            )));
+        }
+        let partial = (self.partials)(name)
+            .ok_or_else(|| template_error(format!("Partial '{}' not found", name)))?;
+        let nodes = parse(&partial.content)?;
+
+        // The partial's own defaults sit below the variables in scope
+        let mut root: HashMap<String, Value> = partial
+            .defaults
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        root.extend(self.root.clone());
+        let mut renderer = Renderer {
+            root,
+            frames: self.frames.clone(),
+            partials: self.partials,
+            depth: self.depth + 1,
+        };
+        renderer.render_nodes(&nodes, output)
+    }
+
+    /// Resolve a variable path against the enclosing `{{#each}}` items,
+    /// innermost first, then the template variables.
+    fn lookup(&self, path: &str) -> Option<Value> {
+        if let Some(local) = path.strip_prefix('@') {
+            let frame = self.frames.last()?;
+            return match local {
+                "index" => Some(Value::from(frame.index)),
+                "first" => Some(Value::Bool(frame.index == 0)),
+                "last" => Some(Value::Bool(frame.index + 1 == frame.len)),
+                "key" => frame.key.clone().map(Value::String),
+                _ => None,
+            };
+        }
+        if path == "this" {
+            return self.frames.last().map(|frame| frame.item.clone());
+        }
+        if let Some(rest) = path.strip_prefix("this.") {
+            return traverse(&self.frames.last()?.item, rest);
+        }
+
+        let (first, rest) = path.split_once('.').unwrap_or((path, ""));
+        let scoped = self
+            .frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.item.as_object()?.get(first));
+        let value = match scoped {
+            Some(value) => value,
+            // A variable whose name contains dots wins over path traversal
+            None => match self.root.get(path) {
+                Some(value) => return Some(value.clone()),
+                None => self.root.get(first)?,
+            },
+        };
+        if rest.is_empty() {
+            Some(value.clone())
+        } else {
+            traverse(value, rest)
+        }
+    }
+}
+
+/// Follow a dotted path into a value.
+fn traverse(value: &Value, path: &str) -> Option<Value> {
+    let mut current = value;
+    for segment in path.split('.') {
+        current = match current {
+            Value::Object(obj) => obj.get(segment)?,
+            Value::Array(arr) => arr.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current.clone())
+}
+
+/// Items iterated by `{{#each}}`, with their keys for objects.
+///
+/// A string holding a JSON array is iterated as that array; any other
+/// string is iterated line by line, skipping blank lines.
+fn into_items(value: Value) -> Vec<(Option<String>, Value)> {
+    match value {
+        Value::Array(items) => items.into_iter().map(|item| (None, item)).collect(),
+        Value::Object(obj) => obj.into_iter().map(|(k, v)| (Some(k), v)).collect(),
+        Value::String(s) => match serde_json::from_str::<Vec<Value>>(&s) {
+            Ok(items) => items.into_iter().map(|item| (None, item)).collect(),
+            Err(_) => s
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| (None, Value::String(line.to_string())))
+                .collect(),
+        },
+        Value::Null => Vec::new(),
+        other => vec![(None, other)],
+    }
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(arr)) => !arr.is_empty(),
+        Some(Value::Object(obj)) => !obj.is_empty(),
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,449 @@
+//! Snapshot tests for templates.
+//!
+//! A fixture file lists variable sets to render a template with:
+//!
+//! ```yaml
+//! template: code-review
+//! cases:
+//!   rust:
+//!     language: rust
+//!     diff: "fn main() {}"
+//!   no-diff:
+//!     language: go
+//! ```
+//!
+//! [`SnapshotTester`] renders each case and compares the output with a
+//! golden file at `<snapshot dir>/<template>/<case>.snap`. A mismatch is
+//! reported with a line diff; in update mode the golden file is rewritten
+//! instead, which is also how new cases get their first snapshot. Run it
+//! from a test so template changes that alter prompts show up in CI:
+//!
+//! ```rust,no_run
+//! use thulp_guidance::snapshot::{load_cases, SnapshotTester};
+//! use thulp_guidance::TemplateRegistry;
+//!
+//! let registry = TemplateRegistry::load_dir(".thulp/prompts").unwrap();
+//! let cases = load_cases(".thulp/prompts/tests").unwrap();
+//! let report = SnapshotTester::new(&registry, ".thulp/prompts/tests/snapshots")
+//!     .update(std::env::var("UPDATE_SNAPSHOTS").is_ok())
+//!     .run(&cases)
+//!     .unwrap();
+//! assert!(report.is_success(), "{}", report);
+//! ```
+//!
+//! `thulp guidance test` does the same for a workspace's templates.
+
+use crate::{GuidanceError, Result, TemplateRegistry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Extension of golden output files
+pub const SNAPSHOT_EXTENSION: &str = "snap";
+
+/// Unchanged lines shown around each change in a diff
+const DIFF_CONTEXT: usize = 2;
+
+/// A template rendered with one set of variables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotCase {
+    /// Template to render
+    pub template: String,
+    /// Case name, used for the snapshot file name
+    pub name: String,
+    /// Variables to render with
+    #[serde(default)]
+    pub variables: HashMap<String, Value>,
+}
+
+impl SnapshotCase {
+    /// Create a case with no variables
+    pub fn new(template: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            name: name.into(),
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Set a variable
+    pub fn with_variable(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.variables.insert(key.into(), value);
+        self
+    }
+}
+
+/// A fixture file: one template and its cases by name
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    template: String,
+    #[serde(default)]
+    cases: BTreeMap<String, HashMap<String, Value>>,
+}
+
+/// Load the cases of every `.yaml`, `.yml` and `.json` fixture file in a
+/// directory, sorted by file name.
+pub fn load_cases(dir: impl AsRef<Path>) -> Result<Vec<SnapshotCase>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir.as_ref())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| matches!(ext, "yaml" | "yml" | "json"))
+        })
+        .collect();
+    files.sort();
+
+    let mut cases = Vec::new();
+    for path in files {
+        let source = std::fs::read_to_string(&path)?;
+        let fixture: Fixture = serde_yaml::from_str(&source)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("{}: {}", path.display(), e)))?;
+        cases.extend(
+            fixture
+                .cases
+                .into_iter()
+                .map(|(name, variables)| SnapshotCase {
+                    template: fixture.template.clone(),
+                    name,
+                    variables,
+                }),
+        );
+    }
+    Ok(cases)
+}
+
+/// Outcome of checking one case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SnapshotStatus {
+    /// Output matches the snapshot
+    Passed,
+    /// No snapshot exists yet
+    Missing,
+    /// Output differs from the snapshot
+    Changed {
+        /// Line diff from the snapshot to the output
+        diff: String,
+    },
+    /// A snapshot was written in update mode
+    Written,
+    /// The template failed to render
+    Error {
+        /// Render error
+        message: String,
+    },
+}
+
+/// Result of checking one case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotResult {
+    /// Template rendered
+    pub template: String,
+    /// Case name
+    pub case: String,
+    /// Snapshot file
+    pub path: PathBuf,
+    /// What happened
+    #[serde(flatten)]
+    pub status: SnapshotStatus,
+}
+
+impl SnapshotResult {
+    /// Check whether the case passed or its snapshot was written.
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self.status,
+            SnapshotStatus::Passed | SnapshotStatus::Written
+        )
+    }
+}
+
+/// Results of a snapshot run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotReport {
+    /// One result per case, in run order
+    pub results: Vec<SnapshotResult>,
+}
+
+impl SnapshotReport {
+    /// Check whether every case passed or had its snapshot written.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(SnapshotResult::is_success)
+    }
+
+    /// Results of cases that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &SnapshotResult> {
+        self.results.iter().filter(|result| !result.is_success())
+    }
+}
+
+impl std::fmt::Display for SnapshotReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for result in &self.results {
+            let label = format!("{} / {}", result.template, result.case);
+            match &result.status {
+                SnapshotStatus::Passed => writeln!(f, "ok       {}", label)?,
+                SnapshotStatus::Written => {
+                    writeln!(f, "written  {} -> {}", label, result.path.display())?
+                }
+                SnapshotStatus::Missing => writeln!(
+                    f,
+                    "missing  {} (no snapshot at {})",
+                    label,
+                    result.path.display()
+                )?,
+                SnapshotStatus::Error { message } => {
+                    writeln!(f, "error    {}: {}", label, message)?
+                }
+                SnapshotStatus::Changed { diff } => {
+                    writeln!(f, "changed  {}", label)?;
+                    for line in diff.lines() {
+                        writeln!(f, "    {}", line)?;
+                    }
+                }
+            }
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{} cases, {} passed, {} failed",
+            self.results.len(),
+            self.results.len() - failed,
+            failed
+        )
+    }
+}
+
+/// Renders snapshot cases and compares them with golden files
+#[derive(Debug)]
+pub struct SnapshotTester<'a> {
+    registry: &'a TemplateRegistry,
+    snapshot_dir: PathBuf,
+    update: bool,
+}
+
+impl<'a> SnapshotTester<'a> {
+    /// Check cases rendered from `registry` against snapshots in
+    /// `snapshot_dir`.
+    pub fn new(registry: &'a TemplateRegistry, snapshot_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            registry,
+            snapshot_dir: snapshot_dir.into(),
+            update: false,
+        }
+    }
+
+    /// Write missing and changed snapshots instead of failing on them.
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Snapshot file of a case.
+    pub fn snapshot_path(&self, case: &SnapshotCase) -> PathBuf {
+        self.snapshot_dir
+            .join(&case.template)
+            .join(format!("{}.{}", case.name, SNAPSHOT_EXTENSION))
+    }
+
+    /// Check one case.
+    ///
+    /// Fails only if a snapshot can't be read or written; render errors
+    /// are reported in the result.
+    pub fn check(&self, case: &SnapshotCase) -> Result<SnapshotResult> {
+        let path = self.snapshot_path(case);
+        let result = |status| SnapshotResult {
+            template: case.template.clone(),
+            case: case.name.clone(),
+            path: path.clone(),
+            status,
+        };
+
+        let output = match self.registry.render_values(&case.template, &case.variables) {
+            Ok(output) => output,
+            Err(e) => {
+                return Ok(result(SnapshotStatus::Error {
+                    message: e.to_string(),
+                }))
+            }
+        };
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => Some(expected),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let status = match expected {
+            Some(expected) if expected == output => SnapshotStatus::Passed,
+            _ if self.update => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, &output)?;
+                SnapshotStatus::Written
+            }
+            Some(expected) => SnapshotStatus::Changed {
+                diff: diff_lines(&expected, &output),
+            },
+            None => SnapshotStatus::Missing,
+        };
+        Ok(result(status))
+    }
+
+    /// Check every case.
+    pub fn run(&self, cases: &[SnapshotCase]) -> Result<SnapshotReport> {
+        let results = cases
+            .iter()
+            .map(|case| self.check(case))
+            .collect::<Result<_>>()?;
+        Ok(SnapshotReport { results })
+    }
+}
+
+/// Diff two texts line by line.
+///
+/// Removed lines start with `-`, added lines with `+`, and a couple of
+/// unchanged lines around each change with a space; longer unchanged runs
+/// are collapsed to `...`.
+pub fn diff_lines(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        lines.push(('~', "(trailing newline differs)"));
+    }
+
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(lines.len());
+        lines[start..end].iter().any(|(tag, _)| *tag != ' ')
+    };
+    let mut diff = String::new();
+    let mut elided = false;
+    for (index, (tag, line)) in lines.iter().enumerate() {
+        if *tag == ' ' && !near_change(index) {
+            if !elided {
+                diff.push_str("...\n");
+                elided = true;
+            }
+            continue;
+        }
+        elided = false;
+        diff.push(*tag);
+        diff.push(' ');
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PromptTemplate;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_lifecycle() {
+        let temp = TempDir::new().unwrap();
+        let mut registry = TemplateRegistry::new();
+        registry.register(PromptTemplate::new("greet", "Hello {{name}}!\nBye.\n"));
+        let cases = vec![SnapshotCase::new("greet", "world").with_variable("name", json!("World"))];
+
+        let tester = SnapshotTester::new(&registry, temp.path());
+        let report = tester.run(&cases).unwrap();
+        assert_eq!(report.results[0].status, SnapshotStatus::Missing);
+        assert!(!report.is_success());
+
+        let report = SnapshotTester::new(&registry, temp.path())
+            .update(true)
+            .run(&cases)
+            .unwrap();
+        assert_eq!(report.results[0].status, SnapshotStatus::Written);
+        let path = temp.path().join("greet").join("world.snap");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Hello World!\nBye.\n"
+        );
+        assert!(tester.run(&cases).unwrap().is_success());
+
+        registry.register(PromptTemplate::new("greet", "Hi {{name}}!\nBye.\n"));
+        let tester = SnapshotTester::new(&registry, temp.path());
+        let report = tester.run(&cases).unwrap();
+        assert_eq!(
+            report.results[0].status,
+            SnapshotStatus::Changed {
+                diff: "- Hello World!\n+ Hi World!\n  Bye.\n".to_string()
+            }
+        );
+        assert!(report.to_string().ends_with("1 cases, 0 passed, 1 failed"));
+
+        let broken = [SnapshotCase::new("missing", "x")];
+        assert!(matches!(
+            tester.run(&broken).unwrap().results[0].status,
+            SnapshotStatus::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_cases() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("greet.yaml"),
+            "template: greet\ncases:\n  world:\n    name: World\n  empty: {}\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("notes.txt"), "ignored").unwrap();
+
+        let cases = load_cases(temp.path()).unwrap();
+        let names: Vec<_> = cases.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["empty", "world"]);
+        assert_eq!(cases[1].variables["name"], json!("World"));
+
+        std::fs::write(temp.path().join("bad.yml"), "cases: [").unwrap();
+        assert!(load_cases(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_diff_collapses_unchanged_lines() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\n";
+        let actual = "a\nb\nc\nd\ne\nf\nG\n";
+        assert_eq!(diff_lines(expected, actual), "...\n  e\n  f\n- g\n+ G\n");
+        assert_eq!(
+            diff_lines("x\n", "x"),
+            "  x\n~ (trailing newline differs)\n"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Template usage analytics.
+//!
+//! A [`TemplateRegistry`](crate::TemplateRegistry) counts every render it
+//! does by template: how often it succeeded or failed, how long the output
+//! was and which variables were missing. Templates that are never rendered
+//! are candidates for removal, and ones that often miss variables usually
+//! have callers out of step with them.
+//!
+//! Counts live in memory. To keep them across runs, merge them into a file
+//! such as the workspace's `.thulp/guidance/usage.json`:
+//!
+//! ```rust,no_run
+//! use thulp_guidance::{TemplateRegistry, UsageStats};
+//!
+//! # fn example(registry: &TemplateRegistry) -> thulp_guidance::Result<()> {
+//! let path = UsageStats::workspace_path(".");
+//! let mut stats = UsageStats::load(&path)?;
+//! stats.merge(&registry.take_usage());
+//! stats.save(&path)?;
+//! println!("{}", stats.report(registry.list()));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{GuidanceError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Usage file relative to a workspace directory
+pub const WORKSPACE_USAGE_PATH: &str = ".thulp/guidance/usage.json";
+
+/// Render counts for one template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateUsage {
+    /// Successful renders
+    pub renders: u64,
+
+    /// Failed renders, including variable misses
+    pub failures: u64,
+
+    /// Renders that failed because a variable was missing
+    pub variable_misses: u64,
+
+    /// Characters output by successful renders
+    pub rendered_chars: u64,
+
+    /// How often each variable was missing
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub missing: BTreeMap<String, u64>,
+}
+
+impl TemplateUsage {
+    /// Renders attempted, successful or not
+    pub fn attempts(&self) -> u64 {
+        self.renders + self.failures
+    }
+
+    /// Average length of successful renders in characters
+    pub fn average_length(&self) -> f64 {
+        ratio(self.rendered_chars, self.renders)
+    }
+
+    /// Share of attempts that failed
+    pub fn failure_rate(&self) -> f64 {
+        ratio(self.failures, self.attempts())
+    }
+
+    /// Share of attempts that failed on a missing variable
+    pub fn miss_rate(&self) -> f64 {
+        ratio(self.variable_misses, self.attempts())
+    }
+
+    fn record(&mut self, result: &Result<String>) {
+        match result {
+            Ok(output) => {
+                self.renders += 1;
+                self.rendered_chars += output.chars().count() as u64;
+            }
+            Err(GuidanceError::VariableNotFound(variable)) => {
+                self.failures += 1;
+                self.variable_misses += 1;
+                *self.missing.entry(variable.clone()).or_default() += 1;
+            }
+            Err(_) => self.failures += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &TemplateUsage) {
+        self.renders += other.renders;
+        self.failures += other.failures;
+        self.variable_misses += other.variable_misses;
+        self.rendered_chars += other.rendered_chars;
+        for (variable, count) in &other.missing {
+            *self.missing.entry(variable.clone()).or_default() += count;
+        }
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Usage of each template, by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageStats {
+    templates: BTreeMap<String, TemplateUsage>,
+}
+
+impl UsageStats {
+    /// Create empty stats
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a render of `template`
+    pub fn record(&mut self, template: &str, result: &Result<String>) {
+        self.templates
+            .entry(template.to_string())
+            .or_default()
+            .record(result);
+    }
+
+    /// Usage of one template, if it was rendered
+    pub fn get(&self, template: &str) -> Option<&TemplateUsage> {
+        self.templates.get(template)
+    }
+
+    /// Usage of every rendered template, by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TemplateUsage)> {
+        self.templates
+            .iter()
+            .map(|(name, usage)| (name.as_str(), usage))
+    }
+
+    /// Check whether nothing was rendered
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Add another set of counts to these
+    pub fn merge(&mut self, other: &UsageStats) {
+        for (name, usage) in &other.templates {
+            self.templates.entry(name.clone()).or_default().merge(usage);
+        }
+    }
+
+    /// Report on `templates` and every template with counts, so ones never
+    /// rendered show up with zero usage
+    pub fn report<I, S>(&self, templates: I) -> UsageReport
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut usage = self.templates.clone();
+        for name in templates {
+            usage.entry(name.into()).or_default();
+        }
+        let mut templates: Vec<_> = usage
+            .into_iter()
+            .map(|(template, usage)| TemplateUsageEntry { template, usage })
+            .collect();
+        // Busiest first, unused at the bottom; BTreeMap order breaks ties
+        templates.sort_by_key(|t| std::cmp::Reverse(t.usage.attempts()));
+        UsageReport { templates }
+    }
+
+    /// Read stats saved with [`save`](Self::save), or return empty stats if
+    /// the file doesn't exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let source = std::fs::read_to_string(path)?;
+        serde_json::from_str(&source)
+            .map_err(|e| GuidanceError::InvalidFormat(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write the stats as JSON, creating parent directories.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| GuidanceError::InvalidFormat(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Path of a workspace's usage file
+    pub fn workspace_path(workspace_dir: impl AsRef<Path>) -> PathBuf {
+        workspace_dir.as_ref().join(WORKSPACE_USAGE_PATH)
+    }
+}
+
+/// Usage of one template in a [`UsageReport`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateUsageEntry {
+    /// Template name
+    pub template: String,
+
+    /// Its counts
+    #[serde(flatten)]
+    pub usage: TemplateUsage,
+}
+
+/// Template usage, most used first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// One entry per template
+    pub templates: Vec<TemplateUsageEntry>,
+}
+
+impl UsageReport {
+    /// Templates that were never rendered
+    pub fn unused(&self) -> impl Iterator<Item = &TemplateUsageEntry> {
+        self.templates.iter().filter(|t| t.usage.attempts() == 0)
+    }
+}
+
+impl std::fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.templates {
+            let usage = &entry.usage;
+            if usage.attempts() == 0 {
+                writeln!(f, "unused   {}", entry.template)?;
+                continue;
+            }
+            writeln!(
+                f,
+                "{:<8} {}: {} ok, {} failed ({:.0}% missing variables), avg {:.0} chars",
+                usage.attempts(),
+                entry.template,
+                usage.renders,
+                usage.failures,
+                usage.miss_rate() * 100.0,
+                usage.average_length()
+            )?;
+            for (variable, count) in &usage.missing {
+                writeln!(f, "    missing {} x{}", variable, count)?;
+            }
+        }
+        write!(
+            f,
+            "{} templates, {} unused",
+            self.templates.len(),
+            self.unused().count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptTemplate, TemplateRegistry};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_registry_tracks_usage() {
+        let mut registry = TemplateRegistry::new();
+        registry.register(PromptTemplate::new("greet", "Hello {{name}}!"));
+        registry.register(PromptTemplate::new("bad", "{{#if x}}"));
+        registry.register(PromptTemplate::new("unused", "never"));
+
+        registry.render("greet", &vars(&[("name", "Ann")])).unwrap();
+        registry.render("greet", &vars(&[("name", "Bo")])).unwrap();
+        registry.render("greet", &HashMap::new()).unwrap_err();
+        registry.render("bad", &HashMap::new()).unwrap_err();
+
+        let greet = registry.usage().get("greet").cloned().unwrap();
+        assert_eq!(greet.attempts(), 3);
+        assert_eq!(greet.renders, 2);
+        assert_eq!(greet.variable_misses, 1);
+        assert_eq!(greet.missing["name"], 1);
+        assert_eq!(greet.average_length(), 9.5);
+        assert!((greet.miss_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        let bad = registry.usage().get("bad").cloned().unwrap();
+        assert_eq!((bad.failures, bad.variable_misses), (1, 0));
+
+        let report = registry.usage_report();
+        let order: Vec<_> = report.templates.iter().map(|t| t.template.as_str()).collect();
+        assert_eq!(order, ["greet", "bad", "unused"]);
+        assert_eq!(report.unused().count(), 1);
+        assert!(report.to_string().contains("missing name x1"));
+    }
+
+    #[test]
+    fn test_take_merge_and_save() {
+        let mut registry = TemplateRegistry::new();
+        registry.register(PromptTemplate::new("greet", "Hi {{name}}"));
+        registry.render("greet", &vars(&[("name", "Ann")])).unwrap();
+
+        let temp = TempDir::new().unwrap();
+        let path = UsageStats::workspace_path(temp.path());
+        let mut stats = UsageStats::load(&path).unwrap();
+        assert!(stats.is_empty());
+        stats.merge(&registry.take_usage());
+        assert!(registry.usage().is_empty());
+        stats.save(&path).unwrap();
+
+        registry.render("greet", &vars(&[("name", "Bo")])).unwrap();
+        let mut stats = UsageStats::load(&path).unwrap();
+        stats.merge(&registry.take_usage());
+        let greet = stats.get("greet").unwrap();
+        assert_eq!(greet.renders, 2);
+        assert_eq!(greet.rendered_chars, 11);
+    }
+}
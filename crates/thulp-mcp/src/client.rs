@@ -1,40 +1,94 @@
 //! MCP client implementation.
 
-use crate::{McpTransport, Result};
+use crate::{
+    ConnectionEvent, ConnectionObserver, McpRequester, McpTransport, NotificationRouter,
+    ResourcesClient, Result,
+};
+use async_trait::async_trait;
+use serde_json::Value;
 use std::collections::HashMap;
-use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+use std::sync::{Arc, Weak};
+use thulp_core::{
+    Error, NotificationSink, Redactor, ToolCall, ToolDefinition, ToolResult, Transport,
+};
 
 /// MCP client wrapper.
 pub struct McpClient {
-    transport: McpTransport,
+    transport: Arc<McpTransport>,
     tool_cache: HashMap<String, ToolDefinition>,
     session_id: String,
+    notifications: Arc<NotificationRouter>,
+    resources: Arc<ResourcesClient>,
+    redactor: Option<Arc<dyn Redactor>>,
 }
 
 impl McpClient {
     /// Create a new MCP client.
+    ///
+    /// Its resources client sends subscription requests over `transport`.
     pub fn new(transport: McpTransport) -> Self {
+        Self::with_requester(transport, None)
+    }
+
+    /// Create a client whose resources client sends requests through
+    /// `requester`, or the transport without one, and receives the
+    /// notifications the transport delivers.
+    ///
+    /// Subscriptions are sent again whenever the transport reconnects.
+    fn with_requester(transport: McpTransport, requester: Option<Arc<dyn McpRequester>>) -> Self {
+        let mut resources = None;
+        let transport = Arc::new_cyclic(|weak| {
+            let requester =
+                requester.unwrap_or_else(|| Arc::new(TransportRequester(weak.clone())));
+            let client = Arc::new(ResourcesClient::with_requester(requester));
+            let observer = Arc::new(Resubscribe(client.clone()));
+            resources = Some(client);
+            transport.with_connection_observer(observer)
+        });
+        let resources = resources.expect("set while creating the transport");
+        let notifications = transport.notifications();
+        notifications.add_sink(resources.clone());
         Self {
             transport,
             tool_cache: HashMap::new(),
             session_id: uuid::Uuid::new_v4().to_string(),
+            notifications,
+            resources,
+            redactor: None,
         }
     }
 
+    /// Mask sensitive values in tool arguments before they are logged.
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
     /// Create a client builder.
     pub fn builder() -> McpClientBuilder {
         McpClientBuilder::new()
     }
 
     /// Connect to the MCP server.
+    ///
+    /// Resource subscriptions from an earlier connection are sent again,
+    /// as they are whenever the transport reconnects by itself; failures
+    /// are logged rather than failing the connection.
     pub async fn connect(&mut self) -> Result<()> {
-        self.transport.connect().await?;
+        self.transport.establish().await?;
+        if let Err(e) = self.resources.resubscribe().await {
+            tracing::warn!(
+                server = %self.transport.server_name(),
+                error = %e,
+                "Failed to restore resource subscriptions"
+            );
+        }
         Ok(())
     }
 
     /// Disconnect from the MCP server.
     pub async fn disconnect(&mut self) -> Result<()> {
-        self.transport.disconnect().await?;
+        self.transport.close();
         self.tool_cache.clear();
         Ok(())
     }
@@ -67,6 +121,19 @@ impl McpClient {
 
     /// Execute a tool call.
     pub async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> Result<ToolResult> {
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let logged = match &self.redactor {
+                Some(redactor) => redactor.redact(&arguments),
+                None => arguments.clone(),
+            };
+            tracing::debug!(
+                server = %self.transport.server_name(),
+                tool = %name,
+                arguments = %logged,
+                "Calling MCP tool"
+            );
+        }
+
         let call = ToolCall {
             tool: name.to_string(),
             arguments,
@@ -83,17 +150,93 @@ impl McpClient {
     pub fn clear_cache(&mut self) {
         self.tool_cache.clear();
     }
+
+    /// Get the resources client, which receives this client's resource
+    /// update notifications.
+    pub fn resources(&self) -> &Arc<ResourcesClient> {
+        &self.resources
+    }
+
+    /// Get the notification router for this client, which receives the
+    /// notifications the server sends.
+    pub fn notifications(&self) -> &Arc<NotificationRouter> {
+        &self.notifications
+    }
+
+    /// Register a sink for server notifications.
+    pub fn add_notification_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.notifications.add_sink(sink);
+    }
+
+    /// Handle a raw JSON-RPC notification as if the server had sent it.
+    ///
+    /// The transport routes the server's own notifications; this parses
+    /// one received some other way and routes it to all registered sinks,
+    /// tagged with this client's server name.
+    pub async fn handle_notification(&self, method: &str, params: serde_json::Value) -> Result<()> {
+        let server = self.transport.server_name();
+        self.notifications
+            .route_jsonrpc(&server, method, params)
+            .await
+    }
+}
+
+/// Sends a client's resource requests over its transport.
+///
+/// Holds the transport weakly: the transport's notification router holds
+/// the resources client, which holds this.
+struct TransportRequester(Weak<McpTransport>);
+
+#[async_trait]
+impl McpRequester for TransportRequester {
+    async fn send_request(&self, method: &str, params: Value) -> Result<Value> {
+        let transport = self
+            .0
+            .upgrade()
+            .ok_or_else(|| Error::ExecutionFailed("MCP client was dropped".to_string()))?;
+        transport.send_request(method, params).await
+    }
+}
+
+/// Restores a resources client's subscriptions after the transport
+/// reconnects
+struct Resubscribe(Arc<ResourcesClient>);
+
+impl ConnectionObserver for Resubscribe {
+    fn on_connection_event(&self, server: &str, event: &ConnectionEvent) {
+        if !matches!(event, ConnectionEvent::Reconnected { .. }) {
+            return;
+        }
+        // Observers can't wait, so the requests go out on their own task
+        let resources = self.0.clone();
+        let server = server.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = resources.resubscribe().await {
+                tracing::warn!(
+                    server = %server,
+                    error = %e,
+                    "Failed to restore resource subscriptions"
+                );
+            }
+        });
+    }
 }
 
 /// Builder for [`McpClient`].
 pub struct McpClientBuilder {
     transport: Option<McpTransport>,
+    redactor: Option<Arc<dyn Redactor>>,
+    requester: Option<Arc<dyn McpRequester>>,
 }
 
 impl McpClientBuilder {
     /// Create a new builder.
     pub fn new() -> Self {
-        Self { transport: None }
+        Self {
+            transport: None,
+            redactor: None,
+            requester: None,
+        }
     }
 
     /// Set the transport.
@@ -102,6 +245,19 @@ impl McpClientBuilder {
         self
     }
 
+    /// Set the redactor used when logging tool arguments.
+    pub fn redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Set the requester used to send resource subscription requests,
+    /// instead of the transport.
+    pub fn resource_requester(mut self, requester: Arc<dyn McpRequester>) -> Self {
+        self.requester = Some(requester);
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Result<McpClient> {
         use thulp_core::Error;
@@ -109,7 +265,9 @@ impl McpClientBuilder {
             .transport
             .ok_or_else(|| Error::InvalidConfig("transport not set".to_string()))?;
 
-        Ok(McpClient::new(transport))
+        let mut client = McpClient::with_requester(transport, self.requester);
+        client.redactor = self.redactor;
+        Ok(client)
     }
 }
 
@@ -168,10 +326,250 @@ mod tests {
         assert!(!client.is_connected());
     }
 
+    #[tokio::test]
+    async fn client_builder_with_redactor() {
+        let client = McpClient::builder()
+            .transport(McpTransport::new_http(
+                "test".to_string(),
+                "http://localhost:8080".to_string(),
+            ))
+            .redactor(Arc::new(thulp_core::PathRedactor::sensitive_defaults()))
+            .build()
+            .unwrap();
+        assert!(client.redactor.is_some());
+    }
+
+    #[tokio::test]
+    async fn client_routes_notifications() {
+        use async_trait::async_trait;
+        use std::sync::Mutex;
+        use thulp_core::McpNotification;
+
+        #[derive(Default)]
+        struct Sink(Mutex<Vec<(String, McpNotification)>>);
+
+        #[async_trait]
+        impl NotificationSink for Sink {
+            async fn notify(&self, server: &str, notification: &McpNotification) -> Result<()> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((server.to_string(), notification.clone()));
+                Ok(())
+            }
+        }
+
+        let client = McpClient::new(McpTransport::new_http(
+            "docs".to_string(),
+            "http://localhost:8080".to_string(),
+        ));
+        let sink = Arc::new(Sink::default());
+        client.add_notification_sink(sink.clone());
+
+        client
+            .handle_notification(
+                "notifications/resources/updated",
+                serde_json::json!({"uri": "file:///a.md"}),
+            )
+            .await
+            .unwrap();
+
+        let received = sink.0.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "docs");
+        assert_eq!(
+            received[0].1,
+            McpNotification::ResourceUpdated {
+                uri: "file:///a.md".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn client_delivers_resource_updates() {
+        use async_trait::async_trait;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Server(Mutex<Vec<serde_json::Value>>);
+
+        #[async_trait]
+        impl McpRequester for Server {
+            async fn send_request(
+                &self,
+                method: &str,
+                params: serde_json::Value,
+            ) -> Result<serde_json::Value> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::json!({"method": method, "params": params}));
+                Ok(serde_json::json!({}))
+            }
+        }
+
+        let server = Arc::new(Server::default());
+        let client = McpClient::builder()
+            .transport(McpTransport::new_http(
+                "docs".to_string(),
+                "http://localhost:8080".to_string(),
+            ))
+            .resource_requester(server.clone())
+            .build()
+            .unwrap();
+
+        let mut watch = client.resources().watch("file:///a.md").await.unwrap();
+        assert_eq!(
+            server.0.lock().unwrap()[0],
+            serde_json::json!({"method": "resources/subscribe", "params": {"uri": "file:///a.md"}})
+        );
+
+        client
+            .handle_notification(
+                "notifications/resources/updated",
+                serde_json::json!({"uri": "file:///a.md"}),
+            )
+            .await
+            .unwrap();
+        let update = watch.next().await.unwrap();
+        assert_eq!(update.server, "docs");
+        assert_eq!(update.uri, "file:///a.md");
+    }
+
+    /// Stdio server that accepts resource subscriptions, reporting an update
+    /// to `file:///a.md` after each, and knows no other method
+    #[cfg(unix)]
+    fn resource_server(dir: &std::path::Path) -> McpTransport {
+        let script = dir.join("server.sh");
+        std::fs::write(
+            &script,
+            r#"while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"capabilities":{}}}' ;;
+    *notifications/initialized*) ;;
+    *resources/subscribe*)
+      echo '{"jsonrpc":"2.0","id":'$id',"result":{}}'
+      printf '%s%s\n' '{"jsonrpc":"2.0","method":"notifications/resources/updated",' \
+        '"params":{"uri":"file:///a.md"}}' ;;
+    *) echo '{"jsonrpc":"2.0","id":'$id',"error":{"code":-32601,"message":"unknown"}}' ;;
+  esac
+done
+"#,
+        )
+        .unwrap();
+        let args = vec![script.to_string_lossy().into_owned()];
+        McpTransport::new_stdio("docs".to_string(), "sh".to_string(), Some(args))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn transport_sends_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut transport = resource_server(dir.path());
+        let params = serde_json::json!({"uri": "file:///a.md"});
+        let err = transport
+            .send_request("resources/subscribe", params.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not connected"));
+
+        transport.connect().await.unwrap();
+        let result = transport
+            .send_request("resources/subscribe", params.clone())
+            .await
+            .unwrap();
+        assert_eq!(result, serde_json::json!({}));
+        let err = transport
+            .send_request("resources/unsubscribe", params)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("resources/unsubscribe failed"));
+        assert!(err.to_string().contains("-32601"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn client_watches_resources_over_transport() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = McpClient::new(resource_server(dir.path()));
+        client.connect().await.unwrap();
+
+        let mut watch = client.resources().watch("file:///a.md").await.unwrap();
+        assert_eq!(client.resources().subscriptions(), ["file:///a.md"]);
+        let update = watch.next().await.unwrap();
+        assert_eq!(update.server, "docs");
+        assert_eq!(update.uri, "file:///a.md");
+
+        // The server refuses to unsubscribe, but updates stop anyway
+        assert!(client.resources().unsubscribe("file:///a.md").await.is_err());
+        assert!(client.resources().subscriptions().is_empty());
+        client.disconnect().await.unwrap();
+    }
+
+    /// HTTP server that sends a resource update on its GET event stream once
+    /// `send_update` is notified
+    #[cfg(feature = "http-server")]
+    async fn http_resource_server(send_update: Arc<tokio::sync::Notify>) -> McpTransport {
+        use axum::http::StatusCode;
+        use axum::response::sse::{Event, Sse};
+        use axum::response::IntoResponse;
+        use futures::StreamExt;
+
+        let post = |axum::Json(message): axum::Json<Value>| async move {
+            let result = serde_json::json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} });
+            match message["method"].as_str() {
+                Some("initialize") => {
+                    ([("mcp-session-id", "s1")], axum::Json(result)).into_response()
+                }
+                Some("resources/subscribe") => axum::Json(result).into_response(),
+                _ => StatusCode::ACCEPTED.into_response(),
+            }
+        };
+        let get = move || async move {
+            let update = futures::stream::once(async move {
+                send_update.notified().await;
+                let message = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/resources/updated",
+                    "params": { "uri": "file:///a.md" }
+                });
+                Ok::<_, std::convert::Infallible>(Event::default().data(message.to_string()))
+            });
+            Sse::new(update.chain(futures::stream::pending()))
+        };
+        let app: axum::Router =
+            axum::Router::new().route("/mcp", axum::routing::post(post).get(get));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        McpTransport::new_http("docs".to_string(), url)
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn client_watches_resources_over_http() {
+        let send_update = Arc::new(tokio::sync::Notify::new());
+        let mut client = McpClient::new(http_resource_server(send_update.clone()).await);
+        client.connect().await.unwrap();
+
+        let mut watch = client.resources().watch("file:///a.md").await.unwrap();
+        // The update comes on the event stream after subscribing returned
+        send_update.notify_one();
+        let update = tokio::time::timeout(std::time::Duration::from_secs(5), watch.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.server, "docs");
+        assert_eq!(update.uri, "file:///a.md");
+        client.disconnect().await.unwrap();
+    }
+
     #[tokio::test]
     async fn client_convenience() {
         // This is a placeholder test since we can't actually connect to MCP servers in tests
         // In real usage, this would connect to a real MCP server
-        assert!(true);
+        assert!(McpClient::builder().build().is_err());
     }
 }
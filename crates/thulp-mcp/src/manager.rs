@@ -0,0 +1,554 @@
+//! Connections to several MCP servers behind one transport.
+
+use crate::{McpTransport, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use thulp_core::{Error, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+
+/// Separates the server name from the tool name in routed tool names.
+pub const TOOL_SEPARATOR: char = '.';
+
+/// Outcome of a server health check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "error")]
+pub enum ServerStatus {
+    /// Connected and answered a tool listing
+    Healthy,
+    /// Connected but the tool listing failed or timed out
+    Unhealthy(String),
+    /// Not connected
+    Disconnected,
+}
+
+/// Health of one server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerHealth {
+    /// Server name
+    pub server: String,
+
+    /// Check outcome
+    #[serde(flatten)]
+    pub status: ServerStatus,
+
+    /// Number of tools listed, when healthy
+    pub tools: usize,
+
+    /// Time taken to list tools, when connected
+    pub latency: Option<Duration>,
+}
+
+impl ServerHealth {
+    /// Check if the server is healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.status == ServerStatus::Healthy
+    }
+}
+
+struct Connection {
+    name: String,
+    transport: Box<dyn Transport>,
+}
+
+/// Named connections to several MCP servers, exposed as one [`Transport`].
+///
+/// Servers are reached over stdio or HTTP, any number of them. Tools are
+/// listed as `server.tool` and calls are routed by that prefix, so a skill or
+/// agent can use tools from every server without knowing which one it talks
+/// to:
+///
+/// ```rust,no_run
+/// use thulp_core::{ToolCall, Transport};
+/// use thulp_mcp::McpConnectionManager;
+///
+/// # async fn example() -> thulp_core::Result<()> {
+/// let mut manager = McpConnectionManager::new();
+/// manager.add_stdio("fs", "mcp-server-filesystem", Some(vec![".".to_string()]))?;
+/// manager.add_http("search", "http://localhost:8080/mcp")?;
+/// manager.connect().await?;
+///
+/// for tool in manager.list_tools().await? {
+///     println!("{}", tool.name); // e.g. "fs.read_file"
+/// }
+/// let result = manager
+///     .call(&ToolCall::builder("fs.read_file").arg_str("path", "README.md").build())
+///     .await?;
+///
+/// for health in manager.health_check().await {
+///     println!("{}: {:?}", health.server, health.status);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct McpConnectionManager {
+    connections: Vec<Connection>,
+    health_timeout: Duration,
+}
+
+impl std::fmt::Debug for McpConnectionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpConnectionManager")
+            .field("servers", &self.servers())
+            .field("health_timeout", &self.health_timeout)
+            .finish()
+    }
+}
+
+impl Default for McpConnectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpConnectionManager {
+    /// Create a manager without servers, with a 10 second health check
+    /// timeout.
+    pub fn new() -> Self {
+        Self {
+            connections: Vec::new(),
+            health_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Set how long a health check waits for a server to list its tools.
+    pub fn with_health_timeout(mut self, timeout: Duration) -> Self {
+        self.health_timeout = timeout;
+        self
+    }
+
+    /// Add a server under a name.
+    ///
+    /// Fails if the name is empty, contains [`TOOL_SEPARATOR`], or is
+    /// already taken. The server is not connected until
+    /// [`connect`](Transport::connect) or [`connect_server`](Self::connect_server).
+    pub fn add_server(
+        &mut self,
+        name: impl Into<String>,
+        transport: impl Transport + 'static,
+    ) -> Result<()> {
+        let name = name.into();
+        if name.is_empty() || name.contains(TOOL_SEPARATOR) {
+            return Err(Error::InvalidConfig(format!(
+                "invalid server name '{}': must be non-empty and not contain '{}'",
+                name, TOOL_SEPARATOR
+            )));
+        }
+        if self.connection(&name).is_some() {
+            return Err(Error::InvalidConfig(format!(
+                "server '{}' is already registered",
+                name
+            )));
+        }
+        self.connections.push(Connection {
+            name,
+            transport: Box::new(transport),
+        });
+        Ok(())
+    }
+
+    /// Add a server reached over HTTP.
+    pub fn add_http(&mut self, name: impl Into<String>, url: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        let transport = McpTransport::new_http(name.clone(), url.into());
+        self.add_server(name, transport)
+    }
+
+    /// Add a server started as a subprocess and reached over stdio.
+    pub fn add_stdio(
+        &mut self,
+        name: impl Into<String>,
+        command: impl Into<String>,
+        args: Option<Vec<String>>,
+    ) -> Result<()> {
+        let name = name.into();
+        let transport = McpTransport::new_stdio(name.clone(), command.into(), args);
+        self.add_server(name, transport)
+    }
+
+    /// Disconnect and remove a server.
+    ///
+    /// Returns `false` if no server has that name.
+    pub async fn remove_server(&mut self, name: &str) -> Result<bool> {
+        let Some(index) = self.connections.iter().position(|c| c.name == name) else {
+            return Ok(false);
+        };
+        let mut connection = self.connections.remove(index);
+        if connection.transport.is_connected() {
+            connection.transport.disconnect().await?;
+        }
+        Ok(true)
+    }
+
+    /// Names of the servers, in the order they were added.
+    pub fn servers(&self) -> Vec<String> {
+        self.connections.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// Check if a server is connected.
+    pub fn is_server_connected(&self, name: &str) -> bool {
+        self.connection(name)
+            .is_some_and(|c| c.transport.is_connected())
+    }
+
+    /// Connect one server.
+    pub async fn connect_server(&mut self, name: &str) -> Result<()> {
+        let connection = self
+            .connections
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| Error::InvalidConfig(format!("unknown server '{}'", name)))?;
+        connection.transport.connect().await
+    }
+
+    /// Disconnect one server.
+    pub async fn disconnect_server(&mut self, name: &str) -> Result<()> {
+        let connection = self
+            .connections
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| Error::InvalidConfig(format!("unknown server '{}'", name)))?;
+        connection.transport.disconnect().await
+    }
+
+    /// Check every server by listing its tools.
+    ///
+    /// Servers that are connected but fail to answer within the health
+    /// timeout are reported unhealthy.
+    pub async fn health_check(&self) -> Vec<ServerHealth> {
+        let mut report = Vec::with_capacity(self.connections.len());
+        for connection in &self.connections {
+            report.push(self.check(connection).await);
+        }
+        report
+    }
+
+    /// Check one server.
+    ///
+    /// Returns `None` if no server has that name.
+    pub async fn health_check_server(&self, name: &str) -> Option<ServerHealth> {
+        match self.connection(name) {
+            Some(connection) => Some(self.check(connection).await),
+            None => None,
+        }
+    }
+
+    async fn check(&self, connection: &Connection) -> ServerHealth {
+        let mut health = ServerHealth {
+            server: connection.name.clone(),
+            status: ServerStatus::Disconnected,
+            tools: 0,
+            latency: None,
+        };
+        if !connection.transport.is_connected() {
+            return health;
+        }
+
+        let start = Instant::now();
+        let listed =
+            tokio::time::timeout(self.health_timeout, connection.transport.list_tools()).await;
+        health.latency = Some(start.elapsed());
+        health.status = match listed {
+            Ok(Ok(tools)) => {
+                health.tools = tools.len();
+                ServerStatus::Healthy
+            }
+            Ok(Err(e)) => ServerStatus::Unhealthy(e.to_string()),
+            Err(_) => {
+                ServerStatus::Unhealthy(format!("no answer within {:?}", self.health_timeout))
+            }
+        };
+        health
+    }
+
+    fn connection(&self, name: &str) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.name == name)
+    }
+
+    /// Find the server a routed call goes to and the call it receives
+    fn route(&self, call: &ToolCall) -> Result<(&Connection, ToolCall)> {
+        let (server, tool) =
+            split_tool_name(&call.tool).ok_or_else(|| Error::ToolNotFound(call.tool.clone()))?;
+        let connection = self
+            .connection(server)
+            .ok_or_else(|| Error::ToolNotFound(call.tool.clone()))?;
+        if !connection.transport.is_connected() {
+            return Err(Error::ExecutionFailed(format!(
+                "server '{}' is not connected",
+                server
+            )));
+        }
+        Ok((
+            connection,
+            ToolCall {
+                tool: tool.to_string(),
+                arguments: call.arguments.clone(),
+            },
+        ))
+    }
+}
+
+/// Split a routed tool name into its server and tool names.
+///
+/// Only the first [`TOOL_SEPARATOR`] splits, so tool names may contain it.
+pub fn split_tool_name(name: &str) -> Option<(&str, &str)> {
+    name.split_once(TOOL_SEPARATOR)
+        .filter(|(server, tool)| !server.is_empty() && !tool.is_empty())
+}
+
+#[async_trait]
+impl Transport for McpConnectionManager {
+    /// Connect every server that isn't connected yet.
+    ///
+    /// Servers that fail to connect are logged and skipped; this only fails
+    /// if no server is connected afterwards.
+    async fn connect(&mut self) -> Result<()> {
+        let mut last_error = None;
+        for connection in &mut self.connections {
+            if connection.transport.is_connected() {
+                continue;
+            }
+            if let Err(e) = connection.transport.connect().await {
+                tracing::warn!(server = %connection.name, error = %e, "Failed to connect MCP server");
+                last_error = Some(Error::ExecutionFailed(format!(
+                    "server '{}' failed to connect: {}",
+                    connection.name, e
+                )));
+            }
+        }
+        if !self.is_connected() {
+            return Err(last_error
+                .unwrap_or_else(|| Error::InvalidConfig("No servers configured".to_string())));
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for connection in &mut self.connections {
+            if connection.transport.is_connected() {
+                if let Err(e) = connection.transport.disconnect().await {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connections.iter().any(|c| c.transport.is_connected())
+    }
+
+    /// List the tools of every connected server as `server.tool`.
+    ///
+    /// A server that fails to list its tools is logged and left out.
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let mut definitions = Vec::new();
+        for connection in &self.connections {
+            if !connection.transport.is_connected() {
+                continue;
+            }
+            match connection.transport.list_tools().await {
+                Ok(tools) => definitions.extend(tools.into_iter().map(|mut tool| {
+                    tool.name = format!("{}{}{}", connection.name, TOOL_SEPARATOR, tool.name);
+                    tool
+                })),
+                Err(e) => {
+                    tracing::warn!(server = %connection.name, error = %e, "Failed to list tools")
+                }
+            }
+        }
+        Ok(definitions)
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        let (connection, routed) = self.route(call)?;
+        connection.transport.call(&routed).await
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        let (connection, routed) = self.route(call)?;
+        connection.transport.call_streaming(&routed).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Server echoing the tool it was called with
+    struct Server {
+        tools: Vec<&'static str>,
+        connected: bool,
+        refuse: bool,
+        broken: Arc<AtomicBool>,
+    }
+
+    impl Server {
+        fn new(tools: Vec<&'static str>) -> Self {
+            Self {
+                tools,
+                connected: false,
+                refuse: false,
+                broken: Arc::new(AtomicBool::new(false)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Transport for Server {
+        async fn connect(&mut self) -> Result<()> {
+            if self.refuse {
+                return Err(Error::ExecutionFailed("connection refused".to_string()));
+            }
+            self.connected = true;
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            self.connected = false;
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected
+        }
+
+        async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+            if self.broken.load(Ordering::SeqCst) {
+                return Err(Error::ExecutionFailed("broken pipe".to_string()));
+            }
+            Ok(self.tools.iter().map(|t| ToolDefinition::new(*t)).collect())
+        }
+
+        async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+            Ok(ToolResult::success(
+                json!({"tool": call.tool, "args": call.arguments}),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_calls_by_server_prefix() {
+        let mut manager = McpConnectionManager::new();
+        manager
+            .add_server("fs", Server::new(vec!["read_file", "list"]))
+            .unwrap();
+        manager
+            .add_server("search", Server::new(vec!["query", "v2.query"]))
+            .unwrap();
+        manager.connect().await.unwrap();
+
+        let names: Vec<_> = manager
+            .list_tools()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["fs.read_file", "fs.list", "search.query", "search.v2.query"]
+        );
+
+        let result = manager
+            .call(
+                &ToolCall::builder("search.v2.query")
+                    .arg_str("q", "x")
+                    .build(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.data,
+            Some(json!({"tool": "v2.query", "args": {"q": "x"}}))
+        );
+
+        assert!(matches!(
+            manager.call(&ToolCall::new("web.fetch")).await,
+            Err(Error::ToolNotFound(_))
+        ));
+        assert!(matches!(
+            manager.call(&ToolCall::new("read_file")).await,
+            Err(Error::ToolNotFound(_))
+        ));
+
+        manager.disconnect_server("fs").await.unwrap();
+        let err = manager
+            .call(&ToolCall::new("fs.read_file"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not connected"));
+        assert_eq!(manager.list_tools().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_server_names() {
+        let mut manager = McpConnectionManager::new();
+        manager.add_server("fs", Server::new(vec![])).unwrap();
+        assert!(manager.add_server("fs", Server::new(vec![])).is_err());
+        assert!(manager.add_server("a.b", Server::new(vec![])).is_err());
+        assert!(manager.add_server("", Server::new(vec![])).is_err());
+        assert_eq!(manager.servers(), ["fs"]);
+
+        assert!(manager.remove_server("fs").await.unwrap());
+        assert!(!manager.remove_server("fs").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn connect_skips_failing_servers() {
+        let mut manager = McpConnectionManager::new();
+        let mut down = Server::new(vec!["x"]);
+        down.refuse = true;
+        manager.add_server("down", down).unwrap();
+        manager.add_server("up", Server::new(vec!["y"])).unwrap();
+
+        manager.connect().await.unwrap();
+        assert!(manager.is_server_connected("up"));
+        assert!(!manager.is_server_connected("down"));
+
+        let mut all_down = McpConnectionManager::new();
+        let mut down = Server::new(vec![]);
+        down.refuse = true;
+        all_down.add_server("down", down).unwrap();
+        assert!(all_down.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn health_check_reports_each_server() {
+        let mut manager = McpConnectionManager::new();
+        let flaky = Server::new(vec!["a"]);
+        let broken = flaky.broken.clone();
+        manager.add_server("flaky", flaky).unwrap();
+        manager.add_server("idle", Server::new(vec![])).unwrap();
+        manager.connect_server("flaky").await.unwrap();
+
+        let report = manager.health_check().await;
+        assert!(report[0].is_healthy());
+        assert_eq!(report[0].tools, 1);
+        assert!(report[0].latency.is_some());
+        assert_eq!(report[1].status, ServerStatus::Disconnected);
+
+        broken.store(true, Ordering::SeqCst);
+        let health = manager.health_check_server("flaky").await.unwrap();
+        assert_eq!(
+            health.status,
+            ServerStatus::Unhealthy("tool execution failed: broken pipe".to_string())
+        );
+        assert!(manager.health_check_server("missing").await.is_none());
+
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["status"], "unhealthy");
+        assert_eq!(json["error"], "tool execution failed: broken pipe");
+    }
+
+    #[test]
+    fn splits_tool_names() {
+        assert_eq!(split_tool_name("fs.read"), Some(("fs", "read")));
+        assert_eq!(split_tool_name("a.b.c"), Some(("a", "b.c")));
+        assert_eq!(split_tool_name("read"), None);
+        assert_eq!(split_tool_name(".read"), None);
+    }
+}
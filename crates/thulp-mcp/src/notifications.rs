@@ -0,0 +1,159 @@
+//! MCP server notification routing.
+//!
+//! Servers emit notifications (`notifications/message`, `notifications/progress`,
+//! `notifications/resources/updated`, ...) outside of request/response pairs.
+//! The [`NotificationRouter`] parses these into typed [`McpNotification`]s and
+//! fans them out to every registered [`NotificationSink`], so they can be
+//! recorded alongside the rest of the interaction.
+
+use crate::Result;
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use thulp_core::{McpNotification, NotificationSink};
+
+/// Fans out server notifications to registered sinks.
+#[derive(Default)]
+pub struct NotificationRouter {
+    /// Registered notification sinks
+    sinks: RwLock<Vec<Arc<dyn NotificationSink>>>,
+}
+
+impl NotificationRouter {
+    /// Create a router with no sinks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a sink to receive notifications.
+    pub fn add_sink(&self, sink: Arc<dyn NotificationSink>) {
+        self.sinks.write().unwrap().push(sink);
+    }
+
+    /// Remove all registered sinks.
+    pub fn clear_sinks(&self) {
+        self.sinks.write().unwrap().clear();
+    }
+
+    /// Number of registered sinks.
+    pub fn sink_count(&self) -> usize {
+        self.sinks.read().unwrap().len()
+    }
+
+    /// Parse a raw JSON-RPC notification and route it to all sinks.
+    pub async fn route_jsonrpc(&self, server: &str, method: &str, params: Value) -> Result<()> {
+        self.route(server, McpNotification::from_jsonrpc(method, params))
+            .await
+    }
+
+    /// Route a notification to all sinks.
+    ///
+    /// Every sink is called even if an earlier one fails; the first error
+    /// is returned.
+    pub async fn route(&self, server: &str, notification: McpNotification) -> Result<()> {
+        // Clone the sink list so the lock isn't held across awaits
+        let sinks = self.sinks.read().unwrap().clone();
+
+        let mut first_error = None;
+        for sink in sinks {
+            if let Err(e) = sink.notify(server, &notification).await {
+                first_error.get_or_insert(e);
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use thulp_core::{Error, LoggingLevel};
+
+    #[derive(Default)]
+    struct RecordingSink {
+        received: Mutex<Vec<(String, McpNotification)>>,
+    }
+
+    #[async_trait]
+    impl NotificationSink for RecordingSink {
+        async fn notify(&self, server: &str, notification: &McpNotification) -> Result<()> {
+            self.received
+                .lock()
+                .unwrap()
+                .push((server.to_string(), notification.clone()));
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    #[async_trait]
+    impl NotificationSink for FailingSink {
+        async fn notify(&self, _server: &str, _notification: &McpNotification) -> Result<()> {
+            Err(Error::ExecutionFailed("sink failed".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_route_to_all_sinks() {
+        let router = NotificationRouter::new();
+        let a = Arc::new(RecordingSink::default());
+        let b = Arc::new(RecordingSink::default());
+        router.add_sink(a.clone());
+        router.add_sink(b.clone());
+        assert_eq!(router.sink_count(), 2);
+
+        router
+            .route_jsonrpc(
+                "github",
+                "notifications/message",
+                serde_json::json!({"level": "error", "data": "boom"}),
+            )
+            .await
+            .unwrap();
+
+        for sink in [a, b] {
+            let received = sink.received.lock().unwrap();
+            assert_eq!(received.len(), 1);
+            assert_eq!(received[0].0, "github");
+            assert!(matches!(
+                received[0].1,
+                McpNotification::Log {
+                    level: LoggingLevel::Error,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_block_others() {
+        let router = NotificationRouter::new();
+        let recorder = Arc::new(RecordingSink::default());
+        router.add_sink(Arc::new(FailingSink));
+        router.add_sink(recorder.clone());
+
+        let result = router.route("srv", McpNotification::ToolListChanged).await;
+
+        assert!(result.is_err());
+        assert_eq!(recorder.received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_route_without_sinks() {
+        let router = NotificationRouter::new();
+        router
+            .route("srv", McpNotification::PromptListChanged)
+            .await
+            .unwrap();
+
+        router.add_sink(Arc::new(RecordingSink::default()));
+        router.clear_sinks();
+        assert_eq!(router.sink_count(), 0);
+    }
+}
@@ -0,0 +1,202 @@
+//! Reconnecting dropped MCP connections.
+//!
+//! Stdio servers exit and HTTP servers drop connections. When a call or tool
+//! listing on an [`McpTransport`](crate::McpTransport) fails because the
+//! connection is gone, the transport opens a new one (starting a fresh
+//! process for stdio servers and repeating the `initialize` handshake),
+//! waiting longer before each attempt.
+//!
+//! Only requests that are safe to repeat are then sent again: tool listings,
+//! and calls to tools the last listing annotated `idempotentHint` or
+//! `readOnlyHint`. Other calls fail with the connection error, since the
+//! server may have run them before the connection dropped. An
+//! [`McpClient`](crate::McpClient) restores its resource subscriptions after
+//! every reconnect.
+//!
+//! Every step is reported to [`ConnectionObserver`]s as a
+//! [`ConnectionEvent`]:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use thulp_mcp::{ConnectionEvent, ConnectionObserver, McpTransport, ReconnectConfig};
+//!
+//! struct LogEvents;
+//!
+//! impl ConnectionObserver for LogEvents {
+//!     fn on_connection_event(&self, server: &str, event: &ConnectionEvent) {
+//!         println!("{}: {:?}", server, event);
+//!     }
+//! }
+//!
+//! let transport = McpTransport::new_stdio("fs".to_string(), "mcp-server-fs".to_string(), None)
+//!     .with_reconnect(ReconnectConfig::new().with_max_attempts(3))
+//!     .with_connection_observer(Arc::new(LogEvents));
+//! ```
+
+use std::time::Duration;
+
+/// How a dropped connection is re-established.
+///
+/// The delay before attempt `n` is `initial_delay * 2^(n-1)` plus up to 50%
+/// jitter, capped at `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectConfig {
+    /// Attempts before giving up; 0 disables reconnecting
+    pub max_attempts: usize,
+
+    /// Delay before the first attempt
+    pub initial_delay: Duration,
+
+    /// Longest delay between attempts
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReconnectConfig {
+    /// Five attempts, starting at 100ms and backing off to at most 10s.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+
+    /// Never reconnect; a dropped connection fails the request.
+    pub fn disabled() -> Self {
+        Self::new().with_max_attempts(0)
+    }
+
+    /// Set the number of attempts.
+    pub fn with_max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Set the delay before the first attempt.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the longest delay between attempts.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Check if reconnecting is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.max_attempts > 0
+    }
+
+    /// Delay before an attempt, counting from 1.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let multiplier = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        let base = self.initial_delay.saturating_mul(multiplier);
+        let jitter_range = base.as_millis() as u64 / 2;
+        let jitter = if jitter_range > 0 {
+            fastrand::u64(0..jitter_range)
+        } else {
+            0
+        };
+        std::cmp::min(base + Duration::from_millis(jitter), self.max_delay)
+    }
+}
+
+/// A change in the state of a transport's connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The transport connected.
+    Connected,
+    /// The transport was disconnected on request.
+    Disconnected,
+    /// A request failed because the connection is gone.
+    Lost {
+        /// The failure that revealed it
+        error: String,
+    },
+    /// About to try reconnecting, after the delay.
+    Reconnecting {
+        /// Attempt number, starting at 1
+        attempt: usize,
+        /// Wait before the attempt
+        delay: Duration,
+    },
+    /// The connection was re-established.
+    Reconnected {
+        /// Attempts it took
+        attempts: usize,
+    },
+    /// Every attempt failed; the transport is now disconnected.
+    ReconnectFailed {
+        /// Attempts made
+        attempts: usize,
+        /// Error from the last attempt
+        error: String,
+    },
+}
+
+/// Receives connection lifecycle events from transports.
+///
+/// Called inline while the transport reconnects, so implementations should
+/// return quickly.
+pub trait ConnectionObserver: Send + Sync {
+    /// Handle an event from the named server's transport.
+    fn on_connection_event(&self, server: &str, event: &ConnectionEvent);
+}
+
+/// Check if an error means the connection itself is broken, rather than the
+/// server rejecting a request.
+pub(crate) fn is_connection_error(message: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "closed connection",
+        "connection closed",
+        "connection refused",
+        "connection reset",
+        "connection aborted",
+        "broken pipe",
+        "unexpected eof",
+        "error sending request",
+        "no such process",
+    ];
+    let message = message.to_lowercase();
+    MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_backs_off_and_caps() {
+        let config = ReconnectConfig::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500));
+
+        let first = config.delay(1);
+        assert!(first >= Duration::from_millis(100) && first < Duration::from_millis(150));
+        let third = config.delay(3);
+        assert!(third >= Duration::from_millis(400) && third <= Duration::from_millis(500));
+        assert_eq!(config.delay(10), Duration::from_millis(500));
+
+        assert!(!ReconnectConfig::disabled().is_enabled());
+    }
+
+    #[test]
+    fn test_is_connection_error() {
+        assert!(is_connection_error("MCP process closed connection"));
+        assert!(is_connection_error("Broken pipe (os error 32)"));
+        assert!(is_connection_error(
+            "error sending request for url (http://localhost:1/)"
+        ));
+        assert!(!is_connection_error("MCP error: {\"code\":-32602}"));
+        assert!(!is_connection_error("Provider is not an McpProvider"));
+    }
+}
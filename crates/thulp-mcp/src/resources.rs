@@ -5,13 +5,82 @@
 //! - `resources/read` - Read resource contents
 //! - `resources/templates/list` - List resource templates
 //! - `resources/subscribe` / `resources/unsubscribe` - Resource subscriptions
+//!
+//! ## Subscriptions
+//!
+//! With an [`McpRequester`] to send requests through, such as the
+//! [`McpTransport`](crate::McpTransport) an [`McpClient`](crate::McpClient)'s
+//! resources client uses, [`ResourcesClient::subscribe`] asks the server for
+//! `notifications/resources/updated` about a resource.
+//! The client is a [`NotificationSink`]: once it receives the server's
+//! notifications (an [`McpClient`](crate::McpClient) routes them to its own
+//! resources client), updates to subscribed resources are broadcast to every
+//! receiver from [`ResourcesClient::updates`] and to each [`ResourceWatch`].
+//!
+//! Servers forget subscriptions when the connection drops, so
+//! [`ResourcesClient::resubscribe`] sends them again; `McpClient::connect`
+//! does this automatically.
 
 use crate::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use thulp_core::{
-    Resource, ResourceContents, ResourceListResult, ResourceTemplate, ResourceTemplateListResult,
+    McpNotification, NotificationSink, Resource, ResourceContents, ResourceListResult,
+    ResourceTemplate, ResourceTemplateListResult,
 };
+use tokio::sync::broadcast;
+
+/// Number of updates a slow receiver can fall behind before missing some.
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// Sends JSON-RPC requests to an MCP server.
+#[async_trait]
+pub trait McpRequester: Send + Sync {
+    /// Send a request and return its result.
+    async fn send_request(&self, method: &str, params: Value) -> Result<Value>;
+}
+
+/// A change to a subscribed resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceUpdate {
+    /// Server that sent the notification.
+    pub server: String,
+    /// URI of the changed resource.
+    pub uri: String,
+}
+
+/// Updates to a single resource, from [`ResourcesClient::watch`].
+pub struct ResourceWatch {
+    uri: String,
+    receiver: broadcast::Receiver<ResourceUpdate>,
+}
+
+impl ResourceWatch {
+    /// URI being watched.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Wait for the next update to the resource.
+    ///
+    /// Returns `None` once the resources client is dropped. Updates missed
+    /// because the watch fell behind are skipped; the next one still
+    /// signals that the resource changed.
+    pub async fn next(&mut self) -> Option<ResourceUpdate> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(update) if update.uri == self.uri => return Some(update),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::debug!(uri = %self.uri, missed, "Resource watch fell behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
 
 /// MCP Resources client for managing and accessing resources.
 pub struct ResourcesClient {
@@ -21,15 +90,34 @@ pub struct ResourcesClient {
     templates_cache: RwLock<Vec<ResourceTemplate>>,
     /// Subscribed resource URIs
     subscriptions: RwLock<Vec<String>>,
+    /// Channel to the server for subscription requests
+    requester: Option<Arc<dyn McpRequester>>,
+    /// Broadcasts updates to subscribed resources
+    updates: broadcast::Sender<ResourceUpdate>,
 }
 
 impl ResourcesClient {
     /// Create a new resources client.
+    ///
+    /// Subscriptions are only recorded locally; use
+    /// [`with_requester`](Self::with_requester) to send them to a server.
     pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
         Self {
             cache: RwLock::new(HashMap::new()),
             templates_cache: RwLock::new(Vec::new()),
             subscriptions: RwLock::new(Vec::new()),
+            requester: None,
+            updates,
+        }
+    }
+
+    /// Create a resources client that sends subscription requests through
+    /// `requester`.
+    pub fn with_requester(requester: Arc<dyn McpRequester>) -> Self {
+        Self {
+            requester: Some(requester),
+            ..Self::new()
         }
     }
 
@@ -64,21 +152,90 @@ impl ResourcesClient {
     }
 
     /// Subscribe to resource changes.
+    ///
+    /// Sends `resources/subscribe` to the server, if there is a requester,
+    /// and only keeps the subscription if the server accepts it.
     pub async fn subscribe(&self, uri: &str) -> Result<()> {
-        let mut subs = self.subscriptions.write().unwrap();
-        if !subs.contains(&uri.to_string()) {
-            subs.push(uri.to_string());
+        // Record it first so an update sent along with the response isn't dropped
+        let added = {
+            let mut subs = self.subscriptions.write().unwrap();
+            let added = !subs.contains(&uri.to_string());
+            if added {
+                subs.push(uri.to_string());
+            }
+            added
+        };
+        if let Some(requester) = &self.requester {
+            let result = requester
+                .send_request("resources/subscribe", json!({ "uri": uri }))
+                .await;
+            if let Err(e) = result {
+                if added {
+                    self.subscriptions.write().unwrap().retain(|s| s != uri);
+                }
+                return Err(e);
+            }
         }
         Ok(())
     }
 
     /// Unsubscribe from resource changes.
+    ///
+    /// Updates stop being delivered even if the `resources/unsubscribe`
+    /// request fails.
     pub async fn unsubscribe(&self, uri: &str) -> Result<()> {
-        let mut subs = self.subscriptions.write().unwrap();
-        subs.retain(|s| s != uri);
+        self.subscriptions.write().unwrap().retain(|s| s != uri);
+        if let Some(requester) = &self.requester {
+            requester
+                .send_request("resources/unsubscribe", json!({ "uri": uri }))
+                .await?;
+        }
         Ok(())
     }
 
+    /// Subscribe to a resource and watch for its updates.
+    pub async fn watch(&self, uri: &str) -> Result<ResourceWatch> {
+        // Listen first so an update sent right after subscribing isn't missed
+        let receiver = self.updates.subscribe();
+        self.subscribe(uri).await?;
+        Ok(ResourceWatch {
+            uri: uri.to_string(),
+            receiver,
+        })
+    }
+
+    /// Receive updates to all subscribed resources.
+    pub fn updates(&self) -> broadcast::Receiver<ResourceUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Send `resources/subscribe` again for every subscription.
+    ///
+    /// Call this after the transport reconnects, since servers don't keep
+    /// subscriptions across connections. Every subscription is retried even
+    /// if one fails; the first error is returned.
+    pub async fn resubscribe(&self) -> Result<()> {
+        let Some(requester) = &self.requester else {
+            return Ok(());
+        };
+
+        let mut first_error = None;
+        for uri in self.subscriptions() {
+            if let Err(e) = requester
+                .send_request("resources/subscribe", json!({ "uri": uri }))
+                .await
+            {
+                tracing::warn!(uri = %uri, error = %e, "Failed to resubscribe to resource");
+                first_error.get_or_insert(e);
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
     /// Get list of subscribed resources.
     pub fn subscriptions(&self) -> Vec<String> {
         self.subscriptions.read().unwrap().clone()
@@ -115,6 +272,23 @@ impl Default for ResourcesClient {
     }
 }
 
+#[async_trait]
+impl NotificationSink for ResourcesClient {
+    /// Broadcast `notifications/resources/updated` for subscribed resources.
+    async fn notify(&self, server: &str, notification: &McpNotification) -> Result<()> {
+        if let McpNotification::ResourceUpdated { uri } = notification {
+            if self.subscriptions.read().unwrap().contains(uri) {
+                // Nobody listening is not an error
+                let _ = self.updates.send(ResourceUpdate {
+                    server: server.to_string(),
+                    uri: uri.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,4 +357,116 @@ mod tests {
         assert!(client.list().await.unwrap().resources.is_empty());
         assert!(client.subscriptions().is_empty());
     }
+
+    /// In-memory MCP server that tracks subscriptions like a real one and
+    /// forgets them when it restarts.
+    #[derive(Default)]
+    struct MockServer {
+        subscribed: std::sync::Mutex<Vec<String>>,
+        requests: std::sync::Mutex<Vec<String>>,
+        reject: std::sync::Mutex<bool>,
+    }
+
+    impl MockServer {
+        fn restart(&self) {
+            self.subscribed.lock().unwrap().clear();
+        }
+
+        /// Notify the client about a change, if it is subscribed
+        async fn change(&self, client: &ResourcesClient, uri: &str) {
+            if self.subscribed.lock().unwrap().iter().any(|s| s == uri) {
+                let notification = McpNotification::from_jsonrpc(
+                    "notifications/resources/updated",
+                    json!({ "uri": uri }),
+                );
+                client.notify("mock", &notification).await.unwrap();
+            }
+        }
+    }
+
+    #[async_trait]
+    impl McpRequester for MockServer {
+        async fn send_request(&self, method: &str, params: Value) -> Result<Value> {
+            self.requests.lock().unwrap().push(method.to_string());
+            if *self.reject.lock().unwrap() {
+                return Err(thulp_core::Error::ExecutionFailed(
+                    "unavailable".to_string(),
+                ));
+            }
+            let uri = params["uri"].as_str().unwrap_or_default().to_string();
+            let mut subscribed = self.subscribed.lock().unwrap();
+            match method {
+                "resources/subscribe" => subscribed.push(uri),
+                "resources/unsubscribe" => subscribed.retain(|s| *s != uri),
+                _ => {}
+            }
+            Ok(json!({}))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_receives_updates() {
+        let server = Arc::new(MockServer::default());
+        let client = ResourcesClient::with_requester(server.clone());
+        let mut all = client.updates();
+
+        let mut watch = client.watch("file:///a.md").await.unwrap();
+        client.subscribe("file:///b.md").await.unwrap();
+        assert_eq!(watch.uri(), "file:///a.md");
+
+        server.change(&client, "file:///b.md").await;
+        server.change(&client, "file:///a.md").await;
+
+        let update = watch.next().await.unwrap();
+        assert_eq!(update.uri, "file:///a.md");
+        assert_eq!(update.server, "mock");
+        assert_eq!(all.recv().await.unwrap().uri, "file:///b.md");
+        assert_eq!(all.recv().await.unwrap().uri, "file:///a.md");
+
+        // Unsubscribed resources are ignored even if the server keeps sending
+        client.unsubscribe("file:///b.md").await.unwrap();
+        client
+            .notify(
+                "mock",
+                &McpNotification::ResourceUpdated {
+                    uri: "file:///b.md".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(all.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_after_reconnect() {
+        let server = Arc::new(MockServer::default());
+        let client = ResourcesClient::with_requester(server.clone());
+        let mut watch = client.watch("file:///a.md").await.unwrap();
+
+        // The server restarts and no longer knows about the subscription
+        server.restart();
+        server.change(&client, "file:///a.md").await;
+        assert!(watch.receiver.try_recv().is_err());
+
+        client.resubscribe().await.unwrap();
+        server.change(&client, "file:///a.md").await;
+        assert_eq!(watch.next().await.unwrap().uri, "file:///a.md");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_subscription_is_not_recorded() {
+        let server = Arc::new(MockServer::default());
+        *server.reject.lock().unwrap() = true;
+        let client = ResourcesClient::with_requester(server.clone());
+
+        assert!(client.subscribe("file:///a.md").await.is_err());
+        assert!(client.subscriptions().is_empty());
+        assert_eq!(
+            *server.requests.lock().unwrap(),
+            vec!["resources/subscribe"]
+        );
+
+        // Without a requester subscriptions stay local
+        assert!(ResourcesClient::new().resubscribe().await.is_ok());
+    }
 }
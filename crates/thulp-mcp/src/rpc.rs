@@ -0,0 +1,650 @@
+//! JSON-RPC connections to MCP servers.
+//!
+//! An [`RpcConnection`] talks to one server, either over a child process's
+//! stdin and stdout, one message per line, or over HTTP, POSTing each message
+//! and reading the answer as JSON or as an event stream, handled event by
+//! event as it arrives. Over HTTP the connection also keeps a GET event stream
+//! open, if the server offers one, for messages sent between requests.
+//! Responses are matched to requests by id. Notifications the server sends in
+//! between go to a [`NotificationRouter`], and requests the server makes of
+//! the client are answered: `ping` with an empty result, anything else as an
+//! unknown method.
+
+use crate::NotificationRouter;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::oneshot;
+
+/// Protocol revision asked for in `initialize`
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// How long a server may take to answer `initialize`
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long connecting to an HTTP server may take. Requests themselves
+/// aren't limited here, so callers' timeouts apply
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Header carrying the session an HTTP server assigned in `initialize`
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Error for requests to a server that went away, which
+/// [`is_connection_error`](crate::reconnect::is_connection_error) recognizes
+const CLOSED: &str = "MCP server closed connection";
+
+/// JSON-RPC error code for methods the client doesn't implement
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Where an MCP server is reached
+#[derive(Debug, Clone)]
+pub(crate) enum Endpoint {
+    /// A program speaking MCP on its stdin and stdout
+    Stdio { command: String, args: Vec<String> },
+    /// An MCP server's HTTP endpoint
+    Http { url: String },
+}
+
+/// Requests waiting for their response, by id
+type Replies = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// An initialized connection to an MCP server
+pub(crate) struct RpcConnection {
+    next_id: AtomicU64,
+    channel: Channel,
+}
+
+enum Channel {
+    Stdio(StdioChannel),
+    Http(HttpChannel),
+}
+
+impl RpcConnection {
+    /// Connect to `endpoint` and complete the `initialize` handshake,
+    /// delivering the server's notifications to `notifications`
+    pub(crate) async fn open(
+        server: &str,
+        endpoint: &Endpoint,
+        notifications: Arc<NotificationRouter>,
+    ) -> Result<Self, String> {
+        let channel = match endpoint {
+            Endpoint::Stdio { command, args } => {
+                Channel::Stdio(StdioChannel::spawn(server, command, args, notifications)?)
+            }
+            Endpoint::Http { url } => Channel::Http(HttpChannel::new(server, url, notifications)?),
+        };
+        let connection = Self {
+            next_id: AtomicU64::new(1),
+            channel,
+        };
+
+        let initialize = connection.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "thulp", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        );
+        match tokio::time::timeout(INITIALIZE_TIMEOUT, initialize).await {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err(format!(
+                    "no answer to initialize within {:?}",
+                    INITIALIZE_TIMEOUT
+                ))
+            }
+        };
+        connection
+            .notify("notifications/initialized", json!({}))
+            .await?;
+        if let Channel::Http(channel) = &connection.channel {
+            channel.listen();
+        }
+        Ok(connection)
+    }
+
+    /// Send a request and wait for its result
+    pub(crate) async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        match &self.channel {
+            Channel::Stdio(channel) => channel.request(id, &message).await,
+            Channel::Http(channel) => channel
+                .post(&message, Some(id))
+                .await?
+                .unwrap_or_else(|| Err("No response to MCP request".to_string())),
+        }
+    }
+
+    /// Send a notification
+    pub(crate) async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        match &self.channel {
+            Channel::Stdio(channel) => write_line(&channel.stdin, &message).await,
+            Channel::Http(channel) => channel.post(&message, None).await.map(|_| ()),
+        }
+    }
+}
+
+/// A server running as a child process
+struct StdioChannel {
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    replies: Replies,
+    /// Set, with `replies` locked, once the server's output ends
+    closed: Arc<AtomicBool>,
+    reader: tokio::task::JoinHandle<()>,
+    /// Killed when the connection is dropped
+    _child: Child,
+}
+
+impl StdioChannel {
+    fn spawn(
+        server: &str,
+        command: &str,
+        args: &[String],
+        notifications: Arc<NotificationRouter>,
+    ) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start '{}': {}", command, e))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(format!("Failed to open the pipes of '{}'", command));
+        };
+
+        let stdin = Arc::new(tokio::sync::Mutex::new(stdin));
+        let replies = Replies::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader = tokio::spawn(read_stdio(
+            server.to_string(),
+            stdout,
+            stdin.clone(),
+            replies.clone(),
+            closed.clone(),
+            notifications,
+        ));
+        Ok(Self {
+            stdin,
+            replies,
+            closed,
+            reader,
+            _child: child,
+        })
+    }
+
+    async fn request(&self, id: u64, message: &Value) -> Result<Value, String> {
+        let (reply, response) = oneshot::channel();
+        {
+            let mut replies = self.replies.lock().unwrap();
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(CLOSED.to_string());
+            }
+            replies.insert(id, reply);
+        }
+        if let Err(e) = write_line(&self.stdin, message).await {
+            self.replies.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+        response.await.unwrap_or_else(|_| Err(CLOSED.to_string()))
+    }
+}
+
+impl Drop for StdioChannel {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Read the server's messages until its output ends, then fail the requests
+/// still waiting
+async fn read_stdio(
+    server: String,
+    stdout: ChildStdout,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    replies: Replies,
+    closed: Arc<AtomicBool>,
+    notifications: Arc<NotificationRouter>,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value = match serde_json::from_str::<Value>(&line) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::debug!(server = %server, error = %e, "Ignoring output that isn't JSON");
+                continue;
+            }
+        };
+        for message in batch(value) {
+            match classify(message) {
+                Some(Incoming::Response { id, result }) => {
+                    if let Some(reply) = replies.lock().unwrap().remove(&id) {
+                        let _ = reply.send(result);
+                    }
+                }
+                Some(Incoming::Notification { method, params }) => {
+                    route(&notifications, &server, &method, params).await
+                }
+                Some(Incoming::Request { id, method }) => {
+                    let _ = write_line(&stdin, &answer(id, &method)).await;
+                }
+                None => {}
+            }
+        }
+    }
+
+    let mut replies = replies.lock().unwrap();
+    closed.store(true, Ordering::SeqCst);
+    replies.clear();
+}
+
+async fn write_line(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<(), String> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut stdin = stdin.lock().await;
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    stdin.flush().await.map_err(|e| e.to_string())
+}
+
+/// A server's HTTP endpoint
+struct HttpChannel {
+    shared: Arc<HttpShared>,
+    /// Reads the server's GET event stream; stopped when the channel is dropped
+    listener: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+/// What requests share with the event stream listener
+struct HttpShared {
+    server: String,
+    client: reqwest::Client,
+    url: String,
+    /// Session the server assigned, sent with every later message
+    session: Mutex<Option<String>>,
+    notifications: Arc<NotificationRouter>,
+}
+
+impl HttpChannel {
+    fn new(
+        server: &str,
+        url: &str,
+        notifications: Arc<NotificationRouter>,
+    ) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            shared: Arc::new(HttpShared {
+                server: server.to_string(),
+                client,
+                url: url.to_string(),
+                session: Mutex::new(None),
+                notifications,
+            }),
+            listener: Mutex::new(None),
+        })
+    }
+
+    /// POST a message, handling what the server answers with as it arrives,
+    /// and return the outcome of request `id` if the server answered it
+    async fn post(
+        &self,
+        message: &Value,
+        id: Option<u64>,
+    ) -> Result<Option<Result<Value, String>>, String> {
+        let shared = &self.shared;
+        let request = shared
+            .client
+            .post(&shared.url)
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .json(message);
+        let response = shared.send(request).await?;
+        if !response.status().is_success() {
+            return Err(format!("MCP request failed: {}", response.status()));
+        }
+        shared.read(response, id).await
+    }
+
+    /// Keep the server's GET event stream open in the background, for the
+    /// messages it sends between requests
+    fn listen(&self) {
+        let shared = self.shared.clone();
+        let listener = tokio::spawn(async move {
+            if let Err(e) = shared.listen().await {
+                tracing::debug!(server = %shared.server, error = %e, "MCP event stream ended");
+            }
+        });
+        *self.listener.lock().unwrap() = Some(listener);
+    }
+}
+
+impl Drop for HttpChannel {
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.get_mut().unwrap().take() {
+            listener.abort();
+        }
+    }
+}
+
+impl HttpShared {
+    /// Send a request in the session, keeping the session the server assigns
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+        let session = self.session.lock().unwrap().clone();
+        let request = match &session {
+            Some(session) => request.header(SESSION_HEADER, session),
+            None => request,
+        };
+        let response = request.send().await.map_err(|e| e.to_string())?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND && session.is_some() {
+            // The server ended the session; reconnecting starts a new one
+            return Err(format!("{}: session expired", CLOSED));
+        }
+        let assigned = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok());
+        if let Some(id) = assigned {
+            *self.session.lock().unwrap() = Some(id.to_string());
+        }
+        Ok(response)
+    }
+
+    /// Read the GET event stream until the server ends it
+    async fn listen(&self) -> Result<(), String> {
+        let request = self
+            .client
+            .get(&self.url)
+            .header(reqwest::header::ACCEPT, "text/event-stream");
+        let response = self.send(request).await?;
+        if response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+            // The server only talks in answer to requests
+            return Ok(());
+        }
+        if !response.status().is_success() {
+            return Err(format!("MCP event stream refused: {}", response.status()));
+        }
+        self.read(response, None).await.map(|_| ())
+    }
+
+    /// Handle the messages of a response, event by event for an event stream,
+    /// until request `id` is answered or the response ends
+    async fn read(
+        &self,
+        mut response: reqwest::Response,
+        id: Option<u64>,
+    ) -> Result<Option<Result<Value, String>>, String> {
+        let event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        let mut outcome = None;
+
+        if !event_stream {
+            let body = response.text().await.map_err(|e| e.to_string())?;
+            if body.trim().is_empty() {
+                return Ok(None);
+            }
+            let value =
+                serde_json::from_str(&body).map_err(|e| format!("Invalid MCP response: {}", e))?;
+            for message in batch(value) {
+                self.handle(message, id, &mut outcome).await;
+            }
+            return Ok(outcome);
+        }
+
+        let mut events = EventStream::default();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            for data in events.push(&chunk) {
+                self.handle_event(&data, id, &mut outcome).await;
+            }
+            if outcome.is_some() {
+                return Ok(outcome);
+            }
+        }
+        if let Some(data) = events.finish() {
+            self.handle_event(&data, id, &mut outcome).await;
+        }
+        Ok(outcome)
+    }
+
+    async fn handle_event(
+        &self,
+        data: &str,
+        id: Option<u64>,
+        outcome: &mut Option<Result<Value, String>>,
+    ) {
+        match serde_json::from_str(data) {
+            Ok(value) => {
+                for message in batch(value) {
+                    self.handle(message, id, outcome).await;
+                }
+            }
+            Err(e) => {
+                tracing::debug!(server = %self.server, error = %e, "Ignoring event that isn't JSON")
+            }
+        }
+    }
+
+    /// Handle one message, keeping it in `outcome` if it answers request `id`
+    async fn handle(
+        &self,
+        message: Value,
+        id: Option<u64>,
+        outcome: &mut Option<Result<Value, String>>,
+    ) {
+        match classify(message) {
+            Some(Incoming::Response {
+                id: answered,
+                result,
+            }) if Some(answered) == id => {
+                *outcome = Some(result);
+            }
+            Some(Incoming::Notification { method, params }) => {
+                route(&self.notifications, &self.server, &method, params).await
+            }
+            Some(Incoming::Request { id, method }) => {
+                let request = self
+                    .client
+                    .post(&self.url)
+                    .header(
+                        reqwest::header::ACCEPT,
+                        "application/json, text/event-stream",
+                    )
+                    .json(&answer(id, &method));
+                let _ = self.send(request).await;
+            }
+            Some(Incoming::Response { .. }) | None => {}
+        }
+    }
+}
+
+/// A message from the server
+enum Incoming {
+    /// Outcome of one of our requests
+    Response {
+        id: u64,
+        result: Result<Value, String>,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+    /// A request the server makes of the client
+    Request {
+        id: Value,
+        method: String,
+    },
+}
+
+fn classify(message: Value) -> Option<Incoming> {
+    let Value::Object(mut message) = message else {
+        return None;
+    };
+    let method = message
+        .get("method")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    match (method, message.remove("id")) {
+        (Some(method), Some(id)) => Some(Incoming::Request { id, method }),
+        (Some(method), None) => Some(Incoming::Notification {
+            method,
+            params: message.remove("params").unwrap_or(Value::Null),
+        }),
+        (None, Some(id)) => {
+            let result = match message.remove("error") {
+                Some(error) => Err(format!("MCP error: {}", error)),
+                None => message
+                    .remove("result")
+                    .ok_or_else(|| "No result in MCP response".to_string()),
+            };
+            Some(Incoming::Response {
+                id: id.as_u64()?,
+                result,
+            })
+        }
+        (None, None) => None,
+    }
+}
+
+/// The client's response to a request from the server
+fn answer(id: Value, method: &str) -> Value {
+    match method {
+        "ping" => json!({ "jsonrpc": "2.0", "id": id, "result": {} }),
+        _ => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": METHOD_NOT_FOUND, "message": format!("Method not found: {}", method) },
+        }),
+    }
+}
+
+/// The messages of a JSON-RPC batch, or the one message
+fn batch(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(messages) => messages,
+        message => vec![message],
+    }
+}
+
+/// Splits an event stream into the data of its events as chunks arrive
+#[derive(Default)]
+struct EventStream {
+    /// Start of a line whose end hasn't arrived yet
+    line: Vec<u8>,
+    /// Data of the event being read
+    data: String,
+}
+
+impl EventStream {
+    /// Take in a chunk and return the data of the events it completes
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let bytes = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&bytes);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(std::mem::take(&mut self.data));
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !self.data.is_empty() {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        events
+    }
+
+    /// The data of an event the stream ended in the middle of
+    fn finish(mut self) -> Option<String> {
+        self.push(b"\n\n").pop()
+    }
+}
+
+async fn route(notifications: &NotificationRouter, server: &str, method: &str, params: Value) {
+    if let Err(e) = notifications.route_jsonrpc(server, method, params).await {
+        tracing::warn!(
+            server = %server,
+            method = %method,
+            error = %e,
+            "Failed to deliver MCP notification"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let response = json!({ "jsonrpc": "2.0", "id": 3, "result": { "tools": [] } });
+        assert!(matches!(
+            classify(response),
+            Some(Incoming::Response {
+                id: 3,
+                result: Ok(_)
+            })
+        ));
+        let error = json!({ "jsonrpc": "2.0", "id": 4, "error": { "code": -1, "message": "no" } });
+        match classify(error) {
+            Some(Incoming::Response { result: Err(e), .. }) => assert!(e.contains("no")),
+            _ => panic!("expected an error response"),
+        }
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/message" });
+        assert!(matches!(
+            classify(notification),
+            Some(Incoming::Notification {
+                params: Value::Null,
+                ..
+            })
+        ));
+        let request = json!({ "jsonrpc": "2.0", "id": "a", "method": "ping" });
+        assert!(matches!(classify(request), Some(Incoming::Request { .. })));
+        assert!(classify(json!("hello")).is_none());
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(answer(json!(7), "ping")["result"], json!({}));
+        assert_eq!(
+            answer(json!(8), "sampling/createMessage")["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let mut events = EventStream::default();
+        assert!(events.push(b"event: message\ndata: {\"a\"").is_empty());
+        assert_eq!(events.push(b":1}\r\n\r\n: comment\n"), ["{\"a\":1}"]);
+        assert!(events.push(b"data: [1,\ndata: 2]").is_empty());
+        assert_eq!(events.finish().as_deref(), Some("[1,\n2]"));
+    }
+}
@@ -0,0 +1,1015 @@
+//! Serve a [`Transport`]'s tools to other agent hosts as an MCP server.
+//!
+//! [`McpServer`] answers the JSON-RPC side of the Model Context Protocol for
+//! any transport: `tools/list` publishes its tool definitions and
+//! `tools/call` forwards to [`Transport::call`]. It can be driven over stdio,
+//! one message per line, which is what desktop hosts spawn:
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use thulp_mcp::{McpConnectionManager, McpServer};
+//!
+//! # async fn example() -> thulp_core::Result<()> {
+//! let mut manager = McpConnectionManager::new();
+//! manager.add_stdio("fs", "mcp-server-filesystem", Some(vec![".".to_string()]))?;
+//! thulp_core::Transport::connect(&mut manager).await?;
+//!
+//! McpServer::new(Arc::new(manager))
+//!     .with_name("thulp", "0.1.0")
+//!     .serve_stdio()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `http-server` feature, `McpServer::router` also serves it over
+//! HTTP: `POST /mcp` answers a message directly, and `GET /sse` plus
+//! `POST /messages` implement the HTTP+SSE transport. Requests from browser
+//! pages are refused unless their `Origin` is a loopback address or was
+//! allowed with `McpServer::with_allowed_origin`, which keeps other sites
+//! from reaching a local server through DNS rebinding, and
+//! `McpServer::with_bearer_token` makes every request authenticate.
+//! `GET /healthz` answers as long as the server runs, and `GET /readyz`
+//! reports a `ReadinessCheck`, for orchestrators' probes.
+//!
+//! MCP tool names may only contain letters, digits, `_` and `-`, so other
+//! characters (such as the `.` in routed `server.tool` names) are published
+//! as `_` and mapped back when the tool is called.
+
+use crate::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thulp_core::{Error, ToolCall, ToolDefinition, ToolResult, Transport};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, RwLock};
+
+/// MCP protocol revision announced when the client asks for one we don't know.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Protocol revisions the server accepts from `initialize`.
+const SUPPORTED_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// MCP server publishing the tools of a transport.
+///
+/// Cloning is cheap; clones share the transport and the tool name map.
+#[derive(Clone)]
+pub struct McpServer {
+    transport: Arc<dyn Transport>,
+    name: String,
+    version: String,
+    instructions: Option<String>,
+    /// Published tool name -> transport tool name
+    names: Arc<RwLock<HashMap<String, String>>>,
+    /// Token HTTP requests must send as `Authorization: Bearer`
+    #[cfg(feature = "http-server")]
+    bearer_token: Option<Arc<str>>,
+    /// Browser origins allowed besides loopback ones
+    #[cfg(feature = "http-server")]
+    allowed_origins: Arc<Vec<String>>,
+    /// What `GET /readyz` reports, instead of listing the tools
+    #[cfg(feature = "http-server")]
+    readiness: Option<Arc<dyn ReadinessCheck>>,
+}
+
+impl McpServer {
+    /// Create a server for the tools of `transport`.
+    ///
+    /// The transport should already be connected.
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instructions: None,
+            names: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "http-server")]
+            bearer_token: None,
+            #[cfg(feature = "http-server")]
+            allowed_origins: Arc::new(Vec::new()),
+            #[cfg(feature = "http-server")]
+            readiness: None,
+        }
+    }
+
+    /// Set the name and version reported in `serverInfo`.
+    pub fn with_name(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.name = name.into();
+        self.version = version.into();
+        self
+    }
+
+    /// Set the usage instructions returned from `initialize`.
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Handle one JSON-RPC message.
+    ///
+    /// Returns the response, or `None` for notifications. A JSON array is
+    /// treated as a batch.
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(messages) => {
+                if messages.is_empty() {
+                    return Some(error_response(
+                        Value::Null,
+                        INVALID_REQUEST,
+                        "Empty batch".to_string(),
+                    ));
+                }
+                let mut responses = Vec::new();
+                for message in messages {
+                    if let Some(response) = self.handle_message(message).await {
+                        responses.push(response);
+                    }
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_message(message).await,
+        }
+    }
+
+    /// Handle one JSON-RPC message given as text.
+    ///
+    /// Text that is not JSON gets a parse error response.
+    pub async fn handle_text(&self, text: &str) -> Option<Value> {
+        match serde_json::from_str(text) {
+            Ok(message) => self.handle(message).await,
+            Err(e) => Some(error_response(
+                Value::Null,
+                PARSE_ERROR,
+                format!("Parse error: {}", e),
+            )),
+        }
+    }
+
+    /// Serve newline-delimited JSON-RPC messages until `reader` is closed.
+    ///
+    /// Requests are handled concurrently, so a slow tool call does not hold
+    /// up pings or other calls; responses are written as they complete.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+        let writer_task = tokio::spawn(async move {
+            while let Some(response) = rx.recv().await {
+                let mut line = response.to_string();
+                line.push('\n');
+                writer.write_all(line.as_bytes()).await?;
+                writer.flush().await?;
+            }
+            Ok::<_, std::io::Error>(())
+        });
+
+        let mut lines = reader.lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to read message: {}", e)))?
+        {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let server = self.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Some(response) = server.handle_text(&line).await {
+                    let _ = tx.send(response);
+                }
+            });
+        }
+
+        drop(tx);
+        writer_task
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Writer task failed: {}", e)))?
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to write response: {}", e)))
+    }
+
+    /// Serve over this process's stdin and stdout.
+    ///
+    /// Anything else the process prints must go to stderr.
+    pub async fn serve_stdio(&self) -> Result<()> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.serve(stdin, tokio::io::stdout()).await
+    }
+
+    async fn handle_message(&self, message: Value) -> Option<Value> {
+        let Some(object) = message.as_object() else {
+            return Some(error_response(
+                Value::Null,
+                INVALID_REQUEST,
+                "Message must be an object".to_string(),
+            ));
+        };
+        let id = object.get("id").cloned();
+        let Some(method) = object.get("method").and_then(Value::as_str) else {
+            // Responses to server-initiated requests; we never send any
+            return id.map(|id| error_response(id, INVALID_REQUEST, "Missing method".to_string()));
+        };
+        let params = object.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications get no response, whatever they are
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => self.list_tools().await,
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let version = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .filter(|v| SUPPORTED_VERSIONS.contains(v))
+            .unwrap_or(PROTOCOL_VERSION);
+
+        let mut result = json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": self.name, "version": self.version },
+        });
+        if let Some(instructions) = &self.instructions {
+            result["instructions"] = json!(instructions);
+        }
+        result
+    }
+
+    async fn list_tools(&self) -> std::result::Result<Value, (i64, String)> {
+        let tools = self.refresh_names().await?;
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": published_name(&tool.name),
+                    "description": tool.description,
+                    "inputSchema": tool.to_mcp_input_schema(),
+                    "annotations": { "destructiveHint": tool.destructive },
+                })
+            })
+            .collect();
+        Ok(json!({ "tools": tools }))
+    }
+
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| (INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let arguments = match params.get("arguments") {
+            None | Some(Value::Null) => json!({}),
+            Some(arguments @ Value::Object(_)) => arguments.clone(),
+            Some(_) => {
+                return Err((
+                    INVALID_PARAMS,
+                    "Tool arguments must be an object".to_string(),
+                ))
+            }
+        };
+
+        // Clients may call without listing first
+        let mut tool = self.names.read().await.get(name).cloned();
+        if tool.is_none() {
+            self.refresh_names().await?;
+            tool = self.names.read().await.get(name).cloned();
+        }
+        let tool = tool.ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {}", name)))?;
+
+        let mut call = ToolCall::new(tool);
+        call.arguments = arguments;
+        match self.transport.call(&call).await {
+            Ok(result) => Ok(call_result(&result)),
+            Err(Error::ToolNotFound(_)) => Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+            Err(e) => Ok(json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            })),
+        }
+    }
+
+    async fn refresh_names(&self) -> std::result::Result<Vec<ToolDefinition>, (i64, String)> {
+        let tools = self
+            .transport
+            .list_tools()
+            .await
+            .map_err(|e| (INTERNAL_ERROR, format!("Failed to list tools: {}", e)))?;
+        let mut names = self.names.write().await;
+        names.clear();
+        for tool in &tools {
+            names.insert(published_name(&tool.name), tool.name.clone());
+        }
+        Ok(tools)
+    }
+}
+
+/// Name a tool is published under: characters outside `[A-Za-z0-9_-]`
+/// become `_`.
+pub fn published_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn call_result(result: &ToolResult) -> Value {
+    let text = if result.success {
+        match &result.data {
+            Some(Value::String(s)) => s.clone(),
+            Some(data) => serde_json::to_string_pretty(data).unwrap_or_else(|_| data.to_string()),
+            None => String::new(),
+        }
+    } else {
+        result
+            .error
+            .clone()
+            .unwrap_or_else(|| "Tool call failed".to_string())
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": !result.success,
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(feature = "http-server")]
+pub use http::{Readiness, ReadinessCheck};
+
+#[cfg(feature = "http-server")]
+mod http {
+    use super::McpServer;
+    use crate::McpConnectionManager;
+    use async_trait::async_trait;
+    use axum::extract::{Query, Request, State};
+    use axum::http::{header, StatusCode};
+    use axum::middleware::{self, Next};
+    use axum::response::sse::{Event, KeepAlive, Sse};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc;
+
+    type Sessions = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>;
+
+    #[derive(Clone)]
+    struct AppState {
+        server: McpServer,
+        sessions: Sessions,
+    }
+
+    #[derive(Deserialize)]
+    struct SessionQuery {
+        session_id: String,
+    }
+
+    /// Answer to `GET /readyz`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Readiness {
+        /// Whether calls can be served; `/readyz` answers `503` otherwise
+        pub ready: bool,
+        /// State of what the server depends on, sent as the response body
+        /// under `ready`
+        pub details: serde_json::Map<String, Value>,
+    }
+
+    /// Reports whether an [`McpServer`] is ready for calls.
+    #[async_trait]
+    pub trait ReadinessCheck: Send + Sync {
+        /// Check what the server depends on.
+        async fn readiness(&self) -> Readiness;
+    }
+
+    /// Ready when every server is healthy, with each server's health under
+    /// `servers`
+    #[async_trait]
+    impl ReadinessCheck for McpConnectionManager {
+        async fn readiness(&self) -> Readiness {
+            let servers = self.health_check().await;
+            let tools: usize = servers.iter().map(|server| server.tools).sum();
+            let mut details = serde_json::Map::new();
+            details.insert("tools".to_string(), json!(tools));
+            details.insert("servers".to_string(), json!(servers));
+            Readiness {
+                ready: servers.iter().all(|server| server.is_healthy()),
+                details,
+            }
+        }
+    }
+
+    impl McpServer {
+        /// Require HTTP requests to send `Authorization: Bearer <token>`.
+        pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+            self.bearer_token = Some(token.into().into());
+            self
+        }
+
+        /// Accept browser requests from `origin` (such as
+        /// `https://app.example.com`) as well as from loopback addresses.
+        pub fn with_allowed_origin(mut self, origin: impl Into<String>) -> Self {
+            Arc::make_mut(&mut self.allowed_origins).push(origin.into());
+            self
+        }
+
+        /// Report `check` from `GET /readyz`.
+        ///
+        /// Without one, the server is ready when the transport is connected
+        /// and lists its tools.
+        pub fn with_readiness(mut self, check: Arc<dyn ReadinessCheck>) -> Self {
+            self.readiness = Some(check);
+            self
+        }
+
+        /// HTTP routes serving this server.
+        ///
+        /// - `POST /mcp` answers a JSON-RPC message in the response body
+        ///   (`202 Accepted` for notifications)
+        /// - `GET /sse` opens an event stream whose first `endpoint` event
+        ///   names the URL to post messages to
+        /// - `POST /messages?session_id=...` accepts a message and sends the
+        ///   response on that session's event stream
+        /// - `GET /healthz` answers `200` while the server runs
+        /// - `GET /readyz` answers `200` when ready and `503` otherwise, with
+        ///   the [`Readiness`] details as JSON
+        ///
+        /// Requests with a disallowed `Origin` get `403 Forbidden`, and ones
+        /// without the bearer token, if one is set, `401 Unauthorized`;
+        /// `/healthz` is exempt from the token so probes need no secret.
+        pub fn router(&self) -> Router {
+            let state = AppState {
+                server: self.clone(),
+                sessions: Arc::new(Mutex::new(HashMap::new())),
+            };
+            Router::new()
+                .route("/mcp", post(handle_post))
+                .route("/sse", get(handle_sse))
+                .route("/messages", post(handle_session_message))
+                .route("/readyz", get(handle_readyz))
+                .route_layer(middleware::from_fn_with_state(state.clone(), check_request))
+                .route("/healthz", get(handle_healthz))
+                .with_state(state)
+        }
+
+        /// Serve over HTTP on `listener` until the process exits.
+        pub async fn serve_http(&self, listener: tokio::net::TcpListener) -> crate::Result<()> {
+            axum::serve(listener, self.router()).await.map_err(|e| {
+                thulp_core::Error::ExecutionFailed(format!("HTTP server failed: {}", e))
+            })
+        }
+    }
+
+    /// Refuse requests from other sites' pages and without the bearer token
+    async fn check_request(
+        State(state): State<AppState>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if let Some(origin) = request.headers().get(header::ORIGIN) {
+            let allowed = origin.to_str().is_ok_and(|origin| {
+                is_loopback_origin(origin)
+                    || state
+                        .server
+                        .allowed_origins
+                        .iter()
+                        .any(|allowed| allowed == origin)
+            });
+            if !allowed {
+                return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+            }
+        }
+        if let Some(token) = &state.server.bearer_token {
+            let sent = request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            if !sent.is_some_and(|sent| same_token(sent.as_bytes(), token.as_bytes())) {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Missing or wrong bearer token",
+                )
+                    .into_response();
+            }
+        }
+        next.run(request).await
+    }
+
+    /// Whether an `Origin` header names localhost or a loopback address
+    fn is_loopback_origin(origin: &str) -> bool {
+        let Some((_, authority)) = origin.split_once("://") else {
+            return false;
+        };
+        let host = match authority.strip_prefix('[') {
+            Some(v6) => v6.split(']').next().unwrap_or_default(),
+            None => authority.split(':').next().unwrap_or_default(),
+        };
+        host.eq_ignore_ascii_case("localhost")
+            || host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+
+    /// Compare tokens in time independent of where they differ
+    fn same_token(sent: &[u8], token: &[u8]) -> bool {
+        let diff = sent
+            .iter()
+            .zip(token)
+            .fold(0, |diff, (a, b)| diff | (a ^ b));
+        sent.len() == token.len() && diff == 0
+    }
+
+    async fn handle_healthz() -> Response {
+        Json(json!({ "status": "ok" })).into_response()
+    }
+
+    async fn handle_readyz(State(state): State<AppState>) -> Response {
+        let readiness = match &state.server.readiness {
+            Some(check) => check.readiness().await,
+            None => transport_readiness(&state.server).await,
+        };
+        let status = match readiness.ready {
+            true => StatusCode::OK,
+            false => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        let mut body = readiness.details;
+        body.insert("ready".to_string(), json!(readiness.ready));
+        (status, Json(Value::Object(body))).into_response()
+    }
+
+    /// Ready when the transport is connected and lists its tools
+    async fn transport_readiness(server: &McpServer) -> Readiness {
+        let mut details = serde_json::Map::new();
+        let listed = match server.transport.is_connected() {
+            true => server
+                .transport
+                .list_tools()
+                .await
+                .map_err(|e| e.to_string()),
+            false => Err("not connected".to_string()),
+        };
+        let ready = match listed {
+            Ok(tools) => {
+                details.insert("tools".to_string(), json!(tools.len()));
+                true
+            }
+            Err(e) => {
+                details.insert("error".to_string(), json!(e));
+                false
+            }
+        };
+        Readiness { ready, details }
+    }
+
+    async fn handle_post(State(state): State<AppState>, body: String) -> Response {
+        match state.server.handle_text(&body).await {
+            Some(response) => Json(response).into_response(),
+            None => StatusCode::ACCEPTED.into_response(),
+        }
+    }
+
+    async fn handle_sse(
+        State(state): State<AppState>,
+    ) -> Sse<impl futures::Stream<Item = Result<Event, Infallible>>> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = mpsc::unbounded_channel::<Value>();
+        state
+            .sessions
+            .lock()
+            .unwrap()
+            .insert(session_id.clone(), tx);
+
+        let endpoint = Event::default()
+            .event("endpoint")
+            .data(format!("/messages?session_id={}", session_id));
+        let guard = SessionGuard {
+            id: session_id,
+            sessions: state.sessions,
+        };
+        let messages = futures::stream::unfold((rx, guard), |(mut rx, guard)| async move {
+            let message = rx.recv().await?;
+            let event = Event::default().event("message").data(message.to_string());
+            Some((Ok(event), (rx, guard)))
+        });
+        let stream =
+            futures::StreamExt::chain(futures::stream::once(async move { Ok(endpoint) }), messages);
+        Sse::new(stream).keep_alive(KeepAlive::default())
+    }
+
+    async fn handle_session_message(
+        State(state): State<AppState>,
+        Query(query): Query<SessionQuery>,
+        body: String,
+    ) -> Response {
+        let Some(tx) = state
+            .sessions
+            .lock()
+            .unwrap()
+            .get(&query.session_id)
+            .cloned()
+        else {
+            return (StatusCode::NOT_FOUND, "Unknown session").into_response();
+        };
+        tokio::spawn(async move {
+            if let Some(response) = state.server.handle_text(&body).await {
+                let _ = tx.send(response);
+            }
+        });
+        StatusCode::ACCEPTED.into_response()
+    }
+
+    /// Removes a session when its event stream is dropped.
+    struct SessionGuard {
+        id: String,
+        sessions: Sessions,
+    }
+
+    impl Drop for SessionGuard {
+        fn drop(&mut self) {
+            if let Ok(mut sessions) = self.sessions.lock() {
+                sessions.remove(&self.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use thulp_core::Parameter;
+
+    struct EchoTransport;
+
+    #[async_trait]
+    impl Transport for EchoTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![
+                ToolDefinition::builder("fs.echo")
+                    .description("Echo text")
+                    .parameter(Parameter::required_string("text"))
+                    .build(),
+                ToolDefinition::builder("fail").build(),
+            ])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            match call.tool.as_str() {
+                "fs.echo" => Ok(ToolResult::success(call.arguments["text"].clone())),
+                "fail" => Ok(ToolResult::failure("it broke")),
+                other => Err(Error::ToolNotFound(other.to_string())),
+            }
+        }
+    }
+
+    /// A server that never connected
+    #[cfg(feature = "http-server")]
+    struct Disconnected;
+
+    #[cfg(feature = "http-server")]
+    #[async_trait]
+    impl Transport for Disconnected {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Err(Error::ExecutionFailed("unreachable".to_string()))
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            false
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Err(Error::ExecutionFailed("not connected".to_string()))
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            Err(Error::ToolNotFound(call.tool.clone()))
+        }
+    }
+
+    fn server() -> McpServer {
+        McpServer::new(Arc::new(EchoTransport)).with_name("test", "1.0")
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    #[tokio::test]
+    async fn test_initialize() {
+        let response = server()
+            .handle(request(
+                1,
+                "initialize",
+                json!({ "protocolVersion": "2025-03-26" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "test");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+
+        let response = server()
+            .handle(request(
+                2,
+                "initialize",
+                json!({ "protocolVersion": "1999-01-01" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_get_no_response() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server().handle(notification).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_tools() {
+        let response = server()
+            .handle(request(1, "tools/list", json!({})))
+            .await
+            .unwrap();
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0]["name"], "fs_echo");
+        assert_eq!(tools[0]["description"], "Echo text");
+        assert_eq!(tools[0]["inputSchema"]["required"], json!(["text"]));
+    }
+
+    #[tokio::test]
+    async fn test_call_tool() {
+        let server = server();
+        // Published names resolve without a prior tools/list
+        let response = server
+            .handle(request(
+                1,
+                "tools/call",
+                json!({ "name": "fs_echo", "arguments": { "text": "hi" } }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "hi");
+        assert_eq!(response["result"]["isError"], false);
+
+        let response = server
+            .handle(request(2, "tools/call", json!({ "name": "fail" })))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "it broke");
+        assert_eq!(response["result"]["isError"], true);
+
+        let response = server
+            .handle(request(3, "tools/call", json!({ "name": "missing" })))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let server = server();
+        let response = server.handle(request(1, "bogus", json!({}))).await.unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server.handle_text("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_batch() {
+        let batch = json!([
+            request(1, "ping", json!({})),
+            { "jsonrpc": "2.0", "method": "notifications/initialized" },
+            request(2, "tools/list", json!({})),
+        ]);
+        let response = server().handle(batch).await.unwrap();
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_serve_lines() {
+        let input = format!(
+            "{}\n\n{}\n",
+            request(1, "ping", json!({})),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+        );
+        let (client, server_end) = tokio::io::duplex(4096);
+        server()
+            .serve(tokio::io::BufReader::new(input.as_bytes()), server_end)
+            .await
+            .unwrap();
+
+        let mut output = String::new();
+        tokio::io::AsyncReadExt::read_to_string(
+            &mut tokio::io::BufReader::new(client),
+            &mut output,
+        )
+        .await
+        .unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let response: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(response["id"], 1);
+    }
+
+    #[cfg(feature = "http-server")]
+    async fn post_mcp(router: axum::Router, headers: &[(&str, &str)]) -> axum::http::StatusCode {
+        use tower::ServiceExt;
+        let mut builder = axum::http::Request::post("/mcp");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let body = request(1, "ping", json!({})).to_string();
+        router
+            .oneshot(builder.body(axum::body::Body::from(body)).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_origin() {
+        use axum::http::StatusCode;
+        let router = server()
+            .with_allowed_origin("https://app.example.com")
+            .router();
+        for origin in [
+            "http://localhost:3000",
+            "http://127.0.0.1:3000",
+            "http://[::1]:3000",
+            "https://app.example.com",
+        ] {
+            let status = post_mcp(router.clone(), &[("origin", origin)]).await;
+            assert_eq!(status, StatusCode::OK, "{}", origin);
+        }
+        for origin in [
+            "http://evil.example",
+            "http://localhost.evil.example",
+            "null",
+        ] {
+            let status = post_mcp(router.clone(), &[("origin", origin)]).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", origin);
+        }
+        // Clients other than browsers send no origin
+        assert_eq!(post_mcp(router, &[]).await, StatusCode::OK);
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_bearer_token() {
+        use axum::http::StatusCode;
+        let router = server().with_bearer_token("s3cret").router();
+        assert_eq!(
+            post_mcp(router.clone(), &[]).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let wrong = [("authorization", "Bearer s3cre")];
+        assert_eq!(
+            post_mcp(router.clone(), &wrong).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let right = [("authorization", "Bearer s3cret")];
+        assert_eq!(post_mcp(router, &right).await, StatusCode::OK);
+    }
+
+    #[cfg(feature = "http-server")]
+    async fn get(router: axum::Router, path: &str) -> (axum::http::StatusCode, Value) {
+        use tower::ServiceExt;
+        let request = axum::http::Request::get(path)
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_health() {
+        use axum::http::StatusCode;
+        let router = server().with_bearer_token("s3cret").router();
+        let (status, body) = get(router.clone(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        let request = axum::http::Request::get("/readyz")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = tower::ServiceExt::oneshot(router, request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let (status, body) = get(server().router(), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "ready": true, "tools": 2 }));
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_readiness_of_servers() {
+        use crate::{McpConnectionManager, ReadinessCheck};
+        use axum::http::StatusCode;
+        let mut manager = McpConnectionManager::new();
+        manager.add_server("echo", EchoTransport).unwrap();
+        manager.add_server("gone", Disconnected).unwrap();
+        let manager = Arc::new(manager);
+
+        let readiness = manager.readiness().await;
+        assert!(!readiness.ready);
+        assert_eq!(readiness.details["tools"], 2);
+
+        let server = server().with_readiness(manager);
+        let (status, body) = get(server.router(), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["servers"][0]["server"], "echo");
+        assert_eq!(body["servers"][0]["status"], "healthy");
+        assert_eq!(body["servers"][1]["status"], "disconnected");
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_client() {
+        use crate::McpTransport;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { server().serve_http(listener).await });
+
+        let mut client = McpTransport::new_http("test".to_string(), url);
+        client.connect().await.unwrap();
+        let tools = client.list_tools().await.unwrap();
+        assert_eq!(tools[0].name, "fs_echo");
+        assert!(tools[0].parameters[0].required);
+
+        let mut call = ToolCall::new("fs_echo");
+        call.arguments = json!({ "text": "hi" });
+        let result = client.call(&call).await.unwrap();
+        assert_eq!(result.data.unwrap()["content"][0]["text"], "hi");
+    }
+
+    #[test]
+    fn test_published_name() {
+        assert_eq!(published_name("fs.read_file"), "fs_read_file");
+        assert_eq!(published_name("code-review"), "code-review");
+        assert_eq!(published_name("a b/c"), "a_b_c");
+    }
+}
@@ -1,55 +1,241 @@
-//! MCP transport implementations using rs-utcp.
+//! MCP transport over stdio or HTTP.
 
-use crate::Result;
+use crate::reconnect::is_connection_error;
+use crate::rpc::{Endpoint, RpcConnection};
+use crate::{
+    ConnectionEvent, ConnectionObserver, McpRequester, NotificationRouter, ReconnectConfig, Result,
+};
 use async_trait::async_trait;
-use rs_utcp::providers::base::Provider;
-use rs_utcp::providers::mcp::McpProvider;
-use rs_utcp::transports::mcp::McpTransport as RsUtcpMcpTransport;
-use rs_utcp::transports::ClientTransport;
-use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::Arc;
-use thulp_core::{Error, ToolCall, ToolDefinition, ToolResult, Transport as CoreTransport};
-
-/// Wrapper around rs-utcp's MCP transport
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use thulp_core::{
+    Error, NotificationSink, ToolCall, ToolDefinition, ToolResult, Transport as CoreTransport,
+};
+
+/// Connection to one MCP server
 pub struct McpTransport {
-    /// The underlying rs-utcp transport
-    inner: RsUtcpMcpTransport,
-    /// The MCP provider
-    provider: Arc<dyn Provider>,
+    /// Name of the server
+    name: String,
+    /// Where the server is reached
+    endpoint: Endpoint,
+    /// The open connection, replaced on reconnect
+    connection: RwLock<Option<Arc<RpcConnection>>>,
     /// Connection status
-    connected: bool,
+    connected: AtomicBool,
+    /// How dropped connections are re-established
+    reconnect: ReconnectConfig,
+    /// Receivers of connection lifecycle events
+    observers: Vec<Arc<dyn ConnectionObserver>>,
+    /// Held while reconnecting so concurrent failures reconnect once
+    reconnecting: tokio::sync::Mutex<()>,
+    /// Bumped on every (re)connect
+    generation: AtomicU64,
+    /// Tools annotated idempotent or read-only in the last listing, whose
+    /// calls are sent again after reconnecting
+    retry_safe: RwLock<HashSet<String>>,
+    /// Receives the notifications the server sends
+    notifications: Arc<NotificationRouter>,
+    /// How long a request may wait for its answer, if limited
+    timeout: Option<Duration>,
 }
 
 impl McpTransport {
     /// Create a new MCP transport for HTTP connection
     pub fn new_http(name: String, url: String) -> Self {
-        let provider = Arc::new(McpProvider::new(name, url, None));
-        let inner = RsUtcpMcpTransport::new();
-
-        Self {
-            inner,
-            provider,
-            connected: false,
-        }
+        Self::with_endpoint(name, Endpoint::Http { url })
     }
 
     /// Create a new MCP transport for STDIO connection
     pub fn new_stdio(name: String, command: String, args: Option<Vec<String>>) -> Self {
-        let provider = Arc::new(McpProvider::new_stdio(name, command, args, None));
-        let inner = RsUtcpMcpTransport::new();
+        let args = args.unwrap_or_default();
+        Self::with_endpoint(name, Endpoint::Stdio { command, args })
+    }
 
+    fn with_endpoint(name: String, endpoint: Endpoint) -> Self {
         Self {
-            inner,
-            provider,
-            connected: false,
+            name,
+            endpoint,
+            connection: RwLock::new(None),
+            connected: AtomicBool::new(false),
+            reconnect: ReconnectConfig::new(),
+            observers: Vec::new(),
+            reconnecting: tokio::sync::Mutex::new(()),
+            generation: AtomicU64::new(0),
+            retry_safe: RwLock::new(HashSet::new()),
+            notifications: Arc::new(NotificationRouter::new()),
+            timeout: None,
         }
     }
 
+    /// Set how dropped connections are re-established.
+    ///
+    /// See [`reconnect`](crate::reconnect) for when reconnecting happens.
+    pub fn with_reconnect(mut self, config: ReconnectConfig) -> Self {
+        self.reconnect = config;
+        self
+    }
+
+    /// Limit how long each request waits for the server's answer.
+    ///
+    /// Without a limit requests wait as long as the caller does, so a
+    /// caller's own timeout, such as a skill step's, decides.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Register an observer for connection lifecycle events.
+    pub fn with_connection_observer(mut self, observer: Arc<dyn ConnectionObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Register a sink for the notifications the server sends.
+    pub fn with_notification_sink(self, sink: Arc<dyn NotificationSink>) -> Self {
+        self.notifications.add_sink(sink);
+        self
+    }
+
+    /// Get the router the server's notifications are delivered to.
+    pub fn notifications(&self) -> Arc<NotificationRouter> {
+        self.notifications.clone()
+    }
+
+    /// Get the reconnect settings.
+    pub fn reconnect_config(&self) -> &ReconnectConfig {
+        &self.reconnect
+    }
+
     /// Create a new MCP transport with default configuration
     pub fn new() -> Self {
         Self::new_http("default".to_string(), "http://localhost:8080".to_string())
     }
+
+    /// Get the name of the server this transport connects to
+    pub fn server_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn emit(&self, event: ConnectionEvent) {
+        for observer in &self.observers {
+            observer.on_connection_event(&self.name, &event);
+        }
+    }
+
+    /// Connect through a shared reference, for [`McpClient`](crate::McpClient)
+    /// whose resources client shares the transport
+    pub(crate) async fn establish(&self) -> Result<()> {
+        self.open()
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to connect: {}", e)))?;
+
+        self.connected.store(true, Ordering::SeqCst);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.emit(ConnectionEvent::Connected);
+        Ok(())
+    }
+
+    /// Disconnect through a shared reference
+    pub(crate) fn close(&self) {
+        // Dropping the connection stops a stdio server
+        self.connection.write().unwrap().take();
+        self.connected.store(false, Ordering::SeqCst);
+        self.emit(ConnectionEvent::Disconnected);
+    }
+
+    async fn open(&self) -> std::result::Result<(), String> {
+        let connection =
+            RpcConnection::open(&self.name, &self.endpoint, self.notifications.clone()).await?;
+        *self.connection.write().unwrap() = Some(Arc::new(connection));
+        Ok(())
+    }
+
+    /// Send a request, reconnecting if the connection turns out to be
+    /// broken. With `retry` the request is sent once more on the new
+    /// connection; otherwise it fails, since the server may have acted on it.
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        retry: bool,
+    ) -> std::result::Result<Value, String> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        match self.send(method, params.clone()).await {
+            Ok(value) => Ok(value),
+            Err(error) => {
+                if !is_connection_error(&error) {
+                    return Err(error);
+                }
+                self.recover(generation, error.clone()).await?;
+                if !retry {
+                    return Err(format!(
+                        "{} (reconnected, but not sent again since the server may have \
+                         handled it)",
+                        error
+                    ));
+                }
+                self.send(method, params).await
+            }
+        }
+    }
+
+    async fn send(&self, method: &str, params: Value) -> std::result::Result<Value, String> {
+        let Some(connection) = self.connection.read().unwrap().clone() else {
+            return Err("not connected".to_string());
+        };
+        let Some(timeout) = self.timeout else {
+            return connection.request(method, params).await;
+        };
+        tokio::time::timeout(timeout, connection.request(method, params))
+            .await
+            .unwrap_or_else(|_| Err(format!("no answer to {} within {:?}", method, timeout)))
+    }
+
+    /// Re-establish a connection found broken while on `generation`
+    async fn recover(&self, generation: u64, error: String) -> std::result::Result<(), String> {
+        let _guard = self.reconnecting.lock().await;
+        // Another request reconnected while this one waited
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+
+        tracing::warn!(server = %self.name, error = %error, "MCP connection lost");
+        self.emit(ConnectionEvent::Lost {
+            error: error.clone(),
+        });
+        // Drops a dead stdio process
+        self.connection.write().unwrap().take();
+
+        let mut last_error = error;
+        for attempt in 1..=self.reconnect.max_attempts {
+            let delay = self.reconnect.delay(attempt);
+            self.emit(ConnectionEvent::Reconnecting { attempt, delay });
+            tokio::time::sleep(delay).await;
+
+            match self.open().await {
+                Ok(()) => {
+                    self.generation.fetch_add(1, Ordering::SeqCst);
+                    tracing::info!(server = %self.name, attempt, "MCP connection restored");
+                    self.emit(ConnectionEvent::Reconnected { attempts: attempt });
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        self.connected.store(false, Ordering::SeqCst);
+        self.emit(ConnectionEvent::ReconnectFailed {
+            attempts: self.reconnect.max_attempts,
+            error: last_error.clone(),
+        });
+        Err(format!(
+            "connection lost and {} reconnect attempts failed: {}",
+            self.reconnect.max_attempts, last_error
+        ))
+    }
 }
 
 impl Default for McpTransport {
@@ -58,82 +244,136 @@ impl Default for McpTransport {
     }
 }
 
+/// Convert a tool from a `tools/list` result
+fn tool_definition(tool: &Value) -> Option<ToolDefinition> {
+    let name = tool.get("name")?.as_str()?.to_string();
+    let description = tool
+        .get("description")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let parameters = tool
+        .get("inputSchema")
+        .and_then(|schema| ToolDefinition::parse_mcp_input_schema(schema).ok())
+        .unwrap_or_default();
+    let hint = |name: &str| {
+        tool.pointer(&format!("/annotations/{}", name))
+            .and_then(Value::as_bool)
+    };
+    let tagged = tool
+        .get("tags")
+        .and_then(Value::as_array)
+        .is_some_and(|tags| tags.iter().any(|tag| tag == "destructive"));
+    let destructive =
+        (tagged || hint("destructiveHint") == Some(true)) && hint("readOnlyHint") != Some(true);
+    Some(ToolDefinition {
+        name,
+        description,
+        parameters,
+        destructive,
+    })
+}
+
+/// Whether calling a tool twice does no more than calling it once, as its
+/// `idempotentHint` or `readOnlyHint` annotation says
+fn is_retry_safe(tool: &Value) -> bool {
+    ["idempotentHint", "readOnlyHint"]
+        .iter()
+        .any(|hint| tool.pointer(&format!("/annotations/{}", hint)) == Some(&Value::Bool(true)))
+}
+
+/// Sends requests such as `resources/subscribe` over the transport's
+/// connection. A request that finds the connection dropped reconnects and
+/// fails rather than being sent again.
 #[async_trait]
-impl CoreTransport for McpTransport {
-    async fn connect(&mut self) -> Result<()> {
-        // Register the provider with the transport
-        let _tools = self
-            .inner
-            .register_tool_provider(&*self.provider)
+impl McpRequester for McpTransport {
+    async fn send_request(&self, method: &str, params: Value) -> Result<Value> {
+        if !self.is_connected() {
+            return Err(Error::ExecutionFailed("not connected".to_string()));
+        }
+        self.request(method, params, false)
             .await
-            .map_err(|e| Error::ExecutionFailed(format!("Failed to register provider: {}", e)))?;
+            .map_err(|e| Error::ExecutionFailed(format!("{} failed: {}", method, e)))
+    }
+}
 
-        self.connected = true;
-        Ok(())
+#[async_trait]
+impl CoreTransport for McpTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.establish().await
     }
 
     async fn disconnect(&mut self) -> Result<()> {
-        self.inner
-            .deregister_tool_provider(&*self.provider)
-            .await
-            .map_err(|e| Error::ExecutionFailed(format!("Failed to deregister provider: {}", e)))?;
-        self.connected = false;
+        self.close();
         Ok(())
     }
 
     fn is_connected(&self) -> bool {
-        self.connected
+        self.connected.load(Ordering::SeqCst)
     }
 
     async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
-        if !self.connected {
+        if !self.is_connected() {
             return Err(Error::ExecutionFailed("not connected".to_string()));
         }
 
-        // Use rs-utcp's register_tool_provider which calls tools/list internally
-        let tools = self
-            .inner
-            .register_tool_provider(&*self.provider)
-            .await
-            .map_err(|e| Error::ExecutionFailed(format!("Failed to list tools: {}", e)))?;
-
-        // Convert rs-utcp Tool to ToolDefinition
         let mut definitions = Vec::new();
-        for tool in tools {
-            // Convert the ToolInputOutputSchema to JSON for parsing
-            let inputs_json = serde_json::to_value(&tool.inputs).map_err(|e| {
-                Error::ExecutionFailed(format!("Failed to serialize inputs: {}", e))
-            })?;
-
-            // Extract parameters from tool.inputs
-            let parameters =
-                ToolDefinition::parse_mcp_input_schema(&inputs_json).unwrap_or_default();
-
-            definitions.push(ToolDefinition {
-                name: tool.name,
-                description: tool.description,
-                parameters,
-            });
+        let mut retry_safe = HashSet::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page = self
+                .request("tools/list", params, true)
+                .await
+                .map_err(|e| Error::ExecutionFailed(format!("Failed to list tools: {}", e)))?;
+
+            for tool in page
+                .get("tools")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let Some(definition) = tool_definition(tool) else {
+                    continue;
+                };
+                if is_retry_safe(tool) {
+                    retry_safe.insert(definition.name.clone());
+                }
+                definitions.push(definition);
+            }
+
+            cursor = page
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
         }
 
+        *self.retry_safe.write().unwrap() = retry_safe;
         Ok(definitions)
     }
 
     async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
-        if !self.connected {
+        if !self.is_connected() {
             return Err(Error::ExecutionFailed("not connected".to_string()));
         }
 
-        // Convert arguments to the format expected by rs-utcp
-        let args: HashMap<String, Value> = match &call.arguments {
-            Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
-            _ => HashMap::new(),
+        let arguments = match &call.arguments {
+            Value::Object(_) => call.arguments.clone(),
+            _ => json!({}),
         };
-
-        // Call the tool through the transport
+        let retry = self.retry_safe.read().unwrap().contains(&call.tool);
         let result = self
-            .inner
-            .call_tool(&call.tool, args, &*self.provider)
+            .request(
+                "tools/call",
+                json!({ "name": call.tool, "arguments": arguments }),
+                retry,
+            )
             .await
             .map_err(|e| Error::ExecutionFailed(format!("Tool call failed: {}", e)))?;
 
@@ -145,6 +385,8 @@ impl CoreTransport for McpTransport {
 mod tests {
     use super::*;
     use serde_json::json;
+    #[cfg(feature = "http-server")]
+    use thulp_core::McpNotification;
 
     #[test]
     fn transport_new_http() {
@@ -187,7 +429,7 @@ mod tests {
 
         // Note: We can't actually connect in tests without a real MCP server
         // This test verifies the structure and state management
-        assert_eq!(transport.is_connected(), false);
+        assert!(!transport.is_connected());
     }
 
     #[test]
@@ -373,14 +615,14 @@ mod tests {
             .arg_int("positive", 42)
             .arg_int("negative", -42)
             .arg_int("zero", 0)
-            .arg("float", json!(3.14159))
+            .arg("float", json!(1.23456))
             .arg("scientific", json!(1.5e10))
             .build();
 
         assert_eq!(call.arguments["positive"], 42);
         assert_eq!(call.arguments["negative"], -42);
         assert_eq!(call.arguments["zero"], 0);
-        assert_eq!(call.arguments["float"], 3.14159);
+        assert_eq!(call.arguments["float"], 1.23456);
         assert_eq!(call.arguments["scientific"], 1.5e10);
     }
 
@@ -400,4 +642,307 @@ mod tests {
         assert_eq!(call.arguments["boolean_true"], true);
         assert_eq!(call.arguments["boolean_false"], false);
     }
+
+    /// Stdio server that answers one tool call and then exits
+    #[cfg(unix)]
+    fn one_shot_server(dir: &std::path::Path) -> McpTransport {
+        let script = dir.join("server.sh");
+        std::fs::write(
+            &script,
+            r#"while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*) echo '{"jsonrpc":"2.0","id":'$id',"result":{"capabilities":{}}}' ;;
+    *tools/list*)
+      printf '%s%s\n' '{"jsonrpc":"2.0","id":'$id',"result":{"tools":[' \
+        '{"name":"echo","annotations":{"readOnlyHint":true}},{"name":"write"}]}}' ;;
+    *tools/call*)
+      printf '%s%s\n' '{"jsonrpc":"2.0","method":"notifications/message",' \
+        '"params":{"level":"info","data":"called"}}'
+      echo '{"jsonrpc":"2.0","id":'$id',"result":{"ok":true}}'
+      exit 0 ;;
+  esac
+done
+"#,
+        )
+        .unwrap();
+        McpTransport::new_stdio(
+            "one-shot".to_string(),
+            "sh".to_string(),
+            Some(vec![script.to_string_lossy().into_owned()]),
+        )
+        .with_reconnect(
+            ReconnectConfig::new()
+                .with_max_attempts(2)
+                .with_initial_delay(std::time::Duration::from_millis(1)),
+        )
+    }
+
+    #[derive(Default)]
+    struct Events(std::sync::Mutex<Vec<ConnectionEvent>>);
+
+    impl ConnectionObserver for Events {
+        fn on_connection_event(&self, server: &str, event: &ConnectionEvent) {
+            assert_eq!(server, "one-shot");
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    impl Events {
+        fn names(&self) -> Vec<&'static str> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|event| match event {
+                    ConnectionEvent::Connected => "connected",
+                    ConnectionEvent::Disconnected => "disconnected",
+                    ConnectionEvent::Lost { .. } => "lost",
+                    ConnectionEvent::Reconnecting { .. } => "reconnecting",
+                    ConnectionEvent::Reconnected { .. } => "reconnected",
+                    ConnectionEvent::ReconnectFailed { .. } => "failed",
+                })
+                .collect()
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reconnects_after_server_exits() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Events::default());
+        let mut transport = one_shot_server(dir.path()).with_connection_observer(events.clone());
+        transport.connect().await.unwrap();
+        assert_eq!(transport.list_tools().await.unwrap().len(), 2);
+
+        // The server exits after each call, so every call after the first
+        // needs a new process; read-only calls are sent again on it
+        for _ in 0..2 {
+            let result = transport.call(&ToolCall::new("echo")).await.unwrap();
+            assert_eq!(result.data, Some(json!({"ok": true})));
+        }
+        assert!(transport.is_connected());
+        assert_eq!(
+            events.names(),
+            ["connected", "lost", "reconnecting", "reconnected"]
+        );
+
+        // Other calls may have run, so they fail after reconnecting
+        let err = transport.call(&ToolCall::new("write")).await.unwrap_err();
+        assert!(err.to_string().contains("not sent again"));
+        assert!(transport.is_connected());
+        assert_eq!(events.names().len(), 7);
+        // The new process is there for the next call
+        transport.call(&ToolCall::new("write")).await.unwrap();
+
+        transport.disconnect().await.unwrap();
+        assert_eq!(events.names().last(), Some(&"disconnected"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Events::default());
+        let mut transport = one_shot_server(dir.path()).with_connection_observer(events.clone());
+        transport.connect().await.unwrap();
+        transport.call(&ToolCall::new("echo")).await.unwrap();
+
+        // Restarting fails once the script is gone
+        std::fs::remove_file(dir.path().join("server.sh")).unwrap();
+        let err = transport.call(&ToolCall::new("echo")).await.unwrap_err();
+        assert!(err.to_string().contains("2 reconnect attempts failed"));
+        assert!(!transport.is_connected());
+        assert_eq!(
+            events.names(),
+            [
+                "connected",
+                "lost",
+                "reconnecting",
+                "reconnecting",
+                "failed"
+            ]
+        );
+
+        // Reconnecting can be turned off
+        let transport = McpTransport::new().with_reconnect(ReconnectConfig::disabled());
+        assert!(!transport.reconnect_config().is_enabled());
+    }
+
+    #[derive(Default)]
+    struct Notifications(std::sync::Mutex<Vec<(String, thulp_core::McpNotification)>>);
+
+    #[async_trait]
+    impl NotificationSink for Notifications {
+        async fn notify(
+            &self,
+            server: &str,
+            notification: &thulp_core::McpNotification,
+        ) -> Result<()> {
+            let received = (server.to_string(), notification.clone());
+            self.0.lock().unwrap().push(received);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "http-server")]
+    impl Notifications {
+        fn has(&self, matches: impl Fn(&McpNotification) -> bool) -> bool {
+            self.0.lock().unwrap().iter().any(|(_, n)| matches(n))
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_delivers_server_notifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = Arc::new(Notifications::default());
+        let mut transport = one_shot_server(dir.path()).with_notification_sink(sink.clone());
+        assert_eq!(transport.notifications().sink_count(), 1);
+        transport.connect().await.unwrap();
+
+        // The server logs before answering the call
+        transport.call(&ToolCall::new("echo")).await.unwrap();
+        let received = sink.0.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "one-shot");
+        assert!(matches!(
+            &received[0].1,
+            thulp_core::McpNotification::Log { data, .. } if data == "called"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut transport = one_shot_server(dir.path()).with_timeout(Duration::from_millis(50));
+        transport.connect().await.unwrap();
+
+        // The server never answers this
+        let err = transport
+            .send_request("prompts/list", json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no answer to prompts/list within"));
+        assert!(transport.is_connected());
+    }
+
+    /// HTTP server that logs on its GET event stream, and streams progress
+    /// for a tool call, only finishing the call once the client has the
+    /// progress
+    #[cfg(feature = "http-server")]
+    async fn streaming_server(sink: Arc<Notifications>) -> McpTransport {
+        use axum::http::{HeaderMap, StatusCode};
+        use axum::response::sse::{Event, Sse};
+        use axum::response::IntoResponse;
+        use futures::StreamExt;
+
+        type Events = futures::stream::BoxStream<'static, std::result::Result<Event, Error>>;
+        fn event(message: Value) -> std::result::Result<Event, Error> {
+            Ok(Event::default().data(message.to_string()))
+        }
+
+        let post = move |axum::Json(message): axum::Json<Value>| async move {
+            let id = message["id"].clone();
+            let result = json!({ "jsonrpc": "2.0", "id": id, "result": { "ok": true } });
+            match message["method"].as_str() {
+                Some("initialize") => {
+                    ([("mcp-session-id", "s1")], axum::Json(result)).into_response()
+                }
+                Some("tools/call") => {
+                    let progress = event(json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/progress",
+                        "params": { "progressToken": "t", "progress": 1 }
+                    }));
+                    let done = futures::stream::once(async move {
+                        while !sink.has(|n| matches!(n, McpNotification::Progress { .. })) {
+                            tokio::time::sleep(Duration::from_millis(10)).await;
+                        }
+                        event(result)
+                    });
+                    let events: Events = futures::stream::iter([progress]).chain(done).boxed();
+                    Sse::new(events).into_response()
+                }
+                _ => StatusCode::ACCEPTED.into_response(),
+            }
+        };
+        let get = |headers: HeaderMap| async move {
+            if headers.get("mcp-session-id").and_then(|v| v.to_str().ok()) != Some("s1") {
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+            let log = event(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": { "level": "info", "data": "between requests" }
+            }));
+            let events: Events = futures::stream::iter([log])
+                .chain(futures::stream::pending())
+                .boxed();
+            Sse::new(events).into_response()
+        };
+        let app: axum::Router =
+            axum::Router::new().route("/mcp", axum::routing::post(post).get(get));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        McpTransport::new_http("streaming".to_string(), url)
+    }
+
+    #[cfg(feature = "http-server")]
+    #[tokio::test]
+    async fn test_http_event_streams() {
+        let sink = Arc::new(Notifications::default());
+        let mut transport = streaming_server(sink.clone())
+            .await
+            .with_notification_sink(sink.clone());
+        transport.connect().await.unwrap();
+
+        // Logged on the GET stream without any request in flight
+        let logged = async {
+            while !sink.has(|n| matches!(n, McpNotification::Log { .. })) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), logged)
+            .await
+            .unwrap();
+
+        // The call only finishes once its progress arrived ahead of it
+        let call = ToolCall::new("slow");
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.call(&call))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.data, Some(json!({ "ok": true })));
+        assert_eq!(sink.0.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tool_definition_from_list() {
+        let tool = tool_definition(&json!({
+            "name": "delete_file",
+            "description": "Delete a file",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            },
+            "annotations": { "destructiveHint": true }
+        }))
+        .unwrap();
+        assert_eq!(tool.name, "delete_file");
+        assert_eq!(tool.parameters.len(), 1);
+        assert!(tool.parameters[0].required);
+        assert!(tool.destructive);
+
+        // Read-only tools are never destructive, and tools need a name
+        let read = json!({ "name": "read", "annotations": {
+            "destructiveHint": true, "readOnlyHint": true
+        }});
+        assert!(!tool_definition(&read).unwrap().destructive);
+        assert!(tool_definition(&json!({ "description": "nameless" })).is_none());
+    }
 }
@@ -3,69 +3,19 @@
 //! Query engine for searching and filtering tools.
 //!
 //! This crate provides a DSL for querying tool definitions by various criteria.
+//! [`parse_query`] parses the text form, e.g.
+//! `name:"file_*" and (has:path or params >= 2)`.
+//! Queries either filter tools ([`Query::execute`]) or rank them by relevance
+//! ([`Query::execute_ranked`], see [`scoring`]).
 
-use serde::{Deserialize, Serialize};
-use thulp_core::ToolDefinition;
+mod parser;
+pub mod scoring;
 
-/// Parse a natural language query into QueryCriteria
-pub fn parse_query(query: &str) -> Result<QueryCriteria> {
-    // Simple parser for demonstration
-    // In a real implementation, this would use a proper NLP library
-
-    let lower_query = query.to_lowercase();
-
-    if lower_query.contains("and") {
-        let parts: Vec<&str> = query.split(" and ").collect();
-        let criteria = parts
-            .into_iter()
-            .map(parse_single_criterion)
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-        Ok(QueryCriteria::And(criteria))
-    } else if lower_query.contains("or") {
-        let parts: Vec<&str> = query.split(" or ").collect();
-        let criteria = parts
-            .into_iter()
-            .map(parse_single_criterion)
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-        Ok(QueryCriteria::Or(criteria))
-    } else {
-        parse_single_criterion(query)
-    }
-}
+pub use parser::parse_query;
+pub use scoring::{Corpus, ScoreWeights, ScoredTool};
 
-fn parse_single_criterion(criterion: &str) -> Result<QueryCriteria> {
-    let lower = criterion.to_lowercase();
-
-    if lower.starts_with("name:") {
-        let name = criterion[5..].trim();
-        Ok(QueryCriteria::Name(name.to_string()))
-    } else if lower.starts_with("has:") {
-        let param = criterion[4..].trim();
-        Ok(QueryCriteria::HasParameter(param.to_string()))
-    } else if lower.starts_with("min:") {
-        let count: usize = criterion[4..]
-            .trim()
-            .parse()
-            .map_err(|_| QueryError::Parse("Invalid number for min".to_string()))?;
-        Ok(QueryCriteria::MinParameters(count))
-    } else if lower.starts_with("max:") {
-        let count: usize = criterion[4..]
-            .trim()
-            .parse()
-            .map_err(|_| QueryError::Parse("Invalid number for max".to_string()))?;
-        Ok(QueryCriteria::MaxParameters(count))
-    } else if lower.starts_with("desc:") || lower.starts_with("description:") {
-        let desc = if lower.starts_with("desc:") {
-            criterion[5..].trim()
-        } else {
-            criterion[12..].trim()
-        };
-        Ok(QueryCriteria::Description(desc.to_string()))
-    } else {
-        // Default to name search
-        Ok(QueryCriteria::Name(criterion.to_string()))
-    }
-}
+use serde::{Deserialize, Serialize};
+use thulp_core::ToolDefinition;
 
 /// Result type for query operations
 pub type Result<T> = std::result::Result<T, QueryError>;
@@ -76,6 +26,9 @@ pub enum QueryError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    #[error("Syntax error at position {position}: {message}")]
+    Syntax { position: usize, message: String },
+
     #[error("Invalid query: {0}")]
     Invalid(String),
 }
@@ -142,6 +95,7 @@ impl QueryCriteria {
 #[derive(Debug, Default)]
 pub struct QueryBuilder {
     criteria: Vec<QueryCriteria>,
+    limit: Option<usize>,
 }
 
 impl QueryBuilder {
@@ -182,14 +136,22 @@ impl QueryBuilder {
         self
     }
 
+    /// Limit the number of ranked results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
     /// Build the query
     pub fn build(self) -> Query {
+        let criteria = if self.criteria.len() == 1 {
+            self.criteria.into_iter().next().unwrap()
+        } else {
+            QueryCriteria::And(self.criteria)
+        };
         Query {
-            criteria: if self.criteria.len() == 1 {
-                self.criteria.into_iter().next().unwrap()
-            } else {
-                QueryCriteria::And(self.criteria)
-            },
+            limit: self.limit,
+            ..Query::new(criteria)
         }
     }
 }
@@ -198,12 +160,30 @@ impl QueryBuilder {
 #[derive(Debug, Clone)]
 pub struct Query {
     criteria: QueryCriteria,
+    weights: ScoreWeights,
+    limit: Option<usize>,
 }
 
 impl Query {
     /// Create a new query from criteria
     pub fn new(criteria: QueryCriteria) -> Self {
-        Self { criteria }
+        Self {
+            criteria,
+            weights: ScoreWeights::default(),
+            limit: None,
+        }
+    }
+
+    /// Set the weights used to score ranked results
+    pub fn with_weights(mut self, weights: ScoreWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Return at most `limit` ranked results
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
     }
 
     /// Execute the query on a collection of tools
@@ -214,6 +194,30 @@ impl Query {
             .cloned()
             .collect()
     }
+
+    /// Execute the query and return matching tools, most relevant first
+    ///
+    /// Term statistics for description scoring come from `tools`. Tools with
+    /// equal scores keep their input order.
+    pub fn execute_ranked(&self, tools: &[ToolDefinition]) -> Vec<ScoredTool> {
+        let corpus = Corpus::new(tools).with_weights(self.weights);
+        let mut ranked: Vec<ScoredTool> = tools
+            .iter()
+            .filter_map(|tool| {
+                self.criteria
+                    .matches_scored(tool, &corpus)
+                    .map(|score| ScoredTool {
+                        tool: tool.clone(),
+                        score,
+                    })
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(limit) = self.limit {
+            ranked.truncate(limit);
+        }
+        ranked
+    }
 }
 
 #[cfg(test)]
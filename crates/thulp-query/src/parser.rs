@@ -0,0 +1,435 @@
+//! Parser for the query DSL, see [`parse_query`] for the grammar.
+
+use crate::{QueryCriteria, QueryError, Result};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{alpha1, char, digit1, multispace0};
+use nom::combinator::{cut, map};
+use nom::error::{context, ContextError, ErrorKind, ParseError};
+use nom::multi::many0;
+use nom::sequence::{preceded, terminated};
+use nom::IResult;
+
+/// Parse error pointing at the input where parsing stopped
+#[derive(Debug)]
+struct SyntaxError<'a> {
+    input: &'a str,
+    message: Option<&'static str>,
+}
+
+impl<'a> SyntaxError<'a> {
+    fn new(input: &'a str, message: &'static str) -> Self {
+        Self {
+            input,
+            message: Some(message),
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for SyntaxError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            message: None,
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(self, other: Self) -> Self {
+        // Report the alternative that got furthest
+        if other.input.len() <= self.input.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for SyntaxError<'a> {
+    fn add_context(_input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        // The innermost context is the most specific
+        other.message.get_or_insert(ctx);
+        other
+    }
+}
+
+type PResult<'a, T> = IResult<&'a str, T, SyntaxError<'a>>;
+
+/// Parse a query string into [`QueryCriteria`].
+///
+/// ```text
+/// query      := or_expr
+/// or_expr    := and_expr (("or" | "||") and_expr)*
+/// and_expr   := unary (("and" | "&&")? unary)*
+/// unary      := ("not" | "!") unary | primary
+/// primary    := "(" query ")" | comparison | field ":" value | value
+/// comparison := ("params" | "parameters") (">=" | "<=" | ">" | "<" | "=" | "==" | "!=") number
+/// field      := "name" | "desc" | "description" | "has" | "param" | "min" | "max"
+/// value      := '"' chars '"' | "'" chars "'" | word
+/// ```
+///
+/// Keywords are case-insensitive. Terms next to each other without an
+/// operator are combined with AND, and AND binds tighter than OR. A value
+/// without a field matches tool names. Quote values that contain spaces,
+/// parentheses or operator characters; `\` escapes the next character
+/// inside quotes.
+///
+/// Errors carry the byte offset where parsing failed.
+pub fn parse_query(query: &str) -> Result<QueryCriteria> {
+    let error = match terminated(or_expr, multispace0)(query) {
+        Ok(("", criteria)) => return Ok(criteria),
+        Ok((rest, _)) => {
+            let message = if rest.starts_with(')') {
+                "unmatched ')'"
+            } else {
+                "expected 'and', 'or' or end of query"
+            };
+            SyntaxError::new(rest, message)
+        }
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => e,
+        Err(nom::Err::Incomplete(_)) => SyntaxError::new("", "incomplete query"),
+    };
+    Err(syntax_error(query, error))
+}
+
+fn syntax_error(query: &str, error: SyntaxError<'_>) -> QueryError {
+    let position = query.len() - error.input.len();
+    let message = error.message.unwrap_or("unexpected input");
+    let found: String = error
+        .input
+        .split_whitespace()
+        .next()
+        .map(|token| token.chars().take(20).collect())
+        .unwrap_or_default();
+    let message = if found.is_empty() {
+        format!("{}, found end of query", message)
+    } else {
+        format!("{}, found '{}'", message, found)
+    };
+    QueryError::Syntax { position, message }
+}
+
+fn or_expr(input: &str) -> PResult<'_, QueryCriteria> {
+    let (input, first) = and_expr(input)?;
+    let (input, rest) = many0(preceded(or_op, cut(and_expr)))(input)?;
+    Ok((input, combine(first, rest, QueryCriteria::Or)))
+}
+
+fn and_expr(input: &str) -> PResult<'_, QueryCriteria> {
+    let (input, first) = unary(input)?;
+    let (input, rest) = many0(alt((preceded(and_op, cut(unary)), unary)))(input)?;
+    Ok((input, combine(first, rest, QueryCriteria::And)))
+}
+
+fn unary(input: &str) -> PResult<'_, QueryCriteria> {
+    alt((
+        map(preceded(not_op, cut(unary)), |criteria| {
+            QueryCriteria::Not(Box::new(criteria))
+        }),
+        primary,
+    ))(input)
+}
+
+fn primary(input: &str) -> PResult<'_, QueryCriteria> {
+    preceded(
+        multispace0,
+        context(
+            "expected a search term",
+            alt((
+                group,
+                comparison,
+                field_term,
+                map(quoted, QueryCriteria::Name),
+                name_term,
+            )),
+        ),
+    )(input)
+}
+
+fn group(input: &str) -> PResult<'_, QueryCriteria> {
+    preceded(
+        char('('),
+        cut(terminated(
+            or_expr,
+            preceded(multispace0, context("expected ')'", char(')'))),
+        )),
+    )(input)
+}
+
+/// `params >= 2` and friends, written in terms of min/max criteria
+fn comparison(input: &str) -> PResult<'_, QueryCriteria> {
+    let (rest, word) = bare_word(input)?;
+    if !(word.eq_ignore_ascii_case("params") || word.eq_ignore_ascii_case("parameters")) {
+        return Err(nom::Err::Error(SyntaxError::from_error_kind(
+            input,
+            ErrorKind::Tag,
+        )));
+    }
+    let (rest, op) = preceded(
+        multispace0,
+        alt((
+            tag(">="),
+            tag("<="),
+            tag("!="),
+            tag("=="),
+            tag(">"),
+            tag("<"),
+            tag("="),
+        )),
+    )(rest)?;
+    let (after, count) = cut(preceded(multispace0, count))(rest)?;
+
+    let exactly = |n| {
+        QueryCriteria::And(vec![
+            QueryCriteria::MinParameters(n),
+            QueryCriteria::MaxParameters(n),
+        ])
+    };
+    let criteria = match op {
+        ">=" => QueryCriteria::MinParameters(count),
+        "<=" => QueryCriteria::MaxParameters(count),
+        ">" => QueryCriteria::MinParameters(count + 1),
+        "<" => match count.checked_sub(1) {
+            Some(max) => QueryCriteria::MaxParameters(max),
+            None => {
+                return Err(nom::Err::Failure(SyntaxError::new(
+                    rest.trim_start(),
+                    "no tool has fewer than 0 parameters",
+                )))
+            }
+        },
+        "!=" => QueryCriteria::Not(Box::new(exactly(count))),
+        _ => exactly(count),
+    };
+    Ok((after, criteria))
+}
+
+fn field_term(input: &str) -> PResult<'_, QueryCriteria> {
+    let (rest, field) = terminated(alpha1, char(':'))(input)?;
+    let rest = rest.trim_start();
+    match field.to_ascii_lowercase().as_str() {
+        "name" => cut(context("expected a name", map(value, QueryCriteria::Name)))(rest),
+        "desc" | "description" => cut(context(
+            "expected a description keyword",
+            map(value, QueryCriteria::Description),
+        ))(rest),
+        "has" | "param" => cut(context(
+            "expected a parameter name",
+            map(value, QueryCriteria::HasParameter),
+        ))(rest),
+        "min" => cut(map(count, QueryCriteria::MinParameters))(rest),
+        "max" => cut(map(count, QueryCriteria::MaxParameters))(rest),
+        _ => Err(nom::Err::Failure(SyntaxError::new(
+            input,
+            "unknown field (expected name, desc, has, min or max)",
+        ))),
+    }
+}
+
+/// A bare word that is not a keyword, matched against tool names
+fn name_term(input: &str) -> PResult<'_, QueryCriteria> {
+    let (rest, word) = bare_word(input)?;
+    if is_keyword(word) {
+        return Err(nom::Err::Error(SyntaxError::from_error_kind(
+            input,
+            ErrorKind::Tag,
+        )));
+    }
+    Ok((rest, QueryCriteria::Name(word.to_string())))
+}
+
+fn value(input: &str) -> PResult<'_, String> {
+    alt((quoted, map(bare_word, str::to_string)))(input)
+}
+
+fn count(input: &str) -> PResult<'_, usize> {
+    let (rest, digits) = context("expected a parameter count", digit1)(input)?;
+    match digits.parse() {
+        Ok(count) => Ok((rest, count)),
+        Err(_) => Err(nom::Err::Failure(SyntaxError::new(
+            input,
+            "parameter count is too large",
+        ))),
+    }
+}
+
+/// A single- or double-quoted string with `\` escapes
+fn quoted(input: &str) -> PResult<'_, String> {
+    let quote = match input.chars().next() {
+        Some(q @ ('"' | '\'')) => q,
+        _ => {
+            return Err(nom::Err::Error(SyntaxError::from_error_kind(
+                input,
+                ErrorKind::Char,
+            )))
+        }
+    };
+    let mut value = String::new();
+    let mut chars = input.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
+            },
+            c if c == quote => return Ok((&input[i + 1..], value)),
+            c => value.push(c),
+        }
+    }
+    Err(nom::Err::Failure(SyntaxError::new(
+        input,
+        "unterminated string",
+    )))
+}
+
+fn bare_word(input: &str) -> PResult<'_, &str> {
+    take_while1(|c: char| !c.is_whitespace() && !"()\"':<>=!&|".contains(c))(input)
+}
+
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> PResult<'a, &'a str> {
+    move |input| {
+        let (rest, found) = preceded(multispace0, bare_word)(input)?;
+        if found.eq_ignore_ascii_case(word) {
+            Ok((rest, found))
+        } else {
+            Err(nom::Err::Error(SyntaxError::from_error_kind(
+                input,
+                ErrorKind::Tag,
+            )))
+        }
+    }
+}
+
+fn and_op(input: &str) -> PResult<'_, &str> {
+    alt((keyword("and"), preceded(multispace0, tag("&&"))))(input)
+}
+
+fn or_op(input: &str) -> PResult<'_, &str> {
+    alt((keyword("or"), preceded(multispace0, tag("||"))))(input)
+}
+
+fn not_op(input: &str) -> PResult<'_, &str> {
+    alt((keyword("not"), preceded(multispace0, tag("!"))))(input)
+}
+
+fn is_keyword(word: &str) -> bool {
+    ["and", "or", "not"]
+        .iter()
+        .any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+/// A single term, or all terms joined by `join`
+fn combine(
+    first: QueryCriteria,
+    rest: Vec<QueryCriteria>,
+    join: fn(Vec<QueryCriteria>) -> QueryCriteria,
+) -> QueryCriteria {
+    if rest.is_empty() {
+        first
+    } else {
+        let mut criteria = vec![first];
+        criteria.extend(rest);
+        join(criteria)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> String {
+        format!("{:?}", parse_query(query).unwrap())
+    }
+
+    fn error(query: &str) -> (usize, String) {
+        match parse_query(query).unwrap_err() {
+            QueryError::Syntax { position, message } => (position, message),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_precedence_and_grouping() {
+        assert_eq!(
+            parse("a or b and c"),
+            r#"Or([Name("a"), And([Name("b"), Name("c")])])"#
+        );
+        assert_eq!(
+            parse("(a OR b) c"),
+            r#"And([Or([Name("a"), Name("b")]), Name("c")])"#
+        );
+        assert_eq!(
+            parse("not has:url && !(name:x || desc:y)"),
+            r#"And([Not(HasParameter("url")), Not(Or([Name("x"), Description("y")]))])"#
+        );
+        // Keywords only count as whole words
+        assert_eq!(parse("handler"), r#"Name("handler")"#);
+        assert_eq!(
+            parse("notes or orders"),
+            r#"Or([Name("notes"), Name("orders")])"#
+        );
+    }
+
+    #[test]
+    fn test_quoted_values() {
+        assert_eq!(
+            parse(r#"desc:"read a file" and name:'file_*'"#),
+            r#"And([Description("read a file"), Name("file_*")])"#
+        );
+        assert_eq!(parse(r#""and""#), r#"Name("and")"#);
+        assert_eq!(
+            parse(r#"desc:"say \"hi\"""#),
+            r#"Description("say \"hi\"")"#
+        );
+        assert_eq!(parse("name: search"), r#"Name("search")"#);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(parse("params >= 2"), "MinParameters(2)");
+        assert_eq!(parse("params<3"), "MaxParameters(2)");
+        assert_eq!(parse("Parameters > 0"), "MinParameters(1)");
+        assert_eq!(
+            parse("params = 1"),
+            "And([MinParameters(1), MaxParameters(1)])"
+        );
+        assert_eq!(
+            parse("params != 1"),
+            "Not(And([MinParameters(1), MaxParameters(1)]))"
+        );
+        // Without an operator it is just a name
+        assert_eq!(parse("params"), r#"Name("params")"#);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let (position, message) = error("name:a and (has:b or");
+        assert_eq!(position, 20);
+        assert_eq!(message, "expected a search term, found end of query");
+
+        let (position, message) = error("(a or b");
+        assert_eq!(position, 7);
+        assert_eq!(message, "expected ')', found end of query");
+
+        assert_eq!(error("a b)").0, 3);
+        assert_eq!(error("desc:\"open").0, 5);
+        assert_eq!(
+            error("params >= lots"),
+            (10, "expected a parameter count, found 'lots'".to_string())
+        );
+        assert_eq!(error("params < 0").0, 9);
+
+        let (position, message) = error("size:3");
+        assert_eq!(position, 0);
+        assert!(message.starts_with("unknown field"), "{message}");
+
+        assert_eq!(error("").1, "expected a search term, found end of query");
+        assert!(parse_query("min:x")
+            .unwrap_err()
+            .to_string()
+            .contains("position 4"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Relevance scoring for ranked queries.
+//!
+//! [`QueryCriteria::matches_scored`] works like
+//! [`matches`](QueryCriteria::matches) but also says how well a tool matches:
+//!
+//! - **Name**: an exact name scores highest, then a prefix, then a substring
+//!   or wildcard match covering less of the name.
+//! - **Description**: TF-IDF of the keyword's terms in the description, with
+//!   document frequencies taken from the tools being ranked, so rare words
+//!   count for more than words every tool uses.
+//! - **Parameters**: a required parameter scores more, and a tool with fewer
+//!   other parameters is a closer fit.
+//!
+//! Parameter counts only filter. `And` and `Or` add up the scores of their
+//! matching parts, and `Not` matches with a score of zero.
+
+use crate::QueryCriteria;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thulp_core::ToolDefinition;
+
+/// Relative weight of each kind of match in a relevance score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreWeights {
+    /// Weight of name matches
+    pub name: f64,
+
+    /// Weight of description term matches
+    pub description: f64,
+
+    /// Weight of parameter matches
+    pub parameter: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            name: 3.0,
+            description: 1.0,
+            parameter: 1.5,
+        }
+    }
+}
+
+/// Term statistics of a tool collection, used to weigh description terms.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    weights: ScoreWeights,
+    documents: usize,
+    document_frequency: HashMap<String, usize>,
+}
+
+impl Corpus {
+    /// Collect description term statistics from a set of tools.
+    pub fn new(tools: &[ToolDefinition]) -> Self {
+        let mut document_frequency: HashMap<String, usize> = HashMap::new();
+        for tool in tools {
+            let terms: HashSet<String> = tokenize(&tool.description).collect();
+            for term in terms {
+                *document_frequency.entry(term).or_default() += 1;
+            }
+        }
+        Self {
+            weights: ScoreWeights::default(),
+            documents: tools.len(),
+            document_frequency,
+        }
+    }
+
+    /// Set the weights of each kind of match.
+    pub fn with_weights(mut self, weights: ScoreWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Get the score weights.
+    pub fn weights(&self) -> &ScoreWeights {
+        &self.weights
+    }
+
+    /// Smoothed inverse document frequency; 1.0 for an empty corpus
+    fn idf(&self, term: &str) -> f64 {
+        let df = self.document_frequency.get(term).copied().unwrap_or(0);
+        ((self.documents as f64 + 1.0) / (df as f64 + 1.0)).ln() + 1.0
+    }
+}
+
+/// A tool and its relevance score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredTool {
+    /// The matching tool
+    pub tool: ToolDefinition,
+
+    /// Relevance score; higher is more relevant
+    pub score: f64,
+}
+
+impl QueryCriteria {
+    /// Check if a tool matches and score how relevant it is.
+    ///
+    /// Returns `None` exactly when [`matches`](Self::matches) returns false.
+    pub fn matches_scored(&self, tool: &ToolDefinition, corpus: &Corpus) -> Option<f64> {
+        if !self.matches(tool) {
+            return None;
+        }
+        Some(self.score(tool, corpus))
+    }
+
+    /// Score of a tool already known to match
+    fn score(&self, tool: &ToolDefinition, corpus: &Corpus) -> f64 {
+        let weights = corpus.weights();
+        match self {
+            QueryCriteria::Name(pattern) => weights.name * name_score(pattern, &tool.name),
+            QueryCriteria::Description(keyword) => {
+                weights.description * description_score(keyword, &tool.description, corpus)
+            }
+            QueryCriteria::HasParameter(name) => {
+                let required = tool
+                    .parameters
+                    .iter()
+                    .any(|p| p.name == *name && p.required);
+                let fit = 1.0 / tool.parameters.len().max(1) as f64;
+                weights.parameter * (if required { 0.6 } else { 0.3 } + 0.4 * fit)
+            }
+            QueryCriteria::MinParameters(_) | QueryCriteria::MaxParameters(_) => 0.0,
+            QueryCriteria::And(criteria) => criteria.iter().map(|c| c.score(tool, corpus)).sum(),
+            QueryCriteria::Or(criteria) => criteria
+                .iter()
+                .filter_map(|c| c.matches_scored(tool, corpus))
+                .sum(),
+            QueryCriteria::Not(_) => 0.0,
+        }
+    }
+}
+
+/// How closely a name matches a pattern, between 0 and 1
+fn name_score(pattern: &str, name: &str) -> f64 {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    if pattern == name {
+        return 1.0;
+    }
+    let literal: String = pattern.chars().filter(|c| *c != '*').collect();
+    let coverage = literal.len() as f64 / name.len().max(1) as f64;
+    if !pattern.contains('*') && name.starts_with(&pattern) {
+        0.6 + 0.3 * coverage
+    } else {
+        0.2 + 0.4 * coverage.min(1.0)
+    }
+}
+
+/// TF-IDF of the keyword's terms in a description, averaged over terms
+fn description_score(keyword: &str, description: &str, corpus: &Corpus) -> f64 {
+    let terms: Vec<String> = tokenize(keyword).collect();
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut length = 0;
+    for token in tokenize(description) {
+        *counts.entry(token).or_default() += 1;
+        length += 1;
+    }
+
+    let total: f64 = terms
+        .iter()
+        .map(|term| {
+            // The keyword matched as a substring, so partial words still count a little
+            let tf = match counts.get(term) {
+                Some(&count) => 1.0 + (count as f64).ln(),
+                None => 0.25,
+            };
+            tf * corpus.idf(term)
+        })
+        .sum();
+    // Damp long descriptions so a focused one ranks above a rambling one
+    total / terms.len() as f64 / (1.0 + (length as f64).ln_1p() / 4.0)
+}
+
+/// Lowercase alphanumeric words of a text
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Query, QueryBuilder};
+    use thulp_core::Parameter;
+
+    fn tools() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::builder("file_read_lines")
+                .description("Read lines from a file on disk")
+                .parameter(Parameter::required_string("path"))
+                .parameter(Parameter::builder("start").build())
+                .build(),
+            ToolDefinition::builder("file_read")
+                .description("Read a file")
+                .parameter(Parameter::required_string("path"))
+                .build(),
+            ToolDefinition::builder("http_get")
+                .description("Fetch a URL and read the response body")
+                .parameter(Parameter::required_string("url"))
+                .build(),
+            ToolDefinition::builder("archive")
+                .description("Compress a file into a tar archive")
+                .parameter(Parameter::builder("path").build())
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_matches_scored_agrees_with_matches() {
+        let tools = tools();
+        let corpus = Corpus::new(&tools);
+        let criteria = [
+            QueryCriteria::Name("file*".to_string()),
+            QueryCriteria::Description("tar".to_string()),
+            QueryCriteria::MaxParameters(1),
+            QueryCriteria::Not(Box::new(QueryCriteria::HasParameter("url".to_string()))),
+        ];
+        for criterion in &criteria {
+            for tool in &tools {
+                assert_eq!(
+                    criterion.matches_scored(tool, &corpus).is_some(),
+                    criterion.matches(tool),
+                    "{:?} on {}",
+                    criterion,
+                    tool.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_name_exact_beats_prefix_beats_substring() {
+        let exact = name_score("file_read", "file_read");
+        let prefix = name_score("file_read", "file_read_lines");
+        let substring = name_score("read", "file_read");
+        assert!(
+            exact > prefix && prefix > substring,
+            "{exact} {prefix} {substring}"
+        );
+    }
+
+    #[test]
+    fn test_rare_terms_weigh_more() {
+        let tools = tools();
+        let corpus = Corpus::new(&tools);
+        // "read" is in three descriptions, "disk" in one
+        assert!(corpus.idf("disk") > corpus.idf("read"));
+        assert_eq!(Corpus::default().idf("anything"), 1.0);
+    }
+
+    #[test]
+    fn test_execute_ranked() {
+        let query = Query::new(QueryCriteria::Or(vec![
+            QueryCriteria::Name("file_read".to_string()),
+            QueryCriteria::Description("read".to_string()),
+        ]))
+        .with_limit(3);
+        let ranked = query.execute_ranked(&tools());
+
+        let names: Vec<&str> = ranked.iter().map(|s| s.tool.name.as_str()).collect();
+        assert_eq!(names, ["file_read", "file_read_lines", "http_get"]);
+        assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_parameter_fit_and_weights() {
+        let tools = tools();
+        let query = Query::new(QueryCriteria::HasParameter("path".to_string()));
+        let ranked = query.execute_ranked(&tools);
+
+        // Required and alone beats required with company beats optional
+        let names: Vec<&str> = ranked.iter().map(|s| s.tool.name.as_str()).collect();
+        assert_eq!(names, ["file_read", "file_read_lines", "archive"]);
+
+        let weights = ScoreWeights {
+            parameter: 0.0,
+            ..Default::default()
+        };
+        let ranked = query.with_weights(weights).execute_ranked(&tools);
+        assert!(ranked.iter().all(|s| s.score == 0.0));
+
+        let limited = QueryBuilder::new().has_parameter("path").limit(1).build();
+        assert_eq!(limited.execute_ranked(&tools).len(), 1);
+        assert_eq!(limited.execute(&tools).len(), 3);
+    }
+}
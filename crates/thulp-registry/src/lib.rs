@@ -25,12 +25,65 @@
 //! filtering) for the metadata side.
 //!
 //! See `README.md` "Intended Use" for the full rationale.
-
+//!
+//! ## Namespaces
+//!
+//! Tools discovered from different MCP servers often share names (`search`,
+//! `read_file`). [`ToolRegistry::register_namespaced`] stores them under
+//! `server.tool` keys and records where each one came from, so they can
+//! coexist and be listed per server. Namespaced registration never
+//! overwrites: a key that is already taken is reported as a conflict.
+//!
+//! ```rust
+//! use thulp_core::ToolDefinition;
+//! use thulp_registry::ToolRegistry;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> thulp_core::Result<()> {
+//! let registry = ToolRegistry::new();
+//! registry.register_namespaced("github", ToolDefinition::new("search")).await?;
+//! registry.register_namespaced("jira", ToolDefinition::new("search")).await?;
+//!
+//! assert!(registry.contains("github.search").await);
+//! assert_eq!(registry.list_by_source("jira").await.len(), 1);
+//! assert!(registry
+//!     .register_namespaced("github", ToolDefinition::new("search"))
+//!     .await
+//!     .is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod schema;
+
+pub use schema::{OutputSchema, SchemaCapture};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use thulp_core::{Error, Result, ToolDefinition};
+use thulp_core::{Error, Result, ToolDefinition, ToolResult};
 use tokio::sync::RwLock;
 
+/// Separator between the source and tool name in namespaced keys.
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+/// Build the registry key for a tool provided by `source`.
+pub fn namespaced_name(source: &str, tool: &str) -> String {
+    format!("{}{}{}", source, NAMESPACE_SEPARATOR, tool)
+}
+
+/// Where a namespaced tool came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolSource {
+    /// Source (typically the MCP server name)
+    pub server: String,
+
+    /// Tool name as known to the source
+    pub tool: String,
+}
+
 /// Tool registry for managing and discovering tools.
 ///
 /// The registry supports:
@@ -44,6 +97,15 @@ pub struct ToolRegistry {
 
     /// Map of tags to tool names for discovery
     tags: Arc<RwLock<HashMap<String, Vec<String>>>>,
+
+    /// Map of namespaced tool key to its source
+    sources: Arc<RwLock<HashMap<String, ToolSource>>>,
+
+    /// Map of tool name to its learned output schema
+    output_schemas: Arc<RwLock<HashMap<String, OutputSchema>>>,
+
+    /// Whether tool results are recorded into output schemas
+    learning: Arc<AtomicBool>,
 }
 
 impl ToolRegistry {
@@ -52,12 +114,24 @@ impl ToolRegistry {
         Self {
             tools: Arc::new(RwLock::new(HashMap::new())),
             tags: Arc::new(RwLock::new(HashMap::new())),
+            sources: Arc::new(RwLock::new(HashMap::new())),
+            output_schemas: Arc::new(RwLock::new(HashMap::new())),
+            learning: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Enable or disable output schema learning (see [`schema`]).
+    pub fn with_schema_learning(self, enabled: bool) -> Self {
+        self.set_schema_learning(enabled);
+        self
+    }
+
     /// Register a tool in the registry.
+    ///
+    /// An existing tool with the same name is replaced.
     pub async fn register(&self, tool: ToolDefinition) -> Result<()> {
         let mut tools = self.tools.write().await;
+        self.sources.write().await.remove(&tool.name);
         tools.insert(tool.name.clone(), tool);
         Ok(())
     }
@@ -65,18 +139,129 @@ impl ToolRegistry {
     /// Register multiple tools at once.
     pub async fn register_many(&self, tools: Vec<ToolDefinition>) -> Result<()> {
         let mut registry = self.tools.write().await;
+        let mut sources = self.sources.write().await;
         for tool in tools {
+            sources.remove(&tool.name);
             registry.insert(tool.name.clone(), tool);
         }
         Ok(())
     }
 
+    /// Register a tool from `source` under the key `source.tool`.
+    ///
+    /// The definition is stored unchanged, so its `name` is still the name
+    /// the source knows it by. Returns the registry key, or an error if the
+    /// key is already taken.
+    pub async fn register_namespaced(&self, source: &str, tool: ToolDefinition) -> Result<String> {
+        let mut keys = self.register_namespaced_many(source, vec![tool]).await?;
+        Ok(keys.remove(0))
+    }
+
+    /// Register several tools from `source` under `source.tool` keys.
+    ///
+    /// Either all tools are registered or none are: if any key is already
+    /// taken (or appears twice in `tools`), every conflicting key is
+    /// reported in the error.
+    pub async fn register_namespaced_many(
+        &self,
+        source: &str,
+        tools: Vec<ToolDefinition>,
+    ) -> Result<Vec<String>> {
+        validate_source(source)?;
+
+        let mut registry = self.tools.write().await;
+        let mut sources = self.sources.write().await;
+
+        let keys: Vec<String> = tools
+            .iter()
+            .map(|tool| namespaced_name(source, &tool.name))
+            .collect();
+        let mut conflicts: Vec<String> = Vec::new();
+        for (index, key) in keys.iter().enumerate() {
+            let taken = registry.contains_key(key) || keys[..index].contains(key);
+            if taken && !conflicts.contains(key) {
+                conflicts.push(key.clone());
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(Error::InvalidConfig(format!(
+                "Tool name conflict: {} already registered",
+                conflicts.join(", ")
+            )));
+        }
+
+        for (key, tool) in keys.iter().zip(tools) {
+            sources.insert(
+                key.clone(),
+                ToolSource {
+                    server: source.to_string(),
+                    tool: tool.name.clone(),
+                },
+            );
+            registry.insert(key.clone(), tool);
+        }
+        Ok(keys)
+    }
+
     /// Unregister a tool from the registry.
     pub async fn unregister(&self, name: &str) -> Result<Option<ToolDefinition>> {
         let mut tools = self.tools.write().await;
+        self.sources.write().await.remove(name);
+        self.output_schemas.write().await.remove(name);
         Ok(tools.remove(name))
     }
 
+    /// Unregister every tool registered from `source`.
+    ///
+    /// Returns the number of tools removed. Useful before re-registering a
+    /// server's tools after its tool list changed.
+    pub async fn unregister_source(&self, source: &str) -> usize {
+        let mut tools = self.tools.write().await;
+        let mut sources = self.sources.write().await;
+        let mut output_schemas = self.output_schemas.write().await;
+
+        let before = sources.len();
+        sources.retain(|key, origin| {
+            let keep = origin.server != source;
+            if !keep {
+                tools.remove(key);
+                output_schemas.remove(key);
+            }
+            keep
+        });
+        before - sources.len()
+    }
+
+    /// Get the source of a namespaced tool.
+    pub async fn source_of(&self, name: &str) -> Option<ToolSource> {
+        self.sources.read().await.get(name).cloned()
+    }
+
+    /// List the tools registered from `source`, sorted by name.
+    pub async fn list_by_source(&self, source: &str) -> Vec<ToolDefinition> {
+        let tools = self.tools.read().await;
+        let sources = self.sources.read().await;
+
+        let mut keys: Vec<&String> = sources
+            .iter()
+            .filter(|(_, origin)| origin.server == source)
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        keys.into_iter()
+            .filter_map(|key| tools.get(key).cloned())
+            .collect()
+    }
+
+    /// List the distinct sources with registered tools, sorted.
+    pub async fn sources(&self) -> Vec<String> {
+        let sources = self.sources.read().await;
+        let mut servers: Vec<String> = sources.values().map(|s| s.server.clone()).collect();
+        servers.sort();
+        servers.dedup();
+        servers
+    }
+
     /// Get a tool definition by name.
     pub async fn get(&self, name: &str) -> Result<Option<ToolDefinition>> {
         let tools = self.tools.read().await;
@@ -101,6 +286,8 @@ impl ToolRegistry {
         let mut tags = self.tags.write().await;
         tools.clear();
         tags.clear();
+        self.sources.write().await.clear();
+        self.output_schemas.write().await.clear();
     }
 
     /// Check if a tool is registered.
@@ -145,6 +332,94 @@ impl ToolRegistry {
         }
         Ok(results)
     }
+
+    /// Turn output schema learning on or off.
+    pub fn set_schema_learning(&self, enabled: bool) {
+        self.learning.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether output schema learning is enabled.
+    pub fn is_learning_schemas(&self) -> bool {
+        self.learning.load(Ordering::Relaxed)
+    }
+
+    /// Record the output of a call to a registered tool.
+    ///
+    /// Does nothing unless learning is enabled, the tool is registered and the
+    /// call succeeded with data. Returns whether the result was recorded.
+    pub async fn record_output(&self, name: &str, result: &ToolResult) -> bool {
+        let data = match &result.data {
+            Some(data) if result.success && self.is_learning_schemas() => data,
+            _ => return false,
+        };
+        if !self.contains(name).await {
+            return false;
+        }
+
+        let mut schemas = self.output_schemas.write().await;
+        match schemas.get_mut(name) {
+            Some(schema) => schema.observe(data),
+            None => {
+                schemas.insert(name.to_string(), OutputSchema::from_sample(data));
+            }
+        }
+        true
+    }
+
+    /// Get the learned output schema of a tool.
+    pub async fn output_schema(&self, name: &str) -> Option<OutputSchema> {
+        self.output_schemas.read().await.get(name).cloned()
+    }
+
+    /// Get every learned output schema, e.g. to persist them.
+    pub async fn output_schemas(&self) -> HashMap<String, OutputSchema> {
+        self.output_schemas.read().await.clone()
+    }
+
+    /// Set the output schema of a tool, e.g. one loaded from disk.
+    ///
+    /// Later results recorded while learning keep widening it.
+    pub async fn set_output_schema(&self, name: impl Into<String>, schema: OutputSchema) {
+        self.output_schemas
+            .write()
+            .await
+            .insert(name.into(), schema);
+    }
+
+    /// Forget the learned output schema of a tool.
+    pub async fn clear_output_schema(&self, name: &str) -> Option<OutputSchema> {
+        self.output_schemas.write().await.remove(name)
+    }
+
+    /// Check a tool output against the tool's learned schema.
+    ///
+    /// Tools without a schema accept any output.
+    pub async fn validate_output(&self, name: &str, output: &Value) -> Result<()> {
+        let errors = match self.output_schemas.read().await.get(name) {
+            Some(schema) => schema.validate(output),
+            None => return Ok(()),
+        };
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ExecutionFailed(format!(
+                "Output of '{}' does not match its learned schema: {}",
+                name,
+                errors.join("; ")
+            )))
+        }
+    }
+}
+
+/// Check that a source name can be used as a namespace
+fn validate_source(source: &str) -> Result<()> {
+    if source.is_empty() || source.contains(NAMESPACE_SEPARATOR) {
+        return Err(Error::InvalidConfig(format!(
+            "Invalid tool source '{}': must be non-empty and not contain '{}'",
+            source, NAMESPACE_SEPARATOR
+        )));
+    }
+    Ok(())
 }
 
 impl Default for ToolRegistry {
@@ -264,4 +539,186 @@ mod tests {
         let result = registry.tag("nonexistent", "test").await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn namespaced_tools_coexist() {
+        let registry = ToolRegistry::new();
+        let key = registry
+            .register_namespaced("github", create_test_tool("search"))
+            .await
+            .unwrap();
+        assert_eq!(key, "github.search");
+        registry
+            .register_namespaced_many(
+                "jira",
+                vec![create_test_tool("search"), create_test_tool("create_issue")],
+            )
+            .await
+            .unwrap();
+        registry.register(create_test_tool("local")).await.unwrap();
+
+        assert_eq!(registry.count().await, 4);
+        let tool = registry.get("jira.search").await.unwrap().unwrap();
+        assert_eq!(tool.name, "search");
+
+        assert_eq!(
+            registry.source_of("jira.create_issue").await,
+            Some(ToolSource {
+                server: "jira".to_string(),
+                tool: "create_issue".to_string(),
+            })
+        );
+        assert_eq!(registry.source_of("local").await, None);
+        assert_eq!(registry.sources().await, vec!["github", "jira"]);
+
+        let jira: Vec<String> = registry
+            .list_by_source("jira")
+            .await
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(jira, vec!["create_issue", "search"]);
+    }
+
+    #[tokio::test]
+    async fn namespaced_conflicts_are_reported() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_namespaced("github", create_test_tool("search"))
+            .await
+            .unwrap();
+
+        let err = registry
+            .register_namespaced_many(
+                "github",
+                vec![
+                    create_test_tool("search"),
+                    create_test_tool("issues"),
+                    create_test_tool("issues"),
+                ],
+            )
+            .await
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("github.search"));
+        assert!(message.contains("github.issues"));
+
+        // Nothing from the failed batch was registered
+        assert_eq!(registry.count().await, 1);
+        assert!(!registry.contains("github.issues").await);
+
+        assert!(registry
+            .register_namespaced("bad.name", create_test_tool("x"))
+            .await
+            .is_err());
+        assert!(registry
+            .register_namespaced("", create_test_tool("x"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn unregister_source_removes_only_its_tools() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_namespaced_many("a", vec![create_test_tool("one"), create_test_tool("two")])
+            .await
+            .unwrap();
+        registry
+            .register_namespaced("b", create_test_tool("one"))
+            .await
+            .unwrap();
+
+        assert_eq!(registry.unregister_source("a").await, 2);
+        assert_eq!(registry.count().await, 1);
+        assert!(registry.contains("b.one").await);
+        assert_eq!(registry.sources().await, vec!["b"]);
+
+        registry.unregister("b.one").await.unwrap();
+        assert!(registry.sources().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn learn_and_validate_output_schema() {
+        let registry = ToolRegistry::new();
+        registry.register(create_test_tool("search")).await.unwrap();
+        let result = ToolResult::success(serde_json::json!({"count": 2, "items": ["a", "b"]}));
+
+        // Learning is off by default
+        assert!(!registry.record_output("search", &result).await);
+        assert!(registry.output_schema("search").await.is_none());
+
+        registry.set_schema_learning(true);
+        assert!(registry.record_output("search", &result).await);
+        assert!(
+            !registry
+                .record_output("search", &ToolResult::failure("boom"))
+                .await
+        );
+        assert!(!registry.record_output("unknown", &result).await);
+        registry
+            .record_output(
+                "search",
+                &ToolResult::success(serde_json::json!({"count": 0, "items": []})),
+            )
+            .await;
+
+        let schema = registry.output_schema("search").await.unwrap();
+        assert_eq!(schema.samples, 2);
+        assert!(registry
+            .validate_output("search", &serde_json::json!({"count": 1, "items": ["c"]}))
+            .await
+            .is_ok());
+        let err = registry
+            .validate_output("search", &serde_json::json!({"items": [1]}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing field 'count'"));
+        assert!(registry
+            .validate_output("other", &serde_json::json!(null))
+            .await
+            .is_ok());
+
+        registry.unregister("search").await.unwrap();
+        assert!(registry.output_schemas().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn schema_capture_records_calls() {
+        use async_trait::async_trait;
+        use thulp_core::{ToolCall, Transport};
+
+        struct Echo;
+
+        #[async_trait]
+        impl Transport for Echo {
+            async fn connect(&mut self) -> Result<()> {
+                Ok(())
+            }
+            async fn disconnect(&mut self) -> Result<()> {
+                Ok(())
+            }
+            fn is_connected(&self) -> bool {
+                true
+            }
+            async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+                Ok(Vec::new())
+            }
+            async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+                Ok(ToolResult::success(call.arguments.clone()))
+            }
+        }
+
+        let registry = Arc::new(ToolRegistry::new().with_schema_learning(true));
+        registry.register(create_test_tool("echo")).await.unwrap();
+        let transport = SchemaCapture::new(Echo, registry.clone());
+
+        let call = ToolCall::builder("echo")
+            .arg("title", serde_json::json!("hello"))
+            .build();
+        transport.call(&call).await.unwrap();
+
+        let schema = registry.output_schema("echo").await.unwrap();
+        assert_eq!(schema.paths(), ["title"]);
+    }
 }
@@ -0,0 +1,414 @@
+//! Output schemas learned from live tool calls.
+//!
+//! Tool definitions describe their inputs but rarely their outputs. With
+//! schema learning enabled, a [`ToolRegistry`] records the JSON shape of every
+//! successful [`ToolResult`] it is shown and merges the observations into an
+//! [`OutputSchema`] per tool: fields seen in every result become `required`,
+//! integers widen to numbers, and a field that is sometimes `null` gets a
+//! `["string", "null"]`-style type.
+//!
+//! Learned schemas can then check that a tool still returns what downstream
+//! steps expect ([`ToolRegistry::validate_output`]) and list the paths that
+//! can be referenced from templates ([`OutputSchema::paths`]).
+//!
+//! [`SchemaCapture`] wraps a transport and feeds every call result into the
+//! registry, so learning happens as a side effect of normal use.
+//!
+//! ```rust
+//! use serde_json::json;
+//! use thulp_core::{ToolDefinition, ToolResult};
+//! use thulp_registry::ToolRegistry;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> thulp_core::Result<()> {
+//! let registry = ToolRegistry::new().with_schema_learning(true);
+//! registry.register(ToolDefinition::new("search")).await?;
+//!
+//! let result = ToolResult::success(json!({"count": 1, "items": [{"title": "a"}]}));
+//! registry.record_output("search", &result).await;
+//!
+//! let schema = registry.output_schema("search").await.unwrap();
+//! assert_eq!(schema.paths(), ["count", "items", "items.0", "items.0.title"]);
+//! assert!(registry.validate_output("search", &json!({"count": "one"})).await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::ToolRegistry;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use thulp_core::{Result, ToolCall, ToolDefinition, ToolResult, ToolResultStream, Transport};
+
+/// JSON Schema type names in the order they are listed in merged schemas.
+const TYPE_ORDER: [&str; 7] = [
+    "object", "array", "string", "number", "integer", "boolean", "null",
+];
+
+/// An output schema synthesized from observed tool results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputSchema {
+    /// JSON Schema describing every result observed so far
+    pub schema: Value,
+
+    /// Number of results the schema was learned from
+    pub samples: u64,
+}
+
+impl OutputSchema {
+    /// Start a schema from a single result.
+    pub fn from_sample(value: &Value) -> Self {
+        Self {
+            schema: infer_schema(value),
+            samples: 1,
+        }
+    }
+
+    /// Widen the schema to also accept `value`.
+    pub fn observe(&mut self, value: &Value) {
+        self.schema = merge_schemas(&self.schema, &infer_schema(value));
+        self.samples += 1;
+    }
+
+    /// Check a value against the schema, returning every mismatch.
+    pub fn validate(&self, value: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        validate_value(&self.schema, value, "$", &mut errors);
+        errors
+    }
+
+    /// Dotted paths into the output, as used in `{{step.path}}` references.
+    ///
+    /// Array items appear under index `0`.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        collect_paths(&self.schema, "", &mut paths);
+        paths
+    }
+}
+
+/// Infer the JSON Schema of a single value.
+pub fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({"type": "null"}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_f64() => json!({"type": "number"}),
+        Value::Number(_) => json!({"type": "integer"}),
+        Value::String(_) => json!({"type": "string"}),
+        Value::Array(items) => {
+            let mut schema = json!({"type": "array"});
+            if let Some(items) = items
+                .iter()
+                .map(infer_schema)
+                .reduce(|a, b| merge_schemas(&a, &b))
+            {
+                schema["items"] = items;
+            }
+            schema
+        }
+        Value::Object(obj) => {
+            let properties: Map<String, Value> = obj
+                .iter()
+                .map(|(k, v)| (k.clone(), infer_schema(v)))
+                .collect();
+            let mut schema = json!({"type": "object", "properties": properties});
+            if !obj.is_empty() {
+                let mut required: Vec<&String> = obj.keys().collect();
+                required.sort();
+                schema["required"] = json!(required);
+            }
+            schema
+        }
+    }
+}
+
+/// Merge two schemas into one accepting values of either.
+pub fn merge_schemas(a: &Value, b: &Value) -> Value {
+    let (types_a, types_b) = (schema_types(a), schema_types(b));
+    let mut types: Vec<&str> = TYPE_ORDER
+        .iter()
+        .copied()
+        .filter(|t| types_a.contains(t) || types_b.contains(t))
+        .collect();
+    if types.contains(&"number") {
+        types.retain(|t| *t != "integer");
+    }
+
+    let mut merged = Map::new();
+    merged.insert(
+        "type".to_string(),
+        match types.as_slice() {
+            [single] => json!(single),
+            many => json!(many),
+        },
+    );
+
+    let objects = (types_a.contains(&"object"), types_b.contains(&"object"));
+    if objects.0 || objects.1 {
+        let props_a = a.get("properties").and_then(Value::as_object);
+        let props_b = b.get("properties").and_then(Value::as_object);
+        let mut properties = props_a.cloned().unwrap_or_default();
+        for (key, schema) in props_b.into_iter().flatten() {
+            let combined = match properties.get(key) {
+                Some(existing) => merge_schemas(existing, schema),
+                None => schema.clone(),
+            };
+            properties.insert(key.clone(), combined);
+        }
+        merged.insert("properties".to_string(), Value::Object(properties));
+
+        // Only fields present in every observed object stay required
+        let required = match objects {
+            (true, true) => {
+                let other = required_fields(b);
+                required_fields(a)
+                    .into_iter()
+                    .filter(|field| other.contains(field))
+                    .collect()
+            }
+            (true, false) => required_fields(a),
+            _ => required_fields(b),
+        };
+        if !required.is_empty() {
+            merged.insert("required".to_string(), json!(required));
+        }
+    }
+
+    match (a.get("items"), b.get("items")) {
+        (Some(x), Some(y)) => {
+            merged.insert("items".to_string(), merge_schemas(x, y));
+        }
+        (Some(items), None) | (None, Some(items)) => {
+            merged.insert("items".to_string(), items.clone());
+        }
+        (None, None) => {}
+    }
+
+    Value::Object(merged)
+}
+
+/// Type names accepted by a schema
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fields a schema marks as required
+fn required_fields(schema: &Value) -> Vec<String> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|f| f.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// JSON Schema type name of a value
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let types = schema_types(schema);
+    let actual = value_type(value);
+    let type_matches = types.is_empty()
+        || types.contains(&actual)
+        || (actual == "integer" && types.contains(&"number"));
+    if !type_matches {
+        errors.push(format!(
+            "{}: expected {}, got {}",
+            path,
+            types.join(" or "),
+            actual
+        ));
+        return;
+    }
+
+    match value {
+        Value::Object(obj) => {
+            for field in required_fields(schema) {
+                if !obj.contains_key(&field) {
+                    errors.push(format!("{}: missing field '{}'", path, field));
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (key, value) in obj {
+                    if let Some(schema) = properties.get(key) {
+                        validate_value(schema, value, &format!("{}.{}", path, key), errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(schema, item, &format!("{}.{}", path, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_paths(schema: &Value, prefix: &str, paths: &mut Vec<String>) {
+    let join = |segment: &str| {
+        if prefix.is_empty() {
+            segment.to_string()
+        } else {
+            format!("{}.{}", prefix, segment)
+        }
+    };
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (key, schema) in properties {
+            let path = join(key);
+            paths.push(path.clone());
+            collect_paths(schema, &path, paths);
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        let path = join("0");
+        paths.push(path.clone());
+        collect_paths(items, &path, paths);
+    }
+}
+
+/// A [`Transport`] wrapper that records the output of every call in a
+/// [`ToolRegistry`].
+///
+/// Results are only recorded while the registry's schema learning is enabled,
+/// so the wrapper can stay in place and learning be switched on and off.
+pub struct SchemaCapture<T> {
+    inner: T,
+    registry: Arc<ToolRegistry>,
+}
+
+impl<T> SchemaCapture<T> {
+    /// Wrap a transport, recording into `registry`.
+    pub fn new(inner: T, registry: Arc<ToolRegistry>) -> Self {
+        Self { inner, registry }
+    }
+
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get the registry results are recorded in.
+    pub fn registry(&self) -> &Arc<ToolRegistry> {
+        &self.registry
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for SchemaCapture<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        self.inner.list_tools().await
+    }
+
+    async fn call(&self, call: &ToolCall) -> Result<ToolResult> {
+        let result = self.inner.call(call).await?;
+        self.registry.record_output(&call.tool, &result).await;
+        Ok(result)
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> Result<ToolResultStream> {
+        self.inner.call_streaming(call).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_schema() {
+        let schema = infer_schema(&json!({"id": 1, "score": 0.5, "tags": ["a"], "note": null}));
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["score"]["type"], "number");
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(schema["properties"]["note"]["type"], "null");
+        assert_eq!(schema["required"], json!(["id", "note", "score", "tags"]));
+        assert_eq!(infer_schema(&json!([])), json!({"type": "array"}));
+    }
+
+    #[test]
+    fn test_merge_widens_types_and_relaxes_required() {
+        let mut schema = OutputSchema::from_sample(&json!({"id": 1, "name": "a"}));
+        schema.observe(&json!({"id": 2.5, "name": null, "extra": true}));
+
+        let s = &schema.schema;
+        assert_eq!(schema.samples, 2);
+        assert_eq!(s["properties"]["id"]["type"], "number");
+        assert_eq!(s["properties"]["name"]["type"], json!(["string", "null"]));
+        assert_eq!(s["properties"]["extra"]["type"], "boolean");
+        assert_eq!(s["required"], json!(["id", "name"]));
+    }
+
+    #[test]
+    fn test_merge_nested_arrays() {
+        let mut schema = OutputSchema::from_sample(&json!({"items": []}));
+        schema.observe(&json!({"items": [{"title": "a", "url": "x"}, {"title": "b"}]}));
+
+        let items = &schema.schema["properties"]["items"]["items"];
+        assert_eq!(items["required"], json!(["title"]));
+        assert_eq!(
+            schema.paths(),
+            ["items", "items.0", "items.0.title", "items.0.url"]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut schema = OutputSchema::from_sample(&json!({"count": 1, "items": [{"id": 1}]}));
+        schema.observe(&json!({"count": 2, "items": []}));
+
+        assert!(schema
+            .validate(&json!({"count": 3, "items": [{"id": 4}]}))
+            .is_empty());
+        assert_eq!(
+            schema.validate(&json!({"count": 3.5, "items": []})).len(),
+            1
+        );
+        assert_eq!(
+            schema.validate(&json!({"items": [{"id": "x"}]})),
+            vec![
+                "$: missing field 'count'".to_string(),
+                "$.items.0.id: expected integer, got string".to_string(),
+            ]
+        );
+        assert_eq!(
+            schema.validate(&json!("oops")),
+            vec!["$: expected object, got string".to_string()]
+        );
+    }
+}
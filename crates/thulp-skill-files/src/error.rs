@@ -44,6 +44,18 @@ pub enum SkillFileError {
     #[error("Approval required for skill: {0}")]
     ApprovalRequired(String),
 
+    /// Skill package index or installation error.
+    #[error("Package error: {0}")]
+    Package(String),
+
+    /// Downloaded package file doesn't match its checksum.
+    #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+
     /// Regex compilation error.
     #[error("Regex error: {0}")]
     Regex(#[from] regex::Error),
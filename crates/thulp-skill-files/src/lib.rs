@@ -6,6 +6,7 @@
 //! - Parsing SKILL.md files with YAML frontmatter
 //! - Preprocessing skill content (arguments, commands, variables)
 //! - Loading skills from directories with scope-based priority
+//! - Installing shared skills from a package index, see [`package`]
 //!
 //! ## Quick Start
 //!
@@ -66,12 +67,17 @@
 pub mod error;
 pub mod frontmatter;
 pub mod loader;
+#[cfg(feature = "packages")]
+pub mod package;
 pub mod parser;
+pub mod paths;
 pub mod preprocessor;
 
 // Re-export main types
 pub use error::{Result, SkillFileError};
 pub use frontmatter::{PriceModel, SkillContext, SkillFrontmatter, SkillHooks};
 pub use loader::{LoadedSkill, SkillLoader, SkillLoaderConfig, SkillScope};
+#[cfg(feature = "packages")]
+pub use package::{PackageClient, PackageEntry, PackageIndex};
 pub use parser::{SkillFile, SupportingFile, SupportingFileType};
 pub use preprocessor::SkillPreprocessor;
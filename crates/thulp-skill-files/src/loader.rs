@@ -5,6 +5,7 @@
 
 use crate::error::Result;
 use crate::parser::SkillFile;
+use crate::paths;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
@@ -40,6 +41,8 @@ impl std::fmt::Display for SkillScope {
 #[derive(Debug, Clone)]
 pub struct SkillLoaderConfig {
     /// Project skills directory (e.g., ./.claude/skills/).
+    ///
+    /// Directories may start with `~`, which expands to the home directory.
     pub project_dir: Option<PathBuf>,
     /// Personal skills directory (e.g., ~/.claude/skills/).
     pub personal_dir: Option<PathBuf>,
@@ -49,16 +52,22 @@ pub struct SkillLoaderConfig {
     pub plugin_dirs: Vec<PathBuf>,
     /// Maximum directory depth to scan.
     pub max_depth: usize,
+    /// Match `SKILL.md` and skill names without regard to case.
+    ///
+    /// Defaults to on for Windows and macOS, whose file systems are
+    /// case-insensitive by default.
+    pub case_insensitive_names: bool,
 }
 
 impl Default for SkillLoaderConfig {
     fn default() -> Self {
         Self {
-            project_dir: Some(PathBuf::from(".claude/skills")),
-            personal_dir: dirs::home_dir().map(|h| h.join(".claude/skills")),
+            project_dir: Some(PathBuf::from(".claude").join("skills")),
+            personal_dir: paths::home_dir().map(|h| h.join(".claude").join("skills")),
             enterprise_dir: None,
             plugin_dirs: Vec::new(),
             max_depth: 3,
+            case_insensitive_names: paths::CASE_INSENSITIVE_DEFAULT,
         }
     }
 }
@@ -72,9 +81,16 @@ impl SkillLoaderConfig {
             enterprise_dir: None,
             plugin_dirs: Vec::new(),
             max_depth: 3,
+            case_insensitive_names: paths::CASE_INSENSITIVE_DEFAULT,
         }
     }
 
+    /// Set whether `SKILL.md` and skill names are matched without regard to case.
+    pub fn with_case_insensitive_names(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_names = case_insensitive;
+        self
+    }
+
     /// Create a config for testing with a single directory.
     pub fn single(path: impl Into<PathBuf>) -> Self {
         Self::project_only(path)
@@ -152,6 +168,7 @@ impl SkillLoader {
 
         // Load plugins with namespace
         for plugin_dir in &self.config.plugin_dirs {
+            let plugin_dir = paths::expand_tilde(plugin_dir);
             if let Some(plugin_name) = plugin_dir.file_name().and_then(|n| n.to_str()) {
                 let skills_dir = plugin_dir.join("skills");
                 if skills_dir.exists() {
@@ -176,17 +193,18 @@ impl SkillLoader {
     ) -> Result<Vec<LoadedSkill>> {
         let mut skills = Vec::new();
 
+        let dir = paths::expand_tilde(dir);
         if !dir.exists() {
             return Ok(skills);
         }
 
-        for entry in WalkDir::new(dir)
+        for entry in WalkDir::new(&dir)
             .max_depth(self.config.max_depth)
             .into_iter()
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
-            if path.is_file() && path.file_name() == Some(std::ffi::OsStr::new("SKILL.md")) {
+            if path.is_file() && paths::is_skill_file(path, self.config.case_insensitive_names) {
                 match SkillFile::parse(path) {
                     Ok(file) => {
                         skills.push(LoadedSkill {
@@ -231,9 +249,31 @@ impl SkillLoader {
 
     /// Find a skill by name.
     pub fn find_skill<'a>(skills: &'a [LoadedSkill], name: &str) -> Option<&'a LoadedSkill> {
-        skills
-            .iter()
-            .find(|s| s.file.effective_name() == name || s.qualified_name() == name)
+        Self::find_skill_with(skills, name, false)
+    }
+
+    /// Find a skill by name, honouring the loader's
+    /// [`case_insensitive_names`](SkillLoaderConfig::case_insensitive_names) setting.
+    ///
+    /// An exact match is preferred over a case-insensitive one.
+    pub fn find<'a>(&self, skills: &'a [LoadedSkill], name: &str) -> Option<&'a LoadedSkill> {
+        Self::find_skill(skills, name).or_else(|| {
+            self.config
+                .case_insensitive_names
+                .then(|| Self::find_skill_with(skills, name, true))
+                .flatten()
+        })
+    }
+
+    fn find_skill_with<'a>(
+        skills: &'a [LoadedSkill],
+        name: &str,
+        case_insensitive: bool,
+    ) -> Option<&'a LoadedSkill> {
+        skills.iter().find(|s| {
+            paths::names_match(&s.file.effective_name(), name, case_insensitive)
+                || paths::names_match(&s.qualified_name(), name, case_insensitive)
+        })
     }
 
     /// Filter skills that are invocable by the model.
@@ -298,6 +338,47 @@ mod tests {
         assert_eq!(resolved.get("shared").unwrap().scope, SkillScope::Personal);
     }
 
+    #[test]
+    fn test_case_insensitive_discovery_and_lookup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let skill_dir = temp.path().join("Deploy");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(
+            skill_dir.join("skill.md"),
+            "---\nname: Deploy\ndescription: Ship it\n---\nDo it.",
+        )
+        .unwrap();
+
+        let strict = SkillLoader::new(
+            SkillLoaderConfig::single(temp.path()).with_case_insensitive_names(false),
+        );
+        assert!(strict.load_all().unwrap().is_empty());
+
+        let loader = SkillLoader::new(
+            SkillLoaderConfig::single(temp.path()).with_case_insensitive_names(true),
+        );
+        let skills = loader.load_all().unwrap();
+        assert_eq!(skills.len(), 1);
+        assert!(loader.find(&skills, "deploy").is_some());
+        assert!(SkillLoader::find_skill(&skills, "deploy").is_none());
+        assert!(strict.find(&skills, "deploy").is_none());
+    }
+
+    #[test]
+    fn test_default_config_paths() {
+        let config = SkillLoaderConfig::default();
+        assert_eq!(
+            config.project_dir,
+            Some(PathBuf::from(".claude").join("skills"))
+        );
+        if let Some(home) = paths::home_dir() {
+            assert_eq!(
+                config.personal_dir,
+                Some(home.join(".claude").join("skills"))
+            );
+        }
+    }
+
     fn create_mock_skill_file(name: &str) -> SkillFile {
         use crate::frontmatter::SkillFrontmatter;
 
@@ -0,0 +1,576 @@
+//! Installing shared skills from a package index.
+//!
+//! A package index is a static JSON file, usually served over HTTPS, that
+//! lists skill packages and the files each version is made of:
+//!
+//! ```json
+//! {
+//!   "packages": [
+//!     {
+//!       "name": "web-research",
+//!       "version": "1.2.0",
+//!       "description": "Search the web and summarize the results",
+//!       "files": [
+//!         { "path": "SKILL.md", "url": "web-research/1.2.0/SKILL.md", "sha256": "9f86d0..." }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! File URLs may be relative to the index URL, and every file is checked
+//! against its SHA-256 checksum before anything is written. An installed
+//! package gets a [`PACKAGE_MANIFEST`] file recording its version and the
+//! index it came from, which is what [`check_updates`] compares against.
+//! `file://` index URLs are read from disk, which suits mirrors and tests.
+//!
+//! ```rust,ignore
+//! use thulp_skill_files::package::{check_updates, installed_packages, PackageClient};
+//!
+//! let client = PackageClient::new("https://example.com/skills/index.json")?;
+//! let index = client.fetch_index().await?;
+//! let entry = index.find("web-research", None)?.expect("package exists");
+//! client.install(entry, ".thulp/skills".as_ref(), false).await?;
+//!
+//! for update in check_updates(&index, &installed_packages(".thulp/skills".as_ref())?) {
+//!     println!("{} {} -> {}", update.name, update.installed, update.available);
+//! }
+//! ```
+
+use crate::error::{Result, SkillFileError};
+use crate::paths;
+use reqwest::Url;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+
+/// File written into each installed package directory.
+pub const PACKAGE_MANIFEST: &str = ".thulp-package.json";
+
+/// A package index.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackageIndex {
+    /// All published versions of all packages.
+    #[serde(default)]
+    pub packages: Vec<PackageEntry>,
+}
+
+/// One version of a package in the index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageEntry {
+    /// Package name, which is also the installed directory name.
+    pub name: String,
+    /// Semver version.
+    pub version: String,
+    /// What the skill does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Files making up the package.
+    pub files: Vec<PackageFile>,
+}
+
+/// A file in a package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageFile {
+    /// Path inside the package directory, e.g. `SKILL.md` or `scripts/run.sh`.
+    pub path: String,
+    /// Where to download the file, absolute or relative to the index.
+    pub url: String,
+    /// Hex-encoded SHA-256 of the file contents.
+    pub sha256: String,
+}
+
+/// Contents of [`PACKAGE_MANIFEST`] in an installed package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPackage {
+    /// Package name.
+    pub name: String,
+    /// Installed version.
+    pub version: String,
+    /// Index the package was installed from.
+    pub index: String,
+    /// Installed files with their checksums.
+    pub files: Vec<InstalledFile>,
+}
+
+/// A file of an installed package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledFile {
+    /// Path inside the package directory.
+    pub path: String,
+    /// Hex-encoded SHA-256 of the file contents.
+    pub sha256: String,
+}
+
+/// A newer version of an installed package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageUpdate {
+    /// Package name.
+    pub name: String,
+    /// Installed version.
+    pub installed: Version,
+    /// Newest version in the index.
+    pub available: Version,
+}
+
+impl PackageEntry {
+    /// Parsed version of the entry.
+    pub fn semver(&self) -> Result<Version> {
+        Version::parse(&self.version).map_err(|e| {
+            SkillFileError::Package(format!(
+                "Invalid version '{}' of package '{}': {}",
+                self.version, self.name, e
+            ))
+        })
+    }
+}
+
+impl PackageIndex {
+    /// Parse an index from JSON.
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| SkillFileError::Package(format!("Invalid package index: {}", e)))
+    }
+
+    /// Find the newest version of a package matching a version requirement.
+    ///
+    /// A bare version such as `1.2.0` means exactly that version. Without a
+    /// requirement the newest stable version is picked. Entries with
+    /// invalid versions are ignored.
+    pub fn find(&self, name: &str, version: Option<&str>) -> Result<Option<&PackageEntry>> {
+        let req = version.map(parse_requirement).transpose()?;
+        Ok(self
+            .packages
+            .iter()
+            .filter(|entry| entry.name == name)
+            .filter_map(|entry| Some((entry.semver().ok()?, entry)))
+            .filter(|(version, _)| match &req {
+                Some(req) => req.matches(version),
+                None => version.pre.is_empty(),
+            })
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, entry)| entry))
+    }
+}
+
+/// Fetches package indexes and installs packages from them.
+#[derive(Debug, Clone)]
+pub struct PackageClient {
+    http: reqwest::Client,
+    index_url: Url,
+}
+
+impl PackageClient {
+    /// Create a client for an index at an `http`, `https` or `file` URL.
+    pub fn new(index_url: &str) -> Result<Self> {
+        let index_url = Url::parse(index_url).map_err(|e| {
+            SkillFileError::Package(format!("Invalid index URL '{}': {}", index_url, e))
+        })?;
+        if !matches!(index_url.scheme(), "http" | "https" | "file") {
+            return Err(SkillFileError::Package(format!(
+                "Unsupported index URL '{}': expected http, https or file",
+                index_url
+            )));
+        }
+        Ok(Self {
+            http: reqwest::Client::new(),
+            index_url,
+        })
+    }
+
+    /// URL of the index.
+    pub fn index_url(&self) -> &str {
+        self.index_url.as_str()
+    }
+
+    /// Download and parse the index.
+    pub async fn fetch_index(&self) -> Result<PackageIndex> {
+        let bytes = self.download(&self.index_url).await?;
+        PackageIndex::parse(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// Install a package into `skills_dir/<name>`.
+    ///
+    /// All files are downloaded and verified before the package directory
+    /// is touched, so a failed install leaves any existing version intact.
+    /// An existing directory is only replaced when `force` is set or it was
+    /// installed from a package (i.e. has a [`PACKAGE_MANIFEST`]).
+    pub async fn install(
+        &self,
+        entry: &PackageEntry,
+        skills_dir: &Path,
+        force: bool,
+    ) -> Result<InstalledPackage> {
+        validate_entry(entry)?;
+        let target = skills_dir.join(&entry.name);
+        if target.exists() && !force && !target.join(PACKAGE_MANIFEST).exists() {
+            return Err(SkillFileError::Package(format!(
+                "{} already exists and was not installed from a package; use force to replace it",
+                target.display()
+            )));
+        }
+
+        let mut downloads = Vec::with_capacity(entry.files.len());
+        for file in &entry.files {
+            let url = self.file_url(file)?;
+            let bytes = self.download(&url).await?;
+            let actual = sha256_hex(&bytes);
+            if !actual.eq_ignore_ascii_case(file.sha256.trim()) {
+                return Err(SkillFileError::ChecksumMismatch {
+                    path: file.path.clone(),
+                    expected: file.sha256.clone(),
+                    actual,
+                });
+            }
+            downloads.push((file, bytes));
+        }
+
+        let installed = InstalledPackage {
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            index: self.index_url.to_string(),
+            files: entry
+                .files
+                .iter()
+                .map(|file| InstalledFile {
+                    path: file.path.clone(),
+                    sha256: file.sha256.trim().to_lowercase(),
+                })
+                .collect(),
+        };
+
+        // Write to a staging directory and swap it in at the end
+        let staging = skills_dir.join(format!(".{}.partial", entry.name));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        for (file, bytes) in &downloads {
+            let path = paths::join_relative(&staging, &file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, bytes)?;
+        }
+        std::fs::create_dir_all(&staging)?;
+        let manifest = serde_json::to_string_pretty(&installed)
+            .map_err(|e| SkillFileError::Package(e.to_string()))?;
+        std::fs::write(staging.join(PACKAGE_MANIFEST), manifest)?;
+
+        if target.exists() {
+            std::fs::remove_dir_all(&target)?;
+        }
+        std::fs::rename(&staging, &target)?;
+        Ok(installed)
+    }
+
+    /// URL of a package file, which must use the scheme of the index so a
+    /// remote index can't have local files read
+    fn file_url(&self, file: &PackageFile) -> Result<Url> {
+        let url = self
+            .index_url
+            .join(&file.url)
+            .map_err(|e| SkillFileError::Package(format!("Invalid URL '{}': {}", file.url, e)))?;
+        if url.scheme() != self.index_url.scheme() {
+            return Err(SkillFileError::Package(format!(
+                "File URL '{}' of {} doesn't use the {} scheme of the index",
+                file.url,
+                file.path,
+                self.index_url.scheme()
+            )));
+        }
+        Ok(url)
+    }
+
+    async fn download(&self, url: &Url) -> Result<Vec<u8>> {
+        if url.scheme() == "file" {
+            if self.index_url.scheme() != "file" {
+                return Err(SkillFileError::Package(format!(
+                    "Refusing to read {} for a remote index",
+                    url
+                )));
+            }
+            let path = url
+                .to_file_path()
+                .map_err(|_| SkillFileError::InvalidPath(url.to_string()))?;
+            return Ok(tokio::fs::read(&path).await?);
+        }
+
+        let response = self
+            .http
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SkillFileError::Package(format!("Failed to download {}: {}", url, e)))?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| SkillFileError::Package(format!("Failed to download {}: {}", url, e)))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Read the manifest of a package installed at `dir`, if it has one.
+pub fn read_installed(dir: &Path) -> Result<Option<InstalledPackage>> {
+    let path = dir.join(PACKAGE_MANIFEST);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| SkillFileError::Package(format!("Invalid manifest {}: {}", path.display(), e)))
+}
+
+/// All packages installed in a skills directory, sorted by name.
+///
+/// Skills that weren't installed from a package are skipped.
+pub fn installed_packages(skills_dir: &Path) -> Result<Vec<InstalledPackage>> {
+    let entries = match std::fs::read_dir(skills_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut packages = Vec::new();
+    for entry in entries.flatten() {
+        if entry.path().is_dir() {
+            if let Some(package) = read_installed(&entry.path())? {
+                packages.push(package);
+            }
+        }
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+/// Installed packages with a newer stable version in the index.
+pub fn check_updates(index: &PackageIndex, installed: &[InstalledPackage]) -> Vec<PackageUpdate> {
+    installed
+        .iter()
+        .filter_map(|package| {
+            let current = Version::parse(&package.version).ok()?;
+            let latest = index.find(&package.name, None).ok()??.semver().ok()?;
+            (latest > current).then(|| PackageUpdate {
+                name: package.name.clone(),
+                installed: current,
+                available: latest,
+            })
+        })
+        .collect()
+}
+
+/// Parse a version argument, where a bare version means exactly that version.
+fn parse_requirement(version: &str) -> Result<VersionReq> {
+    let req = match Version::parse(version) {
+        Ok(exact) => VersionReq::parse(&format!("={}", exact)),
+        Err(_) => VersionReq::parse(version),
+    };
+    req.map_err(|e| SkillFileError::Package(format!("Invalid version '{}': {}", version, e)))
+}
+
+/// Check that a package can be installed safely: a plain directory name, a
+/// skill definition, and file paths that stay inside the package.
+fn validate_entry(entry: &PackageEntry) -> Result<()> {
+    let invalid = |reason: String| {
+        Err(SkillFileError::Package(format!(
+            "Invalid package '{}': {}",
+            entry.name, reason
+        )))
+    };
+
+    if entry.name.is_empty()
+        || entry.name.starts_with('.')
+        || !entry
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return invalid("names may only contain letters, digits, '-', '_' and '.'".to_string());
+    }
+
+    for file in &entry.files {
+        let path = Path::new(&file.path);
+        let escapes = file.path.contains('\\')
+            || path
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_)));
+        if file.path.is_empty() || escapes || file.path == PACKAGE_MANIFEST {
+            return invalid(format!("file path '{}' is not allowed", file.path));
+        }
+    }
+
+    let has_definition = entry.files.iter().any(|file| {
+        matches!(
+            file.path.as_str(),
+            paths::SKILL_FILE_NAME | "skill.yaml" | "skill.yml"
+        )
+    });
+    if !has_definition {
+        return invalid("no SKILL.md or skill.yaml".to_string());
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Default location of installed packages in a workspace.
+pub fn workspace_skills_dir(root: impl AsRef<Path>) -> PathBuf {
+    paths::thulp_dir(root).join("skills")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SKILL: &str = "---\nname: greet\ndescription: Say hello\n---\nSay hello.\n";
+
+    /// Write an index with the given versions of `greet` and return its URL
+    fn publish(dir: &Path, versions: &[&str]) -> String {
+        let mut packages = Vec::new();
+        for version in versions {
+            let file = dir.join(format!("greet-{}.md", version));
+            std::fs::write(&file, format!("{}v{}\n", SKILL, version)).unwrap();
+            packages.push(serde_json::json!({
+                "name": "greet",
+                "version": version,
+                "files": [
+                    {
+                        "path": "SKILL.md",
+                        "url": format!("greet-{}.md", version),
+                        "sha256": sha256_hex(format!("{}v{}\n", SKILL, version).as_bytes()),
+                    }
+                ],
+            }));
+        }
+        let index = dir.join("index.json");
+        std::fs::write(
+            &index,
+            serde_json::json!({ "packages": packages }).to_string(),
+        )
+        .unwrap();
+        Url::from_file_path(index).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_find_versions() {
+        let index = PackageIndex::parse(
+            r#"{"packages": [
+                {"name": "a", "version": "1.0.0", "files": []},
+                {"name": "a", "version": "1.4.0", "files": []},
+                {"name": "a", "version": "2.0.0-beta.1", "files": []},
+                {"name": "a", "version": "not semver", "files": []},
+                {"name": "b", "version": "3.0.0", "files": []}
+            ]}"#,
+        )
+        .unwrap();
+
+        let version = |req| index.find("a", req).unwrap().map(|e| e.version.as_str());
+        assert_eq!(version(None), Some("1.4.0"));
+        assert_eq!(version(Some("1.0.0")), Some("1.0.0"));
+        assert_eq!(version(Some("^1.1")), Some("1.4.0"));
+        assert_eq!(version(Some(">=2.0.0-beta")), Some("2.0.0-beta.1"));
+        assert_eq!(version(Some("^5")), None);
+        assert!(index.find("a", Some("nonsense")).is_err());
+        assert!(index.find("missing", None).unwrap().is_none());
+        assert!(PackageIndex::parse("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_install_and_check_updates() {
+        let registry = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let skills_dir = workspace_skills_dir(workspace.path());
+
+        let client = PackageClient::new(&publish(registry.path(), &["1.0.0"])).unwrap();
+        let index = client.fetch_index().await.unwrap();
+        let entry = index.find("greet", None).unwrap().unwrap();
+        let installed = client.install(entry, &skills_dir, false).await.unwrap();
+
+        assert_eq!(installed.version, "1.0.0");
+        let skill = std::fs::read_to_string(skills_dir.join("greet").join("SKILL.md")).unwrap();
+        assert!(skill.ends_with("v1.0.0\n"));
+        assert_eq!(
+            installed_packages(&skills_dir).unwrap(),
+            vec![installed.clone()]
+        );
+        assert!(check_updates(&index, std::slice::from_ref(&installed)).is_empty());
+
+        // A new release shows up as an update and replaces the old files
+        let client = PackageClient::new(&publish(registry.path(), &["1.0.0", "1.1.0"])).unwrap();
+        let index = client.fetch_index().await.unwrap();
+        let updates = check_updates(&index, &[installed]);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].available, Version::new(1, 1, 0));
+
+        let entry = index.find("greet", None).unwrap().unwrap();
+        client.install(entry, &skills_dir, false).await.unwrap();
+        let skill = std::fs::read_to_string(skills_dir.join("greet").join("SKILL.md")).unwrap();
+        assert!(skill.ends_with("v1.1.0\n"));
+        assert!(!skills_dir.join(".greet.partial").exists());
+    }
+
+    #[tokio::test]
+    async fn test_install_rejects_bad_packages() {
+        let registry = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let skills_dir = workspace_skills_dir(workspace.path());
+        let client = PackageClient::new(&publish(registry.path(), &["1.0.0"])).unwrap();
+        let index = client.fetch_index().await.unwrap();
+        let entry = index.find("greet", None).unwrap().unwrap();
+
+        // Tampered file
+        let mut tampered = entry.clone();
+        tampered.files[0].sha256 = sha256_hex(b"something else");
+        assert!(matches!(
+            client.install(&tampered, &skills_dir, false).await,
+            Err(SkillFileError::ChecksumMismatch { .. })
+        ));
+        assert!(!skills_dir.join("greet").exists());
+
+        // Paths escaping the package directory
+        let mut escaping = entry.clone();
+        escaping.files.push(PackageFile {
+            path: "../evil.sh".to_string(),
+            ..entry.files[0].clone()
+        });
+        assert!(client.install(&escaping, &skills_dir, false).await.is_err());
+
+        let mut renamed = entry.clone();
+        renamed.name = "../greet".to_string();
+        assert!(client.install(&renamed, &skills_dir, false).await.is_err());
+
+        // Hand-written skills are only replaced with force
+        std::fs::create_dir_all(skills_dir.join("greet")).unwrap();
+        assert!(client.install(entry, &skills_dir, false).await.is_err());
+        assert!(client.install(entry, &skills_dir, true).await.is_ok());
+
+        assert!(PackageClient::new("ftp://example.com/index.json").is_err());
+
+        // Files must use the scheme of the index
+        let mut remote_file = entry.clone();
+        remote_file.files[0].url = "https://example.com/greet.md".to_string();
+        assert!(client
+            .install(&remote_file, &skills_dir, true)
+            .await
+            .is_err());
+
+        let remote = PackageClient::new("https://example.com/skills/index.json").unwrap();
+        let mut local_file = entry.clone();
+        local_file.files[0].url = Url::from_file_path(registry.path().join("greet-1.0.0.md"))
+            .unwrap()
+            .to_string();
+        let err = remote
+            .install(&local_file, &skills_dir, true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("scheme of the index"), "{}", err);
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::error::{Result, SkillFileError};
 use crate::frontmatter::SkillFrontmatter;
+use crate::paths;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -137,7 +138,7 @@ impl SkillFile {
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
-            if path.is_file() && path.file_name() != Some(std::ffi::OsStr::new("SKILL.md")) {
+            if path.is_file() && !paths::is_skill_file(path, paths::CASE_INSENSITIVE_DEFAULT) {
                 let name = path
                     .file_name()
                     .and_then(|n| n.to_str())
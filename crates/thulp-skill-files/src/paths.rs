@@ -0,0 +1,137 @@
+//! Platform-aware path helpers.
+//!
+//! Skill discovery touches user-supplied paths (`~/skills`), the home
+//! directory and file names whose case may not be preserved on the host file
+//! system. These helpers keep that logic in one place so Windows and macOS
+//! resolve `.thulp` and `.claude` directories the same way Linux does:
+//!
+//! - `~`, `~/...` and `~\...` expand to the home directory
+//! - Relative locations are built one component at a time, so paths never
+//!   mix `/` and `\` separators
+//! - File and skill names can be compared case-insensitively where the file
+//!   system is case-insensitive by default
+
+use std::path::{Path, PathBuf};
+
+/// Name of the skill definition file inside a skill directory.
+pub const SKILL_FILE_NAME: &str = "SKILL.md";
+
+/// Whether names are compared case-insensitively by default on this
+/// platform (Windows and macOS file systems are case-insensitive by default).
+pub const CASE_INSENSITIVE_DEFAULT: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Get the current user's home directory.
+///
+/// `HOME` is honoured on every platform (including Windows, where shells
+/// such as Git Bash set it), falling back to the platform's notion of the
+/// profile directory (`USERPROFILE` / known folder on Windows).
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
+}
+
+/// Expand a leading `~` to the home directory.
+///
+/// Both `~/` and `~\` are accepted as separators. Paths without a leading
+/// `~`, and `~user` forms, are returned unchanged.
+pub fn expand_tilde(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+
+    let rest = if text == "~" {
+        ""
+    } else if let Some(rest) = text.strip_prefix("~/").or_else(|| text.strip_prefix("~\\")) {
+        rest
+    } else {
+        return path.to_path_buf();
+    };
+
+    match home_dir() {
+        Some(home) => join_relative(home, rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Join a relative path written with either `/` or `\` separators onto
+/// `base`, one component at a time.
+pub fn join_relative(base: impl Into<PathBuf>, relative: &str) -> PathBuf {
+    relative
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != ".")
+        .fold(base.into(), |path, component| path.join(component))
+}
+
+/// The `.thulp` directory under `root`.
+pub fn thulp_dir(root: impl AsRef<Path>) -> PathBuf {
+    root.as_ref().join(".thulp")
+}
+
+/// The user's global `~/.thulp` directory.
+pub fn global_thulp_dir() -> Option<PathBuf> {
+    home_dir().map(thulp_dir)
+}
+
+/// Compare two names, optionally ignoring case.
+pub fn names_match(a: &str, b: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.to_lowercase() == b.to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+/// Check whether `path` names a `SKILL.md` file.
+pub fn is_skill_file(path: &Path, case_insensitive: bool) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| names_match(name, SKILL_FILE_NAME, case_insensitive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tilde() {
+        let Some(home) = home_dir() else {
+            return;
+        };
+
+        assert_eq!(expand_tilde("~"), home);
+        assert_eq!(
+            expand_tilde("~/.claude/skills"),
+            home.join(".claude").join("skills")
+        );
+        assert_eq!(
+            expand_tilde("~\\.claude\\skills"),
+            home.join(".claude").join("skills")
+        );
+        assert_eq!(expand_tilde("~other/x"), PathBuf::from("~other/x"));
+        assert_eq!(expand_tilde("skills"), PathBuf::from("skills"));
+    }
+
+    #[test]
+    fn test_join_relative() {
+        let base = PathBuf::from("root");
+        let expected = base.join(".thulp").join("skills");
+        assert_eq!(join_relative(&base, ".thulp/skills"), expected);
+        assert_eq!(join_relative(&base, ".thulp\\skills"), expected);
+        assert_eq!(join_relative(&base, "./.thulp//skills/"), expected);
+        assert_eq!(thulp_dir(&base), base.join(".thulp"));
+    }
+
+    #[test]
+    fn test_name_matching() {
+        assert!(names_match("Deploy", "deploy", true));
+        assert!(!names_match("Deploy", "deploy", false));
+
+        assert!(is_skill_file(Path::new("a/SKILL.md"), false));
+        assert!(is_skill_file(Path::new("a/skill.md"), true));
+        assert!(!is_skill_file(Path::new("a/skill.md"), false));
+        assert!(!is_skill_file(Path::new("a/README.md"), true));
+    }
+}
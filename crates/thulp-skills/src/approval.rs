@@ -0,0 +1,238 @@
+//! Approval gates for dangerous steps.
+//!
+//! A step with [`requires_approval`](crate::SkillStep::requires_approval)
+//! set pauses the run once its arguments are rendered and asks the
+//! [`ApprovalHandler`] set with [`ExecutionContext::with_approval_handler`]
+//! whether it may run: a prompt on a terminal, a webhook, or a fixed answer
+//! such as [`AutoDeny`].
+//!
+//! ```yaml
+//! - name: drop
+//!   tool: db.drop_table
+//!   requires_approval: true
+//!   arguments:
+//!     table: "{{table}}"
+//! ```
+//!
+//! An approved step runs as usual. A denied one fails with
+//! [`SkillError::ApprovalDenied`], which aborts the run unless the step
+//! continues on error. Without a handler every gated step is denied.
+//!
+//! Each decision is passed to
+//! [`ExecutionHooks::on_approval`](crate::ExecutionHooks::on_approval) and
+//! kept in [`ExecutionContext::approvals`], for recording in a session.
+//! The request carries the step's arguments with resolved secrets masked; a
+//! `for_each` step is approved once for all its items, and retries of an
+//! approved step are not asked again. Time spent waiting counts against the
+//! skill timeout.
+//!
+//! [`ExecutionContext::with_approval_handler`]: crate::ExecutionContext::with_approval_handler
+//! [`ExecutionContext::approvals`]: crate::ExecutionContext::approvals
+//! [`SkillError::ApprovalDenied`]: crate::SkillError::ApprovalDenied
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// A step waiting for approval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Step name
+    pub step: String,
+
+    /// Tool the step calls, empty for data and nested skill steps
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tool: String,
+
+    /// Skill the step runs, for nested skill steps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+
+    /// Rendered arguments with secrets masked, an array for `for_each`
+    /// steps
+    pub arguments: Value,
+
+    /// How many parent skills run the skill the step belongs to
+    #[serde(default)]
+    pub depth: usize,
+}
+
+/// Answer to an [`ApprovalRequest`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Run the step.
+    Approved,
+
+    /// Fail the step with the given reason.
+    Denied { reason: String },
+}
+
+impl ApprovalDecision {
+    /// Deny with `reason`.
+    pub fn denied(reason: impl Into<String>) -> Self {
+        Self::Denied {
+            reason: reason.into(),
+        }
+    }
+
+    /// Whether the step may run.
+    pub fn is_approved(&self) -> bool {
+        matches!(self, Self::Approved)
+    }
+}
+
+/// A decision taken during a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    /// What was asked
+    pub request: ApprovalRequest,
+
+    /// What was answered
+    pub decision: ApprovalDecision,
+
+    /// Milliseconds spent waiting for the answer
+    pub waited_ms: u64,
+}
+
+/// Decides whether gated steps may run.
+#[async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    /// Approve or deny a step. Runs are paused until this returns, or until
+    /// they are cancelled.
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision;
+}
+
+/// Approves every step, for trusted automation and tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoApprove;
+
+#[async_trait]
+impl ApprovalHandler for AutoApprove {
+    async fn approve(&self, _request: &ApprovalRequest) -> ApprovalDecision {
+        ApprovalDecision::Approved
+    }
+}
+
+/// Denies every step, for runs nobody can answer for.
+#[derive(Debug, Clone, Default)]
+pub struct AutoDeny {
+    reason: Option<String>,
+}
+
+impl AutoDeny {
+    /// Deny with a generic reason.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deny with `reason`.
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self {
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl ApprovalHandler for AutoDeny {
+    async fn approve(&self, _request: &ApprovalRequest) -> ApprovalDecision {
+        ApprovalDecision::denied(
+            self.reason
+                .as_deref()
+                .unwrap_or("no one is available to approve it"),
+        )
+    }
+}
+
+/// The approval handler of a run and the decisions taken so far
+#[derive(Clone, Default)]
+pub(crate) struct Approvals {
+    handler: Option<Arc<dyn ApprovalHandler>>,
+    records: Arc<Mutex<Vec<ApprovalRecord>>>,
+}
+
+impl std::fmt::Debug for Approvals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Approvals")
+            .field("handler", &self.handler.is_some())
+            .field("records", &self.records().len())
+            .finish()
+    }
+}
+
+impl Approvals {
+    pub(crate) fn with_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Ask the handler, denying when there is none
+    pub(crate) async fn decide(&self, request: &ApprovalRequest) -> ApprovalDecision {
+        match &self.handler {
+            Some(handler) => handler.approve(request).await,
+            None => ApprovalDecision::denied("no approval handler is configured"),
+        }
+    }
+
+    pub(crate) fn record(&self, record: ApprovalRecord) {
+        self.records.lock().unwrap().push(record);
+    }
+
+    pub(crate) fn records(&self) -> Vec<ApprovalRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest {
+            step: "drop".to_string(),
+            tool: "db.drop_table".to_string(),
+            skill: None,
+            arguments: json!({"table": "users"}),
+            depth: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixed_handlers() {
+        assert!(AutoApprove.approve(&request()).await.is_approved());
+        assert_eq!(
+            AutoDeny::with_reason("frozen").approve(&request()).await,
+            ApprovalDecision::denied("frozen")
+        );
+
+        let approvals = Approvals::default();
+        assert!(!approvals.decide(&request()).await.is_approved());
+        let approvals = approvals.with_handler(Arc::new(AutoApprove));
+        assert!(approvals.decide(&request()).await.is_approved());
+    }
+
+    #[test]
+    fn test_record_serialization() {
+        let record = ApprovalRecord {
+            request: request(),
+            decision: ApprovalDecision::denied("not during business hours"),
+            waited_ms: 1200,
+        };
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            json!({
+                "request": {
+                    "step": "drop",
+                    "tool": "db.drop_table",
+                    "arguments": {"table": "users"},
+                    "depth": 0
+                },
+                "decision": {"decision": "denied", "reason": "not during business hours"},
+                "waited_ms": 1200
+            })
+        );
+    }
+}
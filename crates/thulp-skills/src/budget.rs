@@ -0,0 +1,204 @@
+//! Budgets on tool calls, time and cost.
+//!
+//! An [`ExecutionConfig`] can cap how many tool calls a run makes
+//! ([`max_tool_calls`](ExecutionConfig::max_tool_calls)), how long it spends
+//! executing ([`max_total_duration`](ExecutionConfig::max_total_duration)),
+//! and what it spends in cost units, with a weight per tool
+//! ([`tool_costs`](ExecutionConfig::tool_costs)) checked against
+//! [`max_cost`](ExecutionConfig::max_cost).
+//!
+//! Usage is tracked in the [`ExecutionContext`](crate::ExecutionContext) and
+//! adds up over every skill run with the same context, so a budget can span
+//! a whole agent session. Every attempt counts, retries included. A call that
+//! would go over a limit is not made; the run aborts with
+//! [`SkillError::BudgetExceeded`], regardless of `continue_on_error`.
+//! Successful runs report their usage in [`SkillResult::usage`](crate::SkillResult::usage).
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use thulp_skills::ExecutionConfig;
+//!
+//! let config = ExecutionConfig::new()
+//!     .with_max_tool_calls(20)
+//!     .with_max_total_duration(Duration::from_secs(120))
+//!     .with_tool_cost("web_search", 5.0)
+//!     .with_max_cost(50.0);
+//!
+//! assert_eq!(config.tool_cost("web_search"), 5.0);
+//! assert_eq!(config.tool_cost("read_file"), 1.0);
+//! ```
+
+use crate::{ExecutionConfig, Result, SkillError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Resources used by skill runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionUsage {
+    /// Tool calls made, retries included
+    pub tool_calls: usize,
+
+    /// Sum of the cost weights of the calls made
+    pub cost: f64,
+
+    /// Time spent executing, in milliseconds
+    pub duration_ms: u64,
+
+    /// Tool calls made per tool
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub calls_by_tool: BTreeMap<String, usize>,
+}
+
+/// Usage accumulated by an execution context
+#[derive(Debug, Default)]
+pub(crate) struct UsageTracker {
+    state: Mutex<UsageState>,
+}
+
+#[derive(Debug, Default)]
+struct UsageState {
+    tool_calls: usize,
+    cost: f64,
+    calls_by_tool: BTreeMap<String, usize>,
+    /// Time spent in runs that have finished
+    finished: Duration,
+    /// Start of the run in progress, and how many nested runs share it
+    running: Option<(Instant, usize)>,
+}
+
+impl UsageState {
+    fn elapsed(&self) -> Duration {
+        self.finished
+            + self
+                .running
+                .map_or(Duration::ZERO, |(start, _)| start.elapsed())
+    }
+}
+
+impl UsageTracker {
+    fn lock(&self) -> std::sync::MutexGuard<'_, UsageState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start timing a run.
+    pub(crate) fn begin(&self) {
+        let mut state = self.lock();
+        state.running = match state.running {
+            Some((start, depth)) => Some((start, depth + 1)),
+            None => Some((Instant::now(), 1)),
+        };
+    }
+
+    /// Stop timing the run started by the matching [`begin`](Self::begin).
+    pub(crate) fn end(&self) {
+        let mut state = self.lock();
+        match state.running {
+            Some((start, depth)) if depth > 1 => state.running = Some((start, depth - 1)),
+            Some((start, _)) => {
+                state.finished += start.elapsed();
+                state.running = None;
+            }
+            None => {}
+        }
+    }
+
+    /// Record a call to `tool`, failing without recording it if that would
+    /// go over the config's budget.
+    pub(crate) fn charge(&self, tool: &str, config: &ExecutionConfig) -> Result<()> {
+        let mut state = self.lock();
+        if let Some(max) = config.max_tool_calls {
+            if state.tool_calls >= max {
+                return Err(SkillError::BudgetExceeded(format!(
+                    "limit of {} tool calls reached",
+                    max
+                )));
+            }
+        }
+        if let Some(max) = config.max_total_duration {
+            let elapsed = state.elapsed();
+            if elapsed >= max {
+                return Err(SkillError::BudgetExceeded(format!(
+                    "time limit of {:?} reached after {:?}",
+                    max, elapsed
+                )));
+            }
+        }
+        let cost = config.tool_cost(tool);
+        if let Some(max) = config.max_cost {
+            if state.cost + cost > max {
+                return Err(SkillError::BudgetExceeded(format!(
+                    "calling '{}' (cost {}) would exceed the cost limit of {} with {} spent",
+                    tool, cost, max, state.cost
+                )));
+            }
+        }
+
+        state.tool_calls += 1;
+        state.cost += cost;
+        *state.calls_by_tool.entry(tool.to_string()).or_default() += 1;
+        Ok(())
+    }
+
+    /// Usage so far, including time in the run in progress.
+    pub(crate) fn usage(&self) -> ExecutionUsage {
+        let state = self.lock();
+        ExecutionUsage {
+            tool_calls: state.tool_calls,
+            cost: state.cost,
+            duration_ms: state.elapsed().as_millis() as u64,
+            calls_by_tool: state.calls_by_tool.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_and_cost_limits() {
+        let config = ExecutionConfig::new()
+            .with_max_tool_calls(3)
+            .with_tool_cost("search", 2.5)
+            .with_max_cost(6.0);
+        let tracker = UsageTracker::default();
+
+        tracker.charge("search", &config).unwrap();
+        tracker.charge("search", &config).unwrap();
+        let err = tracker.charge("search", &config).unwrap_err();
+        assert!(err.to_string().contains("cost limit of 6"), "{}", err);
+
+        tracker.charge("read", &config).unwrap();
+        let err = tracker.charge("read", &config).unwrap_err();
+        assert!(err.to_string().contains("3 tool calls"), "{}", err);
+
+        let usage = tracker.usage();
+        assert_eq!(usage.tool_calls, 3);
+        assert_eq!(usage.cost, 6.0);
+        assert_eq!(usage.calls_by_tool["search"], 2);
+        assert_eq!(usage.calls_by_tool["read"], 1);
+    }
+
+    #[test]
+    fn test_duration_counts_only_runs() {
+        let config = ExecutionConfig::new().with_max_total_duration(Duration::from_millis(30));
+        let tracker = UsageTracker::default();
+
+        // Time outside a run doesn't count
+        std::thread::sleep(Duration::from_millis(40));
+        tracker.charge("a", &config).unwrap();
+
+        tracker.begin();
+        tracker.begin();
+        tracker.end();
+        std::thread::sleep(Duration::from_millis(40));
+        tracker.end();
+        assert!(tracker.usage().duration_ms >= 40);
+        assert!(matches!(
+            tracker.charge("a", &config),
+            Err(SkillError::BudgetExceeded(_))
+        ));
+    }
+}
@@ -0,0 +1,616 @@
+//! Step condition and computed input expressions.
+//!
+//! A [`SkillStep`](crate::SkillStep) can carry a `condition` that decides
+//! whether it runs, based on skill inputs and the outputs of earlier steps,
+//! and a [`Skill`](crate::Skill) can derive inputs from other inputs. Both
+//! use the same small expression language:
+//!
+//! - Variable references: `{{search}}`, `{{search.count}}`, `{{search.items.0.id}}`,
+//!   `{{search.items.length}}`
+//! - Literals: numbers, `'single'` or `"double"` quoted strings, `true`, `false`, `null`
+//! - Arithmetic: `+`, `-`, `*`, `/` on numbers; `+` concatenates when either
+//!   side is a string
+//! - Comparisons: `==`, `!=`, `>`, `>=`, `<`, `<=`
+//! - Logic: `&&`, `||`, `!` and parentheses
+//!
+//! A bare operand is tested for truthiness: `null`, `false`, `0`, and empty
+//! strings, arrays or objects are false. Missing variables resolve to `null`,
+//! as do arithmetic on non-numbers and division by zero.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use thulp_skills::evaluate_condition;
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert("search".to_string(), json!({"count": 3, "items": [1, 2, 3]}));
+//!
+//! assert!(evaluate_condition("{{search.count}} > 0", &vars).unwrap());
+//! assert!(!evaluate_condition("{{search.items.length}} == 0", &vars).unwrap());
+//! assert!(!evaluate_condition("{{missing}}", &vars).unwrap());
+//! ```
+
+use crate::{Result, SkillError};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Evaluate a condition expression against the given variables.
+///
+/// Returns [`SkillError::InvalidConfig`] if the expression cannot be parsed.
+pub fn evaluate_condition(expression: &str, variables: &HashMap<String, Value>) -> Result<bool> {
+    let expr = parse(expression).map_err(|e| {
+        SkillError::InvalidConfig(format!("Invalid condition '{}': {}", expression, e))
+    })?;
+    Ok(is_truthy(&expr.eval(variables)))
+}
+
+/// Evaluate an expression against the given variables and return its value.
+///
+/// Returns [`SkillError::InvalidConfig`] if the expression cannot be parsed.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use serde_json::json;
+/// use thulp_skills::evaluate_expression;
+///
+/// let mut vars = HashMap::new();
+/// vars.insert("page".to_string(), json!(3));
+/// vars.insert("user".to_string(), json!({"first": "Ada", "last": "Lovelace"}));
+///
+/// assert_eq!(evaluate_expression("({{page}} - 1) * 20", &vars).unwrap(), json!(40));
+/// assert_eq!(
+///     evaluate_expression("{{user.first}} + ' ' + {{user.last}}", &vars).unwrap(),
+///     json!("Ada Lovelace")
+/// );
+/// ```
+pub fn evaluate_expression(expression: &str, variables: &HashMap<String, Value>) -> Result<Value> {
+    let expr = parse(expression).map_err(|e| {
+        SkillError::InvalidConfig(format!("Invalid expression '{}': {}", expression, e))
+    })?;
+    Ok(expr.eval(variables))
+}
+
+/// Check whether a JSON value is considered true in a condition
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// Resolve a variable path such as `search.items.0.id` or
+/// `search.items[0]["id"]`.
+///
+/// An exact variable name match wins over path traversal, and a trailing
+/// `length` segment yields the size of an array, object or string.
+pub(crate) fn resolve_path(path: &str, variables: &HashMap<String, Value>) -> Option<Value> {
+    if let Some(value) = variables.get(path) {
+        return Some(value.clone());
+    }
+
+    let segments = path_segments(path)?;
+    let mut segments = segments.iter().map(String::as_str);
+    let mut current = segments.next().and_then(|root| variables.get(root))?;
+
+    for segment in segments {
+        let next = match current {
+            Value::Object(obj) => obj.get(segment),
+            Value::Array(arr) => segment.parse::<usize>().ok().and_then(|i| arr.get(i)),
+            _ => None,
+        };
+        current = match next {
+            Some(value) => value,
+            None if segment == "length" => {
+                return match current {
+                    Value::Array(arr) => Some(Value::from(arr.len())),
+                    Value::Object(obj) => Some(Value::from(obj.len())),
+                    Value::String(s) => Some(Value::from(s.chars().count())),
+                    _ => None,
+                };
+            }
+            None => return None,
+        };
+    }
+
+    Some(current.clone())
+}
+
+/// Split a path into segments; `[0]` and `["key"]` are segments of their own
+fn path_segments(path: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => segments.push(std::mem::take(&mut current)),
+            '[' => {
+                if !current.is_empty() {
+                    segments.push(std::mem::take(&mut current));
+                }
+                let mut key: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                key = key.trim().to_string();
+                for quote in ['"', '\''] {
+                    if key.len() >= 2 && key.starts_with(quote) && key.ends_with(quote) {
+                        key = key[1..key.len() - 1].to_string();
+                    }
+                }
+                segments.push(key);
+                // A `.` may follow a bracket; skip the empty segment it leaves
+                if chars.clone().next() == Some('.') {
+                    chars.next();
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() || segments.is_empty() {
+        segments.push(current);
+    }
+    Some(segments)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOp {
+    fn apply(self, left: &Value, right: &Value) -> bool {
+        let ordering = match (left, right) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+
+        match self {
+            CompareOp::Eq => ordering.map_or(left == right, |o| o == Ordering::Equal),
+            CompareOp::Ne => ordering.map_or(left != right, |o| o != Ordering::Equal),
+            CompareOp::Gt => ordering == Some(Ordering::Greater),
+            CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            CompareOp::Lt => ordering == Some(Ordering::Less),
+            CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl ArithOp {
+    fn apply(self, left: &Value, right: &Value) -> Value {
+        if self == ArithOp::Add && (left.is_string() || right.is_string()) {
+            return Value::String(format!("{}{}", display(left), display(right)));
+        }
+
+        let (Value::Number(a), Value::Number(b)) = (left, right) else {
+            return Value::Null;
+        };
+
+        if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+            let exact = match self {
+                ArithOp::Add => a.checked_add(b),
+                ArithOp::Sub => a.checked_sub(b),
+                ArithOp::Mul => a.checked_mul(b),
+                ArithOp::Div if a.checked_rem(b) == Some(0) => a.checked_div(b),
+                ArithOp::Div => None,
+            };
+            if let Some(result) = exact {
+                return Value::from(result);
+            }
+        }
+
+        let (Some(a), Some(b)) = (a.as_f64(), b.as_f64()) else {
+            return Value::Null;
+        };
+        let result = match self {
+            ArithOp::Add => a + b,
+            ArithOp::Sub => a - b,
+            ArithOp::Mul => a * b,
+            ArithOp::Div => a / b,
+        };
+        serde_json::Number::from_f64(result).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Render a value for string concatenation; strings are not quoted
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Var(String),
+    Literal(Value),
+    Arith(ArithOp),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Var(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    Arith(Box<Expr>, ArithOp, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, variables: &HashMap<String, Value>) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Var(path) => resolve_path(path, variables).unwrap_or(Value::Null),
+            Expr::Not(inner) => Value::Bool(!is_truthy(&inner.eval(variables))),
+            Expr::And(l, r) => {
+                Value::Bool(is_truthy(&l.eval(variables)) && is_truthy(&r.eval(variables)))
+            }
+            Expr::Or(l, r) => {
+                Value::Bool(is_truthy(&l.eval(variables)) || is_truthy(&r.eval(variables)))
+            }
+            Expr::Compare(l, op, r) => {
+                Value::Bool(op.apply(&l.eval(variables), &r.eval(variables)))
+            }
+            Expr::Arith(l, op, r) => op.apply(&l.eval(variables), &r.eval(variables)),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        // A '-' after an operand is subtraction, otherwise it starts a number
+        let after_operand = matches!(
+            tokens.last(),
+            Some(Token::Var(_) | Token::Literal(_) | Token::RParen)
+        );
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '{' if next == Some('{') => {
+                let start = i + 2;
+                let end = (start..chars.len().saturating_sub(1))
+                    .find(|&j| chars[j] == '}' && chars[j + 1] == '}')
+                    .ok_or("unterminated '{{'")?;
+                let path: String = chars[start..end].iter().collect();
+                let path = path.trim();
+                if path.is_empty() {
+                    return Err("empty variable reference".to_string());
+                }
+                tokens.push(Token::Var(path.to_string()));
+                i = end + 2;
+            }
+            '\'' | '"' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| chars[j] == c)
+                    .ok_or("unterminated string literal")?;
+                tokens.push(Token::Literal(Value::String(
+                    chars[i + 1..end].iter().collect(),
+                )));
+                i = end + 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Compare(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Compare(CompareOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '>' | '<' => {
+                let op = match (c, next == Some('=')) {
+                    ('>', true) => CompareOp::Ge,
+                    ('>', false) => CompareOp::Gt,
+                    ('<', true) => CompareOp::Le,
+                    _ => CompareOp::Lt,
+                };
+                tokens.push(Token::Compare(op));
+                i += if next == Some('=') { 2 } else { 1 };
+            }
+            '+' | '*' | '/' => {
+                tokens.push(Token::Arith(match c {
+                    '+' => ArithOp::Add,
+                    '*' => ArithOp::Mul,
+                    _ => ArithOp::Div,
+                }));
+                i += 1;
+            }
+            '-' if after_operand => {
+                tokens.push(Token::Arith(ArithOp::Sub));
+                i += 1;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number: Value = serde_json::from_str(&text)
+                    .map_err(|_| format!("invalid number '{}'", text))?;
+                tokens.push(Token::Literal(number));
+            }
+            c if c.is_alphabetic() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_alphanumeric() {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let literal = match word.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => return Err(format!("unexpected identifier '{}'", word)),
+                };
+                tokens.push(Token::Literal(literal));
+            }
+            _ => return Err(format!("unexpected character '{}'", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse(input: &str) -> std::result::Result<Expr, String> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err("empty expression".to_string());
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!("unexpected token {:?}", parser.tokens[parser.pos]));
+    }
+    Ok(expr)
+}
+
+/// Recursive descent parser; precedence from lowest: `||`, `&&`, `!`,
+/// comparison, `+`/`-`, `*`/`/`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> std::result::Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> std::result::Result<Expr, String> {
+        let left = self.parse_additive()?;
+        if let Some(Token::Compare(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let right = self.parse_additive()?;
+            return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(Token::Arith(op @ (ArithOp::Add | ArithOp::Sub))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            left = Expr::Arith(Box::new(left), op, Box::new(self.parse_multiplicative()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> std::result::Result<Expr, String> {
+        let mut left = self.parse_primary()?;
+        while let Some(Token::Arith(op @ (ArithOp::Mul | ArithOp::Div))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            left = Expr::Arith(Box::new(left), op, Box::new(self.parse_primary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> std::result::Result<Expr, String> {
+        match self.advance() {
+            Some(Token::Var(path)) => Ok(Expr::Var(path)),
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("expected ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected token {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> HashMap<String, Value> {
+        let mut vars = HashMap::new();
+        vars.insert(
+            "search".to_string(),
+            json!({"count": 2, "items": [{"id": "a"}, {"id": "b"}], "query": "rust"}),
+        );
+        vars.insert("empty".to_string(), json!([]));
+        vars.insert("enabled".to_string(), json!(true));
+        vars
+    }
+
+    fn eval(expr: &str) -> bool {
+        evaluate_condition(expr, &vars()).unwrap()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(eval("{{search.count}} > 0"));
+        assert!(eval("{{search.count}} >= 2"));
+        assert!(!eval("{{search.count}} < 2"));
+        assert!(eval("{{search.count}} <= 2.0"));
+        assert!(eval("{{search.count}} == 2"));
+        assert!(eval("{{search.count}} != -1"));
+        assert!(eval("{{search.query}} == 'rust'"));
+        assert!(eval("{{search.query}} != \"go\""));
+        assert!(eval("{{search.items.1.id}} == 'b'"));
+        assert!(eval("{{search.items.length}} == 2"));
+    }
+
+    #[test]
+    fn test_truthiness_and_logic() {
+        assert!(eval("{{enabled}}"));
+        assert!(!eval("{{empty}}"));
+        assert!(!eval("{{missing}}"));
+        assert!(eval("!{{missing.field}}"));
+        assert!(eval("{{missing}} == null"));
+        assert!(eval("{{enabled}} && {{search.count}} > 1"));
+        assert!(eval("{{empty}} || {{search.count}} > 1"));
+        assert!(!eval("!({{enabled}} || {{empty}})"));
+        assert!(eval("{{empty}} && false || true"));
+    }
+
+    #[test]
+    fn test_mismatched_types_do_not_order() {
+        assert!(!eval("{{search.query}} > 1"));
+        assert!(!eval("{{missing}} > 0"));
+        assert!(!eval("{{search.count}} == '2'"));
+    }
+
+    #[test]
+    fn test_arithmetic_and_concatenation() {
+        let value = |expr: &str| evaluate_expression(expr, &vars()).unwrap();
+
+        assert_eq!(value("{{search.count}} + 1"), json!(3));
+        assert_eq!(value("{{search.count}}-1"), json!(1));
+        assert_eq!(value("2 + 3 * 4"), json!(14));
+        assert_eq!(value("(2 + 3) * 4"), json!(20));
+        assert_eq!(value("7 / 2"), json!(3.5));
+        assert_eq!(value("6 / 2"), json!(3));
+        assert_eq!(value("1 / 0"), Value::Null);
+        assert_eq!(
+            value("{{search.query}} + '-' + {{search.count}}"),
+            json!("rust-2")
+        );
+        assert_eq!(value("{{enabled}} * 2"), Value::Null);
+        assert_eq!(value("{{search.items.0}}"), json!({"id": "a"}));
+        assert!(eval("{{search.count}} * 10 > 15"));
+        assert!(eval("-1 < {{search.count}} - 5 + 10"));
+
+        // i64::MIN / -1 overflows i64 and falls back to floats
+        let min = HashMap::from([("min".to_string(), json!(i64::MIN))]);
+        assert_eq!(
+            evaluate_expression("{{min}} / -1", &min).unwrap(),
+            json!(-(i64::MIN as f64))
+        );
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expr in [
+            "",
+            "{{search",
+            "{{search}} >",
+            "({{enabled}}",
+            "{{}}",
+            "maybe",
+            "1 = 1",
+            "1 +",
+            "* 2",
+        ] {
+            assert!(
+                matches!(
+                    evaluate_condition(expr, &vars()),
+                    Err(SkillError::InvalidConfig(_))
+                ),
+                "expected error for {:?}",
+                expr
+            );
+        }
+    }
+}
@@ -1,7 +1,13 @@
 //! Configuration types for skill execution.
 //!
 //! This module provides configuration for timeouts and retries during skill execution.
+//! A workspace can set its own defaults in the `settings` section of
+//! `.thulp/config.yaml`, see [`ExecutionSettings`].
 
+use crate::{Result, SkillError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 /// Configuration for execution timeouts.
@@ -195,6 +201,42 @@ impl RetryConfig {
     }
 }
 
+/// What to do with template placeholders that don't resolve.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemplateStrictness {
+    /// Leave the placeholder text in the arguments.
+    #[default]
+    PassThrough,
+
+    /// Leave the placeholder text and log a warning.
+    Warn,
+
+    /// Fail the step with [`SkillError::UnresolvedVariable`].
+    Error,
+}
+
+/// Which tool calls are passed to
+/// [`ExecutionHooks::on_confirm_call`](crate::ExecutionHooks::on_confirm_call)
+/// before they run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfirmationMode {
+    /// Run calls without asking.
+    #[default]
+    Off,
+
+    /// Ask for tools marked as
+    /// [`destructive`](thulp_core::ToolDefinition::destructive), or for
+    /// every tool if the transport can't list them.
+    Destructive,
+
+    /// Ask for every call.
+    All,
+}
+
+/// Nesting limit for skills run as steps when none is configured; deep
+/// enough for real compositions while stopping a skill that calls itself
+pub const DEFAULT_MAX_SKILL_DEPTH: usize = 8;
+
 /// Combined execution configuration.
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionConfig {
@@ -203,6 +245,36 @@ pub struct ExecutionConfig {
 
     /// Retry configuration.
     pub retry: RetryConfig,
+
+    /// Refuse to run tools marked as
+    /// [`destructive`](thulp_core::ToolDefinition::destructive).
+    pub read_only: bool,
+
+    /// Handling of unresolved placeholders in step arguments.
+    pub template_strictness: TemplateStrictness,
+
+    /// Directory that [`data_file`](crate::SkillStep::data_file) paths are
+    /// relative to, usually the workspace root.
+    pub data_dir: Option<PathBuf>,
+
+    /// Which tool calls need confirmation from the hooks.
+    pub confirmation: ConfirmationMode,
+
+    /// Most tool calls, retries included, before execution aborts.
+    pub max_tool_calls: Option<usize>,
+
+    /// Most time spent executing before execution aborts.
+    pub max_total_duration: Option<Duration>,
+
+    /// Cost weight of each tool; unlisted tools cost 1.
+    pub tool_costs: HashMap<String, f64>,
+
+    /// Most total cost before execution aborts.
+    pub max_cost: Option<f64>,
+
+    /// Most levels of skills running other skills as steps
+    /// (default: [`DEFAULT_MAX_SKILL_DEPTH`]).
+    pub max_skill_depth: Option<usize>,
 }
 
 impl ExecutionConfig {
@@ -222,6 +294,216 @@ impl ExecutionConfig {
         self.retry = config;
         self
     }
+
+    /// Enable or disable read-only mode.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set how unresolved template placeholders are handled.
+    pub fn with_template_strictness(mut self, strictness: TemplateStrictness) -> Self {
+        self.template_strictness = strictness;
+        self
+    }
+
+    /// Set the directory data files are loaded from.
+    pub fn with_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+
+    /// Set which tool calls need confirmation.
+    pub fn with_confirmation(mut self, mode: ConfirmationMode) -> Self {
+        self.confirmation = mode;
+        self
+    }
+
+    /// Limit the number of tool calls, see [`budget`](crate::budget).
+    pub fn with_max_tool_calls(mut self, max: usize) -> Self {
+        self.max_tool_calls = Some(max);
+        self
+    }
+
+    /// Limit the time spent executing, see [`budget`](crate::budget).
+    pub fn with_max_total_duration(mut self, duration: Duration) -> Self {
+        self.max_total_duration = Some(duration);
+        self
+    }
+
+    /// Set the cost weight of a tool.
+    pub fn with_tool_cost(mut self, tool: impl Into<String>, cost: f64) -> Self {
+        self.tool_costs.insert(tool.into(), cost);
+        self
+    }
+
+    /// Limit the total cost of tool calls, see [`budget`](crate::budget).
+    pub fn with_max_cost(mut self, max: f64) -> Self {
+        self.max_cost = Some(max);
+        self
+    }
+
+    /// Limit how deeply skills can run other skills as steps.
+    pub fn with_max_skill_depth(mut self, depth: usize) -> Self {
+        self.max_skill_depth = Some(depth);
+        self
+    }
+
+    /// Get the cost weight of a tool.
+    pub fn tool_cost(&self, tool: &str) -> f64 {
+        self.tool_costs.get(tool).copied().unwrap_or(1.0)
+    }
+
+    /// Get the nesting limit for skills run as steps.
+    pub fn skill_depth_limit(&self) -> usize {
+        self.max_skill_depth.unwrap_or(DEFAULT_MAX_SKILL_DEPTH)
+    }
+}
+
+/// Workspace config file, relative to the workspace root.
+pub const WORKSPACE_CONFIG_PATH: &str = ".thulp/config.yaml";
+
+/// Execution defaults from the `settings` section of a workspace config.
+///
+/// ```yaml
+/// settings:
+///   default_timeout: 30   # seconds per tool call and step
+///   max_retries: 3
+///   max_tool_calls: 50
+/// ```
+///
+/// Unset fields keep the [`ExecutionConfig`] defaults, and other keys in
+/// `settings` are ignored. Callers build on the result, so options given
+/// for a single run override the workspace's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionSettings {
+    /// Seconds a tool call may take; also the step timeout unless
+    /// `step_timeout` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_timeout: Option<u64>,
+
+    /// Seconds a step may take, retries included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_timeout: Option<u64>,
+
+    /// Seconds a whole skill may take.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skill_timeout: Option<u64>,
+
+    /// Retries of a failed tool call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<usize>,
+
+    /// Milliseconds before the first retry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_delay_ms: Option<u64>,
+
+    /// Most tool calls per run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<usize>,
+
+    /// Most seconds spent executing per run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u64>,
+
+    /// Most total tool cost per run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<f64>,
+
+    /// Cost weight of each tool; unlisted tools cost 1.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tool_costs: HashMap<String, f64>,
+
+    /// Most levels of skills running other skills.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_skill_depth: Option<usize>,
+}
+
+impl ExecutionSettings {
+    /// Read the `settings` section of workspace config YAML.
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct WorkspaceConfig {
+            #[serde(default)]
+            settings: Option<ExecutionSettings>,
+        }
+        let config: Option<WorkspaceConfig> = serde_yaml::from_str(source)
+            .map_err(|e| SkillError::InvalidConfig(format!("settings: {}", e)))?;
+        Ok(config.and_then(|c| c.settings).unwrap_or_default())
+    }
+
+    /// Read the settings of the workspace at `workspace_dir`, or return
+    /// empty settings if it has no config file.
+    pub fn load_workspace(workspace_dir: impl AsRef<Path>) -> Result<Self> {
+        let path = workspace_dir.as_ref().join(WORKSPACE_CONFIG_PATH);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| SkillError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+        Self::from_yaml(&source).map_err(|e| match e {
+            SkillError::InvalidConfig(message) => {
+                SkillError::InvalidConfig(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Set the configured fields on `config`, keeping the rest.
+    pub fn apply(&self, mut config: ExecutionConfig) -> ExecutionConfig {
+        let secs = Duration::from_secs;
+        if let Some(timeout) = self.default_timeout {
+            config.timeout.tool_timeout = secs(timeout);
+            config.timeout.step_timeout = secs(timeout);
+        }
+        if let Some(timeout) = self.step_timeout {
+            config.timeout.step_timeout = secs(timeout);
+        }
+        if let Some(timeout) = self.skill_timeout {
+            config.timeout.skill_timeout = secs(timeout);
+        }
+        if let Some(retries) = self.max_retries {
+            config.retry.max_retries = retries;
+        }
+        if let Some(delay) = self.retry_delay_ms {
+            config.retry.initial_delay = Duration::from_millis(delay);
+        }
+        if let Some(max) = self.max_tool_calls {
+            config.max_tool_calls = Some(max);
+        }
+        if let Some(max) = self.max_duration {
+            config.max_total_duration = Some(secs(max));
+        }
+        if let Some(max) = self.max_cost {
+            config.max_cost = Some(max);
+        }
+        config.tool_costs.extend(
+            self.tool_costs
+                .iter()
+                .map(|(tool, cost)| (tool.clone(), *cost)),
+        );
+        if let Some(depth) = self.max_skill_depth {
+            config.max_skill_depth = Some(depth);
+        }
+        config
+    }
+
+    /// An [`ExecutionConfig`] with these settings over the defaults.
+    pub fn to_config(&self) -> ExecutionConfig {
+        self.apply(ExecutionConfig::default())
+    }
+}
+
+impl ExecutionConfig {
+    /// Defaults of the workspace at `workspace_dir`, see
+    /// [`ExecutionSettings`], with data files relative to the workspace.
+    pub fn from_workspace(workspace_dir: impl AsRef<Path>) -> Result<Self> {
+        let workspace_dir = workspace_dir.as_ref();
+        Ok(ExecutionSettings::load_workspace(workspace_dir)?
+            .to_config()
+            .with_data_dir(workspace_dir))
+    }
 }
 
 #[cfg(test)]
@@ -287,4 +569,56 @@ mod tests {
         assert_eq!(config.timeout.skill_timeout, Duration::from_secs(60));
         assert_eq!(config.retry.max_retries, 0);
     }
+
+    #[test]
+    fn test_execution_settings() {
+        let settings = ExecutionSettings::from_yaml(
+            "name: demo\nsettings:\n  default_timeout: 10\n  skill_timeout: 90\n  max_retries: 1\n  max_tool_calls: 5\n  tool_costs:\n    search: 2.5\n  theme: dark\n",
+        )
+        .unwrap();
+        let config = settings.to_config();
+        assert_eq!(config.timeout.tool_timeout, Duration::from_secs(10));
+        assert_eq!(config.timeout.step_timeout, Duration::from_secs(10));
+        assert_eq!(config.timeout.skill_timeout, Duration::from_secs(90));
+        assert_eq!(config.retry.max_retries, 1);
+        assert_eq!(config.max_tool_calls, Some(5));
+        assert_eq!(config.tool_cost("search"), 2.5);
+        assert_eq!(config.max_cost, None);
+
+        // Per-run options go on top
+        let config = settings
+            .to_config()
+            .with_retry(RetryConfig::no_retries())
+            .with_max_tool_calls(20);
+        assert_eq!(config.retry.max_retries, 0);
+        assert_eq!(config.max_tool_calls, Some(20));
+
+        let empty = ExecutionSettings::default();
+        assert_eq!(ExecutionSettings::from_yaml("name: demo\n").unwrap(), empty);
+        assert_eq!(ExecutionSettings::from_yaml("").unwrap(), empty);
+        assert!(ExecutionSettings::from_yaml("settings:\n  max_retries: many\n").is_err());
+    }
+
+    #[test]
+    fn test_execution_config_from_workspace() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config = ExecutionConfig::from_workspace(temp.path()).unwrap();
+        assert_eq!(config.timeout.tool_timeout, Duration::from_secs(30));
+        assert_eq!(config.data_dir.as_deref(), Some(temp.path()));
+
+        let path = temp.path().join(WORKSPACE_CONFIG_PATH);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "settings:\n  default_timeout: 5\n  max_duration: 60\n",
+        )
+        .unwrap();
+        let config = ExecutionConfig::from_workspace(temp.path()).unwrap();
+        assert_eq!(config.timeout.step_timeout, Duration::from_secs(5));
+        assert_eq!(config.max_total_duration, Some(Duration::from_secs(60)));
+
+        std::fs::write(&path, "settings: [").unwrap();
+        let err = ExecutionConfig::from_workspace(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("config.yaml"));
+    }
 }
@@ -16,26 +16,176 @@
 //! let result = executor.execute(&skill, &mut context).await?;
 //! ```
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 use serde_json::Value;
-use thulp_core::{ToolCall, ToolResult, Transport};
+use thulp_core::{collect_stream, PathRedactor, Redactor, ToolCall, ToolResult, Transport};
+use tokio_util::sync::CancellationToken;
 
+use crate::degradation::{trace_consequences, Degradation};
+use crate::json_type_name;
+use crate::retry::set_attempt_variables;
+use crate::template::{render_step_template, render_template_checked};
 use crate::{
-    calculate_delay, is_error_retryable, ExecutionConfig, ExecutionContext, ExecutionHooks,
-    NoOpHooks, RetryConfig, RetryableError, Skill, SkillError, SkillExecutor, SkillResult,
-    SkillStep, StepResult, TimeoutAction,
+    calculate_delay, is_error_retryable, ApprovalDecision, ApprovalRecord, ApprovalRequest,
+    CallDecision, ExecutionConfig, ExecutionContext, ExecutionHooks, NoOpHooks, RetryConfig,
+    RetryableError, Skill, SkillError, SkillExecutor, SkillRegistry, SkillResult, SkillStep,
+    StepErrorContext, StepResult, TemplateStrictness, TimeoutAction,
 };
+use crate::{check_read_only, needs_confirmation};
+
+/// Tool calls prepared for a step
+enum PreparedCall {
+    /// A regular step with a single call
+    Single(ToolCall),
+    /// A `for_each` step with one call per item
+    Each(Vec<(Value, ToolCall)>),
+    /// A data step, which outputs a value without calling a tool
+    Data(Value),
+    /// A nested skill step, with the child skill's inputs
+    Skill(Value),
+}
+
+impl PreparedCall {
+    /// Resolved arguments, as an array for `for_each` steps
+    fn arguments(&self) -> Value {
+        match self {
+            PreparedCall::Single(call) => call.arguments.clone(),
+            PreparedCall::Each(calls) => calls
+                .iter()
+                .map(|(_, call)| call.arguments.clone())
+                .collect(),
+            PreparedCall::Data(_) => Value::Null,
+            PreparedCall::Skill(inputs) => inputs.clone(),
+        }
+    }
+}
+
+/// An error from running a step, with the call and attempts it happened on
+struct StepError {
+    error: SkillError,
+    call: Option<ToolCall>,
+    attempts: usize,
+}
+
+impl StepError {
+    fn at(error: SkillError, call: &ToolCall, attempts: usize) -> Self {
+        Self {
+            error,
+            call: Some(call.clone()),
+            attempts,
+        }
+    }
+}
+
+impl From<SkillError> for StepError {
+    fn from(error: SkillError) -> Self {
+        Self {
+            error,
+            call: None,
+            attempts: 0,
+        }
+    }
+}
+
+/// A single tool call with the variables its arguments were rendered from
+struct ScopedCall<'a> {
+    call: &'a ToolCall,
+    variables: &'a HashMap<String, Value>,
+    /// Index and value of the `for_each` item
+    item: Option<(usize, &'a Value)>,
+    /// Ask [`on_confirm_call`](ExecutionHooks::on_confirm_call) before the
+    /// first attempt
+    confirm: bool,
+}
+
+impl ScopedCall<'_> {
+    /// The step's variables plus `{{item}}` and `{{index}}` of the item
+    fn scoped_variables(&self) -> HashMap<String, Value> {
+        let mut scoped = self.variables.clone();
+        if let Some((index, item)) = self.item {
+            scoped.insert("item".to_string(), item.clone());
+            scoped.insert("index".to_string(), Value::from(index));
+        }
+        scoped
+    }
+
+    /// Render the step's arguments again for a retry, so they can depend on
+    /// `{{__attempt}}` and `{{__last_error}}`, with the same `strictness` as
+    /// the first attempt
+    fn for_attempt(
+        &self,
+        step: &SkillStep,
+        attempt: usize,
+        last_error: &str,
+        strictness: TemplateStrictness,
+    ) -> Result<ToolCall, SkillError> {
+        let mut scoped = self.scoped_variables();
+        set_attempt_variables(&mut scoped, attempt, Some(last_error));
+        Ok(ToolCall {
+            tool: self.call.tool.clone(),
+            arguments: render_step_template(&step.arguments, &scoped, &step.name, strictness)?,
+        })
+    }
+
+    /// Key the result is memoized under: the tool plus the step's rendered
+    /// [`cache_key`](SkillStep::cache_key), or plus its arguments.
+    ///
+    /// A key left with unresolved placeholders would be shared by unrelated
+    /// calls, so it fails the step under [`TemplateStrictness::Error`] and
+    /// otherwise leaves the call uncached.
+    fn cache_key(
+        &self,
+        step: &SkillStep,
+        strictness: TemplateStrictness,
+    ) -> Result<Option<String>, SkillError> {
+        let Some(template) = &step.cache_key else {
+            return Ok(Some(self.call.fingerprint()));
+        };
+        let template = Value::String(template.clone());
+        let (key, unresolved) = render_template_checked(&template, &self.scoped_variables())?;
+        if let Some(variable) = unresolved.into_iter().next() {
+            if strictness == TemplateStrictness::Error {
+                return Err(SkillError::UnresolvedVariable {
+                    step: step.name.clone(),
+                    variable,
+                });
+            }
+            tracing::warn!(
+                step = %step.name,
+                variable = %variable,
+                "Unresolved variable in cache_key, not caching the result"
+            );
+            return Ok(None);
+        }
+        Ok(Some(
+            ToolCall::with_args(&self.call.tool, key).fingerprint(),
+        ))
+    }
+}
+
+/// Variables for rendering a step's first attempt.
+fn step_variables(context: &ExecutionContext) -> HashMap<String, Value> {
+    let mut variables = context.variables();
+    set_attempt_variables(&mut variables, 1, None);
+    variables
+}
 
 /// Default skill executor that uses a [`Transport`] to execute tool calls.
 ///
 /// This executor implements the standard skill execution flow:
-/// 1. Execute steps sequentially
-/// 2. Apply timeout and retry logic per step
-/// 3. Propagate outputs from earlier steps to later steps
-/// 4. Invoke lifecycle hooks at appropriate points
+/// 1. Execute steps sequentially, or as a DAG of concurrent waves when steps
+///    declare [`depends_on`](SkillStep::depends_on)
+/// 2. Skip steps whose [`condition`](SkillStep::condition) is false
+/// 3. Apply timeout and retry logic per step
+/// 4. Propagate outputs from earlier steps to later steps
+/// 5. Run [nested skills](SkillStep::skill) from its [`SkillRegistry`]
+/// 6. Invoke lifecycle hooks at appropriate points
 ///
 /// # Type Parameters
 ///
@@ -61,6 +211,13 @@ use crate::{
 pub struct DefaultSkillExecutor<T, H = NoOpHooks> {
     transport: Arc<T>,
     hooks: Arc<H>,
+    skills: Arc<SkillRegistry>,
+    redactor: Arc<dyn Redactor>,
+}
+
+/// Redactor for call arguments in step errors unless one is set
+fn default_redactor() -> Arc<dyn Redactor> {
+    Arc::new(PathRedactor::sensitive_defaults())
 }
 
 impl<T: Transport> DefaultSkillExecutor<T, NoOpHooks> {
@@ -69,6 +226,8 @@ impl<T: Transport> DefaultSkillExecutor<T, NoOpHooks> {
         Self {
             transport: Arc::new(transport),
             hooks: Arc::new(NoOpHooks),
+            skills: Arc::default(),
+            redactor: default_redactor(),
         }
     }
 }
@@ -79,6 +238,8 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
         Self {
             transport: Arc::new(transport),
             hooks: Arc::new(hooks),
+            skills: Arc::default(),
+            redactor: default_redactor(),
         }
     }
 
@@ -87,7 +248,35 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
     /// This is useful when you want to share the transport or hooks
     /// across multiple executors.
     pub fn from_arcs(transport: Arc<T>, hooks: Arc<H>) -> Self {
-        Self { transport, hooks }
+        Self {
+            transport,
+            hooks,
+            skills: Arc::default(),
+            redactor: default_redactor(),
+        }
+    }
+
+    /// Set the skills that [`SkillStep::skill`] names are resolved from.
+    ///
+    /// Nested skills run with the same transport and hooks, at most
+    /// [`ExecutionConfig::max_skill_depth`] levels deep.
+    pub fn with_skills(mut self, skills: impl Into<Arc<SkillRegistry>>) -> Self {
+        self.skills = skills.into();
+        self
+    }
+
+    /// Set the redactor applied to call arguments in step errors
+    /// (default: [`PathRedactor::sensitive_defaults`]).
+    ///
+    /// See [`error_context`](crate::error_context).
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Get the skills available to nested skill steps.
+    pub fn skills(&self) -> &SkillRegistry {
+        &self.skills
     }
 
     /// Get a reference to the transport.
@@ -100,98 +289,356 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
         &self.hooks
     }
 
-    /// Prepare arguments by substituting context variables.
+    /// Prepare the tool call(s) for a step by substituting context variables.
+    ///
+    /// Placeholders follow the [`template`](crate::template) syntax: a lone
+    /// `"{{var}}"` keeps the variable's JSON type, while embedded placeholders
+    /// like `"prefix {{var | upper}} suffix"` are interpolated as text.
+    ///
+    /// For [`for_each`](SkillStep::for_each) steps, one call is prepared per
+    /// item with `{{item}}` and `{{index}}` bound to the current element.
+    ///
+    /// Each retry renders the arguments again with `{{__attempt}}` and
+    /// `{{__last_error}}` updated, see [`retry`](crate::retry).
+    ///
+    /// Unresolved placeholders are handled according to `strictness`.
+    /// Data steps are loaded here, with files relative to `data_dir`.
+    fn prepare_call(
+        &self,
+        step: &SkillStep,
+        variables: &HashMap<String, Value>,
+        config: &ExecutionConfig,
+    ) -> Result<PreparedCall, SkillError> {
+        if let Some(data) = step.load_data(config.data_dir.as_deref())? {
+            return Ok(PreparedCall::Data(data));
+        }
+        let strictness = config.template_strictness;
+        let render = |value: &Value, variables: &HashMap<String, Value>| {
+            render_step_template(value, variables, &step.name, strictness)
+        };
+        if step.is_nested() {
+            if step.for_each.is_some() {
+                return Err(SkillError::InvalidConfig(format!(
+                    "Step '{}' runs a nested skill and can't use for_each",
+                    step.name
+                )));
+            }
+            return Ok(PreparedCall::Skill(render(&step.arguments, variables)?));
+        }
+        let Some(for_each) = &step.for_each else {
+            return Ok(PreparedCall::Single(ToolCall {
+                tool: step.tool.clone(),
+                arguments: render(&step.arguments, variables)?,
+            }));
+        };
+
+        let items = match render(&Value::String(for_each.clone()), variables)? {
+            Value::Array(items) => items,
+            other => {
+                return Err(SkillError::InvalidConfig(format!(
+                    "for_each of step '{}' must resolve to an array, got {}",
+                    step.name,
+                    json_type_name(&other)
+                )))
+            }
+        };
+
+        let mut scoped = variables.clone();
+        let mut calls = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            scoped.insert("item".to_string(), item.clone());
+            scoped.insert("index".to_string(), Value::from(index));
+            let call = ToolCall {
+                tool: step.tool.clone(),
+                arguments: render(&step.arguments, &scoped)?,
+            };
+            calls.push((item, call));
+        }
+        Ok(PreparedCall::Each(calls))
+    }
+
+    /// Execute a prepared step with timeout and retry logic.
     ///
-    /// This handles two cases:
-    /// 1. Entire string values like `"{{var}}"` → replaced with actual JSON value
-    /// 2. Embedded placeholders like `"prefix {{var}} suffix"` → string interpolation
-    fn prepare_arguments(
+    /// Fan-out calls run up to [`max_concurrency`](SkillStep::max_concurrency)
+    /// at a time; their outputs are collected into an array in item order,
+    /// and any failed item fails the step.
+    async fn execute_prepared(
         &self,
-        args: &Value,
+        prepared: &PreparedCall,
+        variables: &HashMap<String, Value>,
+        step: &SkillStep,
+        timeout: Duration,
+        retry_config: &RetryConfig,
         context: &ExecutionContext,
-    ) -> Result<Value, SkillError> {
-        self.substitute_value(args, &context.variables())
+    ) -> Result<(ToolResult, usize), StepError> {
+        let (listing, config) = (context.tool_listing(), context.config());
+        if step.requires_approval {
+            // Don't ask for a call read-only mode refuses anyway
+            if matches!(prepared, PreparedCall::Single(_) | PreparedCall::Each(_)) {
+                check_read_only(listing, &*self.transport, &step.tool, config).await?;
+            }
+            self.request_approval(step, prepared, context).await?;
+        }
+        let calls = match prepared {
+            PreparedCall::Data(data) => return Ok((ToolResult::success(data.clone()), 0)),
+            PreparedCall::Skill(inputs) => {
+                return Ok(self.execute_nested(step, inputs, context).await?)
+            }
+            PreparedCall::Single(tool_call) => {
+                check_read_only(listing, &*self.transport, &step.tool, config).await?;
+                let call = ScopedCall {
+                    call: tool_call,
+                    variables,
+                    item: None,
+                    confirm: needs_confirmation(listing, &*self.transport, &step.tool, config)
+                        .await,
+                };
+                return self
+                    .execute_step_with_retry_timeout(call, step, timeout, retry_config, context)
+                    .await;
+            }
+            PreparedCall::Each(calls) => {
+                check_read_only(listing, &*self.transport, &step.tool, config).await?;
+                calls
+            }
+        };
+        let confirm = needs_confirmation(listing, &*self.transport, &step.tool, config).await;
+
+        let limit = step.max_concurrency.unwrap_or(1).max(1);
+        let pending: Vec<_> = calls
+            .iter()
+            .enumerate()
+            .map(|(index, (item, tool_call))| {
+                let call = ScopedCall {
+                    call: tool_call,
+                    variables,
+                    item: Some((index, item)),
+                    confirm,
+                };
+                self.execute_step_with_retry_timeout(call, step, timeout, retry_config, context)
+            })
+            .collect();
+        let outcomes: Vec<_> = stream::iter(pending).buffered(limit).collect().await;
+
+        let mut outputs = Vec::with_capacity(outcomes.len());
+        let mut retry_attempts = 0;
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            let (tool_result, retries) = outcome?;
+            retry_attempts += retries;
+            if !tool_result.is_success() {
+                let error = SkillError::Execution(format!(
+                    "Step '{}' failed for item {}: {}",
+                    step.name,
+                    index,
+                    tool_result.error.unwrap_or_default()
+                ));
+                return Err(StepError::at(error, &calls[index].1, retries + 1));
+            }
+            outputs.push(tool_result.data.unwrap_or(Value::Null));
+        }
+
+        Ok((ToolResult::success(Value::Array(outputs)), retry_attempts))
     }
 
-    /// Recursively substitute variables in a JSON value.
-    fn substitute_value(
+    /// Ask the context's [approval handler](crate::approval) whether a gated
+    /// step may run, failing it when denied or cancelled while waiting.
+    async fn request_approval(
         &self,
-        value: &Value,
-        variables: &std::collections::HashMap<String, Value>,
-    ) -> Result<Value, SkillError> {
-        match value {
-            Value::String(s) => {
-                // Check if the entire string is a single placeholder like "{{var}}"
-                let trimmed = s.trim();
-                if trimmed.starts_with("{{") && trimmed.ends_with("}}") {
-                    let inner = &trimmed[2..trimmed.len() - 2];
-                    // Check if it's a simple variable reference (no other text)
-                    if !inner.contains("{{") && !inner.contains("}}") {
-                        let var_name = inner.trim();
-                        if let Some(var_value) = variables.get(var_name) {
-                            return Ok(var_value.clone());
-                        }
-                    }
-                }
+        step: &SkillStep,
+        prepared: &PreparedCall,
+        context: &ExecutionContext,
+    ) -> Result<(), SkillError> {
+        let request = ApprovalRequest {
+            step: step.name.clone(),
+            tool: step.tool.clone(),
+            skill: step.skill.clone(),
+            arguments: context.secret_redactor().redact(&prepared.arguments()),
+            depth: context.depth(),
+        };
+        let gate = context.approval_gate();
+        let start = Instant::now();
+        let decision = tokio::select! {
+            _ = context.cancellation_token().cancelled() => return Err(SkillError::Cancelled),
+            decision = gate.decide(&request) => decision,
+        };
+        let record = ApprovalRecord {
+            request,
+            decision,
+            waited_ms: start.elapsed().as_millis() as u64,
+        };
+        self.hooks.on_approval(step, &record, context);
+        gate.record(record.clone());
+        match record.decision {
+            ApprovalDecision::Approved => Ok(()),
+            ApprovalDecision::Denied { reason } => Err(SkillError::ApprovalDenied {
+                step: step.name.clone(),
+                reason,
+            }),
+        }
+    }
 
-                // Otherwise, do string interpolation
-                let mut result = s.clone();
-                for (key, var_value) in variables {
-                    let placeholder = format!("{{{{{}}}}}", key);
-                    if result.contains(&placeholder) {
-                        // For string interpolation, convert value to string representation
-                        let replacement = match var_value {
-                            Value::String(s) => s.clone(),
-                            Value::Null => "null".to_string(),
-                            Value::Bool(b) => b.to_string(),
-                            Value::Number(n) => n.to_string(),
-                            _ => serde_json::to_string(var_value).map_err(|e| {
-                                SkillError::InvalidConfig(format!(
-                                    "Failed to serialize value: {}",
-                                    e
-                                ))
-                            })?,
-                        };
-                        result = result.replace(&placeholder, &replacement);
-                    }
-                }
-                Ok(Value::String(result))
-            }
-            Value::Array(arr) => {
-                let substituted: Result<Vec<Value>, SkillError> = arr
-                    .iter()
-                    .map(|v| self.substitute_value(v, variables))
-                    .collect();
-                Ok(Value::Array(substituted?))
-            }
-            Value::Object(obj) => {
-                let mut new_obj = serde_json::Map::new();
-                for (k, v) in obj {
-                    new_obj.insert(k.clone(), self.substitute_value(v, variables)?);
-                }
-                Ok(Value::Object(new_obj))
+    /// Run the skill named by a nested step in a child context.
+    ///
+    /// The step's rendered arguments must be an object of the child's
+    /// inputs. A failed child fails the step.
+    async fn execute_nested(
+        &self,
+        step: &SkillStep,
+        inputs: &Value,
+        context: &ExecutionContext,
+    ) -> Result<(ToolResult, usize), SkillError> {
+        let name = step.skill.as_deref().unwrap_or_default();
+        let max_depth = context.config().skill_depth_limit();
+        if context.depth() >= max_depth {
+            return Err(SkillError::SkillDepthExceeded {
+                skill: name.to_string(),
+                max_depth,
+            });
+        }
+        let skill = self
+            .skills
+            .get(name)
+            .ok_or_else(|| SkillError::NotFound(name.to_string()))?;
+        let inputs = match inputs {
+            Value::Object(fields) => fields.clone().into_iter().collect(),
+            Value::Null => HashMap::new(),
+            other => {
+                return Err(SkillError::InvalidConfig(format!(
+                    "Arguments of step '{}' must be an object of inputs for skill '{}', got {}",
+                    step.name,
+                    name,
+                    json_type_name(other)
+                )))
+            }
+        };
+
+        let mut child = context.nested(inputs);
+        let result = self.execute(skill, &mut child).await?;
+        if !result.success {
+            return Err(SkillError::Execution(format!(
+                "Skill '{}' run by step '{}' failed: {}",
+                name,
+                step.name,
+                result.error.unwrap_or_default()
+            )));
+        }
+        Ok((ToolResult::success(result.output.unwrap_or(Value::Null)), 0))
+    }
+
+    /// Call a tool through the transport's streaming API, forwarding each
+    /// chunk to [`on_chunk`](ExecutionHooks::on_chunk) and merging them into
+    /// the step result.
+    async fn call_streaming(
+        &self,
+        tool_call: &ToolCall,
+        step: &SkillStep,
+        context: &ExecutionContext,
+    ) -> thulp_core::Result<ToolResult> {
+        let stream = self.transport.call_streaming(tool_call).await?;
+        collect_stream(stream.inspect(|chunk| {
+            if let Ok(chunk) = chunk {
+                self.hooks.on_chunk(step, chunk, context);
             }
-            // Numbers, booleans, nulls pass through unchanged
-            _ => Ok(value.clone()),
+        }))
+        .await
+    }
+
+    /// Ask the hooks to confirm a call, returning replacement arguments if
+    /// they were modified.
+    fn confirm_call(
+        &self,
+        call: &ToolCall,
+        step: &SkillStep,
+        context: &ExecutionContext,
+    ) -> Result<Option<ToolCall>, SkillError> {
+        match self.hooks.on_confirm_call(step, call, context) {
+            CallDecision::Allow => Ok(None),
+            CallDecision::Deny(reason) => Err(SkillError::CallDenied {
+                step: step.name.clone(),
+                tool: call.tool.clone(),
+                reason,
+            }),
+            CallDecision::Modify(arguments) => Ok(Some(ToolCall {
+                tool: call.tool.clone(),
+                arguments,
+            })),
         }
     }
 
-    /// Execute a single step with timeout and retry logic.
+    /// Execute a single step with timeout and retry logic, answering from
+    /// the context's step cache when it holds the result.
     async fn execute_step_with_retry_timeout(
         &self,
-        tool_call: &ToolCall,
+        call: ScopedCall<'_>,
         step: &SkillStep,
         timeout: Duration,
         retry_config: &RetryConfig,
         context: &ExecutionContext,
-    ) -> Result<(ToolResult, usize), SkillError> {
+    ) -> Result<(ToolResult, usize), StepError> {
+        let strictness = context.config().template_strictness;
+        let cached = match context.step_cache() {
+            Some(cache) => match call.cache_key(step, strictness)? {
+                Some(key) => {
+                    if let Some(result) = cache.get(&key) {
+                        return Ok((result, 0));
+                    }
+                    Some((cache, key))
+                }
+                None => None,
+            },
+            None => None,
+        };
+        let outcome = self
+            .call_with_retry_timeout(call, step, timeout, retry_config, context)
+            .await;
+        if let (Ok((result, _)), Some((cache, key))) = (&outcome, cached) {
+            cache.put(key, result);
+        }
+        outcome
+    }
+
+    /// Call a step's tool, retrying and timing out as configured.
+    ///
+    /// Arguments replaced during confirmation are used for every attempt.
+    async fn call_with_retry_timeout(
+        &self,
+        call: ScopedCall<'_>,
+        step: &SkillStep,
+        timeout: Duration,
+        retry_config: &RetryConfig,
+        context: &ExecutionContext,
+    ) -> Result<(ToolResult, usize), StepError> {
         let mut attempts = 0;
+        let cancellation = context.cancellation_token();
+        let mut retry_call = None;
+        if call.confirm {
+            retry_call = self
+                .confirm_call(call.call, step, context)
+                .map_err(|e| StepError::at(e, call.call, 0))?;
+        }
+        let rerender = retry_call.is_none();
+        let strictness = context.config().template_strictness;
 
         loop {
             attempts += 1;
 
-            // Execute with timeout
-            let result = tokio::time::timeout(timeout, self.transport.call(tool_call)).await;
+            let tool_call = retry_call.as_ref().unwrap_or(call.call);
+            context
+                .usage_tracker()
+                .charge(&tool_call.tool, context.config())
+                .map_err(|e| StepError::at(e, tool_call, attempts - 1))?;
+
+            // Execute with timeout, giving up early if the run is cancelled
+            let result = tokio::select! {
+                biased;
+                _ = cancellation.cancelled() => {
+                    return Err(StepError::at(SkillError::Cancelled, tool_call, attempts))
+                }
+                result = tokio::time::timeout(
+                    timeout,
+                    self.call_streaming(tool_call, step, context),
+                ) => result,
+            };
 
             match result {
                 Ok(Ok(tool_result)) => {
@@ -205,11 +652,12 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                     if attempts > retry_config.max_retries
                         || !is_error_retryable(&error_msg, retry_config)
                     {
-                        return Err(SkillError::RetryExhausted {
+                        let error = SkillError::RetryExhausted {
                             step: step.name.clone(),
                             attempts,
                             message: error_msg,
-                        });
+                        };
+                        return Err(StepError::at(error, tool_call, attempts));
                     }
 
                     // Notify hooks about retry
@@ -224,7 +672,13 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                         error = %e,
                         "Retrying step after error"
                     );
-                    tokio::time::sleep(delay).await;
+                    sleep_unless_cancelled(delay, cancellation).await?;
+                    if rerender {
+                        let next = call
+                            .for_attempt(step, attempts + 1, &error_msg, strictness)
+                            .map_err(|e| StepError::at(e, tool_call, attempts))?;
+                        retry_call = Some(next);
+                    }
                 }
                 Err(_elapsed) => {
                     // Timeout - notify hooks
@@ -237,10 +691,11 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                             .retryable_errors
                             .contains(&RetryableError::Timeout)
                     {
-                        return Err(SkillError::StepTimeout {
+                        let error = SkillError::StepTimeout {
                             step: step.name.clone(),
                             duration: timeout,
-                        });
+                        };
+                        return Err(StepError::at(error, tool_call, attempts));
                     }
 
                     // Notify hooks about retry
@@ -254,13 +709,30 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                         delay_ms = delay.as_millis() as u64,
                         "Retrying step after timeout"
                     );
-                    tokio::time::sleep(delay).await;
+                    sleep_unless_cancelled(delay, cancellation).await?;
+                    if rerender {
+                        let next = call
+                            .for_attempt(step, attempts + 1, "timeout", strictness)
+                            .map_err(|e| StepError::at(e, tool_call, attempts))?;
+                        retry_call = Some(next);
+                    }
                 }
             }
         }
     }
 }
 
+/// Wait out a retry delay, returning early if the run is cancelled.
+async fn sleep_unless_cancelled(
+    delay: Duration,
+    cancellation: &CancellationToken,
+) -> Result<(), SkillError> {
+    tokio::select! {
+        _ = cancellation.cancelled() => Err(SkillError::Cancelled),
+        _ = tokio::time::sleep(delay) => Ok(()),
+    }
+}
+
 #[async_trait]
 impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T, H> {
     async fn execute(
@@ -270,15 +742,20 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
     ) -> Result<SkillResult, SkillError> {
         // Notify hooks
         self.hooks.before_skill(skill, context);
+        if context.depth() == 0 {
+            context.reset_tool_listing();
+        }
 
         let config = context.config().clone();
         let skill_timeout = config.timeout.skill_timeout;
 
         // Wrap entire execution in skill-level timeout
+        context.usage_tracker().begin();
         let result = tokio::time::timeout(skill_timeout, async {
             self.execute_steps(skill, context, &config).await
         })
         .await;
+        context.usage_tracker().end();
 
         let skill_result = match result {
             Ok(inner_result) => inner_result,
@@ -299,12 +776,23 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
                             step_results: vec![],
                             output: None,
                             error: Some(format!("Skill timed out after {:?}", skill_timeout)),
+                            usage: context.usage(),
+                            degradations: Vec::new(),
                         })
                     }
                 }
             }
         };
 
+        // Check the output against the declared schema
+        let skill_result = skill_result.and_then(|mut result| {
+            if result.success {
+                result.output = skill.validate_output(result.output.take())?;
+            }
+            result.usage = context.usage();
+            Ok(result)
+        });
+
         // Notify hooks with result
         match &skill_result {
             Ok(result) => {
@@ -318,6 +806,8 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
                     step_results: vec![],
                     output: None,
                     error: Some(e.to_string()),
+                    usage: context.usage(),
+                    degradations: Vec::new(),
                 };
                 self.hooks.after_skill(skill, &failure_result, context);
             }
@@ -346,13 +836,16 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
             ..config.retry.clone()
         };
 
-        // Prepare arguments
-        let prepared_args = self.prepare_arguments(&step.arguments, context)?;
+        if context.is_cancelled() {
+            self.hooks.on_cancel(None, context);
+            return Err(SkillError::Cancelled);
+        }
 
-        let tool_call = ToolCall {
-            tool: step.tool.clone(),
-            arguments: prepared_args,
-        };
+        // Prepare arguments
+        let base = step_variables(context);
+        let variables = context.secrets().step_variables(step, &base)?;
+        let prepared = self.prepare_call(step, &variables, &config)?;
+        context.record_snapshot(0, step, prepared.arguments());
 
         // Notify hooks
         self.hooks.before_step(step, 0, context);
@@ -360,19 +853,31 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
         let start = Instant::now();
 
         // Execute with retry and timeout
+        context.usage_tracker().begin();
         let result = self
-            .execute_step_with_retry_timeout(
-                &tool_call,
+            .execute_prepared(
+                &prepared,
+                &variables,
                 step,
                 step_timeout,
                 &step_retry_config,
                 context,
             )
             .await;
+        context.usage_tracker().end();
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
         let step_result = match result {
+            Err(StepError {
+                error: SkillError::Cancelled,
+                ..
+            }) => {
+                let sr = StepResult::failure(&step.name, "cancelled", duration_ms);
+                self.hooks.after_step(step, 0, &sr, context);
+                self.hooks.on_cancel(Some(step), context);
+                return Err(SkillError::Cancelled);
+            }
             Ok((tool_result, retry_attempts)) => {
                 // Store output in context
                 if let Some(data) = &tool_result.data {
@@ -389,7 +894,7 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
                     retry_attempts,
                 }
             }
-            Err(e) => {
+            Err(StepError { error: e, .. }) => {
                 self.hooks.on_error(&e, context);
 
                 StepResult {
@@ -418,6 +923,29 @@ impl<T: Transport, H: ExecutionHooks> SkillExecutor for DefaultSkillExecutor<T,
 }
 
 impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
+    /// Attach the skill, step and redacted call to a step error, see
+    /// [`error_context`](crate::error_context). Secrets resolved for the run
+    /// are masked as well.
+    fn step_failed(
+        &self,
+        skill: &Skill,
+        index: usize,
+        step: &SkillStep,
+        failure: StepError,
+        context: &ExecutionContext,
+    ) -> SkillError {
+        let mut error_context =
+            StepErrorContext::new(&skill.name, &step.name, index).with_attempts(failure.attempts);
+        if let Some(call) = &failure.call {
+            let masked = ToolCall {
+                tool: call.tool.clone(),
+                arguments: context.secret_redactor().redact(&call.arguments),
+            };
+            error_context = error_context.with_call(&masked, &*self.redactor);
+        }
+        error_context.wrap(failure.error)
+    }
+
     /// Internal method to execute all steps (used within skill timeout).
     async fn execute_steps(
         &self,
@@ -425,9 +953,29 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
         context: &mut ExecutionContext,
         config: &ExecutionConfig,
     ) -> Result<SkillResult, SkillError> {
+        for (name, value) in skill.resolve_inputs(context.inputs())? {
+            context.set_input(name, value);
+        }
+
+        if skill.has_dependencies() {
+            return self.execute_dag(skill, context, config).await;
+        }
+
         let mut step_results: Vec<(String, ToolResult)> = Vec::new();
+        let mut degradations = Vec::new();
+        let mut output = None;
 
         for (index, step) in skill.steps.iter().enumerate() {
+            if context.is_cancelled() {
+                self.hooks.on_cancel(None, context);
+                return Err(SkillError::Cancelled);
+            }
+
+            if !step.should_run(&context.variables())? {
+                self.hooks.on_skip(step, index, context);
+                continue;
+            }
+
             // Determine timeout for this step
             let step_timeout = step
                 .timeout_secs
@@ -442,12 +990,10 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
             };
 
             // Prepare arguments
-            let prepared_args = self.prepare_arguments(&step.arguments, context)?;
-
-            let tool_call = ToolCall {
-                tool: step.tool.clone(),
-                arguments: prepared_args,
-            };
+            let base = step_variables(context);
+            let variables = context.secrets().step_variables(step, &base)?;
+            let prepared = self.prepare_call(step, &variables, config)?;
+            context.record_snapshot(index, step, prepared.arguments());
 
             // Notify hooks
             self.hooks.before_step(step, index, context);
@@ -456,8 +1002,9 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
 
             // Execute with retry and timeout
             let step_result = self
-                .execute_step_with_retry_timeout(
-                    &tool_call,
+                .execute_prepared(
+                    &prepared,
+                    &variables,
                     step,
                     step_timeout,
                     &step_retry_config,
@@ -480,7 +1027,10 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                     };
                     self.hooks.after_step(step, index, &sr, context);
 
-                    step_results.push((step.name.clone(), tool_result.clone()));
+                    step_results.push((
+                        step.name.clone(),
+                        tool_result.clone().with_duration(duration_ms),
+                    ));
 
                     // Add result to context for use in subsequent steps
                     context.set_output(
@@ -488,39 +1038,55 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
                         tool_result.data.clone().unwrap_or(Value::Null),
                     );
 
-                    // If this is the last step, use its result as output
-                    if step_results.len() == skill.steps.len() {
-                        return Ok(SkillResult {
-                            success: true,
-                            step_results,
-                            output: tool_result.data,
-                            error: None,
-                        });
-                    }
+                    // The last step that runs provides the skill output
+                    output = tool_result.data;
                 }
-                Err(e) => {
+                Err(failure) => {
                     // Create StepResult for hooks
-                    let sr = StepResult::failure(&step.name, e.to_string(), duration_ms);
+                    let message = failure.error.to_string();
+                    let sr = StepResult::failure(&step.name, message.clone(), duration_ms);
                     self.hooks.after_step(step, index, &sr, context);
+
+                    // Cancellation and exhausted budgets stop the run regardless
+                    // of error handling settings
+                    if matches!(failure.error, SkillError::Cancelled) {
+                        self.hooks.on_cancel(Some(step), context);
+                        return Err(failure.error);
+                    }
+                    if matches!(failure.error, SkillError::BudgetExceeded(_)) {
+                        return Err(failure.error);
+                    }
+                    let e = self.step_failed(skill, index, step, failure, context);
                     self.hooks.on_error(&e, context);
+                    output = None;
 
                     if step.continue_on_error {
                         // Continue on error
-                        step_results.push((step.name.clone(), ToolResult::failure(e.to_string())));
+                        degradations.push(Degradation::new(step, message.clone()));
+                        step_results.push((
+                            step.name.clone(),
+                            ToolResult::failure(message).with_duration(duration_ms),
+                        ));
                     } else {
                         // Check timeout action for Skip/Partial behavior
                         match &config.timeout.timeout_action {
                             TimeoutAction::Skip => {
-                                step_results
-                                    .push((step.name.clone(), ToolResult::failure(e.to_string())));
+                                degradations.push(Degradation::new(step, message.clone()));
+                                step_results.push((
+                                    step.name.clone(),
+                                    ToolResult::failure(message).with_duration(duration_ms),
+                                ));
                                 // Continue to next step
                             }
                             TimeoutAction::Partial => {
+                                trace_consequences(skill, &mut degradations, &step_results);
                                 return Ok(SkillResult {
                                     success: false,
                                     step_results,
                                     output: None,
                                     error: Some(e.to_string()),
+                                    usage: context.usage(),
+                                    degradations,
                                 });
                             }
                             TimeoutAction::Fail => {
@@ -532,11 +1098,165 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
             }
         }
 
+        trace_consequences(skill, &mut degradations, &step_results);
+        Ok(SkillResult {
+            success: true,
+            step_results,
+            output,
+            error: None,
+            usage: context.usage(),
+            degradations,
+        })
+    }
+
+    /// Execute steps as a dependency graph.
+    ///
+    /// Steps are grouped into waves with [`Skill::execution_waves`]; every
+    /// step in a wave runs concurrently on the transport, and their outputs
+    /// are written to the context before the next wave starts. Results are
+    /// recorded in execution order and the output of the last step executed
+    /// becomes the skill output. Steps whose condition is false are skipped
+    /// without blocking their dependents.
+    async fn execute_dag(
+        &self,
+        skill: &Skill,
+        context: &mut ExecutionContext,
+        config: &ExecutionConfig,
+    ) -> Result<SkillResult, SkillError> {
+        let waves = skill.execution_waves()?;
+        let mut step_results: Vec<(String, ToolResult)> = Vec::new();
+        let mut degradations = Vec::new();
+        let mut output = None;
+
+        for wave in waves {
+            if context.is_cancelled() {
+                self.hooks.on_cancel(None, context);
+                return Err(SkillError::Cancelled);
+            }
+
+            // Arguments are resolved against the context as of the start of the wave
+            let variables = step_variables(context);
+            let mut calls = Vec::with_capacity(wave.len());
+            for &index in &wave {
+                let step = &skill.steps[index];
+                if !step.should_run(&variables)? {
+                    self.hooks.on_skip(step, index, context);
+                    continue;
+                }
+
+                let step_timeout = step
+                    .timeout_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or(config.timeout.step_timeout);
+                let step_retry_config = RetryConfig {
+                    max_retries: step.max_retries.unwrap_or(config.retry.max_retries),
+                    ..config.retry.clone()
+                };
+                let step_variables = context.secrets().step_variables(step, &variables)?;
+                let prepared = self.prepare_call(step, &step_variables, config)?;
+                context.record_snapshot(index, step, prepared.arguments());
+
+                self.hooks.before_step(step, index, context);
+                calls.push((
+                    index,
+                    prepared,
+                    step_variables,
+                    step_timeout,
+                    step_retry_config,
+                ));
+            }
+
+            let shared: &ExecutionContext = context;
+            let outcomes = join_all(calls.iter().map(
+                |(index, prepared, variables, step_timeout, retry_config)| async move {
+                    let start = Instant::now();
+                    let result = self
+                        .execute_prepared(
+                            prepared,
+                            variables,
+                            &skill.steps[*index],
+                            *step_timeout,
+                            retry_config,
+                            shared,
+                        )
+                        .await;
+                    (*index, result, start.elapsed().as_millis() as u64)
+                },
+            ))
+            .await;
+
+            for (index, result, duration_ms) in outcomes {
+                let step = &skill.steps[index];
+                match result {
+                    Ok((tool_result, retry_attempts)) => {
+                        let sr = StepResult {
+                            step_name: step.name.clone(),
+                            success: true,
+                            output: tool_result.data.clone(),
+                            error: None,
+                            duration_ms,
+                            retry_attempts,
+                        };
+                        self.hooks.after_step(step, index, &sr, context);
+
+                        context.set_output(
+                            step.name.clone(),
+                            tool_result.data.clone().unwrap_or(Value::Null),
+                        );
+                        output = tool_result.data.clone();
+                        step_results
+                            .push((step.name.clone(), tool_result.with_duration(duration_ms)));
+                    }
+                    Err(failure) => {
+                        let message = failure.error.to_string();
+                        let sr = StepResult::failure(&step.name, message.clone(), duration_ms);
+                        self.hooks.after_step(step, index, &sr, context);
+
+                        if matches!(failure.error, SkillError::Cancelled) {
+                            self.hooks.on_cancel(Some(step), context);
+                            return Err(failure.error);
+                        }
+                        if matches!(failure.error, SkillError::BudgetExceeded(_)) {
+                            return Err(failure.error);
+                        }
+                        let e = self.step_failed(skill, index, step, failure, context);
+                        self.hooks.on_error(&e, context);
+                        output = None;
+
+                        if step.continue_on_error
+                            || matches!(config.timeout.timeout_action, TimeoutAction::Skip)
+                        {
+                            degradations.push(Degradation::new(step, message.clone()));
+                            step_results.push((
+                                step.name.clone(),
+                                ToolResult::failure(message).with_duration(duration_ms),
+                            ));
+                        } else if matches!(config.timeout.timeout_action, TimeoutAction::Partial) {
+                            trace_consequences(skill, &mut degradations, &step_results);
+                            return Ok(SkillResult {
+                                success: false,
+                                step_results,
+                                output: None,
+                                error: Some(e.to_string()),
+                                usage: context.usage(),
+                                degradations,
+                            });
+                        } else {
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+
+        trace_consequences(skill, &mut degradations, &step_results);
         Ok(SkillResult {
             success: true,
             step_results,
-            output: None,
+            output,
             error: None,
+            usage: context.usage(),
+            degradations,
         })
     }
 }
@@ -544,18 +1264,21 @@ impl<T: Transport, H: ExecutionHooks> DefaultSkillExecutor<T, H> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     /// Mock transport for testing
     struct MockTransport {
         responses: HashMap<String, ToolResult>,
+        definitions: Vec<thulp_core::ToolDefinition>,
+        listings: AtomicUsize,
     }
 
     impl MockTransport {
         fn new() -> Self {
             Self {
                 responses: HashMap::new(),
+                definitions: Vec::new(),
+                listings: AtomicUsize::new(0),
             }
         }
 
@@ -563,6 +1286,11 @@ mod tests {
             self.responses.insert(tool_name.to_string(), result);
             self
         }
+
+        fn with_definition(mut self, definition: thulp_core::ToolDefinition) -> Self {
+            self.definitions.push(definition);
+            self
+        }
     }
 
     #[async_trait]
@@ -580,7 +1308,8 @@ mod tests {
         }
 
         async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
-            Ok(vec![])
+            self.listings.fetch_add(1, Ordering::SeqCst);
+            Ok(self.definitions.clone())
         }
 
         async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
@@ -605,9 +1334,7 @@ mod tests {
             name: "step1".to_string(),
             tool: "tool1".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
         let mut context = ExecutionContext::new();
@@ -617,6 +1344,74 @@ mod tests {
         assert_eq!(result.step_results.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_default_executor_read_only_refuses_destructive_tools() {
+        let transport = MockTransport::new()
+            .with_response("read", ToolResult::success(serde_json::json!("data")))
+            .with_response("drop", ToolResult::success(serde_json::json!("gone")))
+            .with_response("unlisted", ToolResult::success(serde_json::json!("?")))
+            .with_definition(thulp_core::ToolDefinition::new("read"))
+            .with_definition(
+                thulp_core::ToolDefinition::builder("drop")
+                    .destructive(true)
+                    .build(),
+            );
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill = Skill::new("cleanup", "Cleanup")
+            .with_step(SkillStep {
+                name: "inspect".to_string(),
+                tool: "read".to_string(),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "purge".to_string(),
+                tool: "drop".to_string(),
+                continue_on_error: true,
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "guess".to_string(),
+                tool: "unlisted".to_string(),
+                continue_on_error: true,
+                ..Default::default()
+            });
+
+        let mut context =
+            ExecutionContext::new().with_config(ExecutionConfig::new().with_read_only(true));
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert!(result.step_results[0].1.is_success());
+        assert!(!result.step_results[1].1.is_success());
+        assert!(result.step_results[1]
+            .1
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("read-only mode"));
+        assert!(context.get_output("purge").is_none());
+
+        // A tool the transport doesn't list can't be verified, so it's refused
+        assert_eq!(
+            result.step_results[2].1.error.as_deref(),
+            Some(
+                "Execution error: Cannot verify tool 'unlisted' in read-only mode: \
+                 the transport doesn't list it"
+            )
+        );
+
+        // The tools are listed once for the whole run, and again for the next
+        assert_eq!(executor.transport().listings.load(Ordering::SeqCst), 1);
+        let mut context =
+            ExecutionContext::new().with_config(ExecutionConfig::new().with_read_only(true));
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(executor.transport().listings.load(Ordering::SeqCst), 2);
+
+        // Outside read-only mode the same skill runs both steps
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert!(result.step_results.iter().all(|(_, r)| r.is_success()));
+    }
+
     #[tokio::test]
     async fn test_default_executor_with_hooks() {
         struct CountingHooks {
@@ -683,17 +1478,13 @@ mod tests {
                 name: "step1".to_string(),
                 tool: "tool1".to_string(),
                 arguments: serde_json::json!({}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "step2".to_string(),
                 tool: "tool2".to_string(),
                 arguments: serde_json::json!({}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             });
 
         let mut context = ExecutionContext::new();
@@ -725,17 +1516,13 @@ mod tests {
                 name: "step1".to_string(),
                 tool: "step1_tool".to_string(),
                 arguments: serde_json::json!({}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "step2".to_string(),
                 tool: "step2_tool".to_string(),
                 arguments: serde_json::json!({"input": "{{step1}}"}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             });
 
         let mut context = ExecutionContext::new();
@@ -765,16 +1552,14 @@ mod tests {
                 tool: "step1_tool".to_string(),
                 arguments: serde_json::json!({}),
                 continue_on_error: true, // Should continue even if this fails
-                timeout_secs: None,
                 max_retries: Some(0),
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "step2".to_string(),
                 tool: "step2_tool".to_string(),
                 arguments: serde_json::json!({}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             });
 
         let config = ExecutionConfig::new().with_retry(crate::RetryConfig::no_retries());
@@ -795,11 +1580,70 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_default_executor_from_arcs() {
-        let transport = Arc::new(
-            MockTransport::new().with_response("tool", ToolResult::success(serde_json::json!({}))),
+    async fn test_default_executor_reports_degradations() {
+        let transport = MockTransport::new().with_response(
+            "summarize",
+            ToolResult::success(serde_json::json!("nothing found")),
         );
-        let hooks = Arc::new(NoOpHooks);
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill = Skill::new("digest", "Search and summarize")
+            .with_step(SkillStep {
+                name: "search".to_string(),
+                tool: "search".to_string(),
+                continue_on_error: true,
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "summarize".to_string(),
+                tool: "summarize".to_string(),
+                arguments: serde_json::json!({
+                    "items": "{{search.items | default([])}}",
+                    "total": "{{search.count}}"
+                }),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "publish".to_string(),
+                tool: "publish".to_string(),
+                arguments: serde_json::json!({"text": "{{summarize}}"}),
+                ..Default::default()
+            });
+
+        // `publish` fails without continue_on_error and is skipped
+        let config = ExecutionConfig::new()
+            .with_retry(crate::RetryConfig::no_retries())
+            .with_timeout(crate::TimeoutConfig::new().with_timeout_action(TimeoutAction::Skip));
+        let mut context = ExecutionContext::new().with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.is_degraded());
+        assert_eq!(result.output, None);
+
+        let [search, publish] = result.degradations.as_slice() else {
+            panic!("expected two degradations: {:?}", result.degradations);
+        };
+        assert_eq!(search.step, "search");
+        assert_eq!(search.action, crate::DegradationAction::ContinueOnError);
+        assert_eq!(search.fallbacks.len(), 1);
+        assert_eq!(search.fallbacks[0].path, "search.items");
+        assert_eq!(search.missing.len(), 1);
+        assert_eq!(search.missing[0].path, "search.count");
+        assert!(!search.output_lost);
+
+        assert_eq!(publish.step, "publish");
+        assert_eq!(publish.action, crate::DegradationAction::Skip);
+        assert!(publish.output_lost);
+        assert!(publish.error.contains("publish"), "{}", publish.error);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_from_arcs() {
+        let transport = Arc::new(
+            MockTransport::new().with_response("tool", ToolResult::success(serde_json::json!({}))),
+        );
+        let hooks = Arc::new(NoOpHooks);
 
         let executor = DefaultSkillExecutor::from_arcs(transport.clone(), hooks.clone());
 
@@ -807,9 +1651,7 @@ mod tests {
             name: "s".to_string(),
             tool: "tool".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
         let mut context = ExecutionContext::new();
@@ -817,4 +1659,1298 @@ mod tests {
 
         assert!(result.success);
     }
+
+    #[tokio::test]
+    async fn test_default_executor_conditional_steps() {
+        let transport = MockTransport::new()
+            .with_response(
+                "search",
+                ToolResult::success(serde_json::json!({"count": 0})),
+            )
+            .with_response(
+                "summarize",
+                ToolResult::success(serde_json::json!("summary")),
+            )
+            .with_response(
+                "fallback",
+                ToolResult::success(serde_json::json!("no results")),
+            );
+
+        struct SkipCounter(Arc<AtomicUsize>);
+
+        impl ExecutionHooks for SkipCounter {
+            fn on_skip(&self, _step: &SkillStep, _index: usize, _context: &ExecutionContext) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let skipped = Arc::new(AtomicUsize::new(0));
+        let executor = DefaultSkillExecutor::with_hooks(transport, SkipCounter(skipped.clone()));
+
+        let skill = Skill::new("search", "Search with fallback")
+            .with_step(SkillStep {
+                name: "search".to_string(),
+                tool: "search".to_string(),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "summarize".to_string(),
+                tool: "summarize".to_string(),
+                condition: Some("{{search.count}} > 0".to_string()),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "fallback".to_string(),
+                tool: "fallback".to_string(),
+                condition: Some("{{search.count}} == 0".to_string()),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "disabled".to_string(),
+                tool: "summarize".to_string(),
+                condition: Some("false".to_string()),
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert!(result.success);
+        let names: Vec<&str> = result
+            .step_results
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(names, vec!["search", "fallback"]);
+        assert_eq!(result.output, Some(serde_json::json!("no results")));
+        assert!(context.get_output("summarize").is_none());
+        assert_eq!(skipped.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_invalid_condition() {
+        let transport =
+            MockTransport::new().with_response("tool", ToolResult::success(serde_json::json!({})));
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill = Skill::new("test", "Test").with_step(SkillStep {
+            name: "s".to_string(),
+            tool: "tool".to_string(),
+            condition: Some("{{count}} >".to_string()),
+            ..Default::default()
+        });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await;
+        assert!(matches!(result, Err(SkillError::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_validates_output() {
+        let transport = MockTransport::new()
+            .with_response("good", ToolResult::success(serde_json::json!({"count": 1})))
+            .with_response(
+                "bad",
+                ToolResult::success(serde_json::json!({"count": "one"})),
+            );
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill_for = |tool: &str| {
+            Skill::new("counted", "Counted")
+                .with_step(SkillStep {
+                    name: "count".to_string(),
+                    tool: tool.to_string(),
+                    ..Default::default()
+                })
+                .with_output(
+                    thulp_core::Parameter::builder("count")
+                        .param_type(thulp_core::ParameterType::Integer)
+                        .required(true)
+                        .build(),
+                )
+        };
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill_for("good"), &mut context).await;
+        assert!(result.unwrap().success);
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill_for("bad"), &mut context).await;
+        assert!(matches!(result, Err(SkillError::OutputValidation { .. })));
+    }
+
+    /// Transport that records arguments and tracks how many calls overlap
+    #[derive(Default)]
+    struct ConcurrencyTransport {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        calls: std::sync::Mutex<Vec<ToolCall>>,
+    }
+
+    #[async_trait]
+    impl Transport for ConcurrencyTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+            self.calls.lock().unwrap().push(call.clone());
+
+            let delay = call.arguments.get("delay_ms").and_then(Value::as_u64);
+            tokio::time::sleep(Duration::from_millis(delay.unwrap_or(50))).await;
+
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            let output = call.arguments.get("echo").cloned();
+            Ok(ToolResult::success(
+                output.unwrap_or_else(|| serde_json::json!(call.tool)),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_dag_runs_independent_steps_concurrently() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+
+        let skill = Skill::new("fan_in", "Fan in")
+            .with_step(SkillStep {
+                name: "merge".to_string(),
+                tool: "merge".to_string(),
+                arguments: serde_json::json!({"left": "{{left}}", "right": "{{right}}"}),
+                depends_on: vec!["left".to_string(), "right".to_string()],
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "left".to_string(),
+                tool: "fetch_left".to_string(),
+                arguments: serde_json::json!({}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "right".to_string(),
+                tool: "fetch_right".to_string(),
+                arguments: serde_json::json!({}),
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert!(result.success);
+        let order: Vec<&str> = result
+            .step_results
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(order, vec!["left", "right", "merge"]);
+        assert_eq!(result.output, Some(serde_json::json!("merge")));
+
+        let transport = executor.transport();
+        assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+
+        let calls = transport.calls.lock().unwrap();
+        assert_eq!(
+            calls[2].arguments,
+            serde_json::json!({"left": "fetch_left", "right": "fetch_right"})
+        );
+        assert_eq!(
+            context.get_output("merge"),
+            Some(&serde_json::json!("merge"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_dag_rejects_cycles() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+
+        let skill = Skill::new("cycle", "Cycle")
+            .with_step(SkillStep {
+                name: "a".to_string(),
+                tool: "tool".to_string(),
+                depends_on: vec!["b".to_string()],
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "b".to_string(),
+                tool: "tool".to_string(),
+                depends_on: vec!["a".to_string()],
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await;
+
+        assert!(matches!(result, Err(SkillError::InvalidConfig(_))));
+        assert!(executor.transport().calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_for_each_bounded_and_ordered() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+
+        let skill = Skill::new("crawl", "Fetch each result").with_step(SkillStep {
+            name: "fetch".to_string(),
+            tool: "fetch".to_string(),
+            arguments: serde_json::json!({
+                "echo": "{{item.id}}-{{index}}",
+                "delay_ms": "{{item.delay}}"
+            }),
+            for_each: Some("{{search.results}}".to_string()),
+            max_concurrency: Some(2),
+            ..Default::default()
+        });
+
+        let mut context = ExecutionContext::new().with_input(
+            "search",
+            serde_json::json!({"results": [
+                {"id": "a", "delay": 60},
+                {"id": "b", "delay": 5},
+                {"id": "c", "delay": 30},
+                {"id": "d", "delay": 5}
+            ]}),
+        );
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        let expected = serde_json::json!(["a-0", "b-1", "c-2", "d-3"]);
+        assert!(result.success);
+        assert_eq!(result.output, Some(expected.clone()));
+        assert_eq!(context.get_output("fetch"), Some(&expected));
+
+        let transport = executor.transport();
+        assert_eq!(transport.calls.lock().unwrap().len(), 4);
+        assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_for_each_requires_array() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+
+        let skill = Skill::new("bad", "Bad").with_step(SkillStep {
+            name: "fetch".to_string(),
+            tool: "fetch".to_string(),
+            for_each: Some("{{query}}".to_string()),
+            ..Default::default()
+        });
+
+        let mut context = ExecutionContext::new().with_input("query", serde_json::json!("rust"));
+        let result = executor.execute(&skill, &mut context).await;
+
+        assert!(matches!(result, Err(SkillError::InvalidConfig(msg)) if msg.contains("string")));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_data_steps() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("ids.json"), "[3, 5]").unwrap();
+
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+        let skill = Skill::new("fixtures", "Fixtures")
+            .with_step(SkillStep {
+                name: "ids".to_string(),
+                data_file: Some("ids.json".to_string()),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({"echo": "{{item}}", "delay_ms": 0}),
+                for_each: Some("{{ids}}".to_string()),
+                ..Default::default()
+            });
+
+        let config = ExecutionConfig::new().with_data_dir(temp.path());
+        let mut context = ExecutionContext::new().with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert_eq!(result.output, Some(serde_json::json!([3, 5])));
+        // Only the tool step reached the transport
+        assert_eq!(executor.transport().calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_runs_nested_skills() {
+        let child = Skill::new("echo-child", "Echo its input")
+            .with_input("value")
+            .with_step(SkillStep {
+                name: "echo".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({"echo": "{{value}}", "delay_ms": 0}),
+                ..Default::default()
+            });
+        let mut registry = SkillRegistry::new();
+        registry.register(child);
+        let executor =
+            DefaultSkillExecutor::new(ConcurrencyTransport::default()).with_skills(registry);
+
+        let parent = Skill::new("parent", "Parent")
+            .with_step(SkillStep {
+                name: "seed".to_string(),
+                data: Some(serde_json::json!(7)),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "child".to_string(),
+                skill: Some("echo-child".to_string()),
+                arguments: serde_json::json!({"value": "{{seed}}"}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "after".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({"echo": "{{child}}", "delay_ms": 0}),
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&parent, &mut context).await.unwrap();
+        assert_eq!(result.output, Some(serde_json::json!(7)));
+        assert_eq!(context.get_output("child"), Some(&serde_json::json!(7)));
+        // The child's tool calls count towards the parent's usage
+        assert_eq!(context.usage().tool_calls, 2);
+
+        let missing = Skill::new("missing", "Missing").with_step(SkillStep {
+            name: "child".to_string(),
+            skill: Some("nope".to_string()),
+            ..Default::default()
+        });
+        let err = executor
+            .execute(&missing, &mut ExecutionContext::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err.root(), SkillError::NotFound(name) if name == "nope"));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_limits_skill_depth() {
+        let recursive = Skill::new("recurse", "Calls itself").with_step(SkillStep {
+            name: "again".to_string(),
+            skill: Some("recurse".to_string()),
+            ..Default::default()
+        });
+        let mut registry = SkillRegistry::new();
+        registry.register(recursive.clone());
+        let executor =
+            DefaultSkillExecutor::new(ConcurrencyTransport::default()).with_skills(registry);
+
+        let config = ExecutionConfig::new().with_max_skill_depth(3);
+        let mut context = ExecutionContext::new().with_config(config);
+        let err = executor
+            .execute(&recursive, &mut context)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.root(),
+            SkillError::SkillDepthExceeded { max_depth: 3, .. }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("skill 'recurse' step 1 'again': skill 'recurse' step 1 'again': "));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_step_error_context() {
+        let child = Skill::new("lookup", "Fails")
+            .with_step(SkillStep {
+                name: "prepare".to_string(),
+                data: Some(serde_json::json!({})),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "query".to_string(),
+                tool: "missing".to_string(),
+                arguments: serde_json::json!({"q": "{{term}}", "api_key": "k3y"}),
+                ..Default::default()
+            });
+        let parent = Skill::new("report", "Runs lookup").with_step(SkillStep {
+            name: "run_lookup".to_string(),
+            skill: Some("lookup".to_string()),
+            arguments: serde_json::json!({"term": "rust"}),
+            ..Default::default()
+        });
+        let mut registry = SkillRegistry::new();
+        registry.register(child);
+        let executor = DefaultSkillExecutor::new(MockTransport::new()).with_skills(registry);
+
+        let err = executor
+            .execute(&parent, &mut ExecutionContext::new())
+            .await
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.starts_with(
+                "skill 'report' step 1 'run_lookup': skill 'lookup' step 2 'query' calling missing "
+            ),
+            "{}",
+            message
+        );
+        assert_eq!(err.code(), "retry_exhausted");
+        assert_eq!(err.step_context().unwrap().step, "run_lookup");
+
+        let SkillError::StepFailed { context, source } = err else {
+            panic!("expected a step failure");
+        };
+        assert!(context.call.is_none());
+        let inner = source.step_context().unwrap();
+        assert_eq!(inner.skill, "lookup");
+        assert_eq!(inner.step, "query");
+        assert_eq!((inner.index, inner.attempts), (1, 1));
+        let call = inner.call.as_ref().unwrap();
+        assert_eq!(call.tool, "missing");
+        assert_eq!(
+            call.arguments,
+            serde_json::json!({"q": "rust", "api_key": "[REDACTED]"})
+        );
+        assert!(matches!(source.root(), SkillError::RetryExhausted { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_strict_templates() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+        let skill = Skill::new("typo", "Typo").with_step(SkillStep {
+            name: "search".to_string(),
+            tool: "search".to_string(),
+            arguments: serde_json::json!({"echo": "{{qeury}}", "delay_ms": 0}),
+            ..Default::default()
+        });
+
+        let mut context = ExecutionContext::new().with_input("query", serde_json::json!("rust"));
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(result.output, Some(serde_json::json!("{{qeury}}")));
+
+        let config =
+            ExecutionConfig::new().with_template_strictness(crate::TemplateStrictness::Error);
+        let mut context = ExecutionContext::new()
+            .with_input("query", serde_json::json!("rust"))
+            .with_config(config);
+        let err = executor.execute(&skill, &mut context).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Step 'search' references unresolved template variable 'qeury'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_resolves_input_defaults_and_computed() {
+        let executor = DefaultSkillExecutor::new(ConcurrencyTransport::default());
+
+        let skill = Skill::new("page", "Fetch a page")
+            .with_input("page")
+            .with_input_default("per_page", serde_json::json!(20))
+            .with_computed_input("offset", "({{page}} - 1) * {{per_page}}")
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({"echo": "{{offset}}", "delay_ms": 0}),
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new().with_input("page", serde_json::json!(3));
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert_eq!(result.output, Some(serde_json::json!(40)));
+        assert_eq!(context.get_input("per_page"), Some(&serde_json::json!(20)));
+        assert_eq!(context.get_input("offset"), Some(&serde_json::json!(40)));
+    }
+
+    /// Transport that streams its output as several text chunks
+    struct StreamingTransport;
+
+    #[async_trait]
+    impl Transport for StreamingTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, _call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            Err(thulp_core::Error::ExecutionFailed(
+                "call() should not be used".to_string(),
+            ))
+        }
+
+        async fn call_streaming(
+            &self,
+            _call: &ToolCall,
+        ) -> thulp_core::Result<thulp_core::ToolResultStream> {
+            let chunks = ["line 1\n", "line 2\n", "line 3\n"]
+                .map(|line| Ok(ToolResult::success(Value::from(line))));
+            Ok(Box::pin(stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_streams_chunks_to_hooks() {
+        struct ChunkCollector {
+            chunks: std::sync::Mutex<Vec<Value>>,
+        }
+
+        impl ExecutionHooks for ChunkCollector {
+            fn on_chunk(&self, step: &SkillStep, chunk: &ToolResult, _context: &ExecutionContext) {
+                assert_eq!(step.name, "tail");
+                self.chunks
+                    .lock()
+                    .unwrap()
+                    .push(chunk.data.clone().unwrap_or_default());
+            }
+        }
+
+        let hooks = ChunkCollector {
+            chunks: std::sync::Mutex::new(Vec::new()),
+        };
+        let executor = DefaultSkillExecutor::with_hooks(StreamingTransport, hooks);
+        let skill = Skill::new("logs", "Tail logs").with_step(SkillStep {
+            name: "tail".to_string(),
+            tool: "tail_logs".to_string(),
+            ..Default::default()
+        });
+
+        let mut context = ExecutionContext::new();
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        assert!(result.success);
+        assert_eq!(executor.hooks().chunks.lock().unwrap().len(), 3);
+        assert_eq!(
+            context.get_output("tail"),
+            Some(&Value::from("line 1\nline 2\nline 3\n"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_records_bounded_snapshots() {
+        let transport = MockTransport::new()
+            .with_response(
+                "search",
+                ToolResult::success(serde_json::json!({"hits": 3})),
+            )
+            .with_response("summarize", ToolResult::success(serde_json::json!("ok")))
+            .with_response("notify", ToolResult::success(serde_json::json!(null)));
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill = Skill::new("research", "Research")
+            .with_step(SkillStep {
+                name: "search".to_string(),
+                tool: "search".to_string(),
+                arguments: serde_json::json!({"q": "{{query}}"}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "summarize".to_string(),
+                tool: "summarize".to_string(),
+                arguments: serde_json::json!({"text": "{{query}}: {{search.hits}} hits"}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "notify".to_string(),
+                tool: "notify".to_string(),
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new()
+            .with_input("query", serde_json::json!("rust"))
+            .with_snapshots(2);
+        executor.execute(&skill, &mut context).await.unwrap();
+
+        // The oldest snapshot was dropped to stay within the limit
+        let snapshots = context.snapshots();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].step_name, "summarize");
+        assert_eq!(snapshots[0].step_index, 1);
+        assert_eq!(
+            snapshots[0].arguments,
+            serde_json::json!({"text": "rust: 3 hits"})
+        );
+        assert!(!snapshots[0].variables.contains_key("summarize"));
+        assert_eq!(
+            snapshots[1].resolve("{{summarize}}").unwrap(),
+            serde_json::json!("ok")
+        );
+
+        // Recording is off by default
+        let mut context = ExecutionContext::new().with_input("query", serde_json::json!("rust"));
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert!(context.snapshots().is_empty());
+    }
+
+    #[derive(Default)]
+    struct CancelRecorder {
+        cancelled: std::sync::Mutex<Vec<Option<String>>>,
+    }
+
+    impl ExecutionHooks for CancelRecorder {
+        fn on_cancel(&self, step: Option<&SkillStep>, _context: &ExecutionContext) {
+            self.cancelled
+                .lock()
+                .unwrap()
+                .push(step.map(|s| s.name.clone()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_cancels_in_flight_step() {
+        let executor = DefaultSkillExecutor::with_hooks(
+            ConcurrencyTransport::default(),
+            CancelRecorder::default(),
+        );
+        let skill = Skill::new("long", "Long running")
+            .with_step(SkillStep {
+                name: "crawl".to_string(),
+                tool: "crawl".to_string(),
+                arguments: serde_json::json!({"delay_ms": 10_000}),
+                continue_on_error: true,
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "report".to_string(),
+                tool: "report".to_string(),
+                ..Default::default()
+            });
+
+        let token = CancellationToken::new();
+        let mut context = ExecutionContext::new().with_cancellation(token.child_token());
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result = executor.execute(&skill, &mut context).await;
+
+        assert!(matches!(result, Err(SkillError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(context.is_cancelled());
+        assert_eq!(executor.transport().calls.lock().unwrap().len(), 1);
+        assert_eq!(
+            *executor.hooks().cancelled.lock().unwrap(),
+            vec![Some("crawl".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_stops_between_steps_when_cancelled() {
+        let executor = DefaultSkillExecutor::with_hooks(
+            ConcurrencyTransport::default(),
+            CancelRecorder::default(),
+        );
+        let skill = Skill::new("short", "Short").with_step(SkillStep {
+            name: "only".to_string(),
+            tool: "only".to_string(),
+            ..Default::default()
+        });
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut context = ExecutionContext::new().with_cancellation(token);
+
+        let result = executor.execute(&skill, &mut context).await;
+        assert!(matches!(result, Err(SkillError::Cancelled)));
+        assert!(executor.transport().calls.lock().unwrap().is_empty());
+        assert_eq!(*executor.hooks().cancelled.lock().unwrap(), vec![None]);
+
+        let result = executor.execute_step(&skill.steps[0], &mut context).await;
+        assert!(matches!(result, Err(SkillError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_retries_through_chaos() {
+        let transport = thulp_core::ChaosTransport::new(
+            MockTransport::new()
+                .with_response("flaky", ToolResult::success(serde_json::json!("ok"))),
+            thulp_core::ChaosConfig::new()
+                .with_error_rate(0.5)
+                .with_seed(3),
+        );
+        let executor = DefaultSkillExecutor::new(transport);
+        let skill = Skill::new("flaky", "Flaky").with_step(SkillStep {
+            name: "call".to_string(),
+            tool: "flaky".to_string(),
+            ..Default::default()
+        });
+        let config = ExecutionConfig {
+            retry: RetryConfig {
+                max_retries: 20,
+                initial_delay: Duration::from_millis(1),
+                backoff: crate::BackoffStrategy::Fixed,
+                retryable_errors: vec![RetryableError::ServerError],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for _ in 0..10 {
+            let mut context = ExecutionContext::new().with_config(config.clone());
+            let result = executor.execute(&skill, &mut context).await.unwrap();
+            assert!(result.success);
+        }
+
+        // Every injected failure was retried until the call got through
+        let stats = executor.transport().stats();
+        assert!(stats.errors > 0);
+        assert_eq!(stats.calls, stats.errors + 10);
+    }
+
+    /// Transport that fails the first call for each `key` argument
+    #[derive(Default)]
+    struct FailOnceTransport {
+        failed: std::sync::Mutex<std::collections::HashSet<String>>,
+        calls: std::sync::Mutex<Vec<ToolCall>>,
+    }
+
+    #[async_trait]
+    impl Transport for FailOnceTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            self.calls.lock().unwrap().push(call.clone());
+            let key = call.arguments["key"].to_string();
+            if self.failed.lock().unwrap().insert(key) {
+                return Err(thulp_core::Error::ExecutionFailed(
+                    "503 page too large".to_string(),
+                ));
+            }
+            Ok(ToolResult::success(call.arguments.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_exposes_retry_state_to_arguments() {
+        let executor = DefaultSkillExecutor::new(FailOnceTransport::default());
+        let skill = Skill::new("adaptive", "Adaptive")
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({
+                    "key": "single",
+                    "attempt": "{{__attempt}}",
+                    "error": "{{__last_error}}",
+                }),
+                max_retries: Some(1),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "each".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({
+                    "key": "{{item}}",
+                    "attempt": "{{__attempt}}",
+                }),
+                for_each: Some("{{keys}}".to_string()),
+                max_retries: Some(1),
+                ..Default::default()
+            });
+        let config = ExecutionConfig {
+            retry: RetryConfig {
+                initial_delay: Duration::from_millis(1),
+                retryable_errors: vec![RetryableError::ServerError],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut context = ExecutionContext::new()
+            .with_input("keys", serde_json::json!(["a", "b"]))
+            .with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert!(result.success);
+
+        let calls = executor.transport().calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 6);
+        assert_eq!(calls[0].arguments["attempt"], 1);
+        assert_eq!(calls[0].arguments["error"], Value::Null);
+        assert_eq!(calls[1].arguments["attempt"], 2);
+        assert_eq!(
+            calls[1].arguments["error"],
+            "tool execution failed: 503 page too large"
+        );
+
+        // Fan-out retries keep their item
+        assert_eq!(
+            context.get_output("each"),
+            Some(&serde_json::json!([
+                {"key": "a", "attempt": 2},
+                {"key": "b", "attempt": 2},
+            ]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_retries_with_strict_templates() {
+        let executor = DefaultSkillExecutor::new(FailOnceTransport::default());
+        let step = SkillStep {
+            name: "fetch".to_string(),
+            tool: "fetch".to_string(),
+            arguments: serde_json::json!({
+                "key": "{{key}}",
+                "attempt": "{{__attempt}}",
+                "error": "{{__last_error}}",
+            }),
+            max_retries: Some(1),
+            ..Default::default()
+        };
+        let skill = Skill::new("strict", "Strict").with_step(step.clone());
+        let config = ExecutionConfig {
+            retry: RetryConfig {
+                initial_delay: Duration::from_millis(1),
+                retryable_errors: vec![RetryableError::ServerError],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .with_template_strictness(TemplateStrictness::Error);
+
+        let mut context = ExecutionContext::new()
+            .with_input("key", serde_json::json!("single"))
+            .with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert!(result.success);
+        let calls = executor.transport().calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].arguments["attempt"], 2);
+
+        // A retry that loses a variable fails instead of sending the placeholder
+        let first = ToolCall::new("fetch");
+        let variables = HashMap::new();
+        let call = ScopedCall {
+            call: &first,
+            variables: &variables,
+            item: None,
+            confirm: false,
+        };
+        let err = call
+            .for_attempt(&step, 2, "503", TemplateStrictness::Error)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SkillError::UnresolvedVariable { ref step, ref variable }
+                if step == "fetch" && variable == "key"
+        ));
+        let lenient = call
+            .for_attempt(&step, 2, "503", TemplateStrictness::PassThrough)
+            .unwrap();
+        assert_eq!(lenient.arguments["key"], "{{key}}");
+    }
+
+    /// Hooks that deny `drop` and tag every other call as approved
+    #[derive(Default)]
+    struct Gatekeeper {
+        asked: std::sync::Mutex<Vec<ToolCall>>,
+    }
+
+    impl ExecutionHooks for Gatekeeper {
+        fn on_confirm_call(
+            &self,
+            _step: &SkillStep,
+            call: &ToolCall,
+            _context: &ExecutionContext,
+        ) -> CallDecision {
+            self.asked.lock().unwrap().push(call.clone());
+            if call.tool == "drop" {
+                return CallDecision::Deny("not today".to_string());
+            }
+            let mut arguments = call.arguments.clone();
+            arguments["approved"] = Value::Bool(true);
+            CallDecision::Modify(arguments)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_confirms_calls() {
+        let executor =
+            DefaultSkillExecutor::with_hooks(FailOnceTransport::default(), Gatekeeper::default());
+        let skill = Skill::new("guarded", "Guarded")
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "fetch".to_string(),
+                arguments: serde_json::json!({"key": "k", "attempt": "{{__attempt}}"}),
+                max_retries: Some(1),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "purge".to_string(),
+                tool: "drop".to_string(),
+                arguments: serde_json::json!({"key": "all"}),
+                continue_on_error: true,
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "note".to_string(),
+                data: Some(serde_json::json!("done")),
+                ..Default::default()
+            });
+        let config = ExecutionConfig::new()
+            .with_confirmation(crate::ConfirmationMode::All)
+            .with_retry(RetryConfig {
+                initial_delay: Duration::from_millis(1),
+                retryable_errors: vec![RetryableError::ServerError],
+                ..Default::default()
+            });
+
+        let mut context = ExecutionContext::new().with_config(config.clone());
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+
+        // Confirmed once, and the retry reuses the modified arguments
+        assert_eq!(executor.hooks().asked.lock().unwrap().len(), 2);
+        assert_eq!(
+            context.get_output("fetch"),
+            Some(&serde_json::json!({"key": "k", "attempt": 1, "approved": true}))
+        );
+        assert!(result.step_results[1]
+            .1
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("denied: not today"));
+        assert_eq!(executor.transport().calls.lock().unwrap().len(), 2);
+
+        // Off by default
+        let executor =
+            DefaultSkillExecutor::with_hooks(FailOnceTransport::default(), Gatekeeper::default());
+        let mut context = ExecutionContext::new()
+            .with_config(config.with_confirmation(crate::ConfirmationMode::Off));
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert!(executor.hooks().asked.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_enforces_budget_across_runs() {
+        let transport = MockTransport::new()
+            .with_response("search", ToolResult::success(serde_json::json!(["a"])))
+            .with_response("read", ToolResult::success(serde_json::json!("text")));
+        let executor = DefaultSkillExecutor::new(transport);
+
+        let skill = Skill::new("research", "Research")
+            .with_step(SkillStep {
+                name: "search".to_string(),
+                tool: "search".to_string(),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "read".to_string(),
+                tool: "read".to_string(),
+                continue_on_error: true,
+                ..Default::default()
+            });
+        let config = ExecutionConfig::new()
+            .with_max_tool_calls(3)
+            .with_tool_cost("search", 2.0);
+
+        let mut context = ExecutionContext::new().with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(result.usage.tool_calls, 2);
+        assert_eq!(result.usage.cost, 3.0);
+        assert_eq!(result.usage.calls_by_tool["search"], 1);
+
+        // The budget spans runs with the same context, and running out
+        // aborts even a step that continues on error
+        let err = executor.execute(&skill, &mut context).await.unwrap_err();
+        assert!(matches!(err, SkillError::BudgetExceeded(_)), "{}", err);
+        assert_eq!(context.usage().tool_calls, 3);
+        assert_eq!(context.usage().calls_by_tool["read"], 1);
+    }
+
+    /// Echoes arguments, logging every call
+    #[derive(Default)]
+    struct EchoTransport {
+        calls: std::sync::Mutex<Vec<ToolCall>>,
+    }
+
+    #[async_trait]
+    impl Transport for EchoTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            self.calls.lock().unwrap().push(call.clone());
+            Ok(ToolResult::success(call.arguments.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_step_cache() {
+        let skill = Skill::new("lookup", "Look up terms")
+            .with_step(SkillStep {
+                name: "each".to_string(),
+                tool: "search".to_string(),
+                arguments: serde_json::json!({"q": "{{item}}", "position": "{{index}}"}),
+                for_each: Some("{{terms}}".to_string()),
+                cache_key: Some("{{item}}".to_string()),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "first".to_string(),
+                tool: "search".to_string(),
+                arguments: serde_json::json!({"q": "c", "limit": 1}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "second".to_string(),
+                tool: "search".to_string(),
+                arguments: serde_json::json!({"limit": 1, "q": "c"}),
+                ..Default::default()
+            });
+        let inputs = HashMap::from([("terms".to_string(), serde_json::json!(["a", "b", "a"]))]);
+        let transport = Arc::new(EchoTransport::default());
+        let executor = DefaultSkillExecutor::from_arcs(transport.clone(), Arc::new(NoOpHooks));
+
+        // Without a cache every call is made
+        let mut context = ExecutionContext::from_inputs(inputs.clone());
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(transport.calls.lock().unwrap().len(), 5);
+        transport.calls.lock().unwrap().clear();
+
+        // The third item hits on its key despite a different position, and
+        // the second step on the first one's arguments
+        let cache = crate::StepCache::new();
+        let mut context =
+            ExecutionContext::from_inputs(inputs.clone()).with_step_cache(cache.clone());
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(transport.calls.lock().unwrap().len(), 3);
+        assert_eq!(
+            context.get_output("each"),
+            Some(&serde_json::json!([
+                {"q": "a", "position": 0},
+                {"q": "b", "position": 1},
+                {"q": "a", "position": 0},
+            ]))
+        );
+        assert!(result.success);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 3, 3));
+
+        // A second run with the same cache makes no calls
+        let mut context = ExecutionContext::from_inputs(inputs).with_step_cache(cache.clone());
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(transport.calls.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_skips_cache_for_unresolved_keys() {
+        let skill = Skill::new("lookup", "Look up terms").with_step(SkillStep {
+            name: "each".to_string(),
+            tool: "search".to_string(),
+            arguments: serde_json::json!({"q": "{{item}}"}),
+            for_each: Some("{{terms}}".to_string()),
+            cache_key: Some("{{missing}}".to_string()),
+            ..Default::default()
+        });
+        let inputs = HashMap::from([("terms".to_string(), serde_json::json!(["a", "b"]))]);
+        let transport = Arc::new(EchoTransport::default());
+        let executor = DefaultSkillExecutor::from_arcs(transport.clone(), Arc::new(NoOpHooks));
+
+        // Both items would share the literal placeholder as their key
+        let cache = crate::StepCache::new();
+        let mut context =
+            ExecutionContext::from_inputs(inputs.clone()).with_step_cache(cache.clone());
+        executor.execute(&skill, &mut context).await.unwrap();
+        assert_eq!(transport.calls.lock().unwrap().len(), 2);
+        assert_eq!(
+            context.get_output("each"),
+            Some(&serde_json::json!([{"q": "a"}, {"q": "b"}]))
+        );
+        assert_eq!(cache.stats().entries, 0);
+
+        // Strict templates fail the step instead
+        let mut context = ExecutionContext::from_inputs(inputs)
+            .with_step_cache(cache)
+            .with_config(
+                ExecutionConfig::default().with_template_strictness(TemplateStrictness::Error),
+            );
+        let err = executor.execute(&skill, &mut context).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Step 'each' references unresolved template variable 'missing'"));
+        assert_eq!(transport.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_resolves_secrets() {
+        let skill = Skill::new("issues", "List issues").with_step(SkillStep {
+            name: "list".to_string(),
+            tool: "github.list_issues".to_string(),
+            arguments: serde_json::json!({"auth": "Bearer {{secret:github_token}}"}),
+            ..Default::default()
+        });
+        let transport = Arc::new(EchoTransport {
+            calls: Default::default(),
+        });
+        let executor = DefaultSkillExecutor::from_arcs(transport.clone(), Arc::new(NoOpHooks));
+        let secrets = HashMap::from([("github_token".to_string(), "ghp_123".to_string())]);
+
+        let mut context = ExecutionContext::new()
+            .with_secrets(Arc::new(secrets))
+            .with_snapshots(1);
+        let redactor = context.secret_redactor().clone();
+        executor.execute(&skill, &mut context).await.unwrap();
+
+        assert_eq!(
+            transport.calls.lock().unwrap()[0].arguments,
+            serde_json::json!({"auth": "Bearer ghp_123"})
+        );
+        assert_eq!(
+            context.snapshots()[0].arguments,
+            serde_json::json!({"auth": "Bearer [REDACTED]"})
+        );
+        assert_eq!(redactor.len(), 1);
+
+        let err = executor
+            .execute(&skill, &mut ExecutionContext::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SkillError::UnresolvedVariable { variable, .. } if variable == "secret:github_token"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_resolves_workspace_variables() {
+        let skill = Skill::new("issues", "List issues").with_step(SkillStep {
+            name: "list".to_string(),
+            tool: "tracker.list_issues".to_string(),
+            arguments: serde_json::json!({
+                "project": "{{workspace.context.project_id}}",
+                "team": "{{workspace.metadata.team | default('none')}}"
+            }),
+            condition: Some("{{workspace.context.enabled}}".to_string()),
+            ..Default::default()
+        });
+        let transport = Arc::new(EchoTransport {
+            calls: Default::default(),
+        });
+        let executor = DefaultSkillExecutor::from_arcs(transport.clone(), Arc::new(NoOpHooks));
+
+        let mut context = ExecutionContext::new().with_workspace(serde_json::json!({
+            "name": "demo",
+            "metadata": {},
+            "context": {"project_id": "p-42", "enabled": true}
+        }));
+        executor.execute(&skill, &mut context).await.unwrap();
+
+        assert_eq!(
+            transport.calls.lock().unwrap()[0].arguments,
+            serde_json::json!({"project": "p-42", "team": "none"})
+        );
+    }
+
+    /// Approves steps whose arguments don't name the `users` table
+    struct TableGuard;
+
+    #[async_trait]
+    impl crate::ApprovalHandler for TableGuard {
+        async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision {
+            if request.arguments["table"] == "users" {
+                ApprovalDecision::denied("users is off limits")
+            } else {
+                ApprovalDecision::Approved
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_executor_approval_gates() {
+        let drop_step = |name: &str, table: &str| SkillStep {
+            name: name.to_string(),
+            tool: "db.drop_table".to_string(),
+            arguments: serde_json::json!({"table": table, "auth": "{{secret:token}}"}),
+            requires_approval: true,
+            ..Default::default()
+        };
+        let skill = Skill::new("cleanup", "Drop tables")
+            .with_step(drop_step("logs", "logs"))
+            .with_step(drop_step("users", "users"))
+            .with_step(SkillStep {
+                name: "report".to_string(),
+                tool: "notify".to_string(),
+                ..Default::default()
+            });
+        let transport = Arc::new(EchoTransport {
+            calls: Default::default(),
+        });
+        let executor = DefaultSkillExecutor::from_arcs(transport.clone(), Arc::new(NoOpHooks));
+        let secrets = HashMap::from([("token".to_string(), "t0k3n".to_string())]);
+
+        let mut context = ExecutionContext::new()
+            .with_secrets(Arc::new(secrets.clone()))
+            .with_approval_handler(Arc::new(TableGuard));
+        let err = executor.execute(&skill, &mut context).await.unwrap_err();
+        assert!(matches!(
+            err.root(),
+            SkillError::ApprovalDenied { step, reason } if step == "users" && reason == "users is off limits"
+        ));
+        assert_eq!(err.code(), "approval_denied");
+        assert_eq!(transport.calls.lock().unwrap().len(), 1);
+
+        let approvals = context.approvals();
+        assert_eq!(approvals.len(), 2);
+        assert!(approvals[0].decision.is_approved());
+        assert_eq!(
+            approvals[1].request.arguments,
+            serde_json::json!({"table": "users", "auth": "[REDACTED]"})
+        );
+
+        // Denied steps that continue on error let the run go on
+        let mut lenient = skill.clone();
+        lenient.steps[0].continue_on_error = true;
+        lenient.steps[1].continue_on_error = true;
+        let mut context = ExecutionContext::new().with_secrets(Arc::new(secrets));
+        let result = executor.execute(&lenient, &mut context).await.unwrap();
+        let succeeded: Vec<_> = result.step_results.iter().map(|(_, r)| r.success).collect();
+        assert_eq!(succeeded, vec![false, false, true]);
+        // Without a handler both gated steps were denied
+        assert!(context
+            .approvals()
+            .iter()
+            .all(|r| !r.decision.is_approved()));
+    }
 }
@@ -0,0 +1,343 @@
+//! What a run lost to the failures it tolerated.
+//!
+//! A step that fails with [`continue_on_error`](crate::SkillStep::continue_on_error)
+//! set, or under [`TimeoutAction::Skip`](crate::TimeoutAction::Skip), doesn't
+//! stop the run: the skill still succeeds, but without that step's output.
+//! Each such failure is listed in [`SkillResult::degradations`] with what it
+//! cost, so callers can decide whether the partial output is usable:
+//!
+//! - the later steps that fell back, through a `default(...)` filter on the
+//!   failed step's output or a condition reading it
+//! - the later steps that ran without values they read from it
+//! - whether the skill was left without an output, when the failed step was
+//!   the last to run
+//!
+//! ```json
+//! {
+//!   "step": "search",
+//!   "error": "Tool execution failed: index unavailable",
+//!   "action": "continue_on_error",
+//!   "fallbacks": [{ "step": "summarize", "path": "search.items", "kind": "default" }],
+//!   "missing": [{ "step": "notify", "path": "search.count" }]
+//! }
+//! ```
+//!
+//! Only the templates of steps that ran afterwards are considered; steps
+//! skipped by their condition lose nothing.
+//!
+//! [`SkillResult::degradations`]: crate::SkillResult::degradations
+
+use crate::plan::path_root;
+use crate::template::placeholder_paths;
+use crate::{Skill, SkillStep};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thulp_core::ToolResult;
+
+/// A step failure the run carried on from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Degradation {
+    /// Step that failed
+    pub step: String,
+
+    /// Why it failed
+    pub error: String,
+
+    /// Why the run carried on
+    pub action: DegradationAction,
+
+    /// Later steps that stood in for the missing output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<Fallback>,
+
+    /// Later steps that ran without values they read from the output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<MissingOutput>,
+
+    /// The failed step was the last to run, so the skill has no output
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub output_lost: bool,
+}
+
+/// Why a run carried on after a step failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradationAction {
+    /// The step sets `continue_on_error`.
+    ContinueOnError,
+
+    /// The run's timeout action is [`Skip`](crate::TimeoutAction::Skip).
+    Skip,
+}
+
+/// A later step that stood in for a failed step's output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fallback {
+    /// Step that fell back
+    pub step: String,
+
+    /// Path it read, e.g. `search.items`
+    pub path: String,
+
+    /// How it fell back
+    pub kind: FallbackKind,
+}
+
+/// How a step fell back on a missing output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackKind {
+    /// A `default(...)` filter supplied the value.
+    Default,
+
+    /// The step's condition read the output, so it ran because of the
+    /// failure, like an `else` branch.
+    Condition,
+}
+
+/// A value a later step read from a failed step and didn't get
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingOutput {
+    /// Step that ran without it
+    pub step: String,
+
+    /// Path it read, e.g. `search.count`
+    pub path: String,
+}
+
+impl Degradation {
+    /// A failure of `step` that the run carried on from, before looking at
+    /// later steps
+    pub(crate) fn new(step: &SkillStep, error: impl Into<String>) -> Self {
+        Self {
+            step: step.name.clone(),
+            error: error.into(),
+            action: if step.continue_on_error {
+                DegradationAction::ContinueOnError
+            } else {
+                DegradationAction::Skip
+            },
+            fallbacks: Vec::new(),
+            missing: Vec::new(),
+            output_lost: false,
+        }
+    }
+
+    /// Check whether later steps only ran on stand-in values or not at all
+    /// because of the failure, rather than on missing ones.
+    pub fn is_covered(&self) -> bool {
+        self.missing.is_empty() && !self.output_lost
+    }
+}
+
+/// Fill in what the steps that ran after each failure, listed in
+/// `step_results` in order, made of the missing output
+pub(crate) fn trace_consequences(
+    skill: &Skill,
+    degradations: &mut [Degradation],
+    step_results: &[(String, ToolResult)],
+) {
+    for degradation in degradations {
+        let Some(at) = step_results
+            .iter()
+            .rposition(|(name, _)| *name == degradation.step)
+        else {
+            continue;
+        };
+        degradation.output_lost = at + 1 == step_results.len();
+
+        for (name, _) in &step_results[at + 1..] {
+            let Some(step) = skill.steps.iter().find(|s| s.name == *name) else {
+                continue;
+            };
+            let reads = |paths: Vec<(String, bool)>| {
+                paths
+                    .into_iter()
+                    .filter(|(path, _)| path_root(path) == degradation.step)
+                    .collect::<Vec<_>>()
+            };
+
+            if let Some(condition) = &step.condition {
+                for (path, _) in reads(placeholder_paths(&Value::String(condition.clone()))) {
+                    let fallback = Fallback {
+                        step: step.name.clone(),
+                        path,
+                        kind: FallbackKind::Condition,
+                    };
+                    if !degradation.fallbacks.contains(&fallback) {
+                        degradation.fallbacks.push(fallback);
+                    }
+                }
+            }
+
+            let mut paths = placeholder_paths(&step.arguments);
+            if let Some(for_each) = &step.for_each {
+                paths.extend(placeholder_paths(&Value::String(for_each.clone())));
+            }
+            for (path, defaulted) in reads(paths) {
+                if defaulted {
+                    let fallback = Fallback {
+                        step: step.name.clone(),
+                        path,
+                        kind: FallbackKind::Default,
+                    };
+                    if !degradation.fallbacks.contains(&fallback) {
+                        degradation.fallbacks.push(fallback);
+                    }
+                } else {
+                    let missing = MissingOutput {
+                        step: step.name.clone(),
+                        path,
+                    };
+                    if !degradation.missing.contains(&missing) {
+                        degradation.missing.push(missing);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DegradationAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContinueOnError => write!(f, "continue_on_error"),
+            Self::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+impl std::fmt::Display for Degradation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "step '{}' failed ({}): {}",
+            self.step, self.action, self.error
+        )?;
+        for fallback in &self.fallbacks {
+            match fallback.kind {
+                FallbackKind::Default => write!(
+                    f,
+                    "\n  '{}' used a default for '{}'",
+                    fallback.step, fallback.path
+                )?,
+                FallbackKind::Condition => write!(
+                    f,
+                    "\n  '{}' ran on a condition reading '{}'",
+                    fallback.step, fallback.path
+                )?,
+            }
+        }
+        for missing in &self.missing {
+            write!(f, "\n  '{}' ran without '{}'", missing.step, missing.path)?;
+        }
+        if self.output_lost {
+            write!(f, "\n  the skill has no output")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn step(name: &str, arguments: Value) -> SkillStep {
+        SkillStep {
+            name: name.to_string(),
+            tool: name.to_string(),
+            arguments,
+            ..Default::default()
+        }
+    }
+
+    fn ran(names: &[&str]) -> Vec<(String, ToolResult)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), ToolResult::success(json!(null))))
+            .collect()
+    }
+
+    #[test]
+    fn test_trace_consequences() {
+        let mut search = step("search", json!({"q": "rust"}));
+        search.continue_on_error = true;
+        let skill = Skill::new("digest", "Search and summarize")
+            .with_step(search)
+            .with_step(step(
+                "summarize",
+                json!({"items": "{{search.items | default([])}}", "q": "{{query}}"}),
+            ))
+            .with_step(SkillStep {
+                condition: Some("{{search}} == null".to_string()),
+                ..step("apologize", json!({}))
+            })
+            .with_step(step("notify", json!({"text": "Found {{search.count}}"})))
+            .with_step(SkillStep {
+                condition: Some("{{search.count}} > 0".to_string()),
+                ..step("celebrate", json!({"n": "{{search.count}}"}))
+            });
+
+        let mut degradations = vec![Degradation::new(&skill.steps[0], "index unavailable")];
+        trace_consequences(
+            &skill,
+            &mut degradations,
+            &ran(&["search", "summarize", "apologize", "notify"]),
+        );
+
+        let degradation = &degradations[0];
+        assert_eq!(degradation.action, DegradationAction::ContinueOnError);
+        assert_eq!(
+            degradation.fallbacks,
+            vec![
+                Fallback {
+                    step: "summarize".to_string(),
+                    path: "search.items".to_string(),
+                    kind: FallbackKind::Default,
+                },
+                Fallback {
+                    step: "apologize".to_string(),
+                    path: "search".to_string(),
+                    kind: FallbackKind::Condition,
+                },
+            ]
+        );
+        // `celebrate` was skipped, so only `notify` went without
+        assert_eq!(
+            degradation.missing,
+            vec![MissingOutput {
+                step: "notify".to_string(),
+                path: "search.count".to_string(),
+            }]
+        );
+        assert!(!degradation.output_lost);
+        assert!(!degradation.is_covered());
+        assert_eq!(
+            degradation.to_string(),
+            "step 'search' failed (continue_on_error): index unavailable\n  \
+             'summarize' used a default for 'search.items'\n  \
+             'apologize' ran on a condition reading 'search'\n  \
+             'notify' ran without 'search.count'"
+        );
+    }
+
+    #[test]
+    fn test_last_step_loses_output() {
+        let skill = Skill::new("fetch", "Fetch").with_step(step("fetch", json!({})));
+        let mut degradations = vec![Degradation::new(&skill.steps[0], "timed out")];
+        trace_consequences(&skill, &mut degradations, &ran(&["fetch"]));
+
+        assert_eq!(degradations[0].action, DegradationAction::Skip);
+        assert!(degradations[0].output_lost);
+        assert_eq!(
+            serde_json::to_value(&degradations[0]).unwrap(),
+            json!({
+                "step": "fetch",
+                "error": "timed out",
+                "action": "skip",
+                "output_lost": true
+            })
+        );
+    }
+}
@@ -0,0 +1,533 @@
+//! Comparing a dry-run plan with the calls a run actually made.
+//!
+//! [`ExecutionPlan::diff`] lines up each tool step of a
+//! [plan](crate::plan) with the calls recorded for it in an
+//! [`ExecutionTrace`] and reports where the run went another way:
+//!
+//! - steps that made no calls, because their condition didn't hold or the
+//!   run stopped before them
+//! - calls repeated after failing, and steps whose last call failed, which
+//!   the run only survives with `continue_on_error`
+//! - calls to another tool, or with other values for the arguments the
+//!   plan resolved, as after the skill was edited
+//! - calls no planned step accounts for
+//!
+//! Planning the current skill with the inputs of a golden trace shows what
+//! an edit changes before running it:
+//!
+//! ```rust,ignore
+//! let trace = ExecutionTrace::load(Path::new("tests/traces/report.json"))?;
+//! let diff = skill.plan(&trace.inputs, None)?.diff(&trace);
+//! if !diff.matches_plan() {
+//!     println!("{}", diff);
+//! }
+//! ```
+//!
+//! Arguments left as placeholders in the plan match any value, and the
+//! arguments of `for_each` steps aren't compared. Calls made by the steps
+//! of nested skills aren't in the parent's plan, so they are listed as
+//! unplanned.
+
+use crate::{ExecutionPlan, ExecutionTrace, PlannedStep, RecordedCall};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Differences between an [`ExecutionPlan`] and a recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanDiff {
+    /// Skill name
+    pub skill: String,
+
+    /// Tool steps of the plan, in plan order
+    pub steps: Vec<StepDiff>,
+
+    /// Recorded calls that no planned tool step made
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unplanned: Vec<UnplannedCall>,
+}
+
+impl PlanDiff {
+    /// Check if the run made the planned calls and nothing else.
+    pub fn matches_plan(&self) -> bool {
+        self.unplanned.is_empty() && self.steps.iter().all(|s| s.divergences.is_empty())
+    }
+
+    /// Get the comparison of a step by name.
+    pub fn step(&self, name: &str) -> Option<&StepDiff> {
+        self.steps.iter().find(|s| s.step == name)
+    }
+}
+
+/// How the calls of one planned step went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepDiff {
+    /// Step name
+    pub step: String,
+
+    /// Tool the plan calls
+    pub tool: String,
+
+    /// Number of calls recorded for the step
+    pub calls: usize,
+
+    /// Ways the calls differ from the plan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub divergences: Vec<Divergence>,
+}
+
+/// One way a step's calls differ from its plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Divergence {
+    /// The step made no calls and has a condition, which didn't hold
+    Skipped { condition: String },
+
+    /// The step made no calls: the run stopped before it, its `for_each`
+    /// array was empty, or its result came from the step cache
+    NotCalled,
+
+    /// The calls went to another tool
+    ToolChanged { called: String },
+
+    /// An argument the plan resolved had another value; `None` where one
+    /// side has no such argument
+    ArgumentChanged {
+        path: String,
+        planned: Option<Value>,
+        actual: Option<Value>,
+    },
+
+    /// Calls were made again after failing
+    Retried { retries: usize, errors: Vec<String> },
+
+    /// The step's last call failed
+    Failed { error: String },
+}
+
+/// A recorded call that no planned tool step made
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnplannedCall {
+    /// Step the call was recorded for, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+
+    /// Tool called
+    pub tool: String,
+
+    /// Arguments of the call
+    pub arguments: Value,
+}
+
+impl ExecutionPlan {
+    /// Compare the plan with the calls recorded in `trace`; see
+    /// [`diff`](crate::diff).
+    pub fn diff(&self, trace: &ExecutionTrace) -> PlanDiff {
+        let planned: HashSet<&str> = self
+            .steps
+            .iter()
+            .filter(|s| s.tool.is_some())
+            .map(|s| s.name.as_str())
+            .collect();
+
+        let steps = self
+            .steps
+            .iter()
+            .filter_map(|step| {
+                let tool = step.tool.as_ref()?;
+                let calls: Vec<_> = trace
+                    .calls
+                    .iter()
+                    .filter(|c| c.step.as_deref() == Some(step.name.as_str()))
+                    .collect();
+                Some(StepDiff {
+                    step: step.name.clone(),
+                    tool: tool.clone(),
+                    calls: calls.len(),
+                    divergences: compare_step(step, tool, &calls),
+                })
+            })
+            .collect();
+
+        let unplanned = trace
+            .calls
+            .iter()
+            .filter(|c| !c.step.as_deref().is_some_and(|s| planned.contains(s)))
+            .map(|c| UnplannedCall {
+                step: c.step.clone(),
+                tool: c.call.tool.clone(),
+                arguments: c.call.arguments.clone(),
+            })
+            .collect();
+
+        PlanDiff {
+            skill: self.skill.clone(),
+            steps,
+            unplanned,
+        }
+    }
+}
+
+fn compare_step(step: &PlannedStep, tool: &str, calls: &[&RecordedCall]) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    let Some(first) = calls.first() else {
+        divergences.push(match &step.condition {
+            Some(condition) => Divergence::Skipped {
+                condition: condition.clone(),
+            },
+            None => Divergence::NotCalled,
+        });
+        return divergences;
+    };
+
+    if let Some(call) = calls.iter().find(|c| c.call.tool != tool) {
+        divergences.push(Divergence::ToolChanged {
+            called: call.call.tool.clone(),
+        });
+    }
+
+    if step.for_each.is_none() {
+        compare_arguments(
+            "",
+            &step.arguments,
+            Some(&first.call.arguments),
+            &mut divergences,
+        );
+    }
+
+    // A `for_each` step calls once per item, so only calls repeating
+    // earlier arguments are retries
+    let attempts = match &step.for_each {
+        Some(_) => {
+            let mut seen: Vec<&Value> = Vec::new();
+            for call in calls {
+                if !seen.contains(&&call.call.arguments) {
+                    seen.push(&call.call.arguments);
+                }
+            }
+            seen.len()
+        }
+        None => 1,
+    };
+    if calls.len() > attempts {
+        divergences.push(Divergence::Retried {
+            retries: calls.len() - attempts,
+            errors: calls.iter().filter_map(|c| c.error.clone()).collect(),
+        });
+    }
+
+    // The step failed if the last attempt at any of its calls failed
+    let failed = calls.iter().enumerate().find_map(|(i, call)| {
+        let error = call.error.as_ref()?;
+        let again = calls[i + 1..]
+            .iter()
+            .any(|later| later.call.arguments == call.call.arguments || step.for_each.is_none());
+        (!again).then(|| error.clone())
+    });
+    if let Some(error) = failed {
+        divergences.push(Divergence::Failed { error });
+    }
+
+    divergences
+}
+
+/// Compare the resolved parts of planned arguments with actual ones, at
+/// `path`
+fn compare_arguments(
+    path: &str,
+    planned: &Value,
+    actual: Option<&Value>,
+    divergences: &mut Vec<Divergence>,
+) {
+    match (planned, actual) {
+        // Filled in while running
+        (Value::String(s), _) if s.contains("{{") => {}
+        (Value::Object(planned), Some(Value::Object(actual))) => {
+            for (key, value) in planned {
+                compare_arguments(&join(path, key), value, actual.get(key), divergences);
+            }
+            for (key, value) in actual.iter().filter(|(k, _)| !planned.contains_key(*k)) {
+                divergences.push(Divergence::ArgumentChanged {
+                    path: join(path, key),
+                    planned: None,
+                    actual: Some(value.clone()),
+                });
+            }
+        }
+        (Value::Array(planned), Some(Value::Array(actual))) if planned.len() == actual.len() => {
+            for (i, (planned, actual)) in planned.iter().zip(actual).enumerate() {
+                let path = format!("{}[{}]", path, i);
+                compare_arguments(&path, planned, Some(actual), divergences);
+            }
+        }
+        (planned, actual) if actual != Some(planned) => {
+            divergences.push(Divergence::ArgumentChanged {
+                path: path.to_string(),
+                planned: Some(planned.clone()),
+                actual: actual.cloned(),
+            });
+        }
+        _ => {}
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "nothing".to_string(),
+        };
+        match self {
+            Self::Skipped { condition } => write!(f, "skipped, {} didn't hold", condition),
+            Self::NotCalled => write!(f, "not called"),
+            Self::ToolChanged { called } => write!(f, "called {} instead", called),
+            Self::ArgumentChanged {
+                path,
+                planned,
+                actual,
+            } => {
+                let path = if path.is_empty() { "arguments" } else { path };
+                write!(
+                    f,
+                    "{}: planned {}, got {}",
+                    path,
+                    show(planned),
+                    show(actual)
+                )
+            }
+            Self::Retried { retries, errors } => {
+                write!(f, "retried {} time(s)", retries)?;
+                match errors.last() {
+                    Some(error) => write!(f, " after: {}", error),
+                    None => Ok(()),
+                }
+            }
+            Self::Failed { error } => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+impl std::fmt::Display for PlanDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Plan vs. run of skill '{}'", self.skill)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            let calls = if step.calls == 1 { "call" } else { "calls" };
+            writeln!(
+                f,
+                "  {}. {} -> {}: {} {}",
+                i + 1,
+                step.step,
+                step.tool,
+                step.calls,
+                calls
+            )?;
+            for divergence in &step.divergences {
+                writeln!(f, "     {}", divergence)?;
+            }
+        }
+        if !self.unplanned.is_empty() {
+            writeln!(f, "Unplanned calls:")?;
+            for call in &self.unplanned {
+                let step = call.step.as_deref().unwrap_or("?");
+                writeln!(f, "  - {} -> {} {}", step, call.tool, call.arguments)?;
+            }
+        }
+        if self.matches_plan() {
+            writeln!(f, "The run made the planned calls")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Skill, SkillStep};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use thulp_core::ToolCall;
+
+    fn step(name: &str, tool: &str, arguments: Value) -> SkillStep {
+        SkillStep {
+            name: name.to_string(),
+            tool: tool.to_string(),
+            arguments,
+            ..Default::default()
+        }
+    }
+
+    fn call(step: &str, tool: &str, arguments: Value, error: Option<&str>) -> RecordedCall {
+        RecordedCall {
+            step: Some(step.to_string()),
+            call: ToolCall::with_args(tool, arguments),
+            result: None,
+            error: error.map(str::to_string),
+            duration_ms: 1,
+        }
+    }
+
+    fn skill() -> Skill {
+        Skill::new("research", "")
+            .with_input("query")
+            .with_step(step(
+                "search",
+                "search",
+                json!({"q": "{{query}}", "limit": 10}),
+            ))
+            .with_step(SkillStep {
+                condition: Some("{{search.count}} > 0".to_string()),
+                ..step("summarize", "summarize", json!({"text": "{{search}}"}))
+            })
+            .with_step(SkillStep {
+                condition: Some("{{search.count}} == 0".to_string()),
+                ..step("fallback", "ask", json!({"q": "{{query}}"}))
+            })
+            .with_step(SkillStep {
+                for_each: Some("{{search.links}}".to_string()),
+                ..step("visit", "http_get", json!({"url": "{{item}}"}))
+            })
+    }
+
+    #[test]
+    fn test_diff_matching_run() {
+        let inputs = HashMap::from([("query".to_string(), json!("rust"))]);
+        let plan = skill().plan(&inputs, None).unwrap();
+        let trace = ExecutionTrace {
+            calls: vec![
+                call("search", "search", json!({"q": "rust", "limit": 10}), None),
+                call("summarize", "summarize", json!({"text": "[]"}), None),
+                call("visit", "http_get", json!({"url": "a"}), None),
+            ],
+            ..Default::default()
+        };
+
+        let diff = plan.diff(&trace);
+        // Only the fallback, whose condition didn't hold, diverges
+        assert!(!diff.matches_plan());
+        let divergent: Vec<_> = diff
+            .steps
+            .iter()
+            .filter(|s| !s.divergences.is_empty())
+            .map(|s| s.step.as_str())
+            .collect();
+        assert_eq!(divergent, ["fallback"]);
+        assert_eq!(
+            diff.step("fallback").unwrap().divergences,
+            vec![Divergence::Skipped {
+                condition: "{{search.count}} == 0".to_string()
+            }]
+        );
+        assert!(diff.to_string().contains(
+            "3. fallback -> ask: 0 calls\n     skipped, {{search.count}} == 0 didn't hold"
+        ));
+    }
+
+    #[test]
+    fn test_diff_reports_divergences() {
+        let inputs = HashMap::from([("query".to_string(), json!("rust"))]);
+        let plan = skill().plan(&inputs, None).unwrap();
+        let busy = "tool execution failed: busy";
+        let trace = ExecutionTrace {
+            calls: vec![
+                call(
+                    "search",
+                    "search",
+                    json!({"q": "rust", "limit": 5}),
+                    Some(busy),
+                ),
+                call("search", "search", json!({"q": "rust", "limit": 5}), None),
+                call("fallback", "ask", json!({"q": "rust"}), None),
+                call("visit", "http_get", json!({"url": "a"}), Some(busy)),
+                call("visit", "http_get", json!({"url": "b"}), None),
+                call("visit", "http_get", json!({"url": "a"}), Some(busy)),
+                call("nested", "lookup", json!({}), None),
+            ],
+            ..Default::default()
+        };
+
+        let diff = plan.diff(&trace);
+        assert_eq!(
+            diff.step("search").unwrap().divergences,
+            vec![
+                Divergence::ArgumentChanged {
+                    path: "limit".to_string(),
+                    planned: Some(json!(10)),
+                    actual: Some(json!(5)),
+                },
+                Divergence::Retried {
+                    retries: 1,
+                    errors: vec![busy.to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            diff.step("summarize").unwrap().divergences[0],
+            Divergence::Skipped {
+                condition: "{{search.count}} > 0".to_string()
+            }
+        );
+        assert!(diff.step("fallback").unwrap().divergences.is_empty());
+
+        let visit = diff.step("visit").unwrap();
+        assert_eq!(visit.calls, 3);
+        assert_eq!(
+            visit.divergences,
+            vec![
+                Divergence::Retried {
+                    retries: 1,
+                    errors: vec![busy.to_string(); 2],
+                },
+                Divergence::Failed {
+                    error: busy.to_string()
+                },
+            ]
+        );
+        assert_eq!(diff.unplanned.len(), 1);
+        assert_eq!(diff.unplanned[0].tool, "lookup");
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            json["steps"][0]["divergences"][0]["kind"],
+            "argument_changed"
+        );
+    }
+
+    #[test]
+    fn test_diff_after_edit() {
+        // The trace was recorded before `search` moved to another tool
+        let edited = Skill::new("research", "").with_step(step("search", "web_search", json!({})));
+        let plan = edited.plan(&HashMap::new(), None).unwrap();
+        let trace = ExecutionTrace {
+            calls: vec![call("search", "search", json!({"q": "rust"}), None)],
+            success: Some(true),
+            ..Default::default()
+        };
+
+        let divergences = &plan.diff(&trace).steps[0].divergences;
+        assert_eq!(
+            divergences,
+            &vec![
+                Divergence::ToolChanged {
+                    called: "search".to_string()
+                },
+                Divergence::ArgumentChanged {
+                    path: "q".to_string(),
+                    planned: None,
+                    actual: Some(json!("rust")),
+                },
+            ]
+        );
+        assert_eq!(
+            divergences[1].to_string(),
+            "q: planned nothing, got \"rust\""
+        );
+    }
+}
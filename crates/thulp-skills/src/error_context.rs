@@ -0,0 +1,138 @@
+//! Where in a run a step error happened.
+//!
+//! When a step fails the run, the executor wraps the error in
+//! [`SkillError::StepFailed`] with a [`StepErrorContext`]: the skill, the
+//! step and its position, the tool call that failed and how many attempts
+//! were made. Arguments are redacted, by default with
+//! [`PathRedactor::sensitive_defaults`](thulp_core::PathRedactor::sensitive_defaults),
+//! so the message is safe to log as one line:
+//!
+//! ```text
+//! skill 'deploy' step 2 'push' calling registry.push {"image":"app:1.4","token":"[REDACTED]"} after 3 attempts: Step 'push' failed after 3 attempts: connection reset
+//! ```
+//!
+//! A failing nested skill adds one layer per level, outermost first. Use
+//! [`SkillError::root`] to get at the original error, and
+//! [`std::error::Error::source`] to walk the chain.
+//!
+//! [`SkillError::StepFailed`]: crate::SkillError::StepFailed
+//! [`SkillError::root`]: crate::SkillError::root
+
+use std::fmt;
+use thulp_core::{Redactor, ToolCall};
+
+use crate::SkillError;
+
+/// The skill, step and call a step error happened on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepErrorContext {
+    /// Skill the step belongs to
+    pub skill: String,
+    /// Name of the step
+    pub step: String,
+    /// Position of the step in the skill, from 0
+    pub index: usize,
+    /// Call that failed, with redacted arguments; `None` when the step
+    /// failed before calling a tool or doesn't call one
+    pub call: Option<ToolCall>,
+    /// Tool calls made for the call, retries included
+    pub attempts: usize,
+}
+
+impl StepErrorContext {
+    /// Context of step `index` of `skill`, with no call.
+    pub fn new(skill: impl Into<String>, step: impl Into<String>, index: usize) -> Self {
+        Self {
+            skill: skill.into(),
+            step: step.into(),
+            index,
+            call: None,
+            attempts: 0,
+        }
+    }
+
+    /// Set the failed call, redacting its arguments with `redactor`.
+    pub fn with_call(mut self, call: &ToolCall, redactor: &dyn Redactor) -> Self {
+        self.call = Some(ToolCall {
+            tool: call.tool.clone(),
+            arguments: redactor.redact(&call.arguments),
+        });
+        self
+    }
+
+    /// Set the number of attempts made.
+    pub fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Wrap `error` with this context.
+    pub fn wrap(self, error: SkillError) -> SkillError {
+        SkillError::StepFailed {
+            context: Box::new(self),
+            source: Box::new(error),
+        }
+    }
+}
+
+impl fmt::Display for StepErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "skill '{}' step {} '{}'",
+            self.skill,
+            self.index + 1,
+            self.step
+        )?;
+        if let Some(call) = &self.call {
+            write!(f, " calling {} {}", call.tool, call.arguments)?;
+        }
+        match self.attempts {
+            0 => Ok(()),
+            1 => write!(f, " after 1 attempt"),
+            attempts => write!(f, " after {} attempts", attempts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::error::Error;
+    use thulp_core::PathRedactor;
+
+    #[test]
+    fn test_step_error_context() {
+        let call = ToolCall::with_args("registry.push", json!({"image": "app", "token": "t0p"}));
+        let error = StepErrorContext::new("deploy", "push", 1)
+            .with_call(&call, &PathRedactor::sensitive_defaults())
+            .with_attempts(3)
+            .wrap(SkillError::Execution("connection reset".to_string()));
+
+        assert_eq!(
+            error.to_string(),
+            "skill 'deploy' step 2 'push' calling registry.push \
+             {\"image\":\"app\",\"token\":\"[REDACTED]\"} after 3 attempts: \
+             Execution error: connection reset"
+        );
+        assert_eq!(error.code(), "execution_failed");
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_nested_step_errors() {
+        let inner = StepErrorContext::new("fetch", "get", 0)
+            .with_attempts(1)
+            .wrap(SkillError::Cancelled);
+        let outer = StepErrorContext::new("report", "run_fetch", 2).wrap(inner);
+
+        assert_eq!(
+            outer.to_string(),
+            "skill 'report' step 3 'run_fetch': skill 'fetch' step 1 'get' after 1 attempt: \
+             Execution cancelled"
+        );
+        assert!(matches!(outer.root(), SkillError::Cancelled));
+        assert_eq!(outer.step_context().unwrap().skill, "report");
+    }
+}
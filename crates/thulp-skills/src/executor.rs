@@ -19,8 +19,20 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use thulp_core::{Redactor, SecretRedactor, SecretResolver};
+use tokio_util::sync::CancellationToken;
 
-use crate::{ExecutionConfig, Skill, SkillError, SkillResult, SkillStep};
+use crate::approval::Approvals;
+use crate::budget::UsageTracker;
+use crate::secrets::Secrets;
+use crate::{
+    ApprovalHandler, ApprovalRecord, ContextSnapshot, ExecutionConfig, ExecutionUsage, Skill,
+    SkillError, SkillResult, SkillStep, StepCache, ToolListing,
+};
+
+/// Variable holding the workspace set with [`ExecutionContext::with_workspace`].
+pub const WORKSPACE_VARIABLE: &str = "workspace";
 
 /// Result of executing a single step.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +109,37 @@ pub struct ExecutionContext {
 
     /// Optional metadata for tracking/debugging
     metadata: HashMap<String, Value>,
+
+    /// Maximum number of snapshots to keep (`None` disables recording)
+    snapshot_limit: Option<usize>,
+
+    /// Snapshots recorded at step boundaries, oldest first
+    snapshots: Vec<ContextSnapshot>,
+
+    /// Token checked between steps and raced against tool calls
+    cancellation: CancellationToken,
+
+    /// Tool calls, cost and time used so far, shared by clones
+    usage: Arc<UsageTracker>,
+
+    /// Number of parent skills running this one as a step
+    depth: usize,
+
+    /// Memoized step results, shared by clones
+    step_cache: Option<StepCache>,
+
+    /// Source of `{{secret:name}}` values and the values resolved so far
+    secrets: Secrets,
+
+    /// Workspace context and metadata, read as `{{workspace.…}}`
+    workspace: Option<Value>,
+
+    /// Handler for gated steps and the decisions taken, shared by clones
+    approvals: Approvals,
+
+    /// Tools listed by the transport during the current run, shared by
+    /// clones and nested skills
+    tools: ToolListing,
 }
 
 impl Default for ExecutionContext {
@@ -113,6 +156,16 @@ impl ExecutionContext {
             outputs: HashMap::new(),
             config: ExecutionConfig::default(),
             metadata: HashMap::new(),
+            snapshot_limit: None,
+            snapshots: Vec::new(),
+            cancellation: CancellationToken::new(),
+            usage: Arc::default(),
+            depth: 0,
+            step_cache: None,
+            secrets: Secrets::default(),
+            workspace: None,
+            approvals: Approvals::default(),
+            tools: ToolListing::default(),
         }
     }
 
@@ -120,9 +173,7 @@ impl ExecutionContext {
     pub fn from_inputs(inputs: HashMap<String, Value>) -> Self {
         Self {
             inputs,
-            outputs: HashMap::new(),
-            config: ExecutionConfig::default(),
-            metadata: HashMap::new(),
+            ..Self::new()
         }
     }
 
@@ -132,12 +183,161 @@ impl ExecutionContext {
         self
     }
 
+    /// Set an input value.
+    pub fn set_input(&mut self, key: impl Into<String>, value: Value) {
+        self.inputs.insert(key.into(), value);
+    }
+
     /// Set the execution configuration.
     pub fn with_config(mut self, config: ExecutionConfig) -> Self {
         self.config = config;
         self
     }
 
+    /// Use a cancellation token to stop execution from elsewhere.
+    ///
+    /// Cancelling the token (or any parent it was derived from with
+    /// [`CancellationToken::child_token`]) stops the run before the next step
+    /// and interrupts in-flight tool calls and retry delays.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Get the cancellation token for this execution.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Check whether execution has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Memoize successful tool step results in `cache`.
+    ///
+    /// Pass clones of one cache to several contexts to share results
+    /// between runs; see [`step_cache`](crate::step_cache).
+    pub fn with_step_cache(mut self, cache: StepCache) -> Self {
+        self.step_cache = Some(cache);
+        self
+    }
+
+    /// Get the step cache, if memoization is enabled.
+    pub fn step_cache(&self) -> Option<&StepCache> {
+        self.step_cache.as_ref()
+    }
+
+    /// Resolve `{{secret:name}}` placeholders in step arguments with
+    /// `resolver`; see [`secrets`](crate::secrets).
+    pub fn with_secrets(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secrets = self.secrets.with_resolver(resolver);
+        self
+    }
+
+    /// Ask `handler` before running steps that
+    /// [require approval](SkillStep::requires_approval); see
+    /// [`approval`](crate::approval).
+    pub fn with_approval_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.approvals = self.approvals.with_handler(handler);
+        self
+    }
+
+    /// Get the approval decisions taken so far, in the order they were
+    /// taken.
+    ///
+    /// Clones and nested skills share the decisions, so a clone taken
+    /// before a run sees those taken during it.
+    pub fn approvals(&self) -> Vec<ApprovalRecord> {
+        self.approvals.records()
+    }
+
+    /// Get the approval handler and decisions.
+    pub(crate) fn approval_gate(&self) -> &Approvals {
+        &self.approvals
+    }
+
+    /// Make `workspace` available to step templates as
+    /// `{{workspace.…}}`, such as `{{workspace.context.project_id}}`.
+    ///
+    /// Usually the template variables of a `thulp_workspace::Workspace`:
+    /// its `id`, `name`, `root`, `metadata` and `context`. Inputs and step
+    /// outputs named `workspace` take precedence.
+    pub fn with_workspace(mut self, workspace: Value) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Get the workspace variables, if set.
+    pub fn workspace(&self) -> Option<&Value> {
+        self.workspace.as_ref()
+    }
+
+    /// Get the redactor masking the secrets resolved for steps so far.
+    ///
+    /// Clones share the secrets, so a clone taken before a run masks the
+    /// values resolved during it.
+    pub fn secret_redactor(&self) -> &SecretRedactor {
+        self.secrets.redactor()
+    }
+
+    /// Get the secrets available to steps.
+    pub(crate) fn secrets(&self) -> &Secrets {
+        &self.secrets
+    }
+
+    /// Get the tool calls, cost and time used by runs with this context.
+    ///
+    /// Usage accumulates across runs and is shared with clones of the
+    /// context; see [`budget`](crate::budget).
+    pub fn usage(&self) -> ExecutionUsage {
+        self.usage.usage()
+    }
+
+    /// Get the tracker that budgets are charged to.
+    pub(crate) fn usage_tracker(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Get the transport's tools as listed during the current run.
+    pub(crate) fn tool_listing(&self) -> &ToolListing {
+        &self.tools
+    }
+
+    /// Forget the tools listed by a previous run, so the next one lists
+    /// them again.
+    pub(crate) fn reset_tool_listing(&mut self) {
+        self.tools = ToolListing::default();
+    }
+
+    /// Get how many parent skills are running this one as a step; 0 for a
+    /// top-level run.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Create the context for a skill run as a step of this one.
+    ///
+    /// The child starts with only `inputs`, and shares the configuration,
+    /// metadata, budgets, step cache, secrets, workspace, approvals and
+    /// cancellation of this context, as well as its tool listing.
+    pub(crate) fn nested(&self, inputs: HashMap<String, Value>) -> Self {
+        Self {
+            inputs,
+            config: self.config.clone(),
+            metadata: self.metadata.clone(),
+            cancellation: self.cancellation.child_token(),
+            usage: self.usage.clone(),
+            depth: self.depth + 1,
+            step_cache: self.step_cache.clone(),
+            secrets: self.secrets.clone(),
+            workspace: self.workspace.clone(),
+            approvals: self.approvals.clone(),
+            tools: self.tools.clone(),
+            ..Self::new()
+        }
+    }
+
     /// Add metadata.
     pub fn with_metadata(mut self, key: impl Into<String>, value: Value) -> Self {
         self.metadata.insert(key.into(), value);
@@ -194,11 +394,17 @@ impl ExecutionContext {
         self.metadata.insert(key.into(), value);
     }
 
-    /// Get a combined view of inputs and outputs for variable substitution.
+    /// Get a combined view of the workspace, inputs and outputs for variable
+    /// substitution.
     ///
-    /// Outputs take precedence over inputs if there are key conflicts.
+    /// Outputs take precedence over inputs, and both over the
+    /// [workspace](Self::with_workspace), if there are key conflicts.
     pub fn variables(&self) -> HashMap<String, Value> {
-        let mut vars = self.inputs.clone();
+        let mut vars = HashMap::new();
+        if let Some(workspace) = &self.workspace {
+            vars.insert(WORKSPACE_VARIABLE.to_string(), workspace.clone());
+        }
+        vars.extend(self.inputs.clone());
         vars.extend(self.outputs.clone());
         vars
     }
@@ -207,6 +413,42 @@ impl ExecutionContext {
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
     }
+
+    /// Record a snapshot before each step, keeping at most `limit`.
+    ///
+    /// See [`snapshot`](crate::snapshot) for how to inspect them.
+    pub fn with_snapshots(mut self, limit: usize) -> Self {
+        self.snapshot_limit = Some(limit);
+        self
+    }
+
+    /// Get the recorded snapshots, oldest first.
+    pub fn snapshots(&self) -> &[ContextSnapshot] {
+        &self.snapshots
+    }
+
+    /// Record a snapshot of the current variables before `step` runs.
+    ///
+    /// Secrets resolved so far are masked in the arguments. Does nothing
+    /// unless snapshots were enabled with [`with_snapshots`](Self::with_snapshots).
+    pub fn record_snapshot(&mut self, step_index: usize, step: &SkillStep, arguments: Value) {
+        let Some(limit) = self.snapshot_limit else {
+            return;
+        };
+        if limit == 0 {
+            return;
+        }
+        if self.snapshots.len() >= limit {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(ContextSnapshot {
+            step_index,
+            step_name: step.name.clone(),
+            tool: step.tool.clone(),
+            variables: self.variables(),
+            arguments: self.secrets.redactor().redact(&arguments),
+        });
+    }
 }
 
 /// Trait for executing skills.
@@ -373,6 +615,28 @@ mod tests {
         assert_eq!(vars.get("shared"), Some(&serde_json::json!("from_output")));
     }
 
+    #[test]
+    fn test_execution_context_workspace() {
+        let workspace = serde_json::json!({"context": {"project_id": "p-1"}});
+        let context = ExecutionContext::new().with_workspace(workspace.clone());
+
+        let vars = context.variables();
+        assert_eq!(vars.get(WORKSPACE_VARIABLE), Some(&workspace));
+        assert_eq!(
+            context
+                .nested(HashMap::new())
+                .variables()
+                .get(WORKSPACE_VARIABLE),
+            Some(&workspace)
+        );
+
+        let shadowed = context.with_input("workspace", serde_json::json!("mine"));
+        assert_eq!(
+            shadowed.variables().get(WORKSPACE_VARIABLE),
+            Some(&serde_json::json!("mine"))
+        );
+    }
+
     #[test]
     fn test_execution_context_metadata() {
         let context = ExecutionContext::new()
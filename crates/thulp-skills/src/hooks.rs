@@ -21,7 +21,29 @@
 //! }
 //! ```
 
-use crate::{ExecutionContext, Skill, SkillError, SkillResult, SkillStep, StepResult};
+use crate::{
+    ApprovalDecision, ApprovalRecord, ExecutionContext, Skill, SkillError, SkillResult, SkillStep,
+    StepResult,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use thulp_core::{Redactor, ToolCall, ToolResult};
+
+/// Answer to [`ExecutionHooks::on_confirm_call`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallDecision {
+    /// Make the call as prepared.
+    Allow,
+
+    /// Refuse the call, failing the step with the given reason.
+    Deny(String),
+
+    /// Make the call with these arguments instead.
+    ///
+    /// Retries reuse the replaced arguments rather than rendering the
+    /// step's templates again.
+    Modify(Value),
+}
 
 /// Lifecycle hooks for skill execution.
 ///
@@ -79,6 +101,15 @@ pub trait ExecutionHooks: Send + Sync {
     /// * `context` - The current execution context
     fn before_step(&self, _step: &SkillStep, _step_index: usize, _context: &ExecutionContext) {}
 
+    /// Called when a step is skipped because its condition evaluated to false.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The step that was skipped
+    /// * `step_index` - Zero-based index of the step in the skill
+    /// * `context` - The current execution context
+    fn on_skip(&self, _step: &SkillStep, _step_index: usize, _context: &ExecutionContext) {}
+
     /// Called after a step completes execution (success or failure).
     ///
     /// # Arguments
@@ -132,6 +163,65 @@ pub trait ExecutionHooks: Send + Sync {
     /// * `duration_ms` - How long the step ran before timing out
     /// * `context` - The current execution context
     fn on_timeout(&self, _step: &SkillStep, _duration_ms: u64, _context: &ExecutionContext) {}
+
+    /// Called for each partial result a step's tool streams back.
+    ///
+    /// Transports that don't stream deliver the whole result as one chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The step producing output
+    /// * `chunk` - The partial result
+    /// * `context` - The current execution context
+    fn on_chunk(&self, _step: &SkillStep, _chunk: &ToolResult, _context: &ExecutionContext) {}
+
+    /// Called when execution stops because its cancellation token fired.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The step that was interrupted, or `None` if cancellation
+    ///   was noticed between steps
+    /// * `context` - The current execution context
+    fn on_cancel(&self, _step: Option<&SkillStep>, _context: &ExecutionContext) {}
+
+    /// Called before a tool call is sent to the transport, when the
+    /// execution config's [`ConfirmationMode`](crate::ConfirmationMode)
+    /// selects it.
+    ///
+    /// Interactive frontends can show the exact arguments and let the user
+    /// approve, refuse or edit the call. Each `for_each` item is confirmed
+    /// separately; retries of a confirmed call are not.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The step making the call
+    /// * `call` - The tool and fully rendered arguments
+    /// * `context` - The current execution context
+    fn on_confirm_call(
+        &self,
+        _step: &SkillStep,
+        _call: &ToolCall,
+        _context: &ExecutionContext,
+    ) -> CallDecision {
+        CallDecision::Allow
+    }
+
+    /// Called when a step that
+    /// [requires approval](SkillStep::requires_approval) was approved or
+    /// denied, before it runs or fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The gated step
+    /// * `record` - The request, decision and time spent waiting
+    /// * `context` - The current execution context
+    fn on_approval(
+        &self,
+        _step: &SkillStep,
+        _record: &ApprovalRecord,
+        _context: &ExecutionContext,
+    ) {
+    }
 }
 
 /// A no-op implementation of [`ExecutionHooks`].
@@ -153,11 +243,24 @@ impl ExecutionHooks for NoOpHooks {}
 /// A hooks implementation that logs execution events using tracing.
 ///
 /// This provides observability into skill execution without requiring
-/// custom hook implementations.
-#[derive(Debug, Clone, Copy, Default)]
+/// custom hook implementations. Debug messages include inputs, step arguments
+/// and outputs; configure a [`Redactor`] to mask secrets in them.
+#[derive(Clone, Default)]
 pub struct TracingHooks {
     /// Log level for debug messages
     include_debug: bool,
+
+    /// Redactor applied to values before they are logged
+    redactor: Option<Arc<dyn Redactor>>,
+}
+
+impl std::fmt::Debug for TracingHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TracingHooks")
+            .field("include_debug", &self.include_debug)
+            .field("redactor", &self.redactor.is_some())
+            .finish()
+    }
 }
 
 impl TracingHooks {
@@ -165,6 +268,7 @@ impl TracingHooks {
     pub fn new() -> Self {
         Self {
             include_debug: false,
+            redactor: None,
         }
     }
 
@@ -173,6 +277,20 @@ impl TracingHooks {
         self.include_debug = true;
         self
     }
+
+    /// Mask sensitive values in logged inputs, arguments and outputs.
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Apply the configured redactor to a value about to be logged.
+    fn redact(&self, value: &Value) -> Value {
+        match &self.redactor {
+            Some(redactor) => redactor.redact(value),
+            None => value.clone(),
+        }
+    }
 }
 
 impl ExecutionHooks for TracingHooks {
@@ -185,9 +303,10 @@ impl ExecutionHooks for TracingHooks {
         );
 
         if self.include_debug {
+            let inputs = serde_json::to_value(context.inputs()).unwrap_or_default();
             tracing::debug!(
                 skill_name = %skill.name,
-                inputs = ?context.inputs().keys().collect::<Vec<_>>(),
+                inputs = %self.redact(&inputs),
                 "Skill inputs"
             );
         }
@@ -218,6 +337,23 @@ impl ExecutionHooks for TracingHooks {
             tool = %step.tool,
             "Starting step execution"
         );
+
+        if self.include_debug {
+            tracing::debug!(
+                step_name = %step.name,
+                arguments = %self.redact(&step.arguments),
+                "Step arguments"
+            );
+        }
+    }
+
+    fn on_skip(&self, step: &SkillStep, step_index: usize, _context: &ExecutionContext) {
+        tracing::info!(
+            step_name = %step.name,
+            step_index = step_index,
+            condition = ?step.condition,
+            "Skipping step, condition not met"
+        );
     }
 
     fn after_step(
@@ -235,6 +371,16 @@ impl ExecutionHooks for TracingHooks {
                 retry_attempts = result.retry_attempts,
                 "Step completed successfully"
             );
+
+            if self.include_debug {
+                if let Some(output) = &result.output {
+                    tracing::debug!(
+                        step_name = %step.name,
+                        output = %self.redact(output),
+                        "Step output"
+                    );
+                }
+            }
         } else {
             tracing::warn!(
                 step_name = %step.name,
@@ -269,6 +415,40 @@ impl ExecutionHooks for TracingHooks {
             "Step timed out"
         );
     }
+
+    fn on_cancel(&self, step: Option<&SkillStep>, _context: &ExecutionContext) {
+        tracing::warn!(
+            step_name = step.map(|s| s.name.as_str()).unwrap_or("-"),
+            "Execution cancelled"
+        );
+    }
+
+    fn on_approval(&self, step: &SkillStep, record: &ApprovalRecord, _context: &ExecutionContext) {
+        match &record.decision {
+            ApprovalDecision::Approved => tracing::info!(
+                step_name = %step.name,
+                waited_ms = record.waited_ms,
+                "Step approved"
+            ),
+            ApprovalDecision::Denied { reason } => tracing::warn!(
+                step_name = %step.name,
+                waited_ms = record.waited_ms,
+                reason = %reason,
+                "Step denied"
+            ),
+        }
+    }
+
+    fn on_chunk(&self, step: &SkillStep, chunk: &ToolResult, _context: &ExecutionContext) {
+        if self.include_debug {
+            let data = chunk.data.clone().unwrap_or_default();
+            tracing::debug!(
+                step_name = %step.name,
+                chunk = %self.redact(&data),
+                "Step output chunk"
+            );
+        }
+    }
 }
 
 /// Compose multiple hooks implementations.
@@ -317,6 +497,12 @@ impl ExecutionHooks for CompositeHooks {
         }
     }
 
+    fn on_skip(&self, step: &SkillStep, step_index: usize, context: &ExecutionContext) {
+        for h in &self.hooks {
+            h.on_skip(step, step_index, context);
+        }
+    }
+
     fn after_step(
         &self,
         step: &SkillStep,
@@ -346,11 +532,57 @@ impl ExecutionHooks for CompositeHooks {
             h.on_timeout(step, duration_ms, context);
         }
     }
+
+    fn on_chunk(&self, step: &SkillStep, chunk: &ToolResult, context: &ExecutionContext) {
+        for h in &self.hooks {
+            h.on_chunk(step, chunk, context);
+        }
+    }
+
+    fn on_cancel(&self, step: Option<&SkillStep>, context: &ExecutionContext) {
+        for h in &self.hooks {
+            h.on_cancel(step, context);
+        }
+    }
+
+    fn on_approval(&self, step: &SkillStep, record: &ApprovalRecord, context: &ExecutionContext) {
+        for h in &self.hooks {
+            h.on_approval(step, record, context);
+        }
+    }
+
+    /// Asks each hook in turn, passing on modified arguments; the first
+    /// denial wins.
+    fn on_confirm_call(
+        &self,
+        step: &SkillStep,
+        call: &ToolCall,
+        context: &ExecutionContext,
+    ) -> CallDecision {
+        let mut modified: Option<ToolCall> = None;
+        for h in &self.hooks {
+            match h.on_confirm_call(step, modified.as_ref().unwrap_or(call), context) {
+                CallDecision::Allow => {}
+                CallDecision::Deny(reason) => return CallDecision::Deny(reason),
+                CallDecision::Modify(arguments) => {
+                    modified = Some(ToolCall {
+                        tool: call.tool.clone(),
+                        arguments,
+                    });
+                }
+            }
+        }
+        match modified {
+            Some(call) => CallDecision::Modify(call.arguments),
+            None => CallDecision::Allow,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ExecutionUsage;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
@@ -369,6 +601,8 @@ mod tests {
                 step_results: vec![],
                 output: None,
                 error: None,
+                usage: ExecutionUsage::default(),
+                degradations: Vec::new(),
             },
             &context,
         );
@@ -383,6 +617,20 @@ mod tests {
         assert!(hooks_with_debug.include_debug);
     }
 
+    #[test]
+    fn test_tracing_hooks_redactor() {
+        let hooks = TracingHooks::new().with_debug().with_redactor(Arc::new(
+            thulp_core::PathRedactor::new().with_path("auth.token"),
+        ));
+
+        let redacted = hooks.redact(&serde_json::json!({"auth": {"token": "t"}, "q": 1}));
+        assert_eq!(redacted["auth"]["token"], thulp_core::REDACTED);
+        assert_eq!(redacted["q"], 1);
+
+        let plain = TracingHooks::new().redact(&serde_json::json!({"auth": {"token": "t"}}));
+        assert_eq!(plain["auth"]["token"], "t");
+    }
+
     #[test]
     fn test_composite_hooks() {
         struct CountingHooks {
@@ -427,6 +675,8 @@ mod tests {
             step_results: vec![],
             output: None,
             error: None,
+            usage: ExecutionUsage::default(),
+            degradations: Vec::new(),
         };
 
         hooks.before_skill(&skill, &context);
@@ -447,4 +697,62 @@ mod tests {
         // Should not panic with empty hooks list
         hooks.before_skill(&skill, &context);
     }
+
+    #[test]
+    fn test_composite_hooks_confirm_call() {
+        struct AddFlag(&'static str);
+
+        impl ExecutionHooks for AddFlag {
+            fn on_confirm_call(
+                &self,
+                _step: &SkillStep,
+                call: &ToolCall,
+                _context: &ExecutionContext,
+            ) -> CallDecision {
+                let mut arguments = call.arguments.clone();
+                arguments[self.0] = Value::Bool(true);
+                CallDecision::Modify(arguments)
+            }
+        }
+
+        struct DenyAll;
+
+        impl ExecutionHooks for DenyAll {
+            fn on_confirm_call(
+                &self,
+                _step: &SkillStep,
+                _call: &ToolCall,
+                _context: &ExecutionContext,
+            ) -> CallDecision {
+                CallDecision::Deny("no".to_string())
+            }
+        }
+
+        let step = SkillStep::default();
+        let call = ToolCall::new("write");
+        let context = ExecutionContext::new();
+
+        assert_eq!(
+            CompositeHooks::new()
+                .with(NoOpHooks)
+                .on_confirm_call(&step, &call, &context),
+            CallDecision::Allow
+        );
+
+        // Later hooks see earlier modifications
+        let hooks = CompositeHooks::new()
+            .with(AddFlag("a"))
+            .with(NoOpHooks)
+            .with(AddFlag("b"));
+        assert_eq!(
+            hooks.on_confirm_call(&step, &call, &context),
+            CallDecision::Modify(serde_json::json!({"a": true, "b": true}))
+        );
+
+        let hooks = CompositeHooks::new().with(AddFlag("a")).with(DenyAll);
+        assert_eq!(
+            hooks.on_confirm_call(&step, &call, &context),
+            CallDecision::Deny("no".to_string())
+        );
+    }
 }
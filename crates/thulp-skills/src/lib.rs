@@ -8,10 +8,35 @@
 //!
 //! - **Skill Composition**: Define multi-step workflows as skills
 //! - **Timeout Support**: Prevent hanging executions with configurable timeouts
-//! - **Retry Logic**: Handle transient failures with exponential backoff
+//! - **Retry Logic**: Handle transient failures with exponential backoff, adapting
+//!   arguments with `{{__attempt}}` and `{{__last_error}}` (see [`retry`])
 //! - **Context Propagation**: Pass results between steps using template variables
+//! - **Templates**: Nested paths and filters such as `{{search.items[0].id | default(0)}}`, see [`template`]
+//! - **Conditional Steps**: Skip steps based on earlier outputs with [`evaluate_condition`]
+//! - **Input Defaults**: Fill in missing inputs and derive new ones with [`evaluate_expression`]
+//! - **Data Steps**: Inject literal JSON or file contents with [`SkillStep::data`] and [`SkillStep::data_file`]
 //! - **Pluggable Execution**: Use [`SkillExecutor`] trait for custom execution strategies
-//! - **Lifecycle Hooks**: Observe execution with [`ExecutionHooks`]
+//! - **Lifecycle Hooks**: Observe execution with [`ExecutionHooks`], or as a stream of [`ProgressEvent`]s with [`ProgressHooks`]
+//! - **Metrics**: OpenTelemetry metrics and spans with `MetricsHooks` (`metrics` feature)
+//! - **Reports**: A versioned JSON form of results for other programs, see [`report`]
+//! - **Recording and Replay**: Capture a run's tool calls and replay them offline, see [`trace`]
+//! - **Error Context**: Step errors name the skill, step, redacted call and attempts, see [`error_context`]
+//! - **Secrets**: `{{secret:name}}` in arguments, masked in snapshots and errors, see [`secrets`]
+//! - **Workspace Variables**: Workspace context and metadata as `{{workspace.context.project_id}}`,
+//!   see [`ExecutionContext::with_workspace`]
+//! - **Step Cache**: Memoize step results, keyed by arguments or a [`SkillStep::cache_key`], see [`step_cache`]
+//! - **Approval Gates**: Pause before [`SkillStep::requires_approval`] steps until an
+//!   [`ApprovalHandler`] approves them, see [`approval`]
+//! - **Cancellation**: Stop a running skill with a [`CancellationToken`]
+//! - **Concurrent Runs**: Launch, poll, cancel and await several skill runs with a
+//!   [`SkillRunManager`], see [`runs`]
+//! - **Budgets**: Cap tool calls, time and weighted cost per run, see [`budget`]
+//! - **Dry Runs**: Resolve inputs and templates and check tools without executing, see [`plan`]
+//! - **Plan Diffs**: Compare a plan with a recorded run's calls, reporting skipped, retried,
+//!   failed and changed calls, see [`diff`]
+//! - **Degradation Reports**: Failures a run carried on from, with the fallbacks used and
+//!   the outputs missing, in [`SkillResult::degradations`], see [`degradation`]
+//! - **Nested Skills**: Run another skill as a step with [`SkillStep::skill`]
 //!
 //! ## Example
 //!
@@ -42,28 +67,76 @@
 //! let result = executor.execute(&skill, &mut context).await?;
 //! ```
 
+pub mod approval;
+pub mod budget;
+pub mod condition;
 pub mod config;
 pub mod default_executor;
+pub mod degradation;
+pub mod diff;
+pub mod error_context;
 pub mod executor;
 pub mod hooks;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod plan;
+pub mod progress;
+pub mod report;
 pub mod retry;
+pub mod runs;
+pub mod secrets;
+pub mod snapshot;
+pub mod step_cache;
+pub mod template;
 pub mod timeout;
+pub mod trace;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thulp_core::ToolResult;
 
-use thulp_core::{ToolCall, Transport};
+use thulp_core::{Parameter, ToolCall, ToolDefinition, Transport};
 
+pub use approval::{
+    ApprovalDecision, ApprovalHandler, ApprovalRecord, ApprovalRequest, AutoApprove, AutoDeny,
+};
+pub use budget::ExecutionUsage;
+pub use condition::{evaluate_condition, evaluate_expression};
 pub use config::{
-    BackoffStrategy, ExecutionConfig, RetryConfig, RetryableError, TimeoutAction, TimeoutConfig,
+    BackoffStrategy, ConfirmationMode, ExecutionConfig, ExecutionSettings, RetryConfig,
+    RetryableError, TemplateStrictness, TimeoutAction, TimeoutConfig, DEFAULT_MAX_SKILL_DEPTH,
 };
 pub use default_executor::DefaultSkillExecutor;
-pub use executor::{ExecutionContext, SkillExecutor, StepResult};
-pub use hooks::{CompositeHooks, ExecutionHooks, NoOpHooks, TracingHooks};
-pub use retry::{calculate_delay, is_error_retryable, with_retry, RetryError};
+pub use degradation::{Degradation, DegradationAction, Fallback, FallbackKind, MissingOutput};
+pub use diff::{Divergence, PlanDiff, StepDiff, UnplannedCall};
+pub use error_context::StepErrorContext;
+pub use executor::{ExecutionContext, SkillExecutor, StepResult, WORKSPACE_VARIABLE};
+pub use hooks::{CallDecision, CompositeHooks, ExecutionHooks, NoOpHooks, TracingHooks};
+#[cfg(feature = "metrics")]
+pub use metrics::MetricsHooks;
+pub use plan::{ExecutionPlan, PlanIssue, PlannedStep};
+pub use progress::{ProgressEvent, ProgressHooks};
+pub use report::{ReportError, ReportStatus, SkillReport, StepReport, REPORT_VERSION};
+pub use retry::{
+    calculate_delay, is_error_retryable, with_retry, RetryError, ATTEMPT_VARIABLE,
+    LAST_ERROR_VARIABLE,
+};
+pub use runs::{RunHandle, RunId, RunInfo, RunOutcome, RunStatus, SkillRunManager};
+pub use secrets::SECRET_PREFIX;
+pub use snapshot::{ContextSnapshot, SnapshotLog};
+pub use step_cache::{StepCache, StepCacheStats};
+pub use template::{render_template, render_template_checked};
+
+use template::render_step_template;
 pub use timeout::{with_timeout, with_timeout_infallible, TimeoutError};
+pub use tokio_util::sync::CancellationToken;
+pub use trace::{
+    ExecutionTrace, RecordedCall, RecordingHooks, RecordingTransport, ReplayTransport,
+    TraceRecorder,
+};
 
 #[cfg(test)]
 use async_trait::async_trait;
@@ -98,6 +171,163 @@ pub enum SkillError {
         attempts: usize,
         message: String,
     },
+
+    #[error("Output of skill '{skill}' does not match its schema: {}", .errors.join("; "))]
+    OutputValidation { skill: String, errors: Vec<String> },
+
+    #[error("Execution cancelled")]
+    Cancelled,
+
+    #[error("Tool '{0}' is destructive and cannot run in read-only mode")]
+    ReadOnly(String),
+
+    #[error("Step '{step}' references unresolved template variable '{variable}'")]
+    UnresolvedVariable { step: String, variable: String },
+
+    #[error("Call to tool '{tool}' in step '{step}' was denied: {reason}")]
+    CallDenied {
+        step: String,
+        tool: String,
+        reason: String,
+    },
+
+    #[error("Step '{step}' was not approved: {reason}")]
+    ApprovalDenied { step: String, reason: String },
+
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(String),
+
+    #[error("Skill '{skill}' would nest deeper than {max_depth} levels")]
+    SkillDepthExceeded { skill: String, max_depth: usize },
+
+    /// A step failed the run; see [`error_context`]
+    #[error("{context}: {source}")]
+    StepFailed {
+        context: Box<StepErrorContext>,
+        source: Box<SkillError>,
+    },
+}
+
+impl SkillError {
+    /// Stable, machine-readable name of the error kind, as used in
+    /// [`report::SkillReport`]; a [`StepFailed`](SkillError::StepFailed)
+    /// error has the code of the error it wraps
+    pub fn code(&self) -> &'static str {
+        match self {
+            SkillError::Execution(_) => "execution_failed",
+            SkillError::NotFound(_) => "skill_not_found",
+            SkillError::InvalidConfig(_) => "invalid_config",
+            SkillError::StepTimeout { .. } => "step_timeout",
+            SkillError::SkillTimeout { .. } => "skill_timeout",
+            SkillError::RetryExhausted { .. } => "retry_exhausted",
+            SkillError::OutputValidation { .. } => "output_invalid",
+            SkillError::Cancelled => "cancelled",
+            SkillError::ReadOnly(_) => "read_only",
+            SkillError::UnresolvedVariable { .. } => "unresolved_variable",
+            SkillError::CallDenied { .. } => "call_denied",
+            SkillError::ApprovalDenied { .. } => "approval_denied",
+            SkillError::BudgetExceeded(_) => "budget_exceeded",
+            SkillError::SkillDepthExceeded { .. } => "depth_exceeded",
+            SkillError::StepFailed { source, .. } => source.code(),
+        }
+    }
+
+    /// The original error, under any [`StepFailed`](SkillError::StepFailed)
+    /// context
+    pub fn root(&self) -> &SkillError {
+        match self {
+            SkillError::StepFailed { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// Context of the outermost step that failed, if any
+    pub fn step_context(&self) -> Option<&StepErrorContext> {
+        match self {
+            SkillError::StepFailed { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+}
+
+/// Name of a JSON value's type, for error messages
+pub(crate) fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// The transport's tools, listed at most once per execution for the
+/// read-only and confirmation checks. Clones share the listing.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolListing(
+    Arc<tokio::sync::OnceCell<std::result::Result<Vec<ToolDefinition>, String>>>,
+);
+
+impl ToolListing {
+    /// Get the tools, listing them on first use; a failed listing is kept too
+    async fn tools<T: Transport + ?Sized>(
+        &self,
+        transport: &T,
+    ) -> std::result::Result<&[ToolDefinition], &str> {
+        let listed = self
+            .0
+            .get_or_init(|| async { transport.list_tools().await.map_err(|e| e.to_string()) })
+            .await;
+        listed.as_deref().map_err(String::as_str)
+    }
+}
+
+/// Check whether calls to a tool need confirmation under the config's
+/// [`ConfirmationMode`].
+pub(crate) async fn needs_confirmation<T: Transport + ?Sized>(
+    listing: &ToolListing,
+    transport: &T,
+    tool: &str,
+    config: &ExecutionConfig,
+) -> bool {
+    match config.confirmation {
+        ConfirmationMode::Off => false,
+        ConfirmationMode::All => true,
+        ConfirmationMode::Destructive => match listing.tools(transport).await {
+            Ok(tools) => tools.iter().any(|t| t.name == tool && t.destructive),
+            Err(_) => true,
+        },
+    }
+}
+
+/// Refuse a destructive tool when running in read-only mode.
+///
+/// Fails closed: if the transport can't list its tools, or doesn't list
+/// this one, nothing runs.
+pub(crate) async fn check_read_only<T: Transport + ?Sized>(
+    listing: &ToolListing,
+    transport: &T,
+    tool: &str,
+    config: &ExecutionConfig,
+) -> Result<()> {
+    if !config.read_only {
+        return Ok(());
+    }
+    let tools = listing.tools(transport).await.map_err(|e| {
+        SkillError::Execution(format!(
+            "Cannot verify tool '{}' in read-only mode: {}",
+            tool, e
+        ))
+    })?;
+    match tools.iter().find(|t| t.name == tool) {
+        Some(definition) if definition.destructive => Err(SkillError::ReadOnly(tool.to_string())),
+        Some(_) => Ok(()),
+        None => Err(SkillError::Execution(format!(
+            "Cannot verify tool '{}' in read-only mode: the transport doesn't list it",
+            tool
+        ))),
+    }
 }
 
 /// A step in a skill workflow
@@ -106,9 +336,21 @@ pub struct SkillStep {
     /// Step name/identifier
     pub name: String,
 
-    /// Tool to execute
+    /// Tool to execute; data steps and nested skills leave it empty
+    #[serde(default)]
     pub tool: String,
 
+    /// Skill to run instead of a tool, looked up in the executor's
+    /// [`SkillRegistry`].
+    ///
+    /// The rendered arguments become the child skill's inputs and its
+    /// output becomes this step's output. The child runs in a context of
+    /// its own, sharing the parent's configuration, budgets and
+    /// cancellation; see
+    /// [`DefaultSkillExecutor::with_skills`](crate::DefaultSkillExecutor::with_skills).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+
     /// Arguments for the tool (can reference previous step outputs)
     #[serde(default)]
     pub arguments: Value,
@@ -117,6 +359,11 @@ pub struct SkillStep {
     #[serde(default)]
     pub continue_on_error: bool,
 
+    /// Pause before the step until an [`ApprovalHandler`] approves it; see
+    /// [`approval`]
+    #[serde(default)]
+    pub requires_approval: bool,
+
     /// Optional per-step timeout override (in seconds)
     #[serde(default)]
     pub timeout_secs: Option<u64>,
@@ -124,6 +371,128 @@ pub struct SkillStep {
     /// Optional per-step max retries override
     #[serde(default)]
     pub max_retries: Option<usize>,
+
+    /// Names of steps that must complete before this one runs.
+    ///
+    /// When any step in a skill declares dependencies, the skill is executed
+    /// as a DAG and steps without a path between them run concurrently.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// Optional condition that must hold for the step to run,
+    /// e.g. `{{search.count}} > 0` (see [`evaluate_condition`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+
+    /// Optional array to iterate over, e.g. `{{search.results}}`.
+    ///
+    /// The tool is called once per item with `{{item}}` and `{{index}}`
+    /// available in the arguments, and the step output is the array of
+    /// per-item results in item order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_each: Option<String>,
+
+    /// Maximum number of `for_each` items in flight at once (default: 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+
+    /// Literal value to output instead of calling a tool.
+    ///
+    /// A step with `data` or [`data_file`](Self::data_file) is a data step:
+    /// its tool, arguments and `for_each` are ignored, and the value is
+    /// stored under the step name like any other output. Useful for
+    /// constants, lookup tables and test fixtures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+
+    /// File to output the contents of, relative to
+    /// [`ExecutionConfig::data_dir`].
+    ///
+    /// `.json`, `.yaml` and `.yml` files are parsed; anything else is
+    /// output as a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<String>,
+
+    /// Template for the key the step's result is memoized under, e.g.
+    /// `{{query}}`, instead of the tool and all its arguments.
+    ///
+    /// Only used when the context has a [`StepCache`]; see [`step_cache`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_key: Option<String>,
+}
+
+impl SkillStep {
+    /// Check whether this is a data step that outputs a value without
+    /// calling a tool.
+    pub fn is_data(&self) -> bool {
+        self.data.is_some() || self.data_file.is_some()
+    }
+
+    /// Check whether this step runs another skill instead of a tool.
+    pub fn is_nested(&self) -> bool {
+        self.skill.is_some() && !self.is_data()
+    }
+
+    /// Load the output of a data step, or `None` for a tool step.
+    ///
+    /// Relative [`data_file`](Self::data_file) paths are resolved against
+    /// `data_dir`, or the current directory without one.
+    pub fn load_data(&self, data_dir: Option<&Path>) -> Result<Option<Value>> {
+        let file = match (&self.data, &self.data_file) {
+            (None, None) => return Ok(None),
+            (Some(data), None) => return Ok(Some(data.clone())),
+            (None, Some(file)) => file,
+            (Some(_), Some(_)) => {
+                return Err(SkillError::InvalidConfig(format!(
+                    "Step '{}' sets both data and data_file",
+                    self.name
+                )))
+            }
+        };
+
+        let path = match data_dir {
+            Some(dir) => dir.join(file),
+            None => PathBuf::from(file),
+        };
+        let invalid = |reason: String| {
+            SkillError::InvalidConfig(format!(
+                "Data file '{}' of step '{}': {}",
+                path.display(),
+                self.name,
+                reason
+            ))
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let value = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?,
+            Some("yaml" | "yml") => {
+                serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?
+            }
+            _ => Value::String(content),
+        };
+        Ok(Some(value))
+    }
+
+    /// Check whether this step should run given the current variables.
+    ///
+    /// Steps without a condition always run.
+    pub fn should_run(&self, variables: &HashMap<String, Value>) -> Result<bool> {
+        match &self.condition {
+            Some(condition) => evaluate_condition(condition, variables),
+            None => Ok(true),
+        }
+    }
+}
+
+/// An input derived from other inputs before the skill's steps run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComputedInput {
+    /// Name the computed value is stored under
+    pub name: String,
+
+    /// Expression over inputs, e.g. `({{page}} - 1) * {{per_page}}`
+    /// (see [`evaluate_expression`])
+    pub expression: String,
 }
 
 /// A skill definition - a sequence of tool calls
@@ -139,8 +508,24 @@ pub struct Skill {
     #[serde(default)]
     pub inputs: Vec<String>,
 
+    /// Default values for inputs the caller doesn't provide
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: HashMap<String, Value>,
+
+    /// Inputs derived from other inputs, evaluated in order so later
+    /// entries can use earlier ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub computed: Vec<ComputedInput>,
+
     /// Steps to execute
     pub steps: Vec<SkillStep>,
+
+    /// Declared fields of the skill output.
+    ///
+    /// When non-empty, the output must be an object whose fields match these
+    /// definitions; see [`Skill::validate_output`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<Parameter>,
 }
 
 impl Skill {
@@ -150,7 +535,10 @@ impl Skill {
             name: name.into(),
             description: description.into(),
             inputs: Vec::new(),
+            defaults: HashMap::new(),
+            computed: Vec::new(),
             steps: Vec::new(),
+            outputs: Vec::new(),
         }
     }
 
@@ -160,11 +548,227 @@ impl Skill {
         self
     }
 
+    /// Add an optional input with a default value
+    pub fn with_input_default(mut self, input: impl Into<String>, default: Value) -> Self {
+        let input = input.into();
+        if !self.inputs.contains(&input) {
+            self.inputs.push(input.clone());
+        }
+        self.defaults.insert(input, default);
+        self
+    }
+
+    /// Add an input computed from other inputs
+    pub fn with_computed_input(
+        mut self,
+        name: impl Into<String>,
+        expression: impl Into<String>,
+    ) -> Self {
+        self.computed.push(ComputedInput {
+            name: name.into(),
+            expression: expression.into(),
+        });
+        self
+    }
+
+    /// Apply [`defaults`](Self::defaults) and [`computed`](Self::computed)
+    /// inputs to the caller's inputs.
+    ///
+    /// Defaults only fill inputs that are missing; computed inputs are always
+    /// evaluated and overwrite any value of the same name.
+    pub fn resolve_inputs(
+        &self,
+        inputs: &HashMap<String, Value>,
+    ) -> Result<HashMap<String, Value>> {
+        let mut resolved = inputs.clone();
+        for (name, default) in &self.defaults {
+            resolved
+                .entry(name.clone())
+                .or_insert_with(|| default.clone());
+        }
+
+        for computed in &self.computed {
+            let value =
+                evaluate_expression(&computed.expression, &resolved).map_err(|e| match e {
+                    SkillError::InvalidConfig(message) => SkillError::InvalidConfig(format!(
+                        "Computed input '{}' of skill '{}': {}",
+                        computed.name, self.name, message
+                    )),
+                    other => other,
+                })?;
+            resolved.insert(computed.name.clone(), value);
+        }
+
+        Ok(resolved)
+    }
+
     /// Add a step
     pub fn with_step(mut self, step: SkillStep) -> Self {
         self.steps.push(step);
         self
     }
+
+    /// Declare an output field
+    pub fn with_output(mut self, output: Parameter) -> Self {
+        self.outputs.push(output);
+        self
+    }
+
+    /// Validate a skill output against the declared [`outputs`](Self::outputs).
+    ///
+    /// Missing fields with a default are filled in, so the returned value
+    /// always has the declared shape. Every violation is collected into a
+    /// single [`SkillError::OutputValidation`]. Skills without declared
+    /// outputs accept any output unchanged.
+    pub fn validate_output(&self, output: Option<Value>) -> Result<Option<Value>> {
+        if self.outputs.is_empty() {
+            return Ok(output);
+        }
+
+        let mut fields = match output {
+            Some(Value::Object(fields)) => fields,
+            Some(other) => {
+                return Err(self.output_error(vec![format!(
+                    "expected an object, got {}",
+                    json_type_name(&other)
+                )]))
+            }
+            None => return Err(self.output_error(vec!["skill produced no output".to_string()])),
+        };
+
+        let mut errors = Vec::new();
+        for param in &self.outputs {
+            match fields.get(&param.name) {
+                Some(value) => {
+                    if !param.param_type.matches(value) {
+                        errors.push(format!(
+                            "field '{}' should be {}, got {}",
+                            param.name,
+                            param.param_type.as_str(),
+                            json_type_name(value)
+                        ));
+                    } else if !param.enum_values.is_empty() && !param.enum_values.contains(value) {
+                        errors.push(format!(
+                            "field '{}' must be one of {:?}",
+                            param.name, param.enum_values
+                        ));
+                    }
+                }
+                None => match &param.default {
+                    Some(default) => {
+                        fields.insert(param.name.clone(), default.clone());
+                    }
+                    None if param.required => {
+                        errors.push(format!("missing required field '{}'", param.name));
+                    }
+                    None => {}
+                },
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Some(Value::Object(fields)))
+        } else {
+            Err(self.output_error(errors))
+        }
+    }
+
+    fn output_error(&self, errors: Vec<String>) -> SkillError {
+        SkillError::OutputValidation {
+            skill: self.name.clone(),
+            errors,
+        }
+    }
+
+    /// Check whether any step declares dependencies
+    pub fn has_dependencies(&self) -> bool {
+        self.steps.iter().any(|s| !s.depends_on.is_empty())
+    }
+
+    /// Group steps into waves that can run concurrently.
+    ///
+    /// Each wave holds step indices whose dependencies are all satisfied by
+    /// earlier waves; within a wave, steps keep their declaration order.
+    /// Returns [`SkillError::InvalidConfig`] for duplicate step names, unknown
+    /// dependencies, or dependency cycles.
+    pub fn execution_waves(&self) -> Result<Vec<Vec<usize>>> {
+        let mut indices = HashMap::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            if indices.insert(step.name.as_str(), index).is_some() {
+                return Err(SkillError::InvalidConfig(format!(
+                    "Duplicate step name '{}'",
+                    step.name
+                )));
+            }
+        }
+
+        let mut remaining = vec![0usize; self.steps.len()];
+        let mut dependents = vec![Vec::new(); self.steps.len()];
+        for (index, step) in self.steps.iter().enumerate() {
+            for dep in &step.depends_on {
+                let &dep_index = indices.get(dep.as_str()).ok_or_else(|| {
+                    SkillError::InvalidConfig(format!(
+                        "Step '{}' depends on unknown step '{}'",
+                        step.name, dep
+                    ))
+                })?;
+                remaining[index] += 1;
+                dependents[dep_index].push(index);
+            }
+        }
+
+        let mut waves = Vec::new();
+        let mut ready: Vec<usize> = (0..self.steps.len())
+            .filter(|&i| remaining[i] == 0)
+            .collect();
+        let mut scheduled = 0;
+
+        while !ready.is_empty() {
+            scheduled += ready.len();
+            let mut next = Vec::new();
+            for &index in &ready {
+                for &dependent in &dependents[index] {
+                    remaining[dependent] -= 1;
+                    if remaining[dependent] == 0 {
+                        next.push(dependent);
+                    }
+                }
+            }
+            next.sort_unstable();
+            waves.push(std::mem::replace(&mut ready, next));
+        }
+
+        if scheduled < self.steps.len() {
+            let cyclic: Vec<&str> = self
+                .steps
+                .iter()
+                .zip(&remaining)
+                .filter(|(_, &count)| count > 0)
+                .map(|(step, _)| step.name.as_str())
+                .collect();
+            return Err(SkillError::InvalidConfig(format!(
+                "Dependency cycle between steps: {}",
+                cyclic.join(", ")
+            )));
+        }
+
+        Ok(waves)
+    }
+
+    /// Get the steps in an order that satisfies their dependencies.
+    ///
+    /// Skills without dependencies run in declaration order.
+    pub fn execution_order(&self) -> Result<Vec<&SkillStep>> {
+        if !self.has_dependencies() {
+            return Ok(self.steps.iter().collect());
+        }
+        Ok(self
+            .execution_waves()?
+            .into_iter()
+            .flatten()
+            .map(|index| &self.steps[index])
+            .collect())
+    }
 }
 
 impl Skill {
@@ -218,7 +822,12 @@ impl Skill {
         .await;
 
         match result {
-            Ok(inner_result) => inner_result,
+            Ok(inner_result) => inner_result.and_then(|mut result| {
+                if result.success {
+                    result.output = self.validate_output(result.output.take())?;
+                }
+                Ok(result)
+            }),
             Err(_elapsed) => {
                 // Handle based on timeout action
                 match config.timeout.timeout_action {
@@ -232,6 +841,8 @@ impl Skill {
                             step_results: vec![],
                             output: None,
                             error: Some(format!("Skill timed out after {:?}", skill_timeout)),
+                            usage: ExecutionUsage::default(),
+                            degradations: Vec::new(),
                         })
                     }
                 }
@@ -246,43 +857,51 @@ impl Skill {
         input_args: &HashMap<String, serde_json::Value>,
         config: &ExecutionConfig,
     ) -> Result<SkillResult> {
-        use std::time::Duration;
+        if let Some(step) = self.steps.iter().find(|s| s.for_each.is_some()) {
+            return Err(SkillError::InvalidConfig(format!(
+                "Step '{}' uses for_each, which requires DefaultSkillExecutor",
+                step.name
+            )));
+        }
+        if let Some(step) = self.steps.iter().find(|s| s.is_nested()) {
+            return Err(SkillError::InvalidConfig(format!(
+                "Step '{}' runs a nested skill, which requires DefaultSkillExecutor",
+                step.name
+            )));
+        }
 
         let mut step_results = Vec::new();
-        let mut context = input_args.clone();
-
-        for step in &self.steps {
-            // Determine timeout for this step (per-step override or global)
-            let step_timeout = step
-                .timeout_secs
-                .map(Duration::from_secs)
-                .unwrap_or(config.timeout.step_timeout);
-
-            // Determine max retries for this step
-            let max_retries = step.max_retries.unwrap_or(config.retry.max_retries);
-            let step_retry_config = RetryConfig {
-                max_retries,
-                ..config.retry.clone()
-            };
-
-            // Prepare arguments
-            let prepared_args = self.prepare_arguments(&step.arguments, &context)?;
-
-            let tool_call = ToolCall {
-                tool: step.tool.clone(),
-                arguments: prepared_args,
-            };
+        let mut degradations = Vec::new();
+        let mut context = self.resolve_inputs(input_args)?;
+        let mut output = None;
+        let usage = budget::UsageTracker::default();
+        usage.begin();
+        let listing = ToolListing::default();
+
+        for step in self.execution_order()? {
+            if !step.should_run(&context)? {
+                continue;
+            }
 
             // Execute with retry and timeout
-            let step_result = self
-                .execute_step_with_retry_timeout(
-                    transport,
-                    &tool_call,
-                    &step.name,
-                    step_timeout,
-                    &step_retry_config,
-                )
-                .await;
+            let step_result = match step.load_data(config.data_dir.as_deref()) {
+                Ok(Some(data)) => Ok(ToolResult::success(data)),
+                Err(e) => Err(e),
+                Ok(None) => match check_read_only(&listing, transport, &step.tool, config).await {
+                    Ok(()) => {
+                        self.execute_step_with_retry_timeout(
+                            transport,
+                            step,
+                            &mut context,
+                            config,
+                            &usage,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+            retry::clear_attempt_variables(&mut context);
 
             match step_result {
                 Ok(result) => {
@@ -294,34 +913,40 @@ impl Skill {
                         result.data.clone().unwrap_or(Value::Null),
                     );
 
-                    // If this is the last step, use its result as output
-                    if step_results.len() == self.steps.len() {
-                        return Ok(SkillResult {
-                            success: true,
-                            step_results,
-                            output: result.data,
-                            error: None,
-                        });
-                    }
+                    // The last step that runs provides the skill output
+                    output = result.data;
                 }
                 Err(e) => {
+                    output = None;
+                    if matches!(e, SkillError::BudgetExceeded(_)) {
+                        return Err(e);
+                    }
                     if step.continue_on_error {
                         // Continue on error
+                        degradations.push(Degradation::new(step, e.to_string()));
                         step_results.push((step.name.clone(), ToolResult::failure(e.to_string())));
                     } else {
                         // Check timeout action for Skip/Partial behavior
                         match &config.timeout.timeout_action {
                             TimeoutAction::Skip => {
+                                degradations.push(Degradation::new(step, e.to_string()));
                                 step_results
                                     .push((step.name.clone(), ToolResult::failure(e.to_string())));
                                 // Continue to next step
                             }
                             TimeoutAction::Partial => {
+                                degradation::trace_consequences(
+                                    self,
+                                    &mut degradations,
+                                    &step_results,
+                                );
                                 return Ok(SkillResult {
                                     success: false,
                                     step_results,
                                     output: None,
                                     error: Some(e.to_string()),
+                                    usage: usage.usage(),
+                                    degradations,
                                 });
                             }
                             TimeoutAction::Fail => {
@@ -333,30 +958,54 @@ impl Skill {
             }
         }
 
+        degradation::trace_consequences(self, &mut degradations, &step_results);
         Ok(SkillResult {
             success: true,
             step_results,
-            output: None,
+            output,
             error: None,
+            usage: usage.usage(),
+            degradations,
         })
     }
 
     /// Execute a single step with timeout and retry
+    ///
+    /// Per-step timeout and retry overrides take precedence over `config`.
+    /// Arguments are prepared again for every attempt with the retry state
+    /// variables set in `context`, and every attempt is charged to `usage`.
     async fn execute_step_with_retry_timeout<T: Transport>(
         &self,
         transport: &T,
-        tool_call: &ToolCall,
-        step_name: &str,
-        timeout: std::time::Duration,
-        retry_config: &RetryConfig,
+        step: &SkillStep,
+        context: &mut HashMap<String, Value>,
+        config: &ExecutionConfig,
+        usage: &budget::UsageTracker,
     ) -> Result<ToolResult> {
+        let timeout = step
+            .timeout_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(config.timeout.step_timeout);
+        let retry_config = &RetryConfig {
+            max_retries: step.max_retries.unwrap_or(config.retry.max_retries),
+            ..config.retry.clone()
+        };
+        let step_name = step.name.as_str();
+        let strictness = config.template_strictness;
         let mut attempts = 0;
+        let mut last_error = None;
 
         loop {
             attempts += 1;
+            retry::set_attempt_variables(context, attempts, last_error.as_deref());
+            let tool_call = ToolCall {
+                tool: step.tool.clone(),
+                arguments: render_step_template(&step.arguments, context, step_name, strictness)?,
+            };
 
             // Execute with timeout
-            let result = tokio::time::timeout(timeout, transport.call(tool_call)).await;
+            usage.charge(&tool_call.tool, config)?;
+            let result = tokio::time::timeout(timeout, transport.call(&tool_call)).await;
 
             match result {
                 Ok(Ok(tool_result)) => {
@@ -387,6 +1036,7 @@ impl Skill {
                         "Retrying step after error"
                     );
                     tokio::time::sleep(delay).await;
+                    last_error = Some(error_msg);
                 }
                 Err(_elapsed) => {
                     // Timeout - check if retryable
@@ -410,37 +1060,11 @@ impl Skill {
                         "Retrying step after timeout"
                     );
                     tokio::time::sleep(delay).await;
+                    last_error = Some("timeout".to_string());
                 }
             }
         }
     }
-
-    /// Prepare arguments by substituting context variables
-    fn prepare_arguments(
-        &self,
-        args: &serde_json::Value,
-        context: &HashMap<String, serde_json::Value>,
-    ) -> Result<serde_json::Value> {
-        // Simple string substitution for now
-        // In a real implementation, this would use a proper templating engine
-        let args_str = serde_json::to_string(args)
-            .map_err(|e| SkillError::InvalidConfig(format!("Failed to serialize args: {}", e)))?;
-
-        let mut processed_str = args_str.clone();
-
-        // Replace {{variable}} placeholders with context values
-        for (key, value) in context {
-            let placeholder = format!("{{{{{}}}}}", key);
-            let value_str = serde_json::to_string(value).map_err(|e| {
-                SkillError::InvalidConfig(format!("Failed to serialize value: {}", e))
-            })?;
-            processed_str = processed_str.replace(&placeholder, &value_str);
-        }
-
-        serde_json::from_str(&processed_str).map_err(|e| {
-            SkillError::InvalidConfig(format!("Failed to parse processed args: {}", e))
-        })
-    }
 }
 
 /// Result of executing a skill
@@ -457,6 +1081,21 @@ pub struct SkillResult {
 
     /// Error message if failed
     pub error: Option<String>,
+
+    /// Tool calls, cost and time used, see [`budget`]
+    #[serde(default)]
+    pub usage: ExecutionUsage,
+
+    /// Step failures the run carried on from, see [`degradation`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degradations: Vec<Degradation>,
+}
+
+impl SkillResult {
+    /// Check whether the run carried on from failed steps.
+    pub fn is_degraded(&self) -> bool {
+        !self.degradations.is_empty()
+    }
 }
 
 /// Registry for managing skills
@@ -560,17 +1199,15 @@ mod tests {
                 name: "search".to_string(),
                 tool: "web_search".to_string(),
                 arguments: serde_json::json!({"query": "{{query}}"}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "summarize".to_string(),
                 tool: "summarize".to_string(),
                 arguments: serde_json::json!({"text": "{{search.results}}"}),
-                continue_on_error: false,
                 timeout_secs: Some(30),
                 max_retries: Some(2),
+                ..Default::default()
             });
 
         assert_eq!(skill.inputs.len(), 1);
@@ -615,17 +1252,13 @@ mod tests {
                 name: "search".to_string(),
                 tool: "search".to_string(),
                 arguments: serde_json::json!({"query": "test query"}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "summarize".to_string(),
                 tool: "summarize".to_string(),
                 arguments: serde_json::json!({"text": "summary text"}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             });
 
         let input_args = HashMap::new();
@@ -647,9 +1280,7 @@ mod tests {
             name: "step1".to_string(),
             tool: "test_tool".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
         let config = ExecutionConfig::new()
@@ -665,6 +1296,56 @@ mod tests {
         assert_eq!(result.step_results.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_skill_retry_state_in_arguments() {
+        // Fails until the arguments say it's the second attempt
+        struct SecondTimeLucky;
+
+        #[async_trait]
+        impl Transport for SecondTimeLucky {
+            async fn connect(&mut self) -> thulp_core::Result<()> {
+                Ok(())
+            }
+            async fn disconnect(&mut self) -> thulp_core::Result<()> {
+                Ok(())
+            }
+            fn is_connected(&self) -> bool {
+                true
+            }
+            async fn list_tools(&self) -> thulp_core::Result<Vec<thulp_core::ToolDefinition>> {
+                Ok(vec![])
+            }
+            async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+                if call.arguments["attempt"] == 1 {
+                    return Err(thulp_core::Error::ExecutionFailed("503".to_string()));
+                }
+                Ok(ToolResult::success(call.arguments.clone()))
+            }
+        }
+
+        let skill = Skill::new("retry", "Retry").with_step(SkillStep {
+            name: "call".to_string(),
+            tool: "call".to_string(),
+            arguments: serde_json::json!({"attempt": "{{__attempt}}", "error": "{{__last_error}}"}),
+            ..Default::default()
+        });
+        let config = ExecutionConfig::new().with_retry(
+            RetryConfig::new()
+                .with_max_retries(1)
+                .with_initial_delay(Duration::from_millis(1))
+                .retry_all_errors(),
+        );
+
+        let result = skill
+            .execute_with_config(&SecondTimeLucky, &HashMap::new(), &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.output,
+            Some(serde_json::json!({"attempt": 2, "error": "tool execution failed: 503"}))
+        );
+    }
+
     #[tokio::test]
     async fn test_skill_step_timeout() {
         // Create a transport that delays response
@@ -694,9 +1375,7 @@ mod tests {
             name: "slow_step".to_string(),
             tool: "slow_tool".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
         let config = ExecutionConfig::new()
@@ -744,9 +1423,9 @@ mod tests {
             name: "step".to_string(),
             tool: "tool".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
             timeout_secs: Some(1), // Override: 1 second should be enough
             max_retries: Some(0),
+            ..Default::default()
         });
 
         // Global config has very short timeout, but step overrides it
@@ -774,16 +1453,14 @@ mod tests {
                 tool: "missing_tool".to_string(),
                 arguments: serde_json::json!({}),
                 continue_on_error: true, // Continue even if this fails
-                timeout_secs: None,
                 max_retries: Some(0),
+                ..Default::default()
             })
             .with_step(SkillStep {
                 name: "step2".to_string(),
                 tool: "step2".to_string(),
                 arguments: serde_json::json!({}),
-                continue_on_error: false,
-                timeout_secs: None,
-                max_retries: None,
+                ..Default::default()
             });
 
         let config = ExecutionConfig::new().with_retry(RetryConfig::no_retries());
@@ -796,6 +1473,9 @@ mod tests {
         // Should complete because continue_on_error=true for step1
         assert!(result.success);
         assert_eq!(result.step_results.len(), 2);
+        assert_eq!(result.degradations.len(), 1);
+        assert_eq!(result.degradations[0].step, "step1");
+        assert!(!result.degradations[0].output_lost);
 
         // First step should have failed
         let (_, step1_result) = &result.step_results[0];
@@ -812,9 +1492,9 @@ mod tests {
             name: "test".to_string(),
             tool: "tool".to_string(),
             arguments: serde_json::json!({}),
-            continue_on_error: false,
             timeout_secs: Some(30),
             max_retries: Some(2),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&step).unwrap();
@@ -824,6 +1504,74 @@ mod tests {
         assert_eq!(deserialized.max_retries, Some(2));
     }
 
+    #[test]
+    fn test_data_steps() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("rates.yaml"), "usd: 1.0\neur: 0.9\n").unwrap();
+        std::fs::write(temp.path().join("notes.txt"), "plain text").unwrap();
+
+        let step: SkillStep =
+            serde_json::from_str(r#"{"name": "limits", "data": {"max": 5}}"#).unwrap();
+        assert!(step.is_data());
+        assert_eq!(
+            step.load_data(None).unwrap(),
+            Some(serde_json::json!({"max": 5}))
+        );
+
+        let from_file = |file: &str| SkillStep {
+            name: "fixture".to_string(),
+            data_file: Some(file.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            from_file("rates.yaml")
+                .load_data(Some(temp.path()))
+                .unwrap(),
+            Some(serde_json::json!({"usd": 1.0, "eur": 0.9}))
+        );
+        assert_eq!(
+            from_file("notes.txt").load_data(Some(temp.path())).unwrap(),
+            Some(serde_json::json!("plain text"))
+        );
+        assert!(from_file("missing.json")
+            .load_data(Some(temp.path()))
+            .is_err());
+
+        let tool_step = SkillStep {
+            name: "call".to_string(),
+            tool: "tool".to_string(),
+            ..Default::default()
+        };
+        assert!(!tool_step.is_data());
+        assert_eq!(tool_step.load_data(None).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_skill_data_step_feeds_later_steps() {
+        let transport = MockTransport::new()
+            .with_response("echo", ToolResult::success(serde_json::json!("called")));
+        let skill = Skill::new("lookup", "Lookup")
+            .with_step(SkillStep {
+                name: "codes".to_string(),
+                data: Some(serde_json::json!({"us": "en-US"})),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "locale".to_string(),
+                tool: "echo".to_string(),
+                condition: Some("{{codes.us}} == \"en-US\"".to_string()),
+                ..Default::default()
+            });
+
+        let result = skill.execute(&transport, &HashMap::new()).await.unwrap();
+        assert_eq!(result.step_results.len(), 2);
+        assert_eq!(
+            result.step_results[0].1.data,
+            Some(serde_json::json!({"us": "en-US"}))
+        );
+        assert_eq!(result.output, Some(serde_json::json!("called")));
+    }
+
     #[test]
     fn test_skill_step_default_optional_fields() {
         let json = r#"{"name": "test", "tool": "tool", "arguments": {}}"#;
@@ -832,5 +1580,189 @@ mod tests {
         assert!(!step.continue_on_error);
         assert_eq!(step.timeout_secs, None);
         assert_eq!(step.max_retries, None);
+        assert!(step.depends_on.is_empty());
+    }
+
+    fn step_after(name: &str, depends_on: &[&str]) -> SkillStep {
+        SkillStep {
+            name: name.to_string(),
+            tool: "tool".to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_execution_waves() {
+        let skill = Skill::new("dag", "DAG")
+            .with_step(step_after("report", &["summarize", "stats"]))
+            .with_step(step_after("fetch", &[]))
+            .with_step(step_after("summarize", &["fetch"]))
+            .with_step(step_after("stats", &["fetch"]));
+
+        assert!(skill.has_dependencies());
+        assert_eq!(
+            skill.execution_waves().unwrap(),
+            vec![vec![1], vec![2, 3], vec![0]]
+        );
+
+        let order: Vec<&str> = skill
+            .execution_order()
+            .unwrap()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["fetch", "summarize", "stats", "report"]);
+    }
+
+    #[test]
+    fn test_execution_waves_invalid() {
+        let unknown = Skill::new("s", "s").with_step(step_after("a", &["missing"]));
+        assert!(matches!(
+            unknown.execution_waves(),
+            Err(SkillError::InvalidConfig(msg)) if msg.contains("missing")
+        ));
+
+        let cycle = Skill::new("s", "s")
+            .with_step(step_after("a", &["c"]))
+            .with_step(step_after("b", &["a"]))
+            .with_step(step_after("c", &["b"]));
+        assert!(matches!(
+            cycle.execution_waves(),
+            Err(SkillError::InvalidConfig(msg)) if msg.contains("cycle")
+        ));
+
+        let duplicate = Skill::new("s", "s")
+            .with_step(step_after("a", &[]))
+            .with_step(step_after("a", &["a"]));
+        assert!(duplicate.execution_waves().is_err());
+    }
+
+    fn skill_with_outputs() -> Skill {
+        use thulp_core::ParameterType;
+
+        Skill::new("search", "Search")
+            .with_output(
+                Parameter::builder("count")
+                    .param_type(ParameterType::Integer)
+                    .required(true)
+                    .build(),
+            )
+            .with_output(
+                Parameter::builder("results")
+                    .param_type(ParameterType::Array)
+                    .default(serde_json::json!([]))
+                    .build(),
+            )
+            .with_output(
+                Parameter::builder("source")
+                    .param_type(ParameterType::String)
+                    .enum_value(serde_json::json!("web"))
+                    .enum_value(serde_json::json!("cache"))
+                    .build(),
+            )
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let skill = skill_with_outputs();
+
+        let output = skill
+            .validate_output(Some(serde_json::json!({"count": 2, "extra": true})))
+            .unwrap()
+            .unwrap();
+        assert_eq!(output["results"], serde_json::json!([]));
+        assert_eq!(output["extra"], true);
+
+        // Skills without declared outputs accept anything
+        let plain = Skill::new("plain", "Plain");
+        assert_eq!(
+            plain
+                .validate_output(Some(serde_json::json!("text")))
+                .unwrap(),
+            Some(serde_json::json!("text"))
+        );
+        assert_eq!(plain.validate_output(None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_output_errors() {
+        let skill = skill_with_outputs();
+
+        match skill.validate_output(Some(serde_json::json!({"results": "x", "source": "disk"}))) {
+            Err(SkillError::OutputValidation { skill, errors }) => {
+                assert_eq!(skill, "search");
+                assert_eq!(errors.len(), 3);
+                assert!(errors.iter().any(|e| e.contains("'count'")));
+                assert!(errors
+                    .iter()
+                    .any(|e| e.contains("'results' should be array")));
+                assert!(errors.iter().any(|e| e.contains("'source' must be one of")));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            skill.validate_output(Some(serde_json::json!([1]))),
+            Err(SkillError::OutputValidation { .. })
+        ));
+        assert!(matches!(
+            skill.validate_output(None),
+            Err(SkillError::OutputValidation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_inputs() {
+        let skill = Skill::new("greet", "Greet a user")
+            .with_input("first")
+            .with_input("last")
+            .with_input_default("greeting", serde_json::json!("Hello"))
+            .with_computed_input("name", "{{first}} + ' ' + {{last}}")
+            .with_computed_input("message", "{{greeting}} + ', ' + {{name}} + '!'");
+        assert_eq!(skill.inputs, vec!["first", "last", "greeting"]);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("first".to_string(), serde_json::json!("Ada"));
+        inputs.insert("last".to_string(), serde_json::json!("Lovelace"));
+
+        let resolved = skill.resolve_inputs(&inputs).unwrap();
+        assert_eq!(resolved["greeting"], "Hello");
+        assert_eq!(resolved["name"], "Ada Lovelace");
+        assert_eq!(resolved["message"], "Hello, Ada Lovelace!");
+
+        inputs.insert("greeting".to_string(), serde_json::json!("Hi"));
+        let resolved = skill.resolve_inputs(&inputs).unwrap();
+        assert_eq!(resolved["message"], "Hi, Ada Lovelace!");
+    }
+
+    #[test]
+    fn test_resolve_inputs_errors_and_serde() {
+        let skill = Skill::new("bad", "Bad").with_computed_input("x", "{{a}} +");
+        match skill.resolve_inputs(&HashMap::new()) {
+            Err(SkillError::InvalidConfig(msg)) => {
+                assert!(msg.starts_with("Computed input 'x' of skill 'bad'"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let yaml = r#"
+name: page
+description: Paged fetch
+inputs: [page]
+defaults:
+  per_page: 50
+computed:
+  - name: offset
+    expression: "({{page}} - 1) * {{per_page}}"
+steps: []
+"#;
+        let skill: Skill = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(skill.defaults["per_page"], 50);
+        assert_eq!(skill.computed[0].name, "offset");
+
+        let plain = serde_json::to_value(Skill::new("plain", "Plain")).unwrap();
+        assert!(plain.get("defaults").is_none());
+        assert!(plain.get("computed").is_none());
     }
 }
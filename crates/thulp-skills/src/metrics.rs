@@ -0,0 +1,441 @@
+//! OpenTelemetry metrics and spans for skill executions.
+//!
+//! [`MetricsHooks`] records every execution through the OpenTelemetry API,
+//! so the application decides where the data goes: install a meter provider
+//! with a Prometheus exporter to scrape it, or an OTLP exporter to push it
+//! to a collector. Without a provider installed, recording is a no-op.
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use thulp_skills::{DefaultSkillExecutor, MetricsHooks};
+//!
+//! // After installing the global meter and tracer providers
+//! let executor = DefaultSkillExecutor::with_hooks(transport, MetricsHooks::global());
+//! ```
+//!
+//! Metrics (durations in seconds):
+//!
+//! | Name | Kind | Attributes |
+//! |------|------|------------|
+//! | `thulp.skill.runs` | counter | `skill`, `outcome` |
+//! | `thulp.skill.duration` | histogram | `skill`, `outcome` |
+//! | `thulp.skill.steps` | counter | `skill`, `step`, `tool`, `outcome` |
+//! | `thulp.skill.step.duration` | histogram | `skill`, `step`, `tool`, `outcome` |
+//! | `thulp.skill.step.retries` | counter | `skill`, `step`, `tool` |
+//! | `thulp.skill.step.timeouts` | counter | `skill`, `step`, `tool` |
+//!
+//! `outcome` is `success` or `failure`, or `skipped` for steps whose
+//! condition was false, so success rates are the ratio of the `success`
+//! series to the total. Each skill run is also a span, with a child span
+//! per step; retries and timeouts are recorded as events on the step span.
+
+use crate::{ExecutionContext, ExecutionHooks, Skill, SkillResult, SkillStep, StepResult};
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::trace::{Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Instrumentation scope name of the meter and tracer.
+pub const SCOPE_NAME: &str = "thulp-skills";
+
+/// [`ExecutionHooks`] that record OpenTelemetry metrics and spans.
+///
+/// Steps run outside a skill (through [`SkillExecutor::execute_step`])
+/// are recorded with an empty `skill` attribute.
+///
+/// [`SkillExecutor::execute_step`]: crate::SkillExecutor::execute_step
+pub struct MetricsHooks {
+    tracer: BoxedTracer,
+    runs: Counter<u64>,
+    skill_duration: Histogram<f64>,
+    steps: Counter<u64>,
+    step_duration: Histogram<f64>,
+    retries: Counter<u64>,
+    timeouts: Counter<u64>,
+    /// Skills and steps being executed, innermost last
+    stack: Mutex<Vec<Frame>>,
+}
+
+/// A running skill or step and its span.
+struct Frame {
+    kind: FrameKind,
+    context: Context,
+}
+
+enum FrameKind {
+    Skill { name: String, started: Instant },
+    Step,
+}
+
+impl std::fmt::Debug for MetricsHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsHooks")
+            .field("depth", &self.stack.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl MetricsHooks {
+    /// Create hooks recording with `meter` and `tracer`.
+    pub fn new(meter: &Meter, tracer: BoxedTracer) -> Self {
+        Self {
+            tracer,
+            runs: meter
+                .u64_counter("thulp.skill.runs")
+                .with_description("Skill executions")
+                .build(),
+            skill_duration: meter
+                .f64_histogram("thulp.skill.duration")
+                .with_description("Duration of skill executions")
+                .with_unit("s")
+                .build(),
+            steps: meter
+                .u64_counter("thulp.skill.steps")
+                .with_description("Skill steps executed or skipped")
+                .build(),
+            step_duration: meter
+                .f64_histogram("thulp.skill.step.duration")
+                .with_description("Duration of skill steps, retries included")
+                .with_unit("s")
+                .build(),
+            retries: meter
+                .u64_counter("thulp.skill.step.retries")
+                .with_description("Retries of failed skill steps")
+                .build(),
+            timeouts: meter
+                .u64_counter("thulp.skill.step.timeouts")
+                .with_description("Skill step attempts that timed out")
+                .build(),
+            stack: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create hooks recording with the global meter and tracer providers.
+    pub fn global() -> Self {
+        Self::new(&global::meter(SCOPE_NAME), global::tracer(SCOPE_NAME))
+    }
+
+    /// Context of the innermost running skill or step, to parent a new span.
+    fn parent(&self) -> Context {
+        self.stack
+            .lock()
+            .unwrap()
+            .last()
+            .map(|frame| frame.context.clone())
+            .unwrap_or_else(Context::current)
+    }
+
+    /// Name of the innermost running skill, or empty outside a skill.
+    fn skill_name(&self) -> String {
+        self.stack
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find_map(|frame| match &frame.kind {
+                FrameKind::Skill { name, .. } => Some(name.clone()),
+                FrameKind::Step => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn step_attributes(&self, step: &SkillStep) -> Vec<KeyValue> {
+        vec![
+            KeyValue::new("skill", self.skill_name()),
+            KeyValue::new("step", step.name.clone()),
+            KeyValue::new("tool", step.tool.clone()),
+        ]
+    }
+
+    /// Context of the running step's span, if a step is running.
+    fn step_context(&self) -> Option<Context> {
+        match self.stack.lock().unwrap().last() {
+            Some(Frame {
+                kind: FrameKind::Step,
+                context,
+            }) => Some(context.clone()),
+            _ => None,
+        }
+    }
+}
+
+fn outcome(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+impl ExecutionHooks for MetricsHooks {
+    fn before_skill(&self, skill: &Skill, _context: &ExecutionContext) {
+        let parent = self.parent();
+        let span = self
+            .tracer
+            .span_builder(skill.name.clone())
+            .with_attributes([
+                KeyValue::new("thulp.skill.name", skill.name.clone()),
+                KeyValue::new("thulp.skill.steps", skill.steps.len() as i64),
+            ])
+            .start_with_context(&self.tracer, &parent);
+        let context = parent.with_span(span);
+        self.stack.lock().unwrap().push(Frame {
+            kind: FrameKind::Skill {
+                name: skill.name.clone(),
+                started: Instant::now(),
+            },
+            context,
+        });
+    }
+
+    fn after_skill(&self, skill: &Skill, result: &SkillResult, _context: &ExecutionContext) {
+        // Steps left running (the skill timed out or was cancelled) end with it
+        let mut frame = None;
+        {
+            let mut stack = self.stack.lock().unwrap();
+            while let Some(popped) = stack.pop() {
+                let is_skill = matches!(popped.kind, FrameKind::Skill { .. });
+                if is_skill {
+                    frame = Some(popped);
+                    break;
+                }
+                popped.context.span().end();
+            }
+        }
+
+        let attributes = [
+            KeyValue::new("skill", skill.name.clone()),
+            KeyValue::new("outcome", outcome(result.success)),
+        ];
+        self.runs.add(1, &attributes);
+        if let Some(Frame {
+            kind: FrameKind::Skill { started, .. },
+            context,
+        }) = frame
+        {
+            self.skill_duration
+                .record(started.elapsed().as_secs_f64(), &attributes);
+            let span = context.span();
+            if !result.success {
+                span.set_status(Status::error(result.error.clone().unwrap_or_default()));
+            }
+            span.end();
+        }
+    }
+
+    fn before_step(&self, step: &SkillStep, step_index: usize, _context: &ExecutionContext) {
+        let parent = self.parent();
+        let span = self
+            .tracer
+            .span_builder(step.name.clone())
+            .with_attributes([
+                KeyValue::new("thulp.skill.step.name", step.name.clone()),
+                KeyValue::new("thulp.skill.step.index", step_index as i64),
+                KeyValue::new("thulp.skill.step.tool", step.tool.clone()),
+            ])
+            .start_with_context(&self.tracer, &parent);
+        let context = parent.with_span(span);
+        self.stack.lock().unwrap().push(Frame {
+            kind: FrameKind::Step,
+            context,
+        });
+    }
+
+    fn on_skip(&self, step: &SkillStep, _step_index: usize, _context: &ExecutionContext) {
+        let mut attributes = self.step_attributes(step);
+        attributes.push(KeyValue::new("outcome", "skipped"));
+        self.steps.add(1, &attributes);
+    }
+
+    fn after_step(
+        &self,
+        step: &SkillStep,
+        _step_index: usize,
+        result: &StepResult,
+        _context: &ExecutionContext,
+    ) {
+        let mut attributes = self.step_attributes(step);
+        attributes.push(KeyValue::new("outcome", outcome(result.success)));
+        self.steps.add(1, &attributes);
+        self.step_duration
+            .record(result.duration_ms as f64 / 1000.0, &attributes);
+
+        let frame = {
+            let mut stack = self.stack.lock().unwrap();
+            match stack.last() {
+                Some(Frame {
+                    kind: FrameKind::Step,
+                    ..
+                }) => stack.pop(),
+                _ => None,
+            }
+        };
+        if let Some(frame) = frame {
+            let span = frame.context.span();
+            if !result.success {
+                span.set_status(Status::error(result.error.clone().unwrap_or_default()));
+            }
+            span.end();
+        }
+    }
+
+    fn on_retry(&self, step: &SkillStep, attempt: usize, error: &str, _context: &ExecutionContext) {
+        self.retries.add(1, &self.step_attributes(step));
+        if let Some(context) = self.step_context() {
+            context.span().add_event(
+                "retry",
+                vec![
+                    KeyValue::new("attempt", attempt as i64),
+                    KeyValue::new("error", error.to_string()),
+                ],
+            );
+        }
+    }
+
+    fn on_timeout(&self, step: &SkillStep, duration_ms: u64, _context: &ExecutionContext) {
+        self.timeouts.add(1, &self.step_attributes(step));
+        if let Some(context) = self.step_context() {
+            context.span().add_event(
+                "timeout",
+                vec![KeyValue::new("duration_ms", duration_ms as i64)],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultSkillExecutor, ExecutionConfig, RetryConfig, SkillExecutor};
+    use async_trait::async_trait;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+
+    /// Fails the first call to `flaky`, succeeds otherwise
+    #[derive(Default)]
+    struct FlakyTransport {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for FlakyTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            if call.tool == "flaky" && self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(thulp_core::Error::ExecutionFailed(
+                    "connection reset".to_string(),
+                ));
+            }
+            Ok(ToolResult::success(json!(call.tool)))
+        }
+    }
+
+    /// Sum of the counter `name`'s data points matching `outcome`, if given
+    fn counter_total(exporter: &InMemoryMetricExporter, name: &str, outcome: Option<&str>) -> u64 {
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let mut total = 0;
+        for metric in metrics
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .filter(|metric| metric.name() == name)
+        {
+            if let AggregatedMetrics::U64(MetricData::Sum(sum)) = metric.data() {
+                total += sum
+                    .data_points()
+                    .filter(|point| {
+                        outcome.map_or(true, |outcome| {
+                            point.attributes().any(|kv| {
+                                kv.key.as_str() == "outcome" && kv.value.as_str() == outcome
+                            })
+                        })
+                    })
+                    .map(|point| point.value())
+                    .sum::<u64>();
+            }
+        }
+        total
+    }
+
+    #[tokio::test]
+    async fn test_metrics_and_spans() {
+        let metric_exporter = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metric_exporter.clone()).build())
+            .build();
+        let span_exporter = InMemorySpanExporter::default();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_simple_exporter(span_exporter.clone())
+            .build();
+        let hooks = Arc::new(MetricsHooks::new(
+            &meter_provider.meter(SCOPE_NAME),
+            BoxedTracer::new(Box::new(tracer_provider.tracer(SCOPE_NAME))),
+        ));
+
+        let skill = Skill::new("report", "Write a report")
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "flaky".to_string(),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "never".to_string(),
+                tool: "noop".to_string(),
+                condition: Some("false".to_string()),
+                ..Default::default()
+            });
+        let executor =
+            DefaultSkillExecutor::from_arcs(Arc::new(FlakyTransport::default()), hooks.clone());
+        let config = ExecutionConfig::new().with_retry(RetryConfig {
+            max_retries: 1,
+            initial_delay: std::time::Duration::from_millis(1),
+            ..Default::default()
+        }
+        .retry_all_errors());
+        let mut context = ExecutionContext::new().with_config(config);
+        let result = executor.execute(&skill, &mut context).await.unwrap();
+        assert!(result.success);
+
+        meter_provider.force_flush().unwrap();
+        assert_eq!(counter_total(&metric_exporter, "thulp.skill.runs", Some("success")), 1);
+        assert_eq!(counter_total(&metric_exporter, "thulp.skill.steps", Some("success")), 1);
+        assert_eq!(counter_total(&metric_exporter, "thulp.skill.steps", Some("skipped")), 1);
+        assert_eq!(counter_total(&metric_exporter, "thulp.skill.step.retries", None), 1);
+        assert_eq!(counter_total(&metric_exporter, "thulp.skill.step.timeouts", None), 0);
+
+        let spans = span_exporter.get_finished_spans().unwrap();
+        let skill_span = spans.iter().find(|span| span.name == "report").unwrap();
+        let step_span = spans.iter().find(|span| span.name == "fetch").unwrap();
+        assert_eq!(step_span.parent_span_id, skill_span.span_context.span_id());
+        assert_eq!(
+            step_span.span_context.trace_id(),
+            skill_span.span_context.trace_id()
+        );
+        assert_eq!(step_span.events.len(), 1);
+        assert!(hooks.stack.lock().unwrap().is_empty());
+    }
+}
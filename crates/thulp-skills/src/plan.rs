@@ -0,0 +1,560 @@
+//! Dry-run planning of skills.
+//!
+//! [`Skill::plan`] walks a skill's steps in the order they would run and
+//! resolves everything that can be known before any tool is called: input
+//! defaults and computed inputs, argument templates that only read inputs,
+//! and the tools each step calls. Placeholders that read earlier step
+//! outputs are left as written and listed as deferred. Nothing is sent to a
+//! [`Transport`](thulp_core::Transport).
+//!
+//! Problems found on the way are collected as [`PlanIssue`]s instead of
+//! failing at the first one: missing inputs, variables that nothing
+//! provides, references to steps that haven't run yet, and, when a tool list
+//! is given, unknown tools and missing or mistyped required arguments.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use thulp_core::ToolDefinition;
+//! use thulp_skills::{Skill, SkillStep};
+//!
+//! let skill = Skill::new("research", "Search and summarize")
+//!     .with_input("query")
+//!     .with_step(SkillStep {
+//!         name: "search".to_string(),
+//!         tool: "web_search".to_string(),
+//!         arguments: json!({"q": "{{query}}"}),
+//!         ..Default::default()
+//!     })
+//!     .with_step(SkillStep {
+//!         name: "summarize".to_string(),
+//!         tool: "summarize".to_string(),
+//!         arguments: json!({"text": "{{search.results}}"}),
+//!         ..Default::default()
+//!     });
+//!
+//! let mut inputs = HashMap::new();
+//! inputs.insert("query".to_string(), json!("rust"));
+//! let tools = [ToolDefinition::new("web_search")];
+//!
+//! let plan = skill.plan(&inputs, Some(&tools)).unwrap();
+//! assert_eq!(plan.steps[0].arguments, json!({"q": "rust"}));
+//! assert_eq!(plan.steps[1].deferred, vec!["search.results"]);
+//! assert_eq!(plan.issues[0].message, "unknown tool 'summarize'");
+//! println!("{}", plan);
+//! ```
+
+use crate::template::render_template_checked;
+use crate::{
+    json_type_name, Result, Skill, ATTEMPT_VARIABLE, LAST_ERROR_VARIABLE, SECRET_PREFIX,
+    WORKSPACE_VARIABLE,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use thulp_core::ToolDefinition;
+
+/// What a skill run would do, worked out without running it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    /// Skill name
+    pub skill: String,
+
+    /// Inputs after applying defaults and computed inputs
+    pub inputs: BTreeMap<String, Value>,
+
+    /// Steps in execution order
+    pub steps: Vec<PlannedStep>,
+
+    /// Step names grouped into waves that run concurrently; empty for
+    /// skills that run their steps in sequence
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waves: Vec<Vec<String>>,
+
+    /// Problems that would make the run fail or misbehave
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<PlanIssue>,
+}
+
+impl ExecutionPlan {
+    /// Check if no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Get a planned step by name.
+    pub fn step(&self, name: &str) -> Option<&PlannedStep> {
+        self.steps.iter().find(|s| s.name == name)
+    }
+}
+
+/// One step of an [`ExecutionPlan`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedStep {
+    /// Step name
+    pub name: String,
+
+    /// Tool the step calls; `None` for data steps and nested skills
+    pub tool: Option<String>,
+
+    /// Skill the step runs, for nested skill steps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+
+    /// Whether the tool is in the tool list; `None` when no list was given
+    /// or for data steps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_found: Option<bool>,
+
+    /// Arguments with input placeholders resolved
+    pub arguments: Value,
+
+    /// Placeholders that are filled in from step outputs, `for_each` items
+    /// or retry state while running
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deferred: Vec<String>,
+
+    /// Condition guarding the step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+
+    /// Array the step iterates over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_each: Option<String>,
+
+    /// Steps this one waits for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// File a data step outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<String>,
+}
+
+/// A problem found while planning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanIssue {
+    /// Step the problem is in; `None` for skill-level problems
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+
+    /// What is wrong
+    pub message: String,
+}
+
+impl PlanIssue {
+    fn skill(message: String) -> Self {
+        Self {
+            step: None,
+            message,
+        }
+    }
+
+    fn step(step: &str, message: String) -> Self {
+        Self {
+            step: Some(step.to_string()),
+            message,
+        }
+    }
+}
+
+impl Skill {
+    /// Work out what running the skill with `inputs` would do, without
+    /// calling any tool.
+    ///
+    /// With `tools`, such as the list of a tool registry, each step's tool
+    /// is looked up and its required arguments checked. Fails only for
+    /// definitions no run could start with: invalid computed inputs,
+    /// dependency errors and malformed templates. See [`plan`](crate::plan).
+    pub fn plan(
+        &self,
+        inputs: &HashMap<String, Value>,
+        tools: Option<&[ToolDefinition]>,
+    ) -> Result<ExecutionPlan> {
+        let resolved = self.resolve_inputs(inputs)?;
+        let mut issues: Vec<PlanIssue> = self
+            .inputs
+            .iter()
+            .filter(|input| !resolved.contains_key(*input))
+            .map(|input| PlanIssue::skill(format!("missing input '{}'", input)))
+            .collect();
+
+        // Position of each step in time: its wave, or its index when steps
+        // run in sequence. A step may only read outputs of earlier positions.
+        let (order, waves) = if self.has_dependencies() {
+            let waves = self.execution_waves()?;
+            let order = waves
+                .iter()
+                .enumerate()
+                .flat_map(|(wave, steps)| steps.iter().map(move |&index| (index, wave)))
+                .collect::<Vec<_>>();
+            let names = waves
+                .iter()
+                .map(|wave| wave.iter().map(|&i| self.steps[i].name.clone()).collect())
+                .collect();
+            (order, names)
+        } else {
+            ((0..self.steps.len()).map(|i| (i, i)).collect(), Vec::new())
+        };
+        let positions: HashMap<&str, usize> = order
+            .iter()
+            .map(|&(index, position)| (self.steps[index].name.as_str(), position))
+            .collect();
+
+        let mut steps = Vec::with_capacity(order.len());
+        for (index, position) in order {
+            let step = &self.steps[index];
+            let mut deferred = Vec::new();
+            let mut classify = |paths: Vec<String>, issues: &mut Vec<PlanIssue>| {
+                for path in paths {
+                    let root = path_root(&path);
+                    let runtime = [ATTEMPT_VARIABLE, LAST_ERROR_VARIABLE, WORKSPACE_VARIABLE]
+                        .contains(&root)
+                        || root.starts_with(SECRET_PREFIX)
+                        || (step.for_each.is_some() && (root == "item" || root == "index"));
+                    let problem = match positions.get(root) {
+                        _ if runtime => None,
+                        Some(&at) if at < position => None,
+                        Some(_) => Some(format!(
+                            "'{}' reads step '{}', which hasn't run yet",
+                            path, root
+                        )),
+                        None => Some(format!("nothing provides '{}'", path)),
+                    };
+                    match problem {
+                        Some(message) => issues.push(PlanIssue::step(&step.name, message)),
+                        None if !deferred.contains(&path) => deferred.push(path),
+                        None => {}
+                    }
+                }
+            };
+
+            for template in [&step.condition, &step.for_each].into_iter().flatten() {
+                let (_, unresolved) =
+                    render_template_checked(&Value::String(template.clone()), &resolved)?;
+                classify(unresolved, &mut issues);
+            }
+
+            if step.is_data() {
+                steps.push(PlannedStep {
+                    name: step.name.clone(),
+                    tool: None,
+                    skill: None,
+                    tool_found: None,
+                    arguments: step.data.clone().unwrap_or(Value::Null),
+                    deferred,
+                    condition: step.condition.clone(),
+                    for_each: None,
+                    depends_on: step.depends_on.clone(),
+                    data_file: step.data_file.clone(),
+                });
+                continue;
+            }
+
+            let (arguments, unresolved) = render_template_checked(&step.arguments, &resolved)?;
+            classify(unresolved, &mut issues);
+
+            if step.is_nested() {
+                steps.push(PlannedStep {
+                    name: step.name.clone(),
+                    tool: None,
+                    skill: step.skill.clone(),
+                    tool_found: None,
+                    arguments,
+                    deferred,
+                    condition: step.condition.clone(),
+                    for_each: step.for_each.clone(),
+                    depends_on: step.depends_on.clone(),
+                    data_file: None,
+                });
+                continue;
+            }
+
+            let tool_found = tools.map(|tools| {
+                let definition = tools.iter().find(|t| t.name == step.tool);
+                match definition {
+                    Some(definition) => check_arguments(step, definition, &arguments, &mut issues),
+                    None => issues.push(PlanIssue::step(
+                        &step.name,
+                        format!("unknown tool '{}'", step.tool),
+                    )),
+                }
+                definition.is_some()
+            });
+
+            steps.push(PlannedStep {
+                name: step.name.clone(),
+                tool: Some(step.tool.clone()),
+                skill: None,
+                tool_found,
+                arguments,
+                deferred,
+                condition: step.condition.clone(),
+                for_each: step.for_each.clone(),
+                depends_on: step.depends_on.clone(),
+                data_file: None,
+            });
+        }
+
+        // Templates are walked in map order, which depends on serde_json's
+        // `preserve_order` feature; list findings by step, then sorted
+        let ranks: HashMap<&str, usize> = steps
+            .iter()
+            .enumerate()
+            .map(|(rank, step)| (step.name.as_str(), rank))
+            .collect();
+        issues.sort_by_cached_key(|issue| {
+            let rank = issue.step.as_deref().and_then(|s| ranks.get(s).copied());
+            (rank, issue.message.clone())
+        });
+        for step in &mut steps {
+            step.deferred.sort();
+        }
+
+        Ok(ExecutionPlan {
+            skill: self.name.clone(),
+            inputs: resolved.into_iter().collect(),
+            steps,
+            waves,
+            issues,
+        })
+    }
+}
+
+/// Check resolved arguments against a tool's required parameters, skipping
+/// values that are still placeholders
+fn check_arguments(
+    step: &crate::SkillStep,
+    definition: &ToolDefinition,
+    arguments: &Value,
+    issues: &mut Vec<PlanIssue>,
+) {
+    // `for_each` arguments are rendered per item, and other shapes can't
+    // be checked field by field
+    let Some(fields) = arguments.as_object().filter(|_| step.for_each.is_none()) else {
+        return;
+    };
+    for param in definition.required_parameters() {
+        match fields.get(&param.name) {
+            None => issues.push(PlanIssue::step(
+                &step.name,
+                format!(
+                    "missing required argument '{}' of tool '{}'",
+                    param.name, definition.name
+                ),
+            )),
+            Some(Value::String(s)) if s.contains("{{") => {}
+            Some(value) if !param.param_type.matches(value) => issues.push(PlanIssue::step(
+                &step.name,
+                format!(
+                    "argument '{}' should be {}, got {}",
+                    param.name,
+                    param.param_type.as_str(),
+                    json_type_name(value)
+                ),
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+/// The variable a template path starts from, e.g. `search` in
+/// `search.items[0]`
+pub(crate) fn path_root(path: &str) -> &str {
+    path.split(['.', '[']).next().unwrap_or(path).trim()
+}
+
+impl std::fmt::Display for ExecutionPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Plan for skill '{}'", self.skill)?;
+        if !self.inputs.is_empty() {
+            writeln!(f, "Inputs:")?;
+            for (name, value) in &self.inputs {
+                writeln!(f, "  {} = {}", name, value)?;
+            }
+        }
+        writeln!(f, "Steps:")?;
+        for (i, step) in self.steps.iter().enumerate() {
+            match (&step.tool, &step.data_file) {
+                (None, _) if step.skill.is_some() => {
+                    let skill = step.skill.as_deref().unwrap_or_default();
+                    writeln!(f, "  {}. {} -> skill {}", i + 1, step.name, skill)?
+                }
+                (Some(tool), _) => {
+                    let note = match step.tool_found {
+                        Some(false) => " (unknown tool)",
+                        _ => "",
+                    };
+                    writeln!(f, "  {}. {} -> {}{}", i + 1, step.name, tool, note)?;
+                }
+                (None, Some(file)) => {
+                    writeln!(f, "  {}. {} <- data file {}", i + 1, step.name, file)?
+                }
+                (None, None) => writeln!(f, "  {}. {} <- data", i + 1, step.name)?,
+            }
+            if !step.depends_on.is_empty() {
+                writeln!(f, "     after: {}", step.depends_on.join(", "))?;
+            }
+            if let Some(condition) = &step.condition {
+                writeln!(f, "     when: {}", condition)?;
+            }
+            if let Some(for_each) = &step.for_each {
+                writeln!(f, "     for each: {}", for_each)?;
+            }
+            if !step.arguments.is_null() {
+                writeln!(f, "     arguments: {}", step.arguments)?;
+            }
+            if !step.deferred.is_empty() {
+                writeln!(f, "     resolved at run time: {}", step.deferred.join(", "))?;
+            }
+        }
+        if !self.issues.is_empty() {
+            writeln!(f, "Issues:")?;
+            for issue in &self.issues {
+                match &issue.step {
+                    Some(step) => writeln!(f, "  - {}: {}", step, issue.message)?,
+                    None => writeln!(f, "  - {}", issue.message)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SkillStep;
+    use serde_json::json;
+    use thulp_core::Parameter;
+
+    fn step(name: &str, tool: &str, arguments: Value) -> SkillStep {
+        SkillStep {
+            name: name.to_string(),
+            tool: tool.to_string(),
+            arguments,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_resolves_inputs_and_defers_outputs() {
+        let skill = Skill::new("fetch", "")
+            .with_input("url")
+            .with_input_default("limit", json!(10))
+            .with_computed_input("double", "{{limit}} * 2")
+            .with_step(step(
+                "get",
+                "http_get",
+                json!({
+                    "url": "{{url}}",
+                    "limit": "{{double}}",
+                    "key": "{{secret:api_key}}",
+                    "project": "{{workspace.context.project_id}}"
+                }),
+            ))
+            .with_step(SkillStep {
+                for_each: Some("{{get.links}}".to_string()),
+                ..step(
+                    "visit",
+                    "http_get",
+                    json!({"url": "{{item.href}}", "n": "{{index}}"}),
+                )
+            });
+        let mut inputs = HashMap::new();
+        inputs.insert("url".to_string(), json!("https://example.com"));
+
+        let plan = skill.plan(&inputs, None).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.issues);
+        assert_eq!(plan.inputs["double"], json!(20));
+        assert_eq!(
+            plan.steps[0].arguments,
+            json!({
+                "url": "https://example.com",
+                "limit": 20,
+                "key": "{{secret:api_key}}",
+                "project": "{{workspace.context.project_id}}"
+            })
+        );
+        assert_eq!(
+            plan.steps[0].deferred,
+            vec!["secret:api_key", "workspace.context.project_id"]
+        );
+        assert_eq!(plan.steps[0].tool_found, None);
+        assert_eq!(
+            plan.step("visit").unwrap().deferred,
+            vec!["get.links", "index", "item.href"]
+        );
+        assert!(plan.waves.is_empty());
+    }
+
+    #[test]
+    fn test_plan_reports_issues() {
+        let skill = Skill::new("broken", "")
+            .with_input("query")
+            .with_step(step(
+                "search",
+                "search",
+                json!({"q": "{{query}}", "limit": "{{summary.n}}"}),
+            ))
+            .with_step(step("summary", "summarize", json!({"text": "{{typo}}"})))
+            .with_step(step("write", "write_file", json!({"path": 5})));
+        let tools = [
+            ToolDefinition::builder("search")
+                .parameter(Parameter::required_string("q"))
+                .build(),
+            ToolDefinition::builder("write_file")
+                .parameter(Parameter::required_string("path"))
+                .parameter(Parameter::required_string("content"))
+                .build(),
+        ];
+
+        let plan = skill.plan(&HashMap::new(), Some(&tools)).unwrap();
+        let messages: Vec<_> = plan
+            .issues
+            .iter()
+            .map(|issue| {
+                format!(
+                    "{}: {}",
+                    issue.step.as_deref().unwrap_or("-"),
+                    issue.message
+                )
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "-: missing input 'query'",
+                "search: 'summary.n' reads step 'summary', which hasn't run yet",
+                "search: nothing provides 'query'",
+                "summary: nothing provides 'typo'",
+                "summary: unknown tool 'summarize'",
+                "write: argument 'path' should be string, got number",
+                "write: missing required argument 'content' of tool 'write_file'",
+            ]
+        );
+        assert_eq!(plan.step("summary").unwrap().tool_found, Some(false));
+        assert!(plan
+            .to_string()
+            .contains("summary -> summarize (unknown tool)"));
+    }
+
+    #[test]
+    fn test_plan_uses_waves_for_dependencies() {
+        let skill = Skill::new("dag", "")
+            .with_step(step("a", "t", json!({})))
+            .with_step(step("b", "t", json!({"x": "{{c}}"})))
+            .with_step(SkillStep {
+                depends_on: vec!["a".to_string()],
+                ..step("c", "t", json!({"x": "{{a}}"}))
+            });
+
+        let plan = skill.plan(&HashMap::new(), None).unwrap();
+        assert_eq!(plan.waves, vec![vec!["a", "b"], vec!["c"]]);
+        let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        // `b` runs alongside `a`, before `c`
+        assert_eq!(plan.issues.len(), 1);
+        assert_eq!(plan.issues[0].step.as_deref(), Some("b"));
+    }
+}
@@ -0,0 +1,412 @@
+//! Execution progress as a stream of events.
+//!
+//! [`ProgressHooks`] turns the [`ExecutionHooks`] callbacks into
+//! [`ProgressEvent`] values sent over a channel, so a frontend can render
+//! progress (a progress bar, a log, an NDJSON stream) on its own task
+//! without implementing the hooks itself:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use thulp_skills::{DefaultSkillExecutor, ProgressHooks};
+//!
+//! let (hooks, mut events) = ProgressHooks::channel();
+//! let executor = DefaultSkillExecutor::from_arcs(transport, Arc::new(hooks));
+//! tokio::spawn(async move {
+//!     while let Some(event) = events.recv().await {
+//!         println!("{}", serde_json::to_string(&event).unwrap());
+//!     }
+//! });
+//! executor.execute(&skill, &mut context).await?;
+//! ```
+//!
+//! The channel closes when the hooks are dropped, which is when the executor
+//! holding them is.
+
+use crate::{
+    ApprovalDecision, ApprovalRecord, ExecutionContext, ExecutionHooks, Skill, SkillResult,
+    SkillStep, StepResult,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// One step of execution progress.
+///
+/// `depth` is 0 for events of the skill being run and one more for each
+/// level of nested skill. Step `index` is zero-based and `total` is the
+/// number of steps of the skill the step belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A skill started.
+    SkillStarted {
+        depth: usize,
+        skill: String,
+        steps: usize,
+    },
+
+    /// A step started.
+    StepStarted {
+        depth: usize,
+        index: usize,
+        total: usize,
+        step: String,
+        tool: String,
+    },
+
+    /// A step failed and is about to be retried.
+    StepRetrying {
+        depth: usize,
+        step: String,
+        attempt: usize,
+        error: String,
+    },
+
+    /// A step was skipped because its condition was false.
+    StepSkipped {
+        depth: usize,
+        index: usize,
+        total: usize,
+        step: String,
+    },
+
+    /// A step timed out.
+    StepTimedOut {
+        depth: usize,
+        step: String,
+        duration_ms: u64,
+    },
+
+    /// A step finished, successfully or not.
+    StepFinished {
+        depth: usize,
+        index: usize,
+        total: usize,
+        step: String,
+        success: bool,
+        duration_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// A skill finished, successfully or not.
+    SkillFinished {
+        depth: usize,
+        skill: String,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// A step that requires approval was approved or denied.
+    ApprovalDecided {
+        depth: usize,
+        step: String,
+        approved: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+
+    /// Execution was cancelled, during `step` if one was running.
+    Cancelled {
+        depth: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        step: Option<String>,
+    },
+}
+
+/// [`ExecutionHooks`] that send a [`ProgressEvent`] for each lifecycle
+/// callback.
+///
+/// Events are dropped silently once the receiver is gone, so a frontend
+/// that stops listening never fails the execution.
+#[derive(Debug)]
+pub struct ProgressHooks {
+    sender: mpsc::UnboundedSender<ProgressEvent>,
+    /// Step counts of the skills being executed, innermost last
+    skills: Mutex<Vec<usize>>,
+}
+
+impl ProgressHooks {
+    /// Create hooks sending to `sender`.
+    pub fn new(sender: mpsc::UnboundedSender<ProgressEvent>) -> Self {
+        Self {
+            sender,
+            skills: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create hooks and the receiver of their events.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self::new(sender), receiver)
+    }
+
+    fn send(&self, event: ProgressEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Depth and step count of the innermost running skill.
+    fn current(&self) -> (usize, usize) {
+        let skills = self.skills.lock().unwrap();
+        (
+            skills.len().saturating_sub(1),
+            skills.last().copied().unwrap_or(0),
+        )
+    }
+}
+
+impl ExecutionHooks for ProgressHooks {
+    fn before_skill(&self, skill: &Skill, _context: &ExecutionContext) {
+        let depth = {
+            let mut skills = self.skills.lock().unwrap();
+            skills.push(skill.steps.len());
+            skills.len() - 1
+        };
+        self.send(ProgressEvent::SkillStarted {
+            depth,
+            skill: skill.name.clone(),
+            steps: skill.steps.len(),
+        });
+    }
+
+    fn after_skill(&self, skill: &Skill, result: &SkillResult, _context: &ExecutionContext) {
+        let depth = {
+            let mut skills = self.skills.lock().unwrap();
+            skills.pop();
+            skills.len()
+        };
+        self.send(ProgressEvent::SkillFinished {
+            depth,
+            skill: skill.name.clone(),
+            success: result.success,
+            error: result.error.clone(),
+        });
+    }
+
+    fn before_step(&self, step: &SkillStep, step_index: usize, _context: &ExecutionContext) {
+        let (depth, total) = self.current();
+        self.send(ProgressEvent::StepStarted {
+            depth,
+            index: step_index,
+            total,
+            step: step.name.clone(),
+            tool: step.tool.clone(),
+        });
+    }
+
+    fn on_skip(&self, step: &SkillStep, step_index: usize, _context: &ExecutionContext) {
+        let (depth, total) = self.current();
+        self.send(ProgressEvent::StepSkipped {
+            depth,
+            index: step_index,
+            total,
+            step: step.name.clone(),
+        });
+    }
+
+    fn after_step(
+        &self,
+        step: &SkillStep,
+        step_index: usize,
+        result: &StepResult,
+        _context: &ExecutionContext,
+    ) {
+        let (depth, total) = self.current();
+        self.send(ProgressEvent::StepFinished {
+            depth,
+            index: step_index,
+            total,
+            step: step.name.clone(),
+            success: result.success,
+            duration_ms: result.duration_ms,
+            error: result.error.clone(),
+        });
+    }
+
+    fn on_retry(&self, step: &SkillStep, attempt: usize, error: &str, _context: &ExecutionContext) {
+        let (depth, _) = self.current();
+        self.send(ProgressEvent::StepRetrying {
+            depth,
+            step: step.name.clone(),
+            attempt,
+            error: error.to_string(),
+        });
+    }
+
+    fn on_timeout(&self, step: &SkillStep, duration_ms: u64, _context: &ExecutionContext) {
+        let (depth, _) = self.current();
+        self.send(ProgressEvent::StepTimedOut {
+            depth,
+            step: step.name.clone(),
+            duration_ms,
+        });
+    }
+
+    fn on_approval(&self, step: &SkillStep, record: &ApprovalRecord, _context: &ExecutionContext) {
+        let (depth, _) = self.current();
+        let reason = match &record.decision {
+            ApprovalDecision::Approved => None,
+            ApprovalDecision::Denied { reason } => Some(reason.clone()),
+        };
+        self.send(ProgressEvent::ApprovalDecided {
+            depth,
+            step: step.name.clone(),
+            approved: reason.is_none(),
+            reason,
+        });
+    }
+
+    fn on_cancel(&self, step: Option<&SkillStep>, _context: &ExecutionContext) {
+        let (depth, _) = self.current();
+        self.send(ProgressEvent::Cancelled {
+            depth,
+            step: step.map(|step| step.name.clone()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultSkillExecutor, SkillExecutor};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Arc;
+    use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+
+    struct OkTransport;
+
+    #[async_trait]
+    impl Transport for OkTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            Ok(ToolResult::success(json!(call.tool)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_progress_events() {
+        let skill = Skill::new("report", "Write a report")
+            .with_step(SkillStep {
+                name: "fetch".to_string(),
+                tool: "http.get".to_string(),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "never".to_string(),
+                tool: "noop".to_string(),
+                condition: Some("false".to_string()),
+                ..Default::default()
+            });
+
+        let (hooks, mut events) = ProgressHooks::channel();
+        let executor = DefaultSkillExecutor::from_arcs(Arc::new(OkTransport), Arc::new(hooks));
+        let mut context = ExecutionContext::new();
+        executor.execute(&skill, &mut context).await.unwrap();
+        drop(executor);
+
+        let mut received = Vec::new();
+        while let Some(mut event) = events.recv().await {
+            if let ProgressEvent::StepFinished { duration_ms, .. } = &mut event {
+                *duration_ms = 0;
+            }
+            received.push(event);
+        }
+        assert_eq!(
+            received,
+            vec![
+                ProgressEvent::SkillStarted {
+                    depth: 0,
+                    skill: "report".to_string(),
+                    steps: 2,
+                },
+                ProgressEvent::StepStarted {
+                    depth: 0,
+                    index: 0,
+                    total: 2,
+                    step: "fetch".to_string(),
+                    tool: "http.get".to_string(),
+                },
+                ProgressEvent::StepFinished {
+                    depth: 0,
+                    index: 0,
+                    total: 2,
+                    step: "fetch".to_string(),
+                    success: true,
+                    duration_ms: 0,
+                    error: None,
+                },
+                ProgressEvent::StepSkipped {
+                    depth: 0,
+                    index: 1,
+                    total: 2,
+                    step: "never".to_string(),
+                },
+                ProgressEvent::SkillFinished {
+                    depth: 0,
+                    skill: "report".to_string(),
+                    success: true,
+                    error: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = ProgressEvent::StepFinished {
+            depth: 0,
+            index: 1,
+            total: 3,
+            step: "fetch".to_string(),
+            success: false,
+            duration_ms: 12,
+            error: Some("boom".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "event": "step_finished",
+                "depth": 0,
+                "index": 1,
+                "total": 3,
+                "step": "fetch",
+                "success": false,
+                "duration_ms": 12,
+                "error": "boom",
+            })
+        );
+
+        let event = ProgressEvent::Cancelled {
+            depth: 0,
+            step: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "event": "cancelled", "depth": 0 })
+        );
+    }
+
+    #[test]
+    fn test_send_after_receiver_dropped() {
+        let (hooks, events) = ProgressHooks::channel();
+        drop(events);
+        hooks.before_skill(&Skill::new("s", "d"), &ExecutionContext::new());
+    }
+}
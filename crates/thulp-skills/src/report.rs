@@ -0,0 +1,330 @@
+//! Versioned JSON form of skill results for external consumers.
+//!
+//! [`SkillResult`] and [`StepResult`] are the executor's working types and
+//! change along with it. [`SkillReport`] is the contract for programs that
+//! read results (the CLI's JSON output, daemons, dashboards): fields are
+//! only added, and anything else bumps [`REPORT_VERSION`].
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "skill": "search_and_summarize",
+//!   "status": "failed",
+//!   "started_at": "2026-10-17T09:30:00.000Z",
+//!   "finished_at": "2026-10-17T09:30:01.250Z",
+//!   "duration_ms": 1250,
+//!   "error": { "code": "step_failed", "message": "..." },
+//!   "steps": [
+//!     { "name": "search", "status": "success", "duration_ms": 800, "output": [] },
+//!     { "name": "summarize", "status": "failed", "duration_ms": 450,
+//!       "error": { "code": "step_failed", "message": "..." } }
+//!   ],
+//!   "usage": { "tool_calls": 2, "cost": 2.0, "duration_ms": 1250 }
+//! }
+//! ```
+//!
+//! Timestamps are RFC 3339 in UTC with milliseconds, and error codes are
+//! those of [`SkillError::code`], or [`STEP_FAILED`] for a failed step.
+
+use crate::{Degradation, ExecutionUsage, SkillError, SkillResult, StepResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+/// Version of the report format.
+pub const REPORT_VERSION: u32 = 1;
+
+/// Error code of a step that failed, and of a run that failed because of one.
+pub const STEP_FAILED: &str = "step_failed";
+
+/// Outcome of a skill run or step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Success,
+    Failed,
+}
+
+impl ReportStatus {
+    fn from_success(success: bool) -> Self {
+        if success {
+            Self::Success
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+/// An error with a stable code to match on and a message for people.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportError {
+    pub code: String,
+    pub message: String,
+}
+
+impl ReportError {
+    /// Create an error with `code`.
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<&SkillError> for ReportError {
+    fn from(error: &SkillError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+/// Report of one step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepReport {
+    pub name: String,
+    pub status: ReportStatus,
+    /// Time spent on the step, retries included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Retries made, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ReportError>,
+}
+
+impl From<&StepResult> for StepReport {
+    fn from(result: &StepResult) -> Self {
+        Self {
+            name: result.step_name.clone(),
+            status: ReportStatus::from_success(result.success),
+            duration_ms: Some(result.duration_ms),
+            retries: Some(result.retry_attempts),
+            output: result.output.clone(),
+            error: result
+                .error
+                .as_ref()
+                .map(|message| ReportError::new(STEP_FAILED, message.clone())),
+        }
+    }
+}
+
+/// Tool calls, cost and time used by a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub tool_calls: usize,
+    pub cost: f64,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub calls_by_tool: BTreeMap<String, usize>,
+}
+
+impl From<&ExecutionUsage> for UsageReport {
+    fn from(usage: &ExecutionUsage) -> Self {
+        Self {
+            tool_calls: usage.tool_calls,
+            cost: usage.cost,
+            duration_ms: usage.duration_ms,
+            calls_by_tool: usage.calls_by_tool.clone(),
+        }
+    }
+}
+
+/// Report of a skill run, in the versioned format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillReport {
+    /// Format version, [`REPORT_VERSION`] when created by this crate
+    pub version: u32,
+    pub skill: String,
+    pub status: ReportStatus,
+    /// RFC 3339 timestamp of the start of the run
+    pub started_at: String,
+    /// RFC 3339 timestamp of the end of the run
+    pub finished_at: String,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ReportError>,
+    /// Steps that ran, in order; skipped steps are not listed
+    #[serde(default)]
+    pub steps: Vec<StepReport>,
+    /// Usage, absent when the run failed before producing a result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageReport>,
+    /// Step failures the run carried on from, see
+    /// [`degradation`](crate::degradation)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degradations: Vec<Degradation>,
+}
+
+impl SkillReport {
+    /// Report the outcome of running `skill` between `started_at` and
+    /// `finished_at`.
+    pub fn new(
+        skill: impl Into<String>,
+        outcome: &Result<SkillResult, SkillError>,
+        started_at: SystemTime,
+        finished_at: SystemTime,
+    ) -> Self {
+        let duration = finished_at
+            .duration_since(started_at)
+            .unwrap_or(Duration::ZERO);
+        let mut report = Self {
+            version: REPORT_VERSION,
+            skill: skill.into(),
+            status: ReportStatus::Failed,
+            started_at: format_timestamp(started_at),
+            finished_at: format_timestamp(finished_at),
+            duration_ms: duration.as_millis() as u64,
+            output: None,
+            error: None,
+            steps: Vec::new(),
+            usage: None,
+            degradations: Vec::new(),
+        };
+        match outcome {
+            Ok(result) => {
+                report.status = ReportStatus::from_success(result.success);
+                report.output = result.output.clone();
+                report.error = result
+                    .error
+                    .as_ref()
+                    .map(|message| ReportError::new(STEP_FAILED, message.clone()));
+                report.steps = result
+                    .step_results
+                    .iter()
+                    .map(|(name, result)| StepReport {
+                        name: name.clone(),
+                        status: ReportStatus::from_success(result.success),
+                        duration_ms: result.duration_ms,
+                        retries: None,
+                        output: result.data.clone(),
+                        error: result
+                            .error
+                            .as_ref()
+                            .map(|message| ReportError::new(STEP_FAILED, message.clone())),
+                    })
+                    .collect();
+                report.usage = Some(UsageReport::from(&result.usage));
+                report.degradations = result.degradations.clone();
+            }
+            Err(error) => report.error = Some(ReportError::from(error)),
+        }
+        report
+    }
+
+    /// Whether the run succeeded.
+    pub fn is_success(&self) -> bool {
+        self.status == ReportStatus::Success
+    }
+}
+
+/// Format a time as an RFC 3339 timestamp in UTC with milliseconds.
+pub fn format_timestamp(time: SystemTime) -> String {
+    humantime::format_rfc3339_millis(time).to_string()
+}
+
+/// Parse an RFC 3339 timestamp such as those of [`SkillReport`].
+pub fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    humantime::parse_rfc3339_weak(timestamp).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use thulp_core::ToolResult;
+
+    fn at(millis: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_report_of_result() {
+        let result = SkillResult {
+            success: false,
+            step_results: vec![
+                (
+                    "search".to_string(),
+                    ToolResult::success(json!(["a"])).with_duration(800),
+                ),
+                (
+                    "summarize".to_string(),
+                    ToolResult::failure("model unavailable").with_duration(450),
+                ),
+            ],
+            output: None,
+            error: Some("model unavailable".to_string()),
+            usage: ExecutionUsage {
+                tool_calls: 2,
+                cost: 2.0,
+                duration_ms: 1250,
+                calls_by_tool: BTreeMap::new(),
+            },
+            degradations: Vec::new(),
+        };
+        let report = SkillReport::new(
+            "search_and_summarize",
+            &Ok(result),
+            at(1_792_229_400_000),
+            at(1_792_229_401_250),
+        );
+
+        assert!(!report.is_success());
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "version": REPORT_VERSION,
+                "skill": "search_and_summarize",
+                "status": "failed",
+                "started_at": "2026-10-17T09:30:00.000Z",
+                "finished_at": "2026-10-17T09:30:01.250Z",
+                "duration_ms": 1250,
+                "error": { "code": "step_failed", "message": "model unavailable" },
+                "steps": [
+                    { "name": "search", "status": "success", "duration_ms": 800, "output": ["a"] },
+                    {
+                        "name": "summarize",
+                        "status": "failed",
+                        "duration_ms": 450,
+                        "error": { "code": "step_failed", "message": "model unavailable" }
+                    }
+                ],
+                "usage": { "tool_calls": 2, "cost": 2.0, "duration_ms": 1250 }
+            })
+        );
+        assert_eq!(parse_timestamp(&report.started_at), Some(at(1_792_229_400_000)));
+    }
+
+    #[test]
+    fn test_report_of_error() {
+        let report = SkillReport::new(
+            "deploy",
+            &Err(SkillError::BudgetExceeded("3 tool calls".to_string())),
+            at(0),
+            at(5),
+        );
+        assert_eq!(report.status, ReportStatus::Failed);
+        assert_eq!(report.error.as_ref().unwrap().code, "budget_exceeded");
+        assert!(report.steps.is_empty());
+        assert!(report.usage.is_none());
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: SkillReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_step_report() {
+        let mut result = StepResult::failure("fetch", "connection reset", 30);
+        result.retry_attempts = 2;
+        let report = StepReport::from(&result);
+        assert_eq!(report.status, ReportStatus::Failed);
+        assert_eq!(report.retries, Some(2));
+        assert_eq!(report.error.unwrap().code, STEP_FAILED);
+    }
+}
@@ -1,11 +1,53 @@
 //! Retry utilities for skill execution.
 //!
 //! This module provides retry logic with configurable backoff strategies.
+//!
+//! While a step runs, its arguments can refer to the retry state through
+//! two template variables, so a retry can change what it asks for:
+//!
+//! - `{{__attempt}}`: the attempt number, starting at 1
+//! - `{{__last_error}}`: the error that caused the retry, or null on the
+//!   first attempt; timeouts are reported as `"timeout"`
+//!
+//! ```ignore
+//! json!({
+//!     "query": "{{query}}",
+//!     "attempt": "{{__attempt}}",
+//!     "note": "previous attempt failed with: {{__last_error | default('nothing')}}",
+//! })
+//! ```
 
 use crate::config::{BackoffStrategy, RetryConfig, RetryableError};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::future::Future;
 use std::time::Duration;
 
+/// Template variable holding the current attempt number of a step.
+pub const ATTEMPT_VARIABLE: &str = "__attempt";
+
+/// Template variable holding the error that caused the current retry.
+pub const LAST_ERROR_VARIABLE: &str = "__last_error";
+
+/// Set the retry state variables for an attempt.
+pub(crate) fn set_attempt_variables(
+    variables: &mut HashMap<String, Value>,
+    attempt: usize,
+    last_error: Option<&str>,
+) {
+    variables.insert(ATTEMPT_VARIABLE.to_string(), Value::from(attempt));
+    variables.insert(
+        LAST_ERROR_VARIABLE.to_string(),
+        last_error.map_or(Value::Null, Value::from),
+    );
+}
+
+/// Remove the retry state variables once a step is done.
+pub(crate) fn clear_attempt_variables(variables: &mut HashMap<String, Value>) {
+    variables.remove(ATTEMPT_VARIABLE);
+    variables.remove(LAST_ERROR_VARIABLE);
+}
+
 /// Errors that can occur during retried execution.
 #[derive(Debug, thiserror::Error)]
 pub enum RetryError<E> {
@@ -0,0 +1,470 @@
+//! Running several skills at once.
+//!
+//! A [`SkillRunManager`] launches skill runs as tokio tasks over one
+//! [`SkillExecutor`], each with its own [`ExecutionContext`] and optionally
+//! a session it belongs to, and keeps track of them so an orchestrator can
+//! poll their status, cancel them and await their results:
+//!
+//! ```rust,ignore
+//! let manager = SkillRunManager::new(Arc::new(executor)).with_max_concurrent(4);
+//! let research = manager.launch_in_session(research, ExecutionContext::from_inputs(a), "chat-42");
+//! let digest = manager.launch(digest, ExecutionContext::from_inputs(b));
+//!
+//! for run in manager.runs() {
+//!     println!("{} {} {:?}", run.id, run.skill, run.status);
+//! }
+//! digest.cancel();
+//! let outcome = research.wait().await;
+//! println!("{:?}: {:?}", outcome.status, outcome.result.map(|r| r.output));
+//! ```
+//!
+//! Runs share the executor's transport and hooks; anything that belongs to
+//! one run, such as its inputs, budgets, secrets and approval handler, goes
+//! in its context, which comes back in the [`RunOutcome`]. Cancelling a run
+//! cancels its context's [`CancellationToken`].
+//! With [`with_max_concurrent`](SkillRunManager::with_max_concurrent), runs
+//! over the limit wait as [`RunStatus::Queued`].
+
+use crate::{ExecutionContext, Skill, SkillError, SkillExecutor, SkillResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Identifier of a run, unique within its manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RunId(pub u64);
+
+impl std::fmt::Display for RunId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "run-{}", self.0)
+    }
+}
+
+/// Where a run is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Waiting for a free slot
+    Queued,
+    /// Executing
+    Running,
+    /// Finished and the skill succeeded
+    Succeeded,
+    /// Finished and the skill failed
+    Failed,
+    /// Stopped by cancellation
+    Cancelled,
+}
+
+impl RunStatus {
+    /// Check whether the run has finished.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// What a manager knows about a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunInfo {
+    /// Run identifier
+    pub id: RunId,
+
+    /// Name of the skill being run
+    pub skill: String,
+
+    /// Session the run belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+
+    /// Current status
+    pub status: RunStatus,
+}
+
+/// A finished run
+#[derive(Debug)]
+pub struct RunOutcome {
+    /// Run identifier
+    pub id: RunId,
+
+    /// Final status
+    pub status: RunStatus,
+
+    /// What the executor returned
+    pub result: Result<SkillResult, SkillError>,
+
+    /// The run's context, with its outputs, usage and approvals
+    pub context: ExecutionContext,
+}
+
+/// Status of a run, shared by its entry, handle and task
+type SharedStatus = Arc<Mutex<RunStatus>>;
+
+fn set_status(status: &SharedStatus, value: RunStatus) {
+    *status.lock().unwrap() = value;
+}
+
+struct RunEntry {
+    skill: String,
+    session: Option<String>,
+    status: SharedStatus,
+    cancellation: CancellationToken,
+}
+
+impl RunEntry {
+    fn status(&self) -> RunStatus {
+        *self.status.lock().unwrap()
+    }
+
+    fn info(&self, id: RunId) -> RunInfo {
+        RunInfo {
+            id,
+            skill: self.skill.clone(),
+            session: self.session.clone(),
+            status: self.status(),
+        }
+    }
+}
+
+/// Launches skill runs concurrently and tracks them.
+///
+/// Clones share the same runs. See [`runs`](crate::runs).
+#[derive(Clone)]
+pub struct SkillRunManager {
+    executor: Arc<dyn SkillExecutor>,
+    runs: Arc<Mutex<BTreeMap<RunId, RunEntry>>>,
+    next_id: Arc<AtomicU64>,
+    slots: Option<Arc<Semaphore>>,
+}
+
+impl std::fmt::Debug for SkillRunManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkillRunManager")
+            .field("runs", &self.runs.lock().unwrap().len())
+            .field("slots", &self.slots.as_ref().map(|s| s.available_permits()))
+            .finish()
+    }
+}
+
+impl SkillRunManager {
+    /// Create a manager running skills with `executor`, all at once.
+    pub fn new(executor: Arc<dyn SkillExecutor>) -> Self {
+        Self {
+            executor,
+            runs: Arc::default(),
+            next_id: Arc::default(),
+            slots: None,
+        }
+    }
+
+    /// Run at most `max` skills at a time, queueing the others in launch
+    /// order.
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.slots = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Start running `skill` with `context` in the background.
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn launch(&self, skill: Skill, context: ExecutionContext) -> RunHandle {
+        self.start(skill, context, None)
+    }
+
+    /// Start running `skill` as part of `session`, which is reported with
+    /// the run.
+    pub fn launch_in_session(
+        &self,
+        skill: Skill,
+        context: ExecutionContext,
+        session: impl Into<String>,
+    ) -> RunHandle {
+        self.start(skill, context, Some(session.into()))
+    }
+
+    fn start(
+        &self,
+        skill: Skill,
+        mut context: ExecutionContext,
+        session: Option<String>,
+    ) -> RunHandle {
+        let id = RunId(self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        let cancellation = context.cancellation_token().clone();
+        let status = Arc::new(Mutex::new(match self.slots {
+            Some(_) => RunStatus::Queued,
+            None => RunStatus::Running,
+        }));
+        self.runs.lock().unwrap().insert(
+            id,
+            RunEntry {
+                skill: skill.name.clone(),
+                session,
+                status: status.clone(),
+                cancellation: cancellation.clone(),
+            },
+        );
+
+        let executor = self.executor.clone();
+        let slots = self.slots.clone();
+        let shared = status.clone();
+        let task = tokio::spawn(async move {
+            let _slot = match slots {
+                Some(slots) => tokio::select! {
+                    slot = slots.acquire_owned() => slot.ok(),
+                    _ = context.cancellation_token().cancelled() => {
+                        set_status(&shared, RunStatus::Cancelled);
+                        return RunOutcome {
+                            id,
+                            status: RunStatus::Cancelled,
+                            result: Err(SkillError::Cancelled),
+                            context,
+                        };
+                    }
+                },
+                None => None,
+            };
+            set_status(&shared, RunStatus::Running);
+            let result = executor.execute(&skill, &mut context).await;
+            let status = match &result {
+                Ok(result) if result.success => RunStatus::Succeeded,
+                _ if context.is_cancelled() => RunStatus::Cancelled,
+                _ => RunStatus::Failed,
+            };
+            set_status(&shared, status);
+            RunOutcome {
+                id,
+                status,
+                result,
+                context,
+            }
+        });
+
+        RunHandle {
+            id,
+            status,
+            cancellation,
+            task,
+        }
+    }
+
+    /// Get the status of a run, `None` if the manager doesn't know it.
+    pub fn status(&self, id: RunId) -> Option<RunStatus> {
+        self.runs.lock().unwrap().get(&id).map(RunEntry::status)
+    }
+
+    /// All known runs, oldest first.
+    pub fn runs(&self) -> Vec<RunInfo> {
+        let runs = self.runs.lock().unwrap();
+        runs.iter().map(|(&id, entry)| entry.info(id)).collect()
+    }
+
+    /// Runs that belong to `session`, oldest first.
+    pub fn session_runs(&self, session: &str) -> Vec<RunInfo> {
+        self.runs()
+            .into_iter()
+            .filter(|run| run.session.as_deref() == Some(session))
+            .collect()
+    }
+
+    /// Cancel a run. Returns whether it was known and not finished.
+    pub fn cancel(&self, id: RunId) -> bool {
+        let runs = self.runs.lock().unwrap();
+        match runs.get(&id) {
+            Some(entry) if !entry.status().is_finished() => {
+                entry.cancellation.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel every run that hasn't finished.
+    pub fn cancel_all(&self) {
+        let runs = self.runs.lock().unwrap();
+        for entry in runs.values() {
+            if !entry.status().is_finished() {
+                entry.cancellation.cancel();
+            }
+        }
+    }
+
+    /// Forget finished runs, returning how many there were.
+    pub fn remove_finished(&self) -> usize {
+        let mut runs = self.runs.lock().unwrap();
+        let before = runs.len();
+        runs.retain(|_, entry| !entry.status().is_finished());
+        before - runs.len()
+    }
+}
+
+/// A run launched by a [`SkillRunManager`]
+///
+/// Dropping the handle leaves the run going; its result is then lost.
+#[derive(Debug)]
+pub struct RunHandle {
+    id: RunId,
+    status: SharedStatus,
+    cancellation: CancellationToken,
+    task: JoinHandle<RunOutcome>,
+}
+
+impl RunHandle {
+    /// The run's identifier.
+    pub fn id(&self) -> RunId {
+        self.id
+    }
+
+    /// The run's current status.
+    pub fn status(&self) -> RunStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// Stop the run before its next step, interrupting in-flight tool calls.
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// Wait for the run to finish.
+    ///
+    /// # Panics
+    ///
+    /// Resumes a panic raised while the run executed.
+    pub async fn wait(self) -> RunOutcome {
+        match self.task.await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                set_status(&self.status, RunStatus::Failed);
+                std::panic::resume_unwind(e.into_panic())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultSkillExecutor, SkillStep};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use thulp_core::{ToolCall, ToolDefinition, ToolResult, Transport};
+
+    /// Echoes its arguments, after `delay_ms` if given
+    struct SlowEcho;
+
+    #[async_trait]
+    impl Transport for SlowEcho {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(Vec::new())
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            if let Some(delay) = call.arguments["delay_ms"].as_u64() {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            Ok(ToolResult::success(call.arguments["text"].clone()))
+        }
+    }
+
+    fn skill(name: &str) -> Skill {
+        Skill::new(name, "")
+            .with_input("text")
+            .with_step(SkillStep {
+                name: "echo".to_string(),
+                tool: "echo".to_string(),
+                arguments: json!({"text": "{{text}}", "delay_ms": "{{delay_ms}}"}),
+                ..Default::default()
+            })
+    }
+
+    fn context(text: &str, delay_ms: u64) -> ExecutionContext {
+        ExecutionContext::new()
+            .with_input("text", json!(text))
+            .with_input("delay_ms", json!(delay_ms))
+    }
+
+    fn manager() -> SkillRunManager {
+        SkillRunManager::new(Arc::new(DefaultSkillExecutor::new(SlowEcho)))
+    }
+
+    #[tokio::test]
+    async fn test_runs_concurrently() {
+        let manager = manager();
+        let slow = manager.launch_in_session(skill("slow"), context("a", 200), "chat");
+        let fast = manager.launch(skill("fast"), context("b", 0));
+
+        let fast = fast.wait().await;
+        assert_eq!(fast.status, RunStatus::Succeeded);
+        assert_eq!(fast.result.unwrap().output, Some(json!("b")));
+        assert_eq!(fast.context.get_output("echo"), Some(&json!("b")));
+        // The slow run started first and is still going
+        assert_eq!(slow.status(), RunStatus::Running);
+
+        let sessions: Vec<_> = manager.session_runs("chat").iter().map(|r| r.id).collect();
+        assert_eq!(sessions, vec![slow.id()]);
+        let slow = slow.wait().await;
+        assert_eq!(slow.result.unwrap().output, Some(json!("a")));
+
+        let statuses: Vec<_> = manager.runs().iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![RunStatus::Succeeded; 2]);
+        assert_eq!(manager.remove_finished(), 2);
+        assert!(manager.runs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_runs() {
+        let manager = manager().with_max_concurrent(1);
+        let running = manager.launch(skill("running"), context("a", 10_000));
+        let queued = manager.launch(skill("queued"), context("b", 0));
+        assert_eq!(queued.status(), RunStatus::Queued);
+
+        assert!(manager.cancel(queued.id()));
+        let queued = queued.wait().await;
+        assert_eq!(queued.status, RunStatus::Cancelled);
+        assert!(matches!(queued.result, Err(SkillError::Cancelled)));
+
+        running.cancel();
+        let running = tokio::time::timeout(Duration::from_secs(5), running.wait())
+            .await
+            .expect("cancelled run should stop");
+        assert_eq!(running.status, RunStatus::Cancelled);
+        assert!(!manager.cancel(running.id));
+        assert_eq!(manager.status(RunId(99)), None);
+
+        // The slot is free again
+        let next = manager.launch(skill("next"), context("c", 0)).wait().await;
+        assert_eq!(next.status, RunStatus::Succeeded);
+    }
+
+    #[test]
+    fn test_run_info_serialization() {
+        let info = RunInfo {
+            id: RunId(3),
+            skill: "research".to_string(),
+            session: None,
+            status: RunStatus::Queued,
+        };
+        assert_eq!(info.id.to_string(), "run-3");
+        let value: Value = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            value,
+            json!({"id": 3, "skill": "research", "status": "queued"})
+        );
+    }
+}
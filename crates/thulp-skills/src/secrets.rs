@@ -0,0 +1,173 @@
+//! Secrets in step arguments.
+//!
+//! A placeholder `{{secret:name}}` in a step's arguments, `for_each` or
+//! `cache_key` is replaced with the secret from the resolver set with
+//! [`ExecutionContext::with_secrets`], such as a workspace's secrets store:
+//!
+//! ```yaml
+//! - name: issues
+//!   tool: github.list_issues
+//!   arguments:
+//!     repo: "{{repo}}"
+//!     token: "{{secret:github_token}}"
+//! ```
+//!
+//! Filters apply as usual (`{{secret:token | trim}}`). A secret that isn't
+//! set fails the step with [`SkillError::UnresolvedVariable`] whatever the
+//! [`TemplateStrictness`](crate::TemplateStrictness), since passing the
+//! placeholder on is never what was meant.
+//!
+//! Every value resolved is added to the context's
+//! [`secret_redactor`](crate::ExecutionContext::secret_redactor), which masks it in
+//! snapshots and step errors. Hand the redactor to
+//! [`TracingHooks::with_redactor`](crate::TracingHooks::with_redactor) or a
+//! session recorder to mask it there too; clones taken before the run see the
+//! values resolved during it. Dry-run [plans](crate::plan) leave secret
+//! references unresolved.
+//!
+//! [`ExecutionContext::with_secrets`]: crate::ExecutionContext::with_secrets
+//! [`ExecutionContext::secret_redactor`]: crate::ExecutionContext::secret_redactor
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thulp_core::{SecretRedactor, SecretResolver};
+
+use crate::{Result, SkillError, SkillStep};
+
+/// Prefix of variable paths that name a secret.
+pub const SECRET_PREFIX: &str = "secret:";
+
+/// The secrets available to a run and the values handed out so far
+#[derive(Clone, Default)]
+pub(crate) struct Secrets {
+    resolver: Option<Arc<dyn SecretResolver>>,
+    redactor: SecretRedactor,
+}
+
+impl std::fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Secrets")
+            .field("resolver", &self.resolver.is_some())
+            .field("resolved", &self.redactor.len())
+            .finish()
+    }
+}
+
+impl Secrets {
+    pub(crate) fn with_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    pub(crate) fn redactor(&self) -> &SecretRedactor {
+        &self.redactor
+    }
+
+    /// `variables` plus the secrets that `step`'s templates reference, or
+    /// `variables` as is when there are none.
+    pub(crate) fn step_variables<'a>(
+        &self,
+        step: &SkillStep,
+        variables: &'a HashMap<String, Value>,
+    ) -> Result<Cow<'a, HashMap<String, Value>>> {
+        let mut names = Vec::new();
+        collect_references(&step.arguments, &mut names);
+        for template in [&step.for_each, &step.cache_key].into_iter().flatten() {
+            collect_str_references(template, &mut names);
+        }
+        if names.is_empty() {
+            return Ok(Cow::Borrowed(variables));
+        }
+
+        let mut variables = variables.clone();
+        for name in names {
+            let value = self
+                .resolver
+                .as_ref()
+                .and_then(|resolver| resolver.resolve(name.trim_start_matches(SECRET_PREFIX)))
+                .ok_or_else(|| SkillError::UnresolvedVariable {
+                    step: step.name.clone(),
+                    variable: name.clone(),
+                })?;
+            self.redactor.add(value.clone());
+            variables.insert(name, Value::String(value));
+        }
+        Ok(Cow::Owned(variables))
+    }
+}
+
+/// Collect the `secret:name` paths of placeholders in every string of a value
+fn collect_references(value: &Value, names: &mut Vec<String>) {
+    match value {
+        Value::String(s) => collect_str_references(s, names),
+        Value::Array(items) => items.iter().for_each(|v| collect_references(v, names)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_references(v, names)),
+        _ => {}
+    }
+}
+
+fn collect_str_references(template: &str, names: &mut Vec<String>) {
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let Some(len) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        let inner = &rest[open + 2..open + 2 + len];
+        let path = inner.split('|').next().unwrap_or_default().trim();
+        if path.starts_with(SECRET_PREFIX) && !names.iter().any(|name| name == path) {
+            names.push(path.to_string());
+        }
+        rest = &rest[open + len + 4..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use thulp_core::Redactor;
+
+    #[test]
+    fn test_step_variables() {
+        let resolver = HashMap::from([("token".to_string(), "t0k3n".to_string())]);
+        let secrets = Secrets::default().with_resolver(Arc::new(resolver));
+        let variables = HashMap::from([("repo".to_string(), json!("thulp"))]);
+
+        let plain = SkillStep {
+            arguments: json!({"repo": "{{repo}}"}),
+            ..Default::default()
+        };
+        assert!(matches!(
+            secrets.step_variables(&plain, &variables).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        let step = SkillStep {
+            arguments: json!({"auth": ["Bearer {{ secret:token | trim }}"], "repo": "{{repo}}"}),
+            ..Default::default()
+        };
+        let resolved = secrets.step_variables(&step, &variables).unwrap();
+        assert_eq!(resolved["secret:token"], "t0k3n");
+        assert_eq!(resolved["repo"], "thulp");
+        assert_eq!(
+            secrets.redactor().redact(&json!("Bearer t0k3n")),
+            json!("Bearer [REDACTED]")
+        );
+
+        let missing = SkillStep {
+            name: "fetch".to_string(),
+            cache_key: Some("{{secret:other}}".to_string()),
+            ..Default::default()
+        };
+        let err = secrets.step_variables(&missing, &variables).unwrap_err();
+        assert!(matches!(
+            err,
+            SkillError::UnresolvedVariable { ref variable, .. } if variable == "secret:other"
+        ));
+        assert!(Secrets::default()
+            .step_variables(&step, &variables)
+            .is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Context snapshots for inspecting past skill runs.
+//!
+//! When enabled with [`ExecutionContext::with_snapshots`], the executor records
+//! a [`ContextSnapshot`] right before each step runs: the variables visible to
+//! the step's templates and the arguments they resolved to. After the run,
+//! [`ContextSnapshot::resolve`] evaluates any template against the state at
+//! that point, and a [`SnapshotLog`] persists the snapshots under
+//! `.thulp/runs/<run-id>.json` for `thulp skill inspect`.
+//!
+//! Recording is bounded: once the limit is reached the oldest snapshot is
+//! dropped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut context = ExecutionContext::new()
+//!     .with_input("query", json!("rust"))
+//!     .with_snapshots(100);
+//! executor.execute(&skill, &mut context).await?;
+//!
+//! for snapshot in context.snapshots() {
+//!     println!("{} -> {}", snapshot.step_name, snapshot.resolve("{{query}}")?);
+//! }
+//! SnapshotLog::new(run_id, &skill.name, context.snapshots().to_vec()).save(&runs_dir)?;
+//! ```
+//!
+//! [`ExecutionContext::with_snapshots`]: crate::ExecutionContext::with_snapshots
+
+use crate::template::render_template;
+use crate::{Result, SkillError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// State of the execution context at a step boundary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSnapshot {
+    /// Index of the step in the skill
+    pub step_index: usize,
+
+    /// Name of the step about to run
+    pub step_name: String,
+
+    /// Tool the step calls
+    pub tool: String,
+
+    /// Inputs and earlier step outputs visible to the step's templates
+    pub variables: HashMap<String, Value>,
+
+    /// Arguments after template substitution (an array for `for_each` steps)
+    pub arguments: Value,
+}
+
+impl ContextSnapshot {
+    /// Resolve a template such as `"{{search.results}}"` against the
+    /// variables at this point of the run.
+    pub fn resolve(&self, template: &str) -> Result<Value> {
+        render_template(&Value::String(template.to_string()), &self.variables)
+    }
+}
+
+/// Snapshots of one skill run, as persisted for later inspection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotLog {
+    /// Identifier of the run
+    pub run_id: String,
+
+    /// Name of the skill that ran
+    pub skill: String,
+
+    /// Snapshots in step order
+    pub snapshots: Vec<ContextSnapshot>,
+}
+
+impl SnapshotLog {
+    /// Create a log for a run.
+    pub fn new(
+        run_id: impl Into<String>,
+        skill: impl Into<String>,
+        snapshots: Vec<ContextSnapshot>,
+    ) -> Self {
+        Self {
+            run_id: run_id.into(),
+            skill: skill.into(),
+            snapshots,
+        }
+    }
+
+    /// Path of the log for `run_id` inside `dir`.
+    pub fn path(dir: &Path, run_id: &str) -> PathBuf {
+        dir.join(format!("{}.json", run_id))
+    }
+
+    /// Write the log to `dir/<run_id>.json`, creating `dir` if needed.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = Self::path(dir, &self.run_id);
+        std::fs::create_dir_all(dir)
+            .and_then(|_| {
+                let json = serde_json::to_string_pretty(self)?;
+                std::fs::write(&path, json)
+            })
+            .map_err(|e| {
+                SkillError::Execution(format!(
+                    "Failed to save snapshots to {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        Ok(path)
+    }
+
+    /// Load the log for `run_id` from `dir`.
+    pub fn load(dir: &Path, run_id: &str) -> Result<Self> {
+        let path = Self::path(dir, run_id);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|_| SkillError::NotFound(format!("run '{}' in {}", run_id, dir.display())))?;
+        serde_json::from_str(&content).map_err(|e| {
+            SkillError::InvalidConfig(format!("Invalid snapshot log {}: {}", path.display(), e))
+        })
+    }
+
+    /// Find the snapshot taken before the named step.
+    pub fn step(&self, step_name: &str) -> Option<&ContextSnapshot> {
+        self.snapshots.iter().find(|s| s.step_name == step_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot() -> ContextSnapshot {
+        ContextSnapshot {
+            step_index: 1,
+            step_name: "summarize".to_string(),
+            tool: "llm".to_string(),
+            variables: HashMap::from([
+                ("query".to_string(), json!("rust")),
+                (
+                    "search".to_string(),
+                    json!({"count": 2, "results": ["a", "b"]}),
+                ),
+            ]),
+            arguments: json!({"text": ["a", "b"]}),
+        }
+    }
+
+    #[test]
+    fn test_resolve_templates() {
+        let snapshot = snapshot();
+        assert_eq!(
+            snapshot.resolve("{{search.results}}").unwrap(),
+            json!(["a", "b"])
+        );
+        assert_eq!(
+            snapshot
+                .resolve("{{search.count}} results for {{query}}")
+                .unwrap(),
+            json!("2 results for rust")
+        );
+        assert_eq!(
+            snapshot.resolve("{{missing}}").unwrap(),
+            json!("{{missing}}")
+        );
+    }
+
+    #[test]
+    fn test_log_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let log = SnapshotLog::new("run-1", "research", vec![snapshot()]);
+
+        let path = log.save(temp.path()).unwrap();
+        assert_eq!(path, temp.path().join("run-1.json"));
+
+        let loaded = SnapshotLog::load(temp.path(), "run-1").unwrap();
+        assert_eq!(loaded, log);
+        assert!(loaded.step("summarize").is_some());
+        assert!(loaded.step("search").is_none());
+
+        assert!(matches!(
+            SnapshotLog::load(temp.path(), "run-2"),
+            Err(SkillError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+//! Memoizing step results.
+//!
+//! With a [`StepCache`] on the [`ExecutionContext`], a tool step whose call
+//! was already answered successfully gets the stored result instead of
+//! calling the tool again. Results are keyed by the tool and its rendered
+//! arguments, with object keys sorted. When arguments carry fields that
+//! don't change the result (timestamps, request ids), a step's
+//! [`cache_key`](crate::SkillStep::cache_key) template sets the key instead:
+//!
+//! ```yaml
+//! - name: search
+//!   tool: web.search
+//!   arguments:
+//!     query: "{{query}}"
+//!     requested_at: "{{now}}"
+//!   cache_key: "{{query}}"
+//! ```
+//!
+//! The template sees the same variables as the arguments, `{{item}}` and
+//! `{{index}}` included for `for_each` steps. Clones of a cache share its
+//! entries, so one cache can serve several runs. Only use it with tools
+//! whose calls are idempotent; a memoized write is silently skipped.
+//!
+//! [`ExecutionContext`]: crate::ExecutionContext
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use thulp_core::ToolResult;
+
+/// Counts of step cache lookups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepCacheStats {
+    /// Steps answered from the cache
+    pub hits: u64,
+    /// Steps that called their tool
+    pub misses: u64,
+    /// Results currently stored
+    pub entries: usize,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: Mutex<HashMap<String, ToolResult>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Successful step results by key, shared by clones.
+#[derive(Debug, Clone, Default)]
+pub struct StepCache {
+    inner: Arc<Inner>,
+}
+
+impl StepCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the counts of hits and misses so far.
+    pub fn stats(&self) -> StepCacheStats {
+        StepCacheStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            entries: self.inner.entries.lock().unwrap().len(),
+        }
+    }
+
+    /// Drop every stored result.
+    pub fn clear(&self) {
+        self.inner.entries.lock().unwrap().clear();
+    }
+
+    /// Look up a result, counting the hit or miss.
+    pub(crate) fn get(&self, key: &str) -> Option<ToolResult> {
+        let result = self.inner.entries.lock().unwrap().get(key).cloned();
+        let counter = match result {
+            Some(_) => &self.inner.hits,
+            None => &self.inner.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Store a result if it is successful.
+    pub(crate) fn put(&self, key: String, result: &ToolResult) {
+        if result.is_success() {
+            self.inner
+                .entries
+                .lock()
+                .unwrap()
+                .insert(key, result.clone());
+        }
+    }
+}
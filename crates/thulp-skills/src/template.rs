@@ -0,0 +1,489 @@
+//! Templates in step arguments.
+//!
+//! String values in [`SkillStep::arguments`](crate::SkillStep::arguments)
+//! may contain `{{ ... }}` placeholders, each a variable path followed by
+//! optional filters:
+//!
+//! - Paths: `{{search}}`, `{{search.items[0].id}}`, `{{search.items.0.id}}`,
+//!   `{{headers["content-type"]}}`, `{{search.items.length}}`
+//! - Filters: `{{name | upper}}`, `{{query | trim | lower}}`,
+//!   `{{limit | default(10)}}`, `{{filters | json}}`, `{{tags | join(', ')}}`
+//!
+//! A string that is exactly one placeholder is replaced by the value itself,
+//! keeping its JSON type; otherwise each placeholder is interpolated as text.
+//! Substitution works on JSON values rather than serialized text, so quotes
+//! and backslashes in variables never break the arguments. A placeholder
+//! whose path doesn't resolve and that has no `default` is left as written;
+//! the executor then passes it on, warns or fails depending on
+//! [`TemplateStrictness`].
+//!
+//! ## Filters
+//!
+//! | Filter | Result |
+//! |--------|--------|
+//! | `json` | The value serialized as JSON text |
+//! | `upper`, `lower`, `trim` | String case and whitespace |
+//! | `length` | Size of a string, array or object |
+//! | `first`, `last` | First or last element of an array or string |
+//! | `join(sep)` | Array elements joined with a separator |
+//! | `default(value)` | `value` if the path is missing or null |
+//!
+//! Filter arguments are JSON literals or single-quoted strings.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use thulp_skills::render_template;
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert("search".to_string(), json!({"items": [{"id": 7, "title": "Rust"}]}));
+//!
+//! let args = json!({
+//!     "id": "{{search.items[0].id}}",
+//!     "label": "{{search.items[0].title | upper}} #{{search.items[0].id}}",
+//!     "limit": "{{limit | default(20)}}",
+//! });
+//! assert_eq!(
+//!     render_template(&args, &vars).unwrap(),
+//!     json!({"id": 7, "label": "RUST #7", "limit": 20})
+//! );
+//! ```
+
+use crate::condition::resolve_path;
+use crate::{Result, SkillError, TemplateStrictness};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Substitute placeholders in every string of a JSON value.
+pub fn render_template(value: &Value, variables: &HashMap<String, Value>) -> Result<Value> {
+    render_value(value, variables, &mut Vec::new())
+}
+
+/// Substitute placeholders like [`render_template`], also returning the
+/// paths of placeholders that were left unresolved.
+pub fn render_template_checked(
+    value: &Value,
+    variables: &HashMap<String, Value>,
+) -> Result<(Value, Vec<String>)> {
+    let mut unresolved = Vec::new();
+    let rendered = render_value(value, variables, &mut unresolved)?;
+    Ok((rendered, unresolved))
+}
+
+/// Render a step's template and apply `strictness` to what's left unresolved.
+pub(crate) fn render_step_template(
+    value: &Value,
+    variables: &HashMap<String, Value>,
+    step: &str,
+    strictness: TemplateStrictness,
+) -> Result<Value> {
+    let (rendered, unresolved) = render_template_checked(value, variables)?;
+    match strictness {
+        TemplateStrictness::PassThrough => {}
+        TemplateStrictness::Warn => {
+            for variable in &unresolved {
+                tracing::warn!(step, variable = %variable, "Unresolved template variable");
+            }
+        }
+        TemplateStrictness::Error => {
+            if let Some(variable) = unresolved.into_iter().next() {
+                return Err(SkillError::UnresolvedVariable {
+                    step: step.to_string(),
+                    variable,
+                });
+            }
+        }
+    }
+    Ok(rendered)
+}
+
+/// Paths read by the placeholders in every string of a JSON value, each
+/// with whether a `default` filter stands in when it doesn't resolve.
+/// Malformed placeholders are skipped.
+pub(crate) fn placeholder_paths(value: &Value) -> Vec<(String, bool)> {
+    let mut paths = Vec::new();
+    collect_paths(value, &mut paths);
+    paths
+}
+
+fn collect_paths(value: &Value, paths: &mut Vec<(String, bool)>) {
+    match value {
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(open) = rest.find("{{") {
+                let Some(len) = rest[open + 2..].find("}}") else {
+                    break;
+                };
+                if let Ok(placeholder) = Placeholder::parse(&rest[open + 2..open + 2 + len]) {
+                    let defaulted = placeholder
+                        .filters
+                        .iter()
+                        .any(|f| matches!(f, Filter::Default(_)));
+                    paths.push((placeholder.path.to_string(), defaulted));
+                }
+                rest = &rest[open + len + 4..];
+            }
+        }
+        Value::Array(arr) => arr.iter().for_each(|v| collect_paths(v, paths)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_paths(v, paths)),
+        _ => {}
+    }
+}
+
+fn render_value(
+    value: &Value,
+    variables: &HashMap<String, Value>,
+    unresolved: &mut Vec<String>,
+) -> Result<Value> {
+    match value {
+        Value::String(s) => render_string(s, variables, unresolved),
+        Value::Array(arr) => arr
+            .iter()
+            .map(|v| render_value(v, variables, unresolved))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(obj) => {
+            let mut rendered = serde_json::Map::new();
+            for (k, v) in obj {
+                rendered.insert(k.clone(), render_value(v, variables, unresolved)?);
+            }
+            Ok(Value::Object(rendered))
+        }
+        // Numbers, booleans, nulls pass through unchanged
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Render one string, keeping the value's type for a lone placeholder
+fn render_string(
+    template: &str,
+    variables: &HashMap<String, Value>,
+    unresolved: &mut Vec<String>,
+) -> Result<Value> {
+    let trimmed = template.trim();
+    if let Some(inner) = trimmed
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+    {
+        if !inner.contains("{{") && !inner.contains("}}") {
+            if let Some(value) = Placeholder::parse(inner)?.evaluate(variables) {
+                return Ok(value);
+            }
+        }
+    }
+
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let Some(len) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[open..open + len + 4];
+        result.push_str(&rest[..open]);
+        let parsed = Placeholder::parse(&placeholder[2..len + 2])?;
+        match parsed.evaluate(variables) {
+            Some(value) => result.push_str(&to_text(&value)),
+            None => {
+                unresolved.push(parsed.path.to_string());
+                result.push_str(placeholder);
+            }
+        }
+        rest = &rest[open + len + 4..];
+    }
+    result.push_str(rest);
+    Ok(Value::String(result))
+}
+
+/// Text form of a value for interpolation
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A parsed `{{ path | filter(arg) }}` placeholder
+#[derive(Debug)]
+struct Placeholder<'a> {
+    path: &'a str,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug)]
+enum Filter {
+    Json,
+    Upper,
+    Lower,
+    Trim,
+    Length,
+    First,
+    Last,
+    Join(String),
+    Default(Value),
+}
+
+impl<'a> Placeholder<'a> {
+    fn parse(source: &'a str) -> Result<Self> {
+        let mut parts = split_pipes(source).into_iter();
+        let path = parts.next().unwrap_or_default().trim();
+        let filters = parts
+            .map(|part| {
+                Filter::parse(part.trim()).map_err(|reason| {
+                    SkillError::InvalidConfig(format!(
+                        "Invalid template '{{{{{}}}}}': {}",
+                        source, reason
+                    ))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { path, filters })
+    }
+
+    /// Resolve the path and apply the filters; `None` if the path doesn't
+    /// resolve and no filter supplies a default
+    fn evaluate(&self, variables: &HashMap<String, Value>) -> Option<Value> {
+        let mut value = resolve_path(self.path, variables);
+        for filter in &self.filters {
+            value = match (filter, value) {
+                (Filter::Default(fallback), None | Some(Value::Null)) => Some(fallback.clone()),
+                (_, None) => None,
+                (filter, Some(value)) => Some(filter.apply(value)),
+            };
+        }
+        value
+    }
+}
+
+impl Filter {
+    fn parse(source: &str) -> std::result::Result<Self, String> {
+        let (name, arg) = match source.split_once('(') {
+            Some((name, rest)) => {
+                let arg = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("missing ')' in filter '{}'", source))?;
+                (name.trim(), Some(parse_literal(arg.trim())?))
+            }
+            None => (source, None),
+        };
+        let filter = match (name, arg) {
+            ("json", None) => Filter::Json,
+            ("upper", None) => Filter::Upper,
+            ("lower", None) => Filter::Lower,
+            ("trim", None) => Filter::Trim,
+            ("length", None) => Filter::Length,
+            ("first", None) => Filter::First,
+            ("last", None) => Filter::Last,
+            ("join", Some(sep)) => Filter::Join(to_text(&sep)),
+            ("join", None) => Filter::Join(String::new()),
+            ("default", Some(value)) => Filter::Default(value),
+            ("default", None) => return Err("filter 'default' needs a value".to_string()),
+            ("json" | "upper" | "lower" | "trim" | "length" | "first" | "last", Some(_)) => {
+                return Err(format!("filter '{}' takes no argument", name))
+            }
+            _ => return Err(format!("unknown filter '{}'", name)),
+        };
+        Ok(filter)
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        match self {
+            Filter::Json => Value::String(value.to_string()),
+            Filter::Upper => Value::String(to_text(&value).to_uppercase()),
+            Filter::Lower => Value::String(to_text(&value).to_lowercase()),
+            Filter::Trim => Value::String(to_text(&value).trim().to_string()),
+            Filter::Length => match &value {
+                Value::Array(arr) => Value::from(arr.len()),
+                Value::Object(obj) => Value::from(obj.len()),
+                Value::String(s) => Value::from(s.chars().count()),
+                _ => Value::Null,
+            },
+            Filter::First | Filter::Last => {
+                let first = matches!(self, Filter::First);
+                match value {
+                    Value::Array(arr) => {
+                        let item = if first { arr.first() } else { arr.last() };
+                        item.cloned().unwrap_or(Value::Null)
+                    }
+                    Value::String(s) => {
+                        let c = if first {
+                            s.chars().next()
+                        } else {
+                            s.chars().last()
+                        };
+                        c.map_or(Value::Null, |c| Value::String(c.to_string()))
+                    }
+                    _ => Value::Null,
+                }
+            }
+            Filter::Join(sep) => match value {
+                Value::Array(arr) => {
+                    Value::String(arr.iter().map(to_text).collect::<Vec<_>>().join(sep))
+                }
+                other => other,
+            },
+            Filter::Default(_) => value,
+        }
+    }
+}
+
+/// Parse a filter argument: JSON, or a single-quoted string
+fn parse_literal(source: &str) -> std::result::Result<Value, String> {
+    if let Some(inner) = source
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return Ok(Value::String(inner.replace("\\'", "'")));
+    }
+    serde_json::from_str(source).map_err(|_| format!("invalid filter argument '{}'", source))
+}
+
+/// Split on `|` outside quotes and brackets
+fn split_pipes(source: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in source.char_indices() {
+        match (quote, c) {
+            (Some(_), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, '|') if depth == 0 => {
+                parts.push(&source[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    parts.push(&source[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> HashMap<String, Value> {
+        let mut vars = HashMap::new();
+        vars.insert("query".to_string(), json!("  Rust \"async\"  "));
+        vars.insert(
+            "search".to_string(),
+            json!({"items": [{"id": 1, "tags": ["a", "b"]}, {"id": 2}], "meta": {"content-type": "json"}}),
+        );
+        vars.insert("empty".to_string(), Value::Null);
+        vars
+    }
+
+    fn render(template: Value) -> Value {
+        render_template(&template, &vars()).unwrap()
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(render(json!("{{search.items[1].id}}")), json!(2));
+        assert_eq!(render(json!("{{ search.items.0.tags[1] }}")), json!("b"));
+        assert_eq!(
+            render(json!("{{search.meta[\"content-type\"]}}")),
+            json!("json")
+        );
+        assert_eq!(render(json!("{{search.items.length}}")), json!(2));
+        assert_eq!(
+            render(json!("ids {{search.items[0].id}},{{search.items[1].id}}")),
+            json!("ids 1,2")
+        );
+        // Unknown paths are left alone
+        assert_eq!(
+            render(json!("{{missing}} and {{search.items[9]}}")),
+            json!("{{missing}} and {{search.items[9]}}")
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            render(json!("{{query | trim | upper}}")),
+            json!("RUST \"ASYNC\"")
+        );
+        assert_eq!(
+            render(json!("{{search.items[0].tags | json}}")),
+            json!("[\"a\",\"b\"]")
+        );
+        assert_eq!(
+            render(json!("{{search.items[0].tags | join(', ')}}")),
+            json!("a, b")
+        );
+        assert_eq!(
+            render(json!("{{search.items | first}}")),
+            json!({"id": 1, "tags": ["a", "b"]})
+        );
+        assert_eq!(render(json!("{{search.items | length}}")), json!(2));
+        assert_eq!(render(json!("{{limit | default(10)}}")), json!(10));
+        assert_eq!(
+            render(json!("{{empty | default('none') | upper}}")),
+            json!("NONE")
+        );
+        assert_eq!(
+            render(json!("sep={{missing | default('a|b')}}")),
+            json!("sep=a|b")
+        );
+    }
+
+    #[test]
+    fn test_escaping_survives() {
+        // Quotes in values used to break the serialized-JSON replacement
+        let rendered = render(json!({"q": "{{query}}", "nested": ["say {{query | trim}}"]}));
+        assert_eq!(rendered["q"], json!("  Rust \"async\"  "));
+        assert_eq!(rendered["nested"][0], json!("say Rust \"async\""));
+    }
+
+    #[test]
+    fn test_unresolved_placeholders() {
+        let args = json!({
+            "a": "{{missing.id}}",
+            "b": ["x {{query}} {{other | upper}}"],
+            "c": "{{gone | default(1)}}",
+        });
+        let (rendered, unresolved) = render_template_checked(&args, &vars()).unwrap();
+        assert_eq!(rendered["a"], json!("{{missing.id}}"));
+        assert_eq!(unresolved, vec!["missing.id", "other"]);
+
+        let strict =
+            render_step_template(&args, &vars(), "fetch", TemplateStrictness::Error).unwrap_err();
+        assert!(matches!(
+            strict,
+            SkillError::UnresolvedVariable { ref step, ref variable }
+                if step == "fetch" && variable == "missing.id"
+        ));
+        for lenient in [TemplateStrictness::Warn, TemplateStrictness::PassThrough] {
+            let value = render_step_template(&args, &vars(), "fetch", lenient).unwrap();
+            assert_eq!(value, rendered);
+        }
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for template in [
+            "{{query | shout}}",
+            "{{query | default}}",
+            "{{query | upper(1)}}",
+            "{{query | join('x'}}",
+        ] {
+            let err = render_template(&json!(template), &vars()).unwrap_err();
+            assert!(
+                matches!(err, SkillError::InvalidConfig(_)),
+                "{template}: {err}"
+            );
+        }
+    }
+}
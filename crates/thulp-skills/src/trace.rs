@@ -0,0 +1,499 @@
+//! Recording skill runs and replaying them offline.
+//!
+//! A [`TraceRecorder`] hands out a [`RecordingTransport`], which captures
+//! every tool call and its result, and [`RecordingHooks`], which add the
+//! skill, its inputs and output and the step each call was made for. The
+//! resulting [`ExecutionTrace`] is saved as JSON, and a [`ReplayTransport`]
+//! built from it serves the recorded results back without any server:
+//!
+//! ```rust,ignore
+//! let recorder = TraceRecorder::new();
+//! let executor = DefaultSkillExecutor::with_hooks(recorder.transport(transport), recorder.hooks());
+//! executor.execute(&skill, &mut context).await?;
+//! recorder.trace().save(Path::new("tests/traces/report.json"))?;
+//!
+//! // Later, offline: a golden-file regression test of the skill
+//! let trace = ExecutionTrace::load(Path::new("tests/traces/report.json"))?;
+//! let executor = DefaultSkillExecutor::new(ReplayTransport::new(trace.clone()));
+//! let mut context = ExecutionContext::from_inputs(trace.inputs.clone());
+//! let result = executor.execute(&skill, &mut context).await?;
+//! assert_eq!(result.output, trace.output);
+//! ```
+//!
+//! Traces hold tool arguments and results as they were, secrets included.
+
+use crate::{
+    ExecutionContext, ExecutionHooks, Result, Skill, SkillError, SkillResult, SkillStep, StepResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use thulp_core::{
+    collect_stream, single_chunk, ToolCall, ToolDefinition, ToolResult, ToolResultStream,
+    Transport,
+};
+
+/// One tool call of a recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// Step the call was made for, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+
+    /// The call as sent to the transport
+    pub call: ToolCall,
+
+    /// Result returned by the transport
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ToolResult>,
+
+    /// Error returned by the transport instead of a result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Time the call took, in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Everything needed to replay a skill run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    /// Name of the skill that ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+
+    /// Inputs the skill ran with
+    #[serde(default)]
+    pub inputs: HashMap<String, Value>,
+
+    /// Tools listed by the transport during the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+
+    /// Tool calls in the order they were made
+    #[serde(default)]
+    pub calls: Vec<RecordedCall>,
+
+    /// Whether the skill succeeded, once it finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+
+    /// Output of the skill
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+impl ExecutionTrace {
+    /// Write the trace to `path` as JSON, creating parent directories.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| save_error(path, e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| save_error(path, e))?;
+        std::fs::write(path, json).map_err(|e| save_error(path, e))
+    }
+
+    /// Load a trace saved with [`save`](Self::save).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|_| SkillError::NotFound(format!("trace {}", path.display())))?;
+        serde_json::from_str(&content).map_err(|e| {
+            SkillError::InvalidConfig(format!("Invalid trace {}: {}", path.display(), e))
+        })
+    }
+}
+
+fn save_error(path: &Path, error: impl std::fmt::Display) -> SkillError {
+    SkillError::Execution(format!(
+        "Failed to save trace to {}: {}",
+        path.display(),
+        error
+    ))
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    trace: ExecutionTrace,
+    /// Steps running, innermost last, with the tool each calls
+    steps: Vec<(String, String)>,
+    /// Skills running
+    depth: usize,
+}
+
+/// Collects an [`ExecutionTrace`] from the transport and hooks it hands out.
+///
+/// Clones share the same trace.
+#[derive(Debug, Clone, Default)]
+pub struct TraceRecorder {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl TraceRecorder {
+    /// Create a recorder with an empty trace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap `inner` so its calls are recorded.
+    pub fn transport<T>(&self, inner: T) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner,
+            recorder: self.clone(),
+        }
+    }
+
+    /// Hooks recording the skill and steps the calls belong to.
+    pub fn hooks(&self) -> RecordingHooks {
+        RecordingHooks {
+            recorder: self.clone(),
+        }
+    }
+
+    /// The trace recorded so far.
+    pub fn trace(&self) -> ExecutionTrace {
+        self.lock().trace.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        self.state.lock().unwrap()
+    }
+
+    fn record(&self, call: &ToolCall, outcome: &thulp_core::Result<ToolResult>, started: Instant) {
+        let mut state = self.lock();
+        // The innermost running step calling this tool; parallel steps
+        // calling the same tool can't be told apart
+        let step = state
+            .steps
+            .iter()
+            .rev()
+            .find(|(_, tool)| tool == &call.tool)
+            .map(|(step, _)| step.clone());
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result.clone()), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        state.trace.calls.push(RecordedCall {
+            step,
+            call: call.clone(),
+            result,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+/// A [`Transport`] wrapper that records every call into a [`TraceRecorder`].
+///
+/// Streaming calls are recorded and returned as their merged result, in a
+/// single chunk.
+pub struct RecordingTransport<T> {
+    inner: T,
+    recorder: TraceRecorder,
+}
+
+impl<T> std::fmt::Debug for RecordingTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingTransport")
+            .field("calls", &self.recorder.lock().trace.calls.len())
+            .finish()
+    }
+}
+
+impl<T> RecordingTransport<T> {
+    /// Get the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the transport.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        let tools = self.inner.list_tools().await?;
+        self.recorder.lock().trace.tools = tools.clone();
+        Ok(tools)
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        let started = Instant::now();
+        let outcome = self.inner.call(call).await;
+        self.recorder.record(call, &outcome, started);
+        outcome
+    }
+
+    async fn call_streaming(&self, call: &ToolCall) -> thulp_core::Result<ToolResultStream> {
+        let started = Instant::now();
+        let outcome = match self.inner.call_streaming(call).await {
+            Ok(stream) => collect_stream(stream).await,
+            Err(e) => Err(e),
+        };
+        self.recorder.record(call, &outcome, started);
+        outcome.map(single_chunk)
+    }
+}
+
+/// [`ExecutionHooks`] adding the skill, its inputs and output, and the step
+/// of each call to a [`TraceRecorder`]'s trace.
+#[derive(Debug, Clone)]
+pub struct RecordingHooks {
+    recorder: TraceRecorder,
+}
+
+impl ExecutionHooks for RecordingHooks {
+    fn before_skill(&self, skill: &Skill, context: &ExecutionContext) {
+        let mut state = self.recorder.lock();
+        if state.depth == 0 {
+            state.trace.skill = Some(skill.name.clone());
+            state.trace.inputs = context.inputs().clone();
+        }
+        state.depth += 1;
+    }
+
+    fn after_skill(&self, _skill: &Skill, result: &SkillResult, _context: &ExecutionContext) {
+        let mut state = self.recorder.lock();
+        state.depth = state.depth.saturating_sub(1);
+        if state.depth == 0 {
+            state.trace.success = Some(result.success);
+            state.trace.output = result.output.clone();
+        }
+    }
+
+    fn before_step(&self, step: &SkillStep, _step_index: usize, _context: &ExecutionContext) {
+        self.recorder
+            .lock()
+            .steps
+            .push((step.name.clone(), step.tool.clone()));
+    }
+
+    fn after_step(
+        &self,
+        step: &SkillStep,
+        _step_index: usize,
+        _result: &StepResult,
+        _context: &ExecutionContext,
+    ) {
+        let mut state = self.recorder.lock();
+        if let Some(index) = state.steps.iter().rposition(|(name, _)| name == &step.name) {
+            state.steps.remove(index);
+        }
+    }
+}
+
+/// A [`Transport`] serving the results of an [`ExecutionTrace`].
+///
+/// Each call gets the result of the first recorded call with the same tool
+/// and arguments that hasn't been replayed yet, so retries replay in order
+/// and parallel steps may run in any order. A call with no such recording
+/// fails.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    tools: Vec<ToolDefinition>,
+    /// Recorded calls, and whether each has been replayed
+    calls: Mutex<Vec<(RecordedCall, bool)>>,
+}
+
+impl ReplayTransport {
+    /// Replay `trace`.
+    pub fn new(trace: ExecutionTrace) -> Self {
+        Self {
+            tools: trace.tools,
+            calls: Mutex::new(trace.calls.into_iter().map(|call| (call, false)).collect()),
+        }
+    }
+
+    /// Replay the trace saved at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self::new(ExecutionTrace::load(path)?))
+    }
+
+    /// Recorded calls that haven't been replayed.
+    ///
+    /// A run of an unchanged skill replays every call, so a golden test can
+    /// check this is empty.
+    pub fn remaining(&self) -> Vec<RecordedCall> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, replayed)| !replayed)
+            .map(|(call, _)| call.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn connect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> thulp_core::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+        Ok(self.tools.clone())
+    }
+
+    async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+        let mut calls = self.calls.lock().unwrap();
+        let Some((recorded, replayed)) = calls
+            .iter_mut()
+            .find(|(recorded, replayed)| !*replayed && recorded.call == *call)
+        else {
+            return Err(thulp_core::Error::ExecutionFailed(format!(
+                "no recorded call to '{}' with arguments {}",
+                call.tool, call.arguments
+            )));
+        };
+        *replayed = true;
+        match (&recorded.result, &recorded.error) {
+            (Some(result), _) => Ok(result.clone()),
+            (None, error) => Err(thulp_core::Error::ExecutionFailed(
+                error.clone().unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultSkillExecutor, ExecutionConfig, RetryConfig, SkillExecutor};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Upper-cases `text`, failing the first `shout` call
+    #[derive(Default)]
+    struct LiveTransport {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for LiveTransport {
+        async fn connect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> thulp_core::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_tools(&self) -> thulp_core::Result<Vec<ToolDefinition>> {
+            Ok(vec![ToolDefinition::builder("shout").build()])
+        }
+
+        async fn call(&self, call: &ToolCall) -> thulp_core::Result<ToolResult> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(thulp_core::Error::ExecutionFailed("busy".to_string()));
+            }
+            let text = call.arguments["text"].as_str().unwrap_or_default();
+            Ok(ToolResult::success(json!(text.to_uppercase())))
+        }
+    }
+
+    fn skill() -> Skill {
+        Skill::new("loud", "Shout twice")
+            .with_input("text")
+            .with_step(SkillStep {
+                name: "once".to_string(),
+                tool: "shout".to_string(),
+                arguments: json!({"text": "{{text}}"}),
+                ..Default::default()
+            })
+            .with_step(SkillStep {
+                name: "twice".to_string(),
+                tool: "shout".to_string(),
+                arguments: json!({"text": "{{once}}!"}),
+                ..Default::default()
+            })
+    }
+
+    fn context(inputs: HashMap<String, Value>) -> ExecutionContext {
+        let retry = RetryConfig {
+            max_retries: 1,
+            initial_delay: std::time::Duration::from_millis(1),
+            ..Default::default()
+        }
+        .retry_all_errors();
+        ExecutionContext::from_inputs(inputs).with_config(ExecutionConfig::new().with_retry(retry))
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let recorder = TraceRecorder::new();
+        let transport = recorder.transport(LiveTransport::default());
+        transport.list_tools().await.unwrap();
+        let executor = DefaultSkillExecutor::with_hooks(transport, recorder.hooks());
+        let inputs = HashMap::from([("text".to_string(), json!("hi"))]);
+        let result = executor
+            .execute(&skill(), &mut context(inputs))
+            .await
+            .unwrap();
+        assert_eq!(result.output, Some(json!("HI!")));
+
+        let trace = recorder.trace();
+        assert_eq!(trace.skill.as_deref(), Some("loud"));
+        assert_eq!(trace.inputs["text"], json!("hi"));
+        assert_eq!(trace.tools.len(), 1);
+        assert_eq!(trace.success, Some(true));
+        assert_eq!(trace.output, Some(json!("HI!")));
+        let steps: Vec<_> = trace.calls.iter().map(|c| c.step.as_deref()).collect();
+        assert_eq!(steps, vec![Some("once"), Some("once"), Some("twice")]);
+        assert_eq!(trace.calls[0].error.as_deref(), Some("tool execution failed: busy"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traces/loud.json");
+        trace.save(&path).unwrap();
+        let loaded = ExecutionTrace::load(&path).unwrap();
+        assert_eq!(loaded, trace);
+
+        let replay = Arc::new(ReplayTransport::new(loaded.clone()));
+        let executor = DefaultSkillExecutor::from_arcs(replay.clone(), Arc::new(crate::NoOpHooks));
+        let replayed = executor
+            .execute(&skill(), &mut context(loaded.inputs.clone()))
+            .await
+            .unwrap();
+        assert_eq!(replayed.output, loaded.output);
+        assert!(replay.remaining().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_unrecorded_call() {
+        let replay = ReplayTransport::new(ExecutionTrace::default());
+        let err = replay
+            .call(&ToolCall::builder("shout").arg_str("text", "hi").build())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no recorded call to 'shout'"));
+    }
+}
@@ -0,0 +1,554 @@
+//! Content-addressed artifact storage.
+//!
+//! This module provides the [`ArtifactStore`] for persisting binary outputs
+//! (downloaded files, scraped pages, rendered reports) produced by skills.
+//! Artifacts are stored by the SHA-256 digest of their content, so storing
+//! the same bytes twice only writes a single blob to disk. Each store
+//! operation adds a reference to the blob; blobs whose reference count drops
+//! to zero are removed by [`ArtifactStore::gc`].
+//!
+//! Blobs live in `{workspace}/.thulp/artifacts/blobs/<xx>/<digest>`, where
+//! `<xx>` is the first two hex characters of the digest. Reference counts
+//! are tracked in `{workspace}/.thulp/artifacts/index.json`. Blobs are
+//! zstd-compressed unless the store is configured with
+//! [`Compression::None`]; the digest is always that of the original content.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use thulp_workspace::{ArtifactStore, Workspace};
+//!
+//! let store = ArtifactStore::new(&workspace).await?;
+//!
+//! let a = store.put("page.html", b"<html></html>").await?;
+//! let b = store.put("page-copy.html", b"<html></html>").await?;
+//! assert_eq!(a.hash, b.hash); // stored once, referenced twice
+//!
+//! store.release(&a.hash).await?;
+//! store.release(&b.hash).await?;
+//! let stats = store.gc().await?;
+//! assert_eq!(stats.removed_blobs, 1);
+//! ```
+
+use crate::compression::{self, Compression};
+use crate::{Result, Workspace, WorkspaceError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// SHA-256 digest identifying an artifact's content, hex encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ContentHash(String);
+
+impl ContentHash {
+    /// Compute the content hash of the given bytes.
+    pub fn of(content: &[u8]) -> Self {
+        let digest = Sha256::digest(content);
+        let mut hex = String::with_capacity(digest.len() * 2);
+        for byte in digest {
+            hex.push_str(&format!("{:02x}", byte));
+        }
+        Self(hex)
+    }
+
+    /// Get the hex encoded digest.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Handle returned when an artifact is stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRef {
+    /// Name the artifact was stored under.
+    pub name: String,
+    /// Content hash of the stored blob.
+    pub hash: ContentHash,
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// Whether the content was already present in the store.
+    pub deduplicated: bool,
+}
+
+/// Index entry for a stored blob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BlobEntry {
+    size: u64,
+    /// Size of the blob file; absent in indexes written before compression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stored_size: Option<u64>,
+    ref_count: usize,
+}
+
+impl BlobEntry {
+    fn disk_size(&self) -> u64 {
+        self.stored_size.unwrap_or(self.size)
+    }
+}
+
+/// Statistics returned by [`ArtifactStore::gc`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// Number of unreferenced blobs removed from disk.
+    pub removed_blobs: usize,
+    /// Total bytes freed on disk.
+    pub freed_bytes: u64,
+}
+
+/// Summary of the store's disk usage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactStats {
+    /// Number of distinct blobs on disk.
+    pub blob_count: usize,
+    /// Total number of references across all blobs.
+    pub reference_count: usize,
+    /// Bytes used on disk by blobs.
+    pub stored_bytes: u64,
+    /// Bytes of content that would be stored without deduplication or
+    /// compression.
+    pub logical_bytes: u64,
+}
+
+/// Content-addressed store for skill artifacts with reference counting.
+pub struct ArtifactStore {
+    /// Root directory of the store.
+    root: PathBuf,
+    /// Reference counts keyed by content hash.
+    index: Arc<RwLock<HashMap<ContentHash, BlobEntry>>>,
+    /// Skip writes to disk and index changes.
+    read_only: bool,
+    /// Encoding of newly written blobs.
+    compression: Compression,
+}
+
+impl ArtifactStore {
+    /// Create an artifact store for the given workspace.
+    ///
+    /// The store is rooted at `{workspace}/.thulp/artifacts/`. For a
+    /// [read-only](Workspace::read_only) workspace the store is read-only
+    /// and the directory is not created.
+    pub async fn new(workspace: &Workspace) -> Result<Self> {
+        let root = workspace.root.join(".thulp").join("artifacts");
+        let store = Self::open(root, workspace.read_only).await?;
+        Ok(store.with_compression(workspace.compression))
+    }
+
+    /// Create an artifact store rooted at a custom directory.
+    ///
+    /// An existing index in the directory is loaded.
+    pub async fn with_root(root: PathBuf) -> Result<Self> {
+        Self::open(root, false).await
+    }
+
+    async fn open(root: PathBuf, read_only: bool) -> Result<Self> {
+        if !read_only {
+            fs::create_dir_all(root.join("blobs")).await?;
+        }
+
+        let index_path = root.join("index.json");
+        let index = match fs::read_to_string(&index_path).await {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| WorkspaceError::Serialization(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(WorkspaceError::Io(e)),
+        };
+
+        debug!(?root, "Initialized artifact store");
+
+        Ok(Self {
+            root,
+            index: Arc::new(RwLock::new(index)),
+            read_only,
+            compression: Compression::default(),
+        })
+    }
+
+    /// Enable or disable read-only mode.
+    ///
+    /// In read-only mode stored artifacts can be read, but `put`, `retain`,
+    /// `release` and `gc` leave the store unchanged and log a warning.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set how new blobs are encoded on disk.
+    ///
+    /// Existing blobs are read correctly whichever way they were written.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Check if the store is in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Log and report a write skipped because of read-only mode.
+    fn skip_write(&self, action: &str) -> bool {
+        if self.read_only {
+            warn!(action, root = ?self.root, "Read-only mode: skipping artifact write");
+        }
+        self.read_only
+    }
+
+    /// Get the path to the blob for a content hash.
+    fn blob_path(&self, hash: &ContentHash) -> PathBuf {
+        let prefix = &hash.as_str()[..2];
+        self.root.join("blobs").join(prefix).join(hash.as_str())
+    }
+
+    /// Persist the index to disk.
+    async fn save_index(&self, index: &HashMap<ContentHash, BlobEntry>) -> Result<()> {
+        let json = serde_json::to_string_pretty(index)
+            .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        fs::write(self.root.join("index.json"), json).await?;
+        Ok(())
+    }
+
+    /// Store content and add a reference to it.
+    ///
+    /// If identical content is already stored, no new blob is written and
+    /// the existing blob's reference count is incremented instead. In
+    /// read-only mode nothing is stored, but the returned reference still
+    /// carries the content's hash.
+    pub async fn put(&self, name: impl Into<String>, content: &[u8]) -> Result<ArtifactRef> {
+        let name = name.into();
+        let hash = ContentHash::of(content);
+        let size = content.len() as u64;
+
+        if self.skip_write("put") {
+            let deduplicated = self.contains(&hash).await;
+            return Ok(ArtifactRef {
+                name,
+                hash,
+                size,
+                deduplicated,
+            });
+        }
+
+        let mut index = self.index.write().await;
+        let path = self.blob_path(&hash);
+
+        // Only trust the index if the blob is actually on disk
+        let deduplicated = index.contains_key(&hash) && fs::try_exists(&path).await?;
+        let mut stored_size = None;
+        if !deduplicated {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let blob = self.compression.encode(content)?;
+            fs::write(&path, &blob).await?;
+            stored_size = Some(blob.len() as u64);
+        }
+
+        let entry = index.entry(hash.clone()).or_insert(BlobEntry {
+            size,
+            stored_size,
+            ref_count: 0,
+        });
+        if stored_size.is_some() {
+            entry.stored_size = stored_size;
+        }
+        entry.ref_count += 1;
+
+        self.save_index(&index).await?;
+
+        debug!(%hash, %name, deduplicated, "Stored artifact");
+        Ok(ArtifactRef {
+            name,
+            hash,
+            size,
+            deduplicated,
+        })
+    }
+
+    /// Read the content of a stored blob, decompressing it if needed.
+    pub async fn get(&self, hash: &ContentHash) -> Result<Vec<u8>> {
+        let blob = fs::read(self.blob_path(hash)).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                WorkspaceError::NotFound(format!("Artifact {} not found", hash))
+            } else {
+                WorkspaceError::Io(e)
+            }
+        })?;
+        compression::decode(blob)
+    }
+
+    /// Add a reference to an already stored blob.
+    pub async fn retain(&self, hash: &ContentHash) -> Result<usize> {
+        let mut index = self.index.write().await;
+        let entry = index
+            .get_mut(hash)
+            .ok_or_else(|| WorkspaceError::NotFound(format!("Artifact {} not found", hash)))?;
+        if self.skip_write("retain") {
+            return Ok(entry.ref_count);
+        }
+        entry.ref_count += 1;
+        let count = entry.ref_count;
+        self.save_index(&index).await?;
+        Ok(count)
+    }
+
+    /// Drop a reference to a blob, returning the remaining reference count.
+    ///
+    /// The blob stays on disk until [`gc`](Self::gc) is run.
+    pub async fn release(&self, hash: &ContentHash) -> Result<usize> {
+        let mut index = self.index.write().await;
+        let entry = index
+            .get_mut(hash)
+            .ok_or_else(|| WorkspaceError::NotFound(format!("Artifact {} not found", hash)))?;
+        if self.skip_write("release") {
+            return Ok(entry.ref_count);
+        }
+        entry.ref_count = entry.ref_count.saturating_sub(1);
+        let count = entry.ref_count;
+        self.save_index(&index).await?;
+        Ok(count)
+    }
+
+    /// Get the reference count for a blob, or `None` if it isn't stored.
+    pub async fn ref_count(&self, hash: &ContentHash) -> Option<usize> {
+        self.index.read().await.get(hash).map(|e| e.ref_count)
+    }
+
+    /// Check if a blob is stored.
+    pub async fn contains(&self, hash: &ContentHash) -> bool {
+        self.index.read().await.contains_key(hash)
+    }
+
+    /// Remove all blobs that have no remaining references.
+    pub async fn gc(&self) -> Result<GcStats> {
+        if self.skip_write("gc") {
+            return Ok(GcStats::default());
+        }
+        let mut index = self.index.write().await;
+        let unreferenced: Vec<ContentHash> = index
+            .iter()
+            .filter(|(_, entry)| entry.ref_count == 0)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+
+        let mut stats = GcStats::default();
+        for hash in unreferenced {
+            match fs::remove_file(self.blob_path(&hash)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(WorkspaceError::Io(e)),
+            }
+            if let Some(entry) = index.remove(&hash) {
+                stats.removed_blobs += 1;
+                stats.freed_bytes += entry.disk_size();
+            }
+        }
+
+        self.save_index(&index).await?;
+
+        info!(
+            removed = stats.removed_blobs,
+            freed_bytes = stats.freed_bytes,
+            "Artifact garbage collection complete"
+        );
+        Ok(stats)
+    }
+
+    /// Get disk usage statistics for the store.
+    pub async fn stats(&self) -> ArtifactStats {
+        let index = self.index.read().await;
+        index
+            .values()
+            .fold(ArtifactStats::default(), |mut stats, entry| {
+                stats.blob_count += 1;
+                stats.reference_count += entry.ref_count;
+                stats.stored_bytes += entry.disk_size();
+                stats.logical_bytes += entry.size * entry.ref_count as u64;
+                stats
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn create_test_store() -> (ArtifactStore, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ArtifactStore::with_root(temp_dir.path().join("artifacts"))
+            .await
+            .unwrap();
+        (store, temp_dir)
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = ContentHash::of(b"hello");
+        assert_eq!(
+            hash.as_str(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(hash, ContentHash::of(b"hello"));
+        assert_ne!(hash, ContentHash::of(b"world"));
+    }
+
+    #[tokio::test]
+    async fn test_put_and_get() {
+        let (store, _temp) = create_test_store().await;
+
+        let artifact = store.put("greeting.txt", b"hello").await.unwrap();
+        assert_eq!(artifact.name, "greeting.txt");
+        assert_eq!(artifact.size, 5);
+        assert!(!artifact.deduplicated);
+
+        let content = store.get(&artifact.hash).await.unwrap();
+        assert_eq!(content, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_content_stored_once() {
+        let (store, _temp) = create_test_store().await;
+
+        let a = store.put("a.html", b"<html></html>").await.unwrap();
+        let b = store.put("b.html", b"<html></html>").await.unwrap();
+
+        assert_eq!(a.hash, b.hash);
+        assert!(b.deduplicated);
+        assert_eq!(store.ref_count(&a.hash).await, Some(2));
+
+        let stats = store.stats().await;
+        assert_eq!(stats.blob_count, 1);
+        assert_eq!(stats.reference_count, 2);
+        assert_eq!(stats.stored_bytes, 13);
+        assert_eq!(stats.logical_bytes, 26);
+    }
+
+    #[tokio::test]
+    async fn test_blobs_compressed_transparently() {
+        let (store, _temp) = create_test_store().await;
+        let page = b"<p>thulp</p>".repeat(100);
+
+        let artifact = store.put("page.html", &page).await.unwrap();
+        assert_eq!(artifact.size, page.len() as u64);
+
+        let blob = std::fs::read(store.blob_path(&artifact.hash)).unwrap();
+        assert!(compression::is_compressed(&blob));
+        assert_eq!(store.get(&artifact.hash).await.unwrap(), page);
+
+        let stats = store.stats().await;
+        assert_eq!(stats.stored_bytes, blob.len() as u64);
+        assert_eq!(stats.logical_bytes, page.len() as u64);
+
+        // Blobs stored without compression read back the same way
+        let plain = store.with_compression(Compression::None);
+        let other = plain
+            .put("other.html", &b"<p>other</p>".repeat(100))
+            .await
+            .unwrap();
+        let blob = std::fs::read(plain.blob_path(&other.hash)).unwrap();
+        assert!(!compression::is_compressed(&blob));
+        assert_eq!(plain.get(&artifact.hash).await.unwrap(), page);
+    }
+
+    #[tokio::test]
+    async fn test_gc_removes_only_unreferenced() {
+        let (store, _temp) = create_test_store().await;
+
+        let kept = store.put("kept", b"keep me").await.unwrap();
+        let dropped = store.put("dropped", b"drop me").await.unwrap();
+
+        assert_eq!(store.release(&dropped.hash).await.unwrap(), 0);
+        // Released blobs remain until gc runs
+        assert!(store.contains(&dropped.hash).await);
+
+        let stats = store.gc().await.unwrap();
+        assert_eq!(stats.removed_blobs, 1);
+        assert_eq!(stats.freed_bytes, 7);
+
+        assert!(!store.contains(&dropped.hash).await);
+        assert!(store.get(&dropped.hash).await.is_err());
+        assert_eq!(store.get(&kept.hash).await.unwrap(), b"keep me");
+    }
+
+    #[tokio::test]
+    async fn test_gc_respects_remaining_references() {
+        let (store, _temp) = create_test_store().await;
+
+        let a = store.put("a", b"shared").await.unwrap();
+        store.put("b", b"shared").await.unwrap();
+
+        assert_eq!(store.release(&a.hash).await.unwrap(), 1);
+        let stats = store.gc().await.unwrap();
+        assert_eq!(stats.removed_blobs, 0);
+        assert!(store.get(&a.hash).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_retain_and_release_unknown() {
+        let (store, _temp) = create_test_store().await;
+        let unknown = ContentHash::of(b"missing");
+
+        assert!(matches!(
+            store.retain(&unknown).await,
+            Err(WorkspaceError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.release(&unknown).await,
+            Err(WorkspaceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_index_persists_across_instances() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("artifacts");
+
+        let hash = {
+            let store = ArtifactStore::with_root(root.clone()).await.unwrap();
+            let artifact = store.put("data.json", b"{}").await.unwrap();
+            store.retain(&artifact.hash).await.unwrap();
+            artifact.hash
+        };
+
+        let store = ArtifactStore::with_root(root).await.unwrap();
+        assert_eq!(store.ref_count(&hash).await, Some(2));
+        assert_eq!(store.get(&hash).await.unwrap(), b"{}");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_leaves_store_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("artifacts");
+        let kept = ArtifactStore::with_root(root.clone())
+            .await
+            .unwrap()
+            .put("kept.txt", b"kept")
+            .await
+            .unwrap();
+
+        let store = ArtifactStore::with_root(root)
+            .await
+            .unwrap()
+            .with_read_only(true);
+        let artifact = store.put("new.txt", b"new").await.unwrap();
+        assert_eq!(artifact.hash, ContentHash::of(b"new"));
+        assert!(!store.contains(&artifact.hash).await);
+
+        assert_eq!(store.release(&kept.hash).await.unwrap(), 1);
+        assert_eq!(store.gc().await.unwrap().removed_blobs, 0);
+        assert_eq!(store.get(&kept.hash).await.unwrap(), b"kept");
+    }
+}
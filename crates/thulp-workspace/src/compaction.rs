@@ -0,0 +1,444 @@
+//! Session compaction.
+//!
+//! Sessions grow with every message, tool call and notification. A
+//! [`CompactionPolicy`] caps a session by entry count and/or serialized size:
+//! once a budget is exceeded, the oldest entries are removed and replaced by a
+//! single [`EntryType::Compacted`] marker recording how many entries were
+//! dropped and, if a [`Summarizer`] is configured, a summary of them. The most
+//! recent entries are always kept.
+//!
+//! Compaction runs on demand with
+//! [`SessionManager::compact_session`](crate::SessionManager::compact_session),
+//! or after every new entry when the manager is configured with
+//! [`with_compaction`](crate::SessionManager::with_compaction).
+//!
+//! # Example
+//!
+//! ```rust
+//! use thulp_workspace::{CompactionPolicy, DigestSummarizer, Session, SessionType};
+//!
+//! let mut session = Session::new("chat", SessionType::Conversation {
+//!     purpose: "demo".to_string(),
+//! });
+//! for i in 0..100 {
+//!     session.add_user_message(format!("message {}", i));
+//! }
+//!
+//! let policy = CompactionPolicy::new()
+//!     .with_max_entries(20)
+//!     .with_keep_recent(10)
+//!     .with_summarizer(DigestSummarizer);
+//! let report = policy.compact(&mut session).unwrap();
+//!
+//! assert_eq!(report.removed_entries, 81);
+//! assert_eq!(session.entries.len(), 20);
+//! ```
+
+use crate::session::{EntryType, Session, SessionEntry};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Produces a summary of entries removed by compaction.
+pub trait Summarizer: Send + Sync {
+    /// Summarize the removed entries, oldest first.
+    ///
+    /// Earlier [`EntryType::Compacted`] markers are included, so their
+    /// summaries can be folded into the new one.
+    fn summarize(&self, entries: &[SessionEntry]) -> Value;
+}
+
+impl<F> Summarizer for F
+where
+    F: Fn(&[SessionEntry]) -> Value + Send + Sync,
+{
+    fn summarize(&self, entries: &[SessionEntry]) -> Value {
+        self(entries)
+    }
+}
+
+/// Summarizer that records entry counts by type, the tools and skills used,
+/// and the time range covered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DigestSummarizer;
+
+impl Summarizer for DigestSummarizer {
+    fn summarize(&self, entries: &[SessionEntry]) -> Value {
+        let mut counts: Map<String, Value> = Map::new();
+        let mut tools = BTreeSet::new();
+        let mut skills = BTreeSet::new();
+        let mut from = entries.first().map(|e| e.timestamp.as_millis());
+        let to = entries.last().map(|e| e.timestamp.as_millis());
+
+        let mut add = |kind: &str, n: u64| {
+            let count = counts.entry(kind).or_insert(json!(0));
+            *count = json!(count.as_u64().unwrap_or(0) + n);
+        };
+
+        for entry in entries {
+            match &entry.entry_type {
+                // Fold in the digest of an earlier compaction
+                EntryType::Compacted { .. } => {
+                    let previous = &entry.content["summary"];
+                    if let Some(by_type) = previous["by_type"].as_object() {
+                        for (kind, n) in by_type {
+                            add(kind, n.as_u64().unwrap_or(0));
+                        }
+                    }
+                    for (key, set) in [("tools", &mut tools), ("skills", &mut skills)] {
+                        let names = previous[key].as_array().into_iter().flatten();
+                        set.extend(names.filter_map(Value::as_str).map(str::to_string));
+                    }
+                    if let Some(start) = previous["from"].as_u64() {
+                        from = from.map(|f| f.min(start));
+                    }
+                }
+                other => {
+                    if let EntryType::ToolCall { tool_name, .. } = other {
+                        tools.insert(tool_name.clone());
+                    }
+                    if let EntryType::SkillExecution { skill_name, .. } = other {
+                        skills.insert(skill_name.clone());
+                    }
+                    add(entry_kind(other), 1);
+                }
+            }
+        }
+
+        json!({
+            "by_type": counts,
+            "tools": tools,
+            "skills": skills,
+            "from": from,
+            "to": to,
+        })
+    }
+}
+
+/// Serialized name of an entry type (`user_message`, `tool_call`, ...).
+fn entry_kind(entry_type: &EntryType) -> &'static str {
+    match entry_type {
+        EntryType::UserMessage => "user_message",
+        EntryType::AssistantMessage => "assistant_message",
+        EntryType::SystemMessage => "system_message",
+        EntryType::ToolCall { .. } => "tool_call",
+        EntryType::SkillExecution { .. } => "skill_execution",
+        EntryType::EvaluationResult { .. } => "evaluation_result",
+        EntryType::SystemEvent { .. } => "system_event",
+        EntryType::ServerLog { .. } => "server_log",
+        EntryType::Progress { .. } => "progress",
+        EntryType::ResourceUpdated { .. } => "resource_updated",
+        EntryType::ServerNotification { .. } => "server_notification",
+        EntryType::Compacted { .. } => "compacted",
+    }
+}
+
+/// Outcome of compacting a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// Entries removed (including earlier markers replaced by the new one)
+    pub removed_entries: usize,
+
+    /// Serialized size of the removed entries in bytes
+    pub removed_bytes: usize,
+
+    /// Entries left, including the marker
+    pub remaining_entries: usize,
+
+    /// Serialized size of the remaining entries in bytes
+    pub remaining_bytes: usize,
+}
+
+/// When and how to compact a session.
+#[derive(Clone)]
+pub struct CompactionPolicy {
+    /// Maximum number of entries, including the compaction marker
+    pub max_entries: Option<usize>,
+
+    /// Maximum serialized size of all entries in bytes
+    pub max_bytes: Option<usize>,
+
+    /// Number of most recent entries that are never removed
+    pub keep_recent: usize,
+
+    /// Summarizer for removed entries (`None` only records counts)
+    summarizer: Option<Arc<dyn Summarizer>>,
+}
+
+impl std::fmt::Debug for CompactionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompactionPolicy")
+            .field("max_entries", &self.max_entries)
+            .field("max_bytes", &self.max_bytes)
+            .field("keep_recent", &self.keep_recent)
+            .field("summarizer", &self.summarizer.is_some())
+            .finish()
+    }
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompactionPolicy {
+    /// Create a policy without budgets, which never compacts.
+    pub fn new() -> Self {
+        Self {
+            max_entries: None,
+            max_bytes: None,
+            keep_recent: 10,
+            summarizer: None,
+        }
+    }
+
+    /// Compact once the session has more than `max` entries.
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
+    }
+
+    /// Compact once the entries serialize to more than `max` bytes.
+    pub fn with_max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = Some(max);
+        self
+    }
+
+    /// Always keep the `count` most recent entries (default: 10).
+    ///
+    /// Budgets may be exceeded if the recent entries alone are over them.
+    pub fn with_keep_recent(mut self, count: usize) -> Self {
+        self.keep_recent = count;
+        self
+    }
+
+    /// Summarize removed entries into the compaction marker.
+    pub fn with_summarizer(mut self, summarizer: impl Summarizer + 'static) -> Self {
+        self.summarizer = Some(Arc::new(summarizer));
+        self
+    }
+
+    /// Check whether a session exceeds either budget.
+    pub fn is_exceeded(&self, session: &Session) -> bool {
+        self.max_entries
+            .is_some_and(|max| session.entries.len() > max)
+            || self
+                .max_bytes
+                .is_some_and(|max| entry_sizes(&session.entries).sum::<usize>() > max)
+    }
+
+    /// Compact a session in place if it exceeds a budget.
+    ///
+    /// Returns `None` if nothing was removed.
+    pub fn compact(&self, session: &mut Session) -> Option<CompactionReport> {
+        if !self.is_exceeded(session) {
+            return None;
+        }
+
+        let sizes: Vec<usize> = entry_sizes(&session.entries).collect();
+        let total_bytes: usize = sizes.iter().sum();
+        let removable = session.entries.len().saturating_sub(self.keep_recent);
+
+        // Remove the oldest entries until both budgets fit, counting the marker
+        let mut remove = 0;
+        let mut removed_bytes = 0;
+        while remove < removable {
+            let remaining = session.entries.len() - remove + 1;
+            let fits_entries = self.max_entries.map_or(true, |max| remaining <= max);
+            let fits_bytes = self
+                .max_bytes
+                .map_or(true, |max| total_bytes - removed_bytes <= max);
+            if fits_entries && fits_bytes && remove > 1 {
+                break;
+            }
+            removed_bytes += sizes[remove];
+            remove += 1;
+        }
+        // Replacing a single entry with a marker gains nothing
+        if remove < 2 {
+            return None;
+        }
+
+        // The marker takes space too; remove more until it fits the byte budget
+        let mut marker = self.marker(&session.entries[..remove], removed_bytes);
+        while let Some(max) = self.max_bytes {
+            let marker_size = entry_sizes(std::slice::from_ref(&marker)).sum::<usize>();
+            if total_bytes - removed_bytes + marker_size <= max || remove >= removable {
+                break;
+            }
+            removed_bytes += sizes[remove];
+            remove += 1;
+            marker = self.marker(&session.entries[..remove], removed_bytes);
+        }
+
+        let removed: Vec<SessionEntry> = session.entries.drain(..remove).collect();
+        session.entries.insert(0, marker);
+
+        Some(CompactionReport {
+            removed_entries: removed.len(),
+            removed_bytes,
+            remaining_entries: session.entries.len(),
+            remaining_bytes: entry_sizes(&session.entries).sum(),
+        })
+    }
+
+    /// Build the marker replacing `removed`
+    fn marker(&self, removed: &[SessionEntry], bytes: usize) -> SessionEntry {
+        let represented: usize = removed
+            .iter()
+            .map(|e| match e.entry_type {
+                EntryType::Compacted { removed } => removed,
+                _ => 1,
+            })
+            .sum();
+
+        let mut marker = SessionEntry::new(
+            EntryType::Compacted {
+                removed: represented,
+            },
+            json!({
+                "bytes": bytes,
+                "summary": self.summarizer.as_ref().map(|s| s.summarize(removed)),
+            }),
+        );
+        // Keep entries in chronological order
+        marker.timestamp = removed[removed.len() - 1].timestamp;
+        marker
+    }
+}
+
+/// Serialized size of each entry
+fn entry_sizes(entries: &[SessionEntry]) -> impl Iterator<Item = usize> + '_ {
+    entries
+        .iter()
+        .map(|e| serde_json::to_vec(e).map(|v| v.len()).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionType;
+
+    fn session(messages: usize) -> Session {
+        let mut session = Session::new(
+            "chat",
+            SessionType::Conversation {
+                purpose: "test".to_string(),
+            },
+        );
+        for i in 0..messages {
+            session.add_user_message(format!("message {}", i));
+        }
+        session
+    }
+
+    fn text(entry: &SessionEntry) -> &str {
+        entry.content["text"].as_str().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_within_budget_is_untouched() {
+        let mut session = session(5);
+        let policy = CompactionPolicy::new().with_max_entries(5);
+        assert!(policy.compact(&mut session).is_none());
+        assert!(CompactionPolicy::new().compact(&mut session).is_none());
+        assert_eq!(session.entries.len(), 5);
+    }
+
+    #[test]
+    fn test_entry_budget_keeps_recent_and_marks() {
+        let mut session = session(30);
+        let policy = CompactionPolicy::new()
+            .with_max_entries(10)
+            .with_keep_recent(5);
+
+        let report = policy.compact(&mut session).unwrap();
+        assert_eq!(report.removed_entries, 21);
+        assert_eq!(report.remaining_entries, 10);
+        assert_eq!(session.entries.len(), 10);
+
+        assert!(matches!(
+            session.entries[0].entry_type,
+            EntryType::Compacted { removed: 21 }
+        ));
+        assert!(session.entries[0].content["summary"].is_null());
+        assert_eq!(text(&session.entries[1]), "message 21");
+        assert_eq!(text(&session.entries[9]), "message 29");
+    }
+
+    #[test]
+    fn test_keep_recent_wins_over_budget() {
+        let mut session = session(12);
+        let policy = CompactionPolicy::new()
+            .with_max_entries(4)
+            .with_keep_recent(8);
+
+        let report = policy.compact(&mut session).unwrap();
+        assert_eq!(report.removed_entries, 4);
+        assert_eq!(session.entries.len(), 9);
+        assert_eq!(text(&session.entries[1]), "message 4");
+    }
+
+    #[test]
+    fn test_byte_budget() {
+        let mut session = session(50);
+        let total: usize = entry_sizes(&session.entries).sum();
+        let policy = CompactionPolicy::new()
+            .with_max_bytes(total / 2)
+            .with_keep_recent(0);
+
+        let report = policy.compact(&mut session).unwrap();
+        assert!(report.remaining_bytes <= total / 2);
+        assert!(report.removed_bytes + report.remaining_bytes > total);
+        assert!(!policy.is_exceeded(&session));
+    }
+
+    #[test]
+    fn test_repeated_compaction_accumulates_digest() {
+        let mut session = session(20);
+        session.add_entry(SessionEntry::tool_call("search", true, json!({})));
+        session.add_entry(SessionEntry::assistant_message("done"));
+
+        let policy = CompactionPolicy::new()
+            .with_max_entries(6)
+            .with_keep_recent(2)
+            .with_summarizer(DigestSummarizer);
+        policy.compact(&mut session).unwrap();
+
+        for i in 0..10 {
+            session.add_assistant_message(format!("reply {}", i));
+        }
+        policy.compact(&mut session).unwrap();
+
+        let marker = &session.entries[0];
+        assert_eq!(session.entries.len(), 6);
+        assert!(matches!(
+            marker.entry_type,
+            EntryType::Compacted { removed: 27 }
+        ));
+        let summary = &marker.content["summary"];
+        assert_eq!(summary["by_type"]["user_message"], 20);
+        assert_eq!(summary["by_type"]["tool_call"], 1);
+        assert_eq!(summary["by_type"]["assistant_message"], 6);
+        assert_eq!(summary["tools"], json!(["search"]));
+    }
+
+    #[test]
+    fn test_custom_summarizer() {
+        let mut session = session(10);
+        let policy = CompactionPolicy::new()
+            .with_max_entries(5)
+            .with_keep_recent(1)
+            .with_summarizer(|entries: &[SessionEntry]| {
+                json!(entries.iter().map(text).collect::<Vec<_>>().join(" | "))
+            });
+
+        policy.compact(&mut session).unwrap();
+        assert_eq!(
+            session.entries[0].content["summary"],
+            "message 0 | message 1 | message 2 | message 3 | message 4 | message 5"
+        );
+    }
+}
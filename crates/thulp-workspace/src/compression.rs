@@ -0,0 +1,97 @@
+//! Transparent compression for stored sessions and artifacts.
+//!
+//! Session files and artifact blobs are written as zstd frames by default.
+//! Reads detect the zstd magic bytes and decompress only when they are
+//! present, so files written before compression was enabled, or with
+//! [`Compression::None`], stay readable.
+//!
+//! Compressed session files are named `<id>.json.zst` and plain ones
+//! `<id>.json`; compressed files named `.json` by older versions are still
+//! read.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// Magic bytes at the start of every zstd frame.
+pub(crate) const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// zstd level used when writing; favours speed over the last few percent.
+const ZSTD_LEVEL: i32 = 3;
+
+/// How session and artifact storage encodes data on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Store data as is
+    None,
+    /// Store data as zstd frames
+    #[default]
+    Zstd,
+}
+
+impl Compression {
+    /// Encode data for storage.
+    ///
+    /// Data that zstd can't shrink, such as tiny or already compressed
+    /// payloads, is stored as is. Data that happens to start with the zstd
+    /// magic bytes is always compressed, so [`decode`] can't mistake it for
+    /// a frame.
+    pub(crate) fn encode(self, data: &[u8]) -> Result<Vec<u8>> {
+        if is_compressed(data) {
+            return Ok(zstd::encode_all(data, ZSTD_LEVEL)?);
+        }
+        if self == Compression::Zstd {
+            let compressed = zstd::encode_all(data, ZSTD_LEVEL)?;
+            if compressed.len() < data.len() {
+                return Ok(compressed);
+            }
+        }
+        Ok(data.to_vec())
+    }
+}
+
+/// Decode stored data, decompressing it if it is a zstd frame.
+pub(crate) fn decode(data: Vec<u8>) -> Result<Vec<u8>> {
+    if is_compressed(&data) {
+        Ok(zstd::decode_all(data.as_slice())?)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Check for the zstd magic bytes.
+pub(crate) fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(&ZSTD_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_detection() {
+        let json = br#"{"entries": ["hello", "hello", "hello", "hello"]}"#.repeat(20);
+
+        let compressed = Compression::Zstd.encode(&json).unwrap();
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < json.len() / 5);
+        assert_eq!(decode(compressed).unwrap(), json);
+
+        // Plain data, as written by older versions, passes through
+        let plain = Compression::None.encode(&json).unwrap();
+        assert_eq!(plain, json);
+        assert_eq!(decode(plain).unwrap(), json);
+
+        // Compressing wouldn't pay off for tiny data
+        assert_eq!(Compression::Zstd.encode(b"hi").unwrap(), b"hi");
+    }
+
+    #[test]
+    fn test_magic_prefixed_plain_data_is_not_misread() {
+        let mut data = ZSTD_MAGIC.to_vec();
+        data.extend_from_slice(b"not really a frame");
+
+        let stored = Compression::None.encode(&data).unwrap();
+        assert_eq!(decode(stored).unwrap(), data);
+    }
+}
@@ -0,0 +1,462 @@
+//! Workspace configuration loading with reference expansion.
+//!
+//! String values in `.thulp/config.yaml` may reference the environment and
+//! secrets instead of embedding them, so the file can be committed:
+//!
+//! ```yaml
+//! servers:
+//!   github:
+//!     type: http
+//!     url: ${GITHUB_MCP_URL:-https://api.githubcopilot.com/mcp}
+//!     headers:
+//!       Authorization: Bearer ${secret:github_token}
+//! ```
+//!
+//! - `${NAME}` is replaced with the environment variable `NAME`
+//! - `${NAME:-default}` falls back to `default` when `NAME` is unset
+//! - `${secret:name}` is looked up in the registered [`SecretResolver`]s
+//! - `$${` produces a literal `${`
+//!
+//! Every unresolved reference in the document is collected and reported in a
+//! single [`WorkspaceError::UnresolvedReferences`] error.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use thulp_workspace::ConfigExpander;
+//!
+//! let mut secrets = HashMap::new();
+//! secrets.insert("token".to_string(), "s3cret".to_string());
+//!
+//! let expander = ConfigExpander::new()
+//!     .with_env_var("API_HOST", "api.example.com")
+//!     .with_secrets(secrets);
+//!
+//! let value = expander
+//!     .expand_str("https://${API_HOST}/v1?key=${secret:token}")
+//!     .unwrap();
+//! assert_eq!(value, "https://api.example.com/v1?key=s3cret");
+//! ```
+
+use crate::{Result, Workspace, WorkspaceError};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Source of values for `${secret:name}` references, such as a
+/// [`SecretsStore`](crate::SecretsStore).
+pub use thulp_core::SecretResolver;
+
+/// Expands `${ENV}` and `${secret:name}` references in configuration values.
+#[derive(Clone, Default)]
+pub struct ConfigExpander {
+    /// Variables that take precedence over the process environment
+    env_overrides: HashMap<String, String>,
+
+    /// Variables used when the process environment doesn't set them
+    env_defaults: HashMap<String, String>,
+
+    /// Whether to fall back to the process environment
+    use_process_env: bool,
+
+    /// Secret resolvers, consulted in registration order
+    secrets: Vec<Arc<dyn SecretResolver>>,
+}
+
+impl std::fmt::Debug for ConfigExpander {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigExpander")
+            .field(
+                "env_overrides",
+                &self.env_overrides.keys().collect::<Vec<_>>(),
+            )
+            .field(
+                "env_defaults",
+                &self.env_defaults.keys().collect::<Vec<_>>(),
+            )
+            .field("use_process_env", &self.use_process_env)
+            .field("secret_resolvers", &self.secrets.len())
+            .finish()
+    }
+}
+
+impl ConfigExpander {
+    /// Create an expander that reads the process environment.
+    pub fn new() -> Self {
+        Self {
+            use_process_env: true,
+            ..Default::default()
+        }
+    }
+
+    /// Create an expander that ignores the process environment.
+    ///
+    /// Only variables added with [`with_env_var`](Self::with_env_var) resolve.
+    pub fn isolated() -> Self {
+        Self::default()
+    }
+
+    /// Set a variable, overriding the process environment
+    pub fn with_env_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_overrides.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set a variable unless the process environment sets it, as the `env`
+    /// section of a [`WorkspaceConfig`](crate::WorkspaceConfig) does
+    pub fn with_env_default(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_defaults.insert(name.into(), value.into());
+        self
+    }
+
+    /// Register a secret resolver
+    pub fn with_secrets(mut self, resolver: impl SecretResolver + 'static) -> Self {
+        self.secrets.push(Arc::new(resolver));
+        self
+    }
+
+    /// Register a shared secret resolver
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secrets.push(resolver);
+        self
+    }
+
+    /// Expand references in a single string.
+    pub fn expand_str(&self, input: &str) -> Result<String> {
+        let mut unresolved = Vec::new();
+        let expanded = self.expand_into(input, &mut unresolved);
+        check_unresolved(unresolved)?;
+        Ok(expanded)
+    }
+
+    /// Expand references in every string within a JSON value.
+    ///
+    /// Map keys are left untouched.
+    pub fn expand_value(&self, value: Value) -> Result<Value> {
+        let mut unresolved = Vec::new();
+        let expanded = self.expand_value_into(value, &mut unresolved);
+        check_unresolved(unresolved)?;
+        Ok(expanded)
+    }
+
+    /// Parse YAML and expand all references.
+    pub fn expand_yaml(&self, yaml: &str) -> Result<Value> {
+        let value: Value =
+            serde_yaml::from_str(yaml).map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        self.expand_value(value)
+    }
+
+    /// Load a YAML config file and expand all references.
+    pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<Value> {
+        let yaml = std::fs::read_to_string(path)?;
+        self.expand_yaml(&yaml)
+    }
+
+    fn expand_value_into(&self, value: Value, unresolved: &mut Vec<String>) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.expand_into(&s, unresolved)),
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|v| self.expand_value_into(v, unresolved))
+                    .collect(),
+            ),
+            Value::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k, self.expand_value_into(v, unresolved)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn expand_into(&self, input: &str, unresolved: &mut Vec<String>) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(pos) = rest.find('$') {
+            output.push_str(&rest[..pos]);
+            let after = &rest[pos..];
+
+            if let Some(escaped) = after.strip_prefix("$${") {
+                output.push_str("${");
+                rest = escaped;
+            } else if let Some(body) = after.strip_prefix("${") {
+                let Some(end) = body.find('}') else {
+                    // Unterminated reference, keep it verbatim
+                    output.push_str(after);
+                    return output;
+                };
+                let reference = &body[..end];
+                match self.resolve(reference) {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        let placeholder = format!("${{{}}}", reference);
+                        if !unresolved.contains(&placeholder) {
+                            unresolved.push(placeholder.clone());
+                        }
+                        output.push_str(&placeholder);
+                    }
+                }
+                rest = &body[end + 1..];
+            } else {
+                output.push('$');
+                rest = &after[1..];
+            }
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    fn resolve(&self, reference: &str) -> Option<String> {
+        if let Some(name) = reference.strip_prefix("secret:") {
+            let name = name.trim();
+            return self.secrets.iter().find_map(|r| r.resolve(name));
+        }
+
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name.trim(), Some(default)),
+            None => (reference.trim(), None),
+        };
+        self.env_var(name).or_else(|| default.map(str::to_string))
+    }
+
+    fn env_var(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.env_overrides.get(name) {
+            return Some(value.clone());
+        }
+        if self.use_process_env {
+            if let Ok(value) = std::env::var(name) {
+                return Some(value);
+            }
+        }
+        self.env_defaults.get(name).cloned()
+    }
+}
+
+fn check_unresolved(unresolved: Vec<String>) -> Result<()> {
+    if unresolved.is_empty() {
+        Ok(())
+    } else {
+        Err(WorkspaceError::UnresolvedReferences(unresolved))
+    }
+}
+
+impl Workspace {
+    /// Path to the workspace configuration file (`.thulp/config.yaml`)
+    pub fn config_path(&self) -> std::path::PathBuf {
+        self.root.join(".thulp").join("config.yaml")
+    }
+
+    /// Load the workspace configuration, expanding `${ENV}` and
+    /// `${secret:name}` references with the given expander.
+    pub fn load_config(&self, expander: &ConfigExpander) -> Result<Value> {
+        let path = self.config_path();
+        if !path.exists() {
+            return Err(WorkspaceError::NotFound(path.display().to_string()));
+        }
+        expander.load_file(path)
+    }
+
+    /// Take the `name`, `metadata` and `context` sections of a loaded
+    /// configuration, keeping the current value of any that is missing.
+    ///
+    /// Metadata values must be scalars; numbers and booleans are kept as
+    /// text.
+    pub fn apply_config(&mut self, config: &Value) -> Result<()> {
+        if let Some(name) = config.get("name").and_then(Value::as_str) {
+            self.name = name.to_string();
+        }
+        if let Some(metadata) = section(config, "metadata")? {
+            for (key, value) in metadata {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => {
+                        return Err(WorkspaceError::Serialization(format!(
+                            "metadata '{}' must be a string, number or boolean",
+                            key
+                        )))
+                    }
+                };
+                self.metadata.insert(key.clone(), value);
+            }
+        }
+        if let Some(context) = section(config, "context")? {
+            self.context
+                .extend(context.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Ok(())
+    }
+}
+
+/// A mapping section of a configuration, `None` if missing or empty
+fn section<'a>(
+    config: &'a Value,
+    name: &str,
+) -> Result<Option<&'a serde_json::Map<String, Value>>> {
+    match config.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(map)) => Ok(Some(map)),
+        Some(_) => Err(WorkspaceError::Serialization(format!(
+            "'{}' must be a mapping",
+            name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn expander() -> ConfigExpander {
+        let mut secrets = HashMap::new();
+        secrets.insert("github_token".to_string(), "ghp_123".to_string());
+
+        ConfigExpander::isolated()
+            .with_env_var("HOST", "mcp.example.com")
+            .with_env_var("PORT", "8080")
+            .with_secrets(secrets)
+    }
+
+    #[test]
+    fn test_expand_str() {
+        let e = expander();
+        assert_eq!(
+            e.expand_str("http://${HOST}:${PORT}/mcp").unwrap(),
+            "http://mcp.example.com:8080/mcp"
+        );
+        assert_eq!(
+            e.expand_str("Bearer ${secret:github_token}").unwrap(),
+            "Bearer ghp_123"
+        );
+        assert_eq!(e.expand_str("${MISSING:-fallback}").unwrap(), "fallback");
+        assert_eq!(
+            e.expand_str("${HOST:-fallback}").unwrap(),
+            "mcp.example.com"
+        );
+        assert_eq!(
+            e.expand_str("cost: $5, $${HOST}").unwrap(),
+            "cost: $5, ${HOST}"
+        );
+        assert_eq!(e.expand_str("no refs").unwrap(), "no refs");
+
+        let e = e
+            .with_env_default("HOST", "ignored.example.com")
+            .with_env_default("REGION", "eu");
+        assert_eq!(
+            e.expand_str("${HOST}/${REGION}").unwrap(),
+            "mcp.example.com/eu"
+        );
+    }
+
+    #[test]
+    fn test_expand_yaml_document() {
+        let yaml = r#"
+name: demo
+servers:
+  github:
+    type: http
+    url: https://${HOST}/mcp
+    headers:
+      Authorization: Bearer ${secret:github_token}
+  local:
+    type: stdio
+    command: ${HOME_BIN:-/usr/local/bin}/server
+    args: ["--port", "${PORT}"]
+settings:
+  default_timeout: 30
+"#;
+        let config = expander().expand_yaml(yaml).unwrap();
+
+        assert_eq!(
+            config["servers"]["github"]["url"],
+            "https://mcp.example.com/mcp"
+        );
+        assert_eq!(
+            config["servers"]["github"]["headers"]["Authorization"],
+            "Bearer ghp_123"
+        );
+        assert_eq!(
+            config["servers"]["local"]["command"],
+            "/usr/local/bin/server"
+        );
+        assert_eq!(
+            config["servers"]["local"]["args"],
+            json!(["--port", "8080"])
+        );
+        assert_eq!(config["settings"]["default_timeout"], 30);
+    }
+
+    #[test]
+    fn test_unresolved_references_are_all_reported() {
+        let value = json!({
+            "url": "https://${UNSET_HOST}/api",
+            "headers": {"X-Key": "${secret:missing}", "X-Other": "${UNSET_HOST}"}
+        });
+
+        let err = expander().expand_value(value).unwrap_err();
+        match err {
+            WorkspaceError::UnresolvedReferences(refs) => {
+                assert_eq!(refs.len(), 2);
+                assert!(refs.contains(&"${UNSET_HOST}".to_string()));
+                assert!(refs.contains(&"${secret:missing}".to_string()));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_workspace_load_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let workspace = Workspace::new("ws", "Workspace", temp.path().to_path_buf());
+
+        assert!(matches!(
+            workspace.load_config(&expander()),
+            Err(WorkspaceError::NotFound(_))
+        ));
+
+        std::fs::create_dir_all(temp.path().join(".thulp")).unwrap();
+        std::fs::write(workspace.config_path(), "url: http://${HOST}\n").unwrap();
+
+        let config = workspace.load_config(&expander()).unwrap();
+        assert_eq!(config["url"], "http://mcp.example.com");
+    }
+
+    #[test]
+    fn test_workspace_apply_config() {
+        let mut workspace =
+            Workspace::new("ws", "Workspace", "/tmp".into()).with_metadata("team", "core");
+        let config = expander()
+            .expand_yaml(
+                "name: Demo\nmetadata:\n  env: prod\n  tier: 2\ncontext:\n  project_id: p-${PORT}\n",
+            )
+            .unwrap();
+        workspace.apply_config(&config).unwrap();
+
+        assert_eq!(workspace.name, "Demo");
+        assert_eq!(
+            workspace.get_metadata("team").map(String::as_str),
+            Some("core")
+        );
+        assert_eq!(
+            workspace.get_metadata("tier").map(String::as_str),
+            Some("2")
+        );
+        assert_eq!(workspace.get_context("project_id"), Some(&json!("p-8080")));
+
+        let variables = workspace.template_variables();
+        assert_eq!(variables["context"]["project_id"], "p-8080");
+        assert_eq!(variables["metadata"]["env"], "prod");
+        assert_eq!(variables["root"], "/tmp");
+
+        assert!(workspace.apply_config(&json!({"context": ["a"]})).is_err());
+        assert!(workspace
+            .apply_config(&json!({"metadata": {"nested": {"a": 1}}}))
+            .is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! Session export and import.
+//!
+//! Sessions are stored as pretty JSON inside a workspace. To move them
+//! between workspaces or share them, [`export_session`] renders a session in
+//! one of several [`SessionFormat`]s and [`import_session`] reads it back:
+//!
+//! | Format | Extension | Contents |
+//! |--------|-----------|----------|
+//! | [`Json`](SessionFormat::Json) | `.json` | The session file as stored |
+//! | [`Jsonl`](SessionFormat::Jsonl) | `.jsonl` | A header line with metadata and context, then one entry per line |
+//! | [`Markdown`](SessionFormat::Markdown) | `.md` | A readable transcript with the raw data in HTML comments |
+//! | [`Archive`](SessionFormat::Archive) | `.zst` | zstd-compressed, versioned JSON |
+//!
+//! Every format round-trips: the Markdown transcript embeds each entry as a
+//! single-line JSON comment next to its rendered text, so a shared log can
+//! still be imported.
+//!
+//! [`export_session`]: crate::SessionManager::export_session
+//! [`import_session`]: crate::SessionManager::import_session
+
+use crate::compression::ZSTD_MAGIC;
+use crate::filter::session_type_name;
+use crate::session::{EntryType, Session, SessionEntry, SessionMetadata};
+use crate::{Result, WorkspaceError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Format identifier stored inside archives.
+const ARCHIVE_FORMAT: &str = "thulp-session";
+
+/// Current archive version.
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Marker of the comment holding session metadata in a Markdown transcript.
+const MD_SESSION_MARKER: &str = "<!-- thulp-session ";
+
+/// Marker of the comment holding an entry in a Markdown transcript.
+const MD_ENTRY_MARKER: &str = "<!-- thulp-entry ";
+
+/// Formats sessions can be exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionFormat {
+    /// Pretty-printed JSON, identical to an uncompressed session file
+    Json,
+    /// JSON Lines: metadata header followed by one entry per line
+    Jsonl,
+    /// Markdown transcript with embedded data
+    Markdown,
+    /// zstd-compressed archive
+    Archive,
+}
+
+impl SessionFormat {
+    /// File extension for the format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SessionFormat::Json => "json",
+            SessionFormat::Jsonl => "jsonl",
+            SessionFormat::Markdown => "md",
+            SessionFormat::Archive => "zst",
+        }
+    }
+
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(SessionFormat::Json),
+            "jsonl" | "ndjson" => Some(SessionFormat::Jsonl),
+            "md" | "markdown" => Some(SessionFormat::Markdown),
+            "zst" => Some(SessionFormat::Archive),
+            _ => None,
+        }
+    }
+
+    /// Detect the format of exported data from its contents.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&ZSTD_MAGIC) {
+            return SessionFormat::Archive;
+        }
+        let text = String::from_utf8_lossy(data);
+        let text = text.trim_start();
+        if !text.starts_with('{') {
+            return SessionFormat::Markdown;
+        }
+        // A JSONL header is a complete object on the first line, without entries
+        let first = text.lines().next().unwrap_or_default();
+        match serde_json::from_str::<Value>(first) {
+            Ok(header) if header.get("entries").is_none() => SessionFormat::Jsonl,
+            _ => SessionFormat::Json,
+        }
+    }
+}
+
+impl std::str::FromStr for SessionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SessionFormat::Json),
+            "jsonl" | "ndjson" => Ok(SessionFormat::Jsonl),
+            "md" | "markdown" => Ok(SessionFormat::Markdown),
+            "archive" | "zst" | "zstd" => Ok(SessionFormat::Archive),
+            other => Err(format!(
+                "Unknown session format '{}' (expected json, jsonl, markdown or archive)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SessionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SessionFormat::Json => "json",
+            SessionFormat::Jsonl => "jsonl",
+            SessionFormat::Markdown => "markdown",
+            SessionFormat::Archive => "archive",
+        })
+    }
+}
+
+/// Session data other than entries, as written in JSONL and Markdown headers.
+#[derive(Serialize, Deserialize)]
+struct SessionHeader {
+    metadata: SessionMetadata,
+    #[serde(default)]
+    context: HashMap<String, Value>,
+}
+
+/// Versioned archive envelope.
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: String,
+    version: u32,
+    session: Session,
+}
+
+/// Render a session in the given format.
+pub fn export(session: &Session, format: SessionFormat) -> Result<Vec<u8>> {
+    match format {
+        SessionFormat::Json => serde_json::to_vec_pretty(session).map_err(serialization),
+        SessionFormat::Jsonl => {
+            let header = SessionHeader {
+                metadata: session.metadata.clone(),
+                context: session.context.clone(),
+            };
+            let mut out = serde_json::to_vec(&header).map_err(serialization)?;
+            for entry in &session.entries {
+                out.push(b'\n');
+                out.extend(serde_json::to_vec(entry).map_err(serialization)?);
+            }
+            out.push(b'\n');
+            Ok(out)
+        }
+        SessionFormat::Markdown => to_markdown(session).map(String::into_bytes),
+        SessionFormat::Archive => {
+            let archive = Archive {
+                format: ARCHIVE_FORMAT.to_string(),
+                version: ARCHIVE_VERSION,
+                session: session.clone(),
+            };
+            let json = serde_json::to_vec(&archive).map_err(serialization)?;
+            Ok(zstd::encode_all(json.as_slice(), 0)?)
+        }
+    }
+}
+
+/// Parse a session exported in the given format.
+pub fn import(data: &[u8], format: SessionFormat) -> Result<Session> {
+    match format {
+        SessionFormat::Json => serde_json::from_slice(data).map_err(serialization),
+        SessionFormat::Jsonl => {
+            let text = std::str::from_utf8(data).map_err(serialization)?;
+            let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+            let header: SessionHeader = serde_json::from_str(
+                lines
+                    .next()
+                    .ok_or_else(|| WorkspaceError::Serialization("Empty JSONL session".into()))?,
+            )
+            .map_err(serialization)?;
+            let entries = lines
+                .map(|line| serde_json::from_str(line).map_err(serialization))
+                .collect::<Result<Vec<SessionEntry>>>()?;
+            Ok(Session {
+                metadata: header.metadata,
+                entries,
+                context: header.context,
+            })
+        }
+        SessionFormat::Markdown => {
+            let text = std::str::from_utf8(data).map_err(serialization)?;
+            from_markdown(text)
+        }
+        SessionFormat::Archive => {
+            let json = zstd::decode_all(data)?;
+            let archive: Archive = serde_json::from_slice(&json).map_err(serialization)?;
+            if archive.format != ARCHIVE_FORMAT || archive.version > ARCHIVE_VERSION {
+                return Err(WorkspaceError::Serialization(format!(
+                    "Unsupported session archive {} v{}",
+                    archive.format, archive.version
+                )));
+            }
+            Ok(archive.session)
+        }
+    }
+}
+
+fn serialization(e: impl std::fmt::Display) -> WorkspaceError {
+    WorkspaceError::Serialization(e.to_string())
+}
+
+/// Serialize a value to single-line JSON that is safe inside an HTML comment.
+///
+/// `>` only occurs inside JSON strings, where `>` is equivalent, so this
+/// rules out a premature `-->`.
+fn comment_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)
+        .map_err(serialization)?
+        .replace('>', "\\u003e"))
+}
+
+fn to_markdown(session: &Session) -> Result<String> {
+    let metadata = &session.metadata;
+    let status = serde_json::to_value(metadata.status).unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", metadata.name);
+    let _ = writeln!(out, "- **Session:** `{}`", metadata.id);
+    let _ = writeln!(
+        out,
+        "- **Type:** {}",
+        session_type_name(&metadata.session_type)
+    );
+    let _ = writeln!(out, "- **Status:** {}", status.as_str().unwrap_or_default());
+    if !metadata.tags.is_empty() {
+        let _ = writeln!(out, "- **Tags:** {}", metadata.tags.join(", "));
+    }
+    if !metadata.annotations.is_empty() {
+        let _ = writeln!(out, "- **Annotations:**");
+        for (key, value) in &metadata.annotations {
+            match value {
+                Value::String(text) => {
+                    let _ = writeln!(out, "  - {}: {}", key, text);
+                }
+                value => {
+                    let _ = writeln!(out, "  - {}: `{}`", key, value);
+                }
+            }
+        }
+    }
+    let _ = writeln!(out, "- **Entries:** {}", session.entries.len());
+    let header = SessionHeader {
+        metadata: metadata.clone(),
+        context: session.context.clone(),
+    };
+    let _ = writeln!(out, "\n{}{} -->", MD_SESSION_MARKER, comment_json(&header)?);
+
+    for entry in &session.entries {
+        let _ = writeln!(out, "\n## {}\n", entry_heading(&entry.entry_type));
+        let _ = writeln!(out, "{}{} -->\n", MD_ENTRY_MARKER, comment_json(entry)?);
+        match entry.content.get("text").and_then(Value::as_str) {
+            Some(text) => {
+                let _ = writeln!(out, "{}", text);
+            }
+            None if entry.content.is_null() => {}
+            None => {
+                let pretty = serde_json::to_string_pretty(&entry.content).map_err(serialization)?;
+                let _ = writeln!(out, "```json\n{}\n```", pretty);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn entry_heading(entry_type: &EntryType) -> String {
+    let outcome = |success: bool| if success { "" } else { " (failed)" };
+    match entry_type {
+        EntryType::UserMessage => "User".to_string(),
+        EntryType::AssistantMessage => "Assistant".to_string(),
+        EntryType::SystemMessage => "System".to_string(),
+        EntryType::ToolCall { tool_name, success } => {
+            format!("Tool call: `{}`{}", tool_name, outcome(*success))
+        }
+        EntryType::SkillExecution {
+            skill_name,
+            success,
+        } => format!("Skill: `{}`{}", skill_name, outcome(*success)),
+        EntryType::EvaluationResult { score, .. } => format!("Evaluation: {:.2}", score),
+        EntryType::SystemEvent { event } => format!("Event: {}", event),
+        EntryType::ServerLog { server, level } => format!("Log from {} ({})", server, level),
+        EntryType::Progress {
+            server,
+            progress,
+            total,
+        } => match total {
+            Some(total) => format!("Progress from {}: {}/{}", server, progress, total),
+            None => format!("Progress from {}: {}", server, progress),
+        },
+        EntryType::ResourceUpdated { server, uri } => {
+            format!("Resource updated on {}: {}", server, uri)
+        }
+        EntryType::ServerNotification { server, method } => {
+            format!("Notification from {}: {}", server, method)
+        }
+        EntryType::Compacted { removed } => format!("Compacted: {} earlier entries", removed),
+    }
+}
+
+fn from_markdown(text: &str) -> Result<Session> {
+    let mut header: Option<SessionHeader> = None;
+    let mut entries = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        let Some(line) = line.strip_suffix("-->") else {
+            continue;
+        };
+        if let Some(json) = line.strip_prefix(MD_SESSION_MARKER) {
+            header = Some(serde_json::from_str(json).map_err(serialization)?);
+        } else if let Some(json) = line.strip_prefix(MD_ENTRY_MARKER) {
+            entries.push(serde_json::from_str(json).map_err(serialization)?);
+        }
+    }
+
+    let header = header.ok_or_else(|| {
+        WorkspaceError::Serialization(
+            "Markdown file is not a thulp session transcript (missing session data)".into(),
+        )
+    })?;
+    Ok(Session {
+        metadata: header.metadata,
+        entries,
+        context: header.context,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionType;
+    use serde_json::json;
+
+    fn session() -> Session {
+        let mut session = Session::new(
+            "Support chat",
+            SessionType::Conversation {
+                purpose: "Help".to_string(),
+            },
+        );
+        session.metadata.tags.push("support".to_string());
+        session.annotate("ticket", json!("OPS-12"));
+        session.annotate("review", json!({"score": 4}));
+        session.set_context("user", json!("ada"));
+        session.add_user_message("How do I close a tag? Like <b> -->");
+        session.add_entry(SessionEntry::tool_call(
+            "search",
+            false,
+            json!({"error": "rate limited", "retry_after": 3}),
+        ));
+        session.add_assistant_message("Line one\nline two");
+        session
+    }
+
+    fn assert_same(a: &Session, b: &Session) {
+        assert_eq!(
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_every_format_round_trips() {
+        let session = session();
+        for format in [
+            SessionFormat::Json,
+            SessionFormat::Jsonl,
+            SessionFormat::Markdown,
+            SessionFormat::Archive,
+        ] {
+            let data = export(&session, format).unwrap();
+            assert_eq!(SessionFormat::detect(&data), format, "{}", format);
+            assert_same(&import(&data, format).unwrap(), &session);
+        }
+    }
+
+    #[test]
+    fn test_jsonl_layout() {
+        let data = export(&session(), SessionFormat::Jsonl).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        let header: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["metadata"]["name"], "Support chat");
+        assert_eq!(header["context"]["user"], "ada");
+        assert_eq!(header["metadata"]["annotations"]["ticket"], "OPS-12");
+    }
+
+    #[test]
+    fn test_markdown_transcript() {
+        let data = export(&session(), SessionFormat::Markdown).unwrap();
+        let text = String::from_utf8(data).unwrap();
+
+        assert!(text.starts_with("# Support chat\n"));
+        assert!(text.contains("- **Type:** conversation"));
+        assert!(text.contains("- **Tags:** support"));
+        assert!(text.contains(
+            "- **Annotations:**\n  - review: `{\"score\":4}`\n  - ticket: OPS-12\n"
+        ));
+        assert!(text.contains("## User\n"));
+        assert!(text.contains("How do I close a tag? Like <b> -->"));
+        assert!(text.contains("## Tool call: `search` (failed)"));
+        assert!(text.contains("\"retry_after\": 3"));
+        assert!(text.contains("## Assistant\n"));
+
+        assert!(import(b"# Notes\n\nJust text", SessionFormat::Markdown).is_err());
+    }
+
+    #[test]
+    fn test_archive_is_compressed_and_versioned() {
+        let mut session = session();
+        for i in 0..50 {
+            session.add_user_message(format!("message number {} with some repeated text", i));
+        }
+        let json = export(&session, SessionFormat::Json).unwrap();
+        let archive = export(&session, SessionFormat::Archive).unwrap();
+        assert!(archive.starts_with(&ZSTD_MAGIC));
+        assert!(archive.len() * 4 < json.len());
+
+        let future = json!({"format": ARCHIVE_FORMAT, "version": 99, "session": session});
+        let data = zstd::encode_all(future.to_string().as_bytes(), 0).unwrap();
+        assert!(import(&data, SessionFormat::Archive).is_err());
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("md".parse::<SessionFormat>(), Ok(SessionFormat::Markdown));
+        assert_eq!("zstd".parse::<SessionFormat>(), Ok(SessionFormat::Archive));
+        assert!("xml".parse::<SessionFormat>().is_err());
+        assert_eq!(
+            SessionFormat::from_path(Path::new("chat.JSONL")),
+            Some(SessionFormat::Jsonl)
+        );
+        assert_eq!(SessionFormat::from_path(Path::new("chat.txt")), None);
+    }
+}
@@ -31,6 +31,12 @@ pub enum SessionFilter {
     /// Match sessions that have a specific tag.
     HasTag(String),
 
+    /// Match sessions that have an annotation with the given key.
+    HasAnnotation(String),
+
+    /// Match sessions whose annotation with the given key equals the value.
+    AnnotationEquals(String, serde_json::Value),
+
     /// Match sessions created after the given timestamp.
     CreatedAfter(Timestamp),
 
@@ -81,6 +87,12 @@ impl SessionFilter {
 
             SessionFilter::HasTag(tag) => session.metadata.tags.iter().any(|t| t == tag),
 
+            SessionFilter::HasAnnotation(key) => session.metadata.annotations.contains_key(key),
+
+            SessionFilter::AnnotationEquals(key, value) => {
+                session.get_annotation(key) == Some(value)
+            }
+
             SessionFilter::CreatedAfter(timestamp) => {
                 session.metadata.created_at.as_millis() > timestamp.as_millis()
             }
@@ -190,7 +202,7 @@ impl SessionFilter {
 }
 
 /// Get the type name for a session type.
-fn session_type_name(session_type: &SessionType) -> &'static str {
+pub(crate) fn session_type_name(session_type: &SessionType) -> &'static str {
     match session_type {
         SessionType::TeacherDemo { .. } => "teacher_demo",
         SessionType::Evaluation { .. } => "evaluation",
@@ -266,6 +278,25 @@ mod tests {
         assert!(!no_tag_filter.matches(&session));
     }
 
+    #[test]
+    fn test_filter_annotations() {
+        let mut session = create_test_session("Test");
+        session.annotate("ticket", serde_json::json!("OPS-12"));
+
+        assert!(SessionFilter::HasAnnotation("ticket".to_string()).matches(&session));
+        assert!(!SessionFilter::HasAnnotation("verdict".to_string()).matches(&session));
+        assert!(SessionFilter::AnnotationEquals(
+            "ticket".to_string(),
+            serde_json::json!("OPS-12")
+        )
+        .matches(&session));
+        assert!(!SessionFilter::AnnotationEquals(
+            "ticket".to_string(),
+            serde_json::json!("OPS-13")
+        )
+        .matches(&session));
+    }
+
     #[test]
     fn test_filter_name_contains() {
         let session = create_test_session("My Test Session");
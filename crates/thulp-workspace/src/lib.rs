@@ -11,7 +11,20 @@
 //! - **Session Management**: Track conversation history, tool calls, and skill executions
 //! - **Turn Counting**: Monitor conversation turns with configurable limits
 //! - **Persistence**: File-based storage for sessions with in-memory caching
-//! - **Filtering**: Query sessions by status, type, tags, and timestamps
+//! - **Compression**: zstd-compressed session files and artifact blobs, with plain files still readable
+//! - **Annotations**: Tag sessions and attach key/value notes after the fact
+//! - **Filtering**: Query sessions by status, type, tags, annotations, and timestamps, and update, tag or delete all matches at once
+//! - **Notifications**: Record server logs, progress and resource updates in sessions
+//! - **Artifacts**: Content-addressed blob storage with deduplication and garbage collection
+//! - **Configuration**: `${ENV}` and `${secret:name}` expansion when loading `config.yaml`, whose
+//!   `metadata` and `context` sections skills read as `{{workspace.context.project_id}}`
+//! - **Typed Configuration**: [`WorkspaceConfig`] models `config.yaml` (servers, settings, `env`,
+//!   skill paths) with validation, unknown-key warnings and load/save helpers
+//! - **Secrets**: Credentials in `.thulp/secrets` or the OS keychain (`keychain` feature), see [`secrets`]
+//! - **Compaction**: Trim old session entries once an entry-count or byte budget is exceeded
+//! - **Export/Import**: Move sessions between workspaces as JSON, JSONL, Markdown or zstd archives
+//! - **Anonymization**: Replace e-mails, API keys and paths with stable pseudonyms before sharing
+//! - **Read-only mode**: Turn session and artifact writes into logged no-ops for audits and demos
 //!
 //! ## Example
 //!
@@ -46,16 +59,36 @@
 //! }
 //! ```
 
+pub mod anonymize;
+pub mod artifacts;
+pub mod compaction;
+pub mod compression;
+pub mod config;
+pub mod export;
 pub mod filter;
+pub mod notifications;
+pub mod secrets;
 pub mod session;
 pub mod session_manager;
-
+pub mod workspace_config;
+
+pub use anonymize::{Anonymizer, Pseudonym, PseudonymMap, SensitiveKind};
+pub use artifacts::{ArtifactRef, ArtifactStats, ArtifactStore, ContentHash, GcStats};
+pub use compaction::{CompactionPolicy, CompactionReport, DigestSummarizer, Summarizer};
+pub use compression::Compression;
+pub use config::{ConfigExpander, SecretResolver};
+pub use export::SessionFormat;
 pub use filter::SessionFilter;
+pub use notifications::SessionNotificationSink;
+#[cfg(feature = "keychain")]
+pub use secrets::KeychainSecretsStore;
+pub use secrets::{validate_secret_name, FileSecretsStore, SecretsStore};
 pub use session::{
     EntryType, LimitAction, LimitCheck, LimitExceeded, Session, SessionConfig, SessionEntry,
     SessionId, SessionMetadata, SessionStatus, SessionType, Timestamp,
 };
 pub use session_manager::SessionManager;
+pub use workspace_config::{ServerConfig, WorkspaceConfig};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -79,6 +112,21 @@ pub enum WorkspaceError {
 
     #[error("Workspace not found: {0}")]
     NotFound(String),
+
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Unresolved config references: {}", .0.join(", "))]
+    UnresolvedReferences(Vec<String>),
+
+    #[error("Secret store error: {0}")]
+    Secret(String),
+
+    #[error("Invalid config: {}", .0.join("; "))]
+    InvalidConfig(Vec<String>),
 }
 
 /// A workspace for an agent session
@@ -100,6 +148,14 @@ pub struct Workspace {
     /// Context data
     #[serde(default)]
     pub context: HashMap<String, Value>,
+
+    /// Skip all writes to workspace and session storage
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Encoding of session files and artifact blobs on disk
+    #[serde(default)]
+    pub compression: Compression,
 }
 
 impl Workspace {
@@ -111,9 +167,26 @@ impl Workspace {
             root,
             metadata: HashMap::new(),
             context: HashMap::new(),
+            read_only: false,
+            compression: Compression::default(),
         }
     }
 
+    /// Enable or disable read-only mode.
+    ///
+    /// Session managers and artifact stores created for a read-only
+    /// workspace skip their writes with a warning.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set how sessions and artifacts are encoded on disk.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// Set metadata
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
@@ -135,11 +208,30 @@ impl Workspace {
     pub fn get_metadata(&self, key: &str) -> Option<&String> {
         self.metadata.get(key)
     }
+
+    /// The workspace as skill template variables: its `id`, `name`, `root`,
+    /// `metadata` and `context`, read in step arguments as
+    /// `{{workspace.context.project_id}}`.
+    pub fn template_variables(&self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "root": self.root,
+            "metadata": self.metadata,
+            "context": self.context,
+        })
+    }
 }
 
 impl Workspace {
     /// Save the workspace to a JSON file
+    ///
+    /// Does nothing for a read-only workspace.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if self.read_only {
+            tracing::warn!(path = ?path.as_ref(), "Read-only mode: not saving workspace");
+            return Ok(());
+        }
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
         fs::write(path, json).map_err(WorkspaceError::Io)?;
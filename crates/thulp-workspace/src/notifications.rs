@@ -0,0 +1,199 @@
+//! Recording server notifications into sessions.
+//!
+//! [`SessionNotificationSink`] implements [`NotificationSink`] so it can be
+//! registered with an MCP client. Every notification received while a session
+//! is active is appended to that session as a typed entry (see
+//! [`SessionEntry::notification`]), capturing logs, progress and resource
+//! updates alongside the tool calls that produced them.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use thulp_workspace::{SessionManager, SessionNotificationSink};
+//!
+//! let manager = Arc::new(SessionManager::new(&workspace).await?);
+//! let session = manager.create_session("Run", session_type).await?;
+//!
+//! let sink = Arc::new(SessionNotificationSink::new(manager.clone()));
+//! sink.set_session(Some(session.id().clone())).await;
+//! client.add_notification_sink(sink);
+//! ```
+
+use crate::session::{SessionEntry, SessionId};
+use crate::SessionManager;
+use async_trait::async_trait;
+use std::sync::Arc;
+use thulp_core::{Error, McpNotification, NotificationSink};
+use tokio::sync::RwLock;
+
+/// Notification sink that appends notifications to the active session.
+pub struct SessionNotificationSink {
+    /// Session manager used to persist entries.
+    manager: Arc<SessionManager>,
+    /// Session that receives notifications, if any.
+    active_session: RwLock<Option<SessionId>>,
+}
+
+impl SessionNotificationSink {
+    /// Create a sink with no active session.
+    ///
+    /// Notifications are dropped until a session is set.
+    pub fn new(manager: Arc<SessionManager>) -> Self {
+        Self {
+            manager,
+            active_session: RwLock::new(None),
+        }
+    }
+
+    /// Create a sink that records into the given session.
+    pub fn for_session(manager: Arc<SessionManager>, session_id: SessionId) -> Self {
+        Self {
+            manager,
+            active_session: RwLock::new(Some(session_id)),
+        }
+    }
+
+    /// Set (or clear) the session that receives notifications.
+    pub async fn set_session(&self, session_id: Option<SessionId>) {
+        *self.active_session.write().await = session_id;
+    }
+
+    /// Stop recording into `session_id`, if it is still the session
+    /// receiving notifications.
+    pub async fn end_session(&self, session_id: &SessionId) {
+        let mut active = self.active_session.write().await;
+        if active.as_ref() == Some(session_id) {
+            *active = None;
+        }
+    }
+
+    /// Get the session currently receiving notifications.
+    pub async fn active_session(&self) -> Option<SessionId> {
+        self.active_session.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl NotificationSink for SessionNotificationSink {
+    async fn notify(&self, server: &str, notification: &McpNotification) -> thulp_core::Result<()> {
+        let Some(session_id) = self.active_session().await else {
+            return Ok(());
+        };
+
+        let entry = SessionEntry::notification(server, notification);
+        self.manager
+            .add_entry(&session_id, entry.entry_type, entry.content)
+            .await
+            .map_err(|e| Error::ExecutionFailed(format!("Failed to record notification: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{EntryType, SessionType};
+    use tempfile::TempDir;
+
+    async fn create_test_manager() -> (Arc<SessionManager>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::with_sessions_dir(temp_dir.path().join("sessions"))
+            .await
+            .unwrap();
+        (Arc::new(manager), temp_dir)
+    }
+
+    fn session_type() -> SessionType {
+        SessionType::Conversation {
+            purpose: "Testing".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_recorded_in_active_session() {
+        let (manager, _temp) = create_test_manager().await;
+        let session = manager.create_session("Run", session_type()).await.unwrap();
+
+        let sink = SessionNotificationSink::for_session(manager.clone(), session.id().clone());
+        sink.notify(
+            "github",
+            &McpNotification::from_jsonrpc(
+                "notifications/message",
+                serde_json::json!({"level": "info", "data": "started"}),
+            ),
+        )
+        .await
+        .unwrap();
+        sink.notify(
+            "github",
+            &McpNotification::ResourceUpdated {
+                uri: "repo://issues".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let loaded = manager.load_session(session.id()).await.unwrap();
+        assert_eq!(loaded.entries.len(), 2);
+        assert!(matches!(
+            loaded.entries[0].entry_type,
+            EntryType::ServerLog { .. }
+        ));
+        assert!(matches!(
+            loaded.entries[1].entry_type,
+            EntryType::ResourceUpdated { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_notifications_dropped_without_session() {
+        let (manager, _temp) = create_test_manager().await;
+        let session = manager.create_session("Run", session_type()).await.unwrap();
+
+        let sink = SessionNotificationSink::new(manager.clone());
+        sink.notify("srv", &McpNotification::ToolListChanged)
+            .await
+            .unwrap();
+
+        sink.set_session(Some(session.id().clone())).await;
+        sink.notify("srv", &McpNotification::ToolListChanged)
+            .await
+            .unwrap();
+
+        let loaded = manager.load_session(session.id()).await.unwrap();
+        assert_eq!(loaded.entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_end_session_keeps_newer_session() {
+        let (manager, _temp) = create_test_manager().await;
+        let first = manager
+            .create_session("First", session_type())
+            .await
+            .unwrap();
+        let second = manager
+            .create_session("Second", session_type())
+            .await
+            .unwrap();
+
+        let sink = SessionNotificationSink::for_session(manager.clone(), first.id().clone());
+        sink.set_session(Some(second.id().clone())).await;
+        sink.end_session(first.id()).await;
+        assert_eq!(sink.active_session().await.as_ref(), Some(second.id()));
+
+        sink.end_session(second.id()).await;
+        assert!(sink.active_session().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_missing_session_is_error() {
+        let (manager, _temp) = create_test_manager().await;
+        let sink = SessionNotificationSink::for_session(manager, SessionId::new());
+
+        let result = sink
+            .notify("srv", &McpNotification::PromptListChanged)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Credentials kept out of configuration and skill files.
+//!
+//! A [`SecretsStore`] holds named secrets such as API tokens. Configuration
+//! refers to them as `${secret:name}` (see [`config`](crate::config)) and
+//! skill arguments as `{{secret:name}}`, so the files that use them can be
+//! committed.
+//!
+//! - [`FileSecretsStore`] keeps one file per secret under
+//!   `.thulp/secrets/`, readable only by the owner on Unix, with a
+//!   `.gitignore` that keeps the directory out of version control
+//! - `KeychainSecretsStore` (`keychain` feature) keeps them in the OS
+//!   keychain, under a service named after the workspace
+//!
+//! Stores are also [`SecretResolver`]s and [`SecretWriter`]s, and
+//! [`FileSecretsStore::redactor`] masks every stored value, for session
+//! recording and logs.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use thulp_workspace::{SecretsStore, Workspace};
+//! use thulp_core::SecretResolver;
+//!
+//! let dir = tempfile::TempDir::new().unwrap();
+//! let workspace = Workspace::new("demo", "Demo", dir.path().to_path_buf());
+//!
+//! let secrets = workspace.secrets();
+//! secrets.set("github_token", "ghp_123").unwrap();
+//! assert_eq!(secrets.resolve("github_token").as_deref(), Some("ghp_123"));
+//! assert_eq!(secrets.names().unwrap(), vec!["github_token"]);
+//! ```
+
+use crate::{Result, Workspace, WorkspaceError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thulp_core::{SecretRedactor, SecretResolver, SecretWriter};
+
+/// Storage for named secrets.
+pub trait SecretsStore: Send + Sync {
+    /// Get a secret, `None` if it isn't set.
+    fn get(&self, name: &str) -> Result<Option<String>>;
+
+    /// Set a secret, replacing any previous value.
+    fn set(&self, name: &str, value: &str) -> Result<()>;
+
+    /// Remove a secret, returning whether it was set.
+    fn remove(&self, name: &str) -> Result<bool>;
+
+    /// Names of the stored secrets, sorted.
+    fn names(&self) -> Result<Vec<String>>;
+}
+
+/// Check that a secret name is safe to use as a file or keychain entry name:
+/// ASCII letters, digits, `_`, `-` and `.`, not starting with `.`.
+pub fn validate_secret_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(WorkspaceError::Secret(format!(
+            "invalid secret name '{}': use letters, digits, '_', '-' and '.'",
+            name
+        )))
+    }
+}
+
+/// Secrets stored as files in a directory, one per secret.
+#[derive(Debug, Clone)]
+pub struct FileSecretsStore {
+    dir: PathBuf,
+}
+
+impl FileSecretsStore {
+    /// Create a store in `dir`, which is created on the first
+    /// [`set`](SecretsStore::set).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the secrets are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Create a redactor masking the value of every stored secret.
+    pub fn redactor(&self) -> Result<SecretRedactor> {
+        let redactor = SecretRedactor::new();
+        for name in self.names()? {
+            if let Some(value) = self.get(&name)? {
+                redactor.add(value);
+            }
+        }
+        Ok(redactor)
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        validate_secret_name(name)?;
+        Ok(self.dir.join(name))
+    }
+
+    /// Create the directory, private and ignored by git
+    fn create_dir(&self) -> Result<()> {
+        if self.dir.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        set_private(&self.dir, 0o700)?;
+        fs::write(self.dir.join(".gitignore"), "*\n")?;
+        Ok(())
+    }
+}
+
+impl SecretsStore for FileSecretsStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match fs::read_to_string(self.path(name)?) {
+            // Tolerate the newline an editor adds
+            Ok(value) => Ok(Some(value.trim_end_matches(['\r', '\n']).to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        let path = self.path(name)?;
+        self.create_dir()?;
+        // Restrict the file before the value is written to it
+        fs::write(&path, "")?;
+        set_private(&path, 0o600)?;
+        fs::write(&path, value)?;
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        match fs::remove_file(self.path(name)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn names(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_secret_name(name).is_ok() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+impl SecretResolver for FileSecretsStore {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).ok().flatten()
+    }
+}
+
+impl SecretWriter for FileSecretsStore {
+    fn write(&self, name: &str, value: &str) -> thulp_core::Result<()> {
+        self.set(name, value).map_err(write_error)
+    }
+}
+
+fn write_error(error: WorkspaceError) -> thulp_core::Error {
+    thulp_core::Error::ExecutionFailed(error.to_string())
+}
+
+#[cfg(unix)]
+fn set_private(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_private(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+/// Secrets stored in the OS keychain (macOS Keychain, Windows Credential
+/// Manager, Linux kernel keyring).
+///
+/// Keychains can't list entries by service, so [`names`](SecretsStore::names)
+/// fails.
+#[cfg(feature = "keychain")]
+#[derive(Debug, Clone)]
+pub struct KeychainSecretsStore {
+    service: String,
+}
+
+#[cfg(feature = "keychain")]
+impl KeychainSecretsStore {
+    /// Create a store for entries of `service`.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Create a store for a workspace, with service `thulp:<workspace id>`.
+    pub fn for_workspace(workspace: &Workspace) -> Self {
+        Self::new(format!("thulp:{}", workspace.id))
+    }
+
+    /// Keychain service the secrets are stored under.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    fn entry(&self, name: &str) -> Result<keyring::Entry> {
+        validate_secret_name(name)?;
+        keyring::Entry::new(&self.service, name).map_err(keychain_error)
+    }
+}
+
+#[cfg(feature = "keychain")]
+fn keychain_error(error: keyring::Error) -> WorkspaceError {
+    WorkspaceError::Secret(format!("keychain: {}", error))
+}
+
+#[cfg(feature = "keychain")]
+impl SecretsStore for KeychainSecretsStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match self.entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(keychain_error(e)),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        self.entry(name)?
+            .set_password(value)
+            .map_err(keychain_error)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(keychain_error(e)),
+        }
+    }
+
+    fn names(&self) -> Result<Vec<String>> {
+        Err(WorkspaceError::Secret(
+            "the keychain can't list secrets".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl SecretResolver for KeychainSecretsStore {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).ok().flatten()
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl SecretWriter for KeychainSecretsStore {
+    fn write(&self, name: &str, value: &str) -> thulp_core::Result<()> {
+        self.set(name, value).map_err(write_error)
+    }
+}
+
+impl Workspace {
+    /// Directory of the workspace's secrets (`.thulp/secrets`)
+    pub fn secrets_dir(&self) -> PathBuf {
+        self.root.join(".thulp").join("secrets")
+    }
+
+    /// The workspace's file secrets store
+    pub fn secrets(&self) -> FileSecretsStore {
+        FileSecretsStore::new(self.secrets_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use thulp_core::Redactor;
+
+    #[test]
+    fn test_file_secrets_store() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let store = FileSecretsStore::new(temp.path().join("secrets"));
+        assert!(store.names().unwrap().is_empty());
+        assert_eq!(store.get("token").unwrap(), None);
+
+        store.set("token", "t0k3n").unwrap();
+        store.set("db.password", "hunter2").unwrap();
+        store.set("token", "t0k3n-2").unwrap();
+        assert_eq!(store.get("token").unwrap().as_deref(), Some("t0k3n-2"));
+        assert_eq!(store.names().unwrap(), vec!["db.password", "token"]);
+        assert_eq!(
+            fs::read_to_string(store.dir().join(".gitignore")).unwrap(),
+            "*\n"
+        );
+
+        let redacted = store
+            .redactor()
+            .unwrap()
+            .redact(&json!({"auth": "Bearer t0k3n-2", "password": "hunter2"}));
+        assert_eq!(
+            redacted,
+            json!({"auth": "Bearer [REDACTED]", "password": "[REDACTED]"})
+        );
+
+        assert!(store.remove("token").unwrap());
+        assert!(!store.remove("token").unwrap());
+        assert_eq!(store.resolve("token"), None);
+
+        SecretWriter::write(&store, "oauth", "{}").unwrap();
+        assert_eq!(store.resolve("oauth").as_deref(), Some("{}"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_secrets_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let workspace = Workspace::new("ws", "Workspace", temp.path().to_path_buf());
+        let store = workspace.secrets();
+        store.set("token", "t").unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&workspace.secrets_dir()), 0o700);
+        assert_eq!(mode(&workspace.secrets_dir().join("token")), 0o600);
+    }
+
+    #[test]
+    fn test_secret_names_are_validated() {
+        let store = FileSecretsStore::new("unused");
+        for name in ["", ".gitignore", "../escape", "a/b", "with space"] {
+            assert!(
+                matches!(store.get(name), Err(WorkspaceError::Secret(_))),
+                "{}",
+                name
+            );
+        }
+        assert!(validate_secret_name("GITHUB_token-2.v1").is_ok());
+    }
+}
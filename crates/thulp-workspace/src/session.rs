@@ -5,8 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thulp_core::McpNotification;
 use uuid::Uuid;
 
 /// Unique session identifier.
@@ -151,6 +152,10 @@ pub struct SessionMetadata {
     /// Parent session ID (for linked sessions).
     #[serde(default)]
     pub parent_session: Option<SessionId>,
+    /// Notes attached after the fact, such as a review verdict or a ticket
+    /// number.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, Value>,
 }
 
 impl SessionMetadata {
@@ -166,6 +171,7 @@ impl SessionMetadata {
             status: SessionStatus::Active,
             tags: Vec::new(),
             parent_session: None,
+            annotations: BTreeMap::new(),
         }
     }
 
@@ -175,6 +181,12 @@ impl SessionMetadata {
         self
     }
 
+    /// Add an annotation.
+    pub fn with_annotation(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.annotations.insert(key.into(), value);
+        self
+    }
+
     /// Set parent session.
     pub fn with_parent(mut self, parent: SessionId) -> Self {
         self.parent_session = Some(parent);
@@ -224,6 +236,46 @@ pub enum EntryType {
         /// Event type/name.
         event: String,
     },
+
+    /// Log message sent by a server.
+    ServerLog {
+        /// Name of the server.
+        server: String,
+        /// Log level (debug, info, warning, error, ...).
+        level: String,
+    },
+
+    /// Progress update sent by a server.
+    Progress {
+        /// Name of the server.
+        server: String,
+        /// Progress so far.
+        progress: f64,
+        /// Total amount of work, if known.
+        total: Option<f64>,
+    },
+
+    /// Resource change notification from a server.
+    ResourceUpdated {
+        /// Name of the server.
+        server: String,
+        /// URI of the changed resource.
+        uri: String,
+    },
+
+    /// Any other server notification.
+    ServerNotification {
+        /// Name of the server.
+        server: String,
+        /// JSON-RPC method of the notification.
+        method: String,
+    },
+
+    /// Marker for entries removed by compaction.
+    Compacted {
+        /// Number of original entries it replaces.
+        removed: usize,
+    },
 }
 
 /// A single entry in a session.
@@ -287,6 +339,58 @@ impl SessionEntry {
             result,
         )
     }
+
+    /// Create an entry from a server notification.
+    pub fn notification(server: impl Into<String>, notification: &McpNotification) -> Self {
+        let server = server.into();
+        match notification {
+            McpNotification::Log {
+                level,
+                logger,
+                data,
+            } => Self::new(
+                EntryType::ServerLog {
+                    server,
+                    level: level.as_str().to_string(),
+                },
+                serde_json::json!({ "logger": logger, "data": data }),
+            ),
+            McpNotification::Progress {
+                progress_token,
+                progress,
+                total,
+                message,
+            } => Self::new(
+                EntryType::Progress {
+                    server,
+                    progress: *progress,
+                    total: *total,
+                },
+                serde_json::json!({ "progress_token": progress_token, "message": message }),
+            ),
+            McpNotification::ResourceUpdated { uri } => Self::new(
+                EntryType::ResourceUpdated {
+                    server,
+                    uri: uri.clone(),
+                },
+                Value::Null,
+            ),
+            McpNotification::Other { method, params } => Self::new(
+                EntryType::ServerNotification {
+                    server,
+                    method: method.clone(),
+                },
+                params.clone(),
+            ),
+            other => Self::new(
+                EntryType::ServerNotification {
+                    server,
+                    method: other.method().to_string(),
+                },
+                Value::Null,
+            ),
+        }
+    }
 }
 
 /// Complete session data.
@@ -353,6 +457,53 @@ impl Session {
         self.context.get(key)
     }
 
+    /// Add a tag unless the session already has it.
+    ///
+    /// Returns whether the tag was added.
+    pub fn add_tag(&mut self, tag: impl Into<String>) -> bool {
+        let tag = tag.into();
+        if self.metadata.tags.contains(&tag) {
+            return false;
+        }
+        self.metadata.tags.push(tag);
+        self.metadata.updated_at = Timestamp::now();
+        true
+    }
+
+    /// Remove a tag.
+    ///
+    /// Returns whether the session had it.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.metadata.tags.len();
+        self.metadata.tags.retain(|t| t != tag);
+        if self.metadata.tags.len() == before {
+            return false;
+        }
+        self.metadata.updated_at = Timestamp::now();
+        true
+    }
+
+    /// Set an annotation, returning the value it replaced.
+    pub fn annotate(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        let previous = self.metadata.annotations.insert(key.into(), value);
+        self.metadata.updated_at = Timestamp::now();
+        previous
+    }
+
+    /// Get an annotation.
+    pub fn get_annotation(&self, key: &str) -> Option<&Value> {
+        self.metadata.annotations.get(key)
+    }
+
+    /// Remove an annotation, returning its value.
+    pub fn remove_annotation(&mut self, key: &str) -> Option<Value> {
+        let removed = self.metadata.annotations.remove(key);
+        if removed.is_some() {
+            self.metadata.updated_at = Timestamp::now();
+        }
+        removed
+    }
+
     /// Update session status.
     pub fn set_status(&mut self, status: SessionStatus) {
         self.metadata.status = status;
@@ -382,11 +533,9 @@ impl Session {
                 EntryType::UserMessage => {
                     awaiting_response = true;
                 }
-                EntryType::AssistantMessage => {
-                    if awaiting_response {
-                        turns += 1;
-                        awaiting_response = false;
-                    }
+                EntryType::AssistantMessage if awaiting_response => {
+                    turns += 1;
+                    awaiting_response = false;
                 }
                 _ => {}
             }
@@ -702,6 +851,43 @@ mod tests {
         assert_eq!(session.get_context("key3"), None);
     }
 
+    #[test]
+    fn test_session_tags_and_annotations() {
+        let mut session = Session::new(
+            "Test",
+            SessionType::Conversation {
+                purpose: "Test".to_string(),
+            },
+        );
+
+        assert!(session.add_tag("review"));
+        assert!(!session.add_tag("review"));
+        assert!(session.remove_tag("review"));
+        assert!(!session.remove_tag("review"));
+        assert!(session.metadata.tags.is_empty());
+
+        assert_eq!(session.annotate("verdict", serde_json::json!("good")), None);
+        assert_eq!(
+            session.annotate("verdict", serde_json::json!("bad")),
+            Some(serde_json::json!("good"))
+        );
+        assert_eq!(
+            session.get_annotation("verdict"),
+            Some(&serde_json::json!("bad"))
+        );
+        assert_eq!(
+            session.remove_annotation("verdict"),
+            Some(serde_json::json!("bad"))
+        );
+        assert_eq!(session.get_annotation("verdict"), None);
+
+        // Sessions saved before annotations existed still load
+        let mut json = serde_json::to_value(&session).unwrap();
+        json["metadata"].as_object_mut().unwrap().remove("annotations");
+        let loaded: Session = serde_json::from_value(json).unwrap();
+        assert!(loaded.metadata.annotations.is_empty());
+    }
+
     #[test]
     fn test_session_status() {
         let mut session = Session::new(
@@ -760,4 +946,54 @@ mod tests {
             EntryType::SkillExecution { skill_name, success } if skill_name == "my_skill" && !success
         ));
     }
+
+    #[test]
+    fn test_notification_entries() {
+        let log = SessionEntry::notification(
+            "github",
+            &McpNotification::from_jsonrpc(
+                "notifications/message",
+                serde_json::json!({"level": "warning", "data": "rate limited"}),
+            ),
+        );
+        assert!(matches!(
+            &log.entry_type,
+            EntryType::ServerLog { server, level } if server == "github" && level == "warning"
+        ));
+        assert_eq!(log.content["data"], "rate limited");
+
+        let progress = SessionEntry::notification(
+            "github",
+            &McpNotification::from_jsonrpc(
+                "notifications/progress",
+                serde_json::json!({"progressToken": 1, "progress": 3, "total": 4}),
+            ),
+        );
+        assert!(matches!(
+            progress.entry_type,
+            EntryType::Progress { progress, total: Some(total), .. } if progress == 3.0 && total == 4.0
+        ));
+
+        let updated = SessionEntry::notification(
+            "fs",
+            &McpNotification::ResourceUpdated {
+                uri: "file:///a".to_string(),
+            },
+        );
+        assert!(matches!(
+            &updated.entry_type,
+            EntryType::ResourceUpdated { uri, .. } if uri == "file:///a"
+        ));
+
+        let changed = SessionEntry::notification("fs", &McpNotification::ToolListChanged);
+        assert!(matches!(
+            &changed.entry_type,
+            EntryType::ServerNotification { method, .. } if method == "notifications/tools/list_changed"
+        ));
+
+        // Typed entries survive a serialization round trip
+        let json = serde_json::to_string(&progress).unwrap();
+        let parsed: SessionEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.entry_type, progress.entry_type);
+    }
 }
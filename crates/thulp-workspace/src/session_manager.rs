@@ -3,6 +3,10 @@
 //! This module provides the `SessionManager` for creating, loading,
 //! saving, and querying sessions with file-based persistence.
 
+use crate::anonymize::{Anonymizer, PseudonymMap};
+use crate::compaction::{CompactionPolicy, CompactionReport};
+use crate::compression::{self, Compression};
+use crate::export::{self, SessionFormat};
 use crate::filter::SessionFilter;
 use crate::session::{
     EntryType, Session, SessionEntry, SessionId, SessionMetadata, SessionStatus, SessionType,
@@ -11,16 +15,21 @@ use crate::session::{
 use crate::{Result, Workspace, WorkspaceError};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use thulp_core::Redactor;
 use tokio::fs;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+/// Extension of zstd-compressed session files
+const COMPRESSED_EXTENSION: &str = "json.zst";
+
 /// Manager for session persistence and lifecycle.
 ///
 /// The `SessionManager` provides file-based persistence for sessions,
-/// storing them in `{workspace}/.thulp/sessions/` as JSON files.
+/// storing them in `{workspace}/.thulp/sessions/` as JSON files, named
+/// `<id>.json.zst` when compressed and `<id>.json` otherwise.
 ///
 /// # Example
 ///
@@ -41,21 +50,37 @@ pub struct SessionManager {
     sessions_dir: PathBuf,
     /// In-memory cache of active sessions.
     active_sessions: Arc<RwLock<HashMap<SessionId, Session>>>,
+    /// Redactor applied to entry content before it is recorded.
+    redactor: Option<Arc<dyn Redactor>>,
+    /// Policy applied after every new entry.
+    compaction: Option<CompactionPolicy>,
+    /// Skip writes to disk.
+    read_only: bool,
+    /// Encoding of session files written to disk.
+    compression: Compression,
 }
 
 impl SessionManager {
     /// Create a new session manager for the given workspace.
     ///
-    /// This will create the sessions directory if it doesn't exist.
+    /// This will create the sessions directory if it doesn't exist, unless
+    /// the workspace is [read-only](Workspace::read_only), in which case the
+    /// manager is read-only too.
     pub async fn new(workspace: &Workspace) -> Result<Self> {
         let sessions_dir = workspace.root.join(".thulp").join("sessions");
-        fs::create_dir_all(&sessions_dir).await?;
+        if !workspace.read_only {
+            fs::create_dir_all(&sessions_dir).await?;
+        }
 
         debug!(?sessions_dir, "Initialized session manager");
 
         Ok(Self {
             sessions_dir,
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            redactor: None,
+            compaction: None,
+            read_only: workspace.read_only,
+            compression: workspace.compression,
         })
     }
 
@@ -66,14 +91,77 @@ impl SessionManager {
         Ok(Self {
             sessions_dir,
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            redactor: None,
+            compaction: None,
+            read_only: false,
+            compression: Compression::default(),
         })
     }
 
-    /// Get the path to a session file.
+    /// Mask sensitive values in entry content before it is recorded.
+    ///
+    /// Applies to every entry added through [`add_entry`](Self::add_entry),
+    /// including notifications recorded by
+    /// [`SessionNotificationSink`](crate::SessionNotificationSink).
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Compact sessions automatically whenever
+    /// [`add_entry`](Self::add_entry) pushes them over the policy's budget.
+    pub fn with_compaction(mut self, policy: CompactionPolicy) -> Self {
+        self.compaction = Some(policy);
+        self
+    }
+
+    /// Enable or disable read-only mode.
+    ///
+    /// In read-only mode sessions can be read and changed in memory, but
+    /// nothing is written to or deleted from disk; each skipped write logs a
+    /// warning.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set how session files are encoded on disk.
+    ///
+    /// Sessions are zstd-compressed by default. Files are read correctly
+    /// whichever way they were written, so changing this doesn't require
+    /// rewriting existing sessions.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Check if the manager is in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Log and report a write skipped because of read-only mode.
+    fn skip_write(&self, session_id: &SessionId, action: &str) -> bool {
+        if self.read_only {
+            warn!(session_id = %session_id, action, "Read-only mode: skipping write");
+        }
+        self.read_only
+    }
+
+    /// Get the path to an uncompressed session file.
     fn session_path(&self, id: &SessionId) -> PathBuf {
         self.sessions_dir.join(format!("{}.json", id))
     }
 
+    /// Get the paths a session may be stored at, compressed first.
+    fn session_paths(&self, id: &SessionId) -> [PathBuf; 2] {
+        [
+            self.sessions_dir
+                .join(format!("{}.{}", id, COMPRESSED_EXTENSION)),
+            self.session_path(id),
+        ]
+    }
+
     /// Create a new session.
     ///
     /// The session is automatically persisted to disk and cached in memory.
@@ -111,17 +199,7 @@ impl SessionManager {
         }
 
         // Load from disk
-        let path = self.session_path(id);
-        let content = fs::read_to_string(&path).await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                WorkspaceError::NotFound(format!("Session {} not found", id))
-            } else {
-                WorkspaceError::Io(e)
-            }
-        })?;
-
-        let session: Session = serde_json::from_str(&content)
-            .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        let session = self.read_session(id).await?;
 
         // Cache for future access
         {
@@ -148,11 +226,24 @@ impl SessionManager {
 
     /// Internal save without updating cache (to avoid deadlocks).
     async fn save_session_internal(&self, session: &Session) -> Result<()> {
-        let path = self.session_path(session.id());
-        let content = serde_json::to_string_pretty(session)
+        if self.skip_write(session.id(), "save") {
+            return Ok(());
+        }
+        let json = serde_json::to_vec_pretty(session)
             .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
-
-        fs::write(&path, content).await?;
+        let data = self.compression.encode(&json)?;
+
+        // Name the file after its encoding, and drop the copy in the other one
+        let [compressed, plain] = self.session_paths(session.id());
+        let (path, stale) = if compression::is_compressed(&data) {
+            (compressed, plain)
+        } else {
+            (plain, compressed)
+        };
+        fs::write(&path, data).await?;
+        if stale.exists() {
+            fs::remove_file(&stale).await?;
+        }
         debug!(session_id = %session.id(), "Saved session to disk");
         Ok(())
     }
@@ -166,12 +257,17 @@ impl SessionManager {
         entry_type: EntryType,
         content: Value,
     ) -> Result<SessionEntry> {
+        let content = match &self.redactor {
+            Some(redactor) => redactor.redact(&content),
+            None => content,
+        };
         let entry = SessionEntry::new(entry_type, content);
 
         {
             let mut sessions = self.active_sessions.write().await;
             if let Some(session) = sessions.get_mut(session_id) {
                 session.add_entry(entry.clone());
+                self.auto_compact(session);
                 // Save to disk
                 self.save_session_internal(session).await?;
             } else {
@@ -179,6 +275,7 @@ impl SessionManager {
                 drop(sessions); // Release lock before loading
                 let mut session = self.load_session(session_id).await?;
                 session.add_entry(entry.clone());
+                self.auto_compact(&mut session);
                 self.save_session(&session).await?;
             }
         }
@@ -187,6 +284,40 @@ impl SessionManager {
         Ok(entry)
     }
 
+    /// Apply the configured compaction policy, if any.
+    fn auto_compact(&self, session: &mut Session) {
+        if let Some(report) = self.compaction.as_ref().and_then(|p| p.compact(session)) {
+            debug!(
+                session_id = %session.id(),
+                removed = report.removed_entries,
+                "Compacted session"
+            );
+        }
+    }
+
+    /// Compact a session with the given policy.
+    ///
+    /// Returns `None` and leaves the session untouched if it is within the
+    /// policy's budget.
+    pub async fn compact_session(
+        &self,
+        session_id: &SessionId,
+        policy: &CompactionPolicy,
+    ) -> Result<Option<CompactionReport>> {
+        let mut session = self.load_session(session_id).await?;
+        let report = policy.compact(&mut session);
+        if let Some(report) = &report {
+            self.save_session(&session).await?;
+            info!(
+                session_id = %session_id,
+                removed = report.removed_entries,
+                removed_bytes = report.removed_bytes,
+                "Compacted session"
+            );
+        }
+        Ok(report)
+    }
+
     /// Complete a session.
     ///
     /// Marks the session as completed and saves it.
@@ -234,56 +365,203 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Set an annotation on a session, such as a review verdict or a ticket
+    /// number, replacing any value under the same key.
+    ///
+    /// The value passes through the redactor like entry content. Returns
+    /// the value it replaced.
+    pub async fn annotate(
+        &self,
+        session_id: &SessionId,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Result<Option<Value>> {
+        let value = match &self.redactor {
+            Some(redactor) => redactor.redact(&value),
+            None => value,
+        };
+        let mut session = self.load_session(session_id).await?;
+        let previous = session.annotate(key, value);
+        self.save_session(&session).await?;
+        Ok(previous)
+    }
+
+    /// Remove an annotation from a session, returning its value.
+    pub async fn remove_annotation(
+        &self,
+        session_id: &SessionId,
+        key: &str,
+    ) -> Result<Option<Value>> {
+        let mut session = self.load_session(session_id).await?;
+        let removed = session.remove_annotation(key);
+        if removed.is_some() {
+            self.save_session(&session).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Add a tag to a session.
+    ///
+    /// Returns whether the tag was added; a tag the session already has is
+    /// left alone.
+    pub async fn add_tag(&self, session_id: &SessionId, tag: &str) -> Result<bool> {
+        let mut session = self.load_session(session_id).await?;
+        let added = session.add_tag(tag);
+        if added {
+            self.save_session(&session).await?;
+        }
+        Ok(added)
+    }
+
+    /// Remove a tag from a session.
+    ///
+    /// Returns whether the session had the tag.
+    pub async fn remove_tag(&self, session_id: &SessionId, tag: &str) -> Result<bool> {
+        let mut session = self.load_session(session_id).await?;
+        let removed = session.remove_tag(tag);
+        if removed {
+            self.save_session(&session).await?;
+        }
+        Ok(removed)
+    }
+
     /// List all sessions, optionally filtered.
     pub async fn list_sessions(
         &self,
         filter: Option<&SessionFilter>,
     ) -> Result<Vec<SessionMetadata>> {
-        let mut metadata_list = Vec::new();
+        let mut metadata_list: Vec<SessionMetadata> = self
+            .scan_sessions(filter)
+            .await?
+            .into_iter()
+            .map(|session| session.metadata)
+            .collect();
+
+        // Sort by updated_at descending (most recent first)
+        metadata_list.sort_by_key(|m| std::cmp::Reverse(m.updated_at));
 
-        let mut entries = fs::read_dir(&self.sessions_dir).await?;
+        Ok(metadata_list)
+    }
+
+    /// Read all sessions on disk that match the filter.
+    ///
+    /// Files that can't be read or parsed are skipped with a warning.
+    async fn scan_sessions(&self, filter: Option<&SessionFilter>) -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+
+        // A read-only manager may point at a directory that was never created
+        let mut entries = match fs::read_dir(&self.sessions_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(sessions),
+            Err(e) => return Err(e.into()),
+        };
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            if !is_session_file(&path) {
                 continue;
             }
 
-            match fs::read_to_string(&path).await {
-                Ok(content) => {
-                    match serde_json::from_str::<Session>(&content) {
-                        Ok(session) => {
-                            let metadata = session.metadata.clone();
-
-                            // Apply filter if provided
-                            if let Some(filter) = filter {
-                                if filter.matches(&session) {
-                                    metadata_list.push(metadata);
-                                }
-                            } else {
-                                metadata_list.push(metadata);
-                            }
-                        }
-                        Err(e) => {
-                            warn!(path = ?path, error = %e, "Failed to parse session file");
+            match fs::read(&path).await {
+                Ok(data) => match parse_session(data) {
+                    Ok(session) => {
+                        if filter.map_or(true, |filter| filter.matches(&session)) {
+                            sessions.push(session);
                         }
                     }
-                }
+                    Err(e) => {
+                        warn!(path = ?path, error = %e, "Failed to parse session file");
+                    }
+                },
                 Err(e) => {
                     warn!(path = ?path, error = %e, "Failed to read session file");
                 }
             }
         }
 
-        // Sort by updated_at descending (most recent first)
-        metadata_list.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(sessions)
+    }
 
-        Ok(metadata_list)
+    /// Log and report a bulk operation skipped because of read-only mode.
+    fn skip_bulk(&self, action: &str) -> bool {
+        if self.read_only {
+            warn!(action, "Read-only mode: skipping bulk session operation");
+        }
+        self.read_only
+    }
+
+    /// Set the status of every session matching the filter.
+    ///
+    /// Returns the number of sessions whose status changed; sessions that
+    /// already have the status are left untouched. Does nothing and returns
+    /// 0 in read-only mode.
+    pub async fn bulk_update_status(
+        &self,
+        filter: &SessionFilter,
+        status: SessionStatus,
+    ) -> Result<usize> {
+        if self.skip_bulk("update_status") {
+            return Ok(0);
+        }
+        let mut updated = 0;
+        for mut session in self.scan_sessions(Some(filter)).await? {
+            if session.status() == status {
+                continue;
+            }
+            session.set_status(status);
+            self.save_session(&session).await?;
+            updated += 1;
+        }
+
+        info!(updated, ?status, "Bulk updated session status");
+        Ok(updated)
+    }
+
+    /// Delete every session matching the filter.
+    ///
+    /// Returns the number of sessions deleted. Does nothing and returns 0 in
+    /// read-only mode.
+    pub async fn bulk_delete(&self, filter: &SessionFilter) -> Result<usize> {
+        if self.skip_bulk("delete") {
+            return Ok(0);
+        }
+        let sessions = self.scan_sessions(Some(filter)).await?;
+        for session in &sessions {
+            self.delete_session(session.id()).await?;
+        }
+
+        info!(deleted = sessions.len(), "Bulk deleted sessions");
+        Ok(sessions.len())
+    }
+
+    /// Add a tag to every session matching the filter.
+    ///
+    /// Returns the number of sessions that were tagged; sessions that
+    /// already have the tag are left untouched. Does nothing and returns 0
+    /// in read-only mode.
+    pub async fn bulk_tag(&self, filter: &SessionFilter, tag: &str) -> Result<usize> {
+        if self.skip_bulk("tag") {
+            return Ok(0);
+        }
+        let mut tagged = 0;
+        for mut session in self.scan_sessions(Some(filter)).await? {
+            if session.add_tag(tag) {
+                self.save_session(&session).await?;
+                tagged += 1;
+            }
+        }
+
+        info!(tagged, tag, "Bulk tagged sessions");
+        Ok(tagged)
     }
 
     /// Delete a session.
     ///
-    /// Removes the session from disk and cache.
+    /// Removes the session from disk and cache. Does nothing in read-only mode.
     pub async fn delete_session(&self, session_id: &SessionId) -> Result<()> {
+        if self.skip_write(session_id, "delete") {
+            return Ok(());
+        }
+
         // Remove from cache
         {
             let mut sessions = self.active_sessions.write().await;
@@ -291,10 +569,11 @@ impl SessionManager {
         }
 
         // Remove from disk
-        let path = self.session_path(session_id);
-        if path.exists() {
-            fs::remove_file(&path).await?;
-            info!(session_id = %session_id, "Deleted session");
+        for path in self.session_paths(session_id) {
+            if path.exists() {
+                fs::remove_file(&path).await?;
+                info!(session_id = %session_id, "Deleted session");
+            }
         }
 
         Ok(())
@@ -311,23 +590,90 @@ impl SessionManager {
         }
 
         // Check disk
-        self.session_path(session_id).exists()
+        self.session_paths(session_id)
+            .iter()
+            .any(|path| path.exists())
     }
 
     /// Get a session without loading it into cache.
     ///
     /// Useful for one-off reads where caching isn't beneficial.
     pub async fn peek_session(&self, session_id: &SessionId) -> Result<Session> {
-        let path = self.session_path(session_id);
-        let content = fs::read_to_string(&path).await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                WorkspaceError::NotFound(format!("Session {} not found", session_id))
-            } else {
-                WorkspaceError::Io(e)
+        self.read_session(session_id).await
+    }
+
+    /// Read a session file, compressed or not.
+    ///
+    /// Compressed sessions written before they were named `.json.zst` are
+    /// read from `<id>.json` like plain ones.
+    async fn read_session(&self, id: &SessionId) -> Result<Session> {
+        for path in self.session_paths(id) {
+            match fs::read(&path).await {
+                Ok(data) => return parse_session(data),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(WorkspaceError::Io(e)),
             }
-        })?;
+        }
+        Err(WorkspaceError::NotFound(format!(
+            "Session {} not found",
+            id
+        )))
+    }
 
-        serde_json::from_str(&content).map_err(|e| WorkspaceError::Serialization(e.to_string()))
+    /// Export a session in the given format.
+    ///
+    /// See [`export`] for the available formats.
+    pub async fn export_session(
+        &self,
+        session_id: &SessionId,
+        format: SessionFormat,
+    ) -> Result<Vec<u8>> {
+        let session = self.load_session(session_id).await?;
+        export::export(&session, format)
+    }
+
+    /// Export an anonymized copy of a session for sharing.
+    ///
+    /// Returns the exported data and the mapping from original values to
+    /// pseudonyms. Pass in the mapping from an earlier export to keep
+    /// pseudonyms consistent between exports; the stored session is not
+    /// modified.
+    pub async fn export_anonymized(
+        &self,
+        session_id: &SessionId,
+        format: SessionFormat,
+        anonymizer: &Anonymizer,
+        mut map: PseudonymMap,
+    ) -> Result<(Vec<u8>, PseudonymMap)> {
+        let session = self.load_session(session_id).await?;
+        let anonymized = anonymizer.anonymize_session(&session, &mut map);
+        let data = export::export(&anonymized, format)?;
+        info!(session_id = %session_id, replaced = map.len(), "Exported anonymized session");
+        Ok((data, map))
+    }
+
+    /// Import a session exported by [`export_session`](Self::export_session),
+    /// possibly from another workspace.
+    ///
+    /// The format is taken from the file extension, or detected from the
+    /// contents if the extension is unknown. The session keeps its ID; importing
+    /// a session that already exists here fails.
+    pub async fn import_session(&self, path: impl AsRef<Path>) -> Result<Session> {
+        let path = path.as_ref();
+        let data = fs::read(path).await?;
+        let format = SessionFormat::from_path(path).unwrap_or_else(|| SessionFormat::detect(&data));
+        let session = export::import(&data, format)?;
+
+        if self.session_exists(session.id()).await {
+            return Err(WorkspaceError::AlreadyExists(format!(
+                "Session {}",
+                session.id()
+            )));
+        }
+        self.save_session(&session).await?;
+
+        info!(session_id = %session.id(), %format, "Imported session");
+        Ok(session)
     }
 
     /// Evict a session from the in-memory cache.
@@ -391,9 +737,13 @@ impl SessionManager {
     /// Get session count on disk.
     pub async fn session_count(&self) -> Result<usize> {
         let mut count = 0;
-        let mut entries = fs::read_dir(&self.sessions_dir).await?;
+        let mut entries = match fs::read_dir(&self.sessions_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
         while let Some(entry) = entries.next_entry().await? {
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
+            if is_session_file(&entry.path()) {
                 count += 1;
             }
         }
@@ -405,10 +755,27 @@ impl std::fmt::Debug for SessionManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SessionManager")
             .field("sessions_dir", &self.sessions_dir)
+            .field("read_only", &self.read_only)
+            .field("compression", &self.compression)
             .finish_non_exhaustive()
     }
 }
 
+/// Check whether a file in the sessions directory holds a session.
+fn is_session_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.ends_with(".json") || name.ends_with(&format!(".{}", COMPRESSED_EXTENSION))
+        })
+}
+
+/// Parse stored session data, decompressing it if needed.
+fn parse_session(data: Vec<u8>) -> Result<Session> {
+    let json = compression::decode(data)?;
+    serde_json::from_slice(&json).map_err(|e| WorkspaceError::Serialization(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -494,6 +861,45 @@ mod tests {
         assert_eq!(loaded.entries.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_add_entry_redacts_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = SessionManager::with_sessions_dir(temp_dir.path().join("sessions"))
+            .await
+            .unwrap()
+            .with_redactor(Arc::new(
+                thulp_core::PathRedactor::new().with_path("arguments.*.password"),
+            ));
+
+        let session = manager
+            .create_session(
+                "Redacted",
+                SessionType::Conversation {
+                    purpose: "Testing".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        manager
+            .add_entry(
+                session.id(),
+                EntryType::ToolCall {
+                    tool_name: "db_query".to_string(),
+                    success: true,
+                },
+                serde_json::json!({"arguments": {"db": {"user": "u", "password": "p"}}}),
+            )
+            .await
+            .unwrap();
+
+        manager.clear_cache().await;
+        let loaded = manager.load_session(session.id()).await.unwrap();
+        let content = &loaded.entries[0].content;
+        assert_eq!(content["arguments"]["db"]["password"], thulp_core::REDACTED);
+        assert_eq!(content["arguments"]["db"]["user"], "u");
+    }
+
     #[tokio::test]
     async fn test_complete_session() {
         let (manager, _temp) = create_test_manager().await;
@@ -643,6 +1049,187 @@ mod tests {
         assert!(manager.session_exists(session.id()).await);
     }
 
+    #[tokio::test]
+    async fn test_sessions_compressed_on_disk_and_plain_files_readable() {
+        let (manager, temp) = create_test_manager().await;
+        let session = manager
+            .create_session(
+                "Compressed",
+                SessionType::Conversation {
+                    purpose: "Testing".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        for i in 0..20 {
+            manager
+                .add_entry(
+                    session.id(),
+                    EntryType::UserMessage,
+                    serde_json::json!({"text": format!("message {}", i)}),
+                )
+                .await
+                .unwrap();
+        }
+
+        let [path, plain_path] = manager.session_paths(session.id());
+        assert!(path.to_string_lossy().ends_with(".json.zst"));
+        let stored = std::fs::read(&path).unwrap();
+        assert!(compression::is_compressed(&stored));
+        assert!(!plain_path.exists());
+
+        // A session written uncompressed, as before, still loads and lists
+        let plain = SessionManager::with_sessions_dir(temp.path().join("sessions"))
+            .await
+            .unwrap()
+            .with_compression(Compression::None);
+        let legacy = plain
+            .create_session(
+                "Legacy",
+                SessionType::Conversation {
+                    purpose: "Old".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        let legacy_file = std::fs::read_to_string(plain.session_path(legacy.id())).unwrap();
+        assert!(legacy_file.contains("\"Legacy\""));
+
+        manager.clear_cache().await;
+        assert_eq!(
+            manager
+                .load_session(session.id())
+                .await
+                .unwrap()
+                .entries
+                .len(),
+            20
+        );
+        assert_eq!(
+            manager.peek_session(legacy.id()).await.unwrap().name(),
+            "Legacy"
+        );
+        assert_eq!(manager.list_sessions(None).await.unwrap().len(), 2);
+        assert_eq!(manager.session_count().await.unwrap(), 2);
+
+        // Compressed sessions saved as `.json` by older versions load too,
+        // and move to `.json.zst` when saved again
+        std::fs::rename(&path, &plain_path).unwrap();
+        manager.clear_cache().await;
+        let session = manager.load_session(session.id()).await.unwrap();
+        assert_eq!(session.entries.len(), 20);
+        manager.save_session(&session).await.unwrap();
+        assert!(path.exists());
+        assert!(!plain_path.exists());
+
+        manager.delete_session(session.id()).await.unwrap();
+        assert!(!manager.session_exists(session.id()).await);
+        assert_eq!(manager.session_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operations() {
+        let (manager, _temp) = create_test_manager().await;
+        for name in ["scrape-1", "scrape-2", "chat"] {
+            manager
+                .create_session(
+                    name,
+                    SessionType::Conversation {
+                        purpose: "Test".to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        let scrapes = SessionFilter::NameContains("scrape".to_string());
+
+        assert_eq!(manager.bulk_tag(&scrapes, "crawler").await.unwrap(), 2);
+        assert_eq!(manager.bulk_tag(&scrapes, "crawler").await.unwrap(), 0);
+        assert_eq!(manager.find_by_tag("crawler").await.unwrap().len(), 2);
+
+        let tagged = SessionFilter::HasTag("crawler".to_string());
+        let completed = SessionStatus::Completed;
+        assert_eq!(
+            manager
+                .bulk_update_status(&tagged, completed)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            manager
+                .bulk_update_status(&tagged, completed)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(manager.find_by_status(completed).await.unwrap().len(), 2);
+
+        let chat = manager.find_by_status(SessionStatus::Active).await.unwrap();
+        assert_eq!(chat.len(), 1);
+
+        let read_only = SessionManager::with_sessions_dir(manager.sessions_dir.clone())
+            .await
+            .unwrap()
+            .with_read_only(true);
+        assert_eq!(read_only.bulk_delete(&tagged).await.unwrap(), 0);
+        assert_eq!(manager.session_count().await.unwrap(), 3);
+
+        assert_eq!(manager.bulk_delete(&tagged).await.unwrap(), 2);
+        assert_eq!(manager.session_count().await.unwrap(), 1);
+        assert_eq!(manager.cached_session_count().await, 1);
+        assert_eq!(manager.list_sessions(None).await.unwrap()[0].id, chat[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_annotations_and_tags() {
+        let (manager, _temp) = create_test_manager().await;
+        let session = manager
+            .create_session(
+                "Deploy",
+                SessionType::Conversation {
+                    purpose: "Test".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        let id = session.id();
+
+        assert_eq!(
+            manager
+                .annotate(id, "ticket", serde_json::json!("OPS-12"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(manager.add_tag(id, "reviewed").await.unwrap());
+        assert!(!manager.add_tag(id, "reviewed").await.unwrap());
+
+        // Changes are on disk, not just in the cache
+        manager.clear_cache().await;
+        let loaded = manager.load_session(id).await.unwrap();
+        assert_eq!(
+            loaded.get_annotation("ticket"),
+            Some(&serde_json::json!("OPS-12"))
+        );
+        assert_eq!(loaded.metadata.tags, vec!["reviewed"]);
+
+        let filter =
+            SessionFilter::AnnotationEquals("ticket".to_string(), serde_json::json!("OPS-12"));
+        assert_eq!(manager.list_sessions(Some(&filter)).await.unwrap().len(), 1);
+
+        assert_eq!(
+            manager.remove_annotation(id, "ticket").await.unwrap(),
+            Some(serde_json::json!("OPS-12"))
+        );
+        assert!(manager.remove_tag(id, "reviewed").await.unwrap());
+        assert!(!manager.remove_tag(id, "reviewed").await.unwrap());
+        manager.clear_cache().await;
+        let loaded = manager.load_session(id).await.unwrap();
+        assert!(loaded.metadata.annotations.is_empty());
+        assert!(loaded.metadata.tags.is_empty());
+    }
+
     #[tokio::test]
     async fn test_session_count() {
         let (manager, _temp) = create_test_manager().await;
@@ -671,4 +1258,217 @@ mod tests {
 
         assert_eq!(manager.session_count().await.unwrap(), 2);
     }
+
+    #[tokio::test]
+    async fn test_export_import_between_workspaces() {
+        let (source, _temp) = create_test_manager().await;
+        let (target, _temp2) = create_test_manager().await;
+
+        let session = source
+            .create_session(
+                "Shared",
+                SessionType::Conversation {
+                    purpose: "Testing".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        source
+            .add_entry(
+                session.id(),
+                EntryType::UserMessage,
+                serde_json::json!({"text": "Hello"}),
+            )
+            .await
+            .unwrap();
+
+        let export_dir = TempDir::new().unwrap();
+        for format in [SessionFormat::Jsonl, SessionFormat::Archive] {
+            let data = source.export_session(session.id(), format).await.unwrap();
+            // No extension, so the format is detected from the contents
+            let path = export_dir.path().join(format.to_string());
+            std::fs::write(&path, data).unwrap();
+
+            let imported = target.import_session(&path).await.unwrap();
+            assert_eq!(imported.id(), session.id());
+            assert_eq!(imported.entries.len(), 1);
+
+            target.clear_cache().await;
+            let loaded = target.load_session(session.id()).await.unwrap();
+            assert_eq!(loaded.entries[0].content["text"], "Hello");
+
+            // Importing again would overwrite the session
+            assert!(matches!(
+                target.import_session(&path).await,
+                Err(WorkspaceError::AlreadyExists(_))
+            ));
+            target.delete_session(session.id()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compaction() {
+        let (manager, _temp) = create_test_manager().await;
+        let manager = manager.with_compaction(
+            CompactionPolicy::new()
+                .with_max_entries(10)
+                .with_keep_recent(5),
+        );
+
+        let session = manager
+            .create_session(
+                "Long",
+                SessionType::Conversation {
+                    purpose: "Testing".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        for i in 0..25 {
+            manager
+                .add_entry(
+                    session.id(),
+                    EntryType::UserMessage,
+                    serde_json::json!({"text": format!("message {}", i)}),
+                )
+                .await
+                .unwrap();
+        }
+
+        manager.clear_cache().await;
+        let loaded = manager.load_session(session.id()).await.unwrap();
+        assert!(loaded.entries.len() <= 10);
+        assert!(matches!(
+            loaded.entries[0].entry_type,
+            EntryType::Compacted { .. }
+        ));
+        assert_eq!(loaded.entries.last().unwrap().content["text"], "message 24");
+
+        // A tighter policy on demand
+        let tight = CompactionPolicy::new()
+            .with_max_entries(6)
+            .with_keep_recent(5);
+        let report = manager
+            .compact_session(session.id(), &tight)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.remaining_entries, 6);
+        assert!(manager
+            .compact_session(session.id(), &tight)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_anonymized() {
+        let (manager, _temp) = create_test_manager().await;
+        let session = manager
+            .create_session(
+                "Support",
+                SessionType::Conversation {
+                    purpose: "Testing".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        manager
+            .add_entry(
+                session.id(),
+                EntryType::UserMessage,
+                serde_json::json!({"text": "I am dave@corp.io, see /home/dave/app.log"}),
+            )
+            .await
+            .unwrap();
+
+        let (data, map) = manager
+            .export_anonymized(
+                session.id(),
+                SessionFormat::Jsonl,
+                &Anonymizer::new(),
+                PseudonymMap::new(),
+            )
+            .await
+            .unwrap();
+        let text = String::from_utf8(data).unwrap();
+        assert!(text.contains("I am user1@example.com, see /anon/path1.log"));
+        assert!(!text.contains("dave"));
+        assert_eq!(map.len(), 2);
+
+        // The stored session keeps the original text
+        let stored = manager.load_session(session.id()).await.unwrap();
+        assert!(stored.entries[0].content["text"]
+            .as_str()
+            .unwrap()
+            .contains("dave@corp.io"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_skips_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::new("ws", "Workspace", temp_dir.path().to_path_buf());
+        let writer = SessionManager::new(&workspace).await.unwrap();
+        let existing = writer
+            .create_session(
+                "Existing",
+                SessionType::Conversation {
+                    purpose: "Audit".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let manager = SessionManager::new(&workspace.with_read_only(true))
+            .await
+            .unwrap();
+        assert!(manager.is_read_only());
+
+        // New sessions and entries live only in memory
+        let session = manager
+            .create_session(
+                "Demo",
+                SessionType::Conversation {
+                    purpose: "Demo".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        manager
+            .add_entry(
+                session.id(),
+                EntryType::UserMessage,
+                serde_json::json!({"text": "hi"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            manager
+                .load_session(session.id())
+                .await
+                .unwrap()
+                .entries
+                .len(),
+            1
+        );
+        assert!(manager.peek_session(session.id()).await.is_err());
+
+        // Existing sessions can be read but not changed or deleted on disk
+        manager.complete_session(existing.id()).await.unwrap();
+        manager.delete_session(existing.id()).await.unwrap();
+        let on_disk = writer.peek_session(existing.id()).await.unwrap();
+        assert_eq!(on_disk.status(), SessionStatus::Active);
+        assert_eq!(manager.session_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_workspace_creates_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace =
+            Workspace::new("ws", "Workspace", temp_dir.path().to_path_buf()).with_read_only(true);
+        let manager = SessionManager::new(&workspace).await.unwrap();
+
+        assert!(manager.list_sessions(None).await.unwrap().is_empty());
+        assert!(!temp_dir.path().join(".thulp").exists());
+    }
 }
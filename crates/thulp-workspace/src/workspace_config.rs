@@ -0,0 +1,558 @@
+//! Typed model of `.thulp/config.yaml`.
+//!
+//! ```yaml
+//! name: demo
+//! version: "1.0"
+//! env:
+//!   MCP_HOST: localhost:8080
+//! servers:
+//!   github:
+//!     type: http
+//!     url: http://${MCP_HOST}/mcp
+//!   fs:
+//!     type: stdio
+//!     command: mcp-fs
+//!     args: ["."]
+//!     tool_names:
+//!       prefix: fs_
+//! settings:
+//!   default_timeout: 30
+//!   max_retries: 3
+//! skill_paths:
+//!   - ../shared/skills
+//! ```
+//!
+//! [`WorkspaceConfig::load`] expands `${ENV}` and `${secret:name}`
+//! references as described in [`config`](crate::config), with the `env`
+//! section supplying variables the process environment doesn't set, and
+//! rejects configs that fail [`validate`](WorkspaceConfig::validate).
+//! Programs that edit the file use [`load_raw`](WorkspaceConfig::load_raw)
+//! and [`save`](WorkspaceConfig::save) instead, so references are written
+//! back as they were rather than with their values.
+//!
+//! Keys the model doesn't know are kept, and saved again, in `extra`;
+//! [`warnings`](WorkspaceConfig::warnings) lists them, as they are often
+//! typos. The `settings` section is kept as written for `ExecutionSettings`
+//! in thulp-skills, which reads it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_json::json;
+//! use thulp_workspace::{ServerConfig, WorkspaceConfig};
+//!
+//! let mut config = WorkspaceConfig::from_yaml("name: demo\nservers: {}\nlog_levle: debug\n").unwrap();
+//! config.add_server("fs", ServerConfig::stdio("mcp-fs", vec![".".to_string()])).unwrap();
+//! config.set("settings.max_retries", json!(2)).unwrap();
+//!
+//! assert_eq!(config.get("servers.fs.command"), Some(json!("mcp-fs")));
+//! assert_eq!(config.warnings(), vec!["unknown key 'log_levle'"]);
+//! assert!(config.set("servers.fs.type", json!("ftp")).is_err());
+//! ```
+
+use crate::{ConfigExpander, Result, WorkspaceError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thulp_core::{ExecPolicy, ToolNameRules};
+
+/// Contents of a workspace's `.thulp/config.yaml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Workspace name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Version of the config layout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// MCP servers, by name
+    #[serde(default)]
+    pub servers: BTreeMap<String, ServerConfig>,
+
+    /// Execution defaults, read by `ExecutionSettings` in thulp-skills
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, Value>,
+
+    /// Variables for `${NAME}` references that the process environment
+    /// doesn't set
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Directories searched for skills besides the standard scopes,
+    /// relative to the workspace root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skill_paths: Vec<PathBuf>,
+
+    /// Workspace metadata, see [`Workspace::apply_config`](crate::Workspace::apply_config)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+
+    /// Workspace context, see [`Workspace::apply_config`](crate::Workspace::apply_config)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub context: BTreeMap<String, Value>,
+
+    /// Local commands the built-in exec tool may run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<ExecPolicy>,
+
+    /// Skip writes to workspace storage
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+
+    /// Package index skills are installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill_index: Option<String>,
+
+    /// Range of thulp versions the workspace works with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_version: Option<String>,
+
+    /// What to do outside `required_version`: `warn` or `block`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_policy: Option<String>,
+
+    /// Keys the model doesn't know, kept as written
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// An MCP server entry under `servers`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerConfig {
+    /// Local command reached over stdio
+    Stdio {
+        command: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "ToolNameRules::is_empty")]
+        tool_names: ToolNameRules,
+        /// Keys the model doesn't know, kept as written
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    },
+    /// Remote server reached over HTTP
+    Http {
+        url: String,
+        #[serde(default, skip_serializing_if = "ToolNameRules::is_empty")]
+        tool_names: ToolNameRules,
+        /// Keys the model doesn't know, kept as written
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    },
+}
+
+impl ServerConfig {
+    /// A server started as `command` with `args`.
+    pub fn stdio(command: impl Into<String>, args: Vec<String>) -> Self {
+        Self::Stdio {
+            command: command.into(),
+            args,
+            tool_names: ToolNameRules::default(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// A server reached at `url`.
+    pub fn http(url: impl Into<String>) -> Self {
+        Self::Http {
+            url: url.into(),
+            tool_names: ToolNameRules::default(),
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Rename the server's tools under `rules`.
+    pub fn with_tool_names(mut self, rules: ToolNameRules) -> Self {
+        match &mut self {
+            Self::Stdio { tool_names, .. } | Self::Http { tool_names, .. } => *tool_names = rules,
+        }
+        self
+    }
+
+    /// How the server's tools are renamed
+    pub fn tool_names(&self) -> &ToolNameRules {
+        match self {
+            Self::Stdio { tool_names, .. } | Self::Http { tool_names, .. } => tool_names,
+        }
+    }
+
+    /// The `type` of the entry: `stdio` or `http`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Stdio { .. } => "stdio",
+            Self::Http { .. } => "http",
+        }
+    }
+
+    fn extra(&self) -> &BTreeMap<String, Value> {
+        match self {
+            Self::Stdio { extra, .. } | Self::Http { extra, .. } => extra,
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// Parse config YAML as written, without expanding references or
+    /// validating.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let value: Option<Value> =
+            serde_yaml::from_str(yaml).map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        Self::from_value(value.unwrap_or(Value::Null))
+    }
+
+    /// Parse config YAML, expanding references with `expander` and the
+    /// `env` section, and validate it.
+    pub fn from_yaml_expanded(yaml: &str, expander: &ConfigExpander) -> Result<Self> {
+        let value: Option<Value> =
+            serde_yaml::from_str(yaml).map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        let value = value.unwrap_or(Value::Null);
+
+        let mut expander = expander.clone();
+        if let Some(env) = value.get("env").and_then(Value::as_object) {
+            for (name, default) in env {
+                if let Some(default) = default.as_str() {
+                    expander = expander.with_env_default(name, default);
+                }
+            }
+        }
+
+        let config = Self::from_value(expander.expand_value(value)?)?;
+        config.check()?;
+        Ok(config)
+    }
+
+    /// Load the config at `path`, expanding references and validating it.
+    pub fn load(path: impl AsRef<Path>, expander: &ConfigExpander) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = read(path)?;
+        Self::from_yaml_expanded(&yaml, expander).map_err(|e| in_file(path, e))
+    }
+
+    /// Load the config at `path` as written, to edit and
+    /// [`save`](Self::save) it.
+    pub fn load_raw(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = read(path)?;
+        Self::from_yaml(&yaml).map_err(|e| in_file(path, e))
+    }
+
+    /// Write the config to `path` as YAML.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let yaml = serde_yaml::to_string(self)
+            .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        std::fs::write(path, yaml)?;
+        Ok(())
+    }
+
+    /// Problems that make the config unusable, such as servers without a
+    /// command or an unknown `version_policy`. Values that still contain
+    /// `${...}` references aren't checked.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (name, server) in &self.servers {
+            if name.is_empty() || name.contains(['.', ' ']) {
+                problems.push(format!(
+                    "server name '{}' must be non-empty, without dots or spaces",
+                    name
+                ));
+            }
+            match server {
+                ServerConfig::Stdio { command, .. } if command.trim().is_empty() => {
+                    problems.push(format!("server '{}': command is empty", name));
+                }
+                ServerConfig::Http { url, .. }
+                    if !url.contains("${")
+                        && !url.starts_with("http://")
+                        && !url.starts_with("https://") =>
+                {
+                    problems.push(format!(
+                        "server '{}': url '{}' must start with http:// or https://",
+                        name, url
+                    ));
+                }
+                _ => {}
+            }
+        }
+        for name in self.env.keys() {
+            let valid = name
+                .chars()
+                .enumerate()
+                .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+            if name.is_empty() || !valid {
+                problems.push(format!("env: '{}' is not a valid variable name", name));
+            }
+        }
+        if self.skill_paths.iter().any(|p| p.as_os_str().is_empty()) {
+            problems.push("skill_paths: paths must not be empty".to_string());
+        }
+        if let Some(policy) = &self.version_policy {
+            if !matches!(policy.as_str(), "warn" | "block") {
+                problems.push(format!(
+                    "version_policy '{}' must be 'warn' or 'block'",
+                    policy
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Fail with every problem [`validate`](Self::validate) finds.
+    pub fn check(&self) -> Result<()> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(WorkspaceError::InvalidConfig(problems))
+        }
+    }
+
+    /// Keys the model doesn't know, at the top level and in servers.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .extra
+            .keys()
+            .map(|key| format!("unknown key '{}'", key))
+            .collect();
+        for (name, server) in &self.servers {
+            warnings.extend(
+                server
+                    .extra()
+                    .keys()
+                    .map(|key| format!("server '{}': unknown key '{}'", name, key)),
+            );
+        }
+        warnings
+    }
+
+    /// Get the value at a dotted path such as `servers.github.url`.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let mut current = serde_json::to_value(self).ok()?;
+        for part in key.split('.') {
+            current = match current {
+                Value::Object(mut map) => map.remove(part)?,
+                Value::Array(mut items) => {
+                    let index: usize = part.parse().ok()?;
+                    (index < items.len()).then(|| items.swap_remove(index))?
+                }
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Set the value at a dotted path such as `settings.max_retries`,
+    /// creating the mappings on the way. The config is left unchanged if
+    /// the result doesn't parse or validate.
+    pub fn set(&mut self, key: &str, value: Value) -> Result<()> {
+        let mut document = serde_json::to_value(&*self)
+            .map_err(|e| WorkspaceError::Serialization(e.to_string()))?;
+        let mut current = &mut document;
+        let parts: Vec<&str> = key.split('.').collect();
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() {
+                return Err(WorkspaceError::InvalidConfig(vec![format!(
+                    "'{}' is not a valid key",
+                    key
+                )]));
+            }
+            let Value::Object(map) = current else {
+                return Err(WorkspaceError::InvalidConfig(vec![format!(
+                    "'{}' is not a mapping",
+                    parts[..i].join(".")
+                )]));
+            };
+            current = map.entry(part.to_string()).or_insert(Value::Null);
+            if current.is_null() && i + 1 < parts.len() {
+                *current = Value::Object(serde_json::Map::new());
+            }
+        }
+        *current = value;
+
+        let updated = Self::from_value(document)
+            .map_err(|e| WorkspaceError::InvalidConfig(vec![format!("{}: {}", key, e)]))?;
+        updated.check()?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// Add or replace the server called `name`.
+    pub fn add_server(&mut self, name: impl Into<String>, server: ServerConfig) -> Result<()> {
+        let mut updated = self.clone();
+        updated.servers.insert(name.into(), server);
+        updated.check()?;
+        *self = updated;
+        Ok(())
+    }
+
+    fn from_value(value: Value) -> Result<Self> {
+        if value.is_null() {
+            return Ok(Self::default());
+        }
+        serde_json::from_value(value).map_err(|e| WorkspaceError::Serialization(e.to_string()))
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Err(WorkspaceError::NotFound(path.display().to_string()));
+    }
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// Name the file in parse and validation errors
+fn in_file(path: &Path, error: WorkspaceError) -> WorkspaceError {
+    match error {
+        WorkspaceError::Serialization(message) => {
+            WorkspaceError::Serialization(format!("{}: {}", path.display(), message))
+        }
+        WorkspaceError::InvalidConfig(problems) => WorkspaceError::InvalidConfig(
+            problems
+                .into_iter()
+                .map(|problem| format!("{}: {}", path.display(), problem))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONFIG: &str = r#"
+name: demo
+version: "1.0"
+env:
+  MCP_HOST: localhost:8080
+servers:
+  github:
+    type: http
+    url: http://${MCP_HOST}/mcp
+    headers:
+      Authorization: Bearer ${secret:token}
+  fs:
+    type: stdio
+    command: mcp-fs
+    args: ["."]
+    tool_names:
+      prefix: fs_
+settings:
+  default_timeout: 30
+skill_paths:
+  - ../shared/skills
+log_levle: debug
+"#;
+
+    fn expander() -> ConfigExpander {
+        let secrets = std::collections::HashMap::from([("token".to_string(), "t0k".to_string())]);
+        ConfigExpander::isolated().with_secrets(secrets)
+    }
+
+    #[test]
+    fn test_load_expands_and_warns() {
+        let config = WorkspaceConfig::from_yaml_expanded(CONFIG, &expander()).unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("demo"));
+        assert_eq!(config.servers["fs"].tool_names().apply("read"), "fs_read");
+        assert_eq!(
+            config.get("servers.github.url"),
+            Some(json!("http://localhost:8080/mcp"))
+        );
+        assert_eq!(
+            config.get("servers.github.headers.Authorization"),
+            Some(json!("Bearer t0k"))
+        );
+        assert_eq!(config.get("settings.default_timeout"), Some(json!(30)));
+        assert_eq!(config.skill_paths, vec![PathBuf::from("../shared/skills")]);
+        assert_eq!(
+            config.warnings(),
+            vec![
+                "unknown key 'log_levle'",
+                "server 'github': unknown key 'headers'"
+            ]
+        );
+
+        // The process environment and expander win over `env`
+        let overridden = expander().with_env_var("MCP_HOST", "mcp.example.com");
+        let config = WorkspaceConfig::from_yaml_expanded(CONFIG, &overridden).unwrap();
+        assert_eq!(
+            config.get("servers.github.url"),
+            Some(json!("http://mcp.example.com/mcp"))
+        );
+
+        let err =
+            WorkspaceConfig::from_yaml_expanded(CONFIG, &ConfigExpander::isolated()).unwrap_err();
+        assert!(
+            matches!(err, WorkspaceError::UnresolvedReferences(refs) if refs == ["${secret:token}"])
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_references_and_unknown_keys() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config.yaml");
+        std::fs::write(&path, CONFIG).unwrap();
+
+        let mut config = WorkspaceConfig::load_raw(&path).unwrap();
+        config
+            .add_server("local", ServerConfig::http("http://localhost:9000"))
+            .unwrap();
+        config.save(&path).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("url: http://${MCP_HOST}/mcp"), "{}", saved);
+        assert!(saved.contains("Bearer ${secret:token}"), "{}", saved);
+        assert!(saved.contains("log_levle: debug"), "{}", saved);
+
+        let reloaded = WorkspaceConfig::load(&path, &expander()).unwrap();
+        assert_eq!(reloaded.servers.len(), 3);
+        assert_eq!(reloaded.servers["local"].kind(), "http");
+
+        assert!(matches!(
+            WorkspaceConfig::load(temp.path().join("missing.yaml"), &expander()),
+            Err(WorkspaceError::NotFound(_))
+        ));
+        assert!(WorkspaceConfig::from_yaml("").unwrap().servers.is_empty());
+    }
+
+    #[test]
+    fn test_set_and_validate() {
+        let mut config = WorkspaceConfig::default();
+        config.set("settings.max_retries", json!(2)).unwrap();
+        config.set("read_only", json!(true)).unwrap();
+        config.set("context.project_id", json!("p-42")).unwrap();
+        assert_eq!(config.settings["max_retries"], json!(2));
+        assert!(config.read_only);
+        assert_eq!(config.get("context.project_id"), Some(json!("p-42")));
+        assert_eq!(config.get("context.missing"), None);
+
+        // Rejected changes leave the config as it was
+        let before = config.clone();
+        assert!(config.set("read_only", json!("maybe")).is_err());
+        assert!(config.set("read_only.nested", json!(1)).is_err());
+        assert!(config.set("version_policy", json!("sometimes")).is_err());
+        assert!(config
+            .add_server("a.b", ServerConfig::stdio("x", vec![]))
+            .is_err());
+        assert_eq!(config, before);
+
+        let config = WorkspaceConfig::from_yaml(
+            "servers:\n  web:\n    type: http\n    url: localhost\n  cli:\n    type: stdio\n    command: ''\nenv:\n  1X: a\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.validate(),
+            vec![
+                "server 'cli': command is empty",
+                "server 'web': url 'localhost' must start with http:// or https://",
+                "env: '1X' is not a valid variable name",
+            ]
+        );
+        assert!(WorkspaceConfig::from_yaml("servers:\n  x:\n    type: ftp\n").is_err());
+    }
+}
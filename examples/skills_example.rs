@@ -22,9 +22,7 @@ fn main() {
             "query": "{{query}}",
             "limit": "{{max_results}}"
         }),
-        continue_on_error: false,
-        timeout_secs: None,
-        max_retries: None,
+        ..Default::default()
     })
     .with_step(SkillStep {
         name: "summarize".to_string(),
@@ -33,9 +31,7 @@ fn main() {
             "text": "{{search.results}}",
             "format": "bullet_points"
         }),
-        continue_on_error: false,
-        timeout_secs: None,
-        max_retries: None,
+        ..Default::default()
     })
     .with_step(SkillStep {
         name: "notify".to_string(),
@@ -45,8 +41,7 @@ fn main() {
             "channel": "results"
         }),
         continue_on_error: true, // Continue even if notification fails
-        timeout_secs: None,
-        max_retries: None,
+        ..Default::default()
     });
 
     println!("Skill: {} - {}", skill.name, skill.description);
@@ -72,17 +67,13 @@ fn main() {
             name: "fetch".to_string(),
             tool: "http_get".to_string(),
             arguments: json!({"url": "{{url}}"}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         })
         .with_step(SkillStep {
             name: "parse".to_string(),
             tool: "html_parser".to_string(),
             arguments: json!({"html": "{{fetch.body}}"}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
     let analyze_skill = Skill::new("analyze_code", "Analyze source code quality")
@@ -91,17 +82,13 @@ fn main() {
             name: "read".to_string(),
             tool: "read_file".to_string(),
             arguments: json!({"path": "{{file_path}}"}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         })
         .with_step(SkillStep {
             name: "analyze".to_string(),
             tool: "code_analyzer".to_string(),
             arguments: json!({"code": "{{read.content}}"}),
-            continue_on_error: false,
-            timeout_secs: None,
-            max_retries: None,
+            ..Default::default()
         });
 
     registry.register(skill);